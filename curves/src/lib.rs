@@ -1 +1,3 @@
+#[cfg(feature = "bn254")]
+pub mod grumpkin;
 pub mod pasta;
@@ -0,0 +1,27 @@
+#![cfg(feature = "bn254")]
+
+use ark_algebra_test_templates::*;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use mina_curves::grumpkin::{Grumpkin, ProjectiveGrumpkin};
+
+test_group!(g; ProjectiveGrumpkin; sw);
+
+#[test]
+fn test_grumpkin_bn254_cycle() {
+    // the base field of Grumpkin is the scalar field of BN254, and the other
+    // way around
+    assert_eq!(
+        <ark_bn254::Fr as PrimeField>::MODULUS,
+        <<Grumpkin as AffineRepr>::BaseField as PrimeField>::MODULUS
+    );
+    assert_eq!(
+        <ark_bn254::Fq as PrimeField>::MODULUS,
+        <<Grumpkin as AffineRepr>::ScalarField as PrimeField>::MODULUS
+    );
+
+    let g = Grumpkin::generator();
+    assert!(g.is_on_curve());
+    assert!(g.is_in_correct_subgroup_assuming_on_curve());
+    assert!(g.mul_bigint(ark_bn254::Fq::MODULUS).into_affine().is_zero());
+}
@@ -0,0 +1,72 @@
+#ifndef KIMCHI_H
+#define KIMCHI_H
+
+/* Generated with cbindgen from kimchi-ffi/src/lib.rs, do not edit. Run `make generate-c-header` instead. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The proof is valid
+#define KIMCHI_VERIFY_OK 0
+
+// The proof is invalid
+#define KIMCHI_VERIFY_INVALID_PROOF 1
+
+// A pointer is null while its length is not zero
+#define KIMCHI_VERIFY_NULL_POINTER -1
+
+// The verifier index cannot be decoded
+#define KIMCHI_VERIFY_INVALID_INDEX -2
+
+// The proof cannot be decoded
+#define KIMCHI_VERIFY_INVALID_PROOF_ENCODING -3
+
+// The public input cannot be decoded
+#define KIMCHI_VERIFY_INVALID_PUBLIC_INPUT -4
+
+// The verifier panicked. Only returned if the library is built with
+// `panic = "unwind"`; the process aborts otherwise.
+#define KIMCHI_VERIFY_PANIC -5
+
+// The size of the SRS of the verifier index is not a power of two, or is
+// larger than [`KIMCHI_MAX_SRS_SIZE`]
+#define KIMCHI_VERIFY_INVALID_SRS_SIZE -6
+
+// The largest SRS created for a verifier index, the one of Mina
+#define KIMCHI_MAX_SRS_SIZE (1 << 16)
+
+// Verifies a proof of a circuit over the scalar field of Vesta, i.e. a proof
+// whose commitments are Vesta points.
+//
+// Returns [`KIMCHI_VERIFY_OK`] if the proof is valid,
+// [`KIMCHI_VERIFY_INVALID_PROOF`] if it is not, and a negative error code if
+// one of the inputs cannot be decoded.
+//
+// # Safety
+//
+// Each pointer must be null or point to the given number of readable bytes,
+// which must not be modified during the call.
+int kimchi_verify(const uint8_t *index,
+                  size_t index_len,
+                  const uint8_t *proof,
+                  size_t proof_len,
+                  const uint8_t *public_input,
+                  size_t public_input_len);
+
+// Same as [`kimchi_verify`], for a proof of a circuit over the scalar field
+// of Pallas.
+//
+// # Safety
+//
+// See [`kimchi_verify`].
+int kimchi_verify_pallas(const uint8_t *index,
+                         size_t index_len,
+                         const uint8_t *proof,
+                         size_t proof_len,
+                         const uint8_t *public_input,
+                         size_t public_input_len);
+
+#endif /* KIMCHI_H */
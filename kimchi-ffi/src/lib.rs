@@ -0,0 +1,275 @@
+//! A C API to verify kimchi proofs over the Pasta curves, for the systems that
+//! cannot call the Rust verifier directly.
+//!
+//! The inputs of the verifier are given as bytes, in the formats of the Rust
+//! side:
+//! - the verifier index, as written by
+//!   [`VerifierIndex::serialize_to`](kimchi::verifier_index::VerifierIndex::serialize_to),
+//!   whose header carries a version and a checksum (see
+//!   [`kimchi::index_serialization`]);
+//! - the proof, encoded with MessagePack, e.g. with `rmp_serde::to_vec`;
+//! - the public input, as the concatenation of the compressed encodings of
+//!   its field elements, i.e. 32 bytes in little-endian order per element.
+//!
+//! The SRS is not part of the verifier index. It is created from the size
+//! stored in the index with [`SRS::create`], as Mina does, and kept for the
+//! next calls. As the index is not trusted, its size must be a power of two of
+//! at most [`KIMCHI_MAX_SRS_SIZE`] points.
+//!
+//! The C header `include/kimchi.h` is generated with
+//! [cbindgen](https://github.com/mozilla/cbindgen), see `make generate-c-header`.
+
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use groupmap::GroupMap;
+use kimchi::{
+    curve::KimchiCurve, plonk_sponge::FrSponge, proof::ProverProof, verifier::verify,
+    verifier_index::VerifierIndex,
+};
+use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+    FqSponge,
+};
+use once_cell::sync::Lazy;
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    SRS as _,
+};
+use std::{
+    collections::HashMap,
+    os::raw::c_int,
+    panic::catch_unwind,
+    slice,
+    sync::{Arc, Mutex},
+};
+
+/// The proof is valid
+pub const KIMCHI_VERIFY_OK: c_int = 0;
+
+/// The proof is invalid
+pub const KIMCHI_VERIFY_INVALID_PROOF: c_int = 1;
+
+/// A pointer is null while its length is not zero
+pub const KIMCHI_VERIFY_NULL_POINTER: c_int = -1;
+
+/// The verifier index cannot be decoded
+pub const KIMCHI_VERIFY_INVALID_INDEX: c_int = -2;
+
+/// The proof cannot be decoded
+pub const KIMCHI_VERIFY_INVALID_PROOF_ENCODING: c_int = -3;
+
+/// The public input cannot be decoded
+pub const KIMCHI_VERIFY_INVALID_PUBLIC_INPUT: c_int = -4;
+
+/// The verifier panicked. Only returned if the library is built with
+/// `panic = "unwind"`; the process aborts otherwise.
+pub const KIMCHI_VERIFY_PANIC: c_int = -5;
+
+/// The size of the SRS of the verifier index is not a power of two, or is
+/// larger than [`KIMCHI_MAX_SRS_SIZE`]
+pub const KIMCHI_VERIFY_INVALID_SRS_SIZE: c_int = -6;
+
+/// The largest SRS created for a verifier index, the one of Mina
+pub const KIMCHI_MAX_SRS_SIZE: usize = 1 << 16;
+
+/// A curve whose proofs can be verified through the C API
+trait FfiCurve: KimchiCurve {
+    /// Returns the SRS of `size` points, created on the first call
+    fn srs(size: usize) -> Arc<SRS<Self>>;
+
+    /// Returns the group map of the curve
+    fn group_map() -> &'static Self::Map;
+}
+
+fn cached_srs<G: CommitmentCurve>(
+    cache: &Mutex<HashMap<usize, Arc<SRS<G>>>>,
+    size: usize,
+) -> Arc<SRS<G>> {
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(size)
+        .or_insert_with(|| Arc::new(SRS::create(size)))
+        .clone()
+}
+
+impl FfiCurve for Vesta {
+    fn srs(size: usize) -> Arc<SRS<Self>> {
+        static CACHE: Lazy<Mutex<HashMap<usize, Arc<SRS<Vesta>>>>> = Lazy::new(Mutex::default);
+        cached_srs(&CACHE, size)
+    }
+
+    fn group_map() -> &'static Self::Map {
+        static GROUP_MAP: Lazy<<Vesta as CommitmentCurve>::Map> =
+            Lazy::new(<Vesta as CommitmentCurve>::Map::setup);
+        &GROUP_MAP
+    }
+}
+
+impl FfiCurve for Pallas {
+    fn srs(size: usize) -> Arc<SRS<Self>> {
+        static CACHE: Lazy<Mutex<HashMap<usize, Arc<SRS<Pallas>>>>> = Lazy::new(Mutex::default);
+        cached_srs(&CACHE, size)
+    }
+
+    fn group_map() -> &'static Self::Map {
+        static GROUP_MAP: Lazy<<Pallas as CommitmentCurve>::Map> =
+            Lazy::new(<Pallas as CommitmentCurve>::Map::setup);
+        &GROUP_MAP
+    }
+}
+
+/// Returns the slice of `len` bytes at `ptr`, or `None` if `ptr` is null and
+/// `len` is not zero.
+///
+/// # Safety
+///
+/// If `ptr` is not null, it must point to `len` readable bytes.
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if ptr.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(ptr, len))
+    }
+}
+
+fn verify_bytes<G, EFqSponge, EFrSponge>(index: &[u8], proof: &[u8], public_input: &[u8]) -> c_int
+where
+    G: FfiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let Ok(mut index) =
+        VerifierIndex::<G, OpeningProof<G>>::deserialize_from(index, Arc::new(SRS::default()))
+    else {
+        return KIMCHI_VERIFY_INVALID_INDEX;
+    };
+    // the size comes from the input, it bounds the memory used by the SRS and
+    // the number of SRS kept
+    if !index.max_poly_size.is_power_of_two() || index.max_poly_size > KIMCHI_MAX_SRS_SIZE {
+        return KIMCHI_VERIFY_INVALID_SRS_SIZE;
+    }
+    index.srs = G::srs(index.max_poly_size);
+
+    let Ok(proof) = rmp_serde::from_slice::<ProverProof<G, OpeningProof<G>>>(proof) else {
+        return KIMCHI_VERIFY_INVALID_PROOF_ENCODING;
+    };
+
+    let element_size = G::ScalarField::default().compressed_size();
+    if public_input.len() % element_size != 0 {
+        return KIMCHI_VERIFY_INVALID_PUBLIC_INPUT;
+    }
+    let Ok(public_input) = public_input
+        .chunks(element_size)
+        .map(G::ScalarField::deserialize_compressed)
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return KIMCHI_VERIFY_INVALID_PUBLIC_INPUT;
+    };
+
+    match verify::<G, EFqSponge, EFrSponge, OpeningProof<G>>(
+        G::group_map(),
+        &index,
+        &proof,
+        &public_input,
+    ) {
+        Ok(()) => KIMCHI_VERIFY_OK,
+        Err(_) => KIMCHI_VERIFY_INVALID_PROOF,
+    }
+}
+
+/// Verifies a proof of a circuit over the scalar field of Vesta, i.e. a proof
+/// whose commitments are Vesta points.
+///
+/// Returns [`KIMCHI_VERIFY_OK`] if the proof is valid,
+/// [`KIMCHI_VERIFY_INVALID_PROOF`] if it is not, and a negative error code if
+/// one of the inputs cannot be decoded.
+///
+/// # Safety
+///
+/// Each pointer must be null or point to the given number of readable bytes,
+/// which must not be modified during the call.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_verify(
+    index: *const u8,
+    index_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    public_input: *const u8,
+    public_input_len: usize,
+) -> c_int {
+    verify_ffi::<
+        Vesta,
+        DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>,
+        DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>,
+    >(
+        index,
+        index_len,
+        proof,
+        proof_len,
+        public_input,
+        public_input_len,
+    )
+}
+
+/// Same as [`kimchi_verify`], for a proof of a circuit over the scalar field
+/// of Pallas.
+///
+/// # Safety
+///
+/// See [`kimchi_verify`].
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_verify_pallas(
+    index: *const u8,
+    index_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    public_input: *const u8,
+    public_input_len: usize,
+) -> c_int {
+    verify_ffi::<
+        Pallas,
+        DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>,
+        DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>,
+    >(
+        index,
+        index_len,
+        proof,
+        proof_len,
+        public_input,
+        public_input_len,
+    )
+}
+
+/// # Safety
+///
+/// See [`kimchi_verify`].
+unsafe fn verify_ffi<G, EFqSponge, EFrSponge>(
+    index: *const u8,
+    index_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    public_input: *const u8,
+    public_input_len: usize,
+) -> c_int
+where
+    G: FfiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let (Some(index), Some(proof), Some(public_input)) = (
+        bytes(index, index_len),
+        bytes(proof, proof_len),
+        bytes(public_input, public_input_len),
+    ) else {
+        return KIMCHI_VERIFY_NULL_POINTER;
+    };
+    catch_unwind(|| verify_bytes::<G, EFqSponge, EFrSponge>(index, proof, public_input))
+        .unwrap_or(KIMCHI_VERIFY_PANIC)
+}
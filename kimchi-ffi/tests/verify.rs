@@ -0,0 +1,159 @@
+use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
+use groupmap::GroupMap;
+use kimchi::{
+    circuits::{
+        constraints::ConstraintSystem,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+};
+use kimchi_ffi::{
+    kimchi_verify, kimchi_verify_pallas, KIMCHI_MAX_SRS_SIZE, KIMCHI_VERIFY_INVALID_INDEX,
+    KIMCHI_VERIFY_INVALID_PROOF, KIMCHI_VERIFY_INVALID_PROOF_ENCODING,
+    KIMCHI_VERIFY_INVALID_PUBLIC_INPUT, KIMCHI_VERIFY_INVALID_SRS_SIZE, KIMCHI_VERIFY_NULL_POINTER,
+    KIMCHI_VERIFY_OK,
+};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    SRS as _,
+};
+use std::{array, ptr, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+fn verify(index: &[u8], proof: &[u8], public_input: &[u8]) -> i32 {
+    unsafe {
+        kimchi_verify(
+            index.as_ptr(),
+            index.len(),
+            proof.as_ptr(),
+            proof.len(),
+            public_input.as_ptr(),
+            public_input.len(),
+        )
+    }
+}
+
+#[test]
+fn test_kimchi_verify() {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    // the SRS is the one created by the C API
+    let cs = ConstraintSystem::<Fp>::create(gates)
+        .public(public.len())
+        .build()
+        .unwrap();
+    let srs = SRS::<Vesta>::create(cs.domain.d1.size());
+    let &endo_q = Vesta::other_curve_endo();
+    let index = ProverIndex::<Vesta, OpeningProof<Vesta>>::create(cs, endo_q, Arc::new(srs));
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut o1_utils::tests::make_test_rng(None),
+    )
+    .unwrap();
+
+    let mut index_bytes = vec![];
+    index
+        .verifier_index()
+        .serialize_to(&mut index_bytes)
+        .unwrap();
+    let proof_bytes = rmp_serde::to_vec(&proof).unwrap();
+    let mut public_bytes = vec![];
+    for x in &public {
+        x.serialize_compressed(&mut public_bytes).unwrap();
+    }
+
+    assert_eq!(
+        verify(&index_bytes, &proof_bytes, &public_bytes),
+        KIMCHI_VERIFY_OK
+    );
+
+    // another public input
+    let mut wrong_public_bytes = vec![];
+    for x in &public {
+        (*x + Fp::from(1u8))
+            .serialize_compressed(&mut wrong_public_bytes)
+            .unwrap();
+    }
+    assert_eq!(
+        verify(&index_bytes, &proof_bytes, &wrong_public_bytes),
+        KIMCHI_VERIFY_INVALID_PROOF
+    );
+
+    // the inputs that cannot be decoded
+    assert_eq!(
+        verify(&index_bytes[1..], &proof_bytes, &public_bytes),
+        KIMCHI_VERIFY_INVALID_INDEX
+    );
+    assert_eq!(
+        verify(&index_bytes, &proof_bytes[1..], &public_bytes),
+        KIMCHI_VERIFY_INVALID_PROOF_ENCODING
+    );
+    assert_eq!(
+        verify(&index_bytes, &proof_bytes, &public_bytes[1..]),
+        KIMCHI_VERIFY_INVALID_PUBLIC_INPUT
+    );
+    assert_eq!(
+        verify(&index_bytes, &proof_bytes, &[0xff; 32 * 5]),
+        KIMCHI_VERIFY_INVALID_PUBLIC_INPUT
+    );
+    assert_eq!(
+        unsafe {
+            kimchi_verify(
+                ptr::null(),
+                index_bytes.len(),
+                proof_bytes.as_ptr(),
+                proof_bytes.len(),
+                public_bytes.as_ptr(),
+                public_bytes.len(),
+            )
+        },
+        KIMCHI_VERIFY_NULL_POINTER
+    );
+
+    // the index asks for an SRS too large to be created
+    for size in [2 * KIMCHI_MAX_SRS_SIZE, index.max_poly_size - 1] {
+        let mut verifier_index = index.verifier_index();
+        verifier_index.max_poly_size = size;
+        let mut large_index_bytes = vec![];
+        verifier_index.serialize_to(&mut large_index_bytes).unwrap();
+        assert_eq!(
+            verify(&large_index_bytes, &proof_bytes, &public_bytes),
+            KIMCHI_VERIFY_INVALID_SRS_SIZE
+        );
+    }
+
+    // the index is not one of a Pallas circuit
+    assert_eq!(
+        unsafe {
+            kimchi_verify_pallas(
+                index_bytes.as_ptr(),
+                index_bytes.len(),
+                proof_bytes.as_ptr(),
+                proof_bytes.len(),
+                public_bytes.as_ptr(),
+                public_bytes.len(),
+            )
+        },
+        KIMCHI_VERIFY_INVALID_INDEX
+    );
+}
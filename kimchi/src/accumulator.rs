@@ -0,0 +1,124 @@
+//! Recursion-friendly verification of proofs using the IPA polynomial
+//! commitment scheme.
+//!
+//! The most expensive part of the verification of an IPA opening proof is the
+//! check that the `sg` component of the proof is the commitment to the
+//! polynomial `b(X)` defined by the challenges of the opening proof, as it
+//! requires an MSM of the size of the SRS. In a recursive setting, this check
+//! is not performed by the verifier: the pair (challenges, `sg`), called an
+//! accumulator, is passed to the next proof as one of its
+//! [previous challenges](crate::proof::ProverProof::prev_challenges), which
+//! folds it into its own opening proof.
+//!
+//! [verify_partial] performs all the verification except this check and
+//! returns the accumulator. A batch of accumulators can finally be discharged
+//! with [discharge_accumulators], using a single MSM.
+
+use crate::{
+    curve::KimchiCurve,
+    error::{AccumulatorError, VerifyError},
+    plonk_sponge::FrSponge,
+    proof::{ProverProof, RecursionChallenge},
+    verifier::{to_batch, Result},
+    verifier_index::VerifierIndex,
+};
+use ark_ff::PrimeField;
+use mina_poseidon::FqSponge;
+use poly_commitment::{
+    commitment::PolyComm,
+    ipa::{OpeningProof, SRS},
+};
+use rand::thread_rng;
+
+/// Verify a proof, except the final check of the `sg` component of its
+/// opening proof, and return the corresponding accumulator.
+///
+/// The proof is only valid once the accumulator has been discharged, either
+/// by an outer proof or with [discharge_accumulators].
+///
+/// # Errors
+///
+/// Will give error if the partial verification of the proof fails.
+pub fn verify_partial<G, EFqSponge, EFrSponge>(
+    group_map: &G::Map,
+    verifier_index: &VerifierIndex<G, OpeningProof<G>>,
+    proof: &ProverProof<G, OpeningProof<G>>,
+    public_input: &[G::ScalarField],
+) -> Result<RecursionChallenge<G>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let mut batch = vec![to_batch::<G, EFqSponge, EFrSponge, OpeningProof<G>>(
+        verifier_index,
+        proof,
+        public_input,
+    )?];
+    let mut accumulators = verifier_index
+        .srs()
+        .verify_deferred(group_map, &mut batch, &mut thread_rng())
+        .ok_or(VerifyError::OpenProof)?;
+    let (chals, sg) = accumulators.remove(0);
+    Ok(RecursionChallenge {
+        chals,
+        comm: PolyComm::new(vec![sg]),
+    })
+}
+
+/// Check a batch of accumulators returned by [verify_partial] with a single
+/// MSM.
+///
+/// # Errors
+///
+/// Will give error if one of the accumulators is incorrect. If the check of
+/// the batch fails, the accumulators are checked separately to report the
+/// first incorrect one, and [VerifyError::InvalidAccumulatorBatch] is given if
+/// none of them is.
+pub fn discharge_accumulators<G: KimchiCurve>(
+    srs: &SRS<G>,
+    accumulators: &[RecursionChallenge<G>],
+) -> Result<()> {
+    let mut pairs = Vec::with_capacity(accumulators.len());
+    for (i, acc) in accumulators.iter().enumerate() {
+        match acc.comm.chunks.as_slice() {
+            [sg] => pairs.push((acc.chals.clone(), *sg)),
+            _ => return Err(VerifyError::InvalidAccumulator(i)),
+        }
+    }
+    let mut rng = thread_rng();
+    if srs.check_accumulators(&pairs, &mut rng) {
+        return Ok(());
+    }
+    match pairs
+        .iter()
+        .position(|pair| !srs.check_accumulators(std::slice::from_ref(pair), &mut rng))
+    {
+        Some(invalid) => Err(VerifyError::InvalidAccumulator(invalid)),
+        None => Err(VerifyError::InvalidAccumulatorBatch),
+    }
+}
+
+/// Encode a list of accumulators with MessagePack.
+///
+/// # Errors
+///
+/// Will give error if the encoding fails.
+pub fn serialize_accumulators<G: KimchiCurve>(
+    accumulators: &[RecursionChallenge<G>],
+) -> std::result::Result<Vec<u8>, AccumulatorError> {
+    rmp_serde::to_vec(accumulators).map_err(|e| AccumulatorError::Encode(e.to_string()))
+}
+
+/// Decode a list of accumulators encoded with [serialize_accumulators].
+///
+/// # Errors
+///
+/// Will give error if `bytes` is not a valid encoding of a list of
+/// accumulators.
+pub fn deserialize_accumulators<G: KimchiCurve>(
+    bytes: &[u8],
+) -> std::result::Result<Vec<RecursionChallenge<G>>, AccumulatorError> {
+    rmp_serde::from_slice(bytes).map_err(|e| AccumulatorError::Decode(e.to_string()))
+}
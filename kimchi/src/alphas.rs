@@ -20,7 +20,10 @@
 //! for the various [ArgumentType]s.
 //!
 
-use crate::circuits::{argument::ArgumentType, gate::GateType};
+use crate::{
+    circuits::{argument::ArgumentType, gate::GateType},
+    error::AlphasError,
+};
 use ark_ff::Field;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -53,29 +56,69 @@ pub struct Alphas<F> {
 impl<F: Field> Alphas<F> {
     /// Registers a new [ArgumentType],
     /// associating it with a number `powers` of powers of alpha.
-    /// This function will panic if you register the same type twice.
+    /// This function will panic if you register the same type twice,
+    /// see [Self::try_register] for a version returning an error.
     pub fn register(&mut self, ty: ArgumentType, powers: u32) {
+        if let Err(e) = self.try_register(ty, powers) {
+            panic!("{e}");
+        }
+    }
+
+    /// Registers a new [ArgumentType],
+    /// associating it with a number `powers` of powers of alpha.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the powers of alpha are already instantiated, if the type
+    /// is already registered (all the gates share the same powers, so only one gate
+    /// can be registered), or if there are too many powers of alpha.
+    pub fn try_register(&mut self, ty: ArgumentType, powers: u32) -> Result<(), AlphasError> {
         if self.alphas.is_some() {
-            panic!("you cannot register new constraints once initialized with a field element");
+            return Err(AlphasError::AlreadyInstantiated);
         }
 
         // gates are a special case, as we reuse the same power of alpha
         // across all of them (they're mutually exclusive)
-        let ty = if matches!(ty, ArgumentType::Gate(_)) {
-            // the zero gate is not used, so we default to it
-            ArgumentType::Gate(GateType::Zero)
-        } else {
-            ty
-        };
-
-        if self.mapping.insert(ty, (self.next_power, powers)).is_some() {
-            panic!("cannot re-register {ty:?}");
-        }
+        let ty = normalize(ty);
 
-        self.next_power = self
+        if self.mapping.contains_key(&ty) {
+            return Err(AlphasError::AlreadyRegistered(ty));
+        }
+        let next_power = self
             .next_power
             .checked_add(powers)
-            .expect("too many powers of alphas were registered");
+            .ok_or(AlphasError::TooManyPowers)?;
+
+        self.mapping.insert(ty, (self.next_power, powers));
+        self.next_power = next_power;
+        Ok(())
+    }
+
+    /// Returns the range of exponents allocated to a given [ArgumentType],
+    /// or [None] if it was not registered.
+    /// All the gates share the same range.
+    pub fn exponents_of(&self, ty: ArgumentType) -> Option<Range<u32>> {
+        self.mapping
+            .get(&normalize(ty))
+            .map(|(start, powers)| *start..(start + powers))
+    }
+
+    /// Returns the registered arguments, with the range of exponents allocated to each
+    /// of them, in the order of the exponents.
+    /// All the gates are represented by [GateType::Zero].
+    pub fn registered(&self) -> Vec<(ArgumentType, Range<u32>)> {
+        let mut registered: Vec<_> = self
+            .mapping
+            .iter()
+            .map(|(ty, (start, powers))| (*ty, *start..(start + powers)))
+            .collect();
+        registered.sort_by_key(|(_, range)| (range.start, range.end));
+        registered
+    }
+
+    /// Returns the number of powers of alpha registered so far
+    pub fn num_powers(&self) -> u32 {
+        self.next_power
     }
 
     /// Returns a range of exponents, for a given [ArgumentType], upperbounded by `num`.
@@ -85,11 +128,7 @@ impl<F: Field> Alphas<F> {
         ty: ArgumentType,
         num: u32,
     ) -> MustConsumeIterator<Range<u32>, u32> {
-        let ty = if matches!(ty, ArgumentType::Gate(_)) {
-            ArgumentType::Gate(GateType::Zero)
-        } else {
-            ty
-        };
+        let ty = normalize(ty);
 
         let range = self
             .mapping
@@ -102,6 +141,7 @@ impl<F: Field> Alphas<F> {
                 range.1, ty
             );
         }
+        debug_assert_exact_use(ty, num, range.1);
 
         let start = range.0;
         let end = start + num;
@@ -131,11 +171,7 @@ impl<F: Field> Alphas<F> {
         ty: ArgumentType,
         num: u32,
     ) -> MustConsumeIterator<Cloned<Take<Skip<Iter<F>>>>, F> {
-        let ty = if matches!(ty, ArgumentType::Gate(_)) {
-            ArgumentType::Gate(GateType::Zero)
-        } else {
-            ty
-        };
+        let ty = normalize(ty);
 
         let range = self
             .mapping
@@ -148,6 +184,7 @@ impl<F: Field> Alphas<F> {
                 range.1, ty
             );
         }
+        debug_assert_exact_use(ty, num, range.1);
 
         match &self.alphas {
             None => panic!("you must call instantiate with an actual field element first"),
@@ -166,6 +203,28 @@ impl<F: Field> Alphas<F> {
     }
 }
 
+/// All the gates share the same powers of alpha, registered for the zero gate,
+/// as they are mutually exclusive
+fn normalize(ty: ArgumentType) -> ArgumentType {
+    if matches!(ty, ArgumentType::Gate(_)) {
+        // the zero gate is not used, so we default to it
+        ArgumentType::Gate(GateType::Zero)
+    } else {
+        ty
+    }
+}
+
+/// Checks, in debug builds, that an argument other than the gates uses all its
+/// registered powers of alpha: using fewer powers means that the number of
+/// constraints does not match the registration. The gates can use fewer powers,
+/// as they share the ones of the gate with the most constraints.
+fn debug_assert_exact_use(ty: ArgumentType, num: u32, registered: u32) {
+    debug_assert!(
+        matches!(ty, ArgumentType::Gate(_)) || num == registered,
+        "you asked for {num} powers of alpha, but registered {registered} for {ty:?}"
+    );
+}
+
 impl<T> Display for Alphas<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for arg in [
@@ -311,6 +370,61 @@ mod tests {
         assert_eq!(alphas.next(), Some(8.into()));
     }
 
+    #[test]
+    fn try_register_and_query_powers() {
+        let mut alphas = Alphas::<Fp>::default();
+        alphas
+            .try_register(ArgumentType::Gate(GateType::Poseidon), 3)
+            .unwrap();
+        alphas.try_register(ArgumentType::Permutation, 2).unwrap();
+
+        // all the gates share the same powers
+        assert_eq!(
+            alphas.try_register(ArgumentType::Gate(GateType::Xor16), 1),
+            Err(AlphasError::AlreadyRegistered(ArgumentType::Gate(
+                GateType::Zero
+            )))
+        );
+        assert_eq!(
+            alphas.try_register(ArgumentType::Permutation, 2),
+            Err(AlphasError::AlreadyRegistered(ArgumentType::Permutation))
+        );
+        assert_eq!(
+            alphas.try_register(ArgumentType::Lookup, u32::MAX),
+            Err(AlphasError::TooManyPowers)
+        );
+
+        assert_eq!(
+            alphas.exponents_of(ArgumentType::Gate(GateType::Generic)),
+            Some(0..3)
+        );
+        assert_eq!(alphas.exponents_of(ArgumentType::Permutation), Some(3..5));
+        assert_eq!(alphas.exponents_of(ArgumentType::Lookup), None);
+        assert_eq!(
+            alphas.registered(),
+            vec![
+                (ArgumentType::Gate(GateType::Zero), 0..3),
+                (ArgumentType::Permutation, 3..5)
+            ]
+        );
+        assert_eq!(alphas.num_powers(), 5);
+
+        alphas.instantiate(Fp::from(2));
+        assert_eq!(
+            alphas.try_register(ArgumentType::Lookup, 1),
+            Err(AlphasError::AlreadyInstantiated)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn asked_for_fewer_powers_than_registered() {
+        let mut alphas = Alphas::<Fp>::default();
+        alphas.register(ArgumentType::Permutation, 3);
+        let _ = alphas.get_exponents(ArgumentType::Permutation, 2).count();
+    }
+
     // useful for the spec
 
     use crate::{
@@ -0,0 +1,899 @@
+//! This module implements the [bin_prot](https://github.com/janestreet/bin_prot)
+//! encoding of the proofs, the verifier indexes and the commitments, as it is
+//! produced by Mina's OCaml code from the types of `kimchi_types.ml`.
+//!
+//! The values are encoded as follows:
+//! - an OCaml `int` with the variable-length encoding of bin_prot: one byte
+//!   below `0x80`, otherwise a prefix (`0xff` for a small negative integer,
+//!   `0xfe`, `0xfd` or `0xfc` for an integer of 16, 32 or 64 bits) followed
+//!   by the integer in little-endian order;
+//! - an array with its length, followed by its elements;
+//! - an option with the byte `0` for `None`, and the byte `1` followed by the
+//!   value for `Some`;
+//! - a record or a tuple with the concatenation of its fields, in the order of
+//!   the OCaml type;
+//! - a field element as the `Bigint256` of Mina, i.e. as a bin_prot string of
+//!   its 32 bytes in little-endian order;
+//! - a point as an `or_infinity`, i.e. the byte `0` for the point at infinity,
+//!   and the byte `1` followed by its coordinates otherwise.
+//!
+//! The commitments are encoded with an unused `shifted` field, which is always
+//! `None`. The SRS of a verifier index is not encoded: it is given when the
+//! index is decoded, as with [`VerifierIndex::deserialize_from`]. The OCaml
+//! type has no [public sections](VerifierIndex::public_sections), so only the
+//! indexes without sections can be encoded.
+//!
+//! FIXME: the encodings of the integers, the field elements and the
+//! commitments are checked against fixtures written from the bin_prot
+//! specification, but the layouts of the proofs and of the verifier indexes
+//! are only checked to round-trip. They follow `kimchi_types.ml` and are not
+//! checked yet against blobs produced by Mina's OCaml code.
+
+use crate::{
+    circuits::{
+        gate::GateType,
+        lookup::{
+            index::LookupSelectors,
+            lookups::{LookupFeatures, LookupInfo, LookupPatterns},
+        },
+        wires::{COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+    error::BinProtError,
+    linearization::expr_linearization,
+    proof::{
+        LookupCommitments, PointEvaluations, ProofEvaluations, ProverCommitments, ProverProof,
+        RecursionChallenge,
+    },
+    verifier_index::{LookupVerifierIndex, VerifierIndex},
+};
+use ark_ff::{PrimeField, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain as D};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use once_cell::sync::OnceCell;
+use poly_commitment::{
+    commitment::{CommitmentCurve, PolyComm},
+    ipa::OpeningProof,
+    OpenProof,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+const CODE_NEG_INT8: u8 = 0xff;
+const CODE_INT16: u8 = 0xfe;
+const CODE_INT32: u8 = 0xfd;
+const CODE_INT64: u8 = 0xfc;
+
+/// The optional gates of a verifier index, in the order of their selector
+/// commitments in the OCaml encoding
+const BIN_PROT_OPTIONAL_GATES: [GateType; 6] = [
+    GateType::Xor16,
+    GateType::RangeCheck0,
+    GateType::RangeCheck1,
+    GateType::ForeignFieldAdd,
+    GateType::ForeignFieldMul,
+    GateType::Rot64,
+];
+
+/// A buffer to which values are written in the bin_prot format
+#[derive(Debug, Default)]
+pub struct BinProtWriter {
+    out: Vec<u8>,
+}
+
+impl BinProtWriter {
+    /// Creates an empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the bytes written
+    pub fn finish(self) -> Vec<u8> {
+        self.out
+    }
+
+    /// Writes a non-negative integer, e.g. the length of an array
+    pub fn nat0(&mut self, n: u64) {
+        if n < 0x80 {
+            self.out.push(n as u8);
+        } else if n < 0x1_0000 {
+            self.out.push(CODE_INT16);
+            self.out.extend((n as u16).to_le_bytes());
+        } else if n < 0x1_0000_0000 {
+            self.out.push(CODE_INT32);
+            self.out.extend((n as u32).to_le_bytes());
+        } else {
+            self.out.push(CODE_INT64);
+            self.out.extend(n.to_le_bytes());
+        }
+    }
+
+    /// Writes an OCaml `int`
+    pub fn int(&mut self, n: i64) {
+        if (0..0x80).contains(&n) {
+            self.out.push(n as u8);
+        } else if (-0x80..0).contains(&n) {
+            self.out.push(CODE_NEG_INT8);
+            self.out.push(n as i8 as u8);
+        } else if (-0x8000..0x8000).contains(&n) {
+            self.out.push(CODE_INT16);
+            self.out.extend((n as i16).to_le_bytes());
+        } else if (-0x8000_0000..0x8000_0000).contains(&n) {
+            self.out.push(CODE_INT32);
+            self.out.extend((n as i32).to_le_bytes());
+        } else {
+            self.out.push(CODE_INT64);
+            self.out.extend(n.to_le_bytes());
+        }
+    }
+
+    /// Writes a `usize` as an OCaml `int`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `n` does not fit in an `i64`.
+    pub fn usize(&mut self, n: usize) {
+        self.int(i64::try_from(n).expect("the integer does not fit in an OCaml int"));
+    }
+
+    /// Writes a boolean
+    pub fn bool(&mut self, b: bool) {
+        self.out.push(u8::from(b));
+    }
+
+    /// Writes an option, whose value is written with `f`
+    pub fn option<T>(&mut self, x: &Option<T>, mut f: impl FnMut(&mut Self, &T)) {
+        match x {
+            None => self.out.push(0),
+            Some(x) => {
+                self.out.push(1);
+                f(self, x);
+            }
+        }
+    }
+
+    /// Writes an array, whose elements are written with `f`
+    pub fn array<T>(&mut self, xs: &[T], mut f: impl FnMut(&mut Self, &T)) {
+        self.nat0(xs.len() as u64);
+        for x in xs {
+            f(self, x);
+        }
+    }
+
+    /// Writes a tuple of elements of the same type, i.e. the elements alone
+    pub fn tuple<T>(&mut self, xs: &[T], mut f: impl FnMut(&mut Self, &T)) {
+        for x in xs {
+            f(self, x);
+        }
+    }
+
+    /// Writes a field element
+    pub fn field<F: PrimeField>(&mut self, x: &F) {
+        let mut bytes = vec![];
+        x.serialize_compressed(&mut bytes)
+            .expect("a field element can be written to a vector");
+        self.nat0(bytes.len() as u64);
+        self.out.extend(bytes);
+    }
+
+    /// Writes a point, or the point at infinity
+    pub fn point<G: CommitmentCurve>(&mut self, g: &G)
+    where
+        G::BaseField: PrimeField,
+    {
+        match g.to_coordinates() {
+            None => self.out.push(0),
+            Some((x, y)) => {
+                self.out.push(1);
+                self.field(&x);
+                self.field(&y);
+            }
+        }
+    }
+
+    /// Writes a commitment
+    pub fn poly_comm<G: CommitmentCurve>(&mut self, comm: &PolyComm<G>)
+    where
+        G::BaseField: PrimeField,
+    {
+        self.array(&comm.chunks, Self::point);
+        // shifted
+        self.option(&None::<G>, Self::point);
+    }
+
+    /// Writes the evaluations of a polynomial at `zeta` and `zeta * omega`
+    pub fn point_evaluations<F: PrimeField>(&mut self, evals: &PointEvaluations<Vec<F>>) {
+        self.array(&evals.zeta, Self::field);
+        self.array(&evals.zeta_omega, Self::field);
+    }
+}
+
+/// A cursor over bytes from which values are read in the bin_prot format
+#[derive(Debug)]
+pub struct BinProtReader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> BinProtReader<'a> {
+    /// Creates a reader of `input`
+    pub fn new(input: &'a [u8]) -> Self {
+        BinProtReader { input }
+    }
+
+    /// Checks that all the input has been read
+    ///
+    /// # Errors
+    ///
+    /// Will give error if some bytes have not been read.
+    pub fn finish(self) -> Result<(), BinProtError> {
+        if self.input.is_empty() {
+            Ok(())
+        } else {
+            Err(BinProtError::TrailingBytes(self.input.len()))
+        }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], BinProtError> {
+        if self.input.len() < n {
+            return Err(BinProtError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.input.split_at(n);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, BinProtError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn le_bytes<const N: usize>(&mut self) -> Result<[u8; N], BinProtError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.bytes(N)?);
+        Ok(bytes)
+    }
+
+    /// Reads a non-negative integer
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the input ends, or if its first byte is not a
+    /// prefix of a non-negative integer.
+    pub fn nat0(&mut self) -> Result<u64, BinProtError> {
+        match self.byte()? {
+            n @ 0..=0x7f => Ok(n.into()),
+            CODE_INT16 => Ok(u16::from_le_bytes(self.le_bytes()?).into()),
+            CODE_INT32 => Ok(u32::from_le_bytes(self.le_bytes()?).into()),
+            CODE_INT64 => Ok(u64::from_le_bytes(self.le_bytes()?)),
+            code => Err(BinProtError::InvalidTag("nat0", code)),
+        }
+    }
+
+    /// Reads an OCaml `int`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the input ends, or if its first byte is not a
+    /// prefix of an integer.
+    pub fn int(&mut self) -> Result<i64, BinProtError> {
+        match self.byte()? {
+            n @ 0..=0x7f => Ok(n.into()),
+            CODE_NEG_INT8 => Ok(i8::from_le_bytes(self.le_bytes()?).into()),
+            CODE_INT16 => Ok(i16::from_le_bytes(self.le_bytes()?).into()),
+            CODE_INT32 => Ok(i32::from_le_bytes(self.le_bytes()?).into()),
+            CODE_INT64 => Ok(i64::from_le_bytes(self.le_bytes()?)),
+            code => Err(BinProtError::InvalidTag("int", code)),
+        }
+    }
+
+    /// Reads an OCaml `int` as a `usize`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the integer cannot be read, or if it is negative.
+    pub fn usize(&mut self) -> Result<usize, BinProtError> {
+        let n = self.int()?;
+        usize::try_from(n).map_err(|_| BinProtError::IntegerOutOfRange(n))
+    }
+
+    /// Reads a boolean
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the input ends, or if the byte is not 0 or 1.
+    pub fn bool(&mut self) -> Result<bool, BinProtError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            code => Err(BinProtError::InvalidTag("bool", code)),
+        }
+    }
+
+    /// Reads an option, whose value is read with `f`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the tag of the option is invalid, or if `f` fails.
+    pub fn option<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, BinProtError>,
+    ) -> Result<Option<T>, BinProtError> {
+        match self.byte()? {
+            0 => Ok(None),
+            1 => f(self).map(Some),
+            code => Err(BinProtError::InvalidTag("option", code)),
+        }
+    }
+
+    /// Reads an array, whose elements are read with `f`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the length of the array cannot be read, or if `f`
+    /// fails.
+    pub fn array<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, BinProtError>,
+    ) -> Result<Vec<T>, BinProtError> {
+        let len = self.nat0()?;
+        // each element takes at least a byte
+        if len > self.input.len() as u64 {
+            return Err(BinProtError::UnexpectedEnd);
+        }
+        (0..len).map(|_| f(self)).collect()
+    }
+
+    /// Reads a tuple of `N` elements of the same type, read with `f`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `f` fails.
+    pub fn tuple<T, const N: usize>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, BinProtError>,
+    ) -> Result<[T; N], BinProtError> {
+        let xs: Vec<T> = (0..N).map(|_| f(self)).collect::<Result<_, _>>()?;
+        Ok(xs.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+
+    /// Reads an array of `N` elements, read with `f`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the array cannot be read, or if it does not have `N`
+    /// elements.
+    pub fn sized_array<T, const N: usize>(
+        &mut self,
+        name: &'static str,
+        f: impl FnMut(&mut Self) -> Result<T, BinProtError>,
+    ) -> Result<[T; N], BinProtError> {
+        let xs = self.array(f)?;
+        let len = xs.len();
+        xs.try_into()
+            .map_err(|_| BinProtError::IncorrectLength(name, N, len))
+    }
+
+    /// Reads a field element
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the input does not contain the canonical encoding of
+    /// a field element.
+    pub fn field<F: PrimeField>(&mut self) -> Result<F, BinProtError> {
+        let len = self.nat0()?;
+        if len != F::zero().compressed_size() as u64 {
+            return Err(BinProtError::InvalidFieldElement);
+        }
+        let bytes = self.bytes(len as usize)?;
+        F::deserialize_compressed(bytes).map_err(|_| BinProtError::InvalidFieldElement)
+    }
+
+    /// Reads a point, or the point at infinity
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the input does not contain a point of the curve.
+    pub fn point<G: CommitmentCurve>(&mut self) -> Result<G, BinProtError>
+    where
+        G::BaseField: PrimeField,
+    {
+        match self.byte()? {
+            0 => Ok(G::zero()),
+            1 => {
+                let x = self.field()?;
+                let y = self.field()?;
+                let g = G::of_coordinates(x, y);
+                g.check().map_err(|_| BinProtError::InvalidPoint)?;
+                Ok(g)
+            }
+            code => Err(BinProtError::InvalidTag("or_infinity", code)),
+        }
+    }
+
+    /// Reads a commitment
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the commitment cannot be read, or if it is shifted.
+    pub fn poly_comm<G: CommitmentCurve>(&mut self) -> Result<PolyComm<G>, BinProtError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let chunks = self.array(Self::point)?;
+        if self.option(Self::point::<G>)?.is_some() {
+            return Err(BinProtError::ShiftedCommitment);
+        }
+        Ok(PolyComm { chunks })
+    }
+
+    /// Reads the evaluations of a polynomial at `zeta` and `zeta * omega`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the evaluations cannot be read.
+    pub fn point_evaluations<F: PrimeField>(
+        &mut self,
+    ) -> Result<PointEvaluations<Vec<F>>, BinProtError> {
+        Ok(PointEvaluations {
+            zeta: self.array(Self::field)?,
+            zeta_omega: self.array(Self::field)?,
+        })
+    }
+}
+
+/// Encodes the commitment `comm`
+pub fn poly_comm_to_bin_prot<G: CommitmentCurve>(comm: &PolyComm<G>) -> Vec<u8>
+where
+    G::BaseField: PrimeField,
+{
+    let mut w = BinProtWriter::new();
+    w.poly_comm(comm);
+    w.finish()
+}
+
+/// Decodes a commitment encoded with [`poly_comm_to_bin_prot`]
+///
+/// # Errors
+///
+/// Will give error if `bytes` is not the encoding of a commitment.
+pub fn poly_comm_from_bin_prot<G: CommitmentCurve>(
+    bytes: &[u8],
+) -> Result<PolyComm<G>, BinProtError>
+where
+    G::BaseField: PrimeField,
+{
+    let mut r = BinProtReader::new(bytes);
+    let comm = r.poly_comm()?;
+    r.finish()?;
+    Ok(comm)
+}
+
+fn write_evaluations<F: PrimeField>(
+    w: &mut BinProtWriter,
+    evals: &ProofEvaluations<PointEvaluations<Vec<F>>>,
+) {
+    let ProofEvaluations {
+        public: _,
+        w: witness,
+        z,
+        s,
+        coefficients,
+        generic_selector,
+        poseidon_selector,
+        complete_add_selector,
+        mul_selector,
+        emul_selector,
+        endomul_scalar_selector,
+        range_check0_selector,
+        range_check1_selector,
+        foreign_field_add_selector,
+        foreign_field_mul_selector,
+        xor_selector,
+        rot_selector,
+        lookup_aggregation,
+        lookup_table,
+        lookup_sorted,
+        runtime_lookup_table,
+        runtime_lookup_table_selector,
+        xor_lookup_selector,
+        lookup_gate_lookup_selector,
+        range_check_lookup_selector,
+        foreign_field_mul_lookup_selector,
+    } = evals;
+
+    w.tuple(witness, BinProtWriter::point_evaluations);
+    w.point_evaluations(z);
+    w.tuple(s, BinProtWriter::point_evaluations);
+    w.tuple(coefficients, BinProtWriter::point_evaluations);
+    for selector in [
+        generic_selector,
+        poseidon_selector,
+        complete_add_selector,
+        mul_selector,
+        emul_selector,
+        endomul_scalar_selector,
+    ] {
+        w.point_evaluations(selector);
+    }
+    for selector in [
+        range_check0_selector,
+        range_check1_selector,
+        foreign_field_add_selector,
+        foreign_field_mul_selector,
+        xor_selector,
+        rot_selector,
+        lookup_aggregation,
+        lookup_table,
+    ] {
+        w.option(selector, BinProtWriter::point_evaluations);
+    }
+    w.array(lookup_sorted, |w, sorted| {
+        w.option(sorted, BinProtWriter::point_evaluations)
+    });
+    for selector in [
+        runtime_lookup_table,
+        runtime_lookup_table_selector,
+        xor_lookup_selector,
+        lookup_gate_lookup_selector,
+        range_check_lookup_selector,
+        foreign_field_mul_lookup_selector,
+    ] {
+        w.option(selector, BinProtWriter::point_evaluations);
+    }
+}
+
+fn read_evaluations<F: PrimeField>(
+    r: &mut BinProtReader,
+    public: Option<PointEvaluations<Vec<F>>>,
+) -> Result<ProofEvaluations<PointEvaluations<Vec<F>>>, BinProtError> {
+    let opt = |r: &mut BinProtReader| r.option(BinProtReader::point_evaluations);
+    Ok(ProofEvaluations {
+        public,
+        w: r.tuple(BinProtReader::point_evaluations)?,
+        z: r.point_evaluations()?,
+        s: r.tuple(BinProtReader::point_evaluations)?,
+        coefficients: r.tuple(BinProtReader::point_evaluations)?,
+        generic_selector: r.point_evaluations()?,
+        poseidon_selector: r.point_evaluations()?,
+        complete_add_selector: r.point_evaluations()?,
+        mul_selector: r.point_evaluations()?,
+        emul_selector: r.point_evaluations()?,
+        endomul_scalar_selector: r.point_evaluations()?,
+        range_check0_selector: opt(r)?,
+        range_check1_selector: opt(r)?,
+        foreign_field_add_selector: opt(r)?,
+        foreign_field_mul_selector: opt(r)?,
+        xor_selector: opt(r)?,
+        rot_selector: opt(r)?,
+        lookup_aggregation: opt(r)?,
+        lookup_table: opt(r)?,
+        lookup_sorted: r.sized_array("lookup_sorted", opt)?,
+        runtime_lookup_table: opt(r)?,
+        runtime_lookup_table_selector: opt(r)?,
+        xor_lookup_selector: opt(r)?,
+        lookup_gate_lookup_selector: opt(r)?,
+        range_check_lookup_selector: opt(r)?,
+        foreign_field_mul_lookup_selector: opt(r)?,
+    })
+}
+
+impl<G: KimchiCurve> ProverProof<G, OpeningProof<G>>
+where
+    G::BaseField: PrimeField,
+{
+    /// Encodes the proof with its public input, as the `proof_with_public` of
+    /// Mina's OCaml code
+    pub fn to_bin_prot(&self, public_input: &[G::ScalarField]) -> Vec<u8> {
+        let mut w = BinProtWriter::new();
+
+        // public_evals
+        w.option(&self.evals.public, BinProtWriter::point_evaluations);
+
+        // commitments
+        let ProverCommitments {
+            w_comm,
+            z_comm,
+            t_comm,
+            lookup,
+        } = &self.commitments;
+        w.tuple(w_comm, BinProtWriter::poly_comm);
+        w.poly_comm(z_comm);
+        w.poly_comm(t_comm);
+        w.option(lookup, |w, lookup| {
+            w.array(&lookup.sorted, BinProtWriter::poly_comm);
+            w.poly_comm(&lookup.aggreg);
+            w.option(&lookup.runtime, BinProtWriter::poly_comm);
+        });
+
+        // opening proof
+        w.array(&self.proof.lr, |w, (l, r)| {
+            w.point(l);
+            w.point(r);
+        });
+        w.point(&self.proof.delta);
+        w.field(&self.proof.z1);
+        w.field(&self.proof.z2);
+        w.point(&self.proof.sg);
+
+        write_evaluations(&mut w, &self.evals);
+        w.field(&self.ft_eval1);
+        w.array(public_input, BinProtWriter::field);
+        w.array(&self.prev_challenges, |w, challenge| {
+            w.array(&challenge.chals, BinProtWriter::field);
+            w.poly_comm(&challenge.comm);
+        });
+
+        w.finish()
+    }
+
+    /// Decodes a proof and its public input encoded with
+    /// [`ProverProof::to_bin_prot`]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not the encoding of a proof.
+    pub fn from_bin_prot(bytes: &[u8]) -> Result<(Self, Vec<G::ScalarField>), BinProtError> {
+        let mut r = BinProtReader::new(bytes);
+
+        let public_evals = r.option(BinProtReader::point_evaluations)?;
+
+        let commitments = ProverCommitments {
+            w_comm: r.tuple(BinProtReader::poly_comm)?,
+            z_comm: r.poly_comm()?,
+            t_comm: r.poly_comm()?,
+            lookup: r.option(|r| {
+                Ok(LookupCommitments {
+                    sorted: r.array(BinProtReader::poly_comm)?,
+                    aggreg: r.poly_comm()?,
+                    runtime: r.option(BinProtReader::poly_comm)?,
+                })
+            })?,
+        };
+
+        let proof = OpeningProof {
+            lr: r.array(|r| Ok((r.point()?, r.point()?)))?,
+            delta: r.point()?,
+            z1: r.field()?,
+            z2: r.field()?,
+            sg: r.point()?,
+        };
+
+        let evals = read_evaluations(&mut r, public_evals)?;
+        let ft_eval1 = r.field()?;
+        let public_input = r.array(BinProtReader::field)?;
+        let prev_challenges = r.array(|r| {
+            Ok(RecursionChallenge {
+                chals: r.array(BinProtReader::field)?,
+                comm: r.poly_comm()?,
+            })
+        })?;
+        r.finish()?;
+
+        let proof = ProverProof {
+            commitments,
+            proof,
+            evals,
+            ft_eval1,
+            prev_challenges,
+        };
+        Ok((proof, public_input))
+    }
+}
+
+fn write_lookup_index<G: CommitmentCurve>(w: &mut BinProtWriter, index: &LookupVerifierIndex<G>)
+where
+    G::BaseField: PrimeField,
+{
+    w.bool(index.joint_lookup_used);
+    w.array(&index.lookup_table, BinProtWriter::poly_comm);
+
+    let LookupSelectors {
+        xor,
+        lookup,
+        range_check,
+        ffmul,
+    } = &index.lookup_selectors;
+    for selector in [lookup, xor, range_check, ffmul] {
+        w.option(selector, BinProtWriter::poly_comm);
+    }
+    w.option(&index.table_ids, BinProtWriter::poly_comm);
+
+    let LookupInfo {
+        max_per_row,
+        max_joint_size,
+        features,
+    } = &index.lookup_info;
+    w.usize(*max_per_row);
+    w.int((*max_joint_size).into());
+    let LookupPatterns {
+        xor,
+        lookup,
+        range_check,
+        foreign_field_mul,
+    } = features.patterns;
+    for pattern in [xor, lookup, range_check, foreign_field_mul] {
+        w.bool(pattern);
+    }
+    w.bool(features.joint_lookup_used);
+    w.bool(features.uses_runtime_tables);
+
+    w.option(&index.runtime_tables_selector, BinProtWriter::poly_comm);
+}
+
+fn read_lookup_index<G: CommitmentCurve>(
+    r: &mut BinProtReader,
+) -> Result<LookupVerifierIndex<G>, BinProtError>
+where
+    G::BaseField: PrimeField,
+{
+    let joint_lookup_used = r.bool()?;
+    let lookup_table = r.array(BinProtReader::poly_comm)?;
+
+    let lookup = r.option(BinProtReader::poly_comm)?;
+    let xor = r.option(BinProtReader::poly_comm)?;
+    let range_check = r.option(BinProtReader::poly_comm)?;
+    let ffmul = r.option(BinProtReader::poly_comm)?;
+    let lookup_selectors = LookupSelectors {
+        xor,
+        lookup,
+        range_check,
+        ffmul,
+    };
+    let table_ids = r.option(BinProtReader::poly_comm)?;
+
+    let max_per_row = r.usize()?;
+    let max_joint_size = r.int()?;
+    let max_joint_size = u32::try_from(max_joint_size)
+        .map_err(|_| BinProtError::IntegerOutOfRange(max_joint_size))?;
+    let patterns = LookupPatterns {
+        xor: r.bool()?,
+        lookup: r.bool()?,
+        range_check: r.bool()?,
+        foreign_field_mul: r.bool()?,
+    };
+    let features = LookupFeatures {
+        patterns,
+        joint_lookup_used: r.bool()?,
+        uses_runtime_tables: r.bool()?,
+    };
+    let lookup_info = LookupInfo {
+        max_per_row,
+        max_joint_size,
+        features,
+    };
+
+    Ok(LookupVerifierIndex {
+        joint_lookup_used,
+        lookup_table,
+        lookup_selectors,
+        table_ids,
+        lookup_info,
+        runtime_tables_selector: r.option(BinProtReader::poly_comm)?,
+    })
+}
+
+impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
+where
+    G::BaseField: PrimeField,
+{
+    /// Encodes the verifier index as the `verifier_index` of Mina's OCaml
+    /// code, without its SRS
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index has public sections, which the encoding
+    /// does not carry.
+    pub fn to_bin_prot(&self) -> Result<Vec<u8>, BinProtError> {
+        if !self.public_sections.is_empty() {
+            return Err(BinProtError::PublicSections);
+        }
+        let mut w = BinProtWriter::new();
+
+        // domain
+        w.usize(self.domain.log_size_of_group as usize);
+        w.field(&self.domain.group_gen);
+
+        w.usize(self.max_poly_size);
+        w.usize(self.public);
+        w.usize(self.prev_challenges);
+
+        // evals
+        w.array(&self.sigma_comm, BinProtWriter::poly_comm);
+        w.array(&self.coefficients_comm, BinProtWriter::poly_comm);
+        for comm in [
+            &self.generic_comm,
+            &self.psm_comm,
+            &self.complete_add_comm,
+            &self.mul_comm,
+            &self.emul_comm,
+            &self.endomul_scalar_comm,
+        ] {
+            w.poly_comm(comm);
+        }
+        for gate_type in BIN_PROT_OPTIONAL_GATES {
+            w.option(
+                &self.optional_selector_comm.get(&gate_type).cloned(),
+                BinProtWriter::poly_comm,
+            );
+        }
+
+        w.array(&self.shift, BinProtWriter::field);
+        w.option(&self.lookup_index, write_lookup_index);
+        w.usize(self.zk_rows as usize);
+
+        Ok(w.finish())
+    }
+
+    /// Decodes a verifier index encoded with [`VerifierIndex::to_bin_prot`],
+    /// and sets its SRS to `srs`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not the encoding of a verifier index.
+    pub fn from_bin_prot(bytes: &[u8], srs: Arc<OpeningProof::SRS>) -> Result<Self, BinProtError> {
+        let mut r = BinProtReader::new(bytes);
+
+        let log_size = r.usize()?;
+        let group_gen: G::ScalarField = r.field()?;
+        let domain = (log_size < usize::BITS as usize)
+            .then(|| D::new(1 << log_size))
+            .flatten()
+            .filter(|domain| domain.group_gen == group_gen)
+            .ok_or(BinProtError::InvalidDomain(log_size))?;
+
+        let max_poly_size = r.usize()?;
+        let public = r.usize()?;
+        let prev_challenges = r.usize()?;
+
+        let sigma_comm = r.sized_array::<_, PERMUTS>("sigma_comm", BinProtReader::poly_comm)?;
+        let coefficients_comm =
+            r.sized_array::<_, COLUMNS>("coefficients_comm", BinProtReader::poly_comm)?;
+        let generic_comm = r.poly_comm()?;
+        let psm_comm = r.poly_comm()?;
+        let complete_add_comm = r.poly_comm()?;
+        let mul_comm = r.poly_comm()?;
+        let emul_comm = r.poly_comm()?;
+        let endomul_scalar_comm = r.poly_comm()?;
+        let mut optional_selector_comm = BTreeMap::new();
+        for gate_type in BIN_PROT_OPTIONAL_GATES {
+            if let Some(comm) = r.option(BinProtReader::poly_comm)? {
+                optional_selector_comm.insert(gate_type, comm);
+            }
+        }
+
+        let shift = r.sized_array::<_, PERMUTS>("shifts", BinProtReader::field)?;
+        let lookup_index = r.option(read_lookup_index)?;
+        let zk_rows = r.usize()? as u64;
+        r.finish()?;
+
+        let mut index = VerifierIndex {
+            domain,
+            max_poly_size,
+            zk_rows,
+            srs,
+            public,
+            public_sections: vec![],
+            prev_challenges,
+            sigma_comm,
+            coefficients_comm,
+            generic_comm,
+            psm_comm,
+            complete_add_comm,
+            mul_comm,
+            emul_comm,
+            endomul_scalar_comm,
+            optional_selector_comm,
+            shift,
+            permutation_vanishing_polynomial_m: OnceCell::new(),
+            w: OnceCell::new(),
+            endo: *G::other_curve_endo(),
+            lookup_index,
+            linearization: Default::default(),
+            powers_of_alpha: Default::default(),
+            public_comm: None,
+        };
+        let (linearization, powers_of_alpha) =
+            expr_linearization(Some(&index.feature_flags()), true);
+        index.linearization = linearization;
+        index.powers_of_alpha = powers_of_alpha;
+        Ok(index)
+    }
+}
@@ -0,0 +1,200 @@
+//! A registry giving a human-readable name to the columns of a circuit.
+//!
+//! The columns are identified by an index, which is not very helpful when a
+//! constraint fails. The [ColumnRegistry] maps each column to a [ColumnInfo],
+//! i.e. a name, the subsystem owning the column and an optional description,
+//! so that diagnostics can report `scratch[12] (mips::add carry bit)` instead
+//! of `Relation(12)`. The errors of the verifier and of the gates name the
+//! Kimchi columns this way, and the spot-checks of o1vm name the MIPS columns.
+//!
+//! The name of each column is given by a naming function provided by the
+//! subsystem defining the columns (see [kimchi_column_info] for the Kimchi
+//! columns). More precise descriptions can be attached to single columns with
+//! [ColumnRegistry::annotate].
+
+use crate::circuits::{berkeley_columns::Column, expr::ExprError, lookup::lookups::LookupPattern};
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+/// Human-readable information about a column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnInfo {
+    /// The name of the column, e.g. `w[3]` or `scratch[12]`
+    pub name: String,
+    /// The subsystem owning the column, e.g. `kimchi` or `mips`
+    pub subsystem: &'static str,
+    /// What the column is used for, if known
+    pub description: Option<String>,
+}
+
+impl ColumnInfo {
+    pub fn new(name: impl Into<String>, subsystem: &'static str) -> Self {
+        ColumnInfo {
+            name: name.into(),
+            subsystem,
+            description: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl std::fmt::Display for ColumnInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.description {
+            Some(description) => write!(f, "{} ({}::{})", self.name, self.subsystem, description),
+            None => write!(f, "{} ({})", self.name, self.subsystem),
+        }
+    }
+}
+
+/// Maps the columns of type `C` to their [ColumnInfo].
+#[derive(Clone)]
+pub struct ColumnRegistry<C> {
+    naming: fn(&C) -> ColumnInfo,
+    annotations: HashMap<C, (&'static str, String)>,
+}
+
+impl<C: Copy + Eq + Hash + Debug> ColumnRegistry<C> {
+    /// Create a registry naming the columns with `naming`.
+    pub fn new(naming: fn(&C) -> ColumnInfo) -> Self {
+        ColumnRegistry {
+            naming,
+            annotations: HashMap::new(),
+        }
+    }
+
+    /// Attach a description to the column `col`, owned by `subsystem`. It
+    /// replaces any previous description of the column.
+    pub fn annotate(&mut self, col: C, subsystem: &'static str, description: impl Into<String>) {
+        self.annotations
+            .insert(col, (subsystem, description.into()));
+    }
+
+    /// Returns the information registered for the column `col`.
+    pub fn info(&self, col: &C) -> ColumnInfo {
+        let info = (self.naming)(col);
+        match self.annotations.get(col) {
+            Some((subsystem, description)) => ColumnInfo {
+                subsystem,
+                description: Some(description.clone()),
+                ..info
+            },
+            None => info,
+        }
+    }
+
+    /// Returns a human-readable description of the column `col`.
+    pub fn describe(&self, col: &C) -> String {
+        self.info(col).to_string()
+    }
+
+    /// Format the error `err`, replacing the columns by their description.
+    pub fn describe_error(&self, err: &ExprError<C>) -> String {
+        match err {
+            ExprError::MissingEvaluation(col, row) => format!(
+                "missing evaluation of {} at the {:?} row",
+                self.describe(col),
+                row
+            ),
+            ExprError::MissingIndexEvaluation(col) => format!(
+                "cannot get index evaluation {} (should have been linearized away)",
+                self.describe(col)
+            ),
+            err => err.to_string(),
+        }
+    }
+}
+
+impl ColumnRegistry<Column> {
+    /// The registry of the columns used by Kimchi.
+    pub fn kimchi() -> Self {
+        Self::new(kimchi_column_info)
+    }
+}
+
+/// The naming function of the Kimchi columns.
+pub fn kimchi_column_info(col: &Column) -> ColumnInfo {
+    match col {
+        Column::Witness(i) => ColumnInfo::new(format!("w[{i}]"), "kimchi"),
+        Column::Z => {
+            ColumnInfo::new("z", "kimchi::permutation").with_description("permutation aggregation")
+        }
+        Column::Permutation(i) => ColumnInfo::new(format!("sigma[{i}]"), "kimchi::permutation"),
+        Column::Coefficient(i) => ColumnInfo::new(format!("coeff[{i}]"), "kimchi"),
+        Column::Index(gate) => ColumnInfo::new(format!("selector[{gate:?}]"), "kimchi::gates"),
+        Column::LookupSorted(i) => ColumnInfo::new(format!("sorted[{i}]"), "kimchi::lookup"),
+        Column::LookupAggreg => ColumnInfo::new("aggregation", "kimchi::lookup"),
+        Column::LookupTable => ColumnInfo::new("table", "kimchi::lookup"),
+        Column::LookupKindIndex(pattern) => {
+            let name = match pattern {
+                LookupPattern::Xor => "xor",
+                LookupPattern::Lookup => "lookup",
+                LookupPattern::RangeCheck => "range_check",
+                LookupPattern::ForeignFieldMul => "foreign_field_mul",
+            };
+            ColumnInfo::new(format!("lookup_selector[{name}]"), "kimchi::lookup")
+        }
+        Column::LookupRuntimeSelector => ColumnInfo::new("runtime_selector", "kimchi::lookup"),
+        Column::LookupRuntimeTable => ColumnInfo::new("runtime_table", "kimchi::lookup"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuits::{
+            gate::{CircuitGateError, CurrOrNext, GateType},
+            wires::Wire,
+        },
+        error::VerifyError,
+    };
+
+    #[test]
+    fn test_kimchi_column_names() {
+        let mut registry = ColumnRegistry::kimchi();
+        assert_eq!(registry.describe(&Column::Witness(3)), "w[3] (kimchi)");
+        assert_eq!(
+            registry.describe(&Column::Index(GateType::Generic)),
+            "selector[Generic] (kimchi::gates)"
+        );
+        registry.annotate(Column::Witness(3), "range_check", "limb 0");
+        assert_eq!(
+            registry.describe(&Column::Witness(3)),
+            "w[3] (range_check::limb 0)"
+        );
+        assert_eq!(
+            registry.describe_error(&ExprError::MissingEvaluation(
+                Column::Witness(3),
+                CurrOrNext::Next
+            )),
+            "missing evaluation of w[3] (range_check::limb 0) at the Next row"
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_columns() {
+        assert_eq!(
+            VerifyError::IncorrectEvaluationsLength(1, 2, Some(Column::Z)).to_string(),
+            "proof malformed: an evaluation for z (kimchi::permutation::permutation \
+             aggregation) was of the incorrect size (expected 1, got 2)"
+        );
+        assert_eq!(
+            VerifyError::IncorrectEvaluationsLength(1, 2, None).to_string(),
+            "proof malformed: an evaluation for public input was of the incorrect size \
+             (expected 1, got 2)"
+        );
+        assert_eq!(
+            CircuitGateError::CopyConstraint {
+                typ: GateType::Generic,
+                src: Wire::new(1, 2),
+                dst: Wire::new(3, 0),
+            }
+            .to_string(),
+            "Invalid Generic copy constraint: w[2] (kimchi) at row 1 -> w[0] (kimchi) at row 3"
+        );
+    }
+}
@@ -4,18 +4,19 @@ use crate::{
     circuits::{
         domain_constant_evaluation::DomainConstantEvaluations,
         domains::EvaluationDomains,
-        gate::{CircuitGate, GateType},
+        gate::{CircuitGate, CurrOrNext, GateType},
         lookup::{
             index::LookupConstraintSystem,
-            lookups::{LookupFeatures, LookupPatterns},
-            tables::{GateLookupTables, LookupTable},
+            lookups::{LocalPosition, LookupFeatures, LookupPatterns},
+            runtime_tables::RuntimeTable,
+            tables::LookupTable,
         },
         polynomial::{WitnessEvals, WitnessOverDomains, WitnessShifts},
         polynomials::permutation::Shifts,
         wires::*,
     },
     curve::KimchiCurve,
-    error::{DomainCreationError, SetupError},
+    error::{SetupError, VerifyError},
     prover_index::ProverIndex,
 };
 use ark_ff::{PrimeField, Zero};
@@ -23,18 +24,35 @@ use ark_poly::{
     univariate::DensePolynomial as DP, EvaluationDomain, Evaluations as E,
     Radix2EvaluationDomain as D,
 };
-use o1_utils::ExtendedEvaluations;
+use num_bigint::BigUint;
+use o1_utils::{ExtendedEvaluations, FieldHelpers};
 use once_cell::sync::OnceCell;
 use poly_commitment::OpenProof;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
-use std::{array, default::Default, sync::Arc};
+use std::{array, collections::HashSet, default::Default, sync::Arc};
 
 //
 // ConstraintSystem
 //
 
-/// Flags for optional features in the constraint system
+/// The gates whose selectors are only committed to when the circuit uses them,
+/// in the order their commitments are absorbed in the digest of the verifier
+/// index. A new optional gate only has to be added here and to
+/// [`FeatureFlags`].
+pub const OPTIONAL_GATES: [GateType; 6] = [
+    GateType::RangeCheck0,
+    GateType::RangeCheck1,
+    GateType::ForeignFieldMul,
+    GateType::ForeignFieldAdd,
+    GateType::Xor16,
+    GateType::Rot64,
+];
+
+/// Flags for optional features in the constraint system. They are detected
+/// from the gates of the circuit (see [`FeatureFlags::from_gates`]), and the
+/// indexes only carry the selectors, lookup tables and constraints of the
+/// enabled features.
 #[cfg_attr(
     feature = "ocaml_types",
     derive(ocaml::IntoValue, ocaml::FromValue, ocaml_gen::Struct)
@@ -172,6 +190,10 @@ pub struct ConstraintSystem<F: PrimeField> {
     // ------
     /// number of public inputs
     pub public: usize,
+    /// named sections of the public input, in order, if it has any (see
+    /// [`Builder::public_sections`])
+    #[serde(default)]
+    pub public_sections: Vec<PublicSection>,
     /// number of previous evaluation challenges, for recursive proving
     pub prev_challenges: usize,
     /// evaluation domains
@@ -216,17 +238,92 @@ pub enum GateError {
     IncorrectPublic(usize),
     /// A specific gate did not verify correctly
     Custom { row: usize, err: String },
+    /// A lookup of the row is not in its table, whose ID is given if it is a
+    /// valid table ID
+    LookupNotInTable { row: usize, table_id: Option<i32> },
+    /// The runtime tables do not match the configuration of the circuit
+    RuntimeTablesInconsistent,
+}
+
+/// A named section of the public input, see [`Builder::public_sections`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicSection {
+    /// The name of the section
+    pub name: String,
+    /// The number of public inputs of the section
+    pub size: usize,
+}
+
+impl PublicSection {
+    /// Creates the section `name` of `size` public inputs
+    pub fn new(name: impl Into<String>, size: usize) -> Self {
+        Self {
+            name: name.into(),
+            size,
+        }
+    }
+}
+
+/// Concatenates the values of the public sections `sections`, given in the
+/// order of the sections, into the public input.
+///
+/// # Errors
+///
+/// Will give error if the number of values, or the size of one of them, is not
+/// the one of the sections.
+pub fn concat_public_sections<F: Clone>(
+    sections: &[PublicSection],
+    values: &[&[F]],
+) -> Result<Vec<F>, VerifyError> {
+    if values.len() != sections.len() {
+        return Err(VerifyError::IncorrectPublicSectionsCount(
+            sections.len(),
+            values.len(),
+        ));
+    }
+    let mut public_input = Vec::with_capacity(sections.iter().map(|s| s.size).sum());
+    for (i, (section, values)) in sections.iter().zip(values).enumerate() {
+        if values.len() != section.size {
+            return Err(VerifyError::IncorrectPublicSectionLength(
+                i,
+                section.size,
+                values.len(),
+            ));
+        }
+        public_input.extend_from_slice(values);
+    }
+    Ok(public_input)
+}
+
+/// Splits `public_input` into the public sections `sections`, giving the name
+/// and the values of each section. The values after the last section, if any,
+/// are not returned.
+pub fn split_public_input<'a, F>(
+    sections: &'a [PublicSection],
+    public_input: &'a [F],
+) -> Vec<(&'a str, &'a [F])> {
+    let mut rest = public_input;
+    sections
+        .iter()
+        .map(|section| {
+            let (values, tail) = rest.split_at(std::cmp::min(section.size, rest.len()));
+            rest = tail;
+            (section.name.as_str(), values)
+        })
+        .collect()
 }
 
 pub struct Builder<F: PrimeField> {
     gates: Vec<CircuitGate<F>>,
     public: usize,
+    public_sections: Vec<PublicSection>,
     prev_challenges: usize,
     lookup_tables: Vec<LookupTable<F>>,
     runtime_tables: Option<Vec<RuntimeTableCfg<F>>>,
     precomputations: Option<Arc<DomainConstantEvaluations<F>>>,
     disable_gates_checks: bool,
     max_poly_size: Option<usize>,
+    zk_rows: Option<u64>,
 }
 
 /// Create selector polynomial for a circuit gate
@@ -279,12 +376,14 @@ impl<F: PrimeField> ConstraintSystem<F> {
         Builder {
             gates,
             public: 0,
+            public_sections: vec![],
             prev_challenges: 0,
             lookup_tables: vec![],
             runtime_tables: None,
             precomputations: None,
             disable_gates_checks: false,
             max_poly_size: None,
+            zk_rows: None,
         }
     }
 
@@ -322,6 +421,8 @@ impl<F: PrimeField, G: KimchiCurve<ScalarField = F>, OpeningProof: OpenProof<G>>
     /// assignments (witness) against the constraints
     ///     witness: wire assignment witness
     ///     RETURN: verification status
+    ///
+    /// The lookups are not checked, see [Self::verify_with_lookups].
     pub fn verify(&self, witness: &[Vec<F>; COLUMNS], public: &[F]) -> Result<(), GateError> {
         // pad the witness
         let pad = vec![F::zero(); self.cs.domain.d1.size() - witness[0].len()];
@@ -368,6 +469,112 @@ impl<F: PrimeField, G: KimchiCurve<ScalarField = F>, OpeningProof: OpenProof<G>>
         // all good!
         Ok(())
     }
+
+    /// Verifies the witness as [Self::verify], and also checks that each lookup is
+    /// an entry of its table, the `runtime_tables` being given in the order of the
+    /// configuration of the circuit, as to the prover.
+    ///
+    /// This is slower than [Self::verify], but catches the lookup failures before
+    /// running the prover.
+    pub fn verify_with_lookups(
+        &self,
+        witness: &[Vec<F>; COLUMNS],
+        public: &[F],
+        runtime_tables: &[RuntimeTable<F>],
+    ) -> Result<(), GateError> {
+        self.verify(witness, public)?;
+
+        let lcs = match &self.cs.lookup_constraint_system {
+            Some(lcs) => lcs,
+            None => return Ok(()),
+        };
+
+        // pad the witness
+        let pad = vec![F::zero(); self.cs.domain.d1.size() - witness[0].len()];
+        let witness: [Vec<F>; COLUMNS] = array::from_fn(|i| {
+            let mut w = witness[i].to_vec();
+            w.extend_from_slice(&pad);
+            w
+        });
+
+        // the lookups are not enforced on the zero-knowledge rows and the row before
+        let lookup_rows = self.cs.domain.d1.size() - self.cs.zk_rows as usize - 1;
+
+        // the runtime tables must match the configuration
+        let expected: Vec<_> = lcs
+            .runtime_tables
+            .iter()
+            .flatten()
+            .map(|rt| (rt.id, rt.len))
+            .collect();
+        let given: Vec<_> = runtime_tables
+            .iter()
+            .map(|rt| (rt.id, rt.data.len()))
+            .collect();
+        if expected != given {
+            return Err(GateError::RuntimeTablesInconsistent);
+        }
+        let runtime_data: Vec<F> = runtime_tables
+            .iter()
+            .flat_map(|rt| rt.data.iter().copied())
+            .collect();
+        let runtime_offset = lcs.runtime_table_offset.unwrap_or(0);
+        let runtime_rows = runtime_offset..runtime_offset + runtime_data.len();
+
+        // the entries of the concatenated table
+        let width = lcs.lookup_table8.len();
+        let mut entries = HashSet::new();
+        for row in 0..lookup_rows {
+            let table_id = match &lcs.table_ids8 {
+                Some(table_ids) => table_ids.evals[8 * row],
+                None => F::zero(),
+            };
+            let mut entry: Vec<F> = lcs
+                .lookup_table8
+                .iter()
+                .map(|col| col.evals[8 * row])
+                .collect();
+            // the runtime data is in the second column
+            if runtime_rows.contains(&row) {
+                entry[1] += runtime_data[row - runtime_offset];
+            }
+            entries.insert((table_id, entry));
+        }
+
+        let by_row = lcs.configuration.lookup_info.by_row(&self.cs.gates);
+        for (row, lookups) in by_row.iter().enumerate().take(lookup_rows) {
+            let eval = |pos: LocalPosition| match pos.row {
+                CurrOrNext::Curr => witness[pos.column][row],
+                CurrOrNext::Next => witness[pos.column][row + 1],
+            };
+            for lookup in lookups {
+                let lookup = lookup.reduce(&eval);
+                let mut entry = lookup.entry;
+                entry.resize(width, F::zero());
+                if !entries.contains(&(lookup.table_id, entry)) {
+                    return Err(GateError::LookupNotInTable {
+                        row,
+                        table_id: field_to_i32(lookup.table_id),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the table ID encoded by a field element, the inverse of
+/// [i32_to_field](o1_utils::field_helpers::i32_to_field)
+fn field_to_i32<F: PrimeField>(x: F) -> Option<i32> {
+    let max = BigUint::from(i32::MAX as u32);
+    if x.to_biguint() <= max {
+        i32::try_from(x.to_biguint()).ok()
+    } else if (-x).to_biguint() <= max + 1u32 {
+        i64::try_from((-x).to_biguint()).ok().map(|x| -x as i32)
+    } else {
+        None
+    }
 }
 
 impl<F: PrimeField> ConstraintSystem<F> {
@@ -410,8 +617,8 @@ impl<F: PrimeField> ConstraintSystem<F> {
         }
     }
 
-    pub(crate) fn evaluated_column_coefficients(&self) -> EvaluatedColumnCoefficients<F> {
-        // compute permutation polynomials
+    /// Returns the evaluations over `d1` of the permutation polynomials
+    pub(crate) fn permutation_evaluations1(&self) -> [E<F, D<F>>; PERMUTS] {
         let shifts = Shifts::new(&self.domain.d1);
 
         let n = self.domain.d1.size();
@@ -432,18 +639,65 @@ impl<F: PrimeField> ConstraintSystem<F> {
             }
         }
 
-        let sigmal1: [_; PERMUTS] = {
-            let [s0, s1, s2, s3, s4, s5, s6] = sigmal1;
-            [
-                E::<F, D<F>>::from_vec_and_domain(s0, self.domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s1, self.domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s2, self.domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s3, self.domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s4, self.domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s5, self.domain.d1),
-                E::<F, D<F>>::from_vec_and_domain(s6, self.domain.d1),
-            ]
-        };
+        let [s0, s1, s2, s3, s4, s5, s6] = sigmal1;
+        [
+            E::<F, D<F>>::from_vec_and_domain(s0, self.domain.d1),
+            E::<F, D<F>>::from_vec_and_domain(s1, self.domain.d1),
+            E::<F, D<F>>::from_vec_and_domain(s2, self.domain.d1),
+            E::<F, D<F>>::from_vec_and_domain(s3, self.domain.d1),
+            E::<F, D<F>>::from_vec_and_domain(s4, self.domain.d1),
+            E::<F, D<F>>::from_vec_and_domain(s5, self.domain.d1),
+            E::<F, D<F>>::from_vec_and_domain(s6, self.domain.d1),
+        ]
+    }
+
+    /// Returns the non-zero evaluations over `d1` of the selector polynomial
+    /// of `gate_type` (see [`selector_polynomial`]), as `(row, value)` pairs,
+    /// i.e. the rows of the gates of this type
+    pub(crate) fn sparse_selector(&self, gate_type: GateType) -> Vec<(usize, F)> {
+        if cfg!(debug_assertions) && self.disable_gates_checks {
+            return vec![];
+        }
+        self.gate_rows(gate_type)
+    }
+
+    /// Returns the rows of the gates of type `gate_type`, as `(row, 1)` pairs,
+    /// whether the gate checks are disabled or not
+    pub(crate) fn gate_rows(&self, gate_type: GateType) -> Vec<(usize, F)> {
+        self.gates
+            .iter()
+            .enumerate()
+            .filter(|(_, gate)| gate.typ == gate_type)
+            .map(|(row, _)| (row, F::one()))
+            .collect()
+    }
+
+    /// Returns the non-zero evaluations over `d1` of the Poseidon selector, as
+    /// `(row, value)` pairs
+    pub(crate) fn sparse_poseidon_selector(&self) -> Vec<(usize, F)> {
+        self.gates
+            .iter()
+            .enumerate()
+            .map(|(row, gate)| (row, gate.ps()))
+            .filter(|(_, ps)| !ps.is_zero())
+            .collect()
+    }
+
+    /// Returns the evaluations over `d1` of the coefficient polynomials
+    pub(crate) fn coefficient_evaluations1(&self) -> [E<F, D<F>>; COLUMNS] {
+        array::from_fn(|i| {
+            let padded = self
+                .gates
+                .iter()
+                .map(|gate| gate.coeffs.get(i).cloned().unwrap_or_else(F::zero))
+                .collect();
+            E::from_vec_and_domain(padded, self.domain.d1)
+        })
+    }
+
+    pub(crate) fn evaluated_column_coefficients(&self) -> EvaluatedColumnCoefficients<F> {
+        // compute permutation polynomials
+        let sigmal1 = self.permutation_evaluations1();
 
         let permutation_coefficients: [DP<F>; PERMUTS] =
             array::from_fn(|i| sigmal1[i].clone().interpolate());
@@ -472,15 +726,9 @@ impl<F: PrimeField> ConstraintSystem<F> {
         .interpolate();
 
         // coefficient polynomial
-        let coefficients: [_; COLUMNS] = array::from_fn(|i| {
-            let padded = self
-                .gates
-                .iter()
-                .map(|gate| gate.coeffs.get(i).cloned().unwrap_or_else(F::zero))
-                .collect();
-            let eval = E::from_vec_and_domain(padded, self.domain.d1);
-            eval.interpolate()
-        });
+        let coefficients: [_; COLUMNS] = self
+            .coefficient_evaluations1()
+            .map(|eval| eval.interpolate());
 
         EvaluatedColumnCoefficients {
             permutation_coefficients,
@@ -689,20 +937,39 @@ impl FeatureFlags {
         };
 
         for gate in gates {
-            match gate.typ {
-                GateType::RangeCheck0 => feature_flags.range_check0 = true,
-                GateType::RangeCheck1 => feature_flags.range_check1 = true,
-                GateType::ForeignFieldAdd => feature_flags.foreign_field_add = true,
-                GateType::ForeignFieldMul => feature_flags.foreign_field_mul = true,
-                GateType::Xor16 => feature_flags.xor = true,
-                GateType::Rot64 => feature_flags.rot = true,
-                _ => (),
-            }
+            feature_flags.enable_gate(gate.typ);
         }
 
         feature_flags
     }
 
+    /// The flag of the optional gate `typ`, if it is one of [`OPTIONAL_GATES`]
+    fn gate_flag(&mut self, typ: GateType) -> Option<&mut bool> {
+        match typ {
+            GateType::RangeCheck0 => Some(&mut self.range_check0),
+            GateType::RangeCheck1 => Some(&mut self.range_check1),
+            GateType::ForeignFieldAdd => Some(&mut self.foreign_field_add),
+            GateType::ForeignFieldMul => Some(&mut self.foreign_field_mul),
+            GateType::Xor16 => Some(&mut self.xor),
+            GateType::Rot64 => Some(&mut self.rot),
+            _ => None,
+        }
+    }
+
+    /// Whether the circuit uses the gate `typ`. The gates which are not
+    /// optional are always used.
+    pub fn uses_gate(&self, typ: GateType) -> bool {
+        let mut flags = *self;
+        flags.gate_flag(typ).map_or(true, |flag| *flag)
+    }
+
+    /// Enables the gate `typ`, if it is optional
+    pub fn enable_gate(&mut self, typ: GateType) {
+        if let Some(flag) = self.gate_flag(typ) {
+            *flag = true;
+        }
+    }
+
     pub fn from_gates<F: PrimeField>(
         gates: &[CircuitGate<F>],
         uses_runtime_tables: bool,
@@ -722,6 +989,19 @@ impl<F: PrimeField> Builder<F> {
         self
     }
 
+    /// Set up the public input as named sections, e.g. the statement of the
+    /// circuit and the bookkeeping of the recursion, the number of public
+    /// inputs being the sum of their sizes. The public input is the
+    /// concatenation of the sections in the given order (see
+    /// [`concat_public_sections`]), so that the gates of the public inputs of
+    /// the first section come first.
+    /// If not invoked, the public input has no sections.
+    pub fn public_sections(mut self, sections: Vec<PublicSection>) -> Self {
+        self.public = sections.iter().map(|section| section.size).sum();
+        self.public_sections = sections;
+        self
+    }
+
     /// Set up the number of previous challenges, used for recusive proving.
     /// If not invoked, it equals `0` by default.
     pub fn prev_challenges(mut self, prev_challenges: usize) -> Self {
@@ -770,11 +1050,26 @@ impl<F: PrimeField> Builder<F> {
         self
     }
 
+    /// Set up the size of the SRS the circuit is used with, if it is smaller
+    /// than the domain. The polynomials of the circuit are then committed to
+    /// in chunks of this size, which avoids generating a bigger SRS to grow
+    /// the circuit, and the number of zero-knowledge rows is increased to
+    /// account for the chunks (see [`zk_rows_strict_lower_bound`]).
+    /// The SRS given to [`ProverIndex::create`](crate::prover_index::ProverIndex::create)
+    /// must then have this size.
     pub fn max_poly_size(mut self, max_poly_size: Option<usize>) -> Self {
         self.max_poly_size = max_poly_size;
         self
     }
 
+    /// Set up the minimal number of zero-knowledge rows.
+    /// If not invoked, or if it is smaller than the number of rows required
+    /// to achieve zero-knowledge, the latter is used.
+    pub fn zk_rows(mut self, zk_rows: Option<u64>) -> Self {
+        self.zk_rows = zk_rows;
+        self
+    }
+
     /// Build the [ConstraintSystem] from a [Builder].
     pub fn build(self) -> Result<ConstraintSystem<F>, SetupError> {
         let mut gates = self.gates;
@@ -787,54 +1082,29 @@ impl<F: PrimeField> Builder<F> {
 
         let feature_flags = FeatureFlags::from_gates(&gates, runtime_tables.is_some());
 
-        let lookup_domain_size = {
-            // First we sum over the lookup table size
-            let mut has_table_with_id_0 = false;
-            let mut lookup_domain_size: usize = lookup_tables
-                .iter()
-                .map(|LookupTable { id, data }| {
-                    // See below for the reason
-                    if *id == 0_i32 {
-                        has_table_with_id_0 = true
-                    }
-                    if data.is_empty() {
-                        0
-                    } else {
-                        data[0].len()
-                    }
-                })
-                .sum();
-            // After that on the runtime tables
-            if let Some(runtime_tables) = runtime_tables.as_ref() {
-                // FIXME: Check that a runtime table with ID 0 is enforced to
-                // contain a zero entry row.
-                for runtime_table in runtime_tables.iter() {
-                    lookup_domain_size += runtime_table.len();
-                }
+        //~ 1. If the public input has sections, abort if their names are not unique or
+        //~    if their sizes do not sum to the number of public inputs.
+        if !self.public_sections.is_empty() {
+            let size: usize = self.public_sections.iter().map(|s| s.size).sum();
+            if size != self.public {
+                return Err(SetupError::PublicSectionsSize(size, self.public));
             }
-            // And we add the built-in tables, depending on the features.
-            let LookupFeatures { patterns, .. } = &feature_flags.lookup_features;
-            let mut gate_lookup_tables = GateLookupTables {
-                xor: false,
-                range_check: false,
-            };
-            for pattern in patterns.into_iter() {
-                if let Some(gate_table) = pattern.table() {
-                    gate_lookup_tables[gate_table] = true
+            for (i, section) in self.public_sections.iter().enumerate() {
+                if self.public_sections[..i]
+                    .iter()
+                    .any(|other| other.name == section.name)
+                {
+                    return Err(SetupError::DuplicatePublicSection(section.name.clone()));
                 }
             }
-            for gate_table in gate_lookup_tables.into_iter() {
-                lookup_domain_size += gate_table.table_size();
-            }
+        }
 
-            // A dummy zero entry will be added if there is no table with ID
-            // zero. Therefore we must count this in the size.
-            if has_table_with_id_0 {
-                lookup_domain_size
-            } else {
-                lookup_domain_size + 1
-            }
-        };
+        //~ 1. Compute the number of rows needed by the circuit, i.e. the maximum of
+        //~    the number of gates and of the number of entries of the lookup tables
+        //~    (plus one row to close the permutation argument).
+        let runtime_tables_cfg = runtime_tables.as_deref().unwrap_or(&[]);
+        let circuit_size =
+            EvaluationDomains::<F>::circuit_size(&gates, &lookup_tables, runtime_tables_cfg);
 
         //~ 1. Compute the number of zero-knowledge rows (`zk_rows`) that will be required to
         //~    achieve zero-knowledge. The following constraints apply to `zk_rows`:
@@ -859,44 +1129,47 @@ impl<F: PrimeField> Builder<F> {
         //~    domain_size = circuit_size + zk_rows
         //~    ```
         //~
-        let (zk_rows, domain_size_lower_bound) = {
-            // We add 1 to the lookup domain size because there is one element
-            // used to close the permutation argument (the polynomial Z is of
-            // degree n + 1 where n is the order of the subgroup H).
-            let circuit_lower_bound = std::cmp::max(gates.len(), lookup_domain_size + 1);
-            let get_domain_size_lower_bound = |zk_rows: u64| circuit_lower_bound + zk_rows as usize;
-
-            let mut zk_rows = 3;
+        let zk_rows = {
+            let get_domain_size_lower_bound = |zk_rows: u64| circuit_size + zk_rows as usize;
+
+            let min_zk_rows = self.zk_rows.unwrap_or(0);
+            let mut zk_rows = std::cmp::max(3, min_zk_rows);
             let mut domain_size_lower_bound = get_domain_size_lower_bound(zk_rows);
             if let Some(max_poly_size) = self.max_poly_size {
                 // Iterate to find a fixed-point where zk_rows is sufficient for the number of
                 // chunks that we use, and also does not cause us to overflow the domain size.
                 // NB: We use iteration here rather than hard-coding an assumption about
-                // `compute_size_of_domain`s internals. In practice, this will never be executed
+                // `size_for`s internals. In practice, this will never be executed
                 // more than once.
                 while {
-                    let domain_size = D::<F>::compute_size_of_domain(domain_size_lower_bound)
-                        .ok_or(SetupError::DomainCreation(
-                            DomainCreationError::DomainSizeFailed(domain_size_lower_bound),
-                        ))?;
+                    let domain_size = EvaluationDomains::<F>::size_for(domain_size_lower_bound)
+                        .map_err(SetupError::DomainCreation)?;
                     let num_chunks = if domain_size < max_poly_size {
                         1
                     } else {
                         domain_size / max_poly_size
                     };
-                    zk_rows = (zk_rows_strict_lower_bound(num_chunks) + 1) as u64;
+                    zk_rows = std::cmp::max(
+                        (zk_rows_strict_lower_bound(num_chunks) + 1) as u64,
+                        min_zk_rows,
+                    );
                     domain_size_lower_bound = get_domain_size_lower_bound(zk_rows);
                     domain_size < domain_size_lower_bound
                 } {}
             }
-            (zk_rows, domain_size_lower_bound)
+            zk_rows
         };
 
         //~ 1. Create a domain for the circuit. That is,
         //~    compute the smallest subgroup of the field that
         //~    has order greater or equal to `n + zk_rows` elements.
-        let domain = EvaluationDomains::<F>::create(domain_size_lower_bound)
-            .map_err(SetupError::DomainCreation)?;
+        let domain = EvaluationDomains::<F>::for_circuit(
+            &gates,
+            &lookup_tables,
+            runtime_tables_cfg,
+            zk_rows,
+        )
+        .map_err(SetupError::DomainCreation)?;
 
         assert!(domain.d1.size > zk_rows);
 
@@ -937,6 +1210,7 @@ impl<F: PrimeField> Builder<F> {
         let constraints = ConstraintSystem {
             domain,
             public: self.public,
+            public_sections: self.public_sections,
             prev_challenges: self.prev_challenges,
             sid,
             gates,
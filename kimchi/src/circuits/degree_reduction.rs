@@ -0,0 +1,500 @@
+//! Automatic reduction of the degree of constraints.
+//!
+//! A gadget can be described with natural expressions, whose degree in the
+//! witness cells may exceed the degree supported by the prover. The function
+//! [reduce_degree] rewrites a list of constraints so that each of them has a
+//! degree at most `max_degree`: the subexpressions making a constraint exceed
+//! the bound are moved into auxiliary witness columns, and a linking
+//! constraint `aux - subexpression` is added for each auxiliary column.
+//!
+//! The values of the auxiliary columns are computed row by row with
+//! [DegreeReduction::fill_row], or with the code returned by
+//! [DegreeReduction::witness_fill_code]. If an expression reads an auxiliary
+//! column on the next row, the rows must be filled from the last one, so that
+//! the auxiliary columns of the next row are known.
+//!
+//! Here, the degree of an expression is its degree as a polynomial in the
+//! cells. The atoms which are not cells (e.g. the Lagrange basis polynomials)
+//! are considered as degree one.
+
+use crate::{
+    circuits::{
+        expr::{
+            AlphaChallengeTerm, CacheId, ColumnEvaluations, ConstantExpr, Constants, Expr,
+            ExprError, ExprInner, FormattedOutput, Operations, Variable,
+        },
+        gate::CurrOrNext,
+    },
+    proof::PointEvaluations,
+};
+use ark_ff::{FftField, Field, PrimeField};
+use ark_poly::Radix2EvaluationDomain as D;
+use std::{collections::HashMap, fmt::Debug, ops::Index};
+
+type E<F, Column, ChallengeTerm> = Expr<ConstantExpr<F, ChallengeTerm>, Column>;
+
+/// The result of [reduce_degree]
+#[derive(Clone, Debug)]
+pub struct DegreeReduction<F, Column, ChallengeTerm> {
+    /// The degree bound the constraints have been reduced to.
+    pub max_degree: u64,
+    /// The original constraints, rewritten using the auxiliary columns.
+    pub constraints: Vec<E<F, Column, ChallengeTerm>>,
+    /// The auxiliary columns, with the expression defining each of them. An
+    /// expression can only use the auxiliary columns defined before it.
+    pub aux_columns: Vec<(Column, E<F, Column, ChallengeTerm>)>,
+}
+
+/// Returns the degree of the expression in the cells.
+pub fn cell_degree<F, Column, ChallengeTerm>(e: &E<F, Column, ChallengeTerm>) -> u64 {
+    use ExprInner::*;
+    use Operations::*;
+    match e {
+        Atom(Constant(_)) => 0,
+        Atom(Cell(_))
+        | Atom(VanishesOnZeroKnowledgeAndPreviousRows)
+        | Atom(UnnormalizedLagrangeBasis(_)) => 1,
+        Double(x) | Cache(_, x) => cell_degree(x),
+        Square(x) => 2 * cell_degree(x),
+        Pow(x, k) => cell_degree(x) * k,
+        Mul(x, y) => cell_degree(x) + cell_degree(y),
+        Add(x, y) | Sub(x, y) | IfFeature(_, x, y) => std::cmp::max(cell_degree(x), cell_degree(y)),
+    }
+}
+
+/// Returns whether `e` reads the column `col` on the next row.
+fn reads_next<F, Column: PartialEq, ChallengeTerm>(
+    e: &E<F, Column, ChallengeTerm>,
+    col: &Column,
+) -> bool {
+    use ExprInner::*;
+    use Operations::*;
+    match e {
+        Atom(Cell(v)) => v.col == *col && v.row == CurrOrNext::Next,
+        Atom(_) => false,
+        Double(x) | Square(x) | Pow(x, _) | Cache(_, x) => reads_next(x, col),
+        Mul(x, y) | Add(x, y) | Sub(x, y) | IfFeature(_, x, y) => {
+            reads_next(x, col) || reads_next(y, col)
+        }
+    }
+}
+
+/// Rewrite the `constraints` so that each one has a degree at most
+/// `max_degree`, introducing auxiliary columns when required. The `i`-th
+/// auxiliary column is given by `new_column(i)`. Identical subexpressions share
+/// the same auxiliary column.
+///
+/// # Panics
+///
+/// Will panic if `max_degree` is smaller than 2.
+pub fn reduce_degree<F, Column, ChallengeTerm>(
+    constraints: &[E<F, Column, ChallengeTerm>],
+    max_degree: u64,
+    new_column: impl FnMut(usize) -> Column,
+) -> DegreeReduction<F, Column, ChallengeTerm>
+where
+    F: Clone + PartialEq,
+    Column: Copy + PartialEq,
+    ChallengeTerm: Clone + PartialEq,
+{
+    assert!(
+        max_degree >= 2,
+        "The degree of the constraints cannot be reduced below 2, got {max_degree}"
+    );
+    let mut reducer = Reducer {
+        max_degree,
+        new_column,
+        aux_columns: vec![],
+    };
+    let constraints = constraints.iter().map(|c| reducer.reduce(c)).collect();
+    DegreeReduction {
+        max_degree,
+        constraints,
+        aux_columns: reducer.aux_columns,
+    }
+}
+
+struct Reducer<F, Column, ChallengeTerm, N> {
+    max_degree: u64,
+    new_column: N,
+    aux_columns: Vec<(Column, E<F, Column, ChallengeTerm>)>,
+}
+
+impl<F, Column, ChallengeTerm, N> Reducer<F, Column, ChallengeTerm, N>
+where
+    F: Clone + PartialEq,
+    Column: Copy + PartialEq,
+    ChallengeTerm: Clone + PartialEq,
+    N: FnMut(usize) -> Column,
+{
+    /// Move `e` into an auxiliary column, or reuse the column of an identical
+    /// expression, and return the cell of the column.
+    fn extract(&mut self, e: E<F, Column, ChallengeTerm>) -> E<F, Column, ChallengeTerm> {
+        let col = match self.aux_columns.iter().find(|(_, aux)| *aux == e) {
+            Some((col, _)) => *col,
+            None => {
+                let col = (self.new_column)(self.aux_columns.len());
+                self.aux_columns.push((col, e));
+                col
+            }
+        };
+        Expr::Atom(ExprInner::Cell(Variable {
+            col,
+            row: CurrOrNext::Curr,
+        }))
+    }
+
+    /// Extract `e` if its degree is larger than one.
+    fn extract_if_needed(&mut self, e: E<F, Column, ChallengeTerm>) -> E<F, Column, ChallengeTerm> {
+        if cell_degree(&e) > 1 {
+            self.extract(e)
+        } else {
+            e
+        }
+    }
+
+    fn reduce_mul(
+        &mut self,
+        mut x: E<F, Column, ChallengeTerm>,
+        mut y: E<F, Column, ChallengeTerm>,
+    ) -> E<F, Column, ChallengeTerm> {
+        // As max_degree >= 2, this loop runs at most twice
+        while cell_degree(&x) + cell_degree(&y) > self.max_degree {
+            if cell_degree(&x) >= cell_degree(&y) {
+                x = self.extract(x);
+            } else {
+                y = self.extract(y);
+            }
+        }
+        Expr::Mul(Box::new(x), Box::new(y))
+    }
+
+    fn reduce(&mut self, e: &E<F, Column, ChallengeTerm>) -> E<F, Column, ChallengeTerm> {
+        use Operations::*;
+        if cell_degree(e) <= self.max_degree {
+            return e.clone();
+        }
+        match e {
+            Atom(_) => e.clone(),
+            Double(x) => Double(Box::new(self.reduce(x))),
+            Cache(id, x) => Cache(*id, Box::new(self.reduce(x))),
+            Add(x, y) => Add(Box::new(self.reduce(x)), Box::new(self.reduce(y))),
+            Sub(x, y) => Sub(Box::new(self.reduce(x)), Box::new(self.reduce(y))),
+            IfFeature(feature, x, y) => {
+                IfFeature(*feature, Box::new(self.reduce(x)), Box::new(self.reduce(y)))
+            }
+            Mul(x, y) => {
+                let x = self.reduce(x);
+                let y = self.reduce(y);
+                self.reduce_mul(x, y)
+            }
+            Square(x) => {
+                let mut x = self.reduce(x);
+                if 2 * cell_degree(&x) > self.max_degree {
+                    x = self.extract(x);
+                }
+                Square(Box::new(x))
+            }
+            Pow(x, k) => {
+                let x = self.reduce(x);
+                if cell_degree(&x) * k <= self.max_degree {
+                    return Pow(Box::new(x), *k);
+                }
+                let x = self.extract_if_needed(x);
+                if *k <= self.max_degree {
+                    return Pow(Box::new(x), *k);
+                }
+                // x^k = x^max_degree * x^(k - max_degree)
+                let low = Pow(Box::new(x.clone()), self.max_degree);
+                let high = self.reduce(&Pow(Box::new(x), k - self.max_degree));
+                self.reduce_mul(low, high)
+            }
+        }
+    }
+}
+
+impl<F, Column, ChallengeTerm> DegreeReduction<F, Column, ChallengeTerm>
+where
+    F: Clone,
+    Column: Copy,
+    ChallengeTerm: Clone,
+{
+    /// The constraints binding each auxiliary column to its expression.
+    pub fn linking_constraints(&self) -> Vec<E<F, Column, ChallengeTerm>> {
+        self.aux_columns
+            .iter()
+            .map(|(col, e)| {
+                let cell = Expr::Atom(ExprInner::Cell(Variable {
+                    col: *col,
+                    row: CurrOrNext::Curr,
+                }));
+                Expr::Sub(Box::new(cell), Box::new(e.clone()))
+            })
+            .collect()
+    }
+
+    /// The rewritten constraints followed by the linking constraints.
+    pub fn all_constraints(&self) -> Vec<E<F, Column, ChallengeTerm>> {
+        let mut res = self.constraints.clone();
+        res.extend(self.linking_constraints());
+        res
+    }
+}
+
+/// The evaluations of the original columns, extended with the auxiliary
+/// columns already computed on the current row and with the auxiliary columns
+/// of the next row.
+struct WithAux<'a, F, Column, Evaluations> {
+    evals: &'a Evaluations,
+    aux: Vec<(Column, F)>,
+    next_aux: &'a [(Column, F)],
+}
+
+impl<F, Column, Evaluations> ColumnEvaluations<F> for WithAux<'_, F, Column, Evaluations>
+where
+    F: Field,
+    Column: Copy + PartialEq,
+    Evaluations: ColumnEvaluations<F, Column = Column>,
+{
+    type Column = Column;
+    fn evaluate(&self, col: Column) -> Result<PointEvaluations<F>, ExprError<Column>> {
+        match self.aux.iter().find(|(c, _)| *c == col) {
+            Some((_, v)) => Ok(PointEvaluations {
+                zeta: *v,
+                // [DegreeReduction::fill_row] checks that the value on the
+                // next row is given when an expression reads it, so the
+                // default value is never read
+                zeta_omega: self
+                    .next_aux
+                    .iter()
+                    .find(|(c, _)| *c == col)
+                    .map_or(F::zero(), |(_, v)| *v),
+            }),
+            None => self.evals.evaluate(col),
+        }
+    }
+}
+
+impl<F, Column, ChallengeTerm> DegreeReduction<F, Column, ChallengeTerm>
+where
+    F: FftField,
+    Column: Copy + PartialEq,
+    ChallengeTerm: Copy,
+{
+    /// Compute the values of the auxiliary columns on the row `pt` of the
+    /// domain `d`, given the evaluations of the original columns on this row
+    /// (`zeta`) and on the next one (`zeta_omega`), and the auxiliary columns
+    /// of the next row as returned by this function (`next_aux`, which can be
+    /// empty if no expression reads an auxiliary column on the next row).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if an expression cannot be evaluated, or if it reads an
+    /// auxiliary column on the next row which is not in `next_aux`.
+    pub fn fill_row<Evaluations: ColumnEvaluations<F, Column = Column>>(
+        &self,
+        d: D<F>,
+        pt: F,
+        evals: &Evaluations,
+        next_aux: &[(Column, F)],
+        c: &Constants<F>,
+        chals: &dyn Index<ChallengeTerm, Output = F>,
+    ) -> Result<Vec<(Column, F)>, ExprError<Column>> {
+        for (col, _) in self.aux_columns.iter() {
+            let given = next_aux.iter().any(|(c, _)| c == col);
+            if !given && self.aux_columns.iter().any(|(_, e)| reads_next(e, col)) {
+                return Err(ExprError::MissingEvaluation(*col, CurrOrNext::Next));
+            }
+        }
+        let mut env = WithAux {
+            evals,
+            aux: Vec::with_capacity(self.aux_columns.len()),
+            next_aux,
+        };
+        for (col, e) in self.aux_columns.iter() {
+            let v = e.evaluate_(d, pt, &env, c, chals)?;
+            env.aux.push((*col, v));
+        }
+        Ok(env.aux)
+    }
+}
+
+impl<'a, F, Column, ChallengeTerm> DegreeReduction<F, Column, ChallengeTerm>
+where
+    F: PrimeField,
+    Column: FormattedOutput + Debug + Clone,
+    ChallengeTerm: AlphaChallengeTerm<'a>,
+{
+    /// Returns the code computing the auxiliary columns, one assignment per
+    /// line, in the order in which they must be computed.
+    pub fn witness_fill_code(&self) -> Vec<String> {
+        let mut res = vec![];
+        for (col, e) in self.aux_columns.iter() {
+            let mut cache: HashMap<CacheId, E<F, Column, ChallengeTerm>> = HashMap::new();
+            let rhs = e.text(&mut cache);
+            let mut cache: Vec<_> = cache.into_iter().collect();
+            cache.sort_by(|(x, _), (y, _)| x.cmp(y));
+            for (id, cached) in cache {
+                res.push(format!(
+                    "let {} = {};",
+                    id.var_name(),
+                    cached.text(&mut HashMap::new())
+                ));
+            }
+            res.push(format!("{} = {};", col.text(&mut HashMap::new()), rhs));
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::berkeley_columns::{BerkeleyChallenges, Column, E as BerkeleyE};
+    use ark_ff::{One, Zero};
+    use ark_poly::EvaluationDomain;
+    use mina_curves::pasta::Fp;
+
+    /// The values of the columns on the current row and on the next one
+    struct Row {
+        curr: HashMap<Column, Fp>,
+        next: HashMap<Column, Fp>,
+    }
+
+    impl ColumnEvaluations<Fp> for Row {
+        type Column = Column;
+        fn evaluate(&self, col: Column) -> Result<PointEvaluations<Fp>, ExprError<Column>> {
+            let value = |row: &HashMap<Column, Fp>, curr_or_next| {
+                row.get(&col)
+                    .copied()
+                    .ok_or(ExprError::MissingEvaluation(col, curr_or_next))
+            };
+            Ok(PointEvaluations {
+                zeta: value(&self.curr, CurrOrNext::Curr)?,
+                zeta_omega: value(&self.next, CurrOrNext::Next)?,
+            })
+        }
+    }
+
+    fn witness(values: &[u64]) -> HashMap<Column, Fp> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Column::Witness(i), Fp::from(*v)))
+            .collect()
+    }
+
+    fn w(i: usize) -> BerkeleyE<Fp> {
+        BerkeleyE::cell(Column::Witness(i), CurrOrNext::Curr)
+    }
+
+    fn w_next(i: usize) -> BerkeleyE<Fp> {
+        BerkeleyE::cell(Column::Witness(i), CurrOrNext::Next)
+    }
+
+    fn constants() -> Constants<Fp> {
+        Constants {
+            endo_coefficient: Fp::zero(),
+            mds: &mina_poseidon::pasta::fp_kimchi::static_params().mds,
+            zk_rows: 0,
+        }
+    }
+
+    fn challenges() -> BerkeleyChallenges<Fp> {
+        BerkeleyChallenges {
+            alpha: Fp::zero(),
+            beta: Fp::zero(),
+            gamma: Fp::zero(),
+            joint_combiner: Fp::zero(),
+        }
+    }
+
+    #[test]
+    fn test_reduce_degree() {
+        // w0^5 - w1 * w2 * w3 * w4 = 0, of degree 5
+        let constraint = w(0).pow(5) - w(1) * w(2) * w(3) * w(4);
+        let reduction = reduce_degree(&[constraint], 2, |i| Column::Witness(10 + i));
+        for c in reduction.all_constraints() {
+            assert!(cell_degree(&c) <= 2);
+        }
+        assert!(!reduction.aux_columns.is_empty());
+        assert_eq!(
+            reduction.witness_fill_code().len(),
+            reduction.aux_columns.len()
+        );
+
+        let d = D::<Fp>::new(4).unwrap();
+        let constants = constants();
+        let challenges = challenges();
+        // 2^5 = 32 = 1 * 2 * 4 * 4, on both rows
+        let values = witness(&[2, 1, 2, 4, 4]);
+        let mut row = Row {
+            curr: values.clone(),
+            next: values,
+        };
+        let aux = reduction
+            .fill_row(d, Fp::one(), &row, &[], &constants, &challenges)
+            .unwrap();
+        row.curr.extend(aux.iter().copied());
+        row.next.extend(aux);
+        for c in reduction.all_constraints() {
+            let res = c
+                .evaluate_(d, Fp::one(), &row, &constants, &challenges)
+                .unwrap();
+            assert_eq!(res, Fp::zero());
+        }
+    }
+
+    #[test]
+    fn test_reduce_degree_next_row() {
+        // w0^3 - w1 = 0 and w10(next) * w0 * w0 - w2 = 0, where w10 = w0^2 is
+        // the first auxiliary column
+        let constraints = [w(0).pow(3) - w(1), w_next(10) * w(0) * w(0) - w(2)];
+        let reduction = reduce_degree(&constraints, 2, |i| Column::Witness(10 + i));
+        assert_eq!(reduction.aux_columns.len(), 2);
+        assert_eq!(reduction.aux_columns[0].1, w(0).pow(2));
+        for c in reduction.all_constraints() {
+            assert!(cell_degree(&c) <= 2);
+        }
+
+        let d = D::<Fp>::new(4).unwrap();
+        let constants = constants();
+        let challenges = challenges();
+        // w0 = 2 on the current row and 3 on the next one, so that the
+        // auxiliary columns are 4 and 9 * 2 = 18 on the current row
+        let mut row = Row {
+            curr: witness(&[2, 8, 36]),
+            next: witness(&[3, 27, 0]),
+        };
+
+        // the auxiliary columns of the next row must be given
+        assert!(matches!(
+            reduction.fill_row(d, Fp::one(), &row, &[], &constants, &challenges),
+            Err(ExprError::MissingEvaluation(
+                Column::Witness(10),
+                CurrOrNext::Next
+            ))
+        ));
+
+        let next_aux = [(Column::Witness(10), Fp::from(9u64))];
+        let aux = reduction
+            .fill_row(d, Fp::one(), &row, &next_aux, &constants, &challenges)
+            .unwrap();
+        assert_eq!(
+            aux,
+            [
+                (Column::Witness(10), Fp::from(4u64)),
+                (Column::Witness(11), Fp::from(18u64))
+            ]
+        );
+        row.curr.extend(aux);
+        row.next.extend(next_aux);
+        row.next.insert(Column::Witness(11), Fp::zero());
+        for c in reduction.all_constraints() {
+            let res = c
+                .evaluate_(d, Fp::one(), &row, &constants, &challenges)
+                .unwrap();
+            assert_eq!(res, Fp::zero());
+        }
+    }
+}
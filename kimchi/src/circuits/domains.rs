@@ -1,12 +1,22 @@
 //! This module describes the evaluation domains that can be used by the
 //! polynomials.
 
-use ark_ff::FftField;
+use ark_ff::{FftField, PrimeField};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
-use crate::error::DomainCreationError;
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        lookup::{
+            lookups::LookupPatterns,
+            runtime_tables::RuntimeTableCfg,
+            tables::{GateLookupTables, LookupTable},
+        },
+    },
+    error::DomainCreationError,
+};
 
 /// The different multiplicaive domain sizes that can be used by the polynomials.
 /// We do support up to 8 times the size of the original domain for now.
@@ -36,9 +46,13 @@ impl<F: FftField> EvaluationDomains<F> {
     /// `d4` (of size `4n`), and `d8` (of size `8n`). If generator of `d8` is
     /// `g`, the generator of `d4` is `g^2`, the generator of `d2` is `g^4`, and
     /// the generator of `d1` is `g^8`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `8n` is larger than the largest subgroup of the field,
+    /// see [`EvaluationDomains::max_size`].
     pub fn create(n: usize) -> Result<Self, DomainCreationError> {
-        let n = Radix2EvaluationDomain::<F>::compute_size_of_domain(n)
-            .ok_or(DomainCreationError::DomainSizeFailed(n))?;
+        let n = Self::size_for(n)?;
 
         let d1 = Radix2EvaluationDomain::<F>::new(n).ok_or(
             DomainCreationError::DomainConstructionFailed("d1".to_string(), n),
@@ -66,4 +80,104 @@ impl<F: FftField> EvaluationDomains<F> {
 
         Ok(EvaluationDomains { d1, d2, d4, d8 })
     }
+
+    /// The size of the largest domain `d1` supported by the field, i.e.
+    /// `2^(two-adicity - 3)` as `d8` must be a subgroup of the field.
+    pub fn max_size() -> usize {
+        1usize
+            .checked_shl(F::TWO_ADICITY.saturating_sub(3))
+            .unwrap_or(1 << (usize::BITS - 1))
+    }
+
+    /// Computes the size of the smallest domain `d1` with at least `n`
+    /// elements, i.e. the power of two following `n`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the domain is larger than [`EvaluationDomains::max_size`].
+    pub fn size_for(n: usize) -> Result<usize, DomainCreationError> {
+        let max_size = Self::max_size();
+        n.checked_next_power_of_two()
+            .filter(|size| *size <= max_size)
+            .ok_or(DomainCreationError::DomainTooLarge(n, max_size))
+    }
+}
+
+impl<F: PrimeField> EvaluationDomains<F> {
+    /// Computes the number of rows needed by the circuit `gates` with the
+    /// lookup tables `lookup_tables` and `runtime_tables`, without the
+    /// zero-knowledge rows.
+    ///
+    /// The rows must hold the gates, and the entries of the lookup tables,
+    /// including the tables used by the gates and a dummy zero entry if no table
+    /// has the ID 0, plus one row to close the permutation argument (the
+    /// polynomial `Z` is of degree `n + 1` where `n` is the order of the
+    /// subgroup).
+    pub fn circuit_size(
+        gates: &[CircuitGate<F>],
+        lookup_tables: &[LookupTable<F>],
+        runtime_tables: &[RuntimeTableCfg<F>],
+    ) -> usize {
+        // First we sum over the lookup table size
+        let mut has_table_with_id_0 = false;
+        let mut lookup_domain_size: usize = lookup_tables
+            .iter()
+            .map(|LookupTable { id, data }| {
+                // See below for the reason
+                if *id == 0_i32 {
+                    has_table_with_id_0 = true
+                }
+                if data.is_empty() {
+                    0
+                } else {
+                    data[0].len()
+                }
+            })
+            .sum();
+        // After that on the runtime tables
+        // FIXME: Check that a runtime table with ID 0 is enforced to
+        // contain a zero entry row.
+        for runtime_table in runtime_tables.iter() {
+            lookup_domain_size += runtime_table.len();
+        }
+        // And we add the built-in tables, depending on the patterns used by
+        // the gates.
+        let mut gate_lookup_tables = GateLookupTables {
+            xor: false,
+            range_check: false,
+        };
+        for pattern in LookupPatterns::from_gates(gates).into_iter() {
+            if let Some(gate_table) = pattern.table() {
+                gate_lookup_tables[gate_table] = true
+            }
+        }
+        for gate_table in gate_lookup_tables.into_iter() {
+            lookup_domain_size += gate_table.table_size();
+        }
+        // A dummy zero entry will be added if there is no table with ID
+        // zero. Therefore we must count this in the size.
+        if !has_table_with_id_0 {
+            lookup_domain_size += 1;
+        }
+
+        std::cmp::max(gates.len(), lookup_domain_size + 1)
+    }
+
+    /// Creates the smallest evaluation domains for the circuit `gates` with
+    /// the lookup tables `lookup_tables` and `runtime_tables`, and `zk_rows`
+    /// zero-knowledge rows, see [`EvaluationDomains::circuit_size`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the circuit does not fit in the largest domain of the
+    /// field, see [`EvaluationDomains::max_size`].
+    pub fn for_circuit(
+        gates: &[CircuitGate<F>],
+        lookup_tables: &[LookupTable<F>],
+        runtime_tables: &[RuntimeTableCfg<F>],
+        zk_rows: u64,
+    ) -> Result<Self, DomainCreationError> {
+        let circuit_size = Self::circuit_size(gates, lookup_tables, runtime_tables);
+        Self::create(circuit_size + zk_rows as usize)
+    }
 }
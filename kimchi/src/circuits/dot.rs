@@ -0,0 +1,59 @@
+//! This module implements an export of the layout of a circuit to the DOT
+//! language of [graphviz](https://graphviz.org), to audit the wiring of the
+//! gadgets visually.
+//!
+//! Each row is a node labelled with its index and its gate type, whose ports
+//! are the wired columns of the row. Each wire that does not point to its own
+//! cell is an edge from the cell to the next cell of its permutation cycle,
+//! e.g. a call to [`Connect::connect_cell_pair`] adds the two edges of a
+//! cycle of two cells.
+//!
+//! The padding of the circuit up to the size of the domain is left out, see
+//! [`ConstraintSystem::unpadded_rows`].
+//!
+//! The graph can be rendered with `dot -Tsvg circuit.dot -o circuit.svg`.
+//!
+//! [`Connect::connect_cell_pair`]: crate::circuits::gate::Connect::connect_cell_pair
+
+use crate::circuits::{constraints::ConstraintSystem, wires::PERMUTS};
+use ark_ff::PrimeField;
+use std::fmt::Write;
+
+impl<F: PrimeField> ConstraintSystem<F> {
+    /// Returns the layout of the gates and of their wiring in the DOT language
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        // writing to a string cannot fail
+        self.write_dot(&mut dot).unwrap();
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String) -> std::fmt::Result {
+        writeln!(dot, "digraph circuit {{")?;
+        writeln!(dot, "  rankdir=TB;")?;
+        writeln!(dot, "  node [shape=record, fontname=monospace];")?;
+
+        let circuit = &self.gates[..self.unpadded_rows()];
+
+        for (row, gate) in circuit.iter().enumerate() {
+            let public = if row < self.public { " (public)" } else { "" };
+            let cols: Vec<_> = (0..PERMUTS).map(|col| format!("<c{col}>{col}")).collect();
+            writeln!(
+                dot,
+                "  r{row} [label=\"{{{row}: {:?}{public}|{{{}}}}}\"];",
+                gate.typ,
+                cols.join("|")
+            )?;
+        }
+
+        for (row, gate) in circuit.iter().enumerate() {
+            for (col, wire) in gate.wires.iter().enumerate() {
+                if wire.row != row || wire.col != col {
+                    writeln!(dot, "  r{row}:c{col} -> r{}:c{};", wire.row, wire.col)?;
+                }
+            }
+        }
+
+        writeln!(dot, "}}")
+    }
+}
@@ -16,8 +16,10 @@ use ark_ff::{FftField, Field, One, PrimeField, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
 };
+use ark_std::{cfg_into_iter, cfg_iter_mut};
 use itertools::Itertools;
 use o1_utils::{foreign_field::ForeignFieldHelpers, FieldHelpers};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -478,6 +480,152 @@ where
     }
 }
 
+impl<T: Literal + PartialEq> Operations<T>
+where
+    T::F: Field,
+{
+    /// Simplifies the expression: the operations on literals are folded, the
+    /// additions of zero and the multiplications by zero or one are removed,
+    /// and the nested sums and products are flattened, so that their literal
+    /// terms and factors are combined. As a negation is a multiplication by
+    /// `-1`, nested negations cancel out.
+    ///
+    /// The cached expressions and the feature flags are kept, only their
+    /// content is simplified.
+    pub fn simplify(&self) -> Self {
+        use Operations::*;
+        match self {
+            Atom(_) => self.clone(),
+            Add(_, _) | Sub(_, _) => self.simplify_sum(),
+            Mul(_, _) => self.simplify_product(),
+            Pow(x, 1) => x.simplify(),
+            Pow(x, n) => x.simplify().pow(*n),
+            Double(x) => match x.simplify().to_literal() {
+                Ok(x) => Self::literal(x.double()),
+                Err(x) => Double(Box::new(x)),
+            },
+            Square(x) => match x.simplify().to_literal() {
+                Ok(x) => Self::literal(x.square()),
+                Err(x) => Square(Box::new(x)),
+            },
+            Cache(id, x) => Cache(*id, Box::new(x.simplify())),
+            IfFeature(feature, x, y) => {
+                IfFeature(*feature, Box::new(x.simplify()), Box::new(y.simplify()))
+            }
+        }
+    }
+
+    /// Splits a simplified expression into its literal factor and the other
+    /// factors
+    fn split_literal_factor(self) -> (T::F, Self) {
+        match self {
+            Operations::Mul(x, y) => match x.to_literal_ref() {
+                Some(c) => (*c, *y),
+                None => (T::F::one(), Operations::Mul(x, y)),
+            },
+            x => (T::F::one(), x),
+        }
+    }
+
+    /// Returns the simplified expression `c * x`, `x` being simplified and
+    /// without literal factor
+    fn scale(c: T::F, x: Self) -> Self {
+        if c.is_zero() {
+            Self::zero()
+        } else if c.is_one() {
+            x
+        } else {
+            Operations::Mul(Box::new(Self::literal(c)), Box::new(x))
+        }
+    }
+
+    /// Collects the simplified terms of the sum `self`, with their sign
+    fn sum_terms(&self, negative: bool, terms: &mut Vec<(bool, Self)>) {
+        match self {
+            Operations::Add(x, y) => {
+                x.sum_terms(negative, terms);
+                y.sum_terms(negative, terms);
+            }
+            Operations::Sub(x, y) => {
+                x.sum_terms(negative, terms);
+                y.sum_terms(!negative, terms);
+            }
+            x => {
+                let (c, x) = x.simplify().split_literal_factor();
+                if c == -T::F::one() {
+                    terms.push((!negative, x))
+                } else {
+                    terms.push((negative, Self::scale(c, x)))
+                }
+            }
+        }
+    }
+
+    fn simplify_sum(&self) -> Self {
+        let mut terms = vec![];
+        self.sum_terms(false, &mut terms);
+        let mut constant = T::F::zero();
+        let mut sum: Option<Self> = None;
+        for (negative, term) in terms {
+            match term.to_literal() {
+                Ok(x) if negative => constant -= x,
+                Ok(x) => constant += x,
+                Err(term) => {
+                    sum = Some(match sum {
+                        None if negative => {
+                            let (c, term) = term.split_literal_factor();
+                            Self::scale(-c, term)
+                        }
+                        None => term,
+                        Some(sum) if negative => Operations::Sub(Box::new(sum), Box::new(term)),
+                        Some(sum) => Operations::Add(Box::new(sum), Box::new(term)),
+                    })
+                }
+            }
+        }
+        match sum {
+            None => Self::literal(constant),
+            Some(sum) if constant.is_zero() => sum,
+            Some(sum) => Operations::Add(Box::new(sum), Box::new(Self::literal(constant))),
+        }
+    }
+
+    /// Collects the simplified factors of the product `self`
+    fn product_factors(&self, factors: &mut Vec<Self>) {
+        match self {
+            Operations::Mul(x, y) => {
+                x.product_factors(factors);
+                y.product_factors(factors);
+            }
+            x => factors.push(x.simplify()),
+        }
+    }
+
+    fn simplify_product(&self) -> Self {
+        let mut factors = vec![];
+        self.product_factors(&mut factors);
+        let mut constant = T::F::one();
+        let mut product: Option<Self> = None;
+        for factor in factors {
+            let (c, factor) = factor.split_literal_factor();
+            constant *= c;
+            match factor.to_literal() {
+                Ok(x) => constant *= x,
+                Err(factor) => {
+                    product = Some(match product {
+                        None => factor,
+                        Some(product) => Operations::Mul(Box::new(product), Box::new(factor)),
+                    })
+                }
+            }
+        }
+        match product {
+            None => Self::literal(constant),
+            Some(product) => Self::scale(constant, product),
+        }
+    }
+}
+
 impl<F: Field, ChallengeTerm: Copy> ConstantExpr<F, ChallengeTerm> {
     /// Evaluate the given constant expression to a field element.
     pub fn value(&self, c: &Constants<F>, chals: &dyn Index<ChallengeTerm, Output = F>) -> F {
@@ -537,7 +685,7 @@ impl CacheId {
         })
     }
 
-    fn var_name(&self) -> String {
+    pub(crate) fn var_name(&self) -> String {
         format!("x_{}", self.0)
     }
 
@@ -1153,7 +1301,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
     ) -> Evaluations<F, D<F>> {
         let n = res_domain.1.size();
         Evaluations::<F, D<F>>::from_vec_and_domain(
-            (0..n).into_par_iter().map(g).collect(),
+            cfg_into_iter!(0..n).map(g).collect(),
             res_domain.1,
         )
     }
@@ -1173,7 +1321,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
             (Constant(x), Constant(y)) => Constant(x + y),
             (Evals { domain, mut evals }, Constant(x))
             | (Constant(x), Evals { domain, mut evals }) => {
-                evals.evals.par_iter_mut().for_each(|e| *e += x);
+                cfg_iter_mut!(evals.evals).for_each(|e| *e += x);
                 Evals { domain, evals }
             }
             (
@@ -1200,8 +1348,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
                 column_domain and the evaluation domain of the
                 witnesses are the same"
                 );
-                let v: Vec<_> = (0..n)
-                    .into_par_iter()
+                let v: Vec<_> = cfg_into_iter!(0..n)
                     .map(|i| {
                         x + evals.evals[(scale * i + (domain as usize) * shift) % evals.evals.len()]
                     })
@@ -1257,7 +1404,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
                 column_domain and the evaluation domain of the
                 witnesses are the same"
                 );
-                evals.evals.par_iter_mut().enumerate().for_each(|(i, e)| {
+                cfg_iter_mut!(evals.evals).enumerate().for_each(|(i, e)| {
                     *e += es_sub.evals[(scale * i + (d_sub as usize) * s) % es_sub.evals.len()];
                 });
                 Evals { evals, domain: d }
@@ -1289,8 +1436,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
                 witnesses are the same"
                 );
                 let n = res_domain.1.size();
-                let v: Vec<_> = (0..n)
-                    .into_par_iter()
+                let v: Vec<_> = cfg_into_iter!(0..n)
                     .map(|i| {
                         es1.evals[(scale1 * i + (d1 as usize) * s1) % es1.evals.len()]
                             + es2.evals[(scale2 * i + (d2 as usize) * s2) % es2.evals.len()]
@@ -1310,11 +1456,11 @@ impl<'a, F: FftField> EvalResult<'a, F> {
         match (self, other) {
             (Constant(x), Constant(y)) => Constant(x - y),
             (Evals { domain, mut evals }, Constant(x)) => {
-                evals.evals.par_iter_mut().for_each(|e| *e -= x);
+                cfg_iter_mut!(evals.evals).for_each(|e| *e -= x);
                 Evals { domain, evals }
             }
             (Constant(x), Evals { domain, mut evals }) => {
-                evals.evals.par_iter_mut().for_each(|e| *e = x - *e);
+                cfg_iter_mut!(evals.evals).for_each(|e| *e = x - *e);
                 Evals { domain, evals }
             }
             (
@@ -1392,7 +1538,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
                 witnesses are the same"
                 );
 
-                evals.evals.par_iter_mut().enumerate().for_each(|(i, e)| {
+                cfg_iter_mut!(evals.evals).enumerate().for_each(|(i, e)| {
                     *e = es_sub.evals[(scale * i + (d_sub as usize) * s) % es_sub.evals.len()] - *e;
                 });
                 Evals { evals, domain: d }
@@ -1415,7 +1561,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
                 column_domain and the evaluation domain of the
                 witnesses are the same"
                 );
-                evals.evals.par_iter_mut().enumerate().for_each(|(i, e)| {
+                cfg_iter_mut!(evals.evals).enumerate().for_each(|(i, e)| {
                     *e -= es_sub.evals[(scale * i + (d_sub as usize) * s) % es_sub.evals.len()];
                 });
                 Evals { evals, domain: d }
@@ -1473,7 +1619,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
         match self {
             Constant(x) => Constant(x.square()),
             Evals { domain, mut evals } => {
-                evals.evals.par_iter_mut().for_each(|e| {
+                cfg_iter_mut!(evals.evals).for_each(|e| {
                     e.square_in_place();
                 });
                 Evals { domain, evals }
@@ -1503,7 +1649,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
             (Constant(x), Constant(y)) => Constant(x * y),
             (Evals { domain, mut evals }, Constant(x))
             | (Constant(x), Evals { domain, mut evals }) => {
-                evals.evals.par_iter_mut().for_each(|e| *e *= x);
+                cfg_iter_mut!(evals.evals).for_each(|e| *e *= x);
                 Evals { domain, evals }
             }
             (
@@ -1580,7 +1726,7 @@ impl<'a, F: FftField> EvalResult<'a, F> {
                 witnesses are the same"
                 );
 
-                evals.evals.par_iter_mut().enumerate().for_each(|(i, e)| {
+                cfg_iter_mut!(evals.evals).enumerate().for_each(|(i, e)| {
                     *e *= es_sub.evals[(scale * i + (d_sub as usize) * s) % es_sub.evals.len()];
                 });
                 Evals { evals, domain: d }
@@ -2019,7 +2165,7 @@ impl<F: FftField, Column: Copy> Expr<F, Column> {
                     Either::Left(x) => {
                         let x = match x {
                             EvalResult::Evals { domain, mut evals } => {
-                                evals.evals.par_iter_mut().for_each(|x| {
+                                cfg_iter_mut!(evals.evals).for_each(|x| {
                                     x.double_in_place();
                                 });
                                 return Either::Left(EvalResult::Evals { domain, evals });
@@ -2235,7 +2381,7 @@ impl<F: FftField, Column: Copy + Debug, ChallengeTerm: Copy>
                 .get_column(idx)
                 .unwrap_or_else(|| panic!("Index polynomial {idx:?} not found"));
             let scale = e.evals.len() / n;
-            res.par_iter_mut()
+            cfg_iter_mut!(res)
                 .enumerate()
                 .for_each(|(i, r)| *r += c * e.evals[scale * i]);
         });
@@ -2274,7 +2420,7 @@ impl<F: FftField, Column: Debug + PartialEq + Copy, ChallengeTerm: Copy>
                 .get_column(idx)
                 .unwrap_or_else(|| panic!("Index polynomial {idx:?} not found"));
             let scale = e.evals.len() / n;
-            res.par_iter_mut()
+            cfg_iter_mut!(res)
                 .enumerate()
                 .for_each(|(i, r)| *r += c * e.evals[scale * i])
         });
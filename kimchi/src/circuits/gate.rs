@@ -3,7 +3,8 @@
 use crate::{
     circuits::{
         argument::{Argument, ArgumentEnv},
-        berkeley_columns::BerkeleyChallenges,
+        berkeley_columns::{BerkeleyChallenges, Column},
+        column_registry::kimchi_column_info,
         constraints::ConstraintSystem,
         polynomials::{
             complete_add, endomul_scalar, endosclmul, foreign_field_add, foreign_field_mul, keccak,
@@ -122,10 +123,13 @@ pub enum CircuitGateError {
     #[error("Invalid {0:?} constraint: {1}")]
     Constraint(GateType, usize),
     /// Invalid wire column
-    #[error("Invalid {0:?} wire column: {1}")]
+    #[error("Invalid {0:?} wire column: {}", witness_name(*.1))]
     WireColumn(GateType, usize),
     /// Disconnected wires
-    #[error("Invalid {typ:?} copy constraint: {},{} -> {},{}", .src.row, .src.col, .dst.row, .dst.col)]
+    #[error(
+        "Invalid {typ:?} copy constraint: {} at row {} -> {} at row {}",
+        witness_name(.src.col), .src.row, witness_name(.dst.col), .dst.row
+    )]
     CopyConstraint { typ: GateType, src: Wire, dst: Wire },
     /// Invalid lookup
     #[error("Invalid {0:?} lookup constraint")]
@@ -135,6 +139,11 @@ pub enum CircuitGateError {
     FailedToGetWitnessForRow(GateType, usize),
 }
 
+/// The name of the witness column `col` in a [CircuitGateError]
+fn witness_name(col: usize) -> String {
+    kimchi_column_info(&Column::Witness(col)).to_string()
+}
+
 /// Gate result
 pub type CircuitGateResult<T> = std::result::Result<T, CircuitGateError>;
 
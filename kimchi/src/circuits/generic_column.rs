@@ -0,0 +1,132 @@
+//! A column abstraction shared by the proof systems built on the expression
+//! framework.
+//!
+//! Each system has its own column type, e.g. [Column] for Kimchi or the
+//! columns of `kimchi-msm`, and the expressions of its constraints are defined
+//! over it. Most columns have the same role in all the systems: the witness
+//! columns (called relation columns in `kimchi-msm`), the selectors and the
+//! coefficients of the gates, and the fixed lookup tables. [GenericColumn]
+//! names these roles, and a column type implementing [SharedColumn] can be
+//! converted to and from it, so that a constraint written for a system can be
+//! moved to another one with [Expr::convert_columns] instead of being
+//! rewritten.
+//!
+//! The columns specific to an argument of a system, e.g. the permutation or
+//! the lookup aggregation, have no generic counterpart, and the expressions
+//! using them cannot be converted.
+
+use crate::circuits::{
+    berkeley_columns::Column,
+    expr::{Expr, ExprInner, Operations, Variable},
+    gate::GateType,
+};
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+/// The role of a column, shared by the proof systems
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GenericColumn {
+    /// A column of the witness
+    Witness(usize),
+    /// A selector set by the prover
+    DynamicSelector(usize),
+    /// A selector fixed by the circuit, e.g. the selector of a gate
+    FixedSelector(usize),
+    /// A coefficient of a gate
+    Coefficient(usize),
+    /// A fixed lookup table, given by its ID
+    LookupTable(u32),
+}
+
+/// A column type which can be converted to and from [GenericColumn]
+pub trait SharedColumn: Copy {
+    /// The role of the column, if it has a generic counterpart
+    fn to_generic(self) -> Option<GenericColumn>;
+
+    /// The column having the role `col`, if the system has one
+    fn from_generic(col: GenericColumn) -> Option<Self>;
+}
+
+impl SharedColumn for GenericColumn {
+    fn to_generic(self) -> Option<GenericColumn> {
+        Some(self)
+    }
+
+    fn from_generic(col: GenericColumn) -> Option<Self> {
+        Some(col)
+    }
+}
+
+/// The selectors of the gates are the fixed selectors, indexed by
+/// [GateType]. Kimchi has a single fixed lookup table column, combining all
+/// the tables, which has no generic counterpart.
+impl SharedColumn for Column {
+    fn to_generic(self) -> Option<GenericColumn> {
+        match self {
+            Column::Witness(i) => Some(GenericColumn::Witness(i)),
+            Column::Coefficient(i) => Some(GenericColumn::Coefficient(i)),
+            Column::Index(gate) => gate.to_usize().map(GenericColumn::FixedSelector),
+            _ => None,
+        }
+    }
+
+    fn from_generic(col: GenericColumn) -> Option<Self> {
+        match col {
+            GenericColumn::Witness(i) => Some(Column::Witness(i)),
+            GenericColumn::Coefficient(i) => Some(Column::Coefficient(i)),
+            GenericColumn::FixedSelector(i) => GateType::from_usize(i).map(Column::Index),
+            GenericColumn::DynamicSelector(_) | GenericColumn::LookupTable(_) => None,
+        }
+    }
+}
+
+impl<C: Clone, Col: Copy> Expr<C, Col> {
+    /// Returns the expression with each column `col` replaced by `f(col)`, or
+    /// `None` if `f` gives `None` for one of the columns.
+    pub fn try_map_columns<Col2>(&self, f: &impl Fn(Col) -> Option<Col2>) -> Option<Expr<C, Col2>> {
+        use ExprInner::*;
+        use Operations::*;
+        let res = match self {
+            Atom(Constant(c)) => Atom(Constant(c.clone())),
+            Atom(Cell(Variable { col, row })) => Atom(Cell(Variable {
+                col: f(*col)?,
+                row: *row,
+            })),
+            Atom(VanishesOnZeroKnowledgeAndPreviousRows) => {
+                Atom(VanishesOnZeroKnowledgeAndPreviousRows)
+            }
+            Atom(UnnormalizedLagrangeBasis(i)) => Atom(UnnormalizedLagrangeBasis(*i)),
+            Pow(x, d) => Pow(Box::new(x.try_map_columns(f)?), *d),
+            Add(x, y) => Add(
+                Box::new(x.try_map_columns(f)?),
+                Box::new(y.try_map_columns(f)?),
+            ),
+            Mul(x, y) => Mul(
+                Box::new(x.try_map_columns(f)?),
+                Box::new(y.try_map_columns(f)?),
+            ),
+            Sub(x, y) => Sub(
+                Box::new(x.try_map_columns(f)?),
+                Box::new(y.try_map_columns(f)?),
+            ),
+            Double(x) => Double(Box::new(x.try_map_columns(f)?)),
+            Square(x) => Square(Box::new(x.try_map_columns(f)?)),
+            Cache(id, x) => Cache(*id, Box::new(x.try_map_columns(f)?)),
+            IfFeature(feature, x, y) => IfFeature(
+                *feature,
+                Box::new(x.try_map_columns(f)?),
+                Box::new(y.try_map_columns(f)?),
+            ),
+        };
+        Some(res)
+    }
+}
+
+impl<C: Clone, Col: SharedColumn> Expr<C, Col> {
+    /// Returns the expression over the columns of another system having the
+    /// same roles, or `None` if one of the columns has no counterpart in
+    /// `Col2`.
+    pub fn convert_columns<Col2: SharedColumn>(&self) -> Option<Expr<C, Col2>> {
+        self.try_map_columns(&|col: Col| Col2::from_generic(col.to_generic()?))
+    }
+}
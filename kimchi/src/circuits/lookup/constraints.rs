@@ -12,7 +12,7 @@ use crate::{
 };
 use ark_ff::{FftField, One, PrimeField, Zero};
 use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
-use o1_utils::adjacent_pairs::AdjacentPairs;
+use o1_utils::{adjacent_pairs::AdjacentPairs, field_helpers::batch_inverse_in_place_parallel};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
@@ -24,8 +24,14 @@ use super::runtime_tables;
 /// Number of constraints produced by the argument.
 pub const CONSTRAINTS: u32 = 7;
 
-/// Pad with zeroes and then add 3 random elements in the last two
-/// rows for zero knowledge.
+/// Pad with zeroes and then add random elements in the last `zk_rows` rows
+/// for zero knowledge.
+///
+/// The sorted and aggregation polynomials of the lookup argument are evaluated
+/// at `zeta` and `zeta * omega`, once per chunk, and the number of
+/// zero-knowledge rows is larger than the number of these evaluations (see
+/// [`Builder::build`](crate::circuits::constraints::Builder::build)), so that
+/// they do not reveal the looked-up values.
 ///
 /// # Panics
 ///
@@ -267,7 +273,7 @@ where
             })
             .fold(F::one(), |acc, x| acc * x)
     }));
-    ark_ff::fields::batch_inversion::<F>(&mut lookup_aggreg[1..]);
+    batch_inverse_in_place_parallel(&mut lookup_aggreg[1..]);
 
     let max_lookups_per_row = lookup_info.max_per_row;
 
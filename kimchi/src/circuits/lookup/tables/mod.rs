@@ -3,6 +3,7 @@ use poly_commitment::PolyComm;
 use serde::{Deserialize, Serialize};
 
 pub mod range_check;
+pub mod sparse;
 pub mod xor;
 
 // If you add new tables, update ../../../../../book/src/kimchi/lookup.md
@@ -16,6 +17,10 @@ pub const XOR_TABLE_ID: i32 = 0;
 pub const RANGE_CHECK_TABLE_ID: i32 = 1;
 //~ spec:endcode
 
+/// The sparse table ID. This table is not used by any gate, and must be
+/// added explicitly to the constraint system (see [sparse]).
+pub const SPARSE_TABLE_ID: i32 = 2;
+
 /// Enumerates the different 'fixed' lookup tables used by individual gates
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GateLookupTable {
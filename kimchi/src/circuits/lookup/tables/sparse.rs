@@ -0,0 +1,56 @@
+//! Sparse table
+
+//~ The sparse table is a two-column table mapping each dense value of `bits` bits
+//~ to its sparse representation, in which each bit of the dense value is stored in a
+//~ nibble (i.e. the binary digits of the dense value are read as hexadecimal digits).
+//~ This encoding is used by Keccak arithmetizations, since the sum of up to 15 sparse
+//~ values cannot overflow from one nibble to the next, so that XORs and ANDs of
+//~ 16-bit quarters can be computed with additions instead of XOR lookups.
+//~
+//~ As for the XOR table, `(0, 0)` is the last position in the table.
+//~
+//~ Unlike the XOR and range check tables, this table is not used by any gate,
+//~ and must be added to the constraint system explicitly. It can then be
+//~ queried by `Lookup` gates, with the dense value as index and the sparse
+//~ value as value.
+
+use crate::circuits::{
+    lookup::tables::{LookupTable, SPARSE_TABLE_ID},
+    polynomials::keccak::Keccak,
+};
+use ark_ff::Field;
+
+/// The number of bits of the dense values used by Keccak, i.e. a quarter of a word
+pub const SPARSE_TABLE_BITS: usize = 16;
+
+/// Returns the sparse lookup table for dense values of `bits` bits, i.e. the
+/// table of pairs `(x, Keccak::expand(x))` for `x` in `[0, 2^bits)`.
+///
+/// # Panics
+///
+/// Will panic if `bits` is larger than 16.
+pub fn sparse_table<F: Field>(bits: usize) -> LookupTable<F> {
+    assert!(
+        bits <= SPARSE_TABLE_BITS,
+        "sparse table supports at most {SPARSE_TABLE_BITS} bits"
+    );
+
+    let mut data = vec![vec![], vec![]];
+    for x in (0u64..1 << bits).rev() {
+        data[0].push(F::from(x));
+        data[1].push(F::from(Keccak::expand(x)));
+    }
+
+    LookupTable {
+        id: SPARSE_TABLE_ID,
+        data,
+    }
+}
+
+/// Returns the number of entries of the sparse table for dense values of `bits` bits
+pub const fn table_size(bits: usize) -> usize {
+    1 << bits
+}
+
+/// The number of entries of the sparse table used by Keccak
+pub const TABLE_SIZE: usize = table_size(SPARSE_TABLE_BITS);
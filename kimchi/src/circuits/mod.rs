@@ -3,15 +3,21 @@ pub mod macros;
 
 pub mod argument;
 pub mod berkeley_columns;
+pub mod column_registry;
 pub mod constraints;
+pub mod degree_reduction;
 pub mod domain_constant_evaluation;
 pub mod domains;
+pub mod dot;
 pub mod expr;
 pub mod gate;
+pub mod generic_column;
 pub mod lookup;
 pub mod polynomial;
 pub mod polynomials;
 pub mod scalars;
 mod serialization_helper;
+pub mod stats;
+pub mod testing;
 pub mod wires;
 pub mod witness;
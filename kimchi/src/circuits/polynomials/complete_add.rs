@@ -14,13 +14,17 @@
 //~ The rest of the values are inaccessible from the permutation argument, but
 //~ `same_x` is a boolean that is true iff `x1 == x2`.
 //~
-use crate::circuits::{
-    argument::{Argument, ArgumentEnv, ArgumentType},
-    berkeley_columns::BerkeleyChallengeTerm,
-    expr::{constraints::ExprOps, Cache},
-    gate::{CircuitGate, GateType},
-    wires::COLUMNS,
+use crate::{
+    circuits::{
+        argument::{Argument, ArgumentEnv, ArgumentType},
+        berkeley_columns::BerkeleyChallengeTerm,
+        expr::{constraints::ExprOps, Cache},
+        gate::{CircuitGate, GateType},
+        wires::{GateWires, Wire, COLUMNS},
+    },
+    error::CompleteAddError,
 };
+use ark_ec::AffineRepr;
 use ark_ff::{Field, PrimeField};
 use std::marker::PhantomData;
 
@@ -223,6 +227,12 @@ where
 }
 
 impl<F: PrimeField> CircuitGate<F> {
+    /// Creates a complete addition gate, see [complete_add_witness] for its witness
+    /// and [CompleteAddCells] for the cells to wire.
+    pub fn create_complete_add(wires: GateWires) -> Self {
+        CircuitGate::new(GateType::CompleteAdd, wires, vec![])
+    }
+
     /// Check the correctness of witness values for a complete-add gate.
     ///
     /// # Errors
@@ -301,3 +311,112 @@ impl<F: PrimeField> CircuitGate<F> {
         Ok(())
     }
 }
+
+/// The cells of a complete addition gate accessible from the permutation
+/// argument, to wire the inputs and the output to other gates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompleteAddCells {
+    /// The coordinates of the first input
+    pub p1: [Wire; 2],
+    /// The coordinates of the second input
+    pub p2: [Wire; 2],
+    /// The coordinates of the output
+    pub output: [Wire; 2],
+    /// The boolean telling if the output is the point at infinity
+    pub inf: Wire,
+}
+
+impl CompleteAddCells {
+    /// Returns the cells of the complete addition gate at row `row`
+    pub fn at(row: usize) -> Self {
+        CompleteAddCells {
+            p1: [Wire::new(row, 0), Wire::new(row, 1)],
+            p2: [Wire::new(row, 2), Wire::new(row, 3)],
+            output: [Wire::new(row, 4), Wire::new(row, 5)],
+            inf: Wire::new(row, 6),
+        }
+    }
+}
+
+/// The result of a complete addition gate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompleteAddResult<F> {
+    /// The sum of the inputs, or `None` if it is the point at infinity. In that
+    /// case, the output cells hold values constrained by the gate but meaningless.
+    pub output: Option<(F, F)>,
+    /// The cells of the gate
+    pub cells: CompleteAddCells,
+}
+
+/// Writes the witness of the complete addition gate at row `row` adding `p1` and
+/// `p2`, which covers the addition of distinct points, the doubling of a point and
+/// the addition of a point and its negation, whose result is the point at infinity.
+///
+/// # Errors
+///
+/// Will give error if an input is the point at infinity, which cannot be
+/// represented in the gate, or if the doubled point has a zero y coordinate.
+pub fn complete_add_witness<G: AffineRepr>(
+    witness: &mut [Vec<G::BaseField>; COLUMNS],
+    row: usize,
+    p1: G,
+    p2: G,
+) -> Result<CompleteAddResult<G::BaseField>, CompleteAddError>
+where
+    G::BaseField: PrimeField,
+{
+    let ((x1, y1), (x2, y2)) = match (p1.xy(), p2.xy()) {
+        (Some((x1, y1)), Some((x2, y2))) => ((*x1, *y1), (*x2, *y2)),
+        _ => return Err(CompleteAddError::InfinityInput),
+    };
+    let zero = G::BaseField::zero();
+    let one = G::BaseField::one();
+
+    let same_x = x1 == x2;
+    let s = if same_x {
+        // 2 * s * y1 = 3 * x1^2, also when the output is the point at infinity
+        let x1_squared = x1.square();
+        match y1.double().inverse() {
+            Some(inv) => (x1_squared.double() + x1_squared) * inv,
+            None => return Err(CompleteAddError::DoublingOrderTwo),
+        }
+    } else {
+        // (x2 - x1) * s = y2 - y1
+        (y2 - y1) / (x2 - x1)
+    };
+    let x3 = s.square() - x1 - x2;
+    let y3 = s * (x1 - x3) - y1;
+    let inf = same_x && y1 != y2;
+    let inf_z = if inf {
+        (y2 - y1).inverse().unwrap()
+    } else {
+        zero
+    };
+    let x21_inv = if same_x {
+        zero
+    } else {
+        (x2 - x1).inverse().unwrap()
+    };
+
+    let values = [
+        x1,
+        y1,
+        x2,
+        y2,
+        x3,
+        y3,
+        if inf { one } else { zero },
+        if same_x { one } else { zero },
+        s,
+        inf_z,
+        x21_inv,
+    ];
+    for (col, value) in witness.iter_mut().zip(values) {
+        col[row] = value;
+    }
+
+    Ok(CompleteAddResult {
+        output: if inf { None } else { Some((x3, y3)) },
+        cells: CompleteAddCells::at(row),
+    })
+}
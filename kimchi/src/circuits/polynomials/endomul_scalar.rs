@@ -8,14 +8,47 @@ use crate::{
         constraints::ConstraintSystem,
         expr::{constraints::ExprOps, Cache},
         gate::{CircuitGate, GateType},
-        wires::COLUMNS,
+        wires::{GateWires, Wire, COLUMNS},
     },
     curve::KimchiCurve,
 };
 use ark_ff::{BitIteratorLE, Field, PrimeField};
 use std::{array, marker::PhantomData};
 
+/// Number of bits of the challenge handled by an `EndoMulScalar` gate
+pub const BITS_PER_ROW: usize = 16;
+
 impl<F: PrimeField> CircuitGate<F> {
+    /// Creates the `EndoMulScalar` gates converting a challenge of `num_bits` bits
+    /// to its endo-scalar representation, starting at row `start_row`, see
+    /// [witness]. The outputs `n`, `a` and `b` of each gate are wired to the inputs
+    /// of the next one.
+    ///
+    /// The inputs of the first row (0 for `n` in the first column, and 2 for `a`
+    /// and `b` in the third and fourth columns) and the outputs of the last row
+    /// (the challenge in the second column, `a` and `b` in the fifth and sixth
+    /// columns) are left to be wired to the rest of the circuit.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `num_bits` is not a multiple of [BITS_PER_ROW].
+    pub fn create_endomul_scalar(start_row: usize, num_bits: usize) -> Vec<Self> {
+        assert_eq!(num_bits % BITS_PER_ROW, 0);
+        let rows = num_bits / BITS_PER_ROW;
+        let mut wires: Vec<GateWires> = (0..rows).map(|i| Wire::for_row(start_row + i)).collect();
+        for i in 1..rows {
+            // (n8, a8, b8) of the previous row are (n0, a0, b0)
+            for (prev_col, col) in [(1, 0), (4, 2), (5, 3)] {
+                wires[i - 1][prev_col] = Wire::new(start_row + i, col);
+                wires[i][col] = Wire::new(start_row + i - 1, prev_col);
+            }
+        }
+        wires
+            .into_iter()
+            .map(|wires| CircuitGate::new(GateType::EndoMulScalar, wires, vec![]))
+            .collect()
+    }
+
     /// Verify the `EndoMulscalar` gate.
     ///
     /// # Errors
@@ -216,7 +249,8 @@ where
     }
 }
 
-/// Generate the `witness`
+/// Generate the `witness`, appending its rows to `witness_cols`, and returns the
+/// endo-scalar represented by `scalar`, see [witness].
 ///
 /// # Panics
 ///
@@ -227,8 +261,43 @@ pub fn gen_witness<F: PrimeField + std::fmt::Display>(
     endo_scalar: F,
     num_bits: usize,
 ) -> F {
-    let crumbs_per_row = 8;
-    let bits_per_row = 2 * crumbs_per_row;
+    assert_eq!(num_bits % BITS_PER_ROW, 0);
+    let row0 = witness_cols[0].len();
+    for col in witness_cols.iter_mut() {
+        col.resize(row0 + num_bits / BITS_PER_ROW, F::zero());
+    }
+    witness(witness_cols, row0, scalar, endo_scalar, num_bits).endo_scalar
+}
+
+/// The result of the conversion of a challenge to its endo-scalar representation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EndoMulScalarResult<F> {
+    /// The coefficient of the endomorphism
+    pub a: F,
+    /// The constant coefficient
+    pub b: F,
+    /// The endo-scalar `a * endo_scalar + b`
+    pub endo_scalar: F,
+}
+
+/// Writes the `witness` of the gates created by
+/// [create_endomul_scalar](CircuitGate::create_endomul_scalar) from row `row0`,
+/// for the challenge `scalar` of `num_bits` bits, and returns its endo-scalar
+/// representation, as computed out of the circuit by
+/// [ScalarChallenge::to_field_with_length](mina_poseidon::sponge::ScalarChallenge::to_field_with_length).
+///
+/// # Panics
+///
+/// Will panic if `num_bits` is not a multiple of [BITS_PER_ROW], or if `scalar`
+/// does not fit in `num_bits` bits.
+pub fn witness<F: PrimeField + std::fmt::Display>(
+    witness_cols: &mut [Vec<F>; COLUMNS],
+    row0: usize,
+    scalar: F,
+    endo_scalar: F,
+    num_bits: usize,
+) -> EndoMulScalarResult<F> {
+    let bits_per_row = BITS_PER_ROW;
     assert_eq!(num_bits % bits_per_row, 0);
 
     let bits_lsb: Vec<_> = BitIteratorLE::new(scalar.into_bigint())
@@ -243,17 +312,18 @@ pub fn gen_witness<F: PrimeField + std::fmt::Display>(
     let one = F::one();
     let neg_one = -one;
 
-    for row_bits in bits_msb[..].chunks(bits_per_row) {
-        witness_cols[0].push(n);
-        witness_cols[2].push(a);
-        witness_cols[3].push(b);
+    for (i, row_bits) in bits_msb[..].chunks(bits_per_row).enumerate() {
+        let row = row0 + i;
+        witness_cols[0][row] = n;
+        witness_cols[2][row] = a;
+        witness_cols[3][row] = b;
 
         for (j, crumb_bits) in row_bits.chunks(2).enumerate() {
             let b0 = *crumb_bits[1];
             let b1 = *crumb_bits[0];
 
             let crumb = F::from(u64::from(b0)) + F::from(u64::from(b1)).double();
-            witness_cols[6 + j].push(crumb);
+            witness_cols[6 + j][row] = crumb;
 
             a.double_in_place();
             b.double_in_place();
@@ -272,16 +342,20 @@ pub fn gen_witness<F: PrimeField + std::fmt::Display>(
             n += crumb;
         }
 
-        witness_cols[1].push(n);
-        witness_cols[4].push(a);
-        witness_cols[5].push(b);
+        witness_cols[1][row] = n;
+        witness_cols[4][row] = a;
+        witness_cols[5][row] = b;
 
-        witness_cols[14].push(F::zero()); // unused
+        witness_cols[14][row] = F::zero(); // unused
     }
 
     assert_eq!(scalar, n);
 
-    a * endo_scalar + b
+    EndoMulScalarResult {
+        a,
+        b,
+        endo_scalar: a * endo_scalar + b,
+    }
 }
 
 fn c_func<F: Field>(x: F) -> F {
@@ -6,9 +6,15 @@ use o1_utils::{
 };
 
 use ark_ff::{Field, PrimeField};
-use num_bigint::BigUint;
+use num_bigint::{BigInt, BigUint, ToBigUint};
+use num_integer::Integer;
 use num_traits::{One, Zero};
-use std::array;
+use std::{
+    array,
+    fmt::{Debug, Display, Formatter},
+    marker::PhantomData,
+    ops::{Add, Mul, Neg, Sub},
+};
 
 /// Index of low limb (in 3-limb foreign elements)
 pub const LO: usize = 0;
@@ -209,6 +215,150 @@ impl BigUintArrayCompose<3> for [BigUint; 3] {
     }
 }
 
+/// A foreign field element, stored as an integer modulo the foreign field
+/// modulus, with views as `N` limbs of [LIMB_BITS] bits in the native field
+/// `F`.
+///
+/// Contrary to [KimchiForeignElement], which stores the limbs, the arithmetic
+/// is done on the integer, so that the witness builders and the tests can
+/// compute the values of the foreign field and only split them into limbs when
+/// writing the witness.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BigForeignElement<F, const N: usize> {
+    value: BigUint,
+    modulus: BigUint,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField, const N: usize> BigForeignElement<F, N> {
+    /// Creates the element `value` modulo `modulus`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the modulus is zero or does not fit in `N` limbs.
+    pub fn new(value: BigUint, modulus: BigUint) -> Self {
+        assert!(!modulus.is_zero(), "the foreign field modulus is zero");
+        assert!(
+            modulus.bits() <= (N * LIMB_BITS) as u64,
+            "the foreign field modulus does not fit in {N} limbs"
+        );
+        Self {
+            value: value % &modulus,
+            modulus,
+            _field: PhantomData,
+        }
+    }
+
+    /// Creates the element from its limbs, in little-endian order, reduced
+    /// modulo `modulus`
+    pub fn from_limbs(limbs: &[F; N], modulus: BigUint) -> Self {
+        Self::new(fields_compose(limbs, &BigUint::two_to_limb()), modulus)
+    }
+
+    /// The value of the element, smaller than the modulus
+    pub fn value(&self) -> &BigUint {
+        &self.value
+    }
+
+    /// The foreign field modulus
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// The limbs of the element in the native field, in little-endian order
+    pub fn to_limbs(&self) -> [F; N] {
+        let mut limbs = biguint_to_limbs(&self.value, LIMB_BITS);
+        limbs.resize(N, BigUint::zero());
+        biguints_to_fields(&array::from_fn(|i| limbs[i].clone()))
+    }
+
+    /// The element as a [ForeignElement] of limbs
+    pub fn to_foreign_element(&self) -> ForeignElement<F, LIMB_BITS, N> {
+        ForeignElement::new(self.to_limbs())
+    }
+
+    /// The inverse of the element, if the element is invertible modulo the
+    /// modulus
+    pub fn inverse(&self) -> Option<Self> {
+        // The modulus is not necessarily prime, so the inverse is computed with
+        // the extended Euclidean algorithm
+        let value = BigInt::from(self.value.clone());
+        let modulus = BigInt::from(self.modulus.clone());
+        let gcd = value.extended_gcd(&modulus);
+        if !gcd.gcd.is_one() {
+            return None;
+        }
+        let inverse = gcd.x.mod_floor(&modulus).to_biguint()?;
+        Some(self.with_value(inverse))
+    }
+
+    fn with_value(&self, value: BigUint) -> Self {
+        Self {
+            value: value % &self.modulus,
+            modulus: self.modulus.clone(),
+            _field: PhantomData,
+        }
+    }
+
+    fn check_modulus(&self, other: &Self) {
+        assert_eq!(
+            self.modulus, other.modulus,
+            "the foreign field elements have different moduli"
+        );
+    }
+}
+
+impl<F: PrimeField, const N: usize> Add<&BigForeignElement<F, N>> for &BigForeignElement<F, N> {
+    type Output = BigForeignElement<F, N>;
+
+    fn add(self, other: &BigForeignElement<F, N>) -> Self::Output {
+        self.check_modulus(other);
+        self.with_value(&self.value + &other.value)
+    }
+}
+
+impl<F: PrimeField, const N: usize> Sub<&BigForeignElement<F, N>> for &BigForeignElement<F, N> {
+    type Output = BigForeignElement<F, N>;
+
+    fn sub(self, other: &BigForeignElement<F, N>) -> Self::Output {
+        self.check_modulus(other);
+        self.with_value(&self.value + &self.modulus - &other.value)
+    }
+}
+
+impl<F: PrimeField, const N: usize> Mul<&BigForeignElement<F, N>> for &BigForeignElement<F, N> {
+    type Output = BigForeignElement<F, N>;
+
+    fn mul(self, other: &BigForeignElement<F, N>) -> Self::Output {
+        self.check_modulus(other);
+        self.with_value(&self.value * &other.value)
+    }
+}
+
+impl<F: PrimeField, const N: usize> Neg for &BigForeignElement<F, N> {
+    type Output = BigForeignElement<F, N>;
+
+    fn neg(self) -> Self::Output {
+        self.with_value(&self.modulus - &self.value)
+    }
+}
+
+impl<F, const N: usize> Display for BigForeignElement<F, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:x}", self.value)
+    }
+}
+
+impl<F, const N: usize> Debug for BigForeignElement<F, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "BigForeignElement(0x{:x} mod 0x{:x})",
+            self.value, self.modulus
+        )
+    }
+}
+
 // Compose field limbs into BigUint value
 fn fields_compose<F: PrimeField, const N: usize>(limbs: &[F; N], base: &BigUint) -> BigUint {
     limbs
@@ -363,4 +513,50 @@ mod tests {
             2
         );
     }
+
+    #[test]
+    fn big_foreign_element_arithmetic() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let modulus = secp256k1_modulus();
+        let a_big = rng.gen_biguint_below(&modulus);
+        let b_big = rng.gen_biguint_below(&modulus);
+        let a = BigForeignElement::<BaseField, 3>::new(a_big.clone(), modulus.clone());
+        let b = BigForeignElement::<BaseField, 3>::new(b_big.clone(), modulus.clone());
+
+        assert_eq!(*(&a + &b).value(), (&a_big + &b_big) % &modulus);
+        assert_eq!(*(&a * &b).value(), (&a_big * &b_big) % &modulus);
+        assert_eq!(&(&a - &b) + &b, a);
+        assert!((&a + &-&a).value().is_zero());
+        assert!((&a * &a.inverse().unwrap()).value().is_one());
+
+        let zero = BigForeignElement::<BaseField, 3>::new(modulus.clone(), modulus.clone());
+        assert!(zero.value().is_zero());
+        assert!(zero.inverse().is_none());
+    }
+
+    #[test]
+    fn big_foreign_element_limbs() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let modulus = secp256k1_modulus();
+        let a = BigForeignElement::<BaseField, 3>::new(rng.gen_biguint(256), modulus.clone());
+
+        let limbs = a.to_limbs();
+        assert_eq!(limbs, a.value().to_field_limbs::<BaseField>());
+        assert_eq!(limbs, a.to_foreign_element().limbs);
+        assert_eq!(BigForeignElement::from_limbs(&limbs, modulus), a);
+
+        let one = BigForeignElement::<BaseField, 3>::new(BigUint::one(), BigUint::from(7u32));
+        assert_eq!(
+            one.to_limbs(),
+            [BaseField::one(), BaseField::zero(), BaseField::zero()]
+        );
+        assert_eq!(one.to_string(), "0x1");
+        assert_eq!(format!("{one:?}"), "BigForeignElement(0x1 mod 0x7)");
+    }
+
+    #[test]
+    #[should_panic]
+    fn big_foreign_element_modulus_too_large() {
+        BigForeignElement::<BaseField, 2>::new(BigUint::one(), secp256k1_modulus());
+    }
 }
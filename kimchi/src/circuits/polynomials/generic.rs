@@ -40,7 +40,7 @@ use crate::{
         expr::{constraints::ExprOps, Cache},
         gate::{CircuitGate, GateType},
         polynomial::COLUMNS,
-        wires::GateWires,
+        wires::{GateWires, Wire},
     },
     curve::KimchiCurve,
     prover_index::ProverIndex,
@@ -242,6 +242,215 @@ impl<F: PrimeField> CircuitGate<F> {
     }
 }
 
+/// A value of a circuit built with [`GenericGateBuilder`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenericVar(usize);
+
+/// An operation of a circuit built with [`GenericGateBuilder`], taking half a row
+#[derive(Clone, Debug)]
+enum GenericOp<F> {
+    /// `out = c`
+    Constant(F, GenericVar),
+    /// `out = left + right`
+    Add(GenericVar, GenericVar, GenericVar),
+    /// `out = left - right`
+    Sub(GenericVar, GenericVar, GenericVar),
+    /// `out = left * right`
+    Mul(GenericVar, GenericVar, GenericVar),
+    /// `x * x = x`
+    Boolean(GenericVar),
+}
+
+impl<F: PrimeField> GenericOp<F> {
+    /// The coefficients of the generic gate computing the operation
+    fn coeffs(&self) -> [F; GENERIC_COEFFS] {
+        let (one, zero) = (F::one(), F::zero());
+        match self {
+            GenericOp::Constant(c, _) => [one, zero, zero, zero, -*c],
+            GenericOp::Add(..) => [one, one, -one, zero, zero],
+            GenericOp::Sub(..) => [one, -one, -one, zero, zero],
+            GenericOp::Mul(..) => [zero, zero, -one, one, zero],
+            GenericOp::Boolean(_) => [-one, zero, zero, one, zero],
+        }
+    }
+
+    /// The values in the left, right and output registers
+    fn registers(&self) -> [Option<GenericVar>; GENERIC_REGISTERS] {
+        match self {
+            GenericOp::Constant(_, out) => [Some(*out), None, None],
+            GenericOp::Add(left, right, out)
+            | GenericOp::Sub(left, right, out)
+            | GenericOp::Mul(left, right, out) => [Some(*left), Some(*right), Some(*out)],
+            GenericOp::Boolean(x) => [Some(*x), Some(*x), None],
+        }
+    }
+}
+
+/// Builds a circuit of additions, subtractions, multiplications, constants and boolean checks
+/// with generic gates, without dealing with their coefficients: the operations are
+/// packed two per row, and the registers holding the same value are wired together.
+///
+/// ```ignore
+/// let mut builder = GenericGateBuilder::new(0);
+/// let x = builder.create_input();
+/// let two = builder.create_constant(Fp::from(2));
+/// let y = builder.create_mul(x, two);
+/// let gates = builder.gates();
+/// let witness = builder.witness(&[(x, Fp::from(21))]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GenericGateBuilder<F> {
+    start_row: usize,
+    num_vars: usize,
+    ops: Vec<GenericOp<F>>,
+}
+
+impl<F: PrimeField> GenericGateBuilder<F> {
+    /// Creates a builder for a circuit starting at row `start_row`
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            start_row,
+            num_vars: 0,
+            ops: vec![],
+        }
+    }
+
+    fn new_var(&mut self) -> GenericVar {
+        self.num_vars += 1;
+        GenericVar(self.num_vars - 1)
+    }
+
+    /// Creates a value given to the witness, see [`Self::witness`]
+    pub fn create_input(&mut self) -> GenericVar {
+        self.new_var()
+    }
+
+    /// Creates the constant `c`
+    pub fn create_constant(&mut self, c: F) -> GenericVar {
+        let out = self.new_var();
+        self.ops.push(GenericOp::Constant(c, out));
+        out
+    }
+
+    /// Creates the sum of `left` and `right`
+    pub fn create_add(&mut self, left: GenericVar, right: GenericVar) -> GenericVar {
+        let out = self.new_var();
+        self.ops.push(GenericOp::Add(left, right, out));
+        out
+    }
+
+    /// Creates the difference of `left` and `right`
+    pub fn create_sub(&mut self, left: GenericVar, right: GenericVar) -> GenericVar {
+        let out = self.new_var();
+        self.ops.push(GenericOp::Sub(left, right, out));
+        out
+    }
+
+    /// Creates the product of `left` and `right`
+    pub fn create_mul(&mut self, left: GenericVar, right: GenericVar) -> GenericVar {
+        let out = self.new_var();
+        self.ops.push(GenericOp::Mul(left, right, out));
+        out
+    }
+
+    /// Constrains `x` to be 0 or 1
+    pub fn create_boolean_check(&mut self, x: GenericVar) {
+        self.ops.push(GenericOp::Boolean(x));
+    }
+
+    /// Returns the number of rows of the circuit
+    pub fn num_rows(&self) -> usize {
+        (self.ops.len() + 1) / 2
+    }
+
+    /// Returns the row and the first column of the registers of the `i`-th operation
+    fn position(&self, i: usize) -> (usize, usize) {
+        (self.start_row + i / 2, (i % 2) * GENERIC_REGISTERS)
+    }
+
+    /// Returns the registers holding the value `var`, to wire it to the cells of
+    /// other gadgets
+    pub fn cells(&self, var: GenericVar) -> Vec<Wire> {
+        let mut cells = vec![];
+        for (i, op) in self.ops.iter().enumerate() {
+            let (row, col) = self.position(i);
+            for (j, register) in op.registers().iter().enumerate() {
+                if *register == Some(var) {
+                    cells.push(Wire::new(row, col + j));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Returns the generic gates of the circuit. The second half of the last row is
+    /// left unused if there is an odd number of operations.
+    pub fn gates(&self) -> Vec<CircuitGate<F>> {
+        // the registers of each value, wired in a cycle
+        let cells: Vec<Vec<Wire>> = (0..self.num_vars)
+            .map(|var| self.cells(GenericVar(var)))
+            .collect();
+        let mut wires: Vec<GateWires> = (0..self.num_rows())
+            .map(|row| Wire::for_row(self.start_row + row))
+            .collect();
+        for cycle in cells.iter().filter(|cycle| cycle.len() > 1) {
+            for (i, cell) in cycle.iter().enumerate() {
+                wires[cell.row - self.start_row][cell.col] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+
+        wires
+            .into_iter()
+            .enumerate()
+            .map(|(row, wires)| {
+                let mut coeffs = [F::zero(); DOUBLE_GENERIC_COEFFS];
+                for (half, op) in self.ops.iter().skip(2 * row).take(2).enumerate() {
+                    coeffs[half * GENERIC_COEFFS..(half + 1) * GENERIC_COEFFS]
+                        .copy_from_slice(&op.coeffs());
+                }
+                CircuitGate::create_generic(wires, coeffs)
+            })
+            .collect()
+    }
+
+    /// Returns the witness of the circuit, for the rows of the circuit only, given
+    /// the values of the inputs.
+    /// Panics if a value used by the circuit is not an input nor the result of an
+    /// operation.
+    pub fn witness(&self, inputs: &[(GenericVar, F)]) -> [Vec<F>; COLUMNS] {
+        let mut values: Vec<Option<F>> = vec![None; self.num_vars];
+        for (GenericVar(var), value) in inputs {
+            values[*var] = Some(*value);
+        }
+        let value = |values: &[Option<F>], GenericVar(var): GenericVar| {
+            values[var].unwrap_or_else(|| panic!("the value {var} is not computed"))
+        };
+        let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); self.num_rows()]);
+        for (i, op) in self.ops.iter().enumerate() {
+            match op {
+                GenericOp::Constant(c, GenericVar(out)) => values[*out] = Some(*c),
+                GenericOp::Add(left, right, GenericVar(out)) => {
+                    values[*out] = Some(value(&values, *left) + value(&values, *right))
+                }
+                GenericOp::Sub(left, right, GenericVar(out)) => {
+                    values[*out] = Some(value(&values, *left) - value(&values, *right))
+                }
+                GenericOp::Mul(left, right, GenericVar(out)) => {
+                    values[*out] = Some(value(&values, *left) * value(&values, *right))
+                }
+                GenericOp::Boolean(_) => (),
+            }
+            let (row, col) = self.position(i);
+            for (j, var) in op.registers().iter().enumerate() {
+                if let Some(var) = var {
+                    witness[col + j][row - self.start_row] = value(&values, *var);
+                }
+            }
+        }
+        witness
+    }
+}
+
 // -------------------------------------------------
 
 //~ The constraints:
@@ -2,6 +2,7 @@
 pub mod circuitgates;
 pub mod constants;
 pub mod gadget;
+pub mod sparse;
 pub mod witness;
 
 use crate::circuits::expr::constraints::ExprOps;
@@ -0,0 +1,126 @@
+//! This module includes the definition of the sparse conversion gadget, which
+//! converts dense values into their sparse representation (or back) with
+//! lookups into the sparse table, and the code for its witness generation.
+//! It is an alternative to the `Xor16` route for Keccak-heavy circuits.
+use crate::circuits::{
+    gate::{CircuitGate, Connect, GateType},
+    lookup::tables::{
+        sparse::{sparse_table, SPARSE_TABLE_BITS},
+        LookupTable, SPARSE_TABLE_ID,
+    },
+    polynomial::COLUMNS,
+    polynomials::{generic::GenericGateSpec, keccak::Keccak},
+    wires::Wire,
+};
+use ark_ff::PrimeField;
+use std::array;
+
+//~ The sparse conversion gadget uses one `Generic` gate to fix the table ID,
+//~ followed by `Lookup` gates performing up to 3 conversions each.
+//~ Given dense values `d_i` and their sparse representations `s_i`, the layout looks like this:
+//~
+//~ | Row | Gate      | 0     | 1     | 2     | 3     | 4     | 5     | 6     |
+//~ | --- | --------- | ----- | ----- | ----- | ----- | ----- | ----- | ----- |
+//~ |   0 | `Generic` | `id`  |       |       |       |       |       |       |
+//~ |   1 | `Lookup`  | `id`  | `d_0` | `s_0` | `d_1` | `s_1` | `d_2` | `s_2` |
+//~ |   2 | `Lookup`  | `id`  | `d_3` | `s_3` | `0`   | `0`   | `0`   | `0`   |
+//~
+//~ The `id` cells are all wired together, so that the lookups are done in the sparse table.
+//~ Unused pairs are set to `(0, 0)`, which is an entry of the table.
+
+/// Number of conversions performed in a single `Lookup` row
+const CONVERSIONS_PER_ROW: usize = 3;
+
+/// Number of `Lookup` rows needed to convert `chunks` values
+fn num_lookups(chunks: usize) -> usize {
+    (chunks + CONVERSIONS_PER_ROW - 1) / CONVERSIONS_PER_ROW
+}
+
+impl<F: PrimeField> CircuitGate<F> {
+    /// Extends a sparse conversion gadget for `chunks` values to a circuit
+    /// Includes:
+    /// - 1 Generic gate to fix the table ID
+    /// - ceil(chunks / 3) Lookup gates
+    /// Input:
+    /// - gates     : vector of circuit gates
+    /// - chunks    : number of values to convert
+    /// Output:
+    /// - new row index
+    pub fn extend_sparse_gadget(gates: &mut Vec<Self>, chunks: usize) -> usize {
+        let new_row = gates.len();
+        let (_, mut sparse_gates) = Self::create_sparse_gadget(new_row, chunks);
+        gates.append(&mut sparse_gates);
+
+        // wire the table ID of every lookup to the constant
+        for row in new_row + 1..gates.len() {
+            gates.connect_cell_pair((row - 1, 0), (row, 0));
+        }
+
+        gates.len()
+    }
+
+    /// Creates a sparse conversion gadget for `chunks` values
+    /// Includes:
+    /// - 1 Generic gate to fix the table ID
+    /// - ceil(chunks / 3) Lookup gates
+    /// Input:
+    /// - new_row   : row to start the sparse conversion gadget
+    /// - chunks    : number of values to convert
+    /// Outputs tuple (next_row, circuit_gates) where
+    /// - next_row  : next row after this gate
+    /// - gates     : vector of circuit gates comprising this gate
+    /// Warning:
+    /// - don't forget to wire the table IDs together as in `extend_sparse_gadget`
+    /// - the sparse table must be added to the constraint system, see [`lookup_table`]
+    pub fn create_sparse_gadget(new_row: usize, chunks: usize) -> (usize, Vec<Self>) {
+        let mut gates = vec![CircuitGate::create_generic_gadget(
+            Wire::for_row(new_row),
+            GenericGateSpec::Const(F::from(SPARSE_TABLE_ID as u64)),
+            None,
+        )];
+        gates.extend((0..num_lookups(chunks)).map(|i| CircuitGate {
+            typ: GateType::Lookup,
+            wires: Wire::for_row(new_row + 1 + i),
+            coeffs: vec![],
+        }));
+
+        (new_row + gates.len(), gates)
+    }
+}
+
+/// Get the sparse lookup table for 16-bit quarters, as used by Keccak
+pub fn lookup_table<F: PrimeField>() -> LookupTable<F> {
+    sparse_table(SPARSE_TABLE_BITS)
+}
+
+/// Create a sparse conversion witness for the dense values `dense`
+/// Input: the dense values, each of them fitting in the bits of the sparse table
+/// Output: the witness of the sparse conversion gadget
+pub fn create_sparse_witness<F: PrimeField>(dense: &[u64]) -> [Vec<F>; COLUMNS] {
+    let rows = 1 + num_lookups(dense.len());
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); rows]);
+
+    let table_id = F::from(SPARSE_TABLE_ID as u64);
+    witness[0] = vec![table_id; rows];
+    for (i, d) in dense.iter().enumerate() {
+        let row = 1 + i / CONVERSIONS_PER_ROW;
+        let col = 1 + 2 * (i % CONVERSIONS_PER_ROW);
+        witness[col][row] = F::from(*d);
+        witness[col + 1][row] = F::from(Keccak::expand(*d));
+    }
+
+    witness
+}
+
+/// Create a sparse conversion witness for the sparse values `sparse`, i.e. the
+/// witness converting them back to their dense representation
+/// Input: the sparse values, in canonical form (i.e. with 0 or 1 in each nibble)
+/// Output: the witness of the sparse conversion gadget
+pub fn create_dense_witness<F: PrimeField>(sparse: &[u64]) -> [Vec<F>; COLUMNS] {
+    create_sparse_witness(&Keccak::collapse(sparse))
+}
+
+/// Create a sparse conversion witness for the 4 quarters of a 64-bit word
+pub fn create_sparse_word_witness<F: PrimeField>(word: u64) -> [Vec<F>; COLUMNS] {
+    create_sparse_witness(&Keccak::decompose(word))
+}
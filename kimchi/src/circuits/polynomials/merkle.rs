@@ -0,0 +1,177 @@
+//! This module implements a gadget verifying a Merkle inclusion path with the
+//! Poseidon permutation.
+//!
+//! The node of a Merkle tree is the hash of its two children, that is the first
+//! element of the permutation of the state `[left, right, 0]`, as computed by a
+//! sponge absorbing `left` and `right` before squeezing. At each level of the path,
+//! the bit of the index tells if the current node is the left (0) or the right (1)
+//! child, and the current node and its sibling are swapped accordingly before
+//! hashing:
+//!
+//! ```ignore
+//! left = current + bit * (sibling - current)
+//! right = sibling - bit * (sibling - current)
+//! current = hash(left, right)
+//! ```
+//!
+//! Each level takes [MERKLE_ROWS_PER_LEVEL] rows: 3 rows of generic gates for the
+//! swap and the boolean check of the bit, followed by a Poseidon gadget.
+
+use crate::circuits::{
+    gate::CircuitGate,
+    polynomials::{
+        generic::{GenericGateBuilder, GenericVar},
+        poseidon::{self, POS_ROWS_PER_HASH, SPONGE_WIDTH},
+    },
+    wires::{Wire, COLUMNS},
+};
+use ark_ff::PrimeField;
+use mina_poseidon::poseidon::ArithmeticSpongeParams;
+
+/// Number of rows of generic gates for the swap of a level
+const SWAP_ROWS: usize = 3;
+
+/// Number of rows of a level of the Merkle path
+pub const MERKLE_ROWS_PER_LEVEL: usize = SWAP_ROWS + POS_ROWS_PER_HASH + 1;
+
+/// The values of the swap of a level
+struct Swap {
+    current: GenericVar,
+    sibling: GenericVar,
+    bit: GenericVar,
+    left: GenericVar,
+    right: GenericVar,
+    zero: GenericVar,
+}
+
+/// Returns the generic gates swapping the current node and its sibling, for the
+/// level starting at `row`. The current node is in the second column of the first
+/// row.
+fn swap<F: PrimeField>(row: usize) -> (GenericGateBuilder<F>, Swap) {
+    let mut builder = GenericGateBuilder::new(row);
+    let current = builder.create_input();
+    let sibling = builder.create_input();
+    let bit = builder.create_input();
+    let diff = builder.create_sub(sibling, current);
+    builder.create_boolean_check(bit);
+    let diff_or_zero = builder.create_mul(bit, diff);
+    let left = builder.create_add(current, diff_or_zero);
+    let right = builder.create_sub(sibling, diff_or_zero);
+    let zero = builder.create_constant(F::zero());
+    assert_eq!(builder.num_rows(), SWAP_ROWS);
+    (
+        builder,
+        Swap {
+            current,
+            sibling,
+            bit,
+            left,
+            right,
+            zero,
+        },
+    )
+}
+
+impl<F: PrimeField> CircuitGate<F> {
+    /// Creates the gates verifying a Merkle inclusion path of `depth` levels,
+    /// starting at row `start_row`, with the Poseidon `round_constants`. The leaf is
+    /// in the second column of the first row, and the root in the first column of
+    /// the last row, to be wired to the rest of the circuit. The witness is built
+    /// with [merkle_membership_witness].
+    ///
+    /// Returns the gates and the next row of the circuit.
+    pub fn create_merkle_membership(
+        start_row: usize,
+        depth: usize,
+        round_constants: &[Vec<F>],
+    ) -> (Vec<Self>, usize) {
+        let mut gates = vec![];
+        let mut cycles: Vec<Vec<Wire>> = vec![];
+        for level in 0..depth {
+            let row = start_row + level * MERKLE_ROWS_PER_LEVEL;
+            let (builder, vars) = swap::<F>(row);
+            gates.extend(builder.gates());
+
+            let poseidon_row = row + SWAP_ROWS;
+            let (poseidon_gates, _) = CircuitGate::create_poseidon_gadget(
+                poseidon_row,
+                [
+                    Wire::for_row(poseidon_row),
+                    Wire::for_row(poseidon_row + POS_ROWS_PER_HASH),
+                ],
+                round_constants,
+            );
+            gates.extend(poseidon_gates);
+
+            // the input state of the permutation is [left, right, 0]
+            for (col, var) in [vars.left, vars.right, vars.zero].into_iter().enumerate() {
+                let mut cycle = builder.cells(var);
+                cycle.push(Wire::new(poseidon_row, col));
+                cycles.push(cycle);
+            }
+            // the current node is the hash of the previous level
+            if level > 0 {
+                let mut cycle = builder.cells(vars.current);
+                cycle.push(Wire::new(row - 1, 0));
+                cycles.push(cycle);
+            }
+        }
+        for cycle in cycles {
+            for (i, cell) in cycle.iter().enumerate() {
+                gates[cell.row - start_row].wires[cell.col] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+        (gates, start_row + depth * MERKLE_ROWS_PER_LEVEL)
+    }
+}
+
+/// Writes the witness of the gates created by
+/// [create_merkle_membership](CircuitGate::create_merkle_membership) from row
+/// `start_row`, for the Merkle path of `leaf` at position `index`, `path` being the
+/// siblings from the leaf to the root. Returns the root.
+///
+/// # Panics
+///
+/// Will panic if `index` does not fit in `path.len()` bits, or if the witness
+/// columns are too short.
+pub fn merkle_membership_witness<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    start_row: usize,
+    params: &'static ArithmeticSpongeParams<F>,
+    leaf: F,
+    path: &[F],
+    index: u64,
+) -> F {
+    assert!(
+        index.checked_shr(path.len() as u32).unwrap_or(0) == 0,
+        "the index {index} does not fit in {} bits",
+        path.len()
+    );
+    let mut current = leaf;
+    for (level, sibling) in path.iter().enumerate() {
+        let row = start_row + level * MERKLE_ROWS_PER_LEVEL;
+        let (builder, vars) = swap::<F>(row);
+        let bit = F::from(index.checked_shr(level as u32).unwrap_or(0) & 1);
+        let rows = builder.witness(&[
+            (vars.current, current),
+            (vars.sibling, *sibling),
+            (vars.bit, bit),
+        ]);
+        for (col, values) in witness.iter_mut().zip(rows.iter()) {
+            col[row..row + SWAP_ROWS].copy_from_slice(values);
+        }
+
+        let (left, right) = if bit.is_zero() {
+            (current, *sibling)
+        } else {
+            (*sibling, current)
+        };
+        let poseidon_row = row + SWAP_ROWS;
+        let mut input = [F::zero(); SPONGE_WIDTH];
+        input[0] = left;
+        input[1] = right;
+        poseidon::generate_witness(poseidon_row, params, witness, input);
+        current = witness[0][poseidon_row + POS_ROWS_PER_HASH];
+    }
+    current
+}
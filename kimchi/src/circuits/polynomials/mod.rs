@@ -7,11 +7,13 @@ pub mod foreign_field_common;
 pub mod foreign_field_mul;
 pub mod generic;
 pub mod keccak;
+pub mod merkle;
 pub mod not;
 pub mod permutation;
 pub mod poseidon;
 pub mod range_check;
 pub mod rot;
+pub mod schnorr;
 pub mod turshi;
 pub mod varbasemul;
 pub mod xor;
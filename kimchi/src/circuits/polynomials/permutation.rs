@@ -54,10 +54,14 @@ use ark_poly::{
     univariate::{DenseOrSparsePolynomial, DensePolynomial},
     DenseUVPolynomial, EvaluationDomain, Evaluations, Polynomial, Radix2EvaluationDomain as D,
 };
+use ark_std::cfg_into_iter;
 use blake2::{Blake2b512, Digest};
-use o1_utils::{ExtendedDensePolynomial, ExtendedEvaluations};
+use o1_utils::{
+    field_helpers::batch_inverse_in_place_parallel, ExtendedDensePolynomial, ExtendedEvaluations,
+};
 use poly_commitment::OpenProof;
 use rand::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use std::array;
 
@@ -351,8 +355,7 @@ impl<F: PrimeField, G: KimchiCurve<ScalarField = F>, OpeningProof: OpenProof<G>>
         let evals8 = &self.column_evaluations.permutation_coefficients8[PERMUTS - 1].evals;
         const STRIDE: usize = 8;
         let n = evals8.len() / STRIDE;
-        let evals = (0..n)
-            .into_par_iter()
+        let evals = cfg_into_iter!(0..n)
             .map(|i| scalar * evals8[STRIDE * i])
             .collect();
         Evaluations::from_vec_and_domain(evals, D::new(n).unwrap())
@@ -476,7 +479,7 @@ impl<F: PrimeField, G: KimchiCurve<ScalarField = F>, OpeningProof: OpenProof<G>>
                 .fold(F::one(), |x, y| x * y);
         }
 
-        ark_ff::fields::batch_inversion::<F>(&mut z[1..n]);
+        batch_inverse_in_place_parallel(&mut z[1..n]);
 
         //~ We randomize the evaluations at `n - zk_rows + 1` and `n - zk_rows + 2` in order to add
         //~ zero-knowledge to the protocol.
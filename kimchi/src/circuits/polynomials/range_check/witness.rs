@@ -9,10 +9,12 @@ use std::array;
 use crate::{
     circuits::{
         polynomial::COLUMNS,
-        polynomials::foreign_field_common::{BigUintForeignFieldHelpers, LIMB_BITS},
-        witness::{init_row, CopyBitsCell, CopyCell, VariableCell, Variables, WitnessCell},
+        polynomials::foreign_field_common::{
+            BigUintForeignFieldHelpers, FieldArrayCompact, LIMB_BITS,
+        },
+        witness::{init_row, ConstantCell, SumCell, Variables, WitnessCell},
     },
-    variable_map, variables,
+    variable_map, variables, witness_cells,
 };
 
 /// Witness layout
@@ -26,55 +28,44 @@ use crate::{
 ///     For example, we can convert the `RangeCheck0` circuit gate into
 ///     a 64-bit lookup by adding two copy constraints to constrain
 ///     columns 1 and 2 to zero.
-fn layout<F: PrimeField>() -> [Vec<Box<dyn WitnessCell<F>>>; 4] {
+///   * In the `compact` layout, the second cell of the `RangeCheck1` row is the
+///     compact limb v01, combining the limbs of the second and third rows,
+///     otherwise it is zero.
+fn layout<F: PrimeField>(compact: bool) -> [Vec<Box<dyn WitnessCell<F>>>; 4] {
+    let v12: Box<dyn WitnessCell<F>> = if compact {
+        SumCell::create(vec![(1, 0, 0), (2, 0, LIMB_BITS as u64)])
+    } else {
+        ConstantCell::create(F::zero())
+    };
     [
         /* row 1, RangeCheck0 row */
         range_check_0_row("v0", 0),
         /* row 2, RangeCheck0 row */
         range_check_0_row("v1", 1),
         /* row 3, RangeCheck1 row */
-        vec![
-            VariableCell::create("v2"),
-            VariableCell::create("v12"), // optional
+        witness_cells![
+            variable("v2"),
+            cell(v12), // optional
             /* 2-bit crumbs (placed here to keep lookup pattern */
             /*               the same as RangeCheck0) */
-            CopyBitsCell::create(2, 0, 86, 88),
+            crumbs(2, 0, 88, 86),
             /* 12-bit plookups */
-            CopyBitsCell::create(2, 0, 74, 86),
-            CopyBitsCell::create(2, 0, 62, 74),
-            CopyBitsCell::create(2, 0, 50, 62),
-            CopyBitsCell::create(2, 0, 38, 50),
+            limbs(2, 0, 86, 38, 12),
             /* 2-bit crumbs */
-            CopyBitsCell::create(2, 0, 36, 38),
-            CopyBitsCell::create(2, 0, 34, 36),
-            CopyBitsCell::create(2, 0, 32, 34),
-            CopyBitsCell::create(2, 0, 30, 32),
-            CopyBitsCell::create(2, 0, 28, 30),
-            CopyBitsCell::create(2, 0, 26, 28),
-            CopyBitsCell::create(2, 0, 24, 26),
-            CopyBitsCell::create(2, 0, 22, 24),
+            crumbs(2, 0, 38, 22),
         ],
         /* row 4, Zero row */
-        vec![
-            CopyBitsCell::create(2, 0, 20, 22),
-            /* 2-bit crumbs (placed here to keep lookup pattern */
-            /*               the same as RangeCheck0) */
-            CopyBitsCell::create(2, 0, 18, 20),
-            CopyBitsCell::create(2, 0, 16, 18),
+        witness_cells![
+            /* 2-bit crumbs (the last two placed here to keep lookup */
+            /*               pattern the same as RangeCheck0) */
+            crumbs(2, 0, 22, 16),
             /* 12-bit plookups (see note about copies in range_check_row) */
-            CopyCell::create(0, 1),
-            CopyCell::create(0, 2),
-            CopyCell::create(1, 1),
-            CopyCell::create(1, 2),
+            copy(0, 1),
+            copy(0, 2),
+            copy(1, 1),
+            copy(1, 2),
             /* 2-bit crumbs */
-            CopyBitsCell::create(2, 0, 14, 16),
-            CopyBitsCell::create(2, 0, 12, 14),
-            CopyBitsCell::create(2, 0, 10, 12),
-            CopyBitsCell::create(2, 0, 8, 10),
-            CopyBitsCell::create(2, 0, 6, 8),
-            CopyBitsCell::create(2, 0, 4, 6),
-            CopyBitsCell::create(2, 0, 2, 4),
-            CopyBitsCell::create(2, 0, 0, 2),
+            crumbs(2, 0, 16, 0),
         ],
     ]
 }
@@ -84,46 +75,29 @@ pub fn range_check_0_row<F: PrimeField>(
     limb_name: &'static str,
     row: usize,
 ) -> Vec<Box<dyn WitnessCell<F>>> {
-    vec![
-        VariableCell::create(limb_name),
+    witness_cells![
+        variable(limb_name),
         /* 12-bit copies */
         // Copy cells are required because we have a limit
         // of 4 lookups per row.  These two lookups are moved to
         // the 4th row, which is a Zero circuit gate, and the
         // RangeCheck1 circuit gate triggers the lookup constraints.
-        CopyBitsCell::create(row, 0, 76, 88),
-        CopyBitsCell::create(row, 0, 64, 76),
+        limbs(row, 0, 88, 64, 12),
         /* 12-bit plookups */
-        CopyBitsCell::create(row, 0, 52, 64),
-        CopyBitsCell::create(row, 0, 40, 52),
-        CopyBitsCell::create(row, 0, 28, 40),
-        CopyBitsCell::create(row, 0, 16, 28),
+        limbs(row, 0, 64, 16, 12),
         /* 2-bit crumbs */
-        CopyBitsCell::create(row, 0, 14, 16),
-        CopyBitsCell::create(row, 0, 12, 14),
-        CopyBitsCell::create(row, 0, 10, 12),
-        CopyBitsCell::create(row, 0, 8, 10),
-        CopyBitsCell::create(row, 0, 6, 8),
-        CopyBitsCell::create(row, 0, 4, 6),
-        CopyBitsCell::create(row, 0, 2, 4),
-        CopyBitsCell::create(row, 0, 0, 2),
+        crumbs(row, 0, 16, 0),
     ]
 }
 
 /// Create a multi range check witness from three 88-bit values: v0, v1 and v2
 pub fn create_multi<F: PrimeField>(v0: F, v1: F, v2: F) -> [Vec<F>; COLUMNS] {
-    let layout = layout();
+    let layout = layout(false);
     let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); 4]);
 
     init_row(&mut witness, 0, 0, &layout, &variables!(v0));
     init_row(&mut witness, 0, 1, &layout, &variables!(v1));
-    init_row(
-        &mut witness,
-        0,
-        2,
-        &layout,
-        &variable_map!("v2" => v2, "v12" => F::zero()),
-    );
+    init_row(&mut witness, 0, 2, &layout, &variables!(v2));
     init_row(&mut witness, 0, 3, &layout, &variables!());
 
     witness
@@ -132,7 +106,7 @@ pub fn create_multi<F: PrimeField>(v0: F, v1: F, v2: F) -> [Vec<F>; COLUMNS] {
 /// Create a multi range check witness from two limbs: v01 (176 bits), v2 (88 bits),
 /// where v2 is the most significant limb and v01 is the least significant limb
 pub fn create_multi_compact<F: PrimeField>(v01: F, v2: F) -> [Vec<F>; COLUMNS] {
-    let layout = layout();
+    let layout = layout(true);
     let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); 4]);
 
     let (v1, v0) = v01.to_biguint().div_rem(&BigUint::two_to_limb());
@@ -142,13 +116,7 @@ pub fn create_multi_compact<F: PrimeField>(v01: F, v2: F) -> [Vec<F>; COLUMNS] {
     init_row(&mut witness, 0, 0, &layout, &variable_map!("v0" => v2));
     init_row(&mut witness, 0, 1, &layout, &variable_map!("v1" => v0));
 
-    init_row(
-        &mut witness,
-        0,
-        2,
-        &layout,
-        &variable_map!("v2" => v1, "v12" => v01),
-    );
+    init_row(&mut witness, 0, 2, &layout, &variable_map!("v2" => v1));
     init_row(&mut witness, 0, 3, &layout, &variables!());
 
     witness
@@ -216,6 +184,15 @@ pub fn extend_multi_from_fe<F: PrimeField>(
     extend_multi(witness, fe.limbs[0], fe.limbs[1], fe.limbs[2]);
 }
 
+/// Extend an existing witness with a compact multi-range-check gadget for ForeignElement,
+/// whose two lowest limbs are combined into a single compact limb
+pub fn extend_multi_compact_from_fe<F: PrimeField>(
+    witness: &mut [Vec<F>; COLUMNS],
+    fe: &ForeignElement<F, LIMB_BITS, 3>,
+) {
+    extend_multi_compact_limbs(witness, &fe.limbs.to_compact_limbs());
+}
+
 /// Extend an existing witness with a single range check witness for foreign field element
 pub fn extend<F: PrimeField>(witness: &mut [Vec<F>; COLUMNS], fe: F) {
     let limbs_witness = create(fe);
@@ -0,0 +1,543 @@
+//! This module implements a gadget verifying a Schnorr signature over Pallas, in
+//! a circuit over its base field.
+//!
+//! A signature `(rx, s)` of the message `msg` by the public key `pk` is valid if
+//! the point `R = [s]G - [e]pk` has `rx` as x coordinate and an even y
+//! coordinate, where `e` is the challenge. As in mina-signer, the challenge is
+//! the hash of `(msg, pk.x, pk.y, rx)` with the kimchi Poseidon hasher of
+//! mina-hasher, whose sponge starts from the domain string of the network, see
+//! [challenge]. The signatures of a message made of a single field element by
+//! mina-signer are thus accepted by the gadget.
+//!
+//! The scalar multiplications use `VarBaseMul` gates, which compute
+//! `[2k + 2^n + 1]T` from the `n` bits of `k`. With `c = 2^n + 1`, the gadget
+//! computes
+//!
+//! ```ignore
+//! A = [2k + c]G    // the prover chooses k such that 2k + c = 2s
+//! B = [2e + c]pk
+//! C = [c]pk        // all the bits are zero
+//! ```
+//!
+//! and checks `A + C = [2]R + B` with `CompleteAdd` gates. The challenge `e` is
+//! computed in the circuit with two Poseidon permutations, and `R` and `pk` are
+//! checked to be on the curve with generic gates.
+//!
+//! The parity of the y coordinate of `R` is checked by decomposing it in crumbs
+//! with `EndoMulScalar` gates: the highest crumb is zero, so that the
+//! decomposition is unique, and the lowest one is twice a bit. The witness
+//! builder rejects the `R` whose y coordinate does not fit in 254 bits, which
+//! happens with negligible probability.
+
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        polynomials::{
+            complete_add::{complete_add_witness, CompleteAddCells},
+            endomul_scalar::{self, BITS_PER_ROW},
+            generic::{GenericGateBuilder, GenericVar},
+            poseidon::{self, POS_ROWS_PER_HASH},
+            varbasemul::{self, BITS_PER_CHUNK},
+        },
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    error::SchnorrError,
+};
+use ark_ec::{models::short_weierstrass::SWCurveConfig, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters, Vesta};
+use mina_hasher::{Hashable, Hasher, ROInput};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, Sponge},
+};
+use mina_signer::NetworkId;
+
+/// Number of bits of the scalars of the scalar multiplications
+const SCALAR_BITS: usize = 255;
+
+/// Number of rows of a scalar multiplication
+const VBMUL_ROWS: usize = 2 * SCALAR_BITS / BITS_PER_CHUNK;
+
+/// Number of rows of generic gates
+const GENERIC_ROWS: usize = 12;
+
+/// Number of rows of a Poseidon permutation, including its output row
+const POSEIDON_ROWS: usize = POS_ROWS_PER_HASH + 1;
+
+/// Number of bits of the decomposition of the y coordinate of `R`
+const PARITY_BITS: usize = 256;
+
+/// Number of rows of the decomposition of the y coordinate of `R`
+const PARITY_ROWS: usize = PARITY_BITS / BITS_PER_ROW;
+
+/// Number of rows of the gadget
+pub const SCHNORR_ROWS: usize = GENERIC_ROWS + 2 * POSEIDON_ROWS + 4 + 3 * VBMUL_ROWS + PARITY_ROWS;
+
+/// A Schnorr signature over Pallas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub rx: Fp,
+    pub s: Fq,
+}
+
+/// The cells of the gadget holding its inputs, to wire them to the rest of the
+/// circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SchnorrCells {
+    /// The coordinates of the public key
+    pub pk: [Wire; 2],
+    /// The x coordinate of the point `R` of the signature
+    pub rx: Wire,
+    /// The message
+    pub msg: Wire,
+}
+
+impl SchnorrCells {
+    /// Returns the cells of the gadget starting at row `start_row`
+    pub fn at(start_row: usize) -> Self {
+        let layout = Layout::new(start_row);
+        // the positions of the cells do not depend on the network
+        let (builder, vars) = generic_part(layout.generic, [Fp::zero(); 3]);
+        SchnorrCells {
+            pk: CompleteAddCells::at(layout.double_pk).p1,
+            rx: CompleteAddCells::at(layout.double_r).p1[0],
+            msg: builder.cells(vars.msg)[0],
+        }
+    }
+}
+
+/// The first row of each part of the gadget
+struct Layout {
+    generic: usize,
+    poseidon: [usize; 2],
+    double_pk: usize,
+    add_a_c: usize,
+    double_r: usize,
+    add_r_b: usize,
+    mul_a: usize,
+    mul_b: usize,
+    mul_c: usize,
+    parity: usize,
+}
+
+impl Layout {
+    fn new(start_row: usize) -> Self {
+        let poseidon = start_row + GENERIC_ROWS;
+        let add = poseidon + 2 * POSEIDON_ROWS;
+        let mul = add + 4;
+        Layout {
+            generic: start_row,
+            poseidon: [poseidon, poseidon + POSEIDON_ROWS],
+            double_pk: add,
+            add_a_c: add + 1,
+            double_r: add + 2,
+            add_r_b: add + 3,
+            mul_a: mul,
+            mul_b: mul + VBMUL_ROWS,
+            mul_c: mul + 2 * VBMUL_ROWS,
+            parity: mul + 3 * VBMUL_ROWS,
+        }
+    }
+}
+
+/// The values of the generic gates
+struct Vars {
+    msg: GenericVar,
+    pk: [GenericVar; 2],
+    r: [GenericVar; 2],
+    /// The input of the first permutation
+    h0: [GenericVar; 3],
+    /// The first two elements of the state after the first permutation
+    h1: [GenericVar; 2],
+    /// The first two elements of the input of the second permutation
+    h2: [GenericVar; 2],
+    zero: GenericVar,
+    /// The coefficient `b` of the curve, and `y^2 - x^3` for `pk` and `R`
+    coeff_b: [GenericVar; 3],
+    g: [GenericVar; 2],
+    g2: [GenericVar; 2],
+    /// The second bit of the y coordinate of `R`, and twice its value, which is
+    /// the lowest crumb of the coordinate
+    r_bit: GenericVar,
+    r_crumb: GenericVar,
+}
+
+/// Returns the generic gates absorbing the inputs in the sponge, whose state
+/// after absorbing the domain string is `domain`, checking that `pk` and `R`
+/// are on the curve, and holding the constants of the gadget
+fn generic_part(row: usize, domain: [Fp; 3]) -> (GenericGateBuilder<Fp>, Vars) {
+    let mut builder = GenericGateBuilder::new(row);
+    let msg = builder.create_input();
+    let pk = [builder.create_input(), builder.create_input()];
+    let r = [builder.create_input(), builder.create_input()];
+    let h1 = [builder.create_input(), builder.create_input()];
+    let domain = domain.map(|x| builder.create_constant(x));
+    let h0 = [
+        builder.create_add(domain[0], msg),
+        builder.create_add(domain[1], pk[0]),
+        domain[2],
+    ];
+    let h2 = [
+        builder.create_add(h1[0], pk[1]),
+        builder.create_add(h1[1], r[0]),
+    ];
+
+    let zero = builder.create_constant(Fp::zero());
+    let g = Pallas::generator();
+    let g2: Pallas = (g + g).into();
+    let g = [builder.create_constant(g.x), builder.create_constant(g.y)];
+    let g2 = [builder.create_constant(g2.x), builder.create_constant(g2.y)];
+
+    let mut on_curve = |[x, y]: [GenericVar; 2]| {
+        let x2 = builder.create_mul(x, x);
+        let x3 = builder.create_mul(x2, x);
+        let y2 = builder.create_mul(y, y);
+        builder.create_sub(y2, x3)
+    };
+    let (pk_b, r_b) = (on_curve(pk), on_curve(r));
+    let coeff_b = [
+        builder.create_constant(PallasParameters::COEFF_B),
+        pk_b,
+        r_b,
+    ];
+
+    let r_bit = builder.create_input();
+    builder.create_boolean_check(r_bit);
+    let r_crumb = builder.create_add(r_bit, r_bit);
+    assert_eq!(builder.num_rows(), GENERIC_ROWS);
+
+    (
+        builder,
+        Vars {
+            msg,
+            pk,
+            r,
+            h0,
+            h1,
+            h2,
+            zero,
+            coeff_b,
+            g,
+            g2,
+            r_bit,
+            r_crumb,
+        },
+    )
+}
+
+/// Returns the domain string of the signatures of the network `network_id`,
+/// as used by Mina
+pub fn signature_domain(network_id: NetworkId) -> &'static str {
+    match network_id {
+        NetworkId::MAINNET => "MinaSignatureMainnet",
+        NetworkId::TESTNET => "CodaSignature",
+    }
+}
+
+/// Returns the state of the sponge once the domain string of `network_id` is
+/// absorbed, as mina-hasher initializes it
+fn domain_state(network_id: NetworkId) -> [Fp; 3] {
+    let mut sponge =
+        ArithmeticSponge::<Fp, PlonkSpongeConstantsKimchi>::new(Vesta::sponge_params());
+    sponge.absorb(&[mina_hasher::domain_prefix_to_field(
+        signature_domain(network_id).to_string(),
+    )]);
+    sponge.squeeze();
+    [sponge.state[0], sponge.state[1], sponge.state[2]]
+}
+
+/// Returns the cells of the column `col` of the `VarBaseMul` gates of a scalar
+/// multiplication starting at row `row`
+fn vbmul_cells(row: usize, col: usize) -> impl Iterator<Item = Wire> {
+    (0..VBMUL_ROWS / 2).map(move |chunk| Wire::new(row + 2 * chunk, col))
+}
+
+/// Returns the cells of the output of a scalar multiplication starting at row
+/// `row`
+fn vbmul_output(row: usize) -> [Wire; 2] {
+    [
+        Wire::new(row + VBMUL_ROWS - 1, 0),
+        Wire::new(row + VBMUL_ROWS - 1, 1),
+    ]
+}
+
+/// Returns the cell of the scalar of a scalar multiplication starting at row
+/// `row`
+fn vbmul_scalar(row: usize) -> Wire {
+    Wire::new(row + VBMUL_ROWS - 2, 5)
+}
+
+/// Creates the gates verifying a Schnorr signature over Pallas for the network
+/// `network_id`, starting at row `start_row`. The public key, the signature and
+/// the message are in the cells given by [SchnorrCells::at], and the witness is
+/// built with [witness].
+///
+/// Returns the gates and the next row of the circuit.
+pub fn create_verify(start_row: usize, network_id: NetworkId) -> (Vec<CircuitGate<Fp>>, usize) {
+    let layout = Layout::new(start_row);
+    let (builder, vars) = generic_part(layout.generic, domain_state(network_id));
+    let mut gates = builder.gates();
+
+    let round_constants = &*Vesta::sponge_params().round_constants;
+    for row in layout.poseidon {
+        let (poseidon_gates, _) = CircuitGate::create_poseidon_gadget(
+            row,
+            [Wire::for_row(row), Wire::for_row(row + POS_ROWS_PER_HASH)],
+            round_constants,
+        );
+        gates.extend(poseidon_gates);
+    }
+    let adds = [
+        layout.double_pk,
+        layout.add_a_c,
+        layout.double_r,
+        layout.add_r_b,
+    ];
+    for row in adds {
+        gates.push(CircuitGate::create_complete_add(Wire::for_row(row)));
+    }
+    for row in [layout.mul_a, layout.mul_b, layout.mul_c] {
+        gates.extend(CircuitGate::create_vbmul_bits(row, SCALAR_BITS));
+    }
+    gates.extend(CircuitGate::create_endomul_scalar(
+        layout.parity,
+        PARITY_BITS,
+    ));
+
+    let [double_pk, add_a_c, double_r, add_r_b] = adds.map(CompleteAddCells::at);
+    let [poseidon1, poseidon2] = layout.poseidon;
+    let hash_output = |row: usize, col: usize| Wire::new(row + POS_ROWS_PER_HASH, col);
+    let parity_last = layout.parity + PARITY_ROWS - 1;
+    let mut cycles: Vec<Vec<Wire>> = vec![];
+    for i in 0..3 {
+        let mut cycle = builder.cells(vars.h0[i]);
+        cycle.push(Wire::new(poseidon1, i));
+        cycles.push(cycle);
+    }
+    for i in 0..2 {
+        // the public key is doubled, and is the base of B and C
+        let mut cycle = builder.cells(vars.pk[i]);
+        cycle.extend([double_pk.p1[i], double_pk.p2[i]]);
+        cycle.extend(vbmul_cells(layout.mul_b, i));
+        cycle.extend(vbmul_cells(layout.mul_c, i));
+        cycles.push(cycle);
+
+        // R is doubled, and its y coordinate is decomposed
+        let mut cycle = builder.cells(vars.r[i]);
+        cycle.extend([double_r.p1[i], double_r.p2[i]]);
+        if i == 1 {
+            cycle.push(Wire::new(parity_last, 1));
+        }
+        cycles.push(cycle);
+
+        // the state of the sponge between the two permutations
+        let mut cycle = builder.cells(vars.h1[i]);
+        cycle.push(hash_output(poseidon1, i));
+        cycles.push(cycle);
+        let mut cycle = builder.cells(vars.h2[i]);
+        cycle.push(Wire::new(poseidon2, i));
+        cycles.push(cycle);
+
+        // G is the base of A, and the accumulators start at [2]T
+        let mut cycle = builder.cells(vars.g[i]);
+        cycle.extend(vbmul_cells(layout.mul_a, i));
+        cycles.push(cycle);
+        let mut cycle = builder.cells(vars.g2[i]);
+        cycle.push(Wire::new(layout.mul_a, 2 + i));
+        cycles.push(cycle);
+        cycles.push(vec![
+            double_pk.output[i],
+            Wire::new(layout.mul_b, 2 + i),
+            Wire::new(layout.mul_c, 2 + i),
+        ]);
+
+        // A + C = [2]R + B
+        cycles.push(vec![vbmul_output(layout.mul_a)[i], add_a_c.p1[i]]);
+        cycles.push(vec![vbmul_output(layout.mul_c)[i], add_a_c.p2[i]]);
+        cycles.push(vec![double_r.output[i], add_r_b.p1[i]]);
+        cycles.push(vec![vbmul_output(layout.mul_b)[i], add_r_b.p2[i]]);
+        cycles.push(vec![add_a_c.output[i], add_r_b.output[i]]);
+    }
+    cycles.push(vec![hash_output(poseidon1, 2), Wire::new(poseidon2, 2)]);
+    // the challenge is the scalar of B
+    cycles.push(vec![hash_output(poseidon2, 0), vbmul_scalar(layout.mul_b)]);
+
+    let mut cycle = builder.cells(vars.zero);
+    // the scalars start at 0, and the one of C is 0
+    cycle.extend([layout.mul_a, layout.mul_b, layout.mul_c].map(|row| Wire::new(row, 4)));
+    cycle.push(vbmul_scalar(layout.mul_c));
+    // no output is the point at infinity
+    cycle.extend([double_pk, add_a_c, double_r, add_r_b].map(|cells| cells.inf));
+    // the decomposition of the y coordinate of R starts at 0, and its highest
+    // crumb is 0
+    cycle.extend([Wire::new(layout.parity, 0), Wire::new(layout.parity, 6)]);
+    cycles.push(cycle);
+
+    // the lowest crumb of the y coordinate of R is even
+    let mut cycle = builder.cells(vars.r_crumb);
+    cycle.push(Wire::new(parity_last, 13));
+    cycles.push(cycle);
+
+    // pk and R are on the curve
+    cycles.push(
+        vars.coeff_b
+            .iter()
+            .flat_map(|var| builder.cells(*var))
+            .collect(),
+    );
+
+    for cycle in cycles {
+        for (i, cell) in cycle.iter().enumerate() {
+            gates[cell.row - start_row].wires[cell.col] = cycle[(i + 1) % cycle.len()];
+        }
+    }
+    (gates, start_row + SCHNORR_ROWS)
+}
+
+/// The input of the hash of a challenge, laid out as in mina-signer
+#[derive(Clone)]
+struct ChallengeInput {
+    msg: Fp,
+    pk: Pallas,
+    rx: Fp,
+}
+
+impl Hashable for ChallengeInput {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new()
+            .append_field(self.msg)
+            .append_field(self.pk.x)
+            .append_field(self.pk.y)
+            .append_field(self.rx)
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        Some(signature_domain(network_id).to_string())
+    }
+}
+
+/// Returns the challenge of a signature of `msg` by `pk` with the point `R` of x
+/// coordinate `rx`, for the network `network_id`
+pub fn challenge(pk: Pallas, rx: Fp, msg: Fp, network_id: NetworkId) -> Fp {
+    mina_hasher::create_kimchi::<ChallengeInput>(network_id).hash(&ChallengeInput { msg, pk, rx })
+}
+
+/// Returns the [SCALAR_BITS] lower bits of `x`, from the most significant one
+fn bits_msb<B: BigInteger>(x: B) -> Vec<bool> {
+    let mut bits = x.to_bits_le();
+    bits.truncate(SCALAR_BITS);
+    bits.reverse();
+    bits
+}
+
+/// Writes the witness of the gates created by [create_verify] from row
+/// `start_row`, for the signature `sig` of `msg` by `pk` for the network
+/// `network_id`.
+///
+/// # Errors
+///
+/// Will give error if the public key is the point at infinity, if the
+/// signature does not verify, or if the y coordinate of its point `R` does not
+/// fit in 254 bits.
+pub fn witness(
+    witness: &mut [Vec<Fp>; COLUMNS],
+    start_row: usize,
+    pk: Pallas,
+    sig: &Signature,
+    msg: Fp,
+    network_id: NetworkId,
+) -> Result<(), SchnorrError> {
+    if pk.infinity {
+        return Err(SchnorrError::InfinityPublicKey);
+    }
+    let e = challenge(pk, sig.rx, msg, network_id.clone());
+    let e_scalar = Fq::from_bigint(e.into_bigint()).unwrap();
+    let r = (Pallas::generator() * sig.s - pk * e_scalar).into_affine();
+    if r.infinity || r.x != sig.rx || r.y.into_bigint().is_odd() {
+        return Err(SchnorrError::InvalidSignature);
+    }
+    let r_bits = r.y.into_bigint().to_bits_le();
+    if r_bits[PARITY_BITS - 2..].iter().any(|bit| *bit) {
+        return Err(SchnorrError::LargeCoordinate);
+    }
+
+    let layout = Layout::new(start_row);
+    let params = Vesta::sponge_params();
+    let [poseidon1, poseidon2] = layout.poseidon;
+    let domain = domain_state(network_id);
+    poseidon::generate_witness(
+        poseidon1,
+        params,
+        witness,
+        [domain[0] + msg, domain[1] + pk.x, domain[2]],
+    );
+    let h1: [Fp; 3] = std::array::from_fn(|col| witness[col][poseidon1 + POS_ROWS_PER_HASH]);
+    poseidon::generate_witness(
+        poseidon2,
+        params,
+        witness,
+        [h1[0] + pk.y, h1[1] + r.x, h1[2]],
+    );
+
+    let (builder, vars) = generic_part(layout.generic, domain);
+    let rows = builder.witness(&[
+        (vars.msg, msg),
+        (vars.pk[0], pk.x),
+        (vars.pk[1], pk.y),
+        (vars.r[0], r.x),
+        (vars.r[1], r.y),
+        (vars.h1[0], h1[0]),
+        (vars.h1[1], h1[1]),
+        (vars.r_bit, Fp::from(u64::from(r_bits[1]))),
+    ]);
+    for (col, values) in witness.iter_mut().zip(rows.iter()) {
+        col[layout.generic..layout.generic + GENERIC_ROWS].copy_from_slice(values);
+    }
+
+    // 2k + c = 2s, with c = 2^n + 1
+    let g = Pallas::generator();
+    let g2: Pallas = (g + g).into();
+    let pk2: Pallas = (pk + pk).into();
+    let c = Fq::from(2u64).pow([SCALAR_BITS as u64]) + Fq::one();
+    let k = sig.s - c * Fq::from(2u64).inverse().unwrap();
+    let point = |res: varbasemul::VarbaseMulResult<Fp>| Pallas::new_unchecked(res.acc.0, res.acc.1);
+    let a = point(varbasemul::witness_bits(
+        witness,
+        layout.mul_a,
+        (g.x, g.y),
+        &bits_msb(k.into_bigint()),
+        (g2.x, g2.y),
+    ));
+    let b = point(varbasemul::witness_bits(
+        witness,
+        layout.mul_b,
+        (pk.x, pk.y),
+        &bits_msb(e.into_bigint()),
+        (pk2.x, pk2.y),
+    ));
+    let c = point(varbasemul::witness_bits(
+        witness,
+        layout.mul_c,
+        (pk.x, pk.y),
+        &[false; SCALAR_BITS],
+        (pk2.x, pk2.y),
+    ));
+
+    let r2: Pallas = (r + r).into();
+    for (row, p1, p2) in [
+        (layout.double_pk, pk, pk),
+        (layout.add_a_c, a, c),
+        (layout.double_r, r, r),
+        (layout.add_r_b, r2, b),
+    ] {
+        complete_add_witness(witness, row, p1, p2).map_err(SchnorrError::CompleteAdd)?;
+    }
+
+    // only the decomposition of the coordinate is used, not its endo-scalar
+    endomul_scalar::witness(witness, layout.parity, r.y, Fp::zero(), PARITY_BITS);
+    Ok(())
+}
@@ -15,7 +15,7 @@ use crate::circuits::{
     berkeley_columns::{BerkeleyChallengeTerm, Column},
     expr::{constraints::ExprOps, Cache, Variable as VariableGen},
     gate::{CircuitGate, CurrOrNext, GateType},
-    wires::{GateWires, COLUMNS},
+    wires::{GateWires, Wire, COLUMNS},
 };
 use ark_ff::{FftField, PrimeField};
 use std::marker::PhantomData;
@@ -139,6 +139,48 @@ impl<F: PrimeField> CircuitGate<F> {
         ]
     }
 
+    /// Creates the gates of a variable base scalar multiplication by a scalar of
+    /// `num_bits` bits, starting at row `start_row`. Each `VarBaseMul` gate and the
+    /// `Zero` gate after it handle 5 bits, so the scalar is padded with leading
+    /// zero bits to a multiple of 5, see [witness_bits].
+    ///
+    /// The consecutive gates are wired together: the base point is the same in all
+    /// of them, and the accumulator and the scalar of a gate are the inputs of the
+    /// next one.
+    pub fn create_vbmul_bits(start_row: usize, num_bits: usize) -> Vec<Self> {
+        let chunks = num_bits_padded(num_bits) / BITS_PER_CHUNK;
+        let mut wires: Vec<GateWires> = (0..2 * chunks)
+            .map(|i| Wire::for_row(start_row + i))
+            .collect();
+        let mut connect = |cycle: Vec<Wire>| {
+            for (i, wire) in cycle.iter().enumerate() {
+                wires[wire.row - start_row][wire.col] = cycle[(i + 1) % cycle.len()];
+            }
+        };
+        if chunks > 1 {
+            // the base point (xT, yT)
+            for col in 0..2 {
+                connect(
+                    (0..chunks)
+                        .map(|c| Wire::new(start_row + 2 * c, col))
+                        .collect(),
+                );
+            }
+        }
+        for c in 1..chunks {
+            let row = start_row + 2 * c;
+            // (x5, y5) of the previous chunk is (x0, y0)
+            connect(vec![Wire::new(row - 1, 0), Wire::new(row, 2)]);
+            connect(vec![Wire::new(row - 1, 1), Wire::new(row, 3)]);
+            // n' of the previous chunk is n
+            connect(vec![Wire::new(row - 2, 5), Wire::new(row, 4)]);
+        }
+        wires
+            .chunks(2)
+            .flat_map(|wires| Self::create_vbmul(&[wires[0], wires[1]]))
+            .collect()
+    }
+
     /// Verify the `GateType::VarBaseMul`(TODO)
     ///
     /// # Errors
@@ -374,7 +416,7 @@ pub fn witness<F: FftField + std::fmt::Display>(
 ) -> VarbaseMulResult<F> {
     let layout = Layout::create();
     let bits: Vec<_> = bits.iter().map(|b| F::from(u64::from(*b))).collect();
-    let bits_per_chunk = 5;
+    let bits_per_chunk = BITS_PER_CHUNK;
     assert_eq!(bits_per_chunk * (bits.len() / bits_per_chunk), bits.len());
 
     let mut acc = acc0;
@@ -404,6 +446,32 @@ pub fn witness<F: FftField + std::fmt::Display>(
     VarbaseMulResult { acc, n: n_acc }
 }
 
+/// Number of bits of the scalar handled by a `VarBaseMul` gate
+pub const BITS_PER_CHUNK: usize = 5;
+
+/// Returns the number of bits of a scalar of `num_bits` bits once padded to a
+/// multiple of [BITS_PER_CHUNK]
+pub fn num_bits_padded(num_bits: usize) -> usize {
+    (num_bits + BITS_PER_CHUNK - 1) / BITS_PER_CHUNK * BITS_PER_CHUNK
+}
+
+/// Apply the `witness` value for a scalar of any number of bits, given from the
+/// most significant one, for the gates created by
+/// [create_vbmul_bits](CircuitGate::create_vbmul_bits). The scalar is padded
+/// with leading zero bits to a multiple of [BITS_PER_CHUNK], so the result is
+/// `acc0` scaled as for a scalar of [num_bits_padded] bits.
+pub fn witness_bits<F: FftField + std::fmt::Display>(
+    w: &mut [Vec<F>; COLUMNS],
+    row0: usize,
+    base: (F, F),
+    bits: &[bool],
+    acc0: (F, F),
+) -> VarbaseMulResult<F> {
+    let mut padded = vec![false; num_bits_padded(bits.len()) - bits.len()];
+    padded.extend_from_slice(bits);
+    witness(w, row0, base, &padded, acc0)
+}
+
 /// Implementation of the `VarbaseMul` gate
 #[derive(Default)]
 pub struct VarbaseMul<F>(PhantomData<F>);
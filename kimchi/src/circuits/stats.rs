@@ -0,0 +1,189 @@
+//! This module implements statistics of a circuit, to help circuit developers
+//! plan the capacity of their circuits.
+
+use crate::circuits::{
+    constraints::ConstraintSystem,
+    gate::{CircuitGate, CurrOrNext, GateType},
+    lookup::lookups::{LookupPattern, LookupTableID},
+    wires::{Wire, COLUMNS, PERMUTS},
+};
+use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use std::{collections::BTreeMap, fmt};
+
+/// A rough estimate of the work of the prover, for a circuit whose
+/// polynomials are not chunked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvingCost {
+    /// The number of polynomials committed to by the prover
+    pub commitments: usize,
+    /// The number of points of the multi-scalar multiplications of these
+    /// commitments, i.e. `commitments` times the size of the domain
+    pub msm_points: usize,
+    /// The size of the domain over which the quotient polynomial is computed
+    pub quotient_domain_size: usize,
+}
+
+/// Statistics of a circuit, returned by [`ConstraintSystem::stats`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// The number of rows used by the gates of the circuit, without the
+    /// padding up to the size of the domain
+    pub rows: usize,
+    /// The number of public inputs
+    pub public: usize,
+    /// The number of rows reserved for zero-knowledge
+    pub zk_rows: u64,
+    /// The size of the evaluation domain
+    pub domain_size: usize,
+    /// The number of gates of each type
+    pub gates: BTreeMap<GateType, usize>,
+    /// The number of permutation cycles with at least two cells
+    pub permutation_cycles: usize,
+    /// The number of cells in these cycles
+    pub permuted_cells: usize,
+    /// The number of lookups in each fixed table, by table id
+    pub table_lookups: BTreeMap<i32, usize>,
+    /// The number of lookups whose table is given by the witness, i.e. the
+    /// lookups of the `Lookup` gates
+    pub witness_table_lookups: usize,
+    /// The estimated proving cost
+    pub cost: ProvingCost,
+}
+
+/// Returns the number of permutation cycles of the gates with at least two
+/// cells, and the number of cells in these cycles
+fn permutation_cycles<F: PrimeField>(gates: &[CircuitGate<F>]) -> (usize, usize) {
+    let mut visited = vec![[false; PERMUTS]; gates.len()];
+    let mut cycles = 0;
+    let mut cells = 0;
+    for row in 0..gates.len() {
+        for col in 0..PERMUTS {
+            if visited[row][col] {
+                continue;
+            }
+
+            let mut len = 0;
+            let mut cell = Wire::new(row, col);
+            while !visited[cell.row][cell.col] {
+                visited[cell.row][cell.col] = true;
+                len += 1;
+                cell = gates[cell.row].wires[cell.col];
+            }
+
+            if len > 1 {
+                cycles += 1;
+                cells += len;
+            }
+        }
+    }
+    (cycles, cells)
+}
+
+impl<F: PrimeField> ConstraintSystem<F> {
+    /// Returns the number of rows of the circuit before its padding up to the
+    /// size of the domain. The trailing zero gates whose cells are not wired
+    /// cannot be told apart from the padding, and are not counted.
+    pub fn unpadded_rows(&self) -> usize {
+        self.gates
+            .iter()
+            .enumerate()
+            .rposition(|(row, gate)| {
+                gate.typ != GateType::Zero
+                    || !gate.coeffs.is_empty()
+                    || gate.wires != Wire::for_row(row)
+            })
+            .map_or(0, |row| row + 1)
+    }
+
+    /// Returns the statistics of the circuit, without the padding
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a wire of a gate points out of the circuit.
+    pub fn stats(&self) -> CircuitStats {
+        let rows = self.unpadded_rows();
+        let circuit = &self.gates[..rows];
+
+        let mut gates = BTreeMap::new();
+        for gate in circuit {
+            *gates.entry(gate.typ).or_insert(0) += 1;
+        }
+
+        let (permutation_cycles, permuted_cells) = permutation_cycles(circuit);
+
+        let mut table_lookups = BTreeMap::new();
+        let mut witness_table_lookups = 0;
+        for gate in circuit {
+            for curr_or_next in [CurrOrNext::Curr, CurrOrNext::Next] {
+                let Some(pattern) = LookupPattern::from_gate(gate.typ, curr_or_next) else {
+                    continue;
+                };
+                for lookup in pattern.lookups::<F>() {
+                    match lookup.table_id {
+                        LookupTableID::Constant(id) => *table_lookups.entry(id).or_insert(0) += 1,
+                        LookupTableID::WitnessColumn(_) => witness_table_lookups += 1,
+                    }
+                }
+            }
+        }
+
+        // the witness columns, the permutation aggregation and the chunks of
+        // the quotient polynomial
+        let mut commitments = COLUMNS + 1 + PERMUTS;
+        if let Some(lcs) = &self.lookup_constraint_system {
+            // the sorted polynomials and the lookup aggregation
+            commitments += lcs.configuration.lookup_info.max_per_row + 2;
+            if lcs.runtime_tables.is_some() {
+                commitments += 1;
+            }
+        }
+        let domain_size = self.domain.d1.size();
+
+        CircuitStats {
+            rows,
+            public: self.public,
+            zk_rows: self.zk_rows,
+            domain_size,
+            gates,
+            permutation_cycles,
+            permuted_cells,
+            table_lookups,
+            witness_table_lookups,
+            cost: ProvingCost {
+                commitments,
+                msm_points: commitments * domain_size,
+                quotient_domain_size: self.domain.d8.size(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "rows:               {} ({} public, {} zk, domain of {})",
+            self.rows, self.public, self.zk_rows, self.domain_size
+        )?;
+        writeln!(f, "gates:")?;
+        for (typ, count) in &self.gates {
+            writeln!(f, "  {:<18}{count}", format!("{typ:?}"))?;
+        }
+        writeln!(
+            f,
+            "permutation:        {} cycles, {} cells",
+            self.permutation_cycles, self.permuted_cells
+        )?;
+        writeln!(f, "lookups:")?;
+        for (id, count) in &self.table_lookups {
+            writeln!(f, "  table {id:<12}{count}")?;
+        }
+        writeln!(f, "  witness tables    {}", self.witness_table_lookups)?;
+        write!(
+            f,
+            "proving cost:       {} commitments, {} MSM points, quotient domain of {}",
+            self.cost.commitments, self.cost.msm_points, self.cost.quotient_domain_size
+        )
+    }
+}
@@ -0,0 +1,295 @@
+//! Property-based generators of gadgets and of their witnesses.
+//!
+//! Each generator is a [proptest] strategy that creates the gates of a gadget
+//! together with the witness of random valid inputs, as a [`GadgetCase`].
+//! Gadget authors can reuse them to test the gadgets they build on top of
+//! these ones, e.g.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn xor_is_provable(case in generators::xor_gadget::<Fp>(64)) {
+//!         case.prove_and_verify::<Vesta, BaseSponge, ScalarSponge>().unwrap();
+//!     }
+//! }
+//! ```
+//!
+//! The generators are available to the other crates with the
+//! `proptest-generators` feature.
+
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        polynomials::{
+            and,
+            foreign_field_add::witness::{self as ffadd, FFOps},
+            generic::GenericGateSpec,
+            poseidon::{self, POS_ROWS_PER_HASH},
+            range_check,
+            rot::{self, RotMode},
+            xor,
+        },
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    plonk_sponge::FrSponge,
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test,
+    verifier::verify,
+};
+use ark_ff::{One, PrimeField, Zero};
+use groupmap::GroupMap;
+use mina_poseidon::FqSponge;
+use num_bigint::BigUint;
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof};
+use proptest::{collection::vec, prelude::*};
+use std::array;
+
+/// The number of bits of the limbs checked by the multi range check gadget
+const LIMB_BITS: usize = 88;
+
+/// A gadget and a witness that satisfies it
+#[derive(Debug, Clone)]
+pub struct GadgetCase<F: PrimeField> {
+    /// The gates of the gadget, starting at row 0
+    pub gates: Vec<CircuitGate<F>>,
+    /// The witness of the gadget
+    pub witness: [Vec<F>; COLUMNS],
+    /// The public input, i.e. the first cells of the first column of the witness
+    pub public: Vec<F>,
+}
+
+impl<F: PrimeField> GadgetCase<F> {
+    /// Creates a proof of the witness and verifies it
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the witness does not satisfy the gates, or if the
+    /// proof cannot be created or is not valid.
+    pub fn prove_and_verify<G, EFqSponge, EFrSponge>(&self) -> Result<(), String>
+    where
+        G: KimchiCurve<ScalarField = F>,
+        G::BaseField: PrimeField,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, F>,
+        EFrSponge: FrSponge<F>,
+    {
+        let index = new_index_for_test::<G>(self.gates.clone(), self.public.len());
+        index
+            .verify(&self.witness, &self.public)
+            .map_err(|e| format!("the witness is not satisfied: {e:?}"))?;
+
+        let group_map = <G as CommitmentCurve>::Map::setup();
+        let proof = ProverProof::create::<EFqSponge, EFrSponge, _>(
+            &group_map,
+            self.witness.clone(),
+            &[],
+            &index,
+            &mut rand::rngs::OsRng,
+        )
+        .map_err(|e| e.to_string())?;
+
+        verify::<G, EFqSponge, EFrSponge, OpeningProof<G>>(
+            &group_map,
+            &index.verifier_index(),
+            &proof,
+            &self.public,
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// Returns a strategy of uniformly distributed field elements
+pub fn field_element<F: PrimeField>() -> impl Strategy<Value = F> {
+    let bytes = (F::MODULUS_BIT_SIZE as usize + 7) / 8;
+    // the extra bytes make the modular reduction close to uniform
+    vec(any::<u8>(), bytes + 16).prop_map(|bytes| F::from_le_bytes_mod_order(&bytes))
+}
+
+/// Returns a strategy of integers of at most `bits` bits
+pub fn biguint_with_bits(bits: usize) -> impl Strategy<Value = BigUint> {
+    vec(any::<u8>(), (bits + 7) / 8)
+        .prop_map(move |bytes| BigUint::from_bytes_le(&bytes) % (BigUint::one() << bits))
+}
+
+/// Returns a strategy of field elements of at most `bits` bits
+pub fn field_with_bits<F: PrimeField>(bits: usize) -> impl Strategy<Value = F> {
+    assert!(bits < F::MODULUS_BIT_SIZE as usize);
+    biguint_with_bits(bits).prop_map(F::from)
+}
+
+/// Returns a strategy of circuits of double generic gates, each one adding
+/// and multiplying two pairs of random values, after up to 3 public inputs
+pub fn generic_gadget<F: PrimeField>() -> impl Strategy<Value = GadgetCase<F>> {
+    (
+        vec(field_element::<F>(), 0..4),
+        vec(array::uniform4(field_element::<F>()), 1..8),
+    )
+        .prop_map(|(public, rows)| {
+            let mut gates = vec![];
+            let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![]);
+
+            for p in &public {
+                let row = gates.len();
+                gates.push(CircuitGate::create_generic_gadget(
+                    Wire::for_row(row),
+                    GenericGateSpec::Pub,
+                    None,
+                ));
+                witness[0].push(*p);
+                for col in witness.iter_mut().skip(1) {
+                    col.push(F::zero());
+                }
+            }
+
+            for [a, b, c, d] in rows {
+                let row = gates.len();
+                let add = GenericGateSpec::Add {
+                    left_coeff: None,
+                    right_coeff: None,
+                    output_coeff: None,
+                };
+                let mul = GenericGateSpec::Mul {
+                    output_coeff: None,
+                    mul_coeff: None,
+                };
+                gates.push(CircuitGate::create_generic_gadget(
+                    Wire::for_row(row),
+                    add,
+                    Some(mul),
+                ));
+                let values = [a, b, a + b, c, d, c * d];
+                for (col, value) in witness.iter_mut().enumerate() {
+                    col.push(values.get(col).copied().unwrap_or_else(F::zero));
+                }
+            }
+
+            GadgetCase {
+                gates,
+                witness,
+                public,
+            }
+        })
+}
+
+/// Returns a strategy of Poseidon permutations of random inputs
+pub fn poseidon_gadget<G: KimchiCurve>() -> impl Strategy<Value = GadgetCase<G::ScalarField>> {
+    array::uniform3(field_element::<G::ScalarField>()).prop_map(|input| {
+        let params = G::sponge_params();
+        let (gates, _) = CircuitGate::create_poseidon_gadget(
+            0,
+            [Wire::for_row(0), Wire::for_row(POS_ROWS_PER_HASH)],
+            &params.round_constants,
+        );
+
+        let mut witness: [Vec<G::ScalarField>; COLUMNS] =
+            array::from_fn(|_| vec![G::ScalarField::zero(); POS_ROWS_PER_HASH + 1]);
+        poseidon::generate_witness(0, params, &mut witness, input);
+
+        GadgetCase {
+            gates,
+            witness,
+            public: vec![],
+        }
+    })
+}
+
+/// Returns a strategy of multi range checks of three random 88-bit limbs
+pub fn range_check_gadget<F: PrimeField>() -> impl Strategy<Value = GadgetCase<F>> {
+    array::uniform3(field_with_bits::<F>(LIMB_BITS)).prop_map(|[v0, v1, v2]| {
+        let (_, gates) = CircuitGate::create_multi_range_check(0);
+        GadgetCase {
+            gates,
+            witness: range_check::witness::create_multi(v0, v1, v2),
+            public: vec![],
+        }
+    })
+}
+
+/// Returns a strategy of XORs of two random `bits`-bit inputs
+pub fn xor_gadget<F: PrimeField>(bits: usize) -> impl Strategy<Value = GadgetCase<F>> {
+    array::uniform2(field_with_bits::<F>(bits)).prop_map(move |[input1, input2]| {
+        let mut gates = vec![];
+        CircuitGate::extend_xor_gadget(&mut gates, bits);
+        GadgetCase {
+            gates,
+            witness: xor::create_xor_witness(input1, input2, bits),
+            public: vec![],
+        }
+    })
+}
+
+/// Returns a strategy of ANDs of two random `bytes`-byte inputs
+pub fn and_gadget<F: PrimeField>(bytes: usize) -> impl Strategy<Value = GadgetCase<F>> {
+    array::uniform2(field_with_bits::<F>(bytes * 8)).prop_map(move |[input1, input2]| {
+        let mut gates = vec![];
+        CircuitGate::extend_and(&mut gates, bytes);
+        GadgetCase {
+            gates,
+            witness: and::create_and_witness(input1, input2, bytes),
+            public: vec![],
+        }
+    })
+}
+
+/// Returns a strategy of rotations of a random 64-bit word, by a random
+/// offset and in a random direction
+pub fn rot_gadget<F: PrimeField>() -> impl Strategy<Value = GadgetCase<F>> {
+    (
+        any::<u64>(),
+        1..64u32,
+        prop_oneof![Just(RotMode::Left), Just(RotMode::Right)],
+    )
+        .prop_map(|(word, rot, side)| {
+            // the first row holds the zero used to check the limbs of the rotation
+            let mut gates = vec![CircuitGate::create_generic_gadget(
+                Wire::for_row(0),
+                GenericGateSpec::Pub,
+                None,
+            )];
+            CircuitGate::extend_rot(&mut gates, rot, side, 0);
+
+            let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero()]);
+            rot::extend_rot(&mut witness, word, rot, side);
+
+            GadgetCase {
+                gates,
+                witness,
+                public: vec![],
+            }
+        })
+}
+
+/// Returns a strategy of chains of up to 4 random foreign field additions and
+/// subtractions modulo `modulus`, followed by the bound check of the result
+pub fn foreign_field_add_gadget<F: PrimeField>(
+    modulus: BigUint,
+) -> impl Strategy<Value = GadgetCase<F>> {
+    let bits = modulus.bits() as usize;
+    vec(prop_oneof![Just(FFOps::Add), Just(FFOps::Sub)], 1..5).prop_flat_map(move |opcodes| {
+        let modulus = modulus.clone();
+        vec(biguint_with_bits(bits), opcodes.len() + 1).prop_map(move |inputs| {
+            let inputs: Vec<_> = inputs.into_iter().map(|x| x % &modulus).collect();
+
+            // the first row holds the public input 1 used by the bound check
+            let mut gates = vec![CircuitGate::create_generic_gadget(
+                Wire::for_row(0),
+                GenericGateSpec::Pub,
+                None,
+            )];
+            CircuitGate::extend_chain_ffadd(&mut gates, 0, &mut 1, &opcodes, &modulus);
+
+            let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero()]);
+            witness[0][0] = F::one();
+            let chain = ffadd::create_chain::<F>(&inputs, &opcodes, modulus.clone());
+            for (col, chain) in witness.iter_mut().zip(chain) {
+                col.extend(chain);
+            }
+
+            GadgetCase {
+                gates,
+                witness,
+                public: vec![F::one()],
+            }
+        })
+    })
+}
@@ -0,0 +1,160 @@
+//! This module implements a harness to catch under-constrained gadgets.
+//!
+//! Given the gates of a gadget and a builder of its witnesses, the harness
+//! mutates the witness cells one at a time and checks that the constraints
+//! reject each mutated witness. A cell whose mutation is accepted is not
+//! constrained by the gadget, which is usually a soundness bug.
+//!
+//! The witnesses are checked with [`ProverIndex::verify`], i.e. against the
+//! wiring and the constraints of each gate, without creating proofs.
+//!
+//! The [`generators`] module provides random valid witnesses of the gadgets,
+//! as proptest strategies.
+//!
+//! [`ProverIndex::verify`]: crate::prover_index::ProverIndex::verify
+
+#[cfg(any(test, feature = "proptest-generators"))]
+pub mod generators;
+
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    prover_index::testing::new_index_for_test,
+};
+use ark_ff::{One, PrimeField, UniformRand};
+use rand::Rng;
+use std::ops::Range;
+
+/// The configuration of [`fuzz_constraints`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzConfig {
+    /// The number of witnesses created with the witness builder
+    pub witnesses: usize,
+    /// The number of mutations of each witness, each one changing a single cell
+    pub mutations: usize,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            witnesses: 4,
+            mutations: 64,
+        }
+    }
+}
+
+/// A mutation of a witness cell that the constraints accepted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptedMutation<F> {
+    /// The mutated cell
+    pub cell: Wire,
+    /// The value of the cell in the valid witness
+    pub from: F,
+    /// The value accepted in place of `from`
+    pub to: F,
+}
+
+/// Returns the cells of the given rows and columns
+pub fn cells(rows: Range<usize>, cols: Range<usize>) -> Vec<Wire> {
+    rows.flat_map(|row| cols.clone().map(move |col| Wire::new(row, col)))
+        .collect()
+}
+
+/// Mutates the `cells` of the witnesses created by `build_witness` and
+/// returns the mutations that the constraints of `gates` accepted.
+///
+/// The public input is read from the first column of the first `public` rows
+/// of each witness, so mutating one of these cells also changes the public
+/// input, and is accepted by the generic gates.
+///
+/// A mutation either replaces the cell with a random field element, or adds
+/// or subtracts one from it, to catch the constraints that only check a part
+/// of the bits of a value.
+///
+/// # Panics
+///
+/// Will panic if `cells` is empty, if a cell is out of the witness, or if a
+/// witness created by `build_witness` is not satisfied.
+pub fn fuzz_constraints<G, RNG>(
+    gates: Vec<CircuitGate<G::ScalarField>>,
+    public: usize,
+    cells: &[Wire],
+    mut build_witness: impl FnMut(&mut RNG) -> [Vec<G::ScalarField>; COLUMNS],
+    config: &FuzzConfig,
+    rng: &mut RNG,
+) -> Vec<AcceptedMutation<G::ScalarField>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    RNG: Rng,
+{
+    assert!(!cells.is_empty(), "no cell to mutate");
+
+    let index = new_index_for_test::<G>(gates, public);
+
+    let mut accepted = vec![];
+    for _ in 0..config.witnesses {
+        let witness = build_witness(rng);
+        if let Err(err) = index.verify(&witness, &witness[0][..public]) {
+            panic!("the witness is not satisfied: {err:?}");
+        }
+
+        for _ in 0..config.mutations {
+            let cell = cells[rng.gen_range(0..cells.len())];
+            let from = witness[cell.col][cell.row];
+            let to = loop {
+                let to = match rng.gen_range(0..3) {
+                    0 => G::ScalarField::rand(rng),
+                    1 => from + G::ScalarField::one(),
+                    _ => from - G::ScalarField::one(),
+                };
+                if to != from {
+                    break to;
+                }
+            };
+
+            let mut mutated = witness.clone();
+            mutated[cell.col][cell.row] = to;
+            if index.verify(&mutated, &mutated[0][..public]).is_ok() {
+                accepted.push(AcceptedMutation { cell, from, to });
+            }
+        }
+    }
+
+    accepted
+}
+
+/// Same as [`fuzz_constraints`], but panics if a mutation is accepted, i.e.
+/// if one of the `cells` is not constrained by the gadget.
+///
+/// # Panics
+///
+/// Will panic if a mutation is accepted, or for the reasons listed in
+/// [`fuzz_constraints`].
+pub fn assert_constrained<G, RNG>(
+    gates: Vec<CircuitGate<G::ScalarField>>,
+    public: usize,
+    cells: &[Wire],
+    build_witness: impl FnMut(&mut RNG) -> [Vec<G::ScalarField>; COLUMNS],
+    config: &FuzzConfig,
+    rng: &mut RNG,
+) where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    RNG: Rng,
+{
+    let accepted = fuzz_constraints::<G, RNG>(gates, public, cells, build_witness, config, rng);
+    if let Some(mutation) = accepted.first() {
+        panic!(
+            "{} mutations were accepted, the first one sets cell (row {}, col {}) from {} to {}",
+            accepted.len(),
+            mutation.cell.row,
+            mutation.cell.col,
+            mutation.from,
+            mutation.to
+        );
+    }
+}
@@ -1,3 +1,12 @@
+//! This module implements the witness layouts of the gadgets: a layout gives,
+//! for each cell of the rows of a gadget, a [WitnessCell] computing its value
+//! from the [Variables] of the gadget or from the cells already filled (a
+//! copy, some bits, a sum...), and [init] fills the columns with it.
+//!
+//! The rows of a layout can be declared with the [witness_cells](crate::witness_cells)
+//! macro, which expands the runs of limbs and crumbs of a cell into one cell
+//! each.
+
 use ark_ff::{Field, PrimeField};
 
 mod constant_cell;
@@ -5,6 +14,7 @@ mod copy_bits_cell;
 mod copy_cell;
 mod copy_shift_cell;
 mod index_cell;
+mod sum_cell;
 mod variable_bits_cell;
 mod variable_cell;
 mod variables;
@@ -15,6 +25,7 @@ pub use self::{
     copy_cell::CopyCell,
     copy_shift_cell::CopyShiftCell,
     index_cell::IndexCell,
+    sum_cell::SumCell,
     variable_bits_cell::VariableBitsCell,
     variable_cell::VariableCell,
     variables::{variable_map, variables, Variables},
@@ -32,6 +43,79 @@ pub trait WitnessCell<F: Field, T = F, const W: usize = COLUMNS> {
     }
 }
 
+/// Returns the cells copying the bits `start..end` of the cell at position
+/// `(row, col)` by chunks of `bits` bits, starting from the most significant
+/// ones, as used by the [witness_cells](crate::witness_cells) macro.
+///
+/// # Panics
+///
+/// Will panic if `end - start` is not a multiple of `bits`.
+pub fn copy_bits_cells(
+    row: usize,
+    col: usize,
+    end: usize,
+    start: usize,
+    bits: usize,
+) -> impl Iterator<Item = Box<CopyBitsCell>> {
+    assert_eq!(
+        (end - start) % bits,
+        0,
+        "the bits {start}..{end} are not made of chunks of {bits} bits"
+    );
+    (start..end)
+        .rev()
+        .step_by(bits)
+        .map(move |last| CopyBitsCell::create(row, col, last + 1 - bits, last + 1))
+}
+
+/// Declares a row of a witness layout, as a vector of boxed [WitnessCell]s.
+/// Each item of the row is one of:
+/// - `variable(name)`: a [VariableCell],
+/// - `constant(value)`: a [ConstantCell],
+/// - `copy(row, col)`: a [CopyCell],
+/// - `bits(row, col, start, end)`: a [CopyBitsCell] of the bits `start..end`,
+/// - `limbs(row, col, end, start, bits)`: the [CopyBitsCell]s of the bits
+///   `start..end` by chunks of `bits` bits, from the most significant ones,
+///   see [copy_bits_cells],
+/// - `crumbs(row, col, end, start)`: the 2-bit limbs of the bits `start..end`,
+/// - `sum[(row, col, shift), ...]`: a [SumCell],
+/// - `cell(cell)`: a boxed cell built by the caller.
+#[macro_export]
+macro_rules! witness_cells {
+    [$($kind:ident $args:tt),* $(,)?] => {{
+        #[allow(clippy::vec_init_then_push)]
+        let mut cells: Vec<Box<dyn $crate::circuits::witness::WitnessCell<_>>> = vec![];
+        $( $crate::witness_cells!(@push cells, $kind $args); )*
+        cells
+    }};
+    (@push $cells:ident, variable($name:expr)) => {
+        $cells.push($crate::circuits::witness::VariableCell::create($name))
+    };
+    (@push $cells:ident, constant($value:expr)) => {
+        $cells.push($crate::circuits::witness::ConstantCell::create($value))
+    };
+    (@push $cells:ident, copy($row:expr, $col:expr)) => {
+        $cells.push($crate::circuits::witness::CopyCell::create($row, $col))
+    };
+    (@push $cells:ident, bits($row:expr, $col:expr, $start:expr, $end:expr)) => {
+        $cells.push($crate::circuits::witness::CopyBitsCell::create($row, $col, $start, $end))
+    };
+    (@push $cells:ident, limbs($row:expr, $col:expr, $end:expr, $start:expr, $bits:expr)) => {
+        for cell in $crate::circuits::witness::copy_bits_cells($row, $col, $end, $start, $bits) {
+            $cells.push(cell);
+        }
+    };
+    (@push $cells:ident, crumbs($row:expr, $col:expr, $end:expr, $start:expr)) => {
+        $crate::witness_cells!(@push $cells, limbs($row, $col, $end, $start, 2))
+    };
+    (@push $cells:ident, sum[$(($row:expr, $col:expr, $shift:expr)),* $(,)?]) => {
+        $cells.push($crate::circuits::witness::SumCell::create(vec![$(($row, $col, $shift)),*]))
+    };
+    (@push $cells:ident, cell($cell:expr)) => {
+        $cells.push($cell)
+    };
+}
+
 /// Initialize a witness cell based on layout and computed variables
 /// Inputs:
 /// - witness: the witness to initialize with values
@@ -231,4 +315,65 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn sum_layout() {
+        let layout: Vec<Vec<Box<dyn WitnessCell<PallasField>>>> = vec![vec![
+            VariableCell::create("low"),
+            VariableCell::create("high"),
+            SumCell::create(vec![(0, 0, 0), (0, 1, 8)]),
+        ]];
+
+        let mut witness: [Vec<PallasField>; COLUMNS] =
+            array::from_fn(|_| vec![PallasField::zero(); 1]);
+        let low = PallasField::from(0x34u32);
+        let high = PallasField::from(0x12u32);
+        init_row(&mut witness, 0, 0, &layout, &variables!(low, high));
+
+        assert_eq!(witness[2][0], PallasField::from(0x1234u32));
+    }
+
+    #[test]
+    fn witness_cells_layout() {
+        let declared: Vec<Vec<Box<dyn WitnessCell<PallasField>>>> = vec![
+            crate::witness_cells![
+                variable("value"),
+                constant(PallasField::from(7u32)),
+                limbs(0, 0, 16, 4, 4),
+                crumbs(0, 0, 4, 0),
+                bits(0, 0, 0, 16),
+                sum[(0, 2, 8), (0, 3, 4), (0, 4, 0)],
+            ],
+            crate::witness_cells![copy(0, 1), cell(CopyShiftCell::create(0, 0, 4))],
+        ];
+        let written: Vec<Vec<Box<dyn WitnessCell<PallasField>>>> = vec![
+            vec![
+                VariableCell::create("value"),
+                ConstantCell::create(PallasField::from(7u32)),
+                CopyBitsCell::create(0, 0, 12, 16),
+                CopyBitsCell::create(0, 0, 8, 12),
+                CopyBitsCell::create(0, 0, 4, 8),
+                CopyBitsCell::create(0, 0, 2, 4),
+                CopyBitsCell::create(0, 0, 0, 2),
+                CopyBitsCell::create(0, 0, 0, 16),
+                SumCell::create(vec![(0, 2, 8), (0, 3, 4), (0, 4, 0)]),
+            ],
+            vec![CopyCell::create(0, 1), CopyShiftCell::create(0, 0, 4)],
+        ];
+        assert_eq!(declared[0].len(), written[0].len());
+        assert_eq!(declared[1].len(), written[1].len());
+
+        let value = PallasField::from(0xabcdu32);
+        let mut declared_witness: [Vec<PallasField>; COLUMNS] =
+            array::from_fn(|_| vec![PallasField::zero(); 2]);
+        init(&mut declared_witness, 0, &declared, &variables!(value));
+        let mut written_witness: [Vec<PallasField>; COLUMNS] =
+            array::from_fn(|_| vec![PallasField::zero(); 2]);
+        init(&mut written_witness, 0, &written, &variables!(value));
+
+        assert_eq!(declared_witness, written_witness);
+        assert_eq!(declared_witness[2][0], PallasField::from(0xau32));
+        assert_eq!(declared_witness[6][0], PallasField::from(0x1u32));
+        assert_eq!(declared_witness[8][0], PallasField::from(0xabcu32));
+    }
 }
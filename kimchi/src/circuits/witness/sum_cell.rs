@@ -0,0 +1,25 @@
+use super::{variables::Variables, WitnessCell};
+use ark_ff::Field;
+
+/// Witness cell summing other witness cells, each scaled by a power of two
+pub struct SumCell {
+    cells: Vec<(usize, usize, u64)>,
+}
+
+impl SumCell {
+    /// Create witness cell summing the witness cells at positions (row, col), each
+    /// scaled by 2^shift
+    pub fn create(cells: Vec<(usize, usize, u64)>) -> Box<SumCell> {
+        Box::new(SumCell { cells })
+    }
+}
+
+impl<F: Field, const W: usize> WitnessCell<F, F, W> for SumCell {
+    fn value(&self, witness: &mut [Vec<F>; W], _variables: &Variables<F>, _index: usize) -> F {
+        self.cells
+            .iter()
+            .fold(F::zero(), |acc, &(row, col, shift)| {
+                acc + F::from(2u32).pow([shift]) * witness[col][row]
+            })
+    }
+}
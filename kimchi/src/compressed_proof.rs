@@ -0,0 +1,478 @@
+//! This module implements a compact encoding of the proofs, for the
+//! deployments in which the proofs are sent over a constrained network, and
+//! [`ProverProof::size_report`] to see where the bytes of a proof go.
+//!
+//! Compared to the MessagePack encoding of the proofs, the compressed
+//! encoding:
+//! - stores the points in compressed form, i.e. with their `x` coordinate and
+//!   a sign bit only;
+//! - packs the evaluations: the number of chunks of the evaluations is stored
+//!   once, the presence of the optional evaluations is stored as a bitmap, and
+//!   the evaluations follow without any length or tag;
+//! - stores the lengths as LEB128 integers, without any field name.
+//!
+//! The encoding starts with the version [`COMPRESSED_PROOF_VERSION`], and is
+//! only defined for the proofs using the IPA opening proof.
+
+use crate::{
+    circuits::wires::{COLUMNS, PERMUTS},
+    proof::{
+        LookupCommitments, PointEvaluations, ProofEvaluations, ProverCommitments, ProverProof,
+        RecursionChallenge,
+    },
+};
+use ark_ec::AffineRepr;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use o1_utils::serialization::DeserializationError;
+use poly_commitment::{commitment::PolyComm, ipa::OpeningProof};
+use std::{array, fmt};
+
+/// The current version of the compressed encoding of the proofs
+pub const COMPRESSED_PROOF_VERSION: u8 = 1;
+
+/// The number of evaluations that are always in a proof: the witness, the
+/// permutation, the coefficients and the selectors of the fixed gates
+const NUM_REQUIRED_EVALUATIONS: usize = COLUMNS + PERMUTS + COLUMNS + 6;
+
+/// The number of optional evaluations of a proof
+const NUM_OPTIONAL_EVALUATIONS: usize = 20;
+
+/// The number of bytes of each component of a proof, in the compressed
+/// encoding (see [`ProverProof::to_compressed_bytes`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofSizeReport {
+    /// The commitments to the witness, permutation, quotient and lookup
+    /// polynomials
+    pub commitments: usize,
+    /// The evaluations of the polynomials, including `ft_eval1`
+    pub evaluations: usize,
+    /// The number of rounds of the opening proof
+    pub ipa_rounds: usize,
+    /// The opening proof, i.e. the L and R points of its rounds, `delta`,
+    /// `z1`, `z2` and `sg`
+    pub opening_proof: usize,
+    /// The challenges of the previous recursion layer
+    pub prev_challenges: usize,
+    /// The whole compressed encoding, including its header
+    pub compressed: usize,
+    /// The whole MessagePack encoding, for comparison
+    pub msgpack: usize,
+}
+
+impl fmt::Display for ProofSizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "commitments:     {:>6} bytes", self.commitments)?;
+        writeln!(f, "evaluations:     {:>6} bytes", self.evaluations)?;
+        writeln!(
+            f,
+            "opening proof:   {:>6} bytes ({} rounds)",
+            self.opening_proof, self.ipa_rounds
+        )?;
+        writeln!(f, "prev challenges: {:>6} bytes", self.prev_challenges)?;
+        writeln!(f, "compressed:      {:>6} bytes", self.compressed)?;
+        write!(f, "msgpack:         {:>6} bytes", self.msgpack)
+    }
+}
+
+fn decode_error(e: impl ToString) -> DeserializationError {
+    DeserializationError::Decode(e.to_string())
+}
+
+fn write_len(out: &mut Vec<u8>, mut n: usize) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_len(input: &mut &[u8]) -> Result<usize, DeserializationError> {
+    let mut n = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| decode_error("unexpected end of input"))?;
+        *input = rest;
+        let value = usize::from(byte & 0x7f);
+        if value
+            .checked_shl(shift)
+            .map_or(true, |v| v >> shift != value)
+        {
+            return Err(decode_error("length overflow"));
+        }
+        n |= value << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(decode_error("length overflow"))
+}
+
+fn write_compressed<T: CanonicalSerialize>(out: &mut Vec<u8>, x: &T) {
+    x.serialize_compressed(out)
+        .expect("a value can be written to a vector");
+}
+
+fn read_compressed<T: CanonicalDeserialize>(input: &mut &[u8]) -> Result<T, DeserializationError> {
+    T::deserialize_compressed(input).map_err(decode_error)
+}
+
+fn write_comm<G: AffineRepr>(out: &mut Vec<u8>, comm: &PolyComm<G>) {
+    write_len(out, comm.chunks.len());
+    for g in &comm.chunks {
+        write_compressed(out, g);
+    }
+}
+
+fn read_comm<G: AffineRepr>(input: &mut &[u8]) -> Result<PolyComm<G>, DeserializationError> {
+    let len = read_len(input)?;
+    let chunks = (0..len)
+        .map(|_| read_compressed(input))
+        .collect::<Result<_, _>>()?;
+    Ok(PolyComm { chunks })
+}
+
+fn write_fields<F: CanonicalSerialize>(out: &mut Vec<u8>, xs: &[F]) {
+    write_len(out, xs.len());
+    for x in xs {
+        write_compressed(out, x);
+    }
+}
+
+fn read_fields<F: CanonicalDeserialize>(input: &mut &[u8]) -> Result<Vec<F>, DeserializationError> {
+    let len = read_len(input)?;
+    (0..len).map(|_| read_compressed(input)).collect()
+}
+
+/// The evaluations that are always in a proof, in the order of the encoding
+fn required_evaluations<E>(e: &ProofEvaluations<E>) -> Vec<&E> {
+    e.w.iter()
+        .chain([&e.z])
+        .chain(&e.s)
+        .chain(&e.coefficients)
+        .chain([
+            &e.generic_selector,
+            &e.poseidon_selector,
+            &e.complete_add_selector,
+            &e.mul_selector,
+            &e.emul_selector,
+            &e.endomul_scalar_selector,
+        ])
+        .collect()
+}
+
+/// The optional evaluations of a proof, in the order of the encoding
+fn optional_evaluations<E>(e: &ProofEvaluations<E>) -> Vec<&Option<E>> {
+    [
+        &e.public,
+        &e.range_check0_selector,
+        &e.range_check1_selector,
+        &e.foreign_field_add_selector,
+        &e.foreign_field_mul_selector,
+        &e.xor_selector,
+        &e.rot_selector,
+        &e.lookup_aggregation,
+        &e.lookup_table,
+    ]
+    .into_iter()
+    .chain(&e.lookup_sorted)
+    .chain([
+        &e.runtime_lookup_table,
+        &e.runtime_lookup_table_selector,
+        &e.xor_lookup_selector,
+        &e.lookup_gate_lookup_selector,
+        &e.range_check_lookup_selector,
+        &e.foreign_field_mul_lookup_selector,
+    ])
+    .collect()
+}
+
+impl<G: AffineRepr> ProverProof<G, OpeningProof<G>> {
+    fn write_commitments(&self, out: &mut Vec<u8>) {
+        let ProverCommitments {
+            w_comm,
+            z_comm,
+            t_comm,
+            lookup,
+        } = &self.commitments;
+        for comm in w_comm.iter().chain([z_comm, t_comm]) {
+            write_comm(out, comm);
+        }
+        match lookup {
+            None => out.push(0),
+            Some(lookup) => {
+                out.push(1);
+                write_len(out, lookup.sorted.len());
+                for comm in &lookup.sorted {
+                    write_comm(out, comm);
+                }
+                write_comm(out, &lookup.aggreg);
+                match &lookup.runtime {
+                    None => out.push(0),
+                    Some(runtime) => {
+                        out.push(1);
+                        write_comm(out, runtime);
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_evaluations(&self, out: &mut Vec<u8>) {
+        let required = required_evaluations(&self.evals);
+        let optional = optional_evaluations(&self.evals);
+
+        debug_assert_eq!(required.len(), NUM_REQUIRED_EVALUATIONS);
+        debug_assert_eq!(optional.len(), NUM_OPTIONAL_EVALUATIONS);
+
+        let num_chunks = required[0].zeta.len();
+        write_len(out, num_chunks);
+        let bitmap = optional.iter().enumerate().fold(0u32, |bitmap, (i, e)| {
+            bitmap | (u32::from(e.is_some()) << i)
+        });
+        out.extend(bitmap.to_le_bytes());
+
+        for e in required.into_iter().chain(optional.into_iter().flatten()) {
+            assert!(
+                e.zeta.len() == num_chunks && e.zeta_omega.len() == num_chunks,
+                "the evaluations of the proof are not all of the same length"
+            );
+            for x in e.zeta.iter().chain(&e.zeta_omega) {
+                write_compressed(out, x);
+            }
+        }
+        write_compressed(out, &self.ft_eval1);
+    }
+
+    fn write_opening_proof(&self, out: &mut Vec<u8>) {
+        write_len(out, self.proof.lr.len());
+        for (l, r) in &self.proof.lr {
+            write_compressed(out, l);
+            write_compressed(out, r);
+        }
+        write_compressed(out, &self.proof.delta);
+        write_compressed(out, &self.proof.z1);
+        write_compressed(out, &self.proof.z2);
+        write_compressed(out, &self.proof.sg);
+    }
+
+    fn write_prev_challenges(&self, out: &mut Vec<u8>) {
+        write_len(out, self.prev_challenges.len());
+        for challenge in &self.prev_challenges {
+            write_fields(out, &challenge.chals);
+            write_comm(out, &challenge.comm);
+        }
+    }
+
+    /// Encodes the proof with the compressed encoding described in
+    /// [`crate::compressed_proof`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the evaluations of the proof are not all of the same
+    /// length, which is the case of all the proofs created by the prover.
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        let mut out = vec![COMPRESSED_PROOF_VERSION];
+        self.write_commitments(&mut out);
+        self.write_evaluations(&mut out);
+        self.write_opening_proof(&mut out);
+        self.write_prev_challenges(&mut out);
+        out
+    }
+
+    /// Decodes a proof encoded with [`ProverProof::to_compressed_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not the compressed encoding of a proof,
+    /// e.g. if it contains a point that is not on the curve, or if it is
+    /// followed by other bytes.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, DeserializationError> {
+        let input = &mut &bytes[..];
+
+        let (&version, rest) = input
+            .split_first()
+            .ok_or_else(|| decode_error("unexpected end of input"))?;
+        *input = rest;
+        if version != COMPRESSED_PROOF_VERSION {
+            return Err(decode_error(format!(
+                "unsupported version (expected {COMPRESSED_PROOF_VERSION}, got {version})"
+            )));
+        }
+
+        let read_flag = |input: &mut &[u8]| -> Result<bool, DeserializationError> {
+            let (&flag, rest) = input
+                .split_first()
+                .ok_or_else(|| decode_error("unexpected end of input"))?;
+            *input = rest;
+            match flag {
+                0 => Ok(false),
+                1 => Ok(true),
+                _ => Err(decode_error("invalid option tag")),
+            }
+        };
+
+        // commitments
+        let w_comm: Vec<PolyComm<G>> = (0..COLUMNS)
+            .map(|_| read_comm(input))
+            .collect::<Result<_, _>>()?;
+        let z_comm = read_comm(input)?;
+        let t_comm = read_comm(input)?;
+        let lookup = if read_flag(input)? {
+            let len = read_len(input)?;
+            let sorted = (0..len)
+                .map(|_| read_comm(input))
+                .collect::<Result<_, _>>()?;
+            let aggreg = read_comm(input)?;
+            let runtime = if read_flag(input)? {
+                Some(read_comm(input)?)
+            } else {
+                None
+            };
+            Some(LookupCommitments {
+                sorted,
+                aggreg,
+                runtime,
+            })
+        } else {
+            None
+        };
+        let commitments = ProverCommitments {
+            w_comm: w_comm.try_into().unwrap_or_else(|_| unreachable!()),
+            z_comm,
+            t_comm,
+            lookup,
+        };
+
+        // evaluations
+        let num_chunks = read_len(input)?;
+        let bitmap = input
+            .get(..4)
+            .ok_or_else(|| decode_error("unexpected end of input"))?;
+        let bitmap = u32::from_le_bytes(bitmap.try_into().unwrap());
+        *input = &input[4..];
+        let mut read_evaluations = || -> Result<_, DeserializationError> {
+            let mut read = || {
+                (0..num_chunks)
+                    .map(|_| read_compressed(input))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            Ok(PointEvaluations {
+                zeta: read()?,
+                zeta_omega: read()?,
+            })
+        };
+        if bitmap >> NUM_OPTIONAL_EVALUATIONS != 0 {
+            return Err(decode_error("invalid evaluation bitmap"));
+        }
+        let required: Vec<_> = (0..NUM_REQUIRED_EVALUATIONS)
+            .map(|_| read_evaluations())
+            .collect::<Result<_, _>>()?;
+        let optional: Vec<_> = (0..NUM_OPTIONAL_EVALUATIONS)
+            .map(|i| {
+                if bitmap & (1 << i) != 0 {
+                    read_evaluations().map(Some)
+                } else {
+                    Ok(None)
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        let ft_eval1 = read_compressed(input)?;
+
+        let mut required = required.into_iter();
+        let mut next = || required.next().unwrap();
+        let mut optional = optional.into_iter();
+        let mut next_optional = || optional.next().unwrap();
+        let evals = ProofEvaluations {
+            public: next_optional(),
+            w: array::from_fn(|_| next()),
+            z: next(),
+            s: array::from_fn(|_| next()),
+            coefficients: array::from_fn(|_| next()),
+            generic_selector: next(),
+            poseidon_selector: next(),
+            complete_add_selector: next(),
+            mul_selector: next(),
+            emul_selector: next(),
+            endomul_scalar_selector: next(),
+            range_check0_selector: next_optional(),
+            range_check1_selector: next_optional(),
+            foreign_field_add_selector: next_optional(),
+            foreign_field_mul_selector: next_optional(),
+            xor_selector: next_optional(),
+            rot_selector: next_optional(),
+            lookup_aggregation: next_optional(),
+            lookup_table: next_optional(),
+            lookup_sorted: array::from_fn(|_| next_optional()),
+            runtime_lookup_table: next_optional(),
+            runtime_lookup_table_selector: next_optional(),
+            xor_lookup_selector: next_optional(),
+            lookup_gate_lookup_selector: next_optional(),
+            range_check_lookup_selector: next_optional(),
+            foreign_field_mul_lookup_selector: next_optional(),
+        };
+
+        // opening proof
+        let rounds = read_len(input)?;
+        let lr = (0..rounds)
+            .map(|_| Ok((read_compressed(input)?, read_compressed(input)?)))
+            .collect::<Result<_, DeserializationError>>()?;
+        let proof = OpeningProof {
+            lr,
+            delta: read_compressed(input)?,
+            z1: read_compressed(input)?,
+            z2: read_compressed(input)?,
+            sg: read_compressed(input)?,
+        };
+
+        // previous challenges
+        let len = read_len(input)?;
+        let prev_challenges = (0..len)
+            .map(|_| {
+                Ok(RecursionChallenge {
+                    chals: read_fields(input)?,
+                    comm: read_comm(input)?,
+                })
+            })
+            .collect::<Result<_, DeserializationError>>()?;
+
+        if !input.is_empty() {
+            return Err(decode_error(format!("{} trailing bytes", input.len())));
+        }
+
+        Ok(ProverProof {
+            commitments,
+            proof,
+            evals,
+            ft_eval1,
+            prev_challenges,
+        })
+    }
+
+    /// Returns the number of bytes of each component of the proof, in the
+    /// compressed encoding.
+    ///
+    /// # Panics
+    ///
+    /// See [`ProverProof::to_compressed_bytes`].
+    pub fn size_report(&self) -> ProofSizeReport {
+        let size = |write: fn(&Self, &mut Vec<u8>)| {
+            let mut out = vec![];
+            write(self, &mut out);
+            out.len()
+        };
+        ProofSizeReport {
+            commitments: size(Self::write_commitments),
+            evaluations: size(Self::write_evaluations),
+            ipa_rounds: self.proof.lr.len(),
+            opening_proof: size(Self::write_opening_proof),
+            prev_challenges: size(Self::write_prev_challenges),
+            compressed: self.to_compressed_bytes().len(),
+            msgpack: rmp_serde::to_vec(self).map_or(0, |bytes| bytes.len()),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Conformance of the prover with test vectors produced by another
+//! implementation, e.g. Mina's OCaml prover.
+//!
+//! A [`TestVector`] is the sequence of the operations on the transcripts of a
+//! proof, stage by stage: the commitments and the evaluations absorbed by the
+//! sponges, and the challenges squeezed from them, each value being the hex
+//! encoding of its compressed serialization. It is stored as JSON:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "name": "generic circuit",
+//!   "entries": [
+//!     { "sponge": "fq", "label": "witness_comm", "op": { "absorb": 1 }, "values": ["..."] },
+//!     { "sponge": "fq", "label": "beta", "op": "squeeze", "values": ["..."] }
+//!   ]
+//! }
+//! ```
+//!
+//! The fields other than the ones of [`TestVector`] are kept in
+//! [`TestVector::extra`], so that fixtures can carry more data, e.g. the inputs of
+//! the circuit, without changing the format.
+//!
+//! [`record`] records the vector of the proofs created by a closure, and
+//! [`check`] compares it with the expected vector and reports the first stage
+//! that differs. The blinders of the proof are part of its commitments, so that
+//! the vectors must be produced without hiding or with the same randomness, see
+//! [`ProverConfig`](crate::prover::ProverConfig).
+
+use crate::{
+    error::ConformanceError,
+    transcript::{TranscriptOp, TranscriptSponge},
+};
+use ark_serialize::CanonicalSerialize;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, collections::BTreeMap, path::Path};
+
+/// The version of the format of the test vectors
+pub const VERSION: u32 = 1;
+
+/// An operation on a transcript, with its values
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorEntry {
+    /// The sponge of the transcript
+    pub sponge: TranscriptSponge,
+    /// The label of the operation, e.g. the name of the challenge
+    pub label: String,
+    /// The operation
+    pub op: TranscriptOp,
+    /// The hex encodings of the values absorbed or squeezed
+    pub values: Vec<String>,
+}
+
+/// The operations on the transcripts of a proof
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// The version of the format, see [`VERSION`]
+    pub version: u32,
+    /// The name of the vector
+    pub name: String,
+    /// The operations, in order
+    pub entries: Vec<VectorEntry>,
+    /// The other fields of the fixture
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl TestVector {
+    /// Reads a vector from JSON
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the JSON is not a vector, or if its version is not
+    /// supported.
+    pub fn from_json(json: &str) -> Result<Self, ConformanceError> {
+        let vector: Self =
+            serde_json::from_str(json).map_err(|e| ConformanceError::Json(e.to_string()))?;
+        if vector.version != VERSION {
+            return Err(ConformanceError::UnsupportedVersion(vector.version));
+        }
+        Ok(vector)
+    }
+
+    /// Writes the vector as JSON
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the vector cannot be encoded.
+    pub fn to_json(&self) -> Result<String, ConformanceError> {
+        serde_json::to_string_pretty(self).map_err(|e| ConformanceError::Json(e.to_string()))
+    }
+
+    /// Reads a vector from the JSON file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be read, or see [`TestVector::from_json`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConformanceError> {
+        let json =
+            std::fs::read_to_string(path).map_err(|e| ConformanceError::Io(e.to_string()))?;
+        Self::from_json(&json)
+    }
+}
+
+thread_local! {
+    /// The entries of the vector being recorded on this thread, if any
+    static ENTRIES: RefCell<Option<Vec<VectorEntry>>> = RefCell::new(None);
+}
+
+/// Logs an operation on a transcript, if a vector is being recorded
+pub(crate) fn log<T: CanonicalSerialize>(
+    sponge: TranscriptSponge,
+    label: &'static str,
+    op: TranscriptOp,
+    values: &[T],
+) {
+    ENTRIES.with(|entries| {
+        if let Some(entries) = entries.borrow_mut().as_mut() {
+            let values = values
+                .iter()
+                .map(|value| {
+                    let mut bytes = vec![];
+                    value
+                        .serialize_compressed(&mut bytes)
+                        .expect("a value can be written to a vector");
+                    hex::encode(bytes)
+                })
+                .collect();
+            entries.push(VectorEntry {
+                sponge,
+                label: label.to_string(),
+                op,
+                values,
+            });
+        }
+    });
+}
+
+/// Runs `f`, e.g. the prover, and returns its result with the vector named
+/// `name` of the transcripts used by `f` on this thread.
+pub fn record<R>(name: &str, f: impl FnOnce() -> R) -> (R, TestVector) {
+    let previous = ENTRIES.with(|entries| entries.replace(Some(vec![])));
+    let res = f();
+    let entries = ENTRIES
+        .with(|entries| entries.replace(previous))
+        .unwrap_or_default();
+    let vector = TestVector {
+        version: VERSION,
+        name: name.to_string(),
+        entries,
+        extra: BTreeMap::new(),
+    };
+    (res, vector)
+}
+
+/// Checks that the operations of `actual` are the ones of `expected`, in order.
+///
+/// # Errors
+///
+/// Will give error at the first operation that differs, or if one of the
+/// vectors has more operations.
+pub fn check(expected: &TestVector, actual: &TestVector) -> Result<(), ConformanceError> {
+    for (index, (expected, actual)) in expected.entries.iter().zip(&actual.entries).enumerate() {
+        if expected != actual {
+            return Err(ConformanceError::Mismatch {
+                index,
+                label: expected.label.clone(),
+            });
+        }
+    }
+    if expected.entries.len() != actual.entries.len() {
+        return Err(ConformanceError::IncorrectLength(
+            expected.entries.len(),
+            actual.entries.len(),
+        ));
+    }
+    Ok(())
+}
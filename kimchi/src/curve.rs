@@ -168,39 +168,94 @@ impl KimchiCurve for Affine<LegacyPallasParameters> {
     }
 }
 
+//
+// BN254 and Grumpkin
+//
+
+#[cfg(feature = "bn254")]
+use mina_curves::grumpkin::GrumpkinParameters;
+#[cfg(feature = "bn254")]
+use mina_poseidon::params::sha256_params;
+
+/// The sponge params over the scalar field of BN254, generated as the ones of
+/// the Pasta fields (see [`sha256_params`]).
+#[cfg(feature = "bn254")]
+fn bn254_fr_sponge_params() -> &'static ArithmeticSpongeParams<ark_bn254::Fr> {
+    static PARAMS: Lazy<ArithmeticSpongeParams<ark_bn254::Fr>> =
+        Lazy::new(|| sha256_params("BN254_r_kimchi", 3, 55));
+    &PARAMS
+}
+
+/// The sponge params over the base field of BN254, generated as the ones of
+/// the Pasta fields (see [`sha256_params`]).
 #[cfg(feature = "bn254")]
-use mina_poseidon::dummy_values::kimchi_dummy;
+fn bn254_fq_sponge_params() -> &'static ArithmeticSpongeParams<ark_bn254::Fq> {
+    static PARAMS: Lazy<ArithmeticSpongeParams<ark_bn254::Fq>> =
+        Lazy::new(|| sha256_params("BN254_q_kimchi", 3, 55));
+    &PARAMS
+}
+
+#[cfg(feature = "bn254")]
+fn bn254_endos() -> &'static (ark_bn254::Fq, ark_bn254::Fr) {
+    static ENDOS: Lazy<(ark_bn254::Fq, ark_bn254::Fr)> = Lazy::new(endos::<ark_bn254::G1Affine>);
+    &ENDOS
+}
+
+#[cfg(feature = "bn254")]
+fn grumpkin_endos() -> &'static (ark_bn254::Fr, ark_bn254::Fq) {
+    static ENDOS: Lazy<(ark_bn254::Fr, ark_bn254::Fq)> =
+        Lazy::new(endos::<Affine<GrumpkinParameters>>);
+    &ENDOS
+}
 
 #[cfg(feature = "bn254")]
 impl KimchiCurve for Affine<ark_bn254::g1::Config> {
     const NAME: &'static str = "bn254";
 
     fn sponge_params() -> &'static ArithmeticSpongeParams<Self::ScalarField> {
-        // TODO: Generate some params
-        static PARAMS: Lazy<ArithmeticSpongeParams<ark_bn254::Fr>> = Lazy::new(kimchi_dummy);
-        &PARAMS
+        bn254_fr_sponge_params()
+    }
+
+    fn other_curve_sponge_params() -> &'static ArithmeticSpongeParams<Self::BaseField> {
+        bn254_fq_sponge_params()
+    }
+
+    fn endos() -> &'static (Self::BaseField, Self::ScalarField) {
+        bn254_endos()
+    }
+
+    fn other_curve_endo() -> &'static Self::ScalarField {
+        &grumpkin_endos().0
+    }
+
+    fn other_curve_generator() -> (Self::ScalarField, Self::ScalarField) {
+        Affine::<GrumpkinParameters>::generator()
+            .to_coordinates()
+            .unwrap()
+    }
+}
+
+#[cfg(feature = "bn254")]
+impl KimchiCurve for Affine<GrumpkinParameters> {
+    const NAME: &'static str = "grumpkin";
+
+    fn sponge_params() -> &'static ArithmeticSpongeParams<Self::ScalarField> {
+        bn254_fq_sponge_params()
     }
 
     fn other_curve_sponge_params() -> &'static ArithmeticSpongeParams<Self::BaseField> {
-        // TODO: Generate some params
-        static PARAMS: Lazy<ArithmeticSpongeParams<ark_bn254::Fq>> = Lazy::new(kimchi_dummy);
-        &PARAMS
+        bn254_fr_sponge_params()
     }
 
     fn endos() -> &'static (Self::BaseField, Self::ScalarField) {
-        static ENDOS: Lazy<(ark_bn254::Fq, ark_bn254::Fr)> =
-            Lazy::new(endos::<ark_bn254::G1Affine>);
-        &ENDOS
+        grumpkin_endos()
     }
 
     fn other_curve_endo() -> &'static Self::ScalarField {
-        // TODO: Dummy value, this is definitely not right
-        static ENDO: Lazy<ark_bn254::Fr> = Lazy::new(|| 13u64.into());
-        &ENDO
+        &bn254_endos().0
     }
 
     fn other_curve_generator() -> (Self::ScalarField, Self::ScalarField) {
-        // TODO: Dummy value, this is definitely not right
-        (44u64.into(), 88u64.into())
+        ark_bn254::G1Affine::generator().to_coordinates().unwrap()
     }
 }
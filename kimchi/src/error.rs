@@ -1,6 +1,10 @@
 //! This module implements the [`ProverError`] type.
 
-use crate::circuits::lookup::index::LookupError; // not sure about hierarchy
+use crate::circuits::{
+    argument::ArgumentType, berkeley_columns::Column, column_registry::kimchi_column_info,
+    lookup::index::LookupError,
+}; // not sure about hierarchy
+use o1_utils::serialization::DeserializationError;
 use poly_commitment::error::CommitmentError;
 use thiserror::Error;
 
@@ -19,6 +23,12 @@ pub enum ProverError {
     #[error("the witness columns are not all the same size")]
     WitnessCsInconsistent,
 
+    #[error("the witness could not be read from its source")]
+    WitnessSource,
+
+    #[error("the witness column {0} does not match its shared blob")]
+    SharedColumnMismatch(usize),
+
     #[error("the proof could not be constructed: {0}")]
     Prover(&'static str),
 
@@ -33,6 +43,16 @@ pub enum ProverError {
 
     #[error("wrong number of custom blinders given: {0}")]
     WrongBlinders(CommitmentError),
+    #[error("the prover configuration requires {0} zero-knowledge rows, but the index uses {1}")]
+    IncompatibleZkRows(u64, u64),
+}
+
+/// The name of the evaluations of `col` in [VerifyError::IncorrectEvaluationsLength]
+fn evaluations_name(col: &Option<Column>) -> String {
+    match col {
+        Some(col) => kimchi_column_info(col).to_string(),
+        None => "public input".to_string(),
+    }
 }
 
 /// Errors that can arise when verifying a proof
@@ -47,10 +67,14 @@ pub enum VerifyError {
     #[error("the previous challenges have an unexpected length (expected {0}, got {1})")]
     IncorrectPrevChallengesLength(usize, usize),
 
+    /// The evaluations of a column (or of the public input, if `None`) do not
+    /// have the expected number of chunks
     #[error(
-        "proof malformed: an evaluation for {2} was of the incorrect size (expected {0}, got {1})"
+        "proof malformed: an evaluation for {name} was of the incorrect size \
+         (expected {0}, got {1})",
+        name = evaluations_name(.2)
     )]
-    IncorrectEvaluationsLength(usize, usize, &'static str),
+    IncorrectEvaluationsLength(usize, usize, Option<Column>),
 
     #[error("the opening proof failed to verify")]
     OpenProof,
@@ -81,6 +105,18 @@ pub enum VerifyError {
 
     #[error("the commitment for {0:?} is missing")]
     MissingCommitment(crate::circuits::berkeley_columns::Column),
+
+    #[error("the accumulator {0} is not the commitment to its challenges")]
+    InvalidAccumulator(usize),
+
+    #[error("the batch of accumulators failed to verify, but each of them is valid")]
+    InvalidAccumulatorBatch,
+
+    #[error("the public input has an unexpected number of sections (expected {0}, got {1})")]
+    IncorrectPublicSectionsCount(usize, usize),
+
+    #[error("the public section {0} is of an unexpected size (expected {1}, got {2})")]
+    IncorrectPublicSectionLength(usize, usize, usize),
 }
 
 /// Errors that can arise when preparing the setup
@@ -91,6 +127,9 @@ pub enum DomainCreationError {
 
     #[error("construction of domain {0} for size {1} failed")]
     DomainConstructionFailed(String, usize),
+
+    #[error("the circuit needs a domain of {0} rows, but the field only supports domains of up to {1} rows: reduce the number of gates, of lookup table entries or of zero-knowledge rows")]
+    DomainTooLarge(usize, usize),
 }
 
 /// Errors that can arise when preparing the setup
@@ -104,6 +143,75 @@ pub enum SetupError {
 
     #[error("the lookup constraint system cannot not be constructed: {0}")]
     LookupCreation(LookupError),
+
+    #[error("the public section {0} is defined twice")]
+    DuplicatePublicSection(String),
+
+    #[error("the public sections have {0} public inputs, but the circuit has {1}")]
+    PublicSectionsSize(usize, usize),
+}
+
+/// Errors that can arise when registering powers of alpha, see
+/// [Alphas::try_register](crate::alphas::Alphas::try_register)
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlphasError {
+    #[error("you cannot register new constraints once initialized with a field element")]
+    AlreadyInstantiated,
+
+    #[error("cannot re-register {0:?}")]
+    AlreadyRegistered(ArgumentType),
+
+    #[error("too many powers of alphas were registered")]
+    TooManyPowers,
+}
+
+/// Errors that can arise when computing the witness of a complete addition gate,
+/// see [complete_add_witness](crate::circuits::polynomials::complete_add::complete_add_witness)
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompleteAddError {
+    #[error("the point at infinity cannot be an input of the complete addition gate")]
+    InfinityInput,
+
+    #[error("the point cannot be doubled, its y coordinate is zero")]
+    DoublingOrderTwo,
+}
+
+/// Errors that can arise when computing the witness of the Schnorr signature
+/// gadget, see [witness](crate::circuits::polynomials::schnorr::witness)
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchnorrError {
+    #[error("the public key is the point at infinity")]
+    InfinityPublicKey,
+
+    #[error("the signature does not verify")]
+    InvalidSignature,
+
+    #[error("the y coordinate of the point R does not fit in 254 bits")]
+    LargeCoordinate,
+
+    #[error("the witness of the complete addition gates could not be computed: {0}")]
+    CompleteAdd(CompleteAddError),
+}
+
+/// Errors that can arise when checking a test vector, see
+/// [conformance](crate::conformance)
+#[cfg(feature = "conformance")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConformanceError {
+    #[error("the test vector could not be read or written: {0}")]
+    Io(String),
+
+    #[error("the test vector could not be encoded or decoded: {0}")]
+    Json(String),
+
+    #[error("the version {0} of the test vector is not supported")]
+    UnsupportedVersion(u32),
+
+    #[error("the operation {index} ({label}) differs from the test vector")]
+    Mismatch { index: usize, label: String },
+
+    #[error("the test vector has {0} operations, got {1}")]
+    IncorrectLength(usize, usize),
 }
 
 /// Errors that can arise when creating a verifier index
@@ -112,3 +220,93 @@ pub enum VerifierIndexError {
     #[error("srs has already been set")]
     SRSHasBeenSet,
 }
+
+/// Errors that can arise when writing or reading an index to or from a file
+#[derive(Error, Debug)]
+pub enum IndexSerializationError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("the file does not contain a kimchi index")]
+    InvalidMagic,
+
+    #[error("unsupported index format version (expected {0}, got {1})")]
+    UnsupportedVersion(u32, u32),
+
+    #[error("the file contains an unknown kind of index ({0})")]
+    UnknownKind(u8),
+
+    #[error("the file contains a {1} index, but a {0} index was expected")]
+    IncorrectIndexKind(&'static str, &'static str),
+
+    #[error("the index was created for the curve {1}, but {0} was expected")]
+    IncorrectCurve(&'static str, String),
+
+    #[error("the domain size in the header ({0}) does not match the index ({1})")]
+    IncorrectDomainSize(u64, u64),
+
+    #[error("the checksum of the index does not match the header")]
+    ChecksumMismatch,
+
+    #[error("the index could not be encoded: {0}")]
+    Encode(String),
+
+    #[error("the index could not be decoded: {0}")]
+    Decode(String),
+
+    #[error("the index is not canonically encoded")]
+    NonCanonical,
+}
+
+impl From<DeserializationError> for IndexSerializationError {
+    fn from(e: DeserializationError) -> Self {
+        match e {
+            DeserializationError::Decode(e) => IndexSerializationError::Decode(e),
+            DeserializationError::NonCanonical => IndexSerializationError::NonCanonical,
+        }
+    }
+}
+
+/// Errors that can arise when encoding or decoding deferred accumulators
+#[derive(Error, Debug)]
+pub enum AccumulatorError {
+    #[error("the accumulators could not be encoded: {0}")]
+    Encode(String),
+
+    #[error("the accumulators could not be decoded: {0}")]
+    Decode(String),
+}
+
+/// Errors that can arise when decoding a value encoded with bin_prot
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinProtError {
+    #[error("the input ended unexpectedly")]
+    UnexpectedEnd,
+
+    #[error("the input has {0} trailing bytes")]
+    TrailingBytes(usize),
+
+    #[error("invalid tag for {0}: {1:#04x}")]
+    InvalidTag(&'static str, u8),
+
+    #[error("the integer {0} is out of range")]
+    IntegerOutOfRange(i64),
+
+    #[error("invalid field element")]
+    InvalidFieldElement,
+
+    #[error("the point is not on the curve")]
+    InvalidPoint,
+
+    #[error("shifted commitments are not supported")]
+    ShiftedCommitment,
+
+    #[error("{0} is of an unexpected length (expected {1}, got {2})")]
+    IncorrectLength(&'static str, usize, usize),
+
+    #[error("there is no domain of size 2^{0} with the given generator")]
+    InvalidDomain(usize),
+
+    #[error("the public sections of a verifier index cannot be encoded")]
+    PublicSections,
+}
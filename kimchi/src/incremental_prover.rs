@@ -0,0 +1,115 @@
+//! This module implements a prover for circuits proven many times with
+//! different witnesses.
+//!
+//! A large part of the work of the prover only depends on the index: the
+//! commitments of the selectors, of the permutation and of the lookup tables,
+//! which make the verifier index and its digest absorbed by the prover, and
+//! the evaluations of the constant polynomials of the domain. The
+//! [`IncrementalProver`] computes them once, with [`ProverIndex::prepare`], and
+//! each proof only computes the parts depending on its witness.
+
+use crate::{
+    circuits::{lookup::runtime_tables::RuntimeTable, wires::COLUMNS},
+    curve::KimchiCurve,
+    error::ProverError,
+    plonk_sponge::FrSponge,
+    proof::{ProverProof, RecursionChallenge},
+    prover_index::ProverIndex,
+    verifier_index::VerifierIndex,
+};
+use ark_ff::PrimeField;
+use mina_poseidon::FqSponge;
+use poly_commitment::OpenProof;
+use rand_core::{CryptoRng, RngCore};
+use std::marker::PhantomData;
+
+/// A prover for a fixed index, caching the work which does not depend on the
+/// witness between proofs. The digest of the verifier index is computed with
+/// `EFqSponge`, which is also the sponge of the proofs.
+pub struct IncrementalProver<
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+> {
+    index: ProverIndex<G, OpeningProof>,
+    verifier_index: VerifierIndex<G, OpeningProof>,
+    _sponge: PhantomData<EFqSponge>,
+}
+
+impl<
+        G: KimchiCurve,
+        OpeningProof: OpenProof<G>,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    > IncrementalProver<G, OpeningProof, EFqSponge>
+where
+    G::BaseField: PrimeField,
+    VerifierIndex<G, OpeningProof>: Clone,
+{
+    /// Creates a prover for `index`, computing the work of the prover which
+    /// does not depend on the witness.
+    pub fn new(mut index: ProverIndex<G, OpeningProof>) -> Self {
+        index.prepare::<EFqSponge>();
+        let verifier_index = index.verifier_index();
+        IncrementalProver {
+            index,
+            verifier_index,
+            _sponge: PhantomData,
+        }
+    }
+
+    /// The prepared prover index
+    pub fn index(&self) -> &ProverIndex<G, OpeningProof> {
+        &self.index
+    }
+
+    /// The verifier index of the proofs
+    pub fn verifier_index(&self) -> &VerifierIndex<G, OpeningProof> {
+        &self.verifier_index
+    }
+
+    /// Returns the prepared prover index
+    pub fn into_index(self) -> ProverIndex<G, OpeningProof> {
+        self.index
+    }
+
+    /// Constructs a proof like [`ProverProof::create`] for the new `witness`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    pub fn prove<EFrSponge: FrSponge<G::ScalarField>, RNG: RngCore + CryptoRng>(
+        &self,
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        rng: &mut RNG,
+    ) -> Result<ProverProof<G, OpeningProof>, ProverError> {
+        self.prove_recursive::<EFrSponge, RNG>(group_map, witness, runtime_tables, Vec::new(), rng)
+    }
+
+    /// Constructs a recursive proof like [`ProverProof::create_recursive`] for
+    /// the new `witness`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    pub fn prove_recursive<EFrSponge: FrSponge<G::ScalarField>, RNG: RngCore + CryptoRng>(
+        &self,
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        rng: &mut RNG,
+    ) -> Result<ProverProof<G, OpeningProof>, ProverError> {
+        debug_assert!(self.index.is_prepared());
+        ProverProof::create_recursive::<EFqSponge, EFrSponge, RNG>(
+            group_map,
+            witness,
+            runtime_tables,
+            &self.index,
+            prev_challenges,
+            None,
+            rng,
+        )
+    }
+}
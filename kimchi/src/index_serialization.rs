@@ -0,0 +1,150 @@
+//! This module implements the binary file format used to cache a
+//! [`ProverIndex`](crate::prover_index::ProverIndex) or a
+//! [`VerifierIndex`](crate::verifier_index::VerifierIndex) on disk.
+//!
+//! A file is made of a header followed by the index encoded with MessagePack.
+//! The header contains, in order:
+//! - the magic bytes [`INDEX_MAGIC`],
+//! - the format version, as a little-endian `u32`,
+//! - the kind of index (prover, verifier or lazy prover), as a byte,
+//! - the name of the curve, prefixed by its length as a byte,
+//! - the size of the evaluation domain, as a little-endian `u64`,
+//! - the length of the encoded index, as a little-endian `u64`,
+//! - the Blake2b-512 checksum of the encoded index.
+//!
+//! The parts of the index that can be recomputed cheaply (the linearization,
+//! the powers of alpha) and the SRS are not stored in the file.
+
+use crate::{curve::KimchiCurve, error::IndexSerializationError};
+use blake2::{Blake2b512, Digest};
+use std::io::{Read, Write};
+
+/// The magic bytes at the beginning of an index file
+pub const INDEX_MAGIC: [u8; 8] = *b"KIMCHIDX";
+
+/// The current version of the index file format. It must be incremented each
+/// time the layout of the header or of one of the indexes changes.
+pub const INDEX_FORMAT_VERSION: u32 = 3;
+
+/// Size in bytes of the checksum of the encoded index
+pub(crate) const CHECKSUM_SIZE: usize = 64;
+
+/// The kind of index stored in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    Prover,
+    Verifier,
+    /// A prover index whose column evaluations are stored separately, to be
+    /// loaded on demand (see `crate::lazy_index`).
+    LazyProver,
+}
+
+impl IndexKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            IndexKind::Prover => 0,
+            IndexKind::Verifier => 1,
+            IndexKind::LazyProver => 2,
+        }
+    }
+
+    fn from_byte(b: u8) -> Option<Self> {
+        match b {
+            0 => Some(IndexKind::Prover),
+            1 => Some(IndexKind::Verifier),
+            2 => Some(IndexKind::LazyProver),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            IndexKind::Prover => "prover",
+            IndexKind::Verifier => "verifier",
+            IndexKind::LazyProver => "lazy prover",
+        }
+    }
+}
+
+pub(crate) fn checksum(payload: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut hasher = Blake2b512::new();
+    hasher.update(payload);
+    hasher.finalize().into()
+}
+
+/// Write the header and the encoded index `payload` to `writer`.
+pub(crate) fn write_index<G: KimchiCurve, W: Write>(
+    mut writer: W,
+    kind: IndexKind,
+    domain_size: u64,
+    payload: &[u8],
+) -> Result<(), IndexSerializationError> {
+    let curve = G::NAME.as_bytes();
+    let curve_len = u8::try_from(curve.len())
+        .map_err(|_| IndexSerializationError::Encode("curve name too long".to_string()))?;
+
+    writer.write_all(&INDEX_MAGIC)?;
+    writer.write_all(&INDEX_FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[kind.to_byte(), curve_len])?;
+    writer.write_all(curve)?;
+    writer.write_all(&domain_size.to_le_bytes())?;
+    writer.write_all(&(payload.len() as u64).to_le_bytes())?;
+    writer.write_all(&checksum(payload))?;
+    writer.write_all(payload)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], std::io::Error> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Read and check the header from `reader`, and return the domain size stored
+/// in the header together with the encoded index.
+pub(crate) fn read_index<G: KimchiCurve, R: Read>(
+    mut reader: R,
+    kind: IndexKind,
+) -> Result<(u64, Vec<u8>), IndexSerializationError> {
+    if read_array::<_, 8>(&mut reader)? != INDEX_MAGIC {
+        return Err(IndexSerializationError::InvalidMagic);
+    }
+
+    let version = u32::from_le_bytes(read_array(&mut reader)?);
+    if version != INDEX_FORMAT_VERSION {
+        return Err(IndexSerializationError::UnsupportedVersion(
+            INDEX_FORMAT_VERSION,
+            version,
+        ));
+    }
+
+    let [kind_byte, curve_len] = read_array(&mut reader)?;
+    let file_kind =
+        IndexKind::from_byte(kind_byte).ok_or(IndexSerializationError::UnknownKind(kind_byte))?;
+    if file_kind != kind {
+        return Err(IndexSerializationError::IncorrectIndexKind(
+            kind.name(),
+            file_kind.name(),
+        ));
+    }
+
+    let mut curve = vec![0u8; curve_len as usize];
+    reader.read_exact(&mut curve)?;
+    let curve = String::from_utf8_lossy(&curve).into_owned();
+    if curve != G::NAME {
+        return Err(IndexSerializationError::IncorrectCurve(G::NAME, curve));
+    }
+
+    let domain_size = u64::from_le_bytes(read_array(&mut reader)?);
+    let payload_len = u64::from_le_bytes(read_array(&mut reader)?);
+    let expected_checksum: [u8; CHECKSUM_SIZE] = read_array(&mut reader)?;
+
+    let mut payload = vec![];
+    reader.take(payload_len).read_to_end(&mut payload)?;
+    if payload.len() as u64 != payload_len || checksum(&payload) != expected_checksum {
+        return Err(IndexSerializationError::ChecksumMismatch);
+    }
+
+    Ok((domain_size, payload))
+}
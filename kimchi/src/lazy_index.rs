@@ -0,0 +1,464 @@
+//! This module implements the lazy loading of large indexes, using
+//! memory-mapped files. It is only available with the `lazy-index` feature.
+//!
+//! For big circuits, the column evaluations of the [`ProverIndex`] and the SRS
+//! take gigabytes, and reading them from disk takes minutes. Instead, the file
+//! is memory-mapped and:
+//! - the SRS is deserialized directly from the mapped memory, without copying
+//!   the file in an intermediate buffer, see [`load_srs`],
+//! - the column evaluations of the [`ProverIndex`] are stored separately, and
+//!   each of them is only decoded the first time it is accessed, see
+//!   [`LazyProverIndex::column`].
+//!
+//! The prover does not decode the columns on demand: a proof is created with
+//! the [`ProverIndex`] given by [`LazyProverIndex::into_prover_index`], which
+//! decodes the remaining columns. The columns already decoded are moved into
+//! it, not copied.
+//!
+//! A lazy prover index file starts with the header described in
+//! [`crate::index_serialization`], whose payload contains the constraint
+//! system. It is followed by a table of contents, and by the encoded column
+//! evaluations. The table of contents contains the number of columns as a
+//! little-endian `u32`, and for each column its identifier (two bytes), its
+//! offset and its length (as little-endian `u64`) and its Blake2b-512
+//! checksum, which is verified when the column is decoded.
+
+use crate::{
+    alphas::Alphas,
+    circuits::{
+        berkeley_columns::{BerkeleyChallengeTerm, Column},
+        constraints::{ColumnEvaluations, ConstraintSystem},
+        expr::{Linearization, PolishToken},
+        wires::{COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+    error::IndexSerializationError,
+    index_serialization::{checksum, read_index, write_index, IndexKind, CHECKSUM_SIZE},
+    linearization::expr_linearization,
+    prover_index::ProverIndex,
+};
+use ark_ff::PrimeField;
+use ark_poly::{Evaluations, Radix2EvaluationDomain as D};
+use memmap2::Mmap;
+use once_cell::sync::OnceCell;
+use poly_commitment::OpenProof;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::serde_as;
+use std::{
+    array,
+    cell::RefCell,
+    fs::File,
+    io::{Cursor, Write},
+    ops::Range,
+    path::Path,
+    sync::Arc,
+};
+
+type Evals<F> = Evaluations<F, D<F>>;
+
+/// Size in bytes of an entry of the table of contents
+const ENTRY_SIZE: usize = 2 + 8 + 8 + CHECKSUM_SIZE;
+
+/// Identifies one of the column evaluations of a [`ProverIndex`], see
+/// [`ColumnEvaluations`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnId {
+    PermutationCoefficients(usize),
+    Coefficients(usize),
+    GenericSelector,
+    PoseidonSelector,
+    CompleteAddSelector,
+    MulSelector,
+    EmulSelector,
+    EndomulScalarSelector,
+    RangeCheck0Selector,
+    RangeCheck1Selector,
+    ForeignFieldAddSelector,
+    ForeignFieldMulSelector,
+    XorSelector,
+    RotSelector,
+}
+
+impl ColumnId {
+    /// All the column identifiers, in the order used in the file.
+    pub fn all() -> Vec<ColumnId> {
+        let mut res = Vec::with_capacity(PERMUTS + COLUMNS + 12);
+        res.extend((0..PERMUTS).map(ColumnId::PermutationCoefficients));
+        res.extend((0..COLUMNS).map(ColumnId::Coefficients));
+        res.extend([
+            ColumnId::GenericSelector,
+            ColumnId::PoseidonSelector,
+            ColumnId::CompleteAddSelector,
+            ColumnId::MulSelector,
+            ColumnId::EmulSelector,
+            ColumnId::EndomulScalarSelector,
+            ColumnId::RangeCheck0Selector,
+            ColumnId::RangeCheck1Selector,
+            ColumnId::ForeignFieldAddSelector,
+            ColumnId::ForeignFieldMulSelector,
+            ColumnId::XorSelector,
+            ColumnId::RotSelector,
+        ]);
+        res
+    }
+
+    fn to_bytes(self) -> [u8; 2] {
+        match self {
+            ColumnId::PermutationCoefficients(i) => [0, i as u8],
+            ColumnId::Coefficients(i) => [1, i as u8],
+            ColumnId::GenericSelector => [2, 0],
+            ColumnId::PoseidonSelector => [3, 0],
+            ColumnId::CompleteAddSelector => [4, 0],
+            ColumnId::MulSelector => [5, 0],
+            ColumnId::EmulSelector => [6, 0],
+            ColumnId::EndomulScalarSelector => [7, 0],
+            ColumnId::RangeCheck0Selector => [8, 0],
+            ColumnId::RangeCheck1Selector => [9, 0],
+            ColumnId::ForeignFieldAddSelector => [10, 0],
+            ColumnId::ForeignFieldMulSelector => [11, 0],
+            ColumnId::XorSelector => [12, 0],
+            ColumnId::RotSelector => [13, 0],
+        }
+    }
+
+    fn from_bytes(bytes: [u8; 2]) -> Option<Self> {
+        let [tag, i] = bytes;
+        let i = i as usize;
+        match tag {
+            0 if i < PERMUTS => Some(ColumnId::PermutationCoefficients(i)),
+            1 if i < COLUMNS => Some(ColumnId::Coefficients(i)),
+            2 => Some(ColumnId::GenericSelector),
+            3 => Some(ColumnId::PoseidonSelector),
+            4 => Some(ColumnId::CompleteAddSelector),
+            5 => Some(ColumnId::MulSelector),
+            6 => Some(ColumnId::EmulSelector),
+            7 => Some(ColumnId::EndomulScalarSelector),
+            8 => Some(ColumnId::RangeCheck0Selector),
+            9 => Some(ColumnId::RangeCheck1Selector),
+            10 => Some(ColumnId::ForeignFieldAddSelector),
+            11 => Some(ColumnId::ForeignFieldMulSelector),
+            12 => Some(ColumnId::XorSelector),
+            13 => Some(ColumnId::RotSelector),
+            _ => None,
+        }
+    }
+
+    /// Returns the evaluations of the column in `evals`, if the column is used
+    /// by the circuit.
+    pub fn get<F: PrimeField>(self, evals: &ColumnEvaluations<F>) -> Option<&Evals<F>> {
+        match self {
+            ColumnId::PermutationCoefficients(i) => evals.permutation_coefficients8.get(i),
+            ColumnId::Coefficients(i) => evals.coefficients8.get(i),
+            ColumnId::GenericSelector => Some(&evals.generic_selector4),
+            ColumnId::PoseidonSelector => Some(&evals.poseidon_selector8),
+            ColumnId::CompleteAddSelector => Some(&evals.complete_add_selector4),
+            ColumnId::MulSelector => Some(&evals.mul_selector8),
+            ColumnId::EmulSelector => Some(&evals.emul_selector8),
+            ColumnId::EndomulScalarSelector => Some(&evals.endomul_scalar_selector8),
+            ColumnId::RangeCheck0Selector => evals.range_check0_selector8.as_ref(),
+            ColumnId::RangeCheck1Selector => evals.range_check1_selector8.as_ref(),
+            ColumnId::ForeignFieldAddSelector => evals.foreign_field_add_selector8.as_ref(),
+            ColumnId::ForeignFieldMulSelector => evals.foreign_field_mul_selector8.as_ref(),
+            ColumnId::XorSelector => evals.xor_selector8.as_ref(),
+            ColumnId::RotSelector => evals.rot_selector8.as_ref(),
+        }
+    }
+}
+
+/// The part of the [`ProverIndex`] decoded eagerly
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+struct LazyIndexMeta<G: KimchiCurve> {
+    #[serde(bound = "ConstraintSystem<G::ScalarField>: Serialize + DeserializeOwned")]
+    cs: ConstraintSystem<G::ScalarField>,
+    max_poly_size: usize,
+    #[serde_as(as = "Option<o1_utils::serialization::SerdeAs>")]
+    verifier_index_digest: Option<G::BaseField>,
+}
+
+/// Wrapper used to encode a single column
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+struct EncodedColumn<F: PrimeField>(#[serde_as(as = "o1_utils::serialization::SerdeAs")] Evals<F>);
+
+struct LazyColumn<F: PrimeField> {
+    id: ColumnId,
+    range: Range<usize>,
+    checksum: [u8; CHECKSUM_SIZE],
+    evals: OnceCell<Evals<F>>,
+}
+
+impl<F: PrimeField> LazyColumn<F> {
+    /// Decodes the evaluations of the column from the mapped file `mmap`,
+    /// after checking their checksum.
+    fn decode(&self, mmap: &[u8]) -> Result<Evals<F>, IndexSerializationError> {
+        let blob = &mmap[self.range.clone()];
+        if checksum(blob) != self.checksum {
+            return Err(IndexSerializationError::ChecksumMismatch);
+        }
+        let EncodedColumn(evals) = rmp_serde::from_slice(blob)
+            .map_err(|e| IndexSerializationError::Decode(e.to_string()))?;
+        Ok(evals)
+    }
+}
+
+/// A [`ProverIndex`] backed by a memory-mapped file, whose column evaluations
+/// are decoded on demand.
+pub struct LazyProverIndex<G: KimchiCurve, OpeningProof: OpenProof<G>> {
+    /// constraints system polynomials
+    pub cs: ConstraintSystem<G::ScalarField>,
+    /// The symbolic linearization of the circuit
+    pub linearization:
+        Linearization<Vec<PolishToken<G::ScalarField, Column, BerkeleyChallengeTerm>>, Column>,
+    /// The mapping between powers of alpha and constraints
+    pub powers_of_alpha: Alphas<G::ScalarField>,
+    /// polynomial commitment keys
+    pub srs: Arc<OpeningProof::SRS>,
+    /// maximal size of polynomial section
+    pub max_poly_size: usize,
+    /// The verifier index digest corresponding to this prover index
+    pub verifier_index_digest: Option<G::BaseField>,
+    mmap: Mmap,
+    columns: Vec<LazyColumn<G::ScalarField>>,
+}
+
+impl<G: KimchiCurve, OpeningProof: OpenProof<G>> ProverIndex<G, OpeningProof>
+where
+    G::BaseField: PrimeField,
+{
+    /// Writes the [`ProverIndex`] to `writer`, in a format that can be loaded
+    /// lazily with [`LazyProverIndex::open`]. The SRS is not written.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index cannot be encoded or written.
+    pub fn serialize_lazy_to<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), IndexSerializationError> {
+        let encode = |e: rmp_serde::encode::Error| IndexSerializationError::Encode(e.to_string());
+        let meta = rmp_serde::to_vec(&LazyIndexMeta::<G> {
+            cs: self.cs.clone(),
+            max_poly_size: self.max_poly_size,
+            verifier_index_digest: self.verifier_index_digest,
+        })
+        .map_err(encode)?;
+        write_index::<G, _>(
+            &mut writer,
+            IndexKind::LazyProver,
+            self.cs.domain.d1.size,
+            &meta,
+        )?;
+
+        let mut blobs = vec![];
+        for id in ColumnId::all() {
+            if let Some(evals) = id.get(&self.column_evaluations) {
+                let blob = rmp_serde::to_vec(&EncodedColumn(evals.clone())).map_err(encode)?;
+                blobs.push((id, blob));
+            }
+        }
+
+        writer.write_all(&(blobs.len() as u32).to_le_bytes())?;
+        let mut offset = 0u64;
+        for (id, blob) in blobs.iter() {
+            writer.write_all(&id.to_bytes())?;
+            writer.write_all(&offset.to_le_bytes())?;
+            writer.write_all(&(blob.len() as u64).to_le_bytes())?;
+            writer.write_all(&checksum(blob))?;
+            offset += blob.len() as u64;
+        }
+        for (_, blob) in blobs.iter() {
+            writer.write_all(blob)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<G: KimchiCurve, OpeningProof: OpenProof<G>> LazyProverIndex<G, OpeningProof>
+where
+    G::BaseField: PrimeField,
+{
+    /// Memory-maps the file at `path`, written with
+    /// [`ProverIndex::serialize_lazy_to`]. Only the constraint system is
+    /// decoded, the column evaluations are decoded on demand.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be mapped, if the header does not
+    /// match the expected version, curve or domain, or if the table of
+    /// contents is malformed.
+    pub fn open(path: &Path, srs: Arc<OpeningProof::SRS>) -> Result<Self, IndexSerializationError> {
+        let file = File::open(path)?;
+        // SAFETY: the file must not be modified while it is mapped. The
+        // columns are checked against their checksum when they are decoded.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let mut cursor = Cursor::new(&mmap[..]);
+        let (domain_size, meta) = read_index::<G, _>(&mut cursor, IndexKind::LazyProver)?;
+        let meta: LazyIndexMeta<G> = rmp_serde::from_slice(&meta)
+            .map_err(|e| IndexSerializationError::Decode(e.to_string()))?;
+        if meta.cs.domain.d1.size != domain_size {
+            return Err(IndexSerializationError::IncorrectDomainSize(
+                domain_size,
+                meta.cs.domain.d1.size,
+            ));
+        }
+
+        let truncated = || IndexSerializationError::Decode("truncated index".to_string());
+        let bytes = &mmap[cursor.position() as usize..];
+        let nb_columns =
+            u32::from_le_bytes(bytes.get(0..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+        let toc = bytes
+            .get(4..4 + nb_columns * ENTRY_SIZE)
+            .ok_or_else(truncated)?;
+        let blobs_start = cursor.position() as usize + 4 + nb_columns * ENTRY_SIZE;
+
+        let mut columns = Vec::with_capacity(nb_columns);
+        for entry in toc.chunks_exact(ENTRY_SIZE) {
+            let id = ColumnId::from_bytes([entry[0], entry[1]]).ok_or_else(|| {
+                IndexSerializationError::Decode(format!("unknown column {entry:?}"))
+            })?;
+            let offset = u64::from_le_bytes(entry[2..10].try_into().unwrap());
+            let len = u64::from_le_bytes(entry[10..18].try_into().unwrap());
+            let range = usize::try_from(offset)
+                .ok()
+                .zip(usize::try_from(len).ok())
+                .and_then(|(offset, len)| {
+                    let start = blobs_start.checked_add(offset)?;
+                    Some(start..start.checked_add(len)?)
+                })
+                .filter(|range| range.end <= mmap.len())
+                .ok_or_else(truncated)?;
+            columns.push(LazyColumn {
+                id,
+                range,
+                checksum: entry[18..].try_into().unwrap(),
+                evals: OnceCell::new(),
+            });
+        }
+
+        let (linearization, powers_of_alpha) =
+            expr_linearization(Some(&meta.cs.feature_flags), true);
+
+        Ok(LazyProverIndex {
+            cs: meta.cs,
+            linearization,
+            powers_of_alpha,
+            srs,
+            max_poly_size: meta.max_poly_size,
+            verifier_index_digest: meta.verifier_index_digest,
+            mmap,
+            columns,
+        })
+    }
+
+    /// Returns the evaluations of the column `id`, decoding them from the
+    /// mapped file the first time they are accessed. Returns `Ok(None)` if the
+    /// column is not used by the circuit.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the checksum of the column is incorrect, or if the
+    /// column cannot be decoded.
+    pub fn column(
+        &self,
+        id: ColumnId,
+    ) -> Result<Option<&Evals<G::ScalarField>>, IndexSerializationError> {
+        let column = match self.columns.iter().find(|c| c.id == id) {
+            None => return Ok(None),
+            Some(column) => column,
+        };
+        column
+            .evals
+            .get_or_try_init(|| column.decode(&self.mmap))
+            .map(Some)
+    }
+
+    /// Returns the number of columns already decoded.
+    pub fn materialized_columns(&self) -> usize {
+        self.columns
+            .iter()
+            .filter(|c| c.evals.get().is_some())
+            .count()
+    }
+
+    /// Decodes all the remaining columns and returns the corresponding
+    /// [`ProverIndex`]. The columns already decoded are moved into it.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if one of the columns cannot be decoded, or if a column
+    /// required by the circuit is missing.
+    pub fn into_prover_index(
+        self,
+    ) -> Result<ProverIndex<G, OpeningProof>, IndexSerializationError> {
+        let columns = RefCell::new(self.columns);
+        let missing =
+            |id: ColumnId| IndexSerializationError::Decode(format!("missing column {id:?}"));
+        let get = |id: ColumnId| -> Result<Option<Evals<G::ScalarField>>, IndexSerializationError> {
+            let mut columns = columns.borrow_mut();
+            let column = match columns.iter_mut().find(|c| c.id == id) {
+                None => return Ok(None),
+                Some(column) => column,
+            };
+            match column.evals.take() {
+                Some(evals) => Ok(Some(evals)),
+                None => column.decode(&self.mmap).map(Some),
+            }
+        };
+        let required = |id: ColumnId| get(id)?.ok_or_else(|| missing(id));
+
+        let permutation_coefficients8 =
+            array::from_fn(|i| required(ColumnId::PermutationCoefficients(i)));
+        let coefficients8 = array::from_fn(|i| required(ColumnId::Coefficients(i)));
+        let column_evaluations = ColumnEvaluations {
+            permutation_coefficients8: transpose_results(permutation_coefficients8)?,
+            coefficients8: transpose_results(coefficients8)?,
+            generic_selector4: required(ColumnId::GenericSelector)?,
+            poseidon_selector8: required(ColumnId::PoseidonSelector)?,
+            complete_add_selector4: required(ColumnId::CompleteAddSelector)?,
+            mul_selector8: required(ColumnId::MulSelector)?,
+            emul_selector8: required(ColumnId::EmulSelector)?,
+            endomul_scalar_selector8: required(ColumnId::EndomulScalarSelector)?,
+            range_check0_selector8: get(ColumnId::RangeCheck0Selector)?,
+            range_check1_selector8: get(ColumnId::RangeCheck1Selector)?,
+            foreign_field_add_selector8: get(ColumnId::ForeignFieldAddSelector)?,
+            foreign_field_mul_selector8: get(ColumnId::ForeignFieldMulSelector)?,
+            xor_selector8: get(ColumnId::XorSelector)?,
+            rot_selector8: get(ColumnId::RotSelector)?,
+        };
+
+        Ok(ProverIndex {
+            cs: self.cs,
+            linearization: self.linearization,
+            powers_of_alpha: self.powers_of_alpha,
+            srs: self.srs,
+            max_poly_size: self.max_poly_size,
+            column_evaluations,
+            verifier_index: None,
+            verifier_index_digest: self.verifier_index_digest,
+        })
+    }
+}
+
+fn transpose_results<T, E, const N: usize>(results: [Result<T, E>; N]) -> Result<[T; N], E> {
+    let mut res = Vec::with_capacity(N);
+    for r in results {
+        res.push(r?);
+    }
+    Ok(res.try_into().unwrap_or_else(|_| unreachable!()))
+}
+
+/// Loads a SRS serialized with MessagePack from the file at `path`. The file
+/// is memory-mapped, and the SRS is decoded directly from the mapped memory.
+///
+/// # Errors
+///
+/// Will give error if the file cannot be mapped or the SRS cannot be decoded.
+pub fn load_srs<SRS: DeserializeOwned>(path: &Path) -> Result<SRS, IndexSerializationError> {
+    let file = File::open(path)?;
+    // SAFETY: the file must not be modified while it is mapped.
+    let mmap = unsafe { Mmap::map(&file)? };
+    rmp_serde::from_slice(&mmap[..]).map_err(|e| IndexSerializationError::Decode(e.to_string()))
+}
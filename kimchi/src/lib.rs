@@ -10,12 +10,22 @@ pub use o1_utils;
 pub use poly_commitment;
 pub use turshi;
 
+pub mod accumulator;
 pub mod alphas;
 pub mod bench;
+#[cfg(feature = "ocaml-interop")]
+pub mod bin_prot;
 pub mod circuits;
+pub mod compressed_proof;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod curve;
 pub mod error;
+pub mod incremental_prover;
+pub mod index_serialization;
 pub mod lagrange_basis_evaluations;
+#[cfg(feature = "lazy-index")]
+pub mod lazy_index;
 pub mod linearization;
 pub mod oracles;
 pub mod plonk_sponge;
@@ -23,9 +33,13 @@ pub mod precomputed_srs;
 pub mod proof;
 pub mod prover;
 pub mod prover_index;
+#[cfg(feature = "prover-metrics")]
+pub mod prover_metrics;
 pub mod snarky;
+pub mod transcript;
 pub mod verifier;
 pub mod verifier_index;
+pub mod witness_source;
 
 #[cfg(test)]
 mod tests;
@@ -26,14 +26,22 @@ use crate::{
     },
 };
 
-use crate::circuits::{
-    berkeley_columns::Column,
-    constraints::FeatureFlags,
-    expr::{ConstantExpr, Expr, FeatureFlag, Linearization, PolishToken},
-    gate::GateType,
-    wires::COLUMNS,
+use crate::{
+    circuits::{
+        berkeley_columns::{BerkeleyChallenges, Column},
+        constraints::{ConstraintSystem, FeatureFlags},
+        expr::{ConstantExpr, Constants, Expr, FeatureFlag, Linearization, PolishToken},
+        gate::GateType,
+        scalars::RandomOracles,
+        wires::{COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+    proof::{PointEvaluations, ProofEvaluations},
+    verifier_index::VerifierIndex,
 };
-use ark_ff::{FftField, PrimeField, Zero};
+use ark_ff::{FftField, Field, One, PrimeField, Zero};
+use ark_poly::Polynomial;
+use poly_commitment::OpenProof;
 
 /// Get the expresion of constraints.
 ///
@@ -365,3 +373,193 @@ pub fn expr_linearization<F: PrimeField>(
 
     (linearization, powers_of_alpha)
 }
+
+/// The evaluation of the linearization by the verifier, see [evaluate]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinearizationEvaluation<F> {
+    /// The evaluation of `ft` at `zeta`, which is not sent by the prover
+    pub ft_eval0: F,
+    /// The scalars of the commitments combined into the commitment of the
+    /// linearized polynomial `f`, with their columns
+    pub scalars: Vec<(Column, F)>,
+}
+
+/// Evaluates the linearization of the constraints of `index` as the verifier
+/// does, from the evaluations `evals` of the proof combined over their chunks,
+/// the evaluation `public_eval` at `zeta` of the negated public input
+/// polynomial, and the challenges of the proof. The result gives `ft(zeta)`
+/// and the scalars combining the commitments of the columns into the
+/// commitment of `f`, so that other verifiers, e.g. in-circuit ones, compute
+/// them as [crate::verifier] does.
+///
+/// # Panics
+///
+/// Will panic if the linearization of `index` cannot be evaluated, or if the
+/// powers of alpha of the permutation are missing.
+pub fn evaluate<G: KimchiCurve, OpeningProof: OpenProof<G>>(
+    index: &VerifierIndex<G, OpeningProof>,
+    evals: &ProofEvaluations<PointEvaluations<G::ScalarField>>,
+    public_eval: G::ScalarField,
+    challenges: &RandomOracles<G::ScalarField>,
+) -> LinearizationEvaluation<G::ScalarField> {
+    LinearizationEvaluation {
+        ft_eval0: ft_eval0(index, evals, public_eval, challenges),
+        scalars: commitment_scalars(index, evals, challenges),
+    }
+}
+
+/// Computes the evaluation of `ft` at `zeta`, see [evaluate].
+///
+/// # Panics
+///
+/// Will panic if the constant term of the linearization cannot be evaluated,
+/// or if the powers of alpha of the permutation are missing.
+pub fn ft_eval0<G: KimchiCurve, OpeningProof: OpenProof<G>>(
+    index: &VerifierIndex<G, OpeningProof>,
+    evals: &ProofEvaluations<PointEvaluations<G::ScalarField>>,
+    public_eval: G::ScalarField,
+    challenges: &RandomOracles<G::ScalarField>,
+) -> G::ScalarField {
+    let RandomOracles {
+        alpha,
+        beta,
+        gamma,
+        zeta,
+        ..
+    } = *challenges;
+
+    let permutation_vanishing_polynomial =
+        index.permutation_vanishing_polynomial_m().evaluate(&zeta);
+    let zeta1m1 = zeta.pow([index.domain.size]) - G::ScalarField::one();
+
+    let mut all_alphas = index.powers_of_alpha.clone();
+    all_alphas.instantiate(alpha);
+    let mut alpha_powers =
+        all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
+    let alpha0 = alpha_powers
+        .next()
+        .expect("missing power of alpha for permutation");
+    let alpha1 = alpha_powers
+        .next()
+        .expect("missing power of alpha for permutation");
+    let alpha2 = alpha_powers
+        .next()
+        .expect("missing power of alpha for permutation");
+
+    let init = (evals.witness(PERMUTS - 1).zeta + gamma)
+        * evals.z.zeta_omega
+        * alpha0
+        * permutation_vanishing_polynomial;
+    let mut ft_eval0 = evals
+        .w
+        .iter()
+        .zip(evals.s.iter())
+        .map(|(w, s)| (beta * s.zeta) + w.zeta + gamma)
+        .fold(init, |x, y| x * y);
+
+    ft_eval0 -= public_eval;
+
+    ft_eval0 -= evals
+        .w
+        .iter()
+        .zip(index.shift.iter())
+        .map(|(w, s)| gamma + (beta * zeta * s) + w.zeta)
+        .fold(
+            alpha0 * permutation_vanishing_polynomial * evals.z.zeta,
+            |x, y| x * y,
+        );
+
+    let numerator = ((zeta1m1 * alpha1 * (zeta - index.w()))
+        + (zeta1m1 * alpha2 * (zeta - G::ScalarField::one())))
+        * (G::ScalarField::one() - evals.z.zeta);
+
+    let denominator = (zeta - index.w()) * (zeta - G::ScalarField::one());
+    let denominator = denominator.inverse().expect("negligible probability");
+
+    ft_eval0 += numerator * denominator;
+
+    ft_eval0
+        - PolishToken::evaluate(
+            &index.linearization.constant_term,
+            index.domain,
+            zeta,
+            evals,
+            &verifier_constants(index),
+            &berkeley_challenges(challenges),
+        )
+        .unwrap()
+}
+
+/// Computes the scalars of the commitments combined into the commitment of
+/// the linearized polynomial `f`, see [evaluate]. The first one is the scalar
+/// of the last permutation column, whose evaluation is not in the proof.
+///
+/// # Panics
+///
+/// Will panic if the linearization of `index` cannot be evaluated, or if the
+/// powers of alpha of the permutation are missing.
+pub fn commitment_scalars<G: KimchiCurve, OpeningProof: OpenProof<G>>(
+    index: &VerifierIndex<G, OpeningProof>,
+    evals: &ProofEvaluations<PointEvaluations<G::ScalarField>>,
+    challenges: &RandomOracles<G::ScalarField>,
+) -> Vec<(Column, G::ScalarField)> {
+    // the permutation is written manually (not using the expr framework)
+    let permutation_vanishing_polynomial = index
+        .permutation_vanishing_polynomial_m()
+        .evaluate(&challenges.zeta);
+
+    let mut all_alphas = index.powers_of_alpha.clone();
+    all_alphas.instantiate(challenges.alpha);
+    let alphas = all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
+
+    let mut scalars = vec![(
+        Column::Permutation(PERMUTS - 1),
+        ConstraintSystem::<G::ScalarField>::perm_scalars(
+            evals,
+            challenges.beta,
+            challenges.gamma,
+            alphas,
+            permutation_vanishing_polynomial,
+        ),
+    )];
+
+    // other gates are implemented using the expression framework
+    let constants = verifier_constants(index);
+    let berkeley_challenges = berkeley_challenges(challenges);
+    for (col, tokens) in &index.linearization.index_terms {
+        let scalar = PolishToken::evaluate(
+            tokens,
+            index.domain,
+            challenges.zeta,
+            evals,
+            &constants,
+            &berkeley_challenges,
+        )
+        .expect("should evaluate");
+        scalars.push((*col, scalar));
+    }
+    scalars
+}
+
+fn verifier_constants<G: KimchiCurve, OpeningProof: OpenProof<G>>(
+    index: &VerifierIndex<G, OpeningProof>,
+) -> Constants<G::ScalarField> {
+    Constants {
+        endo_coefficient: index.endo,
+        mds: &G::sponge_params().mds,
+        zk_rows: index.zk_rows,
+    }
+}
+
+fn berkeley_challenges<F: Field>(challenges: &RandomOracles<F>) -> BerkeleyChallenges<F> {
+    BerkeleyChallenges {
+        alpha: challenges.alpha,
+        beta: challenges.beta,
+        gamma: challenges.gamma,
+        joint_combiner: challenges
+            .joint_combiner
+            .as_ref()
+            .map(|j| j.1)
+            .unwrap_or(F::zero()),
+    }
+}
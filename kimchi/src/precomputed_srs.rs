@@ -14,7 +14,7 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use poly_commitment::{hash_map_cache::HashMapCache, ipa::SRS, PolyComm};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use std::{collections::HashMap, fs::File, io::BufReader, path::PathBuf};
+use std::{collections::HashMap, fs::File, io::BufReader, path::PathBuf, sync::Arc};
 
 /// We store several different types of SRS objects. This enum parameterizes
 /// them.
@@ -64,6 +64,7 @@ impl<G> From<TestSRS<G>> for SRS<G> {
             g: value.g,
             h: value.h,
             lagrange_bases: HashMapCache::new_from_hashmap(value.lagrange_bases),
+            msm_table: Arc::default(),
         }
     }
 }
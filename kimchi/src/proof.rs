@@ -8,10 +8,20 @@ use crate::circuits::{
 };
 use ark_ec::AffineRepr;
 use ark_ff::{FftField, One, Zero};
-use ark_poly::univariate::DensePolynomial;
-use o1_utils::ExtendedDensePolynomial;
-use poly_commitment::commitment::{b_poly, b_poly_coefficients, PolyComm};
-use serde::{Deserialize, Serialize};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::cfg_iter;
+use o1_utils::{
+    serialization::{from_msgpack, DeserializationError, DeserializationMode},
+    ExtendedDensePolynomial,
+};
+use poly_commitment::{
+    commitment::{b_poly, b_poly_coefficients, CommitmentCurve, PolyComm},
+    SRS,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
 
@@ -167,6 +177,18 @@ pub struct ProverProof<G: AffineRepr, OpeningProof> {
 }
 
 /// A struct to store the challenges inside a `ProverProof`
+///
+/// The challenges `chals` are the ones of the opening proof of a previous
+/// proof, and `comm` is the commitment to the polynomial `b` they define, see
+/// [RecursionChallenge::from_challenges]. The polynomial is folded into the
+/// opening proof of the proof carrying it, so that its correctness is checked
+/// with the next opening proof.
+///
+/// The order of the challenges of a proof is part of its transcript: the
+/// prover and the verifier absorb the commitments, in order, in the Fq-sponge
+/// right after the digest of the verifier index, and the challenges, in order,
+/// in a separate Fr-sponge whose digest is absorbed after the Fq-sponge digest.
+/// A proof is thus not valid for another order of its challenges.
 #[serde_as]
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(bound = "G: ark_serialize::CanonicalDeserialize + ark_serialize::CanonicalSerialize")]
@@ -353,11 +375,59 @@ impl<Eval> ProofEvaluations<Eval> {
     }
 }
 
+impl<G: AffineRepr, OpeningProof> ProverProof<G, OpeningProof>
+where
+    G: CanonicalDeserialize + CanonicalSerialize,
+    OpeningProof: Serialize + DeserializeOwned,
+{
+    /// Decodes a MessagePack-encoded proof with the given
+    /// [`DeserializationMode`]. In strict mode, a proof containing a
+    /// non-canonical encoding, e.g. a field element that is not reduced, is
+    /// rejected.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` cannot be decoded, or if the mode is strict
+    /// and `bytes` is not the canonical encoding of the proof.
+    pub fn from_bytes(
+        bytes: &[u8],
+        mode: DeserializationMode,
+    ) -> Result<Self, DeserializationError> {
+        from_msgpack(bytes, mode)
+    }
+}
+
 impl<G: AffineRepr> RecursionChallenge<G> {
     pub fn new(chals: Vec<G::ScalarField>, comm: PolyComm<G>) -> RecursionChallenge<G> {
         RecursionChallenge { chals, comm }
     }
 
+    /// The polynomial `b` defined by the challenges, of degree
+    /// `2^chals.len() - 1`
+    pub fn b_poly(&self) -> DensePolynomial<G::ScalarField> {
+        DensePolynomial::from_coefficients_vec(b_poly_coefficients(&self.chals))
+    }
+
+    /// The evaluations of the challenges `challenges` at `evaluation_points`,
+    /// see [RecursionChallenge::evals], computed in parallel with the
+    /// `parallel` feature.
+    pub fn evals_batch(
+        challenges: &[Self],
+        max_poly_size: usize,
+        evaluation_points: &[G::ScalarField],
+        powers_of_eval_points_for_chunks: &[G::ScalarField],
+    ) -> Vec<Vec<Vec<G::ScalarField>>> {
+        cfg_iter!(challenges)
+            .map(|challenge| {
+                challenge.evals(
+                    max_poly_size,
+                    evaluation_points,
+                    powers_of_eval_points_for_chunks,
+                )
+            })
+            .collect()
+    }
+
     pub fn evals(
         &self,
         max_poly_size: usize,
@@ -400,6 +470,32 @@ impl<G: AffineRepr> RecursionChallenge<G> {
     }
 }
 
+impl<G: CommitmentCurve> RecursionChallenge<G> {
+    /// The challenges `chals` with the commitment to their polynomial `b`,
+    /// chunked by the maximum size of the polynomials of `srs`.
+    ///
+    /// For the challenges of an IPA opening proof with `srs`, the commitment
+    /// has a single chunk, equal to the point `sg` of the proof.
+    pub fn from_challenges(srs: &impl SRS<G>, chals: Vec<G::ScalarField>) -> Self {
+        let comm = Self::commit(srs, &chals);
+        RecursionChallenge { chals, comm }
+    }
+
+    /// Checks that `comm` is the commitment to the polynomial of `chals` with
+    /// `srs`, see [RecursionChallenge::from_challenges].
+    pub fn check_commitment(&self, srs: &impl SRS<G>) -> bool {
+        Self::commit(srs, &self.chals) == self.comm
+    }
+
+    fn commit(srs: &impl SRS<G>, chals: &[G::ScalarField]) -> PolyComm<G> {
+        let b_len = 1 << chals.len();
+        let max_poly_size = srs.max_poly_size();
+        let num_chunks = std::cmp::max(1, (b_len + max_poly_size - 1) / max_poly_size);
+        let b = DensePolynomial::from_coefficients_vec(b_poly_coefficients(chals));
+        srs.commit_non_hiding(&b, num_chunks)
+    }
+}
+
 impl<F: Zero + Copy> ProofEvaluations<PointEvaluations<F>> {
     pub fn dummy_with_witness_evaluations(
         curr: [F; COLUMNS],
@@ -486,6 +582,162 @@ impl<F> ProofEvaluations<F> {
             Column::Permutation(i) => Some(&self.s[i]),
         }
     }
+
+    /// The evaluation of the witness column `col`
+    pub fn witness(&self, col: usize) -> &F {
+        &self.w[col]
+    }
+
+    /// The evaluation of the coefficient column `col`
+    pub fn coefficient(&self, col: usize) -> &F {
+        &self.coefficients[col]
+    }
+
+    /// The evaluation of the permutation polynomial `i`, for `i < PERMUTS - 1`
+    pub fn sigma(&self, i: usize) -> &F {
+        &self.s[i]
+    }
+
+    /// The evaluation of the selector of `gate_type`, if there is one
+    pub fn selector(&self, gate_type: GateType) -> Option<&F> {
+        self.get_column(Column::Index(gate_type))
+    }
+
+    /// The columns which can be evaluated in a proof, except the public input,
+    /// in the order of the opening proof
+    pub fn columns() -> impl Iterator<Item = Column> {
+        [
+            Column::Z,
+            Column::Index(GateType::Generic),
+            Column::Index(GateType::Poseidon),
+            Column::Index(GateType::CompleteAdd),
+            Column::Index(GateType::VarBaseMul),
+            Column::Index(GateType::EndoMul),
+            Column::Index(GateType::EndoMulScalar),
+        ]
+        .into_iter()
+        .chain((0..COLUMNS).map(Column::Witness))
+        .chain((0..COLUMNS).map(Column::Coefficient))
+        .chain((0..PERMUTS - 1).map(Column::Permutation))
+        .chain(
+            [
+                GateType::RangeCheck0,
+                GateType::RangeCheck1,
+                GateType::ForeignFieldAdd,
+                GateType::ForeignFieldMul,
+                GateType::Xor16,
+                GateType::Rot64,
+            ]
+            .map(Column::Index),
+        )
+        .chain((0..5).map(Column::LookupSorted))
+        .chain([
+            Column::LookupAggreg,
+            Column::LookupTable,
+            Column::LookupRuntimeTable,
+            Column::LookupRuntimeSelector,
+        ])
+        .chain(
+            [
+                LookupPattern::Xor,
+                LookupPattern::Lookup,
+                LookupPattern::RangeCheck,
+                LookupPattern::ForeignFieldMul,
+            ]
+            .map(Column::LookupKindIndex),
+        )
+    }
+
+    /// Iterates over the evaluations of the proof, except the one of the public
+    /// input, with their columns, in the order of [`ProofEvaluations::columns`]
+    pub fn iter(&self) -> impl Iterator<Item = (Column, &F)> {
+        Self::columns().filter_map(move |col| Some((col, self.get_column(col)?)))
+    }
+
+    /// Pairs the evaluations of `self` and `other`, or returns `None` if they
+    /// do not evaluate the same columns.
+    pub fn zip<F2>(self, other: ProofEvaluations<F2>) -> Option<ProofEvaluations<(F, F2)>> {
+        fn zip_option<A, B>(a: Option<A>, b: Option<B>) -> Option<Option<(A, B)>> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(Some((a, b))),
+                (None, None) => Some(None),
+                _ => None,
+            }
+        }
+        fn zip_array<A, B, const N: usize>(a: [A; N], b: [B; N]) -> [(A, B); N] {
+            let mut b = b.into_iter();
+            a.map(|a| (a, b.next().expect("arrays of the same length")))
+        }
+
+        let mut sorted_mismatch = false;
+        let mut other_sorted = other.lookup_sorted.into_iter();
+        let lookup_sorted = self.lookup_sorted.map(|a| {
+            let b = other_sorted.next().expect("arrays of the same length");
+            zip_option(a, b).unwrap_or_else(|| {
+                sorted_mismatch = true;
+                None
+            })
+        });
+        if sorted_mismatch {
+            return None;
+        }
+
+        Some(ProofEvaluations {
+            public: zip_option(self.public, other.public)?,
+            w: zip_array(self.w, other.w),
+            z: (self.z, other.z),
+            s: zip_array(self.s, other.s),
+            coefficients: zip_array(self.coefficients, other.coefficients),
+            generic_selector: (self.generic_selector, other.generic_selector),
+            poseidon_selector: (self.poseidon_selector, other.poseidon_selector),
+            complete_add_selector: (self.complete_add_selector, other.complete_add_selector),
+            mul_selector: (self.mul_selector, other.mul_selector),
+            emul_selector: (self.emul_selector, other.emul_selector),
+            endomul_scalar_selector: (self.endomul_scalar_selector, other.endomul_scalar_selector),
+            range_check0_selector: zip_option(
+                self.range_check0_selector,
+                other.range_check0_selector,
+            )?,
+            range_check1_selector: zip_option(
+                self.range_check1_selector,
+                other.range_check1_selector,
+            )?,
+            foreign_field_add_selector: zip_option(
+                self.foreign_field_add_selector,
+                other.foreign_field_add_selector,
+            )?,
+            foreign_field_mul_selector: zip_option(
+                self.foreign_field_mul_selector,
+                other.foreign_field_mul_selector,
+            )?,
+            xor_selector: zip_option(self.xor_selector, other.xor_selector)?,
+            rot_selector: zip_option(self.rot_selector, other.rot_selector)?,
+            lookup_aggregation: zip_option(self.lookup_aggregation, other.lookup_aggregation)?,
+            lookup_table: zip_option(self.lookup_table, other.lookup_table)?,
+            lookup_sorted,
+            runtime_lookup_table: zip_option(
+                self.runtime_lookup_table,
+                other.runtime_lookup_table,
+            )?,
+            runtime_lookup_table_selector: zip_option(
+                self.runtime_lookup_table_selector,
+                other.runtime_lookup_table_selector,
+            )?,
+            xor_lookup_selector: zip_option(self.xor_lookup_selector, other.xor_lookup_selector)?,
+            lookup_gate_lookup_selector: zip_option(
+                self.lookup_gate_lookup_selector,
+                other.lookup_gate_lookup_selector,
+            )?,
+            range_check_lookup_selector: zip_option(
+                self.range_check_lookup_selector,
+                other.range_check_lookup_selector,
+            )?,
+            foreign_field_mul_lookup_selector: zip_option(
+                self.foreign_field_mul_lookup_selector,
+                other.foreign_field_mul_lookup_selector,
+            )?,
+        })
+    }
 }
 
 //
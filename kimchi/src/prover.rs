@@ -1,5 +1,7 @@
 //! This module implements prover's zk-proof primitive.
 
+#[cfg(feature = "prover-metrics")]
+use crate::prover_metrics::{self, ProverMetrics};
 use crate::{
     circuits::{
         argument::{Argument, ArgumentType},
@@ -32,24 +34,30 @@ use crate::{
         RecursionChallenge,
     },
     prover_index::ProverIndex,
+    transcript::{FqTranscript, FrTranscript},
     verifier_index::VerifierIndex,
+    witness_source::{self, WitnessSource},
 };
 use ark_ff::{FftField, Field, One, PrimeField, UniformRand, Zero};
 use ark_poly::{
-    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Evaluations, Polynomial,
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Polynomial,
     Radix2EvaluationDomain as D,
 };
+use ark_serialize::CanonicalSerialize;
+use ark_std::cfg_iter_mut;
+use blake2::{Blake2b512, Digest};
 use itertools::Itertools;
 use mina_poseidon::{sponge::ScalarChallenge, FqSponge};
 use o1_utils::ExtendedDensePolynomial as _;
 use poly_commitment::{
-    commitment::{
-        absorb_commitment, b_poly_coefficients, BlindedCommitment, CommitmentCurve, PolyComm,
-    },
+    commitment::{BlindedCommitment, CommitmentCurve, PolyComm},
     ipa::DensePolynomialOrEvaluations,
+    shared::SharedBlob,
     OpenProof, SRS as _,
 };
+use rand::{rngs::StdRng, SeedableRng};
 use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use std::{array, collections::HashMap};
 
@@ -74,6 +82,28 @@ macro_rules! check_constraint {
     }};
 }
 
+/// Starts recording a new stage of the prover, if the `prover-metrics`
+/// feature is enabled (see [`ProverProof::create_with_metrics`]).
+macro_rules! begin_stage {
+    ($stage:ident) => {
+        #[cfg(feature = "prover-metrics")]
+        crate::prover_metrics::begin_stage(crate::prover_metrics::ProverStage::$stage);
+    };
+}
+
+/// Runs `a` and `b` concurrently if the `parallel` feature is enabled, and
+/// sequentially otherwise.
+fn join<A: Send, B: Send>(a: impl FnOnce() -> A + Send, b: impl FnOnce() -> B + Send) -> (A, B) {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::join(a, b)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (a(), b())
+    }
+}
+
 /// Contains variables needed for lookup in the prover algorithm.
 #[derive(Default)]
 struct LookupContext<G, F>
@@ -123,6 +153,112 @@ where
     runtime_second_col_d8: Option<Evaluations<F, D<F>>>,
 }
 
+/// Configuration of the prover, see [`ProverProof::create_with_config`].
+/// [`ProverProof::create`] always uses the default configuration.
+///
+/// The configuration only changes the randomness of the prover: the verifier
+/// index does not depend on it, and the verifier accepts the proofs of any
+/// configuration, hiding or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProverConfig {
+    /// The number of zero-knowledge rows the proof is expected to use. It is
+    /// not chosen by the prover: the number of zero-knowledge rows is fixed
+    /// when the constraint system is built (see
+    /// [`Builder::zk_rows`](crate::circuits::constraints::Builder::zk_rows)),
+    /// and recorded in the verifier index. If set, the prover checks that the
+    /// index uses this number of rows.
+    pub zk_rows: Option<u64>,
+    /// Whether the commitments are hiding and the zero-knowledge rows are
+    /// randomized, including the ones of the sorted, aggregation and runtime
+    /// table polynomials of the lookup argument. Disabling it produces valid
+    /// but non zero-knowledge proofs, and must only be used for benchmarking.
+    /// The choice is not recorded in the verifier index nor in the proof.
+    pub hiding: bool,
+    /// If set, the randomness of the prover is derived from this seed instead
+    /// of the RNG given to the prover.
+    pub seed: Option<[u8; 32]>,
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        ProverConfig {
+            zk_rows: None,
+            hiding: true,
+            seed: None,
+        }
+    }
+}
+
+/// An RNG returning only zeros, used to disable the blinding of the proofs.
+/// It is not a cryptographic RNG and must not be used for anything else.
+struct ZeroRng;
+
+impl RngCore for ZeroRng {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand_core::Error> {
+        dest.fill(0);
+        Ok(())
+    }
+}
+
+impl CryptoRng for ZeroRng {}
+
+/// Returns the seed of the randomness of a deterministic proof (see
+/// [`ProverProof::create_deterministic`]), i.e. the Blake2b hash of a
+/// transcript of the inputs of the prover: the digest of the verifier index,
+/// the witness, the runtime tables and the previous challenges.
+pub fn deterministic_seed<G, OpeningProof, EFqSponge>(
+    index: &ProverIndex<G, OpeningProof>,
+    witness: &[Vec<G::ScalarField>; COLUMNS],
+    runtime_tables: &[RuntimeTable<G::ScalarField>],
+    prev_challenges: &[RecursionChallenge<G>],
+) -> [u8; 32]
+where
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    VerifierIndex<G, OpeningProof>: Clone,
+{
+    fn absorb(hasher: &mut Blake2b512, x: &impl CanonicalSerialize) {
+        let mut bytes = vec![];
+        x.serialize_compressed(&mut bytes)
+            .expect("a value can be written to a vector");
+        hasher.update(bytes);
+    }
+
+    let mut hasher = Blake2b512::new();
+    hasher.update(b"kimchi_deterministic_prover");
+    absorb(&mut hasher, &index.verifier_index_digest::<EFqSponge>());
+    for column in witness {
+        absorb(&mut hasher, column);
+    }
+    hasher.update((runtime_tables.len() as u64).to_le_bytes());
+    for table in runtime_tables {
+        hasher.update(table.id.to_le_bytes());
+        absorb(&mut hasher, &table.data);
+    }
+    hasher.update((prev_challenges.len() as u64).to_le_bytes());
+    for challenge in prev_challenges {
+        absorb(&mut hasher, &challenge.chals);
+        absorb(&mut hasher, &challenge.comm.chunks);
+    }
+
+    let mut seed = [0; 32];
+    seed.copy_from_slice(&hasher.finalize()[..32]);
+    seed
+}
+
 impl<G: KimchiCurve, OpeningProof: OpenProof<G>> ProverProof<G, OpeningProof>
 where
     G::BaseField: PrimeField,
@@ -157,6 +293,180 @@ where
         )
     }
 
+    /// Constructs a proof like [`ProverProof::create`], and reports the wall
+    /// time and the peak heap usage of each stage of the prover. The heap
+    /// usage is only measured if
+    /// [`MetricsAllocator`](crate::prover_metrics::MetricsAllocator) is the
+    /// global allocator.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    #[cfg(feature = "prover-metrics")]
+    pub fn create_with_metrics<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+    >(
+        groupmap: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        rng: &mut RNG,
+    ) -> Result<(Self, ProverMetrics)>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        prover_metrics::start_recording();
+        let proof = Self::create::<EFqSponge, EFrSponge, RNG>(
+            groupmap,
+            witness,
+            runtime_tables,
+            index,
+            rng,
+        );
+        // always stop recording, even if the prover failed
+        let metrics = prover_metrics::take_metrics();
+        Ok((proof?, metrics))
+    }
+
+    /// Constructs a proof like [`ProverProof::create`], collecting the
+    /// witness from the rows of `source`. All the rows are read before
+    /// proving, into columns allocated once with the size of the domain, so
+    /// that they do not need to be copied when padded.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the witness cannot be read from `source`, or if
+    /// `create_recursive` process fails.
+    pub fn create_from_rows<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        S: WitnessSource<G::ScalarField>,
+    >(
+        groupmap: &G::Map,
+        mut source: S,
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        rng: &mut RNG,
+    ) -> Result<Self>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        let witness = witness_source::collect_columns(&mut source, index.cs.domain.d1.size())
+            .map_err(|_| ProverError::WitnessSource)?;
+        Self::create::<EFqSponge, EFrSponge, RNG>(groupmap, witness, runtime_tables, index, rng)
+    }
+
+    /// Constructs a recursive proof like [`ProverProof::create_recursive`],
+    /// using the configuration `config` instead of the default one.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index does not match the configuration, or if
+    /// `create_recursive` process fails.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_with_config<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        config: &ProverConfig,
+        rng: &mut RNG,
+    ) -> Result<Self>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        if let Some(zk_rows) = config.zk_rows {
+            if zk_rows != index.cs.zk_rows {
+                return Err(ProverError::IncompatibleZkRows(zk_rows, index.cs.zk_rows));
+            }
+        }
+        if !config.hiding {
+            Self::create_recursive::<EFqSponge, EFrSponge, _>(
+                group_map,
+                witness,
+                runtime_tables,
+                index,
+                prev_challenges,
+                None,
+                &mut ZeroRng,
+            )
+        } else if let Some(seed) = config.seed {
+            Self::create_recursive::<EFqSponge, EFrSponge, _>(
+                group_map,
+                witness,
+                runtime_tables,
+                index,
+                prev_challenges,
+                None,
+                &mut StdRng::from_seed(seed),
+            )
+        } else {
+            Self::create_recursive::<EFqSponge, EFrSponge, RNG>(
+                group_map,
+                witness,
+                runtime_tables,
+                index,
+                prev_challenges,
+                None,
+                rng,
+            )
+        }
+    }
+
+    /// Constructs a recursive proof like [`ProverProof::create_recursive`],
+    /// with a randomness derived from the inputs of the prover (see
+    /// [`deterministic_seed`]) instead of an RNG. The same inputs always give
+    /// the same proof, byte for byte, e.g. for regression tests.
+    ///
+    /// The proofs remain zero-knowledge as long as the witness is unknown to
+    /// the verifier, but two proofs of the same witness can be linked.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    pub fn create_deterministic<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+    ) -> Result<Self>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        let seed = deterministic_seed::<G, OpeningProof, EFqSponge>(
+            index,
+            &witness,
+            runtime_tables,
+            &prev_challenges,
+        );
+        let config = ProverConfig {
+            seed: Some(seed),
+            ..ProverConfig::default()
+        };
+        // the RNG is not used, as the seed is set
+        Self::create_with_config::<EFqSponge, EFrSponge, _>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            &config,
+            &mut ZeroRng,
+        )
+    }
+
     /// This function constructs prover's recursive zk-proof from the witness &
     /// the `ProverIndex` against SRS instance
     ///
@@ -172,6 +482,89 @@ where
         EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
         EFrSponge: FrSponge<G::ScalarField>,
         RNG: RngCore + CryptoRng,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
+        rng: &mut RNG,
+    ) -> Result<Self>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        Self::create_recursive_with_shared::<EFqSponge, EFrSponge, RNG>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            blinders,
+            &[],
+            rng,
+        )
+    }
+
+    /// Constructs a proof like [`ProverProof::create`], where some witness
+    /// columns are data blobs already committed to, e.g. because they are
+    /// shared with another proof. Each element of `shared` is the index of a
+    /// column and the blob it contains. The padded blob is used as the column,
+    /// including its zero-knowledge rows, and its commitment is used as the
+    /// commitment of the column instead of being recomputed.
+    ///
+    /// A verifier can check that the proof refers to a blob with
+    /// [`SharedBlob::is_committed_in`] on the commitment of the column.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a column does not match its blob, or if
+    /// `create_recursive` process fails.
+    pub fn create_with_shared_columns<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        shared: &[(usize, &SharedBlob<G>)],
+        rng: &mut RNG,
+    ) -> Result<Self>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        Self::create_recursive_with_shared::<EFqSponge, EFrSponge, RNG>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            Vec::new(),
+            None,
+            shared,
+            rng,
+        )
+    }
+
+    /// Constructs a recursive proof like [`ProverProof::create_recursive`],
+    /// with the shared columns `shared` (see
+    /// [`ProverProof::create_with_shared_columns`]). The blinders of the shared
+    /// columns are the ones of their blobs, and `blinders` is ignored for them.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a column does not match its blob, or if inputs (like
+    /// `lookup_context.joint_lookup_table_d8`) are None.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `lookup_context.joint_lookup_table_d8` is None.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_recursive_with_shared<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
     >(
         group_map: &G::Map,
         mut witness: [Vec<G::ScalarField>; COLUMNS],
@@ -179,12 +572,14 @@ where
         index: &ProverIndex<G, OpeningProof>,
         prev_challenges: Vec<RecursionChallenge<G>>,
         blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
+        shared: &[(usize, &SharedBlob<G>)],
         rng: &mut RNG,
     ) -> Result<Self>
     where
         VerifierIndex<G, OpeningProof>: Clone,
     {
         internal_tracing::checkpoint!(internal_traces; create_recursive);
+        begin_stage!(WitnessCommitment);
         let d1_size = index.cs.domain.d1.size();
 
         let (_, endo_r) = G::endos();
@@ -235,11 +630,20 @@ where
         //~ 1. Pad the witness columns with Zero gates to make them the same length as the domain.
         //~    Then, randomize the last `zk_rows` of each columns.
         internal_tracing::checkpoint!(internal_traces; pad_witness);
-        for w in &mut witness {
+        for (col, w) in witness.iter_mut().enumerate() {
             if w.len() != length_witness {
                 return Err(ProverError::WitnessCsInconsistent);
             }
 
+            // shared column: the blob is already padded, with its own zk-rows
+            if let Some((_, blob)) = shared.iter().find(|(c, _)| *c == col) {
+                if blob.evals.domain() != index.cs.domain.d1 || !blob.matches(w) {
+                    return Err(ProverError::SharedColumnMismatch(col));
+                }
+                *w = blob.evals.evals.clone();
+                continue;
+            }
+
             // padding
             w.extend(std::iter::repeat(G::ScalarField::zero()).take(length_padding));
 
@@ -251,15 +655,16 @@ where
 
         //~ 1. Setup the Fq-Sponge.
         internal_tracing::checkpoint!(internal_traces; set_up_fq_sponge);
-        let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
+        let mut fq_transcript =
+            FqTranscript::<G, _>::new(EFqSponge::new(G::other_curve_sponge_params()));
 
         //~ 1. Absorb the digest of the VerifierIndex.
         let verifier_index_digest = index.verifier_index_digest::<EFqSponge>();
-        fq_sponge.absorb_fq(&[verifier_index_digest]);
+        fq_transcript.absorb_fq("verifier_index_digest", &[verifier_index_digest]);
 
         //~ 1. Absorb the commitments of the previous challenges with the Fq-sponge.
         for RecursionChallenge { comm, .. } in &prev_challenges {
-            absorb_commitment(&mut fq_sponge, comm)
+            fq_transcript.absorb_commitment("prev_challenge_comm", comm)
         }
 
         //~ 1. Compute the negated public input polynomial as
@@ -290,7 +695,7 @@ where
         //~    Note: unlike the original PLONK protocol,
         //~    the prover also provides evaluations of the public polynomial to help the verifier circuit.
         //~    This is why we need to absorb the commitment to the public polynomial at this point.
-        absorb_commitment(&mut fq_sponge, &public_comm);
+        fq_transcript.absorb_commitment("public_comm", &public_comm);
 
         //~ 1. Commit to the witness columns by creating `COLUMNS` hidding commitments.
         //~
@@ -299,6 +704,12 @@ where
         internal_tracing::checkpoint!(internal_traces; commit_to_witness_columns);
         let mut w_comm = vec![];
         for col in 0..COLUMNS {
+            // shared column: reuse the commitment of the blob
+            if let Some((_, blob)) = shared.iter().find(|(c, _)| *c == col) {
+                w_comm.push(blob.commitment.clone());
+                continue;
+            }
+
             // witness coeff -> witness eval
             let witness_eval =
                 Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
@@ -334,7 +745,7 @@ where
         //~ 1. Absorb the witness commitments with the Fq-Sponge.
         w_comm
             .iter()
-            .for_each(|c| absorb_commitment(&mut fq_sponge, &c.commitment));
+            .for_each(|c| fq_transcript.absorb_commitment("witness_comm", &c.commitment));
 
         //~ 1. Compute the witness polynomials by interpolating each `COLUMNS` of the witness.
         //~    As mentioned above, we commit using the evaluations form rather than the coefficients
@@ -348,6 +759,7 @@ where
             .interpolate()
         });
 
+        begin_stage!(LookupAggregation);
         let mut lookup_context = LookupContext::default();
 
         //~ 1. If using lookup:
@@ -409,13 +821,12 @@ where
                         .commit(&runtime_table_contribution, num_chunks, rng);
 
                 // absorb the commitment
-                absorb_commitment(&mut fq_sponge, &runtime_table_comm.commitment);
+                fq_transcript
+                    .absorb_commitment("runtime_table_comm", &runtime_table_comm.commitment);
 
                 // pre-compute the updated second column of the lookup table
                 let mut second_column_d8 = runtime_table_contribution_d8.clone();
-                second_column_d8
-                    .evals
-                    .par_iter_mut()
+                cfg_iter_mut!(second_column_d8.evals)
                     .enumerate()
                     .for_each(|(row, e)| {
                         *e += lcs.lookup_table8[1][row];
@@ -431,7 +842,7 @@ where
             //~~   then squeeze the Fq-Sponge to obtain the joint combiner challenge $j'$,
             //~~   otherwise set the joint combiner challenge $j'$ to $0$.
             let joint_combiner = if lcs.configuration.lookup_info.features.joint_lookup_used {
-                fq_sponge.challenge()
+                fq_transcript.challenge("joint_combiner")
             } else {
                 G::ScalarField::zero()
             };
@@ -527,7 +938,7 @@ where
                 index.cs.zk_rows as usize,
             )?;
 
-            //~~ * Randomize the last `EVALS` rows in each of the sorted polynomials
+            //~~ * Randomize the last `zk_rows` rows in each of the sorted polynomials
             //~~   in order to add zero-knowledge to the protocol.
             let sorted: Vec<_> = sorted
                 .into_iter()
@@ -550,7 +961,7 @@ where
             //~~ * Absorb each commitments to the sorted polynomials.
             sorted_comms
                 .iter()
-                .for_each(|c| absorb_commitment(&mut fq_sponge, &c.commitment));
+                .for_each(|c| fq_transcript.absorb_commitment("lookup_sorted_comm", &c.commitment));
 
             // precompute different forms of the sorted polynomials for later
             // TODO: We can avoid storing these coefficients.
@@ -570,10 +981,10 @@ where
         }
 
         //~ 1. Sample $\beta$ with the Fq-Sponge.
-        let beta = fq_sponge.challenge();
+        let beta = fq_transcript.challenge("beta");
 
         //~ 1. Sample $\gamma$ with the Fq-Sponge.
-        let gamma = fq_sponge.challenge();
+        let gamma = fq_transcript.challenge("gamma");
 
         //~ 1. If using lookup:
         if let Some(lcs) = &index.cs.lookup_constraint_system {
@@ -602,7 +1013,7 @@ where
                 .commit_evaluations(index.cs.domain.d1, &aggreg, rng);
 
             //~~ * Absorb the commitment to the aggregation polynomial with the Fq-Sponge.
-            absorb_commitment(&mut fq_sponge, &aggreg_comm.commitment);
+            fq_transcript.absorb_commitment("lookup_aggreg_comm", &aggreg_comm.commitment);
 
             // precompute different forms of the aggregation polynomial for later
             let aggreg_coeffs = aggreg.interpolate();
@@ -617,16 +1028,19 @@ where
 
         //~ 1. Compute the permutation aggregation polynomial $z$.
         internal_tracing::checkpoint!(internal_traces; z_permutation_aggregation_polynomial);
+        begin_stage!(Permutation);
         let z_poly = index.perm_aggreg(&witness, &beta, &gamma, rng)?;
 
         //~ 1. Commit (hidding) to the permutation aggregation polynomial $z$.
         let z_comm = index.srs.commit(&z_poly, num_chunks, rng);
 
         //~ 1. Absorb the permutation aggregation polynomial $z$ with the Fq-Sponge.
-        absorb_commitment(&mut fq_sponge, &z_comm.commitment);
+        fq_transcript.absorb_commitment("z_comm", &z_comm.commitment);
+
+        begin_stage!(Quotient);
 
         //~ 1. Sample $\alpha'$ with the Fq-Sponge.
-        let alpha_chal = ScalarChallenge(fq_sponge.challenge());
+        let alpha_chal = ScalarChallenge(fq_transcript.challenge("alpha"));
 
         //~ 1. Derive $\alpha$ from $\alpha'$ using the endomorphism (TODO: details)
         let alpha: G::ScalarField = alpha_chal.to_field(endo_r);
@@ -748,12 +1162,96 @@ where
         internal_tracing::checkpoint!(internal_traces; compute_quotient_poly);
 
         let quotient_poly = {
+            //~~ * The permutation argument and the generic gate are evaluated
+            //~~   concurrently. The expressions of the custom gates and of the
+            //~~   lookup argument are then evaluated over $d4$ or $d8$ one by
+            //~~   one, and added to the evaluations of the quotient in place.
+            let range_check0_enabled = index.column_evaluations.range_check0_selector8.is_some();
+            let range_check1_enabled = index.column_evaluations.range_check1_selector8.is_some();
+            let foreign_field_addition_enabled = index
+                .column_evaluations
+                .foreign_field_add_selector8
+                .is_some();
+            let foreign_field_multiplication_enabled = index
+                .column_evaluations
+                .foreign_field_mul_selector8
+                .is_some();
+            let xor_enabled = index.column_evaluations.xor_selector8.is_some();
+            let rot_enabled = index.column_evaluations.rot_selector8.is_some();
+
+            // (argument, index of the constraint, constraint, power of alpha,
+            // whether a zero-valued evaluation over another domain is
+            // allowed). The label of a constraint is only formatted when the
+            // witness does not satisfy it.
+            let mut constraints = vec![];
+            for gate in [
+                (
+                    (&CompleteAdd::default()
+                        as &dyn crate::circuits::argument::DynArgument<G::ScalarField>),
+                    true,
+                ),
+                (&VarbaseMul::default(), true),
+                (&EndosclMul::default(), true),
+                (&EndomulScalar::default(), true),
+                (&Poseidon::default(), true),
+                // Range check gates
+                (&RangeCheck0::default(), range_check0_enabled),
+                (&RangeCheck1::default(), range_check1_enabled),
+                // Foreign field addition gate
+                (&ForeignFieldAdd::default(), foreign_field_addition_enabled),
+                // Foreign field multiplication gate
+                (
+                    &ForeignFieldMul::default(),
+                    foreign_field_multiplication_enabled,
+                ),
+                // Xor gate
+                (&Xor16::default(), xor_enabled),
+                // Rot gate
+                (&Rot64::default(), rot_enabled),
+            ]
+            .into_iter()
+            .filter_map(|(gate, is_enabled)| if is_enabled { Some(gate) } else { None })
+            {
+                constraints.push((
+                    gate.argument_type(),
+                    0,
+                    gate.combined_constraints(&all_alphas, &mut cache),
+                    None,
+                    false,
+                ));
+            }
+
+            // lookup
+            if let Some(lcs) = index.cs.lookup_constraint_system.as_ref() {
+                let lookup_constraints =
+                    lookup::constraints::constraints(&lcs.configuration, false);
+                let constraints_len = u32::try_from(lookup_constraints.len())
+                    .expect("not expecting a large amount of constraints");
+                let lookup_alphas = all_alphas.get_alphas(ArgumentType::Lookup, constraints_len);
+
+                // as lookup constraints are computed with the expression framework,
+                // each of them can result in Evaluations of different domains
+                for (ii, (constraint, alpha_pow)) in lookup_constraints
+                    .into_iter()
+                    .zip_eq(lookup_alphas)
+                    .enumerate()
+                {
+                    constraints.push((ArgumentType::Lookup, ii, constraint, Some(alpha_pow), true));
+                }
+            }
+
+            let generic_constraint =
+                generic::Generic::combined_constraints(&all_alphas, &mut cache);
+            let perm_alphas =
+                all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
+
+            let (perm, generic4) = join(
+                || index.perm_quot(&lagrange, beta, gamma, &z_poly, perm_alphas),
+                || generic_constraint.evaluations(&env),
+            );
+
             // generic
             let mut t4 = {
-                let generic_constraint =
-                    generic::Generic::combined_constraints(&all_alphas, &mut cache);
-                let generic4 = generic_constraint.evaluations(&env);
-
                 if cfg!(debug_assertions) {
                     let p4 = public_poly.evaluate_over_domain_by_ref(index.cs.domain.d4);
                     let gen_minus_pub = &generic4 + &p4;
@@ -765,103 +1263,29 @@ where
             };
             // permutation
             let (mut t8, bnd) = {
-                let alphas =
-                    all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
-                let (perm, bnd) = index.perm_quot(&lagrange, beta, gamma, &z_poly, alphas)?;
+                let (perm, bnd) = perm?;
 
                 check_constraint!(index, perm);
 
                 (perm, bnd)
             };
 
-            {
-                use crate::circuits::argument::DynArgument;
-
-                let range_check0_enabled =
-                    index.column_evaluations.range_check0_selector8.is_some();
-                let range_check1_enabled =
-                    index.column_evaluations.range_check1_selector8.is_some();
-                let foreign_field_addition_enabled = index
-                    .column_evaluations
-                    .foreign_field_add_selector8
-                    .is_some();
-                let foreign_field_multiplication_enabled = index
-                    .column_evaluations
-                    .foreign_field_mul_selector8
-                    .is_some();
-                let xor_enabled = index.column_evaluations.xor_selector8.is_some();
-                let rot_enabled = index.column_evaluations.rot_selector8.is_some();
-
-                for gate in [
-                    (
-                        (&CompleteAdd::default() as &dyn DynArgument<G::ScalarField>),
-                        true,
-                    ),
-                    (&VarbaseMul::default(), true),
-                    (&EndosclMul::default(), true),
-                    (&EndomulScalar::default(), true),
-                    (&Poseidon::default(), true),
-                    // Range check gates
-                    (&RangeCheck0::default(), range_check0_enabled),
-                    (&RangeCheck1::default(), range_check1_enabled),
-                    // Foreign field addition gate
-                    (&ForeignFieldAdd::default(), foreign_field_addition_enabled),
-                    // Foreign field multiplication gate
-                    (
-                        &ForeignFieldMul::default(),
-                        foreign_field_multiplication_enabled,
-                    ),
-                    // Xor gate
-                    (&Xor16::default(), xor_enabled),
-                    // Rot gate
-                    (&Rot64::default(), rot_enabled),
-                ]
-                .into_iter()
-                .filter_map(|(gate, is_enabled)| if is_enabled { Some(gate) } else { None })
-                {
-                    let constraint = gate.combined_constraints(&all_alphas, &mut cache);
-                    let eval = constraint.evaluations(&env);
-                    if eval.domain().size == t4.domain().size {
-                        t4 += &eval;
-                    } else if eval.domain().size == t8.domain().size {
-                        t8 += &eval;
-                    } else {
-                        panic!("Bad evaluation")
-                    }
-                    check_constraint!(index, format!("{:?}", gate.argument_type()), eval);
+            // custom gates and lookup, each evaluation being dropped once added
+            for (argument, ii, constraint, alpha_pow, skip_zero) in &constraints {
+                let mut eval = constraint.evaluations(&env);
+                if let Some(alpha_pow) = alpha_pow {
+                    cfg_iter_mut!(eval.evals).for_each(|x| *x *= alpha_pow);
                 }
-            };
-
-            // lookup
-            {
-                if let Some(lcs) = index.cs.lookup_constraint_system.as_ref() {
-                    let constraints = lookup::constraints::constraints(&lcs.configuration, false);
-                    let constraints_len = u32::try_from(constraints.len())
-                        .expect("not expecting a large amount of constraints");
-                    let lookup_alphas =
-                        all_alphas.get_alphas(ArgumentType::Lookup, constraints_len);
-
-                    // as lookup constraints are computed with the expression framework,
-                    // each of them can result in Evaluations of different domains
-                    for (ii, (constraint, alpha_pow)) in
-                        constraints.into_iter().zip_eq(lookup_alphas).enumerate()
-                    {
-                        let mut eval = constraint.evaluations(&env);
-                        eval.evals.par_iter_mut().for_each(|x| *x *= alpha_pow);
-
-                        if eval.domain().size == t4.domain().size {
-                            t4 += &eval;
-                        } else if eval.domain().size == t8.domain().size {
-                            t8 += &eval;
-                        } else if eval.evals.iter().all(|x| x.is_zero()) {
-                            // Skip any 0-valued evaluations
-                        } else {
-                            panic!("Bad evaluation")
-                        }
-
-                        check_constraint!(index, format!("lookup constraint #{ii}"), eval);
-                    }
+                if eval.domain().size == t4.domain().size {
+                    t4 += &eval;
+                } else if eval.domain().size == t8.domain().size {
+                    t8 += &eval;
+                } else if *skip_zero && eval.evals.iter().all(|x| x.is_zero()) {
+                    // Skip any 0-valued evaluations
+                } else {
+                    panic!("Bad evaluation")
                 }
+                check_constraint!(index, format!("{argument:?} constraint #{ii}"), eval);
             }
 
             // public polynomial
@@ -886,10 +1310,12 @@ where
         let t_comm = { index.srs.commit(&quotient_poly, 7 * num_chunks, rng) };
 
         //~ 1. Absorb the commitment of the quotient polynomial with the Fq-Sponge.
-        absorb_commitment(&mut fq_sponge, &t_comm.commitment);
+        fq_transcript.absorb_commitment("t_comm", &t_comm.commitment);
+
+        begin_stage!(Evaluations);
 
         //~ 1. Sample $\zeta'$ with the Fq-Sponge.
-        let zeta_chal = ScalarChallenge(fq_sponge.challenge());
+        let zeta_chal = ScalarChallenge(fq_transcript.challenge("zeta"));
 
         //~ 1. Derive $\zeta$ from $\zeta'$ using the endomorphism (TODO: specify)
         let zeta = zeta_chal.to_field(endo_r);
@@ -1179,38 +1605,36 @@ where
         let ft_eval1 = ft.evaluate(&zeta_omega);
 
         //~ 1. Setup the Fr-Sponge
-        let fq_sponge_before_evaluations = fq_sponge.clone();
-        let mut fr_sponge = EFrSponge::new(G::sponge_params());
+        let fq_sponge_before_evaluations = fq_transcript.sponge().clone();
+        let mut fr_transcript =
+            FrTranscript::<G::ScalarField, _>::new(EFrSponge::new(G::sponge_params()));
 
         //~ 1. Squeeze the Fq-sponge and absorb the result with the Fr-Sponge.
-        fr_sponge.absorb(&fq_sponge.digest());
+        fr_transcript.absorb("fq_digest", &fq_transcript.digest("fq_digest"));
 
         //~ 1. Absorb the previous recursion challenges.
         let prev_challenge_digest = {
             // Note: we absorb in a new sponge here to limit the scope in which we need the
             // more-expensive 'optional sponge'.
-            let mut fr_sponge = EFrSponge::new(G::sponge_params());
+            let mut fr_transcript =
+                FrTranscript::<G::ScalarField, _>::new(EFrSponge::new(G::sponge_params()));
             for RecursionChallenge { chals, .. } in &prev_challenges {
-                fr_sponge.absorb_multiple(chals);
+                fr_transcript.absorb_multiple("prev_challenges", chals);
             }
-            fr_sponge.digest()
+            fr_transcript.digest("prev_challenge_digest")
         };
-        fr_sponge.absorb(&prev_challenge_digest);
+        fr_transcript.absorb("prev_challenge_digest", &prev_challenge_digest);
 
         //~ 1. Compute evaluations for the previous recursion challenges.
         internal_tracing::checkpoint!(internal_traces; build_polynomials);
+        begin_stage!(OpeningProof);
         let polys = prev_challenges
             .iter()
-            .map(|RecursionChallenge { chals, comm }| {
-                (
-                    DensePolynomial::from_coefficients_vec(b_poly_coefficients(chals)),
-                    comm.len(),
-                )
-            })
+            .map(|challenge| (challenge.b_poly(), challenge.comm.len()))
             .collect::<Vec<_>>();
 
         //~ 1. Absorb the unique evaluation of ft: $ft(\zeta\omega)$.
-        fr_sponge.absorb(&ft_eval1);
+        fr_transcript.absorb("ft_eval1", &ft_eval1);
 
         //~ 1. Absorb all the polynomial evaluations in $\zeta$ and $\zeta\omega$:
         //~~ * the public polynomial
@@ -1219,18 +1643,21 @@ where
         //~~ * poseidon selector
         //~~ * the 15 register/witness
         //~~ * 6 sigmas evaluations (the last one is not evaluated)
-        fr_sponge.absorb_multiple(&chunked_evals.public.as_ref().unwrap().zeta);
-        fr_sponge.absorb_multiple(&chunked_evals.public.as_ref().unwrap().zeta_omega);
-        fr_sponge.absorb_evaluations(&chunked_evals);
+        fr_transcript.absorb_multiple("public_evals", &chunked_evals.public.as_ref().unwrap().zeta);
+        fr_transcript.absorb_multiple(
+            "public_evals",
+            &chunked_evals.public.as_ref().unwrap().zeta_omega,
+        );
+        fr_transcript.absorb_evaluations("evaluations", &chunked_evals);
 
         //~ 1. Sample $v'$ with the Fr-Sponge
-        let v_chal = fr_sponge.challenge();
+        let v_chal = fr_transcript.challenge("v");
 
         //~ 1. Derive $v$ from $v'$ using the endomorphism (TODO: specify)
         let v = v_chal.to_field(endo_r);
 
         //~ 1. Sample $u'$ with the Fr-Sponge
-        let u_chal = fr_sponge.challenge();
+        let u_chal = fr_transcript.challenge("u");
 
         //~ 1. Derive $u$ from $u'$ using the endomorphism (TODO: specify)
         let u = u_chal.to_field(endo_r);
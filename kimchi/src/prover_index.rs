@@ -8,15 +8,21 @@ use crate::{
         expr::{Linearization, PolishToken},
     },
     curve::KimchiCurve,
+    error::IndexSerializationError,
+    index_serialization::{read_index, write_index, IndexKind},
     linearization::expr_linearization,
     verifier_index::VerifierIndex,
 };
 use ark_ff::PrimeField;
 use mina_poseidon::FqSponge;
+use o1_utils::serialization::{from_msgpack, DeserializationMode};
 use poly_commitment::{OpenProof, SRS as _};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
-use std::sync::Arc;
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
 
 /// The index used by the prover
 #[serde_as]
@@ -112,6 +118,27 @@ where
         verifier_index_digest
     }
 
+    /// Computes and caches the work of the prover that does not depend on the
+    /// witness: the verifier index, with the commitments of the selectors, of
+    /// the permutation and of the lookup tables, its digest, and the
+    /// evaluations of the constant polynomials of the domain. The proofs created
+    /// with the index afterwards only compute the parts depending on the witness.
+    ///
+    /// Preparing an index which is already prepared does nothing.
+    pub fn prepare<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(&mut self)
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        self.compute_verifier_index_digest::<EFqSponge>();
+        self.cs.precomputations();
+    }
+
+    /// Returns whether [`ProverIndex::prepare`] was called on the index, i.e.
+    /// whether the verifier index and its digest are cached.
+    pub fn is_prepared(&self) -> bool {
+        self.verifier_index.is_some() && self.verifier_index_digest.is_some()
+    }
+
     /// Retrieve or compute the digest for the corresponding verifier index.
     pub fn verifier_index_digest<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
         &self,
@@ -131,6 +158,72 @@ where
             Some(verifier_index) => verifier_index.digest::<EFqSponge>(),
         }
     }
+
+    /// Writes the [`ProverIndex`] to `writer`, using the format described in
+    /// [`crate::index_serialization`]. The SRS is not written.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index cannot be encoded or written.
+    pub fn serialize_to<W: Write>(&self, writer: W) -> Result<(), IndexSerializationError> {
+        let payload =
+            rmp_serde::to_vec(self).map_err(|e| IndexSerializationError::Encode(e.to_string()))?;
+        write_index::<G, _>(writer, IndexKind::Prover, self.cs.domain.d1.size, &payload)
+    }
+
+    /// Reads a [`ProverIndex`] written with [`ProverIndex::serialize_to`], and
+    /// sets its SRS to `srs`. The linearization is recomputed from the
+    /// constraint system.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the header does not match the expected version, curve
+    /// or domain, if the checksum is incorrect, or if the index cannot be
+    /// decoded.
+    pub fn deserialize_from<R: Read>(
+        reader: R,
+        srs: Arc<OpeningProof::SRS>,
+    ) -> Result<Self, IndexSerializationError>
+    where
+        OpeningProof: Default,
+        OpeningProof::SRS: Default,
+    {
+        Self::deserialize_from_with_mode(reader, srs, DeserializationMode::Lenient)
+    }
+
+    /// Same as [`ProverIndex::deserialize_from`], but decodes the index with the
+    /// given [`DeserializationMode`]. In strict mode, an index containing a
+    /// non-canonical encoding, e.g. a field element that is not reduced, is
+    /// rejected with [`IndexSerializationError::NonCanonical`].
+    ///
+    /// # Errors
+    ///
+    /// See [`ProverIndex::deserialize_from`].
+    pub fn deserialize_from_with_mode<R: Read>(
+        reader: R,
+        srs: Arc<OpeningProof::SRS>,
+        mode: DeserializationMode,
+    ) -> Result<Self, IndexSerializationError>
+    where
+        OpeningProof: Default,
+        OpeningProof::SRS: Default,
+    {
+        let (domain_size, payload) = read_index::<G, _>(reader, IndexKind::Prover)?;
+        let mut index: Self = from_msgpack(&payload, mode)?;
+        if index.cs.domain.d1.size != domain_size {
+            return Err(IndexSerializationError::IncorrectDomainSize(
+                domain_size,
+                index.cs.domain.d1.size,
+            ));
+        }
+
+        let (linearization, powers_of_alpha) =
+            expr_linearization(Some(&index.cs.feature_flags), true);
+        index.linearization = linearization;
+        index.powers_of_alpha = powers_of_alpha;
+        index.srs = srs;
+        Ok(index)
+    }
 }
 
 pub mod testing {
@@ -0,0 +1,215 @@
+//! This module implements the instrumentation of the prover, reporting the
+//! wall time and the peak heap usage of each stage of the proof creation
+//! (see [`ProverProof::create_with_metrics`]). It is only available with the
+//! `prover-metrics` feature.
+//!
+//! The heap usage is measured by [`MetricsAllocator`], which must be installed
+//! as the global allocator of the binary to be reported. It is counted per
+//! thread, so that provers running concurrently, e.g. in parallel tests, do
+//! not see the allocations of each other:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: kimchi::prover_metrics::MetricsAllocator =
+//!     kimchi::prover_metrics::MetricsAllocator;
+//! ```
+//!
+//! [`ProverProof::create_with_metrics`]: crate::proof::ProverProof::create_with_metrics
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::{Cell, RefCell},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The stages of the prover, in the order they are run
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProverStage {
+    /// Padding of the witness, and commitment to the witness columns
+    WitnessCommitment,
+    /// Sorted and aggregation polynomials of the lookup argument, if any
+    LookupAggregation,
+    /// Permutation aggregation polynomial
+    Permutation,
+    /// Quotient polynomial and its commitment
+    Quotient,
+    /// Evaluations of the polynomials at $\zeta$ and $\zeta\omega$
+    Evaluations,
+    /// Aggregated opening proof
+    OpeningProof,
+}
+
+/// The metrics of a single stage of the prover
+#[derive(Clone, Debug)]
+pub struct StageMetrics {
+    /// The stage
+    pub stage: ProverStage,
+    /// The wall time of the stage
+    pub wall_time: Duration,
+    /// The peak number of bytes allocated during the stage, on top of the
+    /// bytes allocated when it started, or `None` if [`MetricsAllocator`] is
+    /// not the global allocator. Only the allocations of the thread running
+    /// the prover are counted, not the ones of the threads of the `rayon`
+    /// pool.
+    pub peak_allocated: Option<usize>,
+}
+
+/// The metrics of a proof creation, stage by stage
+#[derive(Clone, Debug, Default)]
+pub struct ProverMetrics {
+    /// The metrics of each stage, in the order they were run
+    pub stages: Vec<StageMetrics>,
+}
+
+impl ProverMetrics {
+    /// Returns the metrics of `stage`, if it was run
+    pub fn stage(&self, stage: ProverStage) -> Option<&StageMetrics> {
+        self.stages.iter().find(|m| m.stage == stage)
+    }
+
+    /// Returns the total wall time of the stages
+    pub fn wall_time(&self) -> Duration {
+        self.stages.iter().map(|m| m.wall_time).sum()
+    }
+
+    /// Returns the largest peak allocation of the stages, if measured
+    pub fn peak_allocated(&self) -> Option<usize> {
+        self.stages.iter().filter_map(|m| m.peak_allocated).max()
+    }
+}
+
+/// Whether [`MetricsAllocator`] has allocated anything, i.e. whether it is
+/// the global allocator
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Number of bytes currently allocated through [`MetricsAllocator`] by
+    /// this thread. It can be negative, as a thread can free the memory
+    /// allocated by another one.
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+
+    /// Peak of [`ALLOCATED`] since the last reset
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+/// A global allocator forwarding to the system allocator, and keeping track
+/// of the number of bytes allocated so that the prover can report its peak
+/// heap usage.
+pub struct MetricsAllocator;
+
+impl MetricsAllocator {
+    fn record_alloc(size: usize) {
+        if !INSTALLED.load(Ordering::Relaxed) {
+            INSTALLED.store(true, Ordering::Relaxed);
+        }
+        // the thread-local values are not available while the thread is
+        // being torn down, the allocations are then not counted
+        let _ = ALLOCATED.try_with(|allocated| {
+            let current = allocated.get().wrapping_add(size as isize);
+            allocated.set(current);
+            let _ = PEAK.try_with(|peak| peak.set(peak.get().max(current)));
+        });
+    }
+
+    fn record_dealloc(size: usize) {
+        let _ = ALLOCATED.try_with(|allocated| {
+            allocated.set(allocated.get().wrapping_sub(size as isize));
+        });
+    }
+}
+
+unsafe impl GlobalAlloc for MetricsAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                Self::record_alloc(new_size - layout.size());
+            } else {
+                Self::record_dealloc(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// The stage being recorded, and the metrics of the previous ones
+#[derive(Default)]
+struct Recorder {
+    metrics: ProverMetrics,
+    current: Option<(ProverStage, Instant, isize)>,
+}
+
+impl Recorder {
+    fn end_stage(&mut self) {
+        if let Some((stage, start, allocated)) = self.current.take() {
+            let wall_time = start.elapsed();
+            let peak = PEAK.with(Cell::get);
+            let peak_allocated = INSTALLED
+                .load(Ordering::Relaxed)
+                .then(|| peak.saturating_sub(allocated).max(0) as usize);
+            self.metrics.stages.push(StageMetrics {
+                stage,
+                wall_time,
+                peak_allocated,
+            });
+        }
+    }
+}
+
+thread_local! {
+    /// The metrics being recorded on this thread, if any
+    static RECORDER: RefCell<Option<Recorder>> = RefCell::new(None);
+}
+
+/// Starts recording the metrics of the prover on this thread
+pub(crate) fn start_recording() {
+    RECORDER.with(|r| *r.borrow_mut() = Some(Recorder::default()));
+}
+
+/// Ends the current stage, and starts recording `stage`. Does nothing if the
+/// metrics are not being recorded on this thread.
+pub(crate) fn begin_stage(stage: ProverStage) {
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            recorder.end_stage();
+            let allocated = ALLOCATED.with(Cell::get);
+            PEAK.with(|peak| peak.set(allocated));
+            recorder.current = Some((stage, Instant::now(), allocated));
+        }
+    });
+}
+
+/// Stops recording the metrics on this thread, and returns them
+pub(crate) fn take_metrics() -> ProverMetrics {
+    RECORDER.with(|r| {
+        r.borrow_mut()
+            .take()
+            .map(|mut recorder| {
+                recorder.end_stage();
+                recorder.metrics
+            })
+            .unwrap_or_default()
+    })
+}
@@ -0,0 +1,229 @@
+use super::framework::generic_index;
+use crate::{
+    bench::BenchmarkCtx,
+    bin_prot::{poly_comm_from_bin_prot, poly_comm_to_bin_prot, BinProtReader, BinProtWriter},
+    circuits::{
+        constraints::PublicSection,
+        gate::{CircuitGate, GateType},
+        lookup::tables::LookupTable,
+        wires::Wire,
+    },
+    error::BinProtError,
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test_with_lookups,
+    verifier::verify,
+    verifier_index::VerifierIndex,
+};
+use ark_ec::AffineRepr;
+use ark_ff::{One, Zero};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof, PolyComm};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+/// The encodings of integers, as written by bin_prot
+const NAT0_FIXTURES: [(u64, &str); 5] = [
+    (0, "00"),
+    (0x7f, "7f"),
+    (0x80, "fe8000"),
+    (0x1_0000, "fd00000100"),
+    (0x1_0000_0000, "fc0000000001000000"),
+];
+
+const INT_FIXTURES: [(i64, &str); 6] = [
+    (-1, "ffff"),
+    (-0x80, "ff80"),
+    (-0x81, "fe7fff"),
+    (0x80, "fe8000"),
+    (0x8000, "fd00800000"),
+    (-0x8000_0001, "fcffffff7fffffffff"),
+];
+
+/// The commitment made of the generator of Vesta and of the point at infinity
+const POLY_COMM_FIXTURE: &str = concat!(
+    "02",
+    "01",
+    "200100000000000000000000000000000000000000000000000000000000000000",
+    "20626a27b0b989434e0ca28b297f3aceac4c7589ae3a4eb6136bae22a96e664319",
+    "00",
+    "00",
+);
+
+#[test]
+fn test_bin_prot_integers() {
+    for (n, fixture) in NAT0_FIXTURES {
+        let mut w = BinProtWriter::new();
+        w.nat0(n);
+        let bytes = w.finish();
+        assert_eq!(hex::encode(&bytes), fixture);
+
+        let mut r = BinProtReader::new(&bytes);
+        assert_eq!(r.nat0().unwrap(), n);
+        r.finish().unwrap();
+    }
+
+    for (n, fixture) in INT_FIXTURES {
+        let mut w = BinProtWriter::new();
+        w.int(n);
+        let bytes = w.finish();
+        assert_eq!(hex::encode(&bytes), fixture);
+
+        let mut r = BinProtReader::new(&bytes);
+        assert_eq!(r.int().unwrap(), n);
+        r.finish().unwrap();
+    }
+
+    // a negative integer is not a valid length
+    let mut r = BinProtReader::new(&[0xff, 0xff]);
+    assert_eq!(r.nat0(), Err(BinProtError::InvalidTag("nat0", 0xff)));
+}
+
+#[test]
+fn test_bin_prot_field() {
+    let mut w = BinProtWriter::new();
+    w.field(&Fp::one());
+    let bytes = w.finish();
+    assert_eq!(
+        hex::encode(&bytes),
+        "200100000000000000000000000000000000000000000000000000000000000000"
+    );
+
+    // the modulus is not reduced
+    let mut bytes = hex::decode("20").unwrap();
+    bytes.extend(
+        hex::decode("01000000ed302d991bf94c09fc98462200000000000000000000000000000040").unwrap(),
+    );
+    assert_eq!(
+        BinProtReader::new(&bytes).field::<Fp>(),
+        Err(BinProtError::InvalidFieldElement)
+    );
+}
+
+#[test]
+fn test_bin_prot_poly_comm() {
+    let comm = PolyComm {
+        chunks: vec![Vesta::generator(), Vesta::zero()],
+    };
+    let bytes = poly_comm_to_bin_prot(&comm);
+    assert_eq!(hex::encode(&bytes), POLY_COMM_FIXTURE);
+    assert_eq!(poly_comm_from_bin_prot::<Vesta>(&bytes).unwrap(), comm);
+
+    // trailing bytes
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        poly_comm_from_bin_prot::<Vesta>(&trailing),
+        Err(BinProtError::TrailingBytes(1))
+    );
+
+    // a shifted commitment
+    let mut shifted = bytes.clone();
+    *shifted.last_mut().unwrap() = 1;
+    shifted.push(0);
+    assert_eq!(
+        poly_comm_from_bin_prot::<Vesta>(&shifted),
+        Err(BinProtError::ShiftedCommitment)
+    );
+
+    // a point that is not on the curve
+    let mut invalid = bytes;
+    invalid[3] = 2;
+    assert_eq!(
+        poly_comm_from_bin_prot::<Vesta>(&invalid),
+        Err(BinProtError::InvalidPoint)
+    );
+}
+
+#[test]
+fn test_bin_prot_proof() {
+    let ctx = BenchmarkCtx::new(4);
+    let (proof, public_input) = ctx.create_proof();
+
+    let bytes = proof.to_bin_prot(&public_input);
+    let (decoded, decoded_public_input) =
+        ProverProof::<Vesta, OpeningProof<Vesta>>::from_bin_prot(&bytes).unwrap();
+    assert_eq!(decoded_public_input, public_input);
+    assert_eq!(decoded.to_bin_prot(&decoded_public_input), bytes);
+
+    // the decoded proof is accepted
+    ctx.batch_verification(&vec![(decoded, decoded_public_input)]);
+
+    // a truncated proof is rejected
+    assert_eq!(
+        ProverProof::<Vesta, OpeningProof<Vesta>>::from_bin_prot(&bytes[..bytes.len() - 1]).err(),
+        Some(BinProtError::UnexpectedEnd)
+    );
+}
+
+#[test]
+fn test_bin_prot_verifier_index() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let verifier_index = index.verifier_index();
+
+    let bytes = verifier_index.to_bin_prot().unwrap();
+    let decoded =
+        VerifierIndex::<Vesta, OpeningProof<Vesta>>::from_bin_prot(&bytes, index.srs.clone())
+            .unwrap();
+    assert_eq!(decoded.to_bin_prot().unwrap(), bytes);
+    assert_eq!(
+        decoded.digest::<BaseSponge>(),
+        verifier_index.digest::<BaseSponge>()
+    );
+
+    // a proof is accepted by the decoded index
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut o1_utils::tests::make_test_rng(None),
+    )
+    .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map, &decoded, &proof, &public,
+    )
+    .unwrap();
+
+    // the public sections cannot be encoded
+    let mut verifier_index = verifier_index;
+    verifier_index.public_sections = vec![PublicSection::new("input", public.len())];
+    assert_eq!(
+        verifier_index.to_bin_prot(),
+        Err(BinProtError::PublicSections)
+    );
+}
+
+#[test]
+fn test_bin_prot_lookup_verifier_index() {
+    let gates = (0..4)
+        .map(|i| CircuitGate::new(GateType::Lookup, Wire::for_row(i), vec![]))
+        .collect();
+    let table = LookupTable {
+        id: 0,
+        data: vec![(0..4u64).map(Fp::from).collect()],
+    };
+    let index =
+        new_index_for_test_with_lookups::<Vesta>(gates, 0, 0, vec![table], None, false, None);
+    let verifier_index = index.verifier_index();
+    assert!(verifier_index.lookup_index.is_some());
+
+    let bytes = verifier_index.to_bin_prot().unwrap();
+    let decoded =
+        VerifierIndex::<Vesta, OpeningProof<Vesta>>::from_bin_prot(&bytes, index.srs.clone())
+            .unwrap();
+    assert_eq!(decoded.to_bin_prot().unwrap(), bytes);
+    assert_eq!(
+        decoded.digest::<BaseSponge>(),
+        verifier_index.digest::<BaseSponge>()
+    );
+}
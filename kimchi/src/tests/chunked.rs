@@ -1,8 +1,11 @@
 use super::framework::TestFramework;
-use crate::circuits::{
-    gate::CircuitGate,
-    polynomials::generic::GenericGateSpec,
-    wires::{Wire, COLUMNS},
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        polynomials::generic::GenericGateSpec,
+        wires::{Wire, COLUMNS},
+    },
+    precomputed_srs::SERIALIZED_SRS_SIZE,
 };
 use ark_ff::{UniformRand, Zero};
 use itertools::iterate;
@@ -11,6 +14,7 @@ use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
+use poly_commitment::{ipa::SRS, SRS as _};
 use std::array;
 
 type SpongeParams = PlonkSpongeConstantsKimchi;
@@ -80,13 +84,19 @@ fn test_generic_gate_with_srs_override(
         .gates(gates)
         .witness(witness)
         .public_inputs(public);
-    let framework = if let Some(srs_size) = override_srs_size {
-        framework.override_srs_size(srs_size)
-    } else {
-        framework
+    let runner = match override_srs_size {
+        // the precomputed SRS is not trimmed, so smaller SRS are created
+        Some(srs_size) if srs_size < 1 << SERIALIZED_SRS_SIZE => framework
+            .override_srs_size(srs_size)
+            .setup_with_custom_srs(|d1, size| {
+                let srs = SRS::<Vesta>::create(size);
+                srs.get_lagrange_basis(d1);
+                srs
+            }),
+        Some(srs_size) => framework.override_srs_size(srs_size).setup(),
+        None => framework.setup(),
     };
-    framework
-        .setup()
+    runner
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
@@ -103,6 +113,18 @@ fn test_2_to_18_chunked_generic_gate_pub() {
     test_generic_gate_with_srs_override(18, Some(1 << 16))
 }*/
 
+#[test]
+fn test_2_to_8_chunked_generic_gate_pub() {
+    // 2 chunks
+    test_generic_gate_with_srs_override(8, Some(1 << 7))
+}
+
+#[test]
+fn test_2_to_9_chunked_generic_gate_pub() {
+    // 4 chunks
+    test_generic_gate_with_srs_override(9, Some(1 << 7))
+}
+
 #[test]
 fn heavy_test_2_to_17_chunked_generic_gate_pub() {
     test_generic_gate_with_srs_override(17, Some(1 << 16))
@@ -0,0 +1,87 @@
+use super::framework::generic_index;
+use crate::{
+    conformance::{self, TestVector},
+    error::ConformanceError,
+    proof::ProverProof,
+    prover::ProverConfig,
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::commitment::CommitmentCurve;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+#[test]
+fn test_conformance_vectors() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let config = ProverConfig {
+        hiding: false,
+        ..ProverConfig::default()
+    };
+    let prove = || {
+        ProverProof::create_with_config::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness.clone(),
+            &[],
+            &index,
+            vec![],
+            &config,
+            &mut o1_utils::tests::make_test_rng(None),
+        )
+        .unwrap()
+    };
+
+    // the fixture, as produced by another implementation
+    let (_, vector) = conformance::record("generic", prove);
+    assert!(!vector.entries.is_empty());
+    let mut fixture: serde_json::Value = serde_json::from_str(&vector.to_json().unwrap()).unwrap();
+    fixture["inputs"] = serde_json::json!({ "public": 5 });
+    let expected = TestVector::from_json(&fixture.to_string()).unwrap();
+    assert_eq!(expected.extra["inputs"]["public"], 5);
+
+    let (_, actual) = conformance::record("generic", prove);
+    conformance::check(&expected, &actual).unwrap();
+
+    // the first differing stage is reported
+    let beta = expected
+        .entries
+        .iter()
+        .position(|entry| entry.label == "beta")
+        .unwrap();
+    let mut wrong = expected.clone();
+    wrong.entries[beta].values[0] = "00".to_string();
+    assert_eq!(
+        conformance::check(&wrong, &actual),
+        Err(ConformanceError::Mismatch {
+            index: beta,
+            label: "beta".to_string(),
+        })
+    );
+
+    let mut truncated = expected.clone();
+    truncated.entries.pop();
+    assert_eq!(
+        conformance::check(&truncated, &actual),
+        Err(ConformanceError::IncorrectLength(
+            expected.entries.len() - 1,
+            expected.entries.len()
+        ))
+    );
+
+    fixture["version"] = serde_json::json!(conformance::VERSION + 1);
+    assert_eq!(
+        TestVector::from_json(&fixture.to_string()),
+        Err(ConformanceError::UnsupportedVersion(
+            conformance::VERSION + 1
+        ))
+    );
+}
@@ -0,0 +1,69 @@
+use super::framework::generic_circuit;
+use crate::{
+    circuits::{gate::CircuitGate, wires::Wire},
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test,
+};
+use ark_ff::Zero;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::commitment::CommitmentCurve;
+use rand::{rngs::StdRng, SeedableRng};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const SEED: [u8; 32] = [13; 32];
+
+/// Creates a proof of a generic circuit of `rows` rows with a pool of
+/// `threads` threads, and returns its serialization
+fn prove_with_threads(rows: usize, threads: usize) -> Vec<u8> {
+    let public = vec![Fp::from(3u8); 5];
+    let (mut gates, mut witness) = generic_circuit(&public);
+    let gates_len = gates.len();
+    gates.extend((gates_len..rows).map(|row| CircuitGate::zero(Wire::for_row(row))));
+    witness
+        .iter_mut()
+        .for_each(|col| col.resize(gates.len(), Fp::zero()));
+
+    let index = new_index_for_test(gates, public.len());
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    let proof = pool.install(|| {
+        ProverProof::create::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness,
+            &[],
+            &index,
+            &mut StdRng::from_seed(SEED),
+        )
+        .unwrap()
+    });
+
+    rmp_serde::to_vec(&proof).unwrap()
+}
+
+#[test]
+fn test_proofs_do_not_depend_on_thread_count() {
+    // the field operations are exact, so the order in which the threads
+    // combine their results does not matter
+    for rows in [32, 3000] {
+        let expected = prove_with_threads(rows, 1);
+        for threads in [2, 8] {
+            assert_eq!(
+                prove_with_threads(rows, threads),
+                expected,
+                "proofs of {rows} rows differ with {threads} threads"
+            );
+        }
+    }
+}
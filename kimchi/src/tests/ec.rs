@@ -1,6 +1,10 @@
-use crate::circuits::{
-    gate::{CircuitGate, GateType},
-    wires::*,
+use crate::{
+    circuits::{
+        gate::{CircuitGate, GateType},
+        polynomials::complete_add::complete_add_witness,
+        wires::*,
+    },
+    error::CompleteAddError,
 };
 use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::{Field, One, UniformRand, Zero};
@@ -151,3 +155,56 @@ fn ec_test() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+#[test]
+fn test_complete_add_witness() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let g = Other::generator().into_group();
+    let p: Other = g.mul(<Other as AffineRepr>::ScalarField::rand(rng)).into();
+    let q: Other = g.mul(<Other as AffineRepr>::ScalarField::rand(rng)).into();
+
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); 3]);
+
+    // p + q
+    let sum = complete_add_witness(&mut witness, 0, p, q).unwrap();
+    let pq: Other = (p + q).into();
+    assert_eq!(sum.output, Some((pq.x, pq.y)));
+
+    // (p + q) + (p + q), the inputs being the output of the first gate
+    let double = complete_add_witness(&mut witness, 1, pq, pq).unwrap();
+    let pq2: Other = (pq + pq).into();
+    assert_eq!(double.output, Some((pq2.x, pq2.y)));
+
+    // p - p
+    let inf = complete_add_witness(&mut witness, 2, p, -p).unwrap();
+    assert_eq!(inf.output, None);
+
+    assert_eq!(
+        complete_add_witness(&mut witness, 2, p, Other::identity()),
+        Err(CompleteAddError::InfinityInput)
+    );
+
+    let mut wires: Vec<GateWires> = (0..3).map(Wire::for_row).collect();
+    let [x, y] = sum.cells.output;
+    let [x1, y1] = double.cells.p1;
+    let [x2, y2] = double.cells.p2;
+    for cycle in [[x, x1, x2], [y, y1, y2]] {
+        for (i, cell) in cycle.iter().enumerate() {
+            wires[cell.row][cell.col] = cycle[(i + 1) % cycle.len()];
+        }
+    }
+    let gates: Vec<_> = wires
+        .into_iter()
+        .map(CircuitGate::create_complete_add)
+        .collect();
+    for (row, gate) in gates.iter().enumerate() {
+        gate.verify_complete_add(row, &witness).unwrap();
+    }
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
@@ -6,13 +6,14 @@ use crate::{
     },
     tests::framework::TestFramework,
 };
-use ark_ff::{BigInteger, BitIteratorLE, PrimeField, UniformRand};
+use ark_ff::{BigInteger, BitIteratorLE, PrimeField, UniformRand, Zero};
 use mina_curves::pasta::{Fp as F, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge, ScalarChallenge},
 };
 use poly_commitment::ipa::endos;
+use rand::Rng;
 use std::array;
 
 type SpongeParams = PlonkSpongeConstantsKimchi;
@@ -70,3 +71,30 @@ fn endomul_scalar_test() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+#[test]
+fn endomul_scalar_gadget_test() {
+    let num_bits = 128;
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let (_, endo_scalar_coeff) = endos::<Vesta>();
+
+    let limbs: [u64; 2] = [rng.gen(), rng.gen()];
+    let chal = ScalarChallenge::<F>::from_limbs(&limbs);
+    let (a, b) = chal.to_endo_coefficients();
+    assert_eq!(a * endo_scalar_coeff + b, chal.to_field(&endo_scalar_coeff));
+
+    let gates = CircuitGate::<F>::create_endomul_scalar(0, num_bits);
+    assert_eq!(gates.len(), num_bits / endomul_scalar::BITS_PER_ROW);
+
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); gates.len()]);
+    let res = endomul_scalar::witness(&mut witness, 0, chal.0, endo_scalar_coeff, num_bits);
+    assert_eq!((res.a, res.b), (a, b));
+    assert_eq!(res.endo_scalar, chal.to_field(&endo_scalar_coeff));
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
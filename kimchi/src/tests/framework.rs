@@ -7,6 +7,7 @@ use crate::{
             runtime_tables::{RuntimeTable, RuntimeTableCfg},
             tables::LookupTable,
         },
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
         wires::COLUMNS,
     },
     curve::KimchiCurve,
@@ -14,7 +15,8 @@ use crate::{
     proof::{ProverProof, RecursionChallenge},
     prover_index::{
         testing::{
-            new_index_for_test_with_lookups, new_index_for_test_with_lookups_and_custom_srs,
+            new_index_for_test, new_index_for_test_with_lookups,
+            new_index_for_test_with_lookups_and_custom_srs,
         },
         ProverIndex,
     },
@@ -30,7 +32,7 @@ use poly_commitment::{
     commitment::CommitmentCurve, ipa::OpeningProof as DlogOpeningProof, OpenProof,
 };
 use rand_core::{CryptoRng, RngCore};
-use std::{fmt::Write, time::Instant};
+use std::{array, fmt::Write, time::Instant};
 
 // aliases
 
@@ -363,6 +365,31 @@ where
     }
 }
 
+/// A circuit of generic gates with `public` as public input, and a witness
+/// satisfying it (see [create_circuit] and [fill_in_witness]).
+pub(crate) fn generic_circuit<F: PrimeField>(
+    public: &[F],
+) -> (Vec<CircuitGate<F>>, [Vec<F>; COLUMNS]) {
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, public);
+    (gates, witness)
+}
+
+/// The prover index of [generic_circuit], and a witness satisfying it.
+pub(crate) fn generic_index<G: KimchiCurve>(
+    public: &[G::ScalarField],
+) -> (
+    ProverIndex<G, DlogOpeningProof<G>>,
+    [Vec<G::ScalarField>; COLUMNS],
+)
+where
+    G::BaseField: PrimeField,
+{
+    let (gates, witness) = generic_circuit(public);
+    (new_index_for_test(gates, public.len()), witness)
+}
+
 pub fn print_witness<F>(cols: &[Vec<F>; COLUMNS], start_row: usize, end_row: usize)
 where
     F: PrimeField,
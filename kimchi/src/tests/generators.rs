@@ -0,0 +1,58 @@
+use crate::circuits::testing::generators::{self, GadgetCase};
+use mina_curves::pasta::{Fp, Fq, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use o1_utils::FieldHelpers;
+use proptest::prelude::*;
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+fn prove_and_verify(case: GadgetCase<Fp>) {
+    case.prove_and_verify::<Vesta, BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+proptest! {
+    // each case creates a proof, which is slow
+    #![proptest_config(ProptestConfig::with_cases(2))]
+
+    #[test]
+    fn test_generic_gadget(case in generators::generic_gadget()) {
+        prove_and_verify(case);
+    }
+
+    #[test]
+    fn test_poseidon_gadget(case in generators::poseidon_gadget::<Vesta>()) {
+        prove_and_verify(case);
+    }
+
+    #[test]
+    fn test_range_check_gadget(case in generators::range_check_gadget()) {
+        prove_and_verify(case);
+    }
+
+    #[test]
+    fn test_xor_gadget(case in generators::xor_gadget(64)) {
+        prove_and_verify(case);
+    }
+
+    #[test]
+    fn test_and_gadget(case in generators::and_gadget(8)) {
+        prove_and_verify(case);
+    }
+
+    #[test]
+    fn test_rot_gadget(case in generators::rot_gadget()) {
+        prove_and_verify(case);
+    }
+
+    #[test]
+    fn test_foreign_field_add_gadget(
+        case in generators::foreign_field_add_gadget(Fq::modulus_biguint())
+    ) {
+        prove_and_verify(case);
+    }
+}
@@ -1,16 +1,37 @@
-use super::framework::TestFramework;
-use crate::circuits::{
-    polynomials::generic::testing::{create_circuit, fill_in_witness},
-    wires::COLUMNS,
+use super::framework::{generic_circuit, generic_index, TestFramework};
+use crate::{
+    circuits::{
+        berkeley_columns::Column,
+        constraints::{ConstraintSystem, PublicSection},
+        gate::{Connect, GateType},
+        polynomials::generic::{
+            testing::{create_circuit, fill_in_witness},
+            GenericGateBuilder,
+        },
+        testing::{self, FuzzConfig},
+        wires::{Wire, COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+    error::{ProverError, SetupError, VerifyError},
+    linearization, precomputed_srs,
+    proof::{ProofEvaluations, ProverProof},
+    prover::ProverConfig,
+    prover_index::{testing::new_index_for_test, ProverIndex},
+    verifier::{batch_verify_proofs, verify, verify_with_report, verify_with_sections},
+    verifier_index::VerifierIndex,
 };
 use ark_ff::Zero;
+use ark_poly::univariate::DensePolynomial;
+use groupmap::GroupMap;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
+    rescue::{RescueFqSponge, RescueFrSponge},
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
-use poly_commitment::SRS;
-use std::array;
+use o1_utils::ExtendedDensePolynomial;
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof, SRS};
+use std::{array, sync::Arc};
 
 type SpongeParams = PlonkSpongeConstantsKimchi;
 type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
@@ -33,6 +54,23 @@ fn test_generic_gate() {
         .unwrap();
 }
 
+#[test]
+fn test_generic_gate_rescue_sponges() {
+    let gates = create_circuit(0, 0);
+
+    // create witness
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &[]);
+
+    // create and verify proof with Rescue as the Fiat-Shamir oracle
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<RescueFqSponge<VestaParameters>, RescueFrSponge<Fp>>()
+        .unwrap();
+}
+
 #[test]
 fn test_generic_gate_pub() {
     let public = vec![Fp::from(3u8); 5];
@@ -90,6 +128,62 @@ fn test_generic_gate_pub_empty() {
         .unwrap();
 }
 
+#[test]
+fn test_generic_gate_cells_are_constrained() {
+    let gates = create_circuit::<Fp>(0, 0);
+    let rows = gates.len();
+    let witness = move |_: &mut _| {
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); rows]);
+        fill_in_witness(0, &mut witness, &[]);
+        witness
+    };
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let config = FuzzConfig::default();
+
+    // the add and mul gates constrain their 6 registers,
+    // the const gates only constrain their left registers
+    let mut cells = testing::cells(0..10, 0..6);
+    cells.extend(testing::cells(10..20, 0..1));
+    cells.extend(testing::cells(10..20, 3..4));
+    testing::assert_constrained::<Vesta, _>(gates.clone(), 0, &cells, witness, &config, rng);
+
+    // the right register of a const gate is free
+    let accepted =
+        testing::fuzz_constraints::<Vesta, _>(gates, 0, &[Wire::new(10, 1)], witness, &config, rng);
+    assert_eq!(accepted.len(), config.witnesses * config.mutations);
+}
+
+#[test]
+fn test_circuit_stats() {
+    let mut gates = create_circuit::<Fp>(0, 3);
+    gates.connect_cell_pair((3, 0), (13, 3));
+    let cs = ConstraintSystem::create(gates).public(3).build().unwrap();
+
+    let stats = cs.stats();
+    assert_eq!(stats.rows, 23);
+    assert_eq!(stats.public, 3);
+    assert_eq!(stats.domain_size, 32);
+    assert_eq!(
+        stats.gates.into_iter().collect::<Vec<_>>(),
+        [(GateType::Generic, 23)]
+    );
+    assert_eq!(stats.permutation_cycles, 1);
+    assert_eq!(stats.permuted_cells, 2);
+    assert!(stats.table_lookups.is_empty());
+    assert_eq!(stats.witness_table_lookups, 0);
+    assert_eq!(stats.cost.msm_points, stats.cost.commitments * 32);
+
+    let text = cs.stats().to_string();
+    assert!(text.starts_with("rows:               23 (3 public, "));
+    assert!(text.contains("\n  Generic           23\n"));
+    assert!(text.contains("\npermutation:        1 cycles, 2 cells\n"));
+    assert!(text.contains("\n  witness tables    0\n"));
+    assert!(text.contains(&format!(
+        "proving cost:       {} commitments, {} MSM points",
+        stats.cost.commitments, stats.cost.msm_points
+    )));
+}
+
 #[cfg(feature = "bn254")]
 #[test]
 fn test_generic_gate_kzg() {
@@ -121,3 +215,653 @@ fn test_generic_gate_kzg() {
     .prove_and_verify::<BaseSponge, ScalarSponge>()
     .unwrap();
 }
+
+#[cfg(feature = "bn254")]
+#[test]
+fn test_generic_gate_grumpkin() {
+    use mina_curves::grumpkin::{Grumpkin, GrumpkinParameters};
+
+    type Fp = ark_bn254::Fq;
+    type SpongeParams = PlonkSpongeConstantsKimchi;
+    type BaseSponge = DefaultFqSponge<GrumpkinParameters, SpongeParams>;
+    type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+    let public = vec![Fp::from(3u8); 5];
+    let (gates, witness) = generic_circuit(&public);
+
+    // create and verify an IPA proof over Grumpkin, the other curve of BN254
+    TestFramework::<Grumpkin>::default()
+        .gates(gates)
+        .witness(witness)
+        .public_inputs(public)
+        .setup_with_custom_srs(|d1, srs_size| {
+            let srs = poly_commitment::ipa::SRS::<Grumpkin>::create(srs_size);
+            srs.get_lagrange_basis(d1);
+            srs
+        })
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[cfg(feature = "bn254")]
+#[test]
+fn test_kzg_proof_size_does_not_depend_on_circuit_size() {
+    use crate::{
+        circuits::{gate::CircuitGate, wires::Wire},
+        prover_index::testing::new_index_for_test_with_lookups_and_custom_srs,
+    };
+    use poly_commitment::kzg::{KZGProof, PairingSRS};
+
+    type Fp = ark_bn254::Fr;
+    type G = ark_bn254::G1Affine;
+    type SpongeParams = PlonkSpongeConstantsKimchi;
+    type BaseSponge = DefaultFqSponge<ark_bn254::g1::Config, SpongeParams>;
+    type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+    type Proof = KZGProof<ark_ec::bn::Bn<ark_bn254::Config>>;
+
+    let group_map = <G as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let public = vec![Fp::from(3u8); 5];
+
+    let mut proof_size = |rows: usize| {
+        let (mut gates, mut witness) = generic_circuit(&public);
+        let gates_len = gates.len();
+        gates.extend((gates_len..rows).map(|row| CircuitGate::zero(Wire::for_row(row))));
+        witness
+            .iter_mut()
+            .for_each(|col| col.resize(gates.len(), Fp::zero()));
+
+        let index = new_index_for_test_with_lookups_and_custom_srs::<G, Proof, _>(
+            gates,
+            public.len(),
+            0,
+            vec![],
+            None,
+            false,
+            None,
+            |d1, srs_size| {
+                let srs = PairingSRS::create(srs_size);
+                srs.full_srs.get_lagrange_basis(d1);
+                srs
+            },
+        );
+        let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness,
+            &[],
+            &index,
+            &mut rng,
+        )
+        .unwrap();
+        verify::<G, BaseSponge, ScalarSponge, Proof>(
+            &group_map,
+            &index.verifier_index(),
+            &proof,
+            &public,
+        )
+        .unwrap();
+        rmp_serde::to_vec(&proof).unwrap().len()
+    };
+
+    assert_eq!(proof_size(32), proof_size(500));
+}
+
+#[test]
+fn test_batch_verify_proofs_of_different_circuits() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let setups: Vec<_> = [0, 5]
+        .into_iter()
+        .map(|nb_public| {
+            let public = vec![Fp::from(3u8); nb_public];
+            let (index, witness) = generic_index::<Vesta>(&public);
+            let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+                &group_map,
+                witness,
+                &[],
+                &index,
+                &mut rng,
+            )
+            .unwrap();
+            (index.verifier_index(), proof, public)
+        })
+        .collect();
+
+    let batch: Vec<_> = setups
+        .iter()
+        .map(|(verifier_index, proof, public)| (verifier_index, proof, public.as_slice()))
+        .collect();
+    batch_verify_proofs::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(&group_map, &batch)
+        .unwrap();
+
+    // A proof verified against the public input of another circuit makes the
+    // whole batch fail
+    let wrong_public = vec![Fp::from(4u8); 5];
+    let mut batch = batch;
+    batch[1].2 = &wrong_public;
+    assert!(
+        batch_verify_proofs::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map, &batch
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_verify_with_report() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rng,
+    )
+    .unwrap();
+    let verifier_index = index.verifier_index();
+
+    let (res, report) = verify_with_report::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    );
+    res.unwrap();
+    assert_eq!(report.proof_size, rmp_serde::to_vec(&proof).unwrap().len());
+    assert!(report.msm_terms > 0);
+    assert!(report.sponge_absorptions > 0);
+    assert!(report.partial_verification + report.opening_proof <= report.total);
+
+    let wrong_public = vec![Fp::from(4u8); 5];
+    let (res, report) = verify_with_report::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &wrong_public,
+    );
+    assert!(res.is_err());
+    assert!(report.proof_size > 0);
+}
+
+#[test]
+fn test_prover_config() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (gates, witness) = generic_circuit(&public);
+
+    let cs = ConstraintSystem::create(gates)
+        .public(public.len())
+        .zk_rows(Some(6))
+        .build()
+        .unwrap();
+    let srs = precomputed_srs::get_srs_test::<Vesta>();
+    srs.get_lagrange_basis(cs.domain.d1);
+    let index = ProverIndex::<Vesta, OpeningProof<Vesta>>::create(
+        cs,
+        *Vesta::other_curve_endo(),
+        Arc::new(srs),
+    );
+    let verifier_index = index.verifier_index();
+    assert_eq!(verifier_index.zk_rows, 6);
+
+    let prove = |config: &ProverConfig, rng: &mut _| {
+        ProverProof::create_with_config::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness.clone(),
+            &[],
+            &index,
+            vec![],
+            config,
+            rng,
+        )
+    };
+
+    // Non-hiding proofs are valid
+    let config = ProverConfig {
+        zk_rows: Some(6),
+        hiding: false,
+        seed: None,
+    };
+    let proof = prove(&config, &mut rng).unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // The same seed gives the same proof
+    let config = ProverConfig {
+        seed: Some([42; 32]),
+        ..Default::default()
+    };
+    let proof = prove(&config, &mut rng).unwrap();
+    assert_eq!(proof, prove(&config, &mut rng).unwrap());
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // The index must use the number of zero-knowledge rows of the config
+    let config = ProverConfig {
+        zk_rows: Some(3),
+        ..Default::default()
+    };
+    assert!(matches!(
+        prove(&config, &mut rng),
+        Err(ProverError::IncompatibleZkRows(3, 6))
+    ));
+}
+
+#[test]
+fn test_deterministic_proofs() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let public = vec![Fp::from(3u8); 5];
+    let (index, mut witness) = generic_index::<Vesta>(&public);
+
+    let verifier_index = index.verifier_index();
+    let prove = |witness: [Vec<Fp>; COLUMNS]| {
+        ProverProof::create_deterministic::<BaseSponge, ScalarSponge>(
+            &group_map,
+            witness,
+            &[],
+            &index,
+            vec![],
+        )
+        .unwrap()
+    };
+
+    // the same inputs give the same proof, byte for byte
+    let proof = prove(witness.clone());
+    assert_eq!(
+        rmp_serde::to_vec(&proof).unwrap(),
+        rmp_serde::to_vec(&prove(witness.clone())).unwrap()
+    );
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // another witness gives another proof
+    let public = vec![Fp::from(4u8); 5];
+    fill_in_witness(0, &mut witness, &public);
+    assert_ne!(prove(witness), proof);
+}
+
+#[test]
+fn test_precomputed_public_comm() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut verifier_index = index.verifier_index();
+    let public_comm = verifier_index.public_comm(&public).unwrap();
+    verifier_index.precompute_public_comm(&public).unwrap();
+    assert_eq!(
+        verifier_index.public_comm,
+        Some((public.clone(), public_comm))
+    );
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // the precomputed commitment is not used for another public input
+    let wrong_public = vec![Fp::from(4u8); 5];
+    assert!(
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index,
+            &proof,
+            &wrong_public,
+        )
+        .is_err()
+    );
+
+    // the public input must have the length expected by the index
+    assert!(matches!(
+        verifier_index.precompute_public_comm(&public[1..]),
+        Err(VerifyError::IncorrectPubicInputLength(5))
+    ));
+}
+
+#[test]
+fn test_verifier_index_from_circuit() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (gates, witness) = generic_circuit(&public);
+    let index = new_index_for_test::<Vesta>(gates.clone(), public.len());
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rng,
+    )
+    .unwrap();
+
+    // the verifier index derived from the circuit is the one of the prover index
+    let verifier_index = VerifierIndex::<Vesta, OpeningProof<Vesta>>::from_circuit(
+        gates,
+        public.len(),
+        vec![],
+        None,
+        index.srs.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        verifier_index.digest::<BaseSponge>(),
+        index.verifier_index().digest::<BaseSponge>()
+    );
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_challenges() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+    let verifier_index = index.verifier_index();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rng,
+    )
+    .unwrap();
+
+    // the challenges are the ones of the verifier
+    let public_comm = verifier_index.public_comm(&public).unwrap();
+    let expected = proof
+        .oracles::<BaseSponge, ScalarSponge>(&verifier_index, &public_comm, Some(&public))
+        .unwrap();
+    let challenges = proof
+        .challenges::<BaseSponge, ScalarSponge>(&verifier_index, &public)
+        .unwrap();
+    assert_eq!(challenges.digest, expected.digest);
+    assert_eq!(challenges.oracles.zeta, expected.oracles.zeta);
+    assert_eq!(challenges.oracles.u, expected.oracles.u);
+
+    // the challenges of an invalid proof are derived without verifying it
+    let mut invalid_proof = proof.clone();
+    invalid_proof.ft_eval1 += Fp::from(1u8);
+    assert!(
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index,
+            &invalid_proof,
+            &public,
+        )
+        .is_err()
+    );
+    let invalid_challenges = invalid_proof
+        .challenges::<BaseSponge, ScalarSponge>(&verifier_index, &public)
+        .unwrap();
+    // ft_eval1 is only absorbed after zeta
+    assert_eq!(invalid_challenges.oracles.beta, challenges.oracles.beta);
+    assert_eq!(invalid_challenges.oracles.gamma, challenges.oracles.gamma);
+    assert_eq!(invalid_challenges.oracles.alpha, challenges.oracles.alpha);
+    assert_eq!(invalid_challenges.oracles.zeta, challenges.oracles.zeta);
+    assert_ne!(invalid_challenges.oracles.v, challenges.oracles.v);
+
+    // the public input must have the length expected by the index
+    assert!(matches!(
+        proof.challenges::<BaseSponge, ScalarSponge>(&verifier_index, &public[1..]),
+        Err(VerifyError::IncorrectPubicInputLength(5))
+    ));
+}
+
+#[test]
+fn test_linearization_evaluate() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+    let verifier_index = index.verifier_index();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rng,
+    )
+    .unwrap();
+
+    let public_comm = verifier_index.public_comm(&public).unwrap();
+    let oracles = proof
+        .oracles::<BaseSponge, ScalarSponge>(&verifier_index, &public_comm, Some(&public))
+        .unwrap();
+    let evals = proof
+        .evals
+        .combine(&oracles.powers_of_eval_points_for_chunks);
+    let public_eval = DensePolynomial::eval_polynomial(
+        &oracles.public_evals[0],
+        oracles.powers_of_eval_points_for_chunks.zeta,
+    );
+
+    // the evaluation is the one of the verifier
+    let linearization =
+        linearization::evaluate(&verifier_index, &evals, public_eval, &oracles.oracles);
+    assert_eq!(linearization.ft_eval0, oracles.ft_eval0);
+    assert_eq!(
+        linearization.scalars.len(),
+        1 + verifier_index.linearization.index_terms.len()
+    );
+    assert_eq!(linearization.scalars[0].0, Column::Permutation(PERMUTS - 1));
+
+    // it depends on the evaluations of the proof
+    let mut invalid_evals = evals.clone();
+    invalid_evals.z.zeta_omega += Fp::from(1u8);
+    let invalid = linearization::evaluate(
+        &verifier_index,
+        &invalid_evals,
+        public_eval,
+        &oracles.oracles,
+    );
+    assert_ne!(invalid.ft_eval0, linearization.ft_eval0);
+    assert_ne!(invalid.scalars, linearization.scalars);
+}
+
+#[test]
+fn test_generic_gate_builder() {
+    // y = 2 * x + x, with a boolean b
+    let mut builder = GenericGateBuilder::<Fp>::new(0);
+    let x = builder.create_input();
+    let b = builder.create_input();
+    let two = builder.create_constant(Fp::from(2u8));
+    let double = builder.create_mul(two, x);
+    let y = builder.create_add(double, x);
+    builder.create_boolean_check(b);
+    let _ = builder.create_add(y, b);
+
+    // two operations per row
+    let gates = builder.gates();
+    assert_eq!(gates.len(), 3);
+    assert_eq!(builder.num_rows(), 3);
+
+    let witness = builder.witness(&[(x, Fp::from(7u8)), (b, Fp::from(1u8))]);
+    assert_eq!(witness[5][0], Fp::from(14u8));
+    assert_eq!(witness[2][1], Fp::from(21u8));
+    for (row, gate) in gates.iter().enumerate() {
+        gate.verify_generic(row, &witness, &[]).unwrap();
+    }
+
+    // b must be a bit
+    let wrong_witness = builder.witness(&[(x, Fp::from(7u8)), (b, Fp::from(2u8))]);
+    assert!(gates[1].verify_generic(1, &wrong_witness, &[]).is_err());
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_proof_evaluations_columns() {
+    let curr: [Fp; COLUMNS] = array::from_fn(|i| Fp::from(i as u64));
+    let next: [Fp; COLUMNS] = array::from_fn(|i| Fp::from(100 + i as u64));
+    let evals = ProofEvaluations::dummy_with_witness_evaluations(curr, next);
+
+    // the evaluations of the columns which are always present
+    let columns: Vec<_> = evals.iter().map(|(col, _)| col).collect();
+    assert_eq!(columns.len(), 7 + 2 * COLUMNS + PERMUTS - 1);
+    assert_eq!(columns[0], Column::Z);
+    assert_eq!(columns[7], Column::Witness(0));
+    for (col, eval) in evals.iter() {
+        assert_eq!(evals.get_column(col), Some(eval));
+    }
+    assert_eq!(evals.witness(3).zeta, Fp::from(3u64));
+    assert!(evals.selector(GateType::Xor16).is_none());
+
+    let zipped = evals.clone().zip(evals.clone()).unwrap();
+    assert_eq!(zipped.witness(3).1.zeta_omega, Fp::from(103u64));
+
+    // the evaluated columns must match
+    let mut other = evals.clone();
+    other.lookup_sorted[0] = Some(*other.witness(0));
+    assert!(evals.zip(other).is_none());
+}
+
+#[test]
+fn test_public_sections() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    // a statement of 2 public inputs and 3 public inputs of bookkeeping
+    let statement = vec![Fp::from(3u8), Fp::from(5u8)];
+    let bookkeeping = vec![Fp::from(7u8); 3];
+    let public = [statement.clone(), bookkeeping.clone()].concat();
+    let (gates, witness) = generic_circuit(&public);
+
+    let sections = vec![
+        PublicSection::new("statement", statement.len()),
+        PublicSection::new("bookkeeping", bookkeeping.len()),
+    ];
+    let cs = ConstraintSystem::create(gates.clone())
+        .public_sections(sections.clone())
+        .build()
+        .unwrap();
+    assert_eq!(cs.public, public.len());
+    let srs = precomputed_srs::get_srs_test::<Vesta>();
+    srs.get_lagrange_basis(cs.domain.d1);
+    let index = ProverIndex::<Vesta, OpeningProof<Vesta>>::create(
+        cs,
+        *Vesta::other_curve_endo(),
+        Arc::new(srs),
+    );
+    let verifier_index = index.verifier_index();
+    assert_eq!(verifier_index.public_sections, sections);
+
+    // the layout of the sections is part of the digest
+    let mut other_index = verifier_index.clone();
+    other_index.public_sections = vec![
+        PublicSection::new("statement", 3),
+        PublicSection::new("bookkeeping", 2),
+    ];
+    assert_ne!(
+        verifier_index.digest::<BaseSponge>(),
+        other_index.digest::<BaseSponge>()
+    );
+
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rng,
+    )
+    .unwrap();
+
+    // the sections are concatenated in their order
+    verify_with_sections::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &[&statement, &bookkeeping],
+    )
+    .unwrap();
+    assert_eq!(
+        verifier_index.split_public_input(&public),
+        vec![
+            ("statement", &statement[..]),
+            ("bookkeeping", &bookkeeping[..])
+        ]
+    );
+    assert!(
+        verify_with_sections::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index,
+            &proof,
+            &[&bookkeeping, &statement],
+        )
+        .is_err()
+    );
+    assert!(matches!(
+        verifier_index.public_input_from_sections(&[&statement]),
+        Err(VerifyError::IncorrectPublicSectionsCount(2, 1))
+    ));
+    assert!(matches!(
+        verifier_index.public_input_from_sections(&[&statement, &statement]),
+        Err(VerifyError::IncorrectPublicSectionLength(1, 3, 2))
+    ));
+
+    // the sections must have distinct names and match the public input
+    assert!(matches!(
+        ConstraintSystem::create(gates.clone())
+            .public_sections(vec![
+                PublicSection::new("statement", 2),
+                PublicSection::new("statement", 3),
+            ])
+            .build(),
+        Err(SetupError::DuplicatePublicSection(name)) if name == "statement"
+    ));
+    assert!(matches!(
+        ConstraintSystem::create(gates)
+            .public_sections(sections)
+            .public(4)
+            .build(),
+        Err(SetupError::PublicSectionsSize(5, 4))
+    ));
+}
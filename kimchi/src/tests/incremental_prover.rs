@@ -0,0 +1,41 @@
+use super::framework::{generic_circuit, generic_index};
+use crate::{incremental_prover::IncrementalProver, verifier::verify};
+use ark_ff::Zero;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+#[test]
+fn test_incremental_prover() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let (index, _) = generic_index::<Vesta>(&[Fp::zero(); 5]);
+    assert!(!index.is_prepared());
+    let prover = IncrementalProver::<Vesta, OpeningProof<Vesta>, BaseSponge>::new(index);
+    assert!(prover.index().is_prepared());
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    // the same index proves several witnesses, differing in their public inputs
+    for i in 0..3u64 {
+        let public = vec![Fp::from(i + 3); 5];
+        let (_, witness) = generic_circuit(&public);
+
+        let proof = prover
+            .prove::<ScalarSponge, _>(&group_map, witness, &[], rng)
+            .unwrap();
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            prover.verifier_index(),
+            &proof,
+            &public,
+        )
+        .unwrap();
+    }
+}
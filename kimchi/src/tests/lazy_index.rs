@@ -0,0 +1,91 @@
+use super::framework::generic_index;
+use crate::{
+    lazy_index::{load_srs, ColumnId, LazyProverIndex},
+    proof::ProverProof,
+    verifier::verify,
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+};
+use std::{fs::File, io::BufWriter};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+#[test]
+fn test_lazy_prover_index() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let verifier_index = index.verifier_index();
+
+    let dir = std::env::temp_dir();
+    let index_path = dir.join(format!("kimchi_lazy_index_{}.bin", std::process::id()));
+    let srs_path = dir.join(format!("kimchi_lazy_srs_{}.bin", std::process::id()));
+    index
+        .serialize_lazy_to(BufWriter::new(File::create(&index_path).unwrap()))
+        .unwrap();
+    std::fs::write(&srs_path, rmp_serde::to_vec(index.srs.as_ref()).unwrap()).unwrap();
+
+    let srs: SRS<Vesta> = load_srs(&srs_path).unwrap();
+    assert_eq!(srs.g, index.srs.g);
+
+    let lazy_index: LazyProverIndex<Vesta, OpeningProof<Vesta>> =
+        LazyProverIndex::open(&index_path, index.srs.clone()).unwrap();
+    assert_eq!(lazy_index.materialized_columns(), 0);
+
+    // Only the requested column is decoded
+    let generic = lazy_index
+        .column(ColumnId::GenericSelector)
+        .unwrap()
+        .unwrap();
+    assert_eq!(generic, &index.column_evaluations.generic_selector4);
+    assert_eq!(lazy_index.materialized_columns(), 1);
+    assert!(lazy_index
+        .column(ColumnId::RangeCheck0Selector)
+        .unwrap()
+        .is_none());
+
+    // The decoded column is moved into the prover index, the other ones are
+    // decoded
+    let prover_index = lazy_index.into_prover_index().unwrap();
+    assert_eq!(
+        prover_index.column_evaluations.generic_selector4,
+        index.column_evaluations.generic_selector4
+    );
+    assert_eq!(
+        prover_index.column_evaluations.coefficients8,
+        index.column_evaluations.coefficients8
+    );
+    assert!(prover_index
+        .column_evaluations
+        .range_check0_selector8
+        .is_none());
+    std::fs::remove_file(&index_path).unwrap();
+    std::fs::remove_file(&srs_path).unwrap();
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &prover_index,
+        &mut rand::rngs::OsRng,
+    )
+    .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+}
@@ -1,19 +1,29 @@
 use super::framework::{print_witness, TestFramework};
-use crate::circuits::{
-    gate::{CircuitGate, GateType},
-    lookup::{
-        runtime_tables::{RuntimeTable, RuntimeTableCfg},
-        tables::LookupTable,
+use crate::{
+    circuits::{
+        constraints::GateError,
+        gate::{CircuitGate, GateType},
+        lookup::{
+            constraints::zk_patch,
+            runtime_tables::{RuntimeTable, RuntimeTableCfg},
+            tables::LookupTable,
+        },
+        polynomial::COLUMNS,
+        wires::Wire,
     },
-    polynomial::COLUMNS,
-    wires::Wire,
+    proof::ProverProof,
+    prover::ProverConfig,
+    verifier::verify,
 };
-use ark_ff::{UniformRand, Zero};
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Evaluations, Polynomial, Radix2EvaluationDomain as D};
+use groupmap::GroupMap;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof};
 use rand::{prelude::*, Rng};
 use std::array;
 
@@ -274,6 +284,92 @@ fn test_runtime_table() {
         .unwrap();
 }
 
+#[test]
+fn test_verify_with_lookups() {
+    let first_column = [8u32, 9, 8, 7, 1];
+    let cfg = RuntimeTableCfg {
+        id: 1,
+        first_column: first_column.into_iter().map(Into::into).collect(),
+    };
+    let data: Vec<Fp> = [0u32, 2, 3, 4, 5].into_iter().map(Into::into).collect();
+    let runtime_table = RuntimeTable {
+        id: cfg.id(),
+        data: data.clone(),
+    };
+    let fixed_table = LookupTable {
+        id: 2,
+        data: vec![
+            (0u32..4).map(Into::into).collect(),
+            (10u32..14).map(Into::into).collect(),
+        ],
+    };
+
+    // circuit
+    let nb_gates = 10;
+    let gates: Vec<_> = (0..nb_gates)
+        .map(|row| CircuitGate::new(GateType::Lookup, Wire::for_row(row), vec![]))
+        .collect();
+
+    // witness, looking up the runtime table on even rows and the fixed table on
+    // odd rows
+    let mut witness: [_; COLUMNS] = array::from_fn(|_col| vec![Fp::zero(); nb_gates]);
+    for row in 0..nb_gates {
+        for (i, chunk) in (1..7).step_by(2).enumerate() {
+            let idx = (row + i) % 4;
+            if row % 2 == 0 {
+                witness[0][row] = 1.into();
+                witness[chunk][row] = first_column[idx].into();
+                witness[chunk + 1][row] = data[idx];
+            } else {
+                witness[0][row] = 2.into();
+                witness[chunk][row] = fixed_table.data[0][idx];
+                witness[chunk + 1][row] = fixed_table.data[1][idx];
+            }
+        }
+    }
+
+    let runner = TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness.clone())
+        .lookup_tables(vec![fixed_table])
+        .runtime_tables_setup(vec![cfg])
+        .setup();
+    let index = runner.prover_index();
+    index
+        .verify_with_lookups(&witness, &[], &[runtime_table.clone()])
+        .unwrap();
+
+    // the runtime tables must be given
+    assert!(matches!(
+        index.verify_with_lookups(&witness, &[], &[]),
+        Err(GateError::RuntimeTablesInconsistent)
+    ));
+
+    // a value not in the fixed table
+    let mut bad_witness = witness.clone();
+    bad_witness[2][3] += Fp::from(1u32);
+    // the gates are still satisfied
+    index.verify(&bad_witness, &[]).unwrap();
+    assert!(matches!(
+        index.verify_with_lookups(&bad_witness, &[], &[runtime_table.clone()]),
+        Err(GateError::LookupNotInTable {
+            row: 3,
+            table_id: Some(2)
+        })
+    ));
+
+    // a value not in the runtime table
+    let mut bad_witness = witness;
+    bad_witness[4][6] += Fp::from(1u32);
+    assert!(matches!(
+        index.verify_with_lookups(&bad_witness, &[], &[runtime_table]),
+        Err(GateError::LookupNotInTable {
+            row: 6,
+            table_id: Some(1)
+        })
+    ));
+}
+
 #[test]
 fn test_negative_test_runtime_table_value_not_in_table() {
     // We create 1 runtime table cfg
@@ -729,3 +825,130 @@ fn test_dummy_zero_entry_is_counted_while_computing_domain_size() {
     // As the dummy entry has been added, we reached the next power of two
     assert!(domain_size == (1 << (power_of_2 + 1)));
 }
+
+#[test]
+fn test_lookup_zero_knowledge() {
+    let table = LookupTable {
+        id: 0,
+        data: vec![
+            (0u32..8).map(Into::into).collect(),
+            (10u32..18).map(Into::into).collect(),
+        ],
+    };
+
+    let nb_gates = 10;
+    let gates: Vec<_> = (0..nb_gates)
+        .map(|row| CircuitGate::new(GateType::Lookup, Wire::for_row(row), vec![]))
+        .collect();
+    let mut witness: [_; COLUMNS] = array::from_fn(|_col| vec![Fp::zero(); nb_gates]);
+    for row in 0..nb_gates {
+        for (i, chunk) in (1..7).step_by(2).enumerate() {
+            let idx = (row + i) % 8;
+            witness[chunk][row] = table.data[0][idx];
+            witness[chunk + 1][row] = table.data[1][idx];
+        }
+    }
+
+    let runner = TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness.clone())
+        .lookup_tables(vec![table])
+        .setup();
+    let index = runner.prover_index();
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let prove = |config: ProverConfig| {
+        ProverProof::create_with_config::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness.clone(),
+            &[],
+            index,
+            vec![],
+            &config,
+            &mut o1_utils::tests::make_test_rng(None),
+        )
+        .unwrap()
+    };
+    let hiding = |seed| ProverConfig {
+        seed: Some(seed),
+        ..ProverConfig::default()
+    };
+    let non_hiding = ProverConfig {
+        hiding: false,
+        ..ProverConfig::default()
+    };
+
+    // the sorted and aggregation polynomials are blinded
+    let proof1 = prove(hiding([1; 32]));
+    let proof2 = prove(hiding([2; 32]));
+    let lookup1 = proof1.commitments.lookup.as_ref().unwrap();
+    let lookup2 = proof2.commitments.lookup.as_ref().unwrap();
+    for (sorted1, sorted2) in lookup1.sorted.iter().zip(&lookup2.sorted) {
+        assert_ne!(sorted1, sorted2);
+    }
+    assert_ne!(lookup1.aggreg, lookup2.aggreg);
+    for proof in [&proof1, &proof2] {
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index,
+            proof,
+            &[],
+        )
+        .unwrap();
+    }
+
+    // unless hiding is disabled
+    let proof1 = prove(non_hiding);
+    let proof2 = prove(non_hiding);
+    assert_eq!(
+        proof1.commitments.lookup.as_ref().unwrap().sorted,
+        proof2.commitments.lookup.as_ref().unwrap().sorted
+    );
+    assert_eq!(
+        proof1.evals.lookup_aggregation,
+        proof2.evals.lookup_aggregation
+    );
+}
+
+#[test]
+fn test_lookup_zk_rows_hide_evaluations() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let domain = D::<Fp>::new(16).unwrap();
+    let n = domain.size();
+    let zk_rows = 3;
+
+    // two sorted columns, with different looked-up values
+    let a: Vec<Fp> = (0..n - zk_rows).map(|_| Fp::rand(rng)).collect();
+    let b: Vec<Fp> = (0..n - zk_rows).map(|_| Fp::rand(rng)).collect();
+
+    let eval = |e: &Evaluations<Fp, D<Fp>>, x: Fp| e.interpolate_by_ref().evaluate(&x);
+    let zeta = Fp::rand(rng);
+    let points = [zeta, zeta * domain.group_gen];
+
+    // the evaluations revealed for a
+    let a = zk_patch(a, domain, zk_rows, rng);
+    let target: Vec<_> = points.iter().map(|x| eval(&a, *x)).collect();
+
+    // can also be the ones of b, for some random rows
+    let mut b = zk_patch(b, domain, zk_rows, rng);
+    let rows = [n - zk_rows, n - zk_rows + 1];
+    let lagrange = |row: usize, x: Fp| {
+        let mut e = vec![Fp::zero(); n];
+        e[row] = Fp::one();
+        eval(&Evaluations::from_vec_and_domain(e, domain), x)
+    };
+    let m: [[Fp; 2]; 2] = array::from_fn(|i| array::from_fn(|j| lagrange(rows[j], points[i])));
+    let det = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+    assert!(!det.is_zero());
+    let diff: Vec<_> = points
+        .iter()
+        .zip(&target)
+        .map(|(x, t)| *t - eval(&b, *x))
+        .collect();
+    b.evals[rows[0]] += (diff[0] * m[1][1] - m[0][1] * diff[1]) / det;
+    b.evals[rows[1]] += (m[0][0] * diff[1] - m[1][0] * diff[0]) / det;
+    for (x, t) in points.iter().zip(&target) {
+        assert_eq!(eval(&b, *x), *t);
+    }
+}
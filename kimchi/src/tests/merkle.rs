@@ -0,0 +1,78 @@
+use crate::{
+    circuits::{
+        gate::CircuitGate,
+        polynomials::merkle::{self, MERKLE_ROWS_PER_LEVEL},
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    tests::framework::TestFramework,
+};
+use ark_ff::{UniformRand, Zero};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, Sponge},
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+fn hash(left: Fp, right: Fp) -> Fp {
+    let mut sponge = ArithmeticSponge::<Fp, SpongeParams>::new(Vesta::sponge_params());
+    sponge.absorb(&[left, right]);
+    sponge.squeeze()
+}
+
+#[test]
+fn test_merkle_membership() {
+    let depth = 4;
+    let index = 0b1011;
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+
+    let leaf = Fp::rand(rng);
+    let path: Vec<Fp> = (0..depth).map(|_| Fp::rand(rng)).collect();
+    let expected_root = path
+        .iter()
+        .enumerate()
+        .fold(leaf, |current, (level, sibling)| {
+            if (index >> level) & 1 == 0 {
+                hash(current, *sibling)
+            } else {
+                hash(*sibling, current)
+            }
+        });
+
+    let round_constants = &*Vesta::sponge_params().round_constants;
+    let (gates, next_row) = CircuitGate::<Fp>::create_merkle_membership(0, depth, round_constants);
+    assert_eq!(next_row, depth * MERKLE_ROWS_PER_LEVEL);
+    assert_eq!(gates.len(), next_row);
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); next_row]);
+    let root = merkle::merkle_membership_witness(
+        &mut witness,
+        0,
+        Vesta::sponge_params(),
+        leaf,
+        &path,
+        index,
+    );
+    assert_eq!(root, expected_root);
+    // the leaf and the root are in the documented cells
+    assert_eq!(witness[1][0], leaf);
+    assert_eq!(witness[0][next_row - 1], root);
+
+    TestFramework::<Vesta>::default()
+        .gates(gates.clone())
+        .witness(witness.clone())
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+
+    // the bit of the index must be boolean
+    witness[3][0] = Fp::from(2u32);
+    witness[4][0] = Fp::from(2u32);
+    assert!(gates[0].verify_generic(0, &witness, &[]).is_err());
+}
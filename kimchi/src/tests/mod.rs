@@ -1,20 +1,38 @@
 // IMPROVEME: move all tests in top-level directory tests
 mod and;
+#[cfg(feature = "ocaml-interop")]
+mod bin_prot;
 mod chunked;
+#[cfg(feature = "conformance")]
+mod conformance;
+#[cfg(feature = "parallel")]
+mod deterministic;
 mod ec;
 mod endomul;
 mod endomul_scalar;
 mod foreign_field_add;
 mod foreign_field_mul;
 mod framework;
+mod generators;
 mod generic;
+mod incremental_prover;
 mod keccak;
+#[cfg(feature = "lazy-index")]
+mod lazy_index;
 mod lookup;
+mod merkle;
 mod not;
 mod poseidon;
+#[cfg(feature = "prover-metrics")]
+mod prover_metrics;
 mod range_check;
 mod recursion;
 mod rot;
+mod schnorr;
 mod serde;
+mod shared;
+mod sparse;
+mod transcript;
 mod varbasemul;
+mod witness_source;
 mod xor;
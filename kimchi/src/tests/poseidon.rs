@@ -3,12 +3,13 @@ use crate::{
         gate::CircuitGate,
         polynomials,
         polynomials::poseidon::ROUNDS_PER_ROW,
+        testing::{self, FuzzConfig},
         wires::{Wire, COLUMNS},
     },
     curve::KimchiCurve,
     tests::framework::TestFramework,
 };
-use ark_ff::Zero;
+use ark_ff::{UniformRand, Zero};
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::{PlonkSpongeConstantsKimchi, SpongeConstants},
@@ -88,3 +89,36 @@ fn test_poseidon() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+#[test]
+fn test_poseidon_cells_are_constrained() {
+    let round_constants = &*Vesta::sponge_params().round_constants;
+    let (gates, _) = CircuitGate::<Fp>::create_poseidon_gadget(
+        0,
+        [Wire::for_row(0), Wire::for_row(POS_ROWS_PER_HASH)],
+        round_constants,
+    );
+
+    // the round rows are fully constrained,
+    // the output row only through the state of its first three columns
+    let mut cells = testing::cells(0..POS_ROWS_PER_HASH, 0..COLUMNS);
+    cells.extend(testing::cells(
+        POS_ROWS_PER_HASH..POS_ROWS_PER_HASH + 1,
+        0..SpongeParams::SPONGE_WIDTH,
+    ));
+
+    testing::assert_constrained::<Vesta, _>(
+        gates,
+        0,
+        &cells,
+        |rng| {
+            let mut witness: [Vec<Fp>; COLUMNS] =
+                array::from_fn(|_| vec![Fp::zero(); POS_ROWS_PER_HASH + 1]);
+            let input = array::from_fn(|_| Fp::rand(rng));
+            polynomials::poseidon::generate_witness(0, Vesta::sponge_params(), &mut witness, input);
+            witness
+        },
+        &FuzzConfig::default(),
+        &mut o1_utils::tests::make_test_rng(None),
+    );
+}
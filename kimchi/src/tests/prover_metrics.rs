@@ -0,0 +1,78 @@
+use super::framework::generic_index;
+use crate::{
+    proof::ProverProof,
+    prover_metrics::{MetricsAllocator, ProverStage},
+    verifier::verify,
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof};
+use rand::{rngs::StdRng, SeedableRng};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const SEED: [u8; 32] = [5; 32];
+
+#[global_allocator]
+static ALLOCATOR: MetricsAllocator = MetricsAllocator;
+
+#[test]
+fn test_prover_metrics() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let expected = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.clone(),
+        &[],
+        &index,
+        &mut StdRng::from_seed(SEED),
+    )
+    .unwrap();
+
+    let (proof, metrics) = ProverProof::create_with_metrics::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut StdRng::from_seed(SEED),
+    )
+    .unwrap();
+
+    // the instrumentation does not change the proof
+    assert_eq!(proof, expected);
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &index.verifier_index(),
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    let stages: Vec<_> = metrics.stages.iter().map(|m| m.stage).collect();
+    assert_eq!(
+        stages,
+        vec![
+            ProverStage::WitnessCommitment,
+            ProverStage::LookupAggregation,
+            ProverStage::Permutation,
+            ProverStage::Quotient,
+            ProverStage::Evaluations,
+            ProverStage::OpeningProof,
+        ]
+    );
+    assert!(metrics.stages.iter().all(|m| m.peak_allocated.is_some()));
+    assert!(metrics.peak_allocated().unwrap() > 0);
+    assert!(metrics.wall_time() > std::time::Duration::ZERO);
+
+    // nothing is recorded outside of `create_with_metrics`
+    assert!(crate::prover_metrics::take_metrics().stages.is_empty());
+}
@@ -1,21 +1,29 @@
 use crate::{
     circuits::{
-        constraints::ConstraintSystem,
+        berkeley_columns::Column,
+        constraints::{ConstraintSystem, OPTIONAL_GATES},
+        expr::PolishToken,
         gate::{CircuitGate, CircuitGateError, GateType},
+        lookup::tables::RANGE_CHECK_TABLE_ID,
         polynomial::COLUMNS,
         polynomials::{
             foreign_field_common::{
                 BigUintArrayFieldHelpers, BigUintForeignFieldHelpers, FieldArrayCompact,
                 KimchiForeignElement,
             },
-            generic::GenericGateSpec,
+            generic::{testing::create_circuit, GenericGateSpec},
             range_check::{self},
         },
+        testing::{self, FuzzConfig},
         wires::Wire,
     },
     proof::ProverProof,
-    prover_index::{testing::new_index_for_test_with_lookups, ProverIndex},
+    prover_index::{
+        testing::{new_index_for_test, new_index_for_test_with_lookups},
+        ProverIndex,
+    },
     verifier::verify,
+    verifier_index::VerifierIndex,
 };
 use ark_ec::AffineRepr;
 use ark_ff::{Field, One, Zero};
@@ -116,12 +124,9 @@ fn verify_range_check0_valid_witness() {
     let index = create_test_prover_index(0, false);
 
     let witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("115655443433221211ffef000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("eeddcdccbbabaa99898877000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("7766565544343322121100000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0xefff111222333444555611_u128),
+        PallasField::from(0x77888999aaabbbcccdddee_u128),
+        PallasField::from(0x11122233344455566677_u128),
     );
 
     // gates[0] is RangeCheck0
@@ -147,12 +152,9 @@ fn verify_range_check0_valid_witness() {
     );
 
     let witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("23d406ac800d1af73040dd000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("a8fe8555371eb021469863000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("3edff808d8f533be9af500000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0xdd4030f71a0d80ac06d423_u128),
+        PallasField::from(0x63984621b01e375585fea8_u128),
+        PallasField::from(0xf59abe33f5d808f8df3e_u128),
     );
 
     // gates[0] is RangeCheck0
@@ -183,12 +185,9 @@ fn verify_range_check0_invalid_witness() {
     let index = create_test_prover_index(0, false);
 
     let mut witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("22f6b4e7ecb4488433ade7000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("e20e9d80333f2fba463ffd000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("25d28bfd6cdff91ca9bc00000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0xe7ad338448b4ece7b4f622_u128),
+        PallasField::from(0xfd3f46ba2f3f33809d0ee2_u128),
+        PallasField::from(0xbca91cf9df6cfd8bd225_u128),
     );
 
     // Invalidate witness copy constraint
@@ -228,12 +227,9 @@ fn verify_range_check0_invalid_witness() {
     );
 
     let mut witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("22cab5e27101eeafd2cbe1000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("1ab61d31f4e27fe41a318c000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("449a45cd749f1e091a3000000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0xe1cbd2afee0171e2b5ca22_u128),
+        PallasField::from(0x8c311ae47fe2f4311db61a_u128),
+        PallasField::from(0x301a091e9f74cd459a44_u128),
     );
 
     // Invalidate witness
@@ -265,6 +261,27 @@ fn verify_range_check0_invalid_witness() {
     );
 }
 
+#[test]
+fn verify_range_check_limbs_of_field_element() {
+    let index = create_test_prover_index(0, false);
+
+    // The largest element, i.e. -1, is decomposed into three valid limbs
+    let [v0, v1, v2] = PallasField::from_i64(-1).to_limbs_88();
+    let witness = range_check::witness::create_multi::<PallasField>(v0, v1, v2);
+
+    for row in 0..3 {
+        assert_eq!(
+            index.cs.gates[row].verify_witness::<Vesta>(
+                row,
+                &witness,
+                &index.cs,
+                &witness[0][0..index.cs.public]
+            ),
+            Ok(())
+        );
+    }
+}
+
 #[test]
 fn verify_range_check0_valid_v0_in_range() {
     let index = create_test_prover_index(0, false);
@@ -681,12 +698,9 @@ fn verify_range_check1_valid_witness() {
     let index = create_test_prover_index(0, false);
 
     let witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("22cab5e27101eeafd2cbe1000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("1ab61d31f4e27fe41a318c000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("449a45cd749f1e091a3000000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0xe1cbd2afee0171e2b5ca22_u128),
+        PallasField::from(0x8c311ae47fe2f4311db61a_u128),
+        PallasField::from(0x301a091e9f74cd459a44_u128),
     );
 
     // gates[2] is RangeCheck1
@@ -701,12 +715,9 @@ fn verify_range_check1_valid_witness() {
     );
 
     let witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("0d96f6fc210316c73bcc4d000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("59c8e7b0ffb3cab6ce8d48000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("686c10e73930b92f375800000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0x4dcc3bc7160321fcf6960d_u128),
+        PallasField::from(0x488dceb6cab3ffb0e7c859_u128),
+        PallasField::from(0x58372fb93039e7106c68_u128),
     );
 
     // gates[2] is RangeCheck1
@@ -726,12 +737,9 @@ fn verify_range_check1_invalid_witness() {
     let index = create_test_prover_index(0, false);
 
     let mut witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("2ce2d3ac942f98d59e7e11000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("52dd43524b95399f5d458d000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("60ca087b427918fa0e2600000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0x117e9ed5982f94acd3e22c_u128),
+        PallasField::from(0x8d455d9f39954b5243dd52_u128),
+        PallasField::from(0x260efa1879427b08ca60_u128),
     );
 
     // Corrupt witness
@@ -749,12 +757,9 @@ fn verify_range_check1_invalid_witness() {
     );
 
     let mut witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("1bd50c94d2dc83d32f01c0000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("e983d7cd9e28e440930f86000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("ea226054772cd009d2af00000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0xc0012fd383dcd2940cd51b_u128),
+        PallasField::from(0x860f9340e4289ecdd783e9_u128),
+        PallasField::from(0xafd209d02c77546022ea_u128),
     );
 
     // Corrupt witness
@@ -1180,12 +1185,9 @@ fn verify_range_check_valid_proof1() {
 
     // Create witness
     let witness = range_check::witness::create_multi::<PallasField>(
-        PallasField::from_hex("2bc0afaa2f6f50b1d1424b000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("8b30889f3a39e297ac851a000000000000000000000000000000000000000000")
-            .unwrap(),
-        PallasField::from_hex("c1c85ec47635e8edac5600000000000000000000000000000000000000000000")
-            .unwrap(),
+        PallasField::from(0x4b42d1b1506f2faaafc02b_u128),
+        PallasField::from(0x1a85ac97e2393a9f88308b_u128),
+        PallasField::from(0x56acede83576c45ec8c1_u128),
     );
 
     // Verify computed witness satisfies the circuit
@@ -1217,6 +1219,54 @@ fn verify_range_check_valid_proof1() {
     res.unwrap();
 }
 
+#[test]
+fn verify_range_check_proof_with_verifier_index_from_circuit() {
+    // Create prover index
+    let prover_index = create_test_prover_index(0, false);
+
+    // Create witness
+    let witness = range_check::witness::create_multi::<PallasField>(
+        PallasField::from(0x4b42d1b1506f2faaafc02b_u128),
+        PallasField::from(0x1a85ac97e2393a9f88308b_u128),
+        PallasField::from(0x56acede83576c45ec8c1_u128),
+    );
+
+    // Generate proof
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &prover_index,
+        &mut rand::rngs::OsRng,
+    )
+    .expect("failed to generate proof");
+
+    // Derive the verifier index from the circuit only
+    let (_next_row, gates) = CircuitGate::<Fp>::create_multi_range_check(0);
+    let verifier_index = VerifierIndex::<Vesta, OpeningProof<Vesta>>::from_circuit(
+        gates,
+        0,
+        vec![],
+        None,
+        prover_index.srs.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        verifier_index.digest::<BaseSponge>(),
+        prover_index.verifier_index().digest::<BaseSponge>()
+    );
+
+    // Verify proof
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &[],
+    )
+    .unwrap();
+}
+
 #[test]
 fn verify_compact_multi_range_check_proof() {
     let rng = &mut o1_utils::tests::make_test_rng(None);
@@ -1237,3 +1287,187 @@ fn verify_compact_multi_range_check_proof() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+#[test]
+fn verify_compact_multi_range_check_from_foreign_element() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+
+    let fe = KimchiForeignElement::<PallasField>::from_biguint(
+        rng.gen_biguint_below(&BigUint::binary_modulus()),
+    );
+
+    // Create witness
+    let mut witness: [Vec<PallasField>; COLUMNS] = array::from_fn(|_| vec![]);
+    range_check::witness::extend_multi_compact_from_fe(&mut witness, &fe);
+    assert_eq!(witness[1][2], fe.limbs.to_compact_limbs()[0]);
+
+    let (_next_row, gates) = CircuitGate::<Fp>::create_compact_multi_range_check(0);
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn multi_range_check_cells_are_constrained() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let (_next_row, gates) = CircuitGate::<Fp>::create_multi_range_check(0);
+
+    // outside of compact mode, v12 (row 2, column 1) is not constrained
+    let mut cells = testing::cells(0..4, 0..COLUMNS);
+    cells.retain(|cell| *cell != Wire::new(2, 1));
+
+    testing::assert_constrained::<Vesta, _>(
+        gates,
+        0,
+        &cells,
+        |rng| {
+            let [v0, v1, v2]: [PallasField; 3] =
+                array::from_fn(|_| rng.gen_biguint_below(&BigUint::two_to_limb())).to_fields();
+            range_check::witness::create_multi(v0, v1, v2)
+        },
+        &FuzzConfig::default(),
+        rng,
+    );
+}
+
+#[test]
+fn multi_range_check_stats() {
+    let index = create_test_prover_index(0, false);
+    let stats = index.cs.stats();
+
+    assert_eq!(stats.rows, 4);
+    assert_eq!(stats.gates[&GateType::RangeCheck0], 2);
+    assert_eq!(stats.gates[&GateType::RangeCheck1], 1);
+    assert_eq!(stats.gates[&GateType::Zero], 1);
+
+    // 4 lookups in each row
+    assert_eq!(
+        stats.table_lookups.into_iter().collect::<Vec<_>>(),
+        [(RANGE_CHECK_TABLE_ID, 16)]
+    );
+    assert_eq!(stats.witness_table_lookups, 0);
+}
+
+#[test]
+fn multi_range_check_dot() {
+    let index = create_test_prover_index(0, false);
+    let dot = index.cs.to_dot();
+
+    assert!(dot.starts_with("digraph circuit {"));
+    assert!(dot
+        .contains("r0 [label=\"{0: RangeCheck0|{<c0>0|<c1>1|<c2>2|<c3>3|<c4>4|<c5>5|<c6>6}}\"];"));
+    assert!(dot.contains("r3 [label=\"{3: Zero|"));
+    assert!(!dot.contains("r4 "));
+
+    // the copies of the 12-bit limbs of v0 and v1
+    for (cell1, cell2) in [
+        ((0, 1), (3, 3)),
+        ((0, 2), (3, 4)),
+        ((1, 1), (3, 5)),
+        ((1, 2), (3, 6)),
+    ] {
+        assert!(dot.contains(&format!(
+            "r{}:c{} -> r{}:c{};",
+            cell1.0, cell1.1, cell2.0, cell2.1
+        )));
+        assert!(dot.contains(&format!(
+            "r{}:c{} -> r{}:c{};",
+            cell2.0, cell2.1, cell1.0, cell1.1
+        )));
+    }
+    assert_eq!(dot.matches("->").count(), 8);
+}
+
+#[test]
+fn multi_range_check_optional_selectors() {
+    let index = create_test_prover_index(0, false);
+    let verifier_index = index.verifier_index();
+
+    // only the selectors of the gates used by the circuit are committed
+    assert_eq!(
+        verifier_index
+            .optional_selector_comm
+            .keys()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![GateType::RangeCheck0, GateType::RangeCheck1]
+    );
+
+    let feature_flags = verifier_index.feature_flags();
+    assert!(feature_flags.uses_gate(GateType::RangeCheck0));
+    assert!(feature_flags.uses_gate(GateType::RangeCheck1));
+    assert!(!feature_flags.uses_gate(GateType::Xor16));
+    assert!(feature_flags.uses_gate(GateType::Generic));
+}
+
+/// Returns the columns used by the linearization of `verifier_index`
+fn linearization_columns(
+    verifier_index: &VerifierIndex<Vesta, OpeningProof<Vesta>>,
+) -> Vec<Column> {
+    let linearization = &verifier_index.linearization;
+    linearization
+        .index_terms
+        .iter()
+        .flat_map(|(col, term)| {
+            term.iter()
+                .filter_map(|token| match token {
+                    PolishToken::Cell(variable) => Some(variable.col),
+                    _ => None,
+                })
+                .chain([*col])
+        })
+        .chain(
+            linearization
+                .constant_term
+                .iter()
+                .filter_map(|token| match token {
+                    PolishToken::Cell(variable) => Some(variable.col),
+                    _ => None,
+                }),
+        )
+        .collect()
+}
+
+fn is_optional_column(col: &Column) -> bool {
+    match col {
+        Column::Index(gate_type) => OPTIONAL_GATES.contains(gate_type),
+        Column::LookupSorted(_)
+        | Column::LookupAggreg
+        | Column::LookupTable
+        | Column::LookupKindIndex(_)
+        | Column::LookupRuntimeSelector
+        | Column::LookupRuntimeTable => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn generic_circuit_has_no_optional_gates() {
+    // a circuit without optional gates carries none of their selectors,
+    // lookup tables and constraints
+    let index = new_index_for_test::<Vesta>(create_circuit(0, 0), 0);
+    assert!(OPTIONAL_GATES
+        .iter()
+        .all(|gate_type| !index.cs.feature_flags.uses_gate(*gate_type)));
+    assert!(index.column_evaluations.range_check0_selector8.is_none());
+    assert!(index.column_evaluations.xor_selector8.is_none());
+
+    let verifier_index = index.verifier_index();
+    assert!(verifier_index.optional_selector_comm.is_empty());
+    assert!(verifier_index.lookup_index.is_none());
+    assert!(!linearization_columns(&verifier_index)
+        .iter()
+        .any(is_optional_column));
+
+    // the ones of the range check gates are detected and carried
+    let verifier_index = create_test_prover_index(0, false).verifier_index();
+    assert!(verifier_index.lookup_index.is_some());
+    let columns = linearization_columns(&verifier_index);
+    assert!(columns.contains(&Column::Index(GateType::RangeCheck0)));
+    assert!(columns.contains(&Column::LookupAggreg));
+    assert!(!columns.contains(&Column::Index(GateType::Xor16)));
+}
@@ -1,20 +1,30 @@
-use super::framework::TestFramework;
+use super::framework::{generic_circuit, generic_index, TestFramework};
 use crate::{
+    accumulator::{
+        deserialize_accumulators, discharge_accumulators, serialize_accumulators, verify_partial,
+    },
     circuits::{
         polynomials::generic::testing::{create_circuit, fill_in_witness},
         wires::COLUMNS,
     },
-    proof::RecursionChallenge,
+    proof::{ProverProof, RecursionChallenge},
+    prover_index::testing::{new_index_for_test, new_index_for_test_with_lookups},
+    verifier::verify,
 };
-use ark_ff::{UniformRand, Zero};
+use ark_ff::{Field, One, UniformRand, Zero};
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use groupmap::GroupMap;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
 use o1_utils::math;
-use poly_commitment::{commitment::b_poly_coefficients, SRS as _};
+use poly_commitment::{
+    commitment::{b_poly_coefficients, CommitmentCurve, PolyComm},
+    ipa::OpeningProof,
+    SRS as _,
+};
 use std::array;
 
 type SpongeParams = PlonkSpongeConstantsKimchi;
@@ -55,3 +65,165 @@ fn test_recursion() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+#[test]
+fn test_verify_partial_and_discharge() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+    let proof =
+        ProverProof::create::<BaseSponge, ScalarSponge, _>(&group_map, witness, &[], &index, rng)
+            .unwrap();
+    let verifier_index = index.verifier_index();
+
+    let acc = verify_partial::<Vesta, BaseSponge, ScalarSponge>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+    assert_eq!(acc.comm, PolyComm::new(vec![proof.proof.sg]));
+
+    // The accumulator survives a round trip through its encoding
+    let bytes = serialize_accumulators(&[acc.clone()]).unwrap();
+    let accs: Vec<RecursionChallenge<Vesta>> = deserialize_accumulators(&bytes).unwrap();
+    assert_eq!(accs, vec![acc.clone()]);
+    discharge_accumulators(&index.srs, &accs).unwrap();
+
+    // An accumulator whose challenges do not match its commitment is rejected
+    let mut wrong_acc = acc.clone();
+    wrong_acc.chals[0] += Fp::one();
+    assert!(discharge_accumulators(&index.srs, &[acc, wrong_acc]).is_err());
+
+    // A proof for another public input does not verify, even partially
+    let wrong_public = vec![Fp::from(4u8); 5];
+    assert!(verify_partial::<Vesta, BaseSponge, ScalarSponge>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &wrong_public,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_two_layer_recursion() {
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+
+    let public = vec![Fp::from(3u8); 5];
+    let (gates, witness) = generic_circuit(&public);
+
+    // The first layer does not carry any challenge
+    let index = new_index_for_test::<Vesta>(gates.clone(), public.len());
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.clone(),
+        &[],
+        &index,
+        rng,
+    )
+    .unwrap();
+    let acc = verify_partial::<Vesta, BaseSponge, ScalarSponge>(
+        &group_map,
+        &index.verifier_index(),
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // The commitment computed from the challenges is the point of the opening
+    let from_chals = RecursionChallenge::from_challenges(&*index.srs, acc.chals.clone());
+    assert_eq!(from_chals, acc);
+    assert!(acc.check_commitment(&*index.srs));
+
+    // The second layer carries the accumulator of the first one
+    let index = new_index_for_test_with_lookups::<Vesta>(
+        gates.clone(),
+        public.len(),
+        1,
+        vec![],
+        None,
+        false,
+        None,
+    );
+    let proof = ProverProof::create_recursive::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.clone(),
+        &[],
+        &index,
+        vec![acc.clone()],
+        None,
+        rng,
+    )
+    .unwrap();
+    let verifier_index = index.verifier_index();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+    let outer_acc = verify_partial::<Vesta, BaseSponge, ScalarSponge>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+    assert!(outer_acc.check_commitment(&*index.srs));
+    discharge_accumulators(&index.srs, &[outer_acc.clone()]).unwrap();
+
+    // The challenges of both layers are evaluated at once
+    let points = [Fp::from(5u8), Fp::from(7u8)];
+    let powers = points.map(|x| x.pow([verifier_index.max_poly_size as u64]));
+    let challenges = vec![acc, outer_acc];
+    let evals = RecursionChallenge::evals_batch(
+        &challenges,
+        verifier_index.max_poly_size,
+        &points,
+        &powers,
+    );
+    for (challenge, evals) in challenges.iter().zip(evals) {
+        assert_eq!(
+            evals,
+            challenge.evals(verifier_index.max_poly_size, &points, &powers)
+        );
+    }
+
+    // The order of the challenges is part of the transcript
+    let index =
+        new_index_for_test_with_lookups::<Vesta>(gates, public.len(), 2, vec![], None, false, None);
+    let mut proof = ProverProof::create_recursive::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        challenges,
+        None,
+        rng,
+    )
+    .unwrap();
+    let verifier_index = index.verifier_index();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public,
+    )
+    .unwrap();
+    proof.prev_challenges.swap(0, 1);
+    assert!(
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index,
+            &proof,
+            &public,
+        )
+        .is_err()
+    );
+}
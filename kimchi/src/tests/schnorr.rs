@@ -0,0 +1,150 @@
+use crate::{
+    circuits::{
+        polynomials::schnorr::{self, Signature, SCHNORR_ROWS},
+        wires::COLUMNS,
+    },
+    error::SchnorrError,
+    tests::framework::TestFramework,
+};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use mina_curves::pasta::{Fp, Fq, Pallas, Vesta, VestaParameters};
+use mina_hasher::{Hashable, ROInput};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use mina_signer::{Keypair, NetworkId, SecKey, Signer};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+fn sign(sk: Fq, msg: Fp, network_id: NetworkId, rng: &mut impl rand::Rng) -> Signature {
+    let pk = (Pallas::generator() * sk).into_affine();
+    let mut k = Fq::rand(rng);
+    let r = (Pallas::generator() * k).into_affine();
+    if r.y.into_bigint().is_odd() {
+        k = -k;
+    }
+    let e = schnorr::challenge(pk, r.x, msg, network_id);
+    let e = Fq::from_bigint(e.into_bigint()).unwrap();
+    Signature {
+        rx: r.x,
+        s: k + e * sk,
+    }
+}
+
+#[test]
+fn test_schnorr_verify() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let sk = Fq::rand(rng);
+    let pk = (Pallas::generator() * sk).into_affine();
+    let msg = Fp::rand(rng);
+    let sig = sign(sk, msg, NetworkId::TESTNET, rng);
+
+    let (gates, next_row) = schnorr::create_verify(0, NetworkId::TESTNET);
+    assert_eq!(gates.len(), SCHNORR_ROWS);
+    assert_eq!(next_row, SCHNORR_ROWS);
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); next_row]);
+    assert_eq!(
+        schnorr::witness(
+            &mut witness,
+            0,
+            pk,
+            &sig,
+            msg + Fp::from(1u32),
+            NetworkId::TESTNET
+        ),
+        Err(SchnorrError::InvalidSignature)
+    );
+    assert_eq!(
+        schnorr::witness(&mut witness, 0, pk, &sig, msg, NetworkId::MAINNET),
+        Err(SchnorrError::InvalidSignature)
+    );
+
+    // the signature whose point R has an odd y coordinate is rejected
+    let e = schnorr::challenge(pk, sig.rx, msg, NetworkId::TESTNET);
+    let e = Fq::from_bigint(e.into_bigint()).unwrap();
+    let odd = Signature {
+        rx: sig.rx,
+        s: e * sk + e * sk - sig.s,
+    };
+    assert_eq!(
+        schnorr::witness(&mut witness, 0, pk, &odd, msg, NetworkId::TESTNET),
+        Err(SchnorrError::InvalidSignature)
+    );
+
+    schnorr::witness(&mut witness, 0, pk, &sig, msg, NetworkId::TESTNET).unwrap();
+
+    let cells = schnorr::SchnorrCells::at(0);
+    assert_eq!(witness[cells.msg.col][cells.msg.row], msg);
+    assert_eq!(witness[cells.rx.col][cells.rx.row], sig.rx);
+    assert_eq!(witness[cells.pk[0].col][cells.pk[0].row], pk.x);
+    assert_eq!(witness[cells.pk[1].col][cells.pk[1].row], pk.y);
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+/// A message made of a single field element, signed with mina-signer
+#[derive(Clone)]
+struct FieldMessage(Fp);
+
+impl Hashable for FieldMessage {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new().append_field(self.0)
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        let domain = match network_id {
+            NetworkId::MAINNET => "MinaSignatureMainnet",
+            NetworkId::TESTNET => "CodaSignature",
+        };
+        Some(domain.to_string())
+    }
+}
+
+#[test]
+fn test_schnorr_mina_signer() {
+    let kp = Keypair::from_secret_key(SecKey::new(Fq::from(42u64))).unwrap();
+    let pk = *kp.public.point();
+    let msg = FieldMessage(Fp::from(1337u64));
+
+    for network_id in [NetworkId::MAINNET, NetworkId::TESTNET] {
+        let mut signer = mina_signer::create_kimchi::<FieldMessage>(network_id.clone());
+        let sig = signer.sign(&kp, &msg);
+        assert!(signer.verify(&sig, &kp.public, &msg));
+        let sig = Signature {
+            rx: sig.rx,
+            s: sig.s,
+        };
+
+        let (gates, next_row) = schnorr::create_verify(0, network_id.clone());
+        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); next_row]);
+        let other = match network_id {
+            NetworkId::MAINNET => NetworkId::TESTNET,
+            NetworkId::TESTNET => NetworkId::MAINNET,
+        };
+        assert_eq!(
+            schnorr::witness(&mut witness, 0, pk, &sig, msg.0, other),
+            Err(SchnorrError::InvalidSignature)
+        );
+        schnorr::witness(&mut witness, 0, pk, &sig, msg.0, network_id).unwrap();
+
+        TestFramework::<Vesta>::default()
+            .gates(gates)
+            .witness(witness)
+            .setup()
+            .prove_and_verify::<BaseSponge, ScalarSponge>()
+            .unwrap();
+    }
+}
@@ -1,28 +1,22 @@
+use super::framework::generic_index;
 use crate::{
-    bench::BenchmarkCtx,
-    circuits::{
-        polynomials::generic::testing::{create_circuit, fill_in_witness},
-        wires::COLUMNS,
-    },
-    proof::ProverProof,
-    prover_index::testing::new_index_for_test,
-    verifier::verify,
-    verifier_index::VerifierIndex,
+    bench::BenchmarkCtx, error::IndexSerializationError, index_serialization::INDEX_MAGIC,
+    proof::ProverProof, prover_index::ProverIndex, verifier::verify, verifier_index::VerifierIndex,
 };
 use ark_ec::short_weierstrass::Affine;
-use ark_ff::Zero;
 use groupmap::GroupMap;
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
+use o1_utils::serialization::{DeserializationError, DeserializationMode};
 use poly_commitment::{
     commitment::CommitmentCurve,
     ipa::{OpeningProof, SRS},
     SRS as _,
 };
-use std::{array, time::Instant};
+use std::time::Instant;
 
 type SpongeParams = PlonkSpongeConstantsKimchi;
 type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
@@ -51,16 +45,97 @@ mod tests {
         ctx.batch_verification(&vec![(de_pf, public_input)]);
     }
 
+    #[test]
+    fn test_strict_proof_deserialization() {
+        let ctx = BenchmarkCtx::new(4);
+
+        let (proof, public_input) = ctx.create_proof();
+        let mut ser_pf = rmp_serde::to_vec(&proof).unwrap();
+
+        // the canonical encoding is accepted in strict mode
+        let de_pf = ProverProof::<Vesta, OpeningProof<Vesta>>::from_bytes(
+            &ser_pf,
+            DeserializationMode::Strict,
+        )
+        .unwrap();
+        ctx.batch_verification(&vec![(de_pf, public_input)]);
+
+        // trailing bytes are only accepted in lenient mode
+        ser_pf.push(0);
+        assert!(ProverProof::<Vesta, OpeningProof<Vesta>>::from_bytes(
+            &ser_pf,
+            DeserializationMode::Lenient
+        )
+        .is_ok());
+        assert!(matches!(
+            ProverProof::<Vesta, OpeningProof<Vesta>>::from_bytes(
+                &ser_pf,
+                DeserializationMode::Strict
+            ),
+            Err(DeserializationError::NonCanonical)
+        ));
+    }
+
+    #[test]
+    fn test_compressed_proof() {
+        let ctx = BenchmarkCtx::new(4);
+        let (proof, public_input) = ctx.create_proof();
+
+        let bytes = proof.to_compressed_bytes();
+        let de_pf =
+            ProverProof::<Vesta, OpeningProof<Vesta>>::from_compressed_bytes(&bytes).unwrap();
+        assert_eq!(de_pf, proof);
+        ctx.batch_verification(&vec![(de_pf, public_input)]);
+
+        // the components add up to the whole encoding, with its version
+        let report = proof.size_report();
+        assert_eq!(
+            report.commitments
+                + report.evaluations
+                + report.opening_proof
+                + report.prev_challenges
+                + 1,
+            report.compressed
+        );
+        assert_eq!(report.compressed, bytes.len());
+        assert!(report.compressed < report.msgpack);
+
+        // the report lists each component, one per line
+        let lines: Vec<String> = report.to_string().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            [
+                format!("commitments:     {:>6} bytes", report.commitments),
+                format!("evaluations:     {:>6} bytes", report.evaluations),
+                format!(
+                    "opening proof:   {:>6} bytes ({} rounds)",
+                    report.opening_proof, report.ipa_rounds
+                ),
+                format!("prev challenges: {:>6} bytes", report.prev_challenges),
+                format!("compressed:      {:>6} bytes", report.compressed),
+                format!("msgpack:         {:>6} bytes", report.msgpack),
+            ]
+        );
+
+        // truncated or extended encodings are rejected
+        assert!(
+            ProverProof::<Vesta, OpeningProof<Vesta>>::from_compressed_bytes(
+                &bytes[..bytes.len() - 1]
+            )
+            .is_err()
+        );
+        let mut extended = bytes;
+        extended.push(0);
+        assert!(
+            ProverProof::<Vesta, OpeningProof<Vesta>>::from_compressed_bytes(&extended).is_err()
+        );
+    }
+
     #[test]
     pub fn test_serialization() {
         let public = vec![Fp::from(3u8); 5];
-        let gates = create_circuit(0, public.len());
+        let (index, witness) = generic_index::<Vesta>(&public);
 
-        // create witness
-        let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
-        fill_in_witness(0, &mut witness, &public);
-
-        let index = new_index_for_test(gates, public.len());
         let verifier_index = index.verifier_index();
 
         let verifier_index_serialize =
@@ -102,4 +177,90 @@ mod tests {
         .unwrap();
         println!("- time to verify: {}ms", start.elapsed().as_millis());
     }
+
+    #[test]
+    fn test_index_file_serialization() {
+        let public = vec![Fp::from(3u8); 5];
+        let (index, witness) = generic_index::<Vesta>(&public);
+
+        let verifier_index = index.verifier_index();
+
+        // round trip of the prover index
+        let mut prover_bytes = vec![];
+        index.serialize_to(&mut prover_bytes).unwrap();
+        let index_deserialize: ProverIndex<Vesta, OpeningProof<Vesta>> =
+            ProverIndex::deserialize_from(prover_bytes.as_slice(), index.srs.clone()).unwrap();
+
+        // round trip of the verifier index
+        let mut verifier_bytes = vec![];
+        verifier_index.serialize_to(&mut verifier_bytes).unwrap();
+        let verifier_index_deserialize: VerifierIndex<Vesta, OpeningProof<Vesta>> =
+            VerifierIndex::deserialize_from(verifier_bytes.as_slice(), index.srs.clone()).unwrap();
+        let verifier_index_strict: VerifierIndex<Vesta, OpeningProof<Vesta>> =
+            VerifierIndex::deserialize_from_with_mode(
+                verifier_bytes.as_slice(),
+                index.srs.clone(),
+                DeserializationMode::Strict,
+            )
+            .unwrap();
+        assert_eq!(
+            verifier_index.digest::<BaseSponge>(),
+            verifier_index_strict.digest::<BaseSponge>()
+        );
+        assert_eq!(
+            verifier_index.digest::<BaseSponge>(),
+            verifier_index_deserialize.digest::<BaseSponge>()
+        );
+
+        // a prover index cannot be read as a verifier index
+        assert!(matches!(
+            VerifierIndex::<Vesta, OpeningProof<Vesta>>::deserialize_from(
+                prover_bytes.as_slice(),
+                index.srs.clone()
+            ),
+            Err(IndexSerializationError::IncorrectIndexKind(_, _))
+        ));
+
+        // an unknown kind of index is reported as such, the kind being stored
+        // after the magic bytes and the version
+        let mut unknown_kind = verifier_bytes.clone();
+        unknown_kind[INDEX_MAGIC.len() + 4] = 7;
+        assert!(matches!(
+            VerifierIndex::<Vesta, OpeningProof<Vesta>>::deserialize_from(
+                unknown_kind.as_slice(),
+                index.srs.clone()
+            ),
+            Err(IndexSerializationError::UnknownKind(7))
+        ));
+
+        // a corrupted index is rejected
+        let last = verifier_bytes.len() - 1;
+        verifier_bytes[last] ^= 1;
+        assert!(matches!(
+            VerifierIndex::<Vesta, OpeningProof<Vesta>>::deserialize_from(
+                verifier_bytes.as_slice(),
+                index.srs.clone()
+            ),
+            Err(IndexSerializationError::ChecksumMismatch)
+        ));
+
+        // a proof created with the deserialized prover index is accepted by the
+        // deserialized verifier index
+        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness,
+            &[],
+            &index_deserialize,
+            &mut rand::rngs::OsRng,
+        )
+        .unwrap();
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index_deserialize,
+            &proof,
+            &public,
+        )
+        .unwrap();
+    }
 }
@@ -0,0 +1,71 @@
+use super::framework::generic_index;
+use crate::{error::ProverError, proof::ProverProof, verifier::verify};
+use ark_ff::One;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof, shared::SharedBlob};
+use rand::{rngs::StdRng, SeedableRng};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const SEED: [u8; 32] = [11; 32];
+
+#[test]
+fn test_shared_columns() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let rng = &mut StdRng::from_seed(SEED);
+
+    // the blob leaves room for the zero-knowledge rows of the column
+    let blob = SharedBlob::commit(
+        &*index.srs,
+        index.cs.domain.d1,
+        &witness[1],
+        index.cs.zk_rows as usize,
+        rng,
+    );
+
+    let proof = ProverProof::create_with_shared_columns::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.clone(),
+        &[],
+        &index,
+        &[(1, &blob)],
+        rng,
+    )
+    .unwrap();
+    assert!(blob.is_committed_in(&proof.commitments.w_comm[1]));
+
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &index.verifier_index(),
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // a blob that does not match the column is rejected
+    let other = SharedBlob::commit(
+        &*index.srs,
+        index.cs.domain.d1,
+        &[Fp::one()],
+        index.cs.zk_rows as usize,
+        rng,
+    );
+    assert!(matches!(
+        ProverProof::<Vesta, OpeningProof<Vesta>>::create_with_shared_columns::<
+            BaseSponge,
+            ScalarSponge,
+            _,
+        >(&group_map, witness, &[], &index, &[(1, &other)], rng),
+        Err(ProverError::SharedColumnMismatch(1))
+    ));
+}
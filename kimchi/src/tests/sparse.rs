@@ -0,0 +1,97 @@
+use super::framework::TestFramework;
+use crate::circuits::{
+    gate::CircuitGate,
+    lookup::tables::{
+        sparse::{sparse_table, TABLE_SIZE},
+        SPARSE_TABLE_ID,
+    },
+    polynomials::keccak::{
+        sparse::{create_dense_witness, create_sparse_witness, create_sparse_word_witness},
+        Keccak,
+    },
+};
+use ark_ff::Zero;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use rand::Rng;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+// Small tables keep the domain small in end-to-end tests
+const TEST_BITS: usize = 8;
+
+fn prove_and_verify_sparse(dense: &[u64], tamper: bool) {
+    let mut gates = vec![];
+    let _next_row = CircuitGate::<Fp>::extend_sparse_gadget(&mut gates, dense.len());
+
+    let mut witness = create_sparse_witness::<Fp>(dense);
+    if tamper {
+        witness[2][1] += Fp::from(1u64);
+    }
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_tables(vec![sparse_table(TEST_BITS)])
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
+
+#[test]
+fn test_sparse_table() {
+    let table = sparse_table::<Fp>(16);
+    assert_eq!(table.id, SPARSE_TABLE_ID);
+    assert_eq!(table.width(), 2);
+    assert_eq!(table.len(), TABLE_SIZE);
+
+    // (0, 0) is the last entry
+    assert!(table.data[0][TABLE_SIZE - 1].is_zero());
+    assert!(table.data[1][TABLE_SIZE - 1].is_zero());
+
+    assert_eq!(table.data[0][0], Fp::from(0xFFFFu64));
+    assert_eq!(table.data[1][0], Fp::from(0x1111111111111111u64));
+    let row = TABLE_SIZE - 1 - 0b1011;
+    assert_eq!(table.data[1][row], Fp::from(0x1011u64));
+}
+
+#[test]
+fn test_sparse_word_witness() {
+    let word = 0x0123456789ABCDEFu64;
+    let witness = create_sparse_word_witness::<Fp>(word);
+    let quarters = Keccak::decompose(word);
+    let sparse = Keccak::sparse(word);
+
+    // 1 generic row and 2 lookup rows
+    assert_eq!(witness[0].len(), 3);
+    assert!(witness[0]
+        .iter()
+        .all(|id| *id == Fp::from(SPARSE_TABLE_ID as u64)));
+    for i in 0..4 {
+        let (row, col) = (1 + i / 3, 1 + 2 * (i % 3));
+        assert_eq!(witness[col][row], Fp::from(quarters[i]));
+        assert_eq!(witness[col + 1][row], Fp::from(sparse[i]));
+    }
+
+    // converting back to dense gives the same witness
+    assert_eq!(create_dense_witness::<Fp>(&sparse), witness);
+}
+
+#[test]
+// End-to-end test of the sparse conversion
+fn test_prove_and_verify_sparse() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let dense: Vec<u64> = (0..10).map(|_| rng.gen_range(0..1 << TEST_BITS)).collect();
+    prove_and_verify_sparse(&dense, false);
+}
+
+#[test]
+#[should_panic]
+fn test_bad_sparse_conversion() {
+    prove_and_verify_sparse(&[0b1010, 0b11, 0b1], true);
+}
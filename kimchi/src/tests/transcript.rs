@@ -0,0 +1,87 @@
+use super::framework::generic_index;
+use crate::{
+    curve::KimchiCurve,
+    plonk_sponge::FrSponge,
+    proof::ProverProof,
+    transcript::{self, FqTranscript, FrTranscript, TranscriptOp, TranscriptSponge},
+    verifier::verify,
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Fq, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+    FqSponge,
+};
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+#[test]
+fn test_symmetric_transcripts() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let (proof, prover_log) = transcript::record(|| {
+        ProverProof::create::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            witness,
+            &[],
+            &index,
+            &mut o1_utils::tests::make_test_rng(None),
+        )
+        .unwrap()
+    });
+    let (res, verifier_log) = transcript::record(|| {
+        verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+            &group_map,
+            &verifier_index,
+            &proof,
+            &public,
+        )
+    });
+    res.unwrap();
+
+    assert_eq!(prover_log, verifier_log);
+
+    // the Fq-transcript ends with its digest, the Fr-transcript with u
+    let last_squeeze = |sponge| {
+        prover_log
+            .iter()
+            .rev()
+            .find(|e| e.sponge == sponge && e.op == TranscriptOp::Squeeze)
+            .map(|e| e.label)
+    };
+    assert_eq!(last_squeeze(TranscriptSponge::Fq), Some("fq_digest"));
+    assert_eq!(last_squeeze(TranscriptSponge::Fr), Some("u"));
+}
+
+#[test]
+fn test_labels_do_not_change_the_challenges() {
+    // the challenges are the ones of the sponges alone, computed before the
+    // labels were introduced, so that the proofs stay compatible with the
+    // other verifiers of kimchi proofs
+    let mut fq_transcript =
+        FqTranscript::<Vesta, _>::new(BaseSponge::new(Vesta::other_curve_sponge_params()));
+    fq_transcript.absorb_fq("public_comm", &[Fq::from(1u8), Fq::from(2u8)]);
+    assert_eq!(
+        fq_transcript.challenge("beta"),
+        Fp::from(0x7aff96e2f7f69e298d0a3547852f745c_u128)
+    );
+    assert_eq!(
+        fq_transcript.challenge("gamma"),
+        Fp::from(0x305a89bbbf897c339fb44a4218363a65_u128)
+    );
+
+    let mut fr_transcript = FrTranscript::<Fp, _>::new(ScalarSponge::new(Vesta::sponge_params()));
+    fr_transcript.absorb("ft_eval1", &Fp::from(1u8));
+    assert_eq!(
+        fr_transcript.challenge("v").0,
+        Fp::from(0x079e44e99ec5b306ddddd88e15fe9e6d_u128)
+    );
+}
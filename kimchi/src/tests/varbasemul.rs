@@ -96,3 +96,48 @@ fn varbase_mul_test() {
         .prove_and_verify::<BaseSponge, ScalarSponge>()
         .unwrap();
 }
+
+#[test]
+fn varbase_mul_short_scalar_test() {
+    // a 128-bit challenge is padded to 130 bits, that is 26 chunks
+    let num_bits = 128;
+    let gates = CircuitGate::<F>::create_vbmul_bits(0, num_bits);
+    assert_eq!(varbasemul::num_bits_padded(num_bits), 130);
+    assert_eq!(gates.len(), 2 * 26);
+
+    let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); gates.len()]);
+
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+
+    let x = F::rand(rng);
+    let bits_lsb: Vec<_> = BitIteratorLE::new(x.into_bigint()).take(num_bits).collect();
+    let x_ = <Other as AffineRepr>::ScalarField::from_bigint(
+        <F as PrimeField>::BigInt::from_bits_le(&bits_lsb[..]),
+    )
+    .unwrap();
+
+    let base = Other::generator();
+    let g = Other::generator().into_group();
+    let acc = (g + g).into_affine();
+    let acc = (acc.x, acc.y);
+
+    let bits_msb: Vec<_> = bits_lsb.iter().copied().rev().collect();
+
+    let res = varbasemul::witness_bits(&mut witness, 0, (base.x, base.y), &bits_msb, acc);
+
+    let shift = <Other as AffineRepr>::ScalarField::from(2)
+        .pow([varbasemul::num_bits_padded(num_bits) as u64]);
+    let expected = g
+        .mul(&(<Other as AffineRepr>::ScalarField::one() + shift + x_.double()))
+        .into_affine();
+
+    assert_eq!(x_.into_bigint(), res.n.into_bigint());
+    assert_eq!((expected.x, expected.y), res.acc);
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
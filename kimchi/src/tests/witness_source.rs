@@ -0,0 +1,87 @@
+use super::framework::generic_index;
+use crate::{
+    error::ProverError,
+    proof::ProverProof,
+    verifier::verify,
+    witness_source::{write_rows, RowGenerator, RowReader},
+};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof};
+use rand::{rngs::StdRng, SeedableRng};
+use std::array;
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+const SEED: [u8; 32] = [7; 32];
+
+#[test]
+fn test_witness_sources() {
+    let public = vec![Fp::from(3u8); 5];
+    let (index, witness) = generic_index::<Vesta>(&public);
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+
+    let expected = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.clone(),
+        &[],
+        &index,
+        &mut StdRng::from_seed(SEED),
+    )
+    .unwrap();
+
+    // rows computed by a closure
+    let generator = RowGenerator::new(witness[0].len(), |row| {
+        array::from_fn(|col| witness[col][row])
+    });
+    let proof = ProverProof::create_from_rows::<BaseSponge, ScalarSponge, _, _>(
+        &group_map,
+        generator,
+        &[],
+        &index,
+        &mut StdRng::from_seed(SEED),
+    )
+    .unwrap();
+    assert_eq!(proof, expected);
+
+    // rows read from a file
+    let mut bytes = vec![];
+    write_rows(&witness, &mut bytes).unwrap();
+    let reader = RowReader::new(witness[0].len(), bytes.as_slice());
+    let proof = ProverProof::create_from_rows::<BaseSponge, ScalarSponge, _, _>(
+        &group_map,
+        reader,
+        &[],
+        &index,
+        &mut StdRng::from_seed(SEED),
+    )
+    .unwrap();
+    assert_eq!(proof, expected);
+
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &index.verifier_index(),
+        &proof,
+        &public,
+    )
+    .unwrap();
+
+    // a truncated file is rejected
+    let reader = RowReader::new(witness[0].len(), &bytes[..bytes.len() - 1]);
+    let res: Result<ProverProof<Vesta, OpeningProof<Vesta>>, _> =
+        ProverProof::create_from_rows::<BaseSponge, ScalarSponge, _, _>(
+            &group_map,
+            reader,
+            &[],
+            &index,
+            &mut StdRng::from_seed(SEED),
+        );
+    assert!(matches!(res, Err(ProverError::WitnessSource)));
+}
@@ -0,0 +1,313 @@
+//! This module implements the Fiat-Shamir transcripts of the prover and of
+//! the verifier. [`FqTranscript`] and [`FrTranscript`] wrap the Fq-sponge and
+//! the Fr-sponge, and name each absorption and squeeze with a label, e.g.
+//! `fq_transcript.absorb_commitment("witness_comm", comm)`.
+//!
+//! The labels are not absorbed, so that the challenges are the ones of the
+//! sponges alone. They are recorded, with the number of absorbed elements,
+//! when the transcripts are used inside [`record`], so that the transcripts of
+//! the prover and of the verifier of a proof can be compared:
+//!
+//! ```ignore
+//! let (proof, prover_log) = transcript::record(|| ProverProof::create(...));
+//! let (res, verifier_log) = transcript::record(|| verify(...));
+//! assert_eq!(prover_log, verifier_log);
+//! ```
+//!
+//! With the `conformance` feature, the values absorbed and squeezed are also
+//! recorded in test vectors, see [`crate::conformance`].
+
+use crate::{
+    plonk_sponge::{evaluations_to_absorb, FrSponge},
+    proof::{PointEvaluations, ProofEvaluations},
+};
+use ark_ec::AffineRepr;
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use mina_poseidon::{sponge::ScalarChallenge, FqSponge};
+use poly_commitment::commitment::{absorb_commitment, PolyComm};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, marker::PhantomData};
+
+/// The sponge of a transcript
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptSponge {
+    /// The sponge over the base field, see [`FqTranscript`]
+    Fq,
+    /// The sponge over the scalar field, see [`FrTranscript`]
+    Fr,
+}
+
+/// An operation on a transcript
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptOp {
+    /// Absorption of a number of field elements or points
+    Absorb(usize),
+    /// Squeeze of a challenge or a digest
+    Squeeze,
+}
+
+/// An entry of the log of the transcripts (see [`record`])
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub sponge: TranscriptSponge,
+    pub label: &'static str,
+    pub op: TranscriptOp,
+}
+
+thread_local! {
+    /// The log of the transcripts used on this thread, if recorded
+    static LOG: RefCell<Option<Vec<TranscriptEntry>>> = RefCell::new(None);
+}
+
+/// Runs `f`, and returns its result with the log of the transcripts used by
+/// `f` on this thread.
+pub fn record<R>(f: impl FnOnce() -> R) -> (R, Vec<TranscriptEntry>) {
+    let previous = LOG.with(|log| log.replace(Some(vec![])));
+    let res = f();
+    let entries = LOG.with(|log| log.replace(previous)).unwrap_or_default();
+    (res, entries)
+}
+
+fn log(sponge: TranscriptSponge, label: &'static str, op: TranscriptOp) {
+    LOG.with(|log| {
+        if let Some(entries) = log.borrow_mut().as_mut() {
+            entries.push(TranscriptEntry { sponge, label, op });
+        }
+    });
+}
+
+/// Logs the values of an operation in the test vector being recorded, if any
+#[cfg(feature = "conformance")]
+fn log_values<T: CanonicalSerialize>(
+    sponge: TranscriptSponge,
+    label: &'static str,
+    op: TranscriptOp,
+    values: &[T],
+) {
+    crate::conformance::log(sponge, label, op, values);
+}
+
+#[cfg(not(feature = "conformance"))]
+fn log_values<T: CanonicalSerialize>(
+    _sponge: TranscriptSponge,
+    _label: &'static str,
+    _op: TranscriptOp,
+    _values: &[T],
+) {
+}
+
+/// The transcript of the Fq-sponge, absorbing the commitments and squeezing
+/// the challenges of the scalar field
+#[derive(Clone)]
+pub struct FqTranscript<G, EFqSponge> {
+    sponge: EFqSponge,
+    _curve: PhantomData<G>,
+}
+
+impl<G, EFqSponge> FqTranscript<G, EFqSponge>
+where
+    G: AffineRepr,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    /// Creates a transcript over `sponge`
+    pub fn new(sponge: EFqSponge) -> Self {
+        FqTranscript {
+            sponge,
+            _curve: PhantomData,
+        }
+    }
+
+    /// Returns the sponge of the transcript
+    pub fn sponge(&self) -> &EFqSponge {
+        &self.sponge
+    }
+
+    /// Returns the sponge of the transcript, to be used without labels
+    pub fn into_sponge(self) -> EFqSponge {
+        self.sponge
+    }
+
+    /// Absorbs the base field elements `x`
+    pub fn absorb_fq(&mut self, label: &'static str, x: &[G::BaseField]) {
+        log(TranscriptSponge::Fq, label, TranscriptOp::Absorb(x.len()));
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Absorb(x.len()),
+            x,
+        );
+        self.sponge.absorb_fq(x);
+    }
+
+    /// Absorbs the points `g`
+    pub fn absorb_g(&mut self, label: &'static str, g: &[G]) {
+        log(TranscriptSponge::Fq, label, TranscriptOp::Absorb(g.len()));
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Absorb(g.len()),
+            g,
+        );
+        self.sponge.absorb_g(g);
+    }
+
+    /// Absorbs the scalar field elements `x`
+    pub fn absorb_fr(&mut self, label: &'static str, x: &[G::ScalarField]) {
+        log(TranscriptSponge::Fq, label, TranscriptOp::Absorb(x.len()));
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Absorb(x.len()),
+            x,
+        );
+        self.sponge.absorb_fr(x);
+    }
+
+    /// Absorbs the chunks of the commitment `comm`
+    pub fn absorb_commitment(&mut self, label: &'static str, comm: &PolyComm<G>) {
+        log(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Absorb(comm.len()),
+        );
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Absorb(comm.len()),
+            &comm.chunks,
+        );
+        absorb_commitment(&mut self.sponge, comm);
+    }
+
+    /// Squeezes a challenge of the scalar field
+    pub fn challenge(&mut self, label: &'static str) -> G::ScalarField {
+        log(TranscriptSponge::Fq, label, TranscriptOp::Squeeze);
+        let challenge = self.sponge.challenge();
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Squeeze,
+            &[challenge],
+        );
+        challenge
+    }
+
+    /// Squeezes a challenge of the base field
+    pub fn challenge_fq(&mut self, label: &'static str) -> G::BaseField {
+        log(TranscriptSponge::Fq, label, TranscriptOp::Squeeze);
+        let challenge = self.sponge.challenge_fq();
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Squeeze,
+            &[challenge],
+        );
+        challenge
+    }
+
+    /// Consumes the transcript and returns its digest in the scalar field
+    pub fn digest(self, label: &'static str) -> G::ScalarField {
+        log(TranscriptSponge::Fq, label, TranscriptOp::Squeeze);
+        let digest = self.sponge.digest();
+        log_values(
+            TranscriptSponge::Fq,
+            label,
+            TranscriptOp::Squeeze,
+            &[digest],
+        );
+        digest
+    }
+}
+
+/// The transcript of the Fr-sponge, absorbing the evaluations and squeezing
+/// the challenges of the opening proof
+pub struct FrTranscript<F, EFrSponge> {
+    sponge: EFrSponge,
+    _field: PhantomData<F>,
+}
+
+impl<F, EFrSponge> FrTranscript<F, EFrSponge>
+where
+    F: Field,
+    EFrSponge: FrSponge<F>,
+{
+    /// Creates a transcript over `sponge`
+    pub fn new(sponge: EFrSponge) -> Self {
+        FrTranscript {
+            sponge,
+            _field: PhantomData,
+        }
+    }
+
+    /// Absorbs the field element `x`
+    pub fn absorb(&mut self, label: &'static str, x: &F) {
+        log(TranscriptSponge::Fr, label, TranscriptOp::Absorb(1));
+        log_values(TranscriptSponge::Fr, label, TranscriptOp::Absorb(1), &[*x]);
+        self.sponge.absorb(x);
+    }
+
+    /// Absorbs the field elements `x`
+    pub fn absorb_multiple(&mut self, label: &'static str, x: &[F]) {
+        log(TranscriptSponge::Fr, label, TranscriptOp::Absorb(x.len()));
+        log_values(
+            TranscriptSponge::Fr,
+            label,
+            TranscriptOp::Absorb(x.len()),
+            x,
+        );
+        self.sponge.absorb_multiple(x);
+    }
+
+    /// Absorbs the evaluations of the polynomials of a proof
+    pub fn absorb_evaluations(
+        &mut self,
+        label: &'static str,
+        e: &ProofEvaluations<PointEvaluations<Vec<F>>>,
+    ) {
+        let values: Vec<F> = evaluations_to_absorb(e)
+            .into_iter()
+            .flat_map(|p| p.zeta.iter().chain(p.zeta_omega.iter()).copied())
+            .collect();
+        log(
+            TranscriptSponge::Fr,
+            label,
+            TranscriptOp::Absorb(values.len()),
+        );
+        log_values(
+            TranscriptSponge::Fr,
+            label,
+            TranscriptOp::Absorb(values.len()),
+            &values,
+        );
+        self.sponge.absorb_evaluations(e);
+    }
+
+    /// Squeezes a challenge
+    pub fn challenge(&mut self, label: &'static str) -> ScalarChallenge<F> {
+        log(TranscriptSponge::Fr, label, TranscriptOp::Squeeze);
+        let challenge = self.sponge.challenge();
+        log_values(
+            TranscriptSponge::Fr,
+            label,
+            TranscriptOp::Squeeze,
+            &[challenge.0],
+        );
+        challenge
+    }
+
+    /// Consumes the transcript and returns its digest
+    pub fn digest(self, label: &'static str) -> F {
+        log(TranscriptSponge::Fr, label, TranscriptOp::Squeeze);
+        let digest = self.sponge.digest();
+        log_values(
+            TranscriptSponge::Fr,
+            label,
+            TranscriptOp::Squeeze,
+            &[digest],
+        );
+        digest
+    }
+}
@@ -2,35 +2,33 @@
 
 use crate::{
     circuits::{
-        argument::ArgumentType,
-        berkeley_columns::{BerkeleyChallenges, Column},
-        constraints::ConstraintSystem,
-        expr::{Constants, PolishToken},
+        berkeley_columns::Column,
         gate::GateType,
         lookup::{lookups::LookupPattern, tables::combine_table},
-        polynomials::permutation,
         scalars::RandomOracles,
         wires::{COLUMNS, PERMUTS},
     },
     curve::KimchiCurve,
     error::VerifyError,
+    linearization,
     oracles::OraclesResult,
     plonk_sponge::FrSponge,
-    proof::{PointEvaluations, ProofEvaluations, ProverProof, RecursionChallenge},
+    proof::{PointEvaluations, ProverProof, RecursionChallenge},
+    transcript::{FqTranscript, FrTranscript},
     verifier_index::VerifierIndex,
 };
 use ark_ec::AffineRepr;
 use ark_ff::{Field, One, PrimeField, Zero};
-use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Polynomial};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
 use mina_poseidon::{sponge::ScalarChallenge, FqSponge};
 use o1_utils::ExtendedDensePolynomial;
 use poly_commitment::{
-    commitment::{
-        absorb_commitment, combined_inner_product, BatchEvaluationProof, Evaluation, PolyComm,
-    },
+    commitment::{combined_inner_product, BatchEvaluationProof, Evaluation, PolyComm},
     OpenProof, SRS as _,
 };
 use rand::thread_rng;
+use serde::Serialize;
+use std::time::{Duration, Instant};
 
 /// The result of a proof verification.
 pub type Result<T> = std::result::Result<T, VerifyError>;
@@ -47,6 +45,26 @@ pub struct Context<'a, G: KimchiCurve, OpeningProof: OpenProof<G>> {
     pub public_input: &'a [G::ScalarField],
 }
 
+/// A proof to verify in a batch, given with its [VerifierIndex] and its public
+/// input.
+pub type BatchItem<'a, G, OpeningProof> = (
+    &'a VerifierIndex<G, OpeningProof>,
+    &'a ProverProof<G, OpeningProof>,
+    &'a [<G as AffineRepr>::ScalarField],
+);
+
+impl<'a, G: KimchiCurve, OpeningProof: OpenProof<G>> From<BatchItem<'a, G, OpeningProof>>
+    for Context<'a, G, OpeningProof>
+{
+    fn from((verifier_index, proof, public_input): BatchItem<'a, G, OpeningProof>) -> Self {
+        Context {
+            verifier_index,
+            proof,
+            public_input,
+        }
+    }
+}
+
 impl<'a, G: KimchiCurve, OpeningProof: OpenProof<G>> Context<'a, G, OpeningProof> {
     pub fn get_column(&self, col: Column) -> Option<&'a PolyComm<G>> {
         use Column::*;
@@ -81,12 +99,8 @@ impl<'a, G: KimchiCurve, OpeningProof: OpenProof<G>> Context<'a, G, OpeningProof
                     EndoMulScalar => Some(&self.verifier_index.endomul_scalar_comm),
                     Poseidon => Some(&self.verifier_index.psm_comm),
                     CairoClaim | CairoInstruction | CairoFlags | CairoTransition => None,
-                    RangeCheck0 => Some(self.verifier_index.range_check0_comm.as_ref()?),
-                    RangeCheck1 => Some(self.verifier_index.range_check1_comm.as_ref()?),
-                    ForeignFieldAdd => Some(self.verifier_index.foreign_field_add_comm.as_ref()?),
-                    ForeignFieldMul => Some(self.verifier_index.foreign_field_mul_comm.as_ref()?),
-                    Xor16 => Some(self.verifier_index.xor_comm.as_ref()?),
-                    Rot64 => Some(self.verifier_index.rot_comm.as_ref()?),
+                    RangeCheck0 | RangeCheck1 | ForeignFieldAdd | ForeignFieldMul | Xor16
+                    | Rot64 => self.verifier_index.optional_selector_comm.get(&t),
                     KeccakRound => todo!(),
                     KeccakSponge => todo!(),
                 }
@@ -134,30 +148,29 @@ where
             }
         };
 
-        let zk_rows = index.zk_rows;
-
         //~ 1. Setup the Fq-Sponge. This sponge mostly absorbs group
         // elements (points as tuples over the base field), but it
         // squeezes out elements of the group's scalar field.
-        let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
+        let mut fq_transcript =
+            FqTranscript::<G, _>::new(EFqSponge::new(G::other_curve_sponge_params()));
 
         //~ 1. Absorb the digest of the VerifierIndex.
         let verifier_index_digest = index.digest::<EFqSponge>();
-        fq_sponge.absorb_fq(&[verifier_index_digest]);
+        fq_transcript.absorb_fq("verifier_index_digest", &[verifier_index_digest]);
 
         //~ 1. Absorb the commitments of the previous challenges with the Fq-sponge.
         for RecursionChallenge { comm, .. } in &self.prev_challenges {
-            absorb_commitment(&mut fq_sponge, comm);
+            fq_transcript.absorb_commitment("prev_challenge_comm", comm);
         }
 
         //~ 1. Absorb the commitment of the public input polynomial with the Fq-Sponge.
-        absorb_commitment(&mut fq_sponge, public_comm);
+        fq_transcript.absorb_commitment("public_comm", public_comm);
 
         //~ 1. Absorb the commitments to the registers / witness columns with the Fq-Sponge.
         self.commitments
             .w_comm
             .iter()
-            .for_each(|c| absorb_commitment(&mut fq_sponge, c));
+            .for_each(|c| fq_transcript.absorb_commitment("witness_comm", c));
 
         //~ 1. If lookup is used:
         if let Some(l) = &index.lookup_index {
@@ -173,7 +186,7 @@ where
                     .runtime
                     .as_ref()
                     .ok_or(VerifyError::IncorrectRuntimeProof)?;
-                absorb_commitment(&mut fq_sponge, runtime_commit);
+                fq_transcript.absorb_commitment("runtime_table_comm", runtime_commit);
             }
         }
 
@@ -182,7 +195,7 @@ where
             //~~   then squeeze the Fq-Sponge to obtain the joint combiner challenge $j'$,
             //~~   otherwise set the joint combiner challenge $j'$ to $0$.
             let joint_combiner = if l.joint_lookup_used {
-                fq_sponge.challenge()
+                fq_transcript.challenge("joint_combiner")
             } else {
                 G::ScalarField::zero()
             };
@@ -207,16 +220,16 @@ where
 
             //~~ * absorb the commitments to the sorted polynomials.
             for com in &lookup_commits.sorted {
-                absorb_commitment(&mut fq_sponge, com);
+                fq_transcript.absorb_commitment("lookup_sorted_comm", com);
             }
         }
 
         // --- PlonK - Round 2
         //~ 1. Sample the first permutation challenge $\beta$ with the Fq-Sponge.
-        let beta = fq_sponge.challenge();
+        let beta = fq_transcript.challenge("beta");
 
         //~ 1. Sample the second permutation challenge $\gamma$ with the Fq-Sponge.
-        let gamma = fq_sponge.challenge();
+        let gamma = fq_transcript.challenge("gamma");
 
         //~ 1. If using lookup, absorb the commitment to the aggregation lookup polynomial.
         if index.lookup_index.is_some() {
@@ -226,15 +239,15 @@ where
                 .lookup
                 .as_ref()
                 .ok_or(VerifyError::LookupCommitmentMissing)?;
-            absorb_commitment(&mut fq_sponge, &lookup_commits.aggreg);
+            fq_transcript.absorb_commitment("lookup_aggreg_comm", &lookup_commits.aggreg);
         }
 
         //~ 1. Absorb the commitment to the permutation trace with the Fq-Sponge.
-        absorb_commitment(&mut fq_sponge, &self.commitments.z_comm);
+        fq_transcript.absorb_commitment("z_comm", &self.commitments.z_comm);
 
         // --- PlonK - Round 3
         //~ 1. Sample the quotient challenge $\alpha'$ with the Fq-Sponge.
-        let alpha_chal = ScalarChallenge(fq_sponge.challenge());
+        let alpha_chal = ScalarChallenge(fq_transcript.challenge("alpha"));
 
         //~ 1. Derive $\alpha$ from $\alpha'$ using the endomorphism (TODO: details).
         let alpha = alpha_chal.to_field(endo_r);
@@ -249,11 +262,11 @@ where
         }
 
         //~ 1. Absorb the commitment to the quotient polynomial $t$ into the argument.
-        absorb_commitment(&mut fq_sponge, &self.commitments.t_comm);
+        fq_transcript.absorb_commitment("t_comm", &self.commitments.t_comm);
 
         // --- PlonK - Round 4
         //~ 1. Sample $\zeta'$ with the Fq-Sponge.
-        let zeta_chal = ScalarChallenge(fq_sponge.challenge());
+        let zeta_chal = ScalarChallenge(fq_transcript.challenge("zeta"));
 
         //~ 1. Derive $\zeta$ from $\zeta'$ using the endomorphism (TODO: specify).
         let zeta = zeta_chal.to_field(endo_r);
@@ -263,23 +276,25 @@ where
         // the previous recursion round), and squeezes scalar elements
         // of the field. The squeeze result is the same as with the
         // `fq_sponge`.
-        let digest = fq_sponge.clone().digest();
-        let mut fr_sponge = EFrSponge::new(G::sponge_params());
+        let digest = fq_transcript.clone().digest("fq_digest");
+        let mut fr_transcript =
+            FrTranscript::<G::ScalarField, _>::new(EFrSponge::new(G::sponge_params()));
 
         //~ 1. Squeeze the Fq-sponge and absorb the result with the Fr-Sponge.
-        fr_sponge.absorb(&digest);
+        fr_transcript.absorb("fq_digest", &digest);
 
         //~ 1. Absorb the previous recursion challenges.
         let prev_challenge_digest = {
             // Note: we absorb in a new sponge here to limit the scope in which we need the
             // more-expensive 'optional sponge'.
-            let mut fr_sponge = EFrSponge::new(G::sponge_params());
+            let mut fr_transcript =
+                FrTranscript::<G::ScalarField, _>::new(EFrSponge::new(G::sponge_params()));
             for RecursionChallenge { chals, .. } in &self.prev_challenges {
-                fr_sponge.absorb_multiple(chals);
+                fr_transcript.absorb_multiple("prev_challenges", chals);
             }
-            fr_sponge.digest()
+            fr_transcript.digest("prev_challenge_digest")
         };
-        fr_sponge.absorb(&prev_challenge_digest);
+        fr_transcript.absorb("prev_challenge_digest", &prev_challenge_digest);
 
         // prepare some often used values
         let zeta1 = zeta.pow([n]);
@@ -294,18 +309,16 @@ where
         let polys: Vec<(PolyComm<G>, _)> = self
             .prev_challenges
             .iter()
-            .map(|challenge| {
-                let evals = challenge.evals(
-                    index.max_poly_size,
-                    &evaluation_points,
-                    &[
-                        powers_of_eval_points_for_chunks.zeta,
-                        powers_of_eval_points_for_chunks.zeta_omega,
-                    ],
-                );
-                let RecursionChallenge { chals: _, comm } = challenge;
-                (comm.clone(), evals)
-            })
+            .map(|challenge| challenge.comm.clone())
+            .zip(RecursionChallenge::evals_batch(
+                &self.prev_challenges,
+                index.max_poly_size,
+                &evaluation_points,
+                &[
+                    powers_of_eval_points_for_chunks.zeta,
+                    powers_of_eval_points_for_chunks.zeta_omega,
+                ],
+            ))
             .collect();
 
         // retrieve ranges for the powers of alphas
@@ -362,7 +375,7 @@ where
         };
 
         //~ 1. Absorb the unique evaluation of ft: $ft(\zeta\omega)$.
-        fr_sponge.absorb(&self.ft_eval1);
+        fr_transcript.absorb("ft_eval1", &self.ft_eval1);
 
         //~ 1. Absorb all the polynomial evaluations in $\zeta$ and $\zeta\omega$:
         //~~ * the public polynomial
@@ -371,18 +384,18 @@ where
         //~~ * poseidon selector
         //~~ * the 15 register/witness
         //~~ * 6 sigmas evaluations (the last one is not evaluated)
-        fr_sponge.absorb_multiple(&public_evals[0]);
-        fr_sponge.absorb_multiple(&public_evals[1]);
-        fr_sponge.absorb_evaluations(&self.evals);
+        fr_transcript.absorb_multiple("public_evals", &public_evals[0]);
+        fr_transcript.absorb_multiple("public_evals", &public_evals[1]);
+        fr_transcript.absorb_evaluations("evaluations", &self.evals);
 
         //~ 1. Sample the "polyscale" $v'$ with the Fr-Sponge.
-        let v_chal = fr_sponge.challenge();
+        let v_chal = fr_transcript.challenge("v");
 
         //~ 1. Derive $v$ from $v'$ using the endomorphism (TODO: specify).
         let v = v_chal.to_field(endo_r);
 
         //~ 1. Sample the "evalscale" $u'$ with the Fr-Sponge.
-        let u_chal = fr_sponge.challenge();
+        let u_chal = fr_transcript.challenge("u");
 
         //~ 1. Derive $u$ from $u'$ using the endomorphism (TODO: specify).
         let u = u_chal.to_field(endo_r);
@@ -391,87 +404,27 @@ where
 
         let evals = self.evals.combine(&powers_of_eval_points_for_chunks);
 
-        //~ 1. Compute the evaluation of $ft(\zeta)$.
-        let ft_eval0 = {
-            let permutation_vanishing_polynomial =
-                index.permutation_vanishing_polynomial_m().evaluate(&zeta);
-            let zeta1m1 = zeta1 - G::ScalarField::one();
-
-            let mut alpha_powers =
-                all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
-            let alpha0 = alpha_powers
-                .next()
-                .expect("missing power of alpha for permutation");
-            let alpha1 = alpha_powers
-                .next()
-                .expect("missing power of alpha for permutation");
-            let alpha2 = alpha_powers
-                .next()
-                .expect("missing power of alpha for permutation");
-
-            let init = (evals.w[PERMUTS - 1].zeta + gamma)
-                * evals.z.zeta_omega
-                * alpha0
-                * permutation_vanishing_polynomial;
-            let mut ft_eval0 = evals
-                .w
-                .iter()
-                .zip(evals.s.iter())
-                .map(|(w, s)| (beta * s.zeta) + w.zeta + gamma)
-                .fold(init, |x, y| x * y);
-
-            ft_eval0 -= DensePolynomial::eval_polynomial(
-                &public_evals[0],
-                powers_of_eval_points_for_chunks.zeta,
-            );
-
-            ft_eval0 -= evals
-                .w
-                .iter()
-                .zip(index.shift.iter())
-                .map(|(w, s)| gamma + (beta * zeta * s) + w.zeta)
-                .fold(
-                    alpha0 * permutation_vanishing_polynomial * evals.z.zeta,
-                    |x, y| x * y,
-                );
-
-            let numerator = ((zeta1m1 * alpha1 * (zeta - index.w()))
-                + (zeta1m1 * alpha2 * (zeta - G::ScalarField::one())))
-                * (G::ScalarField::one() - evals.z.zeta);
-
-            let denominator = (zeta - index.w()) * (zeta - G::ScalarField::one());
-            let denominator = denominator.inverse().expect("negligible probability");
-
-            ft_eval0 += numerator * denominator;
-
-            let constants = Constants {
-                endo_coefficient: index.endo,
-                mds: &G::sponge_params().mds,
-                zk_rows,
-            };
-            let challenges = BerkeleyChallenges {
-                alpha,
-                beta,
-                gamma,
-                joint_combiner: joint_combiner
-                    .as_ref()
-                    .map(|j| j.1)
-                    .unwrap_or(G::ScalarField::zero()),
-            };
-
-            ft_eval0 -= PolishToken::evaluate(
-                &index.linearization.constant_term,
-                index.domain,
-                zeta,
-                &evals,
-                &constants,
-                &challenges,
-            )
-            .unwrap();
-
-            ft_eval0
+        let oracles = RandomOracles {
+            joint_combiner,
+            beta,
+            gamma,
+            alpha_chal,
+            alpha,
+            zeta,
+            v,
+            u,
+            zeta_chal,
+            v_chal,
+            u_chal,
         };
 
+        //~ 1. Compute the evaluation of $ft(\zeta)$.
+        let public_eval = DensePolynomial::eval_polynomial(
+            &public_evals[0],
+            powers_of_eval_points_for_chunks.zeta,
+        );
+        let ft_eval0 = linearization::ft_eval0(index, &evals, public_eval, &oracles);
+
         let combined_inner_product =
             {
                 let ft_eval0 = vec![ft_eval0];
@@ -497,39 +450,9 @@ where
                 .chain((0..PERMUTS - 1).map(Column::Permutation))
                 .chain(
                     index
-                        .range_check0_comm
-                        .as_ref()
-                        .map(|_| Column::Index(GateType::RangeCheck0)),
-                )
-                .chain(
-                    index
-                        .range_check1_comm
-                        .as_ref()
-                        .map(|_| Column::Index(GateType::RangeCheck1)),
-                )
-                .chain(
-                    index
-                        .foreign_field_add_comm
-                        .as_ref()
-                        .map(|_| Column::Index(GateType::ForeignFieldAdd)),
-                )
-                .chain(
-                    index
-                        .foreign_field_mul_comm
-                        .as_ref()
-                        .map(|_| Column::Index(GateType::ForeignFieldMul)),
-                )
-                .chain(
-                    index
-                        .xor_comm
-                        .as_ref()
-                        .map(|_| Column::Index(GateType::Xor16)),
-                )
-                .chain(
-                    index
-                        .rot_comm
-                        .as_ref()
-                        .map(|_| Column::Index(GateType::Rot64)),
+                        .optional_selector_comm
+                        .keys()
+                        .map(|gate_type| Column::Index(*gate_type)),
                 )
                 .chain(
                     index
@@ -588,22 +511,8 @@ where
                 combined_inner_product(&v, &u, &es)
             };
 
-        let oracles = RandomOracles {
-            joint_combiner,
-            beta,
-            gamma,
-            alpha_chal,
-            alpha,
-            zeta,
-            v,
-            u,
-            zeta_chal,
-            v_chal,
-            u_chal,
-        };
-
         Ok(OraclesResult {
-            fq_sponge,
+            fq_sponge: fq_transcript.into_sponge(),
             digest,
             oracles,
             all_alphas,
@@ -615,6 +524,42 @@ where
             combined_inner_product,
         })
     }
+
+    /// Re-derives the challenges of the proof for `public_input`, i.e. runs
+    /// the [Fiat-Shamir argument](ProverProof::oracles) after the checks of
+    /// the shape of the proof, but without the expensive checks of the
+    /// verifier: neither the commitment to the linearized polynomial nor the
+    /// opening proof are computed or verified. The challenges are in
+    /// [`OraclesResult::oracles`].
+    ///
+    /// A proof whose challenges are returned is not known to be valid. This is
+    /// meant for recursion circuits and external tools building their own
+    /// constraints on the challenges of a proof.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the proof does not have the shape expected by
+    /// `index`, or if `public_input` does not have the expected length.
+    pub fn challenges<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        &self,
+        index: &VerifierIndex<G, OpeningProof>,
+        public_input: &[G::ScalarField],
+    ) -> Result<OraclesResult<G, EFqSponge>> {
+        if self.prev_challenges.len() != index.prev_challenges {
+            return Err(VerifyError::IncorrectPrevChallengesLength(
+                index.prev_challenges,
+                self.prev_challenges.len(),
+            ));
+        }
+        let chunk_size = std::cmp::max(1, index.domain.size() / index.max_poly_size);
+        check_proof_evals_len(self, chunk_size)?;
+
+        let public_comm = index.public_comm(public_input)?;
+        self.oracles::<EFqSponge, EFrSponge>(index, &public_comm, Some(public_input))
+    }
 }
 
 /// Enforce the length of evaluations inside [`Proof`].
@@ -628,139 +573,35 @@ where
     G: KimchiCurve,
     G::BaseField: PrimeField,
 {
-    let ProofEvaluations {
-        public,
-        w,
-        z,
-        s,
-        coefficients,
-        generic_selector,
-        poseidon_selector,
-        complete_add_selector,
-        mul_selector,
-        emul_selector,
-        endomul_scalar_selector,
-        range_check0_selector,
-        range_check1_selector,
-        foreign_field_add_selector,
-        foreign_field_mul_selector,
-        xor_selector,
-        rot_selector,
-        lookup_aggregation,
-        lookup_table,
-        lookup_sorted,
-        runtime_lookup_table,
-        runtime_lookup_table_selector,
-        xor_lookup_selector,
-        lookup_gate_lookup_selector,
-        range_check_lookup_selector,
-        foreign_field_mul_lookup_selector,
-    } = &proof.evals;
-
-    let check_eval_len = |eval: &PointEvaluations<Vec<_>>, str: &'static str| -> Result<()> {
+    let check_eval_len = |eval: &PointEvaluations<Vec<_>>, col: Option<Column>| -> Result<()> {
         if eval.zeta.len() != expected_size {
             Err(VerifyError::IncorrectEvaluationsLength(
                 expected_size,
                 eval.zeta.len(),
-                str,
+                col,
             ))
         } else if eval.zeta_omega.len() != expected_size {
             Err(VerifyError::IncorrectEvaluationsLength(
                 expected_size,
                 eval.zeta_omega.len(),
-                str,
+                col,
             ))
         } else {
             Ok(())
         }
     };
 
-    if let Some(public) = public {
-        check_eval_len(public, "public input")?;
-    }
-
-    for w_i in w {
-        check_eval_len(w_i, "witness")?;
-    }
-    check_eval_len(z, "permutation accumulator")?;
-    for s_i in s {
-        check_eval_len(s_i, "permutation shifts")?;
-    }
-    for coeff in coefficients {
-        check_eval_len(coeff, "coefficients")?;
-    }
-
-    // Lookup evaluations
-    for sorted in lookup_sorted.iter().flatten() {
-        check_eval_len(sorted, "lookup sorted")?
-    }
-
-    if let Some(lookup_aggregation) = lookup_aggregation {
-        check_eval_len(lookup_aggregation, "lookup aggregation")?;
-    }
-    if let Some(lookup_table) = lookup_table {
-        check_eval_len(lookup_table, "lookup table")?;
-    }
-    if let Some(runtime_lookup_table) = runtime_lookup_table {
-        check_eval_len(runtime_lookup_table, "runtime lookup table")?;
-    }
-
-    check_eval_len(generic_selector, "generic selector")?;
-    check_eval_len(poseidon_selector, "poseidon selector")?;
-    check_eval_len(complete_add_selector, "complete add selector")?;
-    check_eval_len(mul_selector, "mul selector")?;
-    check_eval_len(emul_selector, "endomul selector")?;
-    check_eval_len(endomul_scalar_selector, "endomul scalar selector")?;
-
-    // Optional gates
-
-    if let Some(range_check0_selector) = range_check0_selector {
-        check_eval_len(range_check0_selector, "range check 0 selector")?
-    }
-    if let Some(range_check1_selector) = range_check1_selector {
-        check_eval_len(range_check1_selector, "range check 1 selector")?
-    }
-    if let Some(foreign_field_add_selector) = foreign_field_add_selector {
-        check_eval_len(foreign_field_add_selector, "foreign field add selector")?
-    }
-    if let Some(foreign_field_mul_selector) = foreign_field_mul_selector {
-        check_eval_len(foreign_field_mul_selector, "foreign field mul selector")?
-    }
-    if let Some(xor_selector) = xor_selector {
-        check_eval_len(xor_selector, "xor selector")?
-    }
-    if let Some(rot_selector) = rot_selector {
-        check_eval_len(rot_selector, "rot selector")?
-    }
-
-    // Lookup selectors
-
-    if let Some(runtime_lookup_table_selector) = runtime_lookup_table_selector {
-        check_eval_len(
-            runtime_lookup_table_selector,
-            "runtime lookup table selector",
-        )?
+    if let Some(public) = &proof.evals.public {
+        check_eval_len(public, None)?;
     }
-    if let Some(xor_lookup_selector) = xor_lookup_selector {
-        check_eval_len(xor_lookup_selector, "xor lookup selector")?
-    }
-    if let Some(lookup_gate_lookup_selector) = lookup_gate_lookup_selector {
-        check_eval_len(lookup_gate_lookup_selector, "lookup gate lookup selector")?
-    }
-    if let Some(range_check_lookup_selector) = range_check_lookup_selector {
-        check_eval_len(range_check_lookup_selector, "range check lookup selector")?
-    }
-    if let Some(foreign_field_mul_lookup_selector) = foreign_field_mul_lookup_selector {
-        check_eval_len(
-            foreign_field_mul_lookup_selector,
-            "foreign field mul lookup selector",
-        )?
+    for (col, eval) in proof.evals.iter() {
+        check_eval_len(eval, Some(col))?;
     }
 
     Ok(())
 }
 
-fn to_batch<'a, G, EFqSponge, EFrSponge, OpeningProof: OpenProof<G>>(
+pub(crate) fn to_batch<'a, G, EFqSponge, EFrSponge, OpeningProof: OpenProof<G>>(
     verifier_index: &VerifierIndex<G, OpeningProof>,
     proof: &'a ProverProof<G, OpeningProof>,
     public_input: &'a [<G as AffineRepr>::ScalarField],
@@ -779,8 +620,6 @@ where
     //~ Essentially, this steps verifies that $f(\zeta) = t(\zeta) * Z_H(\zeta)$.
     //~
 
-    let zk_rows = verifier_index.zk_rows;
-
     if proof.prev_challenges.len() != verifier_index.prev_challenges {
         return Err(VerifyError::IncorrectPrevChallengesLength(
             verifier_index.prev_challenges,
@@ -805,37 +644,13 @@ where
     check_proof_evals_len(proof, chunk_size)?;
 
     //~ 1. Commit to the negated public input polynomial.
-    let public_comm = {
-        if public_input.len() != verifier_index.public {
-            return Err(VerifyError::IncorrectPubicInputLength(
-                verifier_index.public,
-            ));
-        }
-        let lgr_comm = verifier_index
-            .srs()
-            .get_lagrange_basis(verifier_index.domain);
-        let com: Vec<_> = lgr_comm.iter().take(verifier_index.public).collect();
-        if public_input.is_empty() {
-            PolyComm::new(vec![verifier_index.srs().blinding_commitment(); chunk_size])
-        } else {
-            let elm: Vec<_> = public_input.iter().map(|s| -*s).collect();
-            let public_comm = PolyComm::<G>::multi_scalar_mul(&com, &elm);
-            verifier_index
-                .srs()
-                .mask_custom(
-                    public_comm.clone(),
-                    &public_comm.map(|_| G::ScalarField::one()),
-                )
-                .unwrap()
-                .commitment
-        }
-    };
+    //~    It can be precomputed in the verifier index if the public input is known in advance.
+    let public_comm = verifier_index.public_comm(public_input)?;
 
     //~ 1. Run the [Fiat-Shamir argument](#fiat-shamir-argument).
     let OraclesResult {
         fq_sponge,
         oracles,
-        all_alphas,
         public_evals,
         powers_of_eval_points_for_chunks,
         polys,
@@ -865,60 +680,15 @@ where
     //~    unless a polynomial has its evaluation provided by the proof
     //~    in which case the evaluation should be used in place of the commitment.
     let f_comm = {
-        // the permutation is written manually (not using the expr framework)
-        let permutation_vanishing_polynomial = verifier_index
-            .permutation_vanishing_polynomial_m()
-            .evaluate(&oracles.zeta);
-
-        let alphas = all_alphas.get_alphas(ArgumentType::Permutation, permutation::CONSTRAINTS);
-
-        let mut commitments = vec![&verifier_index.sigma_comm[PERMUTS - 1]];
-        let mut scalars = vec![ConstraintSystem::<G::ScalarField>::perm_scalars(
-            &evals,
-            oracles.beta,
-            oracles.gamma,
-            alphas,
-            permutation_vanishing_polynomial,
-        )];
-
-        // other gates are implemented using the expression framework
-        {
-            // TODO: Reuse constants and challenges from oracles function
-            let constants = Constants {
-                endo_coefficient: verifier_index.endo,
-                mds: &G::sponge_params().mds,
-                zk_rows,
-            };
-            let challenges = BerkeleyChallenges {
-                alpha: oracles.alpha,
-                beta: oracles.beta,
-                gamma: oracles.gamma,
-                joint_combiner: oracles
-                    .joint_combiner
-                    .as_ref()
-                    .map(|j| j.1)
-                    .unwrap_or(G::ScalarField::zero()),
-            };
-
-            for (col, tokens) in &verifier_index.linearization.index_terms {
-                let scalar = PolishToken::evaluate(
-                    tokens,
-                    verifier_index.domain,
-                    oracles.zeta,
-                    &evals,
-                    &constants,
-                    &challenges,
-                )
-                .expect("should evaluate");
-
-                let col = *col;
-                scalars.push(scalar);
-                commitments.push(
-                    context
-                        .get_column(col)
-                        .ok_or(VerifyError::MissingCommitment(col))?,
-                );
-            }
+        let mut commitments = vec![];
+        let mut scalars = vec![];
+        for (col, scalar) in linearization::commitment_scalars(verifier_index, &evals, &oracles) {
+            commitments.push(
+                context
+                    .get_column(col)
+                    .ok_or(VerifyError::MissingCommitment(col))?,
+            );
+            scalars.push(scalar);
         }
 
         // MSM
@@ -977,39 +747,9 @@ where
     //~~ * optional gate commitments
     .chain(
         verifier_index
-            .range_check0_comm
-            .as_ref()
-            .map(|_| Column::Index(GateType::RangeCheck0)),
-    )
-    .chain(
-        verifier_index
-            .range_check1_comm
-            .as_ref()
-            .map(|_| Column::Index(GateType::RangeCheck1)),
-    )
-    .chain(
-        verifier_index
-            .foreign_field_add_comm
-            .as_ref()
-            .map(|_| Column::Index(GateType::ForeignFieldAdd)),
-    )
-    .chain(
-        verifier_index
-            .foreign_field_mul_comm
-            .as_ref()
-            .map(|_| Column::Index(GateType::ForeignFieldMul)),
-    )
-    .chain(
-        verifier_index
-            .xor_comm
-            .as_ref()
-            .map(|_| Column::Index(GateType::Xor16)),
-    )
-    .chain(
-        verifier_index
-            .rot_comm
-            .as_ref()
-            .map(|_| Column::Index(GateType::Rot64)),
+            .optional_selector_comm
+            .keys()
+            .map(|gate_type| Column::Index(*gate_type)),
     )
     //~~ * lookup commitments
     //~
@@ -1188,6 +928,30 @@ where
     batch_verify::<G, EFqSponge, EFrSponge, OpeningProof>(group_map, &proofs)
 }
 
+/// Same as [`verify`], with the public input given as the values of its
+/// sections, in the order of [`VerifierIndex::public_sections`] (see
+/// [`Builder::public_sections`](crate::circuits::constraints::Builder::public_sections)).
+///
+/// # Errors
+///
+/// Will give error if the values do not match the sections of the index, or if
+/// the proof is not valid.
+pub fn verify_with_sections<G, EFqSponge, EFrSponge, OpeningProof: OpenProof<G>>(
+    group_map: &G::Map,
+    verifier_index: &VerifierIndex<G, OpeningProof>,
+    proof: &ProverProof<G, OpeningProof>,
+    public_sections: &[&[G::ScalarField]],
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let public_input = verifier_index.public_input_from_sections(public_sections)?;
+    verify::<G, EFqSponge, EFrSponge, OpeningProof>(group_map, verifier_index, proof, &public_input)
+}
+
 /// This function verifies the batch of zk-proofs
 ///     proofs: vector of Plonk proofs
 ///     RETURN: verification status
@@ -1248,3 +1012,183 @@ where
         Err(VerifyError::OpenProof)
     }
 }
+
+/// Verify a batch of proofs, each given with its [VerifierIndex] and its
+/// public input. The proofs can come from different circuits, as long as they
+/// use SRS of the same size.
+///
+/// The final MSMs of all the opening proofs are combined with random scalars
+/// into a single MSM (see [`batch_verify`]), which is much cheaper than
+/// verifying each proof separately.
+///
+/// # Errors
+///
+/// Will give error if one of the proofs is invalid. The error does not tell
+/// which proof is invalid; the proofs must be verified separately for this.
+pub fn batch_verify_proofs<G, EFqSponge, EFrSponge, OpeningProof: OpenProof<G>>(
+    group_map: &G::Map,
+    proofs: &[BatchItem<G, OpeningProof>],
+) -> Result<()>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let proofs: Vec<Context<G, OpeningProof>> = proofs.iter().copied().map(Context::from).collect();
+    batch_verify::<G, EFqSponge, EFrSponge, OpeningProof>(group_map, &proofs)
+}
+
+/// Cost metrics collected while verifying a proof with [`verify_with_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Size in bytes of the proof, encoded with MessagePack.
+    pub proof_size: usize,
+    /// Number of commitment chunks opened by the opening proof, i.e. the
+    /// number of terms the batched evaluations add to the final MSM.
+    pub msm_terms: usize,
+    /// Number of elements (points and field elements) absorbed by the
+    /// Fq-sponge, including the absorptions of the opening proof.
+    pub sponge_absorptions: usize,
+    /// Time spent in the partial verification (oracles, linearization and
+    /// preparation of the evaluations to open).
+    pub partial_verification: Duration,
+    /// Time spent verifying the opening proof.
+    pub opening_proof: Duration,
+    /// Total verification time.
+    pub total: Duration,
+}
+
+/// A wrapper around an Fq-sponge counting the number of elements absorbed.
+#[derive(Clone)]
+struct CountingSponge<S> {
+    sponge: S,
+    absorptions: usize,
+}
+
+impl<G, S> FqSponge<G::BaseField, G, G::ScalarField> for CountingSponge<S>
+where
+    G: AffineRepr,
+    S: FqSponge<G::BaseField, G, G::ScalarField>,
+{
+    fn new(p: &'static mina_poseidon::poseidon::ArithmeticSpongeParams<G::BaseField>) -> Self {
+        CountingSponge {
+            sponge: S::new(p),
+            absorptions: 0,
+        }
+    }
+
+    fn absorb_fq(&mut self, x: &[G::BaseField]) {
+        self.absorptions += x.len();
+        self.sponge.absorb_fq(x)
+    }
+
+    fn absorb_g(&mut self, g: &[G]) {
+        self.absorptions += g.len();
+        self.sponge.absorb_g(g)
+    }
+
+    fn absorb_fr(&mut self, x: &[G::ScalarField]) {
+        self.absorptions += x.len();
+        self.sponge.absorb_fr(x)
+    }
+
+    fn challenge_fq(&mut self) -> G::BaseField {
+        self.sponge.challenge_fq()
+    }
+
+    fn challenge(&mut self) -> G::ScalarField {
+        self.sponge.challenge()
+    }
+
+    fn digest_fq(self) -> G::BaseField {
+        self.sponge.digest_fq()
+    }
+
+    fn digest(self) -> G::ScalarField {
+        self.sponge.digest()
+    }
+}
+
+/// Verify a proof like [`verify`], and return a [`VerificationReport`]
+/// describing the cost of the verification. The result of the verification is
+/// returned alongside the report, so that the cost of failed verifications can
+/// be monitored too.
+pub fn verify_with_report<G, EFqSponge, EFrSponge, OpeningProof: OpenProof<G>>(
+    group_map: &G::Map,
+    verifier_index: &VerifierIndex<G, OpeningProof>,
+    proof: &ProverProof<G, OpeningProof>,
+    public_input: &[G::ScalarField],
+) -> (Result<()>, VerificationReport)
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+    OpeningProof: Serialize,
+{
+    let (res, mut report) = verify_and_report::<G, EFqSponge, EFrSponge, OpeningProof>(
+        group_map,
+        verifier_index,
+        proof,
+        public_input,
+    );
+    report.proof_size = rmp_serde::to_vec(proof).map_or(0, |bytes| bytes.len());
+    (res, report)
+}
+
+/// Verifies a proof, and returns the result with the report of
+/// [`verify_with_report`], except for the size of the proof, which requires
+/// the opening proof to be serializable. Unlike [`verify`], it measures the
+/// time of the verification, which `std::time::Instant` does not support on
+/// all targets (e.g. `wasm32-unknown-unknown`).
+fn verify_and_report<G, EFqSponge, EFrSponge, OpeningProof: OpenProof<G>>(
+    group_map: &G::Map,
+    verifier_index: &VerifierIndex<G, OpeningProof>,
+    proof: &ProverProof<G, OpeningProof>,
+    public_input: &[G::ScalarField],
+) -> (Result<()>, VerificationReport)
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let mut report = VerificationReport::default();
+    let start = Instant::now();
+
+    let batch = to_batch::<G, CountingSponge<EFqSponge>, EFrSponge, OpeningProof>(
+        verifier_index,
+        proof,
+        public_input,
+    );
+    report.partial_verification = start.elapsed();
+    let mut batch = match batch {
+        Ok(batch) => vec![batch],
+        Err(e) => {
+            report.total = start.elapsed();
+            return (Err(e), report);
+        }
+    };
+    report.msm_terms = batch[0]
+        .evaluations
+        .iter()
+        .map(|eval| eval.commitment.len())
+        .sum();
+
+    let opening_start = Instant::now();
+    let res = if OpeningProof::verify(
+        verifier_index.srs(),
+        group_map,
+        &mut batch,
+        &mut thread_rng(),
+    ) {
+        Ok(())
+    } else {
+        Err(VerifyError::OpenProof)
+    };
+    report.opening_proof = opening_start.elapsed();
+    report.sponge_absorptions = batch[0].sponge.absorptions;
+    report.total = start.elapsed();
+    (res, report)
+}
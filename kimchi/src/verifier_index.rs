@@ -1,32 +1,48 @@
 //! This module implements the verifier index as [`VerifierIndex`].
-//! You can derive this struct from the [`ProverIndex`] struct.
+//! You can derive this struct from the [`ProverIndex`] struct, or directly from
+//! the circuit with [`VerifierIndex::from_circuit`].
 
 use crate::{
     alphas::Alphas,
     circuits::{
         berkeley_columns::{BerkeleyChallengeTerm, Column},
+        constraints::{
+            concat_public_sections, split_public_input, ConstraintSystem, FeatureFlags,
+            PublicSection, OPTIONAL_GATES,
+        },
         expr::{Linearization, PolishToken},
-        lookup::{index::LookupSelectors, lookups::LookupInfo},
+        gate::{CircuitGate, GateType},
+        lookup::{
+            index::LookupSelectors, lookups::LookupInfo, runtime_tables::RuntimeTableCfg,
+            tables::LookupTable,
+        },
         polynomials::permutation::{vanishes_on_last_n_rows, zk_w},
         wires::{COLUMNS, PERMUTS},
     },
     curve::KimchiCurve,
+    error::{IndexSerializationError, SetupError, VerifyError},
+    index_serialization::{read_index, write_index, IndexKind},
+    linearization::expr_linearization,
     prover_index::ProverIndex,
 };
 use ark_ff::{One, PrimeField};
-use ark_poly::{univariate::DensePolynomial, Radix2EvaluationDomain as D};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, Radix2EvaluationDomain as D,
+};
 use mina_poseidon::FqSponge;
+use o1_utils::serialization::{from_msgpack, DeserializationMode};
 use once_cell::sync::OnceCell;
 use poly_commitment::{
     commitment::{absorb_commitment, CommitmentCurve, PolyComm},
-    OpenProof, SRS as _,
+    OpenProof, SRS,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
 use std::{
     array,
+    collections::BTreeMap,
     fs::{File, OpenOptions},
-    io::{BufReader, BufWriter, Seek, SeekFrom::Start},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom::Start, Write},
     path::Path,
     sync::Arc,
 };
@@ -70,6 +86,10 @@ pub struct VerifierIndex<G: KimchiCurve, OpeningProof: OpenProof<G>> {
     pub srs: Arc<OpeningProof::SRS>,
     /// number of public inputs
     pub public: usize,
+    /// named sections of the public input, see
+    /// [`ConstraintSystem::public_sections`]
+    #[serde(default)]
+    pub public_sections: Vec<PublicSection>,
     /// number of previous evaluation challenges, for recursive proving
     pub prev_challenges: usize,
 
@@ -103,29 +123,12 @@ pub struct VerifierIndex<G: KimchiCurve, OpeningProof: OpenProof<G>> {
     #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
     pub endomul_scalar_comm: PolyComm<G>,
 
-    /// RangeCheck0 polynomial commitments
-    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
-    pub range_check0_comm: Option<PolyComm<G>>,
-
-    /// RangeCheck1 polynomial commitments
-    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
-    pub range_check1_comm: Option<PolyComm<G>>,
-
-    /// Foreign field addition gates polynomial commitments
-    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
-    pub foreign_field_add_comm: Option<PolyComm<G>>,
-
-    /// Foreign field multiplication gates polynomial commitments
-    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
-    pub foreign_field_mul_comm: Option<PolyComm<G>>,
-
-    /// Xor commitments
-    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
-    pub xor_comm: Option<PolyComm<G>>,
-
-    /// Rot commitments
-    #[serde(bound = "Option<PolyComm<G>>: Serialize + DeserializeOwned")]
-    pub rot_comm: Option<PolyComm<G>>,
+    /// selector polynomial commitments of the optional gates used by the
+    /// circuit (see [`OPTIONAL_GATES`]). They replace the former fields
+    /// `range_check0_comm` to `rot_comm`, so indexes serialized with those
+    /// fields can not be deserialized anymore.
+    #[serde(bound = "PolyComm<G>: Serialize + DeserializeOwned")]
+    pub optional_selector_comm: BTreeMap<GateType, PolyComm<G>>,
 
     /// wire coordinate shifts
     #[serde_as(as = "[o1_utils::serialization::SerdeAs; PERMUTS]")]
@@ -150,9 +153,59 @@ pub struct VerifierIndex<G: KimchiCurve, OpeningProof: OpenProof<G>> {
     /// The mapping between powers of alpha and constraints
     #[serde(skip)]
     pub powers_of_alpha: Alphas<G::ScalarField>,
+    /// The commitment to the negated public input polynomial, precomputed for
+    /// the given public input (see [`VerifierIndex::precompute_public_comm`])
+    #[serde(skip)]
+    pub public_comm: Option<(Vec<G::ScalarField>, PolyComm<G>)>,
 }
 //~spec:endcode
 
+/// Masks the commitment to a fixed polynomial with a blinder of one
+fn mask_fixed<G: CommitmentCurve, S: SRS<G>>(srs: &S, commitment: PolyComm<G>) -> PolyComm<G> {
+    let blinders = commitment.map(|_| G::ScalarField::one());
+    srs.mask_custom(commitment, &blinders).unwrap().commitment
+}
+
+/// Commits to a sparse selector of `cs`, given by its non-zero evaluations
+/// (see [`SRS::commit_sparse_evaluations_non_hiding`])
+fn commit_sparse<G: CommitmentCurve, S: SRS<G>>(
+    cs: &ConstraintSystem<G::ScalarField>,
+    srs: &S,
+    evals: &[(usize, G::ScalarField)],
+) -> PolyComm<G> {
+    srs.commit_sparse_evaluations_non_hiding(cs.domain.d1, evals)
+}
+
+/// Commits to the lookup polynomials of `cs`, if it uses lookups
+fn lookup_verifier_index<G: CommitmentCurve, S: SRS<G>>(
+    cs: &ConstraintSystem<G::ScalarField>,
+    srs: &S,
+) -> Option<LookupVerifierIndex<G>> {
+    let domain = cs.domain.d1;
+    cs.lookup_constraint_system
+        .as_ref()
+        .map(|cs| LookupVerifierIndex {
+            joint_lookup_used: cs.configuration.lookup_info.features.joint_lookup_used,
+            lookup_info: cs.configuration.lookup_info,
+            lookup_selectors: cs
+                .lookup_selectors
+                .as_ref()
+                .map(|e| srs.commit_evaluations_non_hiding(domain, e)),
+            lookup_table: cs
+                .lookup_table8
+                .iter()
+                .map(|e| mask_fixed(srs, srs.commit_evaluations_non_hiding(domain, e)))
+                .collect(),
+            table_ids: cs.table_ids8.as_ref().map(|table_ids8| {
+                mask_fixed(srs, srs.commit_evaluations_non_hiding(domain, table_ids8))
+            }),
+            runtime_tables_selector: cs
+                .runtime_selector
+                .as_ref()
+                .map(|e| srs.commit_evaluations_non_hiding(domain, e)),
+        })
+}
+
 impl<G: KimchiCurve, OpeningProof: OpenProof<G>> ProverIndex<G, OpeningProof>
 where
     G::BaseField: PrimeField,
@@ -170,41 +223,18 @@ where
             return verifier_index.clone();
         }
 
-        let mask_fixed = |commitment: PolyComm<G>| {
-            let blinders = commitment.map(|_| G::ScalarField::one());
-            self.srs
-                .mask_custom(commitment, &blinders)
-                .unwrap()
-                .commitment
+        let mask_fixed = |commitment: PolyComm<G>| mask_fixed(self.srs.as_ref(), commitment);
+        let commit_selector = |gate_type| {
+            commit_sparse(
+                &self.cs,
+                self.srs.as_ref(),
+                &self.cs.sparse_selector(gate_type),
+            )
         };
 
         let domain = self.cs.domain.d1;
 
-        let lookup_index = {
-            self.cs
-                .lookup_constraint_system
-                .as_ref()
-                .map(|cs| LookupVerifierIndex {
-                    joint_lookup_used: cs.configuration.lookup_info.features.joint_lookup_used,
-                    lookup_info: cs.configuration.lookup_info,
-                    lookup_selectors: cs
-                        .lookup_selectors
-                        .as_ref()
-                        .map(|e| self.srs.commit_evaluations_non_hiding(domain, e)),
-                    lookup_table: cs
-                        .lookup_table8
-                        .iter()
-                        .map(|e| mask_fixed(self.srs.commit_evaluations_non_hiding(domain, e)))
-                        .collect(),
-                    table_ids: cs.table_ids8.as_ref().map(|table_ids8| {
-                        mask_fixed(self.srs.commit_evaluations_non_hiding(domain, table_ids8))
-                    }),
-                    runtime_tables_selector: cs
-                        .runtime_selector
-                        .as_ref()
-                        .map(|e| self.srs.commit_evaluations_non_hiding(domain, e)),
-                })
-        };
+        let lookup_index = lookup_verifier_index(&self.cs, self.srs.as_ref());
 
         // TODO: Switch to commit_evaluations for all index polys
         VerifierIndex {
@@ -213,6 +243,7 @@ where
             zk_rows: self.cs.zk_rows,
             powers_of_alpha: self.powers_of_alpha.clone(),
             public: self.cs.public,
+            public_sections: self.cs.public_sections.clone(),
             prev_challenges: self.cs.prev_challenges,
             srs: Arc::clone(&self.srs),
 
@@ -228,69 +259,30 @@ where
                     &self.column_evaluations.coefficients8[i],
                 )
             }),
-            generic_comm: mask_fixed(
-                self.srs.commit_evaluations_non_hiding(
-                    domain,
-                    &self.column_evaluations.generic_selector4,
-                ),
-            ),
-
-            psm_comm: mask_fixed(self.srs.commit_evaluations_non_hiding(
-                domain,
-                &self.column_evaluations.poseidon_selector8,
+            // the selectors are committed from the rows of their gates only
+            generic_comm: mask_fixed(commit_sparse(
+                &self.cs,
+                self.srs.as_ref(),
+                &self.cs.gate_rows(GateType::Generic),
             )),
 
-            complete_add_comm: mask_fixed(self.srs.commit_evaluations_non_hiding(
-                domain,
-                &self.column_evaluations.complete_add_selector4,
+            psm_comm: mask_fixed(commit_sparse(
+                &self.cs,
+                self.srs.as_ref(),
+                &self.cs.sparse_poseidon_selector(),
             )),
-            mul_comm: mask_fixed(
-                self.srs
-                    .commit_evaluations_non_hiding(domain, &self.column_evaluations.mul_selector8),
-            ),
-            emul_comm: mask_fixed(
-                self.srs
-                    .commit_evaluations_non_hiding(domain, &self.column_evaluations.emul_selector8),
-            ),
-
-            endomul_scalar_comm: mask_fixed(self.srs.commit_evaluations_non_hiding(
-                domain,
-                &self.column_evaluations.endomul_scalar_selector8,
-            )),
-
-            range_check0_comm: self
-                .column_evaluations
-                .range_check0_selector8
-                .as_ref()
-                .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
 
-            range_check1_comm: self
-                .column_evaluations
-                .range_check1_selector8
-                .as_ref()
-                .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
+            complete_add_comm: mask_fixed(commit_selector(GateType::CompleteAdd)),
+            mul_comm: mask_fixed(commit_selector(GateType::VarBaseMul)),
+            emul_comm: mask_fixed(commit_selector(GateType::EndoMul)),
 
-            foreign_field_add_comm: self
-                .column_evaluations
-                .foreign_field_add_selector8
-                .as_ref()
-                .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
+            endomul_scalar_comm: mask_fixed(commit_selector(GateType::EndoMulScalar)),
 
-            foreign_field_mul_comm: self
-                .column_evaluations
-                .foreign_field_mul_selector8
-                .as_ref()
-                .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
-            xor_comm: self
-                .column_evaluations
-                .xor_selector8
-                .as_ref()
-                .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
-            rot_comm: self
-                .column_evaluations
-                .rot_selector8
-                .as_ref()
-                .map(|eval8| self.srs.commit_evaluations_non_hiding(domain, eval8)),
+            optional_selector_comm: OPTIONAL_GATES
+                .into_iter()
+                .filter(|gate_type| self.cs.feature_flags.uses_gate(*gate_type))
+                .map(|gate_type| (gate_type, commit_selector(gate_type)))
+                .collect(),
 
             shift: self.cs.shift,
             permutation_vanishing_polynomial_m: {
@@ -312,11 +304,110 @@ where
             endo: self.cs.endo,
             lookup_index,
             linearization: self.linearization.clone(),
+            public_comm: None,
         }
     }
 }
 
 impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof> {
+    /// Produces the [`VerifierIndex`] of the circuit made of `gates`, with
+    /// `public` public inputs and the given lookup configuration, without
+    /// building the [`ProverIndex`] (see [`VerifierIndex::from_constraint_system`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the constraint system cannot be built from `gates`
+    /// and the lookup configuration.
+    pub fn from_circuit(
+        gates: Vec<CircuitGate<G::ScalarField>>,
+        public: usize,
+        lookup_tables: Vec<LookupTable<G::ScalarField>>,
+        runtime_tables: Option<Vec<RuntimeTableCfg<G::ScalarField>>>,
+        srs: Arc<OpeningProof::SRS>,
+    ) -> Result<Self, SetupError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let cs = ConstraintSystem::create(gates)
+            .public(public)
+            .lookup(lookup_tables)
+            .runtime(runtime_tables)
+            .max_poly_size(Some(srs.max_poly_size()))
+            .build()?;
+        Ok(Self::from_constraint_system(&cs, srs))
+    }
+
+    /// Produces the [`VerifierIndex`] of the constraint system `cs`, without
+    /// building the [`ProverIndex`]. The index polynomials are committed from
+    /// their evaluations over the domain of the circuit, which are read from
+    /// the gates, so that none of them has to be interpolated or evaluated
+    /// over the larger domains used by the prover. The resulting index is the
+    /// same as the one given by [`ProverIndex::verifier_index`].
+    pub fn from_constraint_system(
+        cs: &ConstraintSystem<G::ScalarField>,
+        srs: Arc<OpeningProof::SRS>,
+    ) -> Self
+    where
+        G::BaseField: PrimeField,
+    {
+        let mask_fixed = |commitment: PolyComm<G>| mask_fixed(srs.as_ref(), commitment);
+        let domain = cs.domain.d1;
+        let commit = |evals: &Evaluations<G::ScalarField, D<G::ScalarField>>| {
+            srs.commit_evaluations_non_hiding(domain, evals)
+        };
+        let commit_selector =
+            |gate_type| commit_sparse(cs, srs.as_ref(), &cs.sparse_selector(gate_type));
+
+        let (linearization, powers_of_alpha) = expr_linearization(Some(&cs.feature_flags), true);
+        let permutation_evaluations1 = cs.permutation_evaluations1();
+        let coefficient_evaluations1 = cs.coefficient_evaluations1();
+
+        VerifierIndex {
+            domain,
+            max_poly_size: srs.max_poly_size(),
+            zk_rows: cs.zk_rows,
+            powers_of_alpha,
+            public: cs.public,
+            public_sections: cs.public_sections.clone(),
+            prev_challenges: cs.prev_challenges,
+
+            sigma_comm: array::from_fn(|i| commit(&permutation_evaluations1[i])),
+            coefficients_comm: array::from_fn(|i| commit(&coefficient_evaluations1[i])),
+            // the generic and poseidon selectors are never disabled
+            generic_comm: mask_fixed(commit_sparse(
+                cs,
+                srs.as_ref(),
+                &cs.gate_rows(GateType::Generic),
+            )),
+            psm_comm: mask_fixed(commit_sparse(
+                cs,
+                srs.as_ref(),
+                &cs.sparse_poseidon_selector(),
+            )),
+
+            complete_add_comm: mask_fixed(commit_selector(GateType::CompleteAdd)),
+            mul_comm: mask_fixed(commit_selector(GateType::VarBaseMul)),
+            emul_comm: mask_fixed(commit_selector(GateType::EndoMul)),
+            endomul_scalar_comm: mask_fixed(commit_selector(GateType::EndoMulScalar)),
+
+            optional_selector_comm: OPTIONAL_GATES
+                .into_iter()
+                .filter(|gate_type| cs.feature_flags.uses_gate(*gate_type))
+                .map(|gate_type| (gate_type, commit_selector(gate_type)))
+                .collect(),
+
+            shift: cs.shift,
+            // computed lazily, see [`VerifierIndex::permutation_vanishing_polynomial_m`]
+            permutation_vanishing_polynomial_m: OnceCell::new(),
+            w: OnceCell::new(),
+            endo: *G::other_curve_endo(),
+            lookup_index: lookup_verifier_index(cs, srs.as_ref()),
+            linearization,
+            public_comm: None,
+            srs,
+        }
+    }
+
     /// Gets srs from [`VerifierIndex`] lazily
     pub fn srs(&self) -> &Arc<OpeningProof::SRS>
     where
@@ -336,6 +427,95 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
         self.w.get_or_init(|| zk_w(self.domain, self.zk_rows))
     }
 
+    /// Builds the public input from the values of its sections, given in the
+    /// order of [`VerifierIndex::public_sections`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the values do not match the sections of the index.
+    pub fn public_input_from_sections(
+        &self,
+        sections: &[&[G::ScalarField]],
+    ) -> Result<Vec<G::ScalarField>, VerifyError> {
+        concat_public_sections(&self.public_sections, sections)
+    }
+
+    /// Splits `public_input` into the sections of the index, giving the name
+    /// and the values of each section.
+    pub fn split_public_input<'a>(
+        &'a self,
+        public_input: &'a [G::ScalarField],
+    ) -> Vec<(&'a str, &'a [G::ScalarField])> {
+        split_public_input(&self.public_sections, public_input)
+    }
+
+    /// Computes the commitment to the negated public input polynomial for
+    /// `public_input`, or returns the precomputed one if it was computed for
+    /// the same public input (see [`VerifierIndex::precompute_public_comm`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `public_input` does not have the length expected by
+    /// the index.
+    pub fn public_comm(&self, public_input: &[G::ScalarField]) -> Result<PolyComm<G>, VerifyError>
+    where
+        G::BaseField: PrimeField,
+    {
+        if public_input.len() != self.public {
+            return Err(VerifyError::IncorrectPubicInputLength(self.public));
+        }
+        if let Some((input, comm)) = &self.public_comm {
+            if input == public_input {
+                return Ok(comm.clone());
+            }
+        }
+
+        let num_chunks = if self.domain.size() < self.max_poly_size {
+            1
+        } else {
+            self.domain.size() / self.max_poly_size
+        };
+        if public_input.is_empty() {
+            return Ok(PolyComm::new(vec![
+                self.srs().blinding_commitment();
+                num_chunks
+            ]));
+        }
+
+        let lgr_comm = self.srs().get_lagrange_basis(self.domain);
+        let com: Vec<_> = lgr_comm.iter().take(self.public).collect();
+        let elm: Vec<_> = public_input.iter().map(|s| -*s).collect();
+        let public_comm = PolyComm::<G>::multi_scalar_mul(&com, &elm);
+        Ok(self
+            .srs()
+            .mask_custom(
+                public_comm.clone(),
+                &public_comm.map(|_| G::ScalarField::one()),
+            )
+            .unwrap()
+            .commitment)
+    }
+
+    /// Precomputes the commitment to the negated public input polynomial for
+    /// `public_input`, so that verifying proofs with this public input does
+    /// not recompute it. It replaces any previously precomputed commitment.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `public_input` does not have the length expected by
+    /// the index.
+    pub fn precompute_public_comm(
+        &mut self,
+        public_input: &[G::ScalarField],
+    ) -> Result<(), VerifyError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let comm = self.public_comm(public_input)?;
+        self.public_comm = Some((public_input.to_vec(), comm));
+        Ok(())
+    }
+
     /// Deserializes a [`VerifierIndex`] from a file, given a pointer to an SRS and an optional offset in the file.
     ///
     /// # Errors
@@ -393,6 +573,85 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
             .map_err(|e| e.to_string())
     }
 
+    /// Returns the features used by the circuit, as they can be deduced from
+    /// the optional commitments of the [`VerifierIndex`].
+    pub fn feature_flags(&self) -> FeatureFlags {
+        let mut feature_flags = FeatureFlags::default();
+        for gate_type in self.optional_selector_comm.keys() {
+            feature_flags.enable_gate(*gate_type);
+        }
+        if let Some(lookup_index) = &self.lookup_index {
+            feature_flags.lookup_features = lookup_index.lookup_info.features;
+        }
+        feature_flags
+    }
+
+    /// Writes the [`VerifierIndex`] to `writer`, using the format described in
+    /// [`crate::index_serialization`]. The SRS is not written.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the index cannot be encoded or written.
+    pub fn serialize_to<W: Write>(&self, writer: W) -> Result<(), IndexSerializationError> {
+        let payload =
+            rmp_serde::to_vec(self).map_err(|e| IndexSerializationError::Encode(e.to_string()))?;
+        write_index::<G, _>(writer, IndexKind::Verifier, self.domain.size, &payload)
+    }
+
+    /// Reads a [`VerifierIndex`] written with [`VerifierIndex::serialize_to`],
+    /// and sets its SRS to `srs`. The linearization is recomputed from the
+    /// features deduced from the index, and the endoscalar coefficient is the
+    /// one of the other curve.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the header does not match the expected version, curve
+    /// or domain, if the checksum is incorrect, or if the index cannot be
+    /// decoded.
+    pub fn deserialize_from<R: Read>(
+        reader: R,
+        srs: Arc<OpeningProof::SRS>,
+    ) -> Result<Self, IndexSerializationError>
+    where
+        OpeningProof::SRS: Default,
+    {
+        Self::deserialize_from_with_mode(reader, srs, DeserializationMode::Lenient)
+    }
+
+    /// Same as [`VerifierIndex::deserialize_from`], but decodes the index with the
+    /// given [`DeserializationMode`]. In strict mode, an index containing a
+    /// non-canonical encoding, e.g. a field element that is not reduced, is
+    /// rejected with [`IndexSerializationError::NonCanonical`].
+    ///
+    /// # Errors
+    ///
+    /// See [`VerifierIndex::deserialize_from`].
+    pub fn deserialize_from_with_mode<R: Read>(
+        reader: R,
+        srs: Arc<OpeningProof::SRS>,
+        mode: DeserializationMode,
+    ) -> Result<Self, IndexSerializationError>
+    where
+        OpeningProof::SRS: Default,
+    {
+        let (domain_size, payload) = read_index::<G, _>(reader, IndexKind::Verifier)?;
+        let mut index: Self = from_msgpack(&payload, mode)?;
+        if index.domain.size != domain_size {
+            return Err(IndexSerializationError::IncorrectDomainSize(
+                domain_size,
+                index.domain.size,
+            ));
+        }
+
+        let (linearization, powers_of_alpha) =
+            expr_linearization(Some(&index.feature_flags()), true);
+        index.linearization = linearization;
+        index.powers_of_alpha = powers_of_alpha;
+        index.endo = *G::other_curve_endo();
+        index.srs = srs;
+        Ok(index)
+    }
+
     /// Compute the digest of the [`VerifierIndex`], which can be used for the Fiat-Shamir
     /// transformation while proving / verifying.
     pub fn digest<EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
@@ -406,6 +665,7 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
             zk_rows: _,
             srs: _,
             public: _,
+            public_sections,
             prev_challenges: _,
 
             // Always present
@@ -419,12 +679,7 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
             endomul_scalar_comm,
 
             // Optional gates
-            range_check0_comm,
-            range_check1_comm,
-            foreign_field_add_comm,
-            foreign_field_mul_comm,
-            xor_comm,
-            rot_comm,
+            optional_selector_comm,
 
             // Lookup index; optional
             lookup_index,
@@ -436,8 +691,21 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
 
             linearization: _,
             powers_of_alpha: _,
+            public_comm: _,
         } = &self;
 
+        // Public sections; the layout of the public input is absorbed when
+        // the circuit defines sections
+
+        if !public_sections.is_empty() {
+            fq_sponge.absorb_fq(&[G::BaseField::from(public_sections.len() as u64)]);
+            let sizes: Vec<_> = public_sections
+                .iter()
+                .map(|section| G::BaseField::from(section.size as u64))
+                .collect();
+            fq_sponge.absorb_fq(&sizes);
+        }
+
         // Always present
 
         for comm in sigma_comm.iter() {
@@ -455,28 +723,10 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
 
         // Optional gates
 
-        if let Some(range_check0_comm) = range_check0_comm {
-            absorb_commitment(&mut fq_sponge, range_check0_comm);
-        }
-
-        if let Some(range_check1_comm) = range_check1_comm {
-            absorb_commitment(&mut fq_sponge, range_check1_comm);
-        }
-
-        if let Some(foreign_field_mul_comm) = foreign_field_mul_comm {
-            absorb_commitment(&mut fq_sponge, foreign_field_mul_comm);
-        }
-
-        if let Some(foreign_field_add_comm) = foreign_field_add_comm {
-            absorb_commitment(&mut fq_sponge, foreign_field_add_comm);
-        }
-
-        if let Some(xor_comm) = xor_comm {
-            absorb_commitment(&mut fq_sponge, xor_comm);
-        }
-
-        if let Some(rot_comm) = rot_comm {
-            absorb_commitment(&mut fq_sponge, rot_comm);
+        for gate_type in OPTIONAL_GATES {
+            if let Some(comm) = optional_selector_comm.get(&gate_type) {
+                absorb_commitment(&mut fq_sponge, comm);
+            }
         }
 
         // Lookup index; optional
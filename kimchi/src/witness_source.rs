@@ -0,0 +1,176 @@
+//! This module implements the [`WitnessSource`] trait, which lets the caller
+//! produce the witness row by row, e.g. from a file, instead of building its
+//! columns itself (see [`ProverProof::create_from_rows`]).
+//! The witness is not streamed: the prover collects all the rows into columns
+//! before proving, so the whole witness is held in memory once.
+//!
+//! FIXME: streaming the rows into the prover does not lower its peak memory
+//! yet. The columns are interpolated with an FFT over the whole domain, and
+//! their evaluations over the larger domains are kept to compute the quotient,
+//! so each column has to be complete before it is interpolated. Only the
+//! commitments to the columns could be computed chunk by chunk, in the
+//! Lagrange basis.
+//!
+//! [`ProverProof::create_from_rows`]: crate::proof::ProverProof::create_from_rows
+
+use crate::circuits::wires::COLUMNS;
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::{
+    array,
+    io::{Error, ErrorKind, Read, Result, Write},
+};
+
+/// Number of rows requested at once from a [`WitnessSource`]
+pub const WITNESS_CHUNK_ROWS: usize = 1 << 12;
+
+/// A source of witness rows, yielding the rows of the execution trace in
+/// order, by chunks of consecutive rows.
+pub trait WitnessSource<F> {
+    /// Returns the number of rows of the witness, without the padding and the
+    /// zero-knowledge rows added by the prover.
+    fn num_rows(&self) -> usize;
+
+    /// Appends at most `max_rows` of the next rows of the witness to
+    /// `columns`, and returns the number of rows appended. Once all the rows
+    /// have been yielded, returns `0`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the rows cannot be produced, e.g. if they cannot be
+    /// read from the underlying file.
+    fn next_chunk(&mut self, columns: &mut [Vec<F>; COLUMNS], max_rows: usize) -> Result<usize>;
+}
+
+/// A [`WitnessSource`] computing each row with a closure
+pub struct RowGenerator<Gen> {
+    num_rows: usize,
+    next_row: usize,
+    generator: Gen,
+}
+
+impl<Gen> RowGenerator<Gen> {
+    /// Creates a source of `num_rows` rows, where row `i` is `generator(i)`
+    pub fn new(num_rows: usize, generator: Gen) -> Self {
+        RowGenerator {
+            num_rows,
+            next_row: 0,
+            generator,
+        }
+    }
+}
+
+impl<F, Gen: FnMut(usize) -> [F; COLUMNS]> WitnessSource<F> for RowGenerator<Gen> {
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn next_chunk(&mut self, columns: &mut [Vec<F>; COLUMNS], max_rows: usize) -> Result<usize> {
+        let end = std::cmp::min(self.num_rows, self.next_row + max_rows);
+        for row in self.next_row..end {
+            for (col, cell) in columns.iter_mut().zip((self.generator)(row)) {
+                col.push(cell);
+            }
+        }
+        let appended = end - self.next_row;
+        self.next_row = end;
+        Ok(appended)
+    }
+}
+
+/// A [`WitnessSource`] reading rows from a reader, e.g. a file written with
+/// [`write_rows`]. Each row is made of the [`COLUMNS`] cells of the row,
+/// serialized in compressed form.
+pub struct RowReader<R> {
+    num_rows: usize,
+    next_row: usize,
+    reader: R,
+}
+
+impl<R: Read> RowReader<R> {
+    /// Creates a source reading `num_rows` rows from `reader`
+    pub fn new(num_rows: usize, reader: R) -> Self {
+        RowReader {
+            num_rows,
+            next_row: 0,
+            reader,
+        }
+    }
+}
+
+impl<F: Field, R: Read> WitnessSource<F> for RowReader<R> {
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn next_chunk(&mut self, columns: &mut [Vec<F>; COLUMNS], max_rows: usize) -> Result<usize> {
+        let end = std::cmp::min(self.num_rows, self.next_row + max_rows);
+        for _ in self.next_row..end {
+            for col in columns.iter_mut() {
+                let cell = F::deserialize_compressed(&mut self.reader)
+                    .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+                col.push(cell);
+            }
+        }
+        let appended = end - self.next_row;
+        self.next_row = end;
+        Ok(appended)
+    }
+}
+
+/// Writes the rows of `witness` to `writer`, in the format read by [`RowReader`].
+///
+/// # Errors
+///
+/// Will give error if the columns are not all the same size, or if a cell
+/// cannot be written.
+pub fn write_rows<F: Field, W: Write>(witness: &[Vec<F>; COLUMNS], mut writer: W) -> Result<()> {
+    let num_rows = witness[0].len();
+    if witness.iter().any(|col| col.len() != num_rows) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "the witness columns are not all the same size",
+        ));
+    }
+    for row in 0..num_rows {
+        for col in witness {
+            col[row]
+                .serialize_compressed(&mut writer)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        }
+    }
+    writer.flush()
+}
+
+/// Collects all the rows of `source` into columns with room for `capacity`
+/// rows, so that the prover can pad them without reallocating.
+///
+/// # Errors
+///
+/// Will give error if the source fails, or if it does not yield the number of
+/// rows it announced.
+pub(crate) fn collect_columns<F, S: WitnessSource<F>>(
+    source: &mut S,
+    capacity: usize,
+) -> Result<[Vec<F>; COLUMNS]> {
+    let num_rows = source.num_rows();
+    let mut columns = array::from_fn(|_| Vec::with_capacity(std::cmp::max(capacity, num_rows)));
+
+    let mut read = 0;
+    while read <= num_rows {
+        let appended = source.next_chunk(&mut columns, WITNESS_CHUNK_ROWS)?;
+        if appended == 0 {
+            break;
+        }
+        read += appended;
+    }
+
+    if read != num_rows || columns.iter().any(|col| col.len() != num_rows) {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "the witness source did not yield the expected number of rows",
+        ));
+    }
+
+    Ok(columns)
+}
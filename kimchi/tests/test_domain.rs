@@ -1,5 +1,13 @@
-use ark_ff::Field;
-use kimchi::circuits::domains::EvaluationDomains;
+use ark_ff::{FftField, Field, Zero};
+use kimchi::{
+    circuits::{
+        domains::EvaluationDomains,
+        gate::CircuitGate,
+        lookup::tables::{xor, LookupTable},
+        wires::Wire,
+    },
+    error::DomainCreationError,
+};
 use mina_curves::pasta::Fp;
 
 #[test]
@@ -14,3 +22,55 @@ fn test_create_domain() {
         println!("d1 = {:?}", d.d1.group_gen);
     }
 }
+
+#[test]
+fn test_domain_for_circuit() {
+    let gates: Vec<_> = (0..14)
+        .map(|row| CircuitGate::<Fp>::zero(Wire::for_row(row)))
+        .collect();
+
+    // Without lookup tables, the circuit needs one row per gate
+    assert_eq!(EvaluationDomains::circuit_size(&gates, &[], &[]), 14);
+    let domain = EvaluationDomains::for_circuit(&gates, &[], &[], 3).unwrap();
+    assert_eq!(domain.d1.size, 32);
+    let domain = EvaluationDomains::for_circuit(&gates[..12], &[], &[], 3).unwrap();
+    assert_eq!(domain.d1.size, 16);
+
+    // The entries of the tables, the dummy zero entry and the row closing the
+    // permutation argument
+    let table = LookupTable {
+        id: 1,
+        data: vec![vec![Fp::zero(); 20]],
+    };
+    assert_eq!(
+        EvaluationDomains::circuit_size(&gates, &[table.clone()], &[]),
+        22
+    );
+    let table = LookupTable { id: 0, ..table };
+    assert_eq!(EvaluationDomains::circuit_size(&gates, &[table], &[]), 21);
+
+    // The tables used by the gates are included
+    let (_, xor_gates) = CircuitGate::<Fp>::create_xor_gadget(0, 16);
+    assert_eq!(
+        EvaluationDomains::circuit_size(&xor_gates, &[], &[]),
+        xor::xor_table::<Fp>().data[0].len() + 2
+    );
+}
+
+#[test]
+fn test_domain_too_large() {
+    let max_size = EvaluationDomains::<Fp>::max_size();
+    assert_eq!(max_size, 1 << (Fp::TWO_ADICITY - 3));
+    assert_eq!(
+        EvaluationDomains::<Fp>::size_for(max_size).unwrap(),
+        max_size
+    );
+    assert!(matches!(
+        EvaluationDomains::<Fp>::size_for(max_size + 1),
+        Err(DomainCreationError::DomainTooLarge(n, max)) if n == max_size + 1 && max == max_size
+    ));
+    assert!(matches!(
+        EvaluationDomains::<Fp>::create(usize::MAX),
+        Err(DomainCreationError::DomainTooLarge(..))
+    ));
+}
@@ -189,3 +189,76 @@ fn test_combining_constraints_does_not_increase_degree() {
     let combined_expr = Expr::combine_constraints(0..2, vec![expr1.clone(), expr2.clone()]);
     assert_eq!(combined_expr.degree(1, 0), 3);
 }
+
+#[test]
+fn test_simplify() {
+    // Building the expressions without the simplifications of the operators
+    let mul = |x: E<Fp>, y: E<Fp>| E::Mul(Box::new(x), Box::new(y));
+    let add = |x: E<Fp>, y: E<Fp>| E::Add(Box::new(x), Box::new(y));
+    let sub = |x: E<Fp>, y: E<Fp>| E::Sub(Box::new(x), Box::new(y));
+    let minus_one = E::literal(-Fp::one());
+
+    // 1 * (-(-w0) + 0 * (w1 * w2)) = w0
+    let expr = mul(
+        E::one(),
+        add(
+            mul(minus_one.clone(), mul(minus_one.clone(), witness_curr(0))),
+            mul(E::zero(), mul(witness_curr(1), witness_curr(2))),
+        ),
+    );
+    assert_eq!(expr.degree(1, 0), 2);
+    assert_eq!(expr.simplify(), witness_curr(0));
+
+    // (w0 + 2) - (3 - w1) = w0 + w1 - 1
+    let expr = sub(
+        add(witness_curr(0), E::from(2u64)),
+        sub(E::from(3u64), witness_curr(1)),
+    );
+    assert_eq!(
+        expr.simplify(),
+        add(add(witness_curr(0), witness_curr(1)), minus_one.clone())
+    );
+
+    // 2 * (w0 * (3 * w1)) = 6 * (w0 * w1), and its opposite
+    let expr = mul(
+        E::from(2u64),
+        mul(witness_curr(0), mul(E::from(3u64), witness_curr(1))),
+    );
+    let product = mul(witness_curr(0), witness_curr(1));
+    assert_eq!(expr.simplify(), mul(E::from(6u64), product.clone()));
+    assert_eq!(
+        sub(E::zero(), expr).simplify(),
+        mul(E::literal(-Fp::from(6u64)), product)
+    );
+
+    // Literals are folded
+    let expr = E::Square(Box::new(add(E::from(2u64), E::Double(Box::new(E::one())))));
+    assert_eq!(expr.simplify(), E::from(16u64));
+    assert_eq!(
+        E::Pow(Box::new(witness_curr(0)), 1).simplify(),
+        witness_curr(0)
+    );
+}
+
+#[test]
+fn test_convert_columns() {
+    use kimchi::circuits::{
+        argument::Argument,
+        berkeley_columns::{coeff, Column},
+        generic_column::GenericColumn,
+        polynomials::xor::Xor16,
+    };
+
+    let constraints = Xor16::<Fp>::constraints(&mut Cache::default());
+    let expr = index(GateType::Xor16)
+        * coeff(0)
+        * Expr::combine_constraints(0..constraints.len() as u32, constraints);
+
+    // The columns of the gates are shared, and can be converted back
+    let generic = expr.convert_columns::<GenericColumn>().unwrap();
+    assert_eq!(generic.convert_columns::<Column>(), Some(expr.clone()));
+
+    // The permutation aggregation is specific to Kimchi
+    let expr = expr * E::cell(Column::Z, CurrOrNext::Next);
+    assert_eq!(expr.convert_columns::<GenericColumn>(), None);
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 use folding::expressions::FoldingColumnTrait;
-use kimchi::circuits::expr::{CacheId, FormattedOutput};
+use kimchi::circuits::{
+    expr::{CacheId, FormattedOutput},
+    generic_column::{GenericColumn, SharedColumn},
+};
 
 /// Describe a generic indexed variable X_{i}.
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
@@ -99,3 +102,30 @@ impl FoldingColumnTrait for Column {
         }
     }
 }
+
+/// The relation columns are the witness columns of the other systems. The
+/// lookup columns other than the fixed tables are specific to the logup
+/// argument of this crate and have no generic counterpart.
+impl SharedColumn for Column {
+    fn to_generic(self) -> Option<GenericColumn> {
+        match self {
+            Column::Relation(i) => Some(GenericColumn::Witness(i)),
+            Column::DynamicSelector(i) => Some(GenericColumn::DynamicSelector(i)),
+            Column::FixedSelector(i) => Some(GenericColumn::FixedSelector(i)),
+            Column::LookupFixedTable(id) => Some(GenericColumn::LookupTable(id)),
+            Column::LookupPartialSum(_)
+            | Column::LookupMultiplicity(_)
+            | Column::LookupAggregation => None,
+        }
+    }
+
+    fn from_generic(col: GenericColumn) -> Option<Self> {
+        match col {
+            GenericColumn::Witness(i) => Some(Column::Relation(i)),
+            GenericColumn::DynamicSelector(i) => Some(Column::DynamicSelector(i)),
+            GenericColumn::FixedSelector(i) => Some(Column::FixedSelector(i)),
+            GenericColumn::LookupTable(id) => Some(Column::LookupFixedTable(id)),
+            GenericColumn::Coefficient(_) => None,
+        }
+    }
+}
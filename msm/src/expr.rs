@@ -69,3 +69,24 @@ fn test_debug_can_be_called_on_expr() {
     use crate::{columns::Column::*, Fp};
     println!("{:}", curr_cell::<Fp>(Relation(0)) + curr_cell(Relation(1)))
 }
+
+#[test]
+fn test_kimchi_constraint_can_be_converted() {
+    use crate::Fp;
+    use kimchi::circuits::{
+        berkeley_columns::{self, E as KimchiE},
+        gate::GateType,
+    };
+
+    let kimchi_cell = |col| KimchiE::<Fp>::cell(col, CurrOrNext::Curr);
+    let constraint = kimchi_cell(berkeley_columns::Column::Index(GateType::Generic))
+        * (kimchi_cell(berkeley_columns::Column::Witness(2))
+            - kimchi_cell(berkeley_columns::Column::Witness(0)).square());
+    let expected = curr_cell::<Fp>(Column::FixedSelector(GateType::Generic as usize))
+        * (curr_cell(Column::Relation(2)) - curr_cell(Column::Relation(0)).square());
+    assert_eq!(constraint.convert_columns::<Column>(), Some(expected));
+
+    // The permutation aggregation is specific to Kimchi
+    let constraint = constraint * kimchi_cell(berkeley_columns::Column::Z);
+    assert_eq!(constraint.convert_columns::<Column>(), None);
+}
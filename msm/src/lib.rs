@@ -5,7 +5,7 @@ use mina_poseidon::{
 use poly_commitment::kzg::KZGProof;
 
 pub use logup::{
-    Logup, LogupWitness, LookupProof as LogupProof, LookupTable as LogupTable,
+    Logup, LogupArgument, LogupWitness, LookupProof as LogupProof, LookupTable as LogupTable,
     LookupTableID as LogupTableID, LookupTableID,
 };
 
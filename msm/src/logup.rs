@@ -143,7 +143,12 @@ use kimchi::circuits::{
     berkeley_columns::BerkeleyChallengeTerm,
     expr::{ConstantExpr, ConstantTerm, Expr, ExprInner},
 };
-use std::{collections::BTreeMap, hash::Hash};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+use thiserror::Error;
 
 use crate::{
     columns::Column,
@@ -272,16 +277,45 @@ pub struct LogupWitness<F, ID: LookupTableID> {
 /// - `F` for the evaluations at ζ (resp. ζω).
 // FIXME: We should have a fixed number of m and h. Should we encode that in
 // the type?
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>, ID: Deserialize<'de> + Ord"))]
 pub struct LookupProof<T, ID> {
     /// The multiplicity polynomials
-    pub(crate) m: BTreeMap<ID, Vec<T>>,
+    pub m: BTreeMap<ID, Vec<T>>,
     /// The polynomial keeping the sum of each row
-    pub(crate) h: BTreeMap<ID, Vec<T>>,
+    pub h: BTreeMap<ID, Vec<T>>,
     /// The "running-sum" over the rows, coined `φ`
-    pub(crate) sum: T,
+    pub sum: T,
     /// All fixed lookup tables values, indexed by their ID
-    pub(crate) fixed_tables: BTreeMap<ID, T>,
+    pub fixed_tables: BTreeMap<ID, T>,
+}
+
+impl<T, ID: Ord> LookupProof<T, ID> {
+    /// Applies `f` to each polynomial of the proof, e.g. to change the
+    /// representation of the evaluations
+    pub fn map<T2, FN: Fn(T) -> T2>(self, f: &FN) -> LookupProof<T2, ID> {
+        let LookupProof {
+            m,
+            h,
+            sum,
+            fixed_tables,
+        } = self;
+        let map_all = |polys: BTreeMap<ID, Vec<T>>| {
+            polys
+                .into_iter()
+                .map(|(id, polys)| (id, polys.into_iter().map(f).collect()))
+                .collect()
+        };
+        LookupProof {
+            m: map_all(m),
+            h: map_all(h),
+            sum: f(sum),
+            fixed_tables: fixed_tables
+                .into_iter()
+                .map(|(id, poly)| (id, f(poly)))
+                .collect(),
+        }
+    }
 }
 
 /// Iterator implementation to abstract the content of the structure.
@@ -417,6 +451,32 @@ pub fn combine_lookups<F: PrimeField, ID: LookupTableID>(
 pub fn constraint_lookups<F: PrimeField, ID: LookupTableID>(
     lookup_reads: &BTreeMap<ID, Vec<Vec<E<F>>>>,
     lookup_writes: &BTreeMap<ID, Vec<Vec<E<F>>>>,
+) -> Vec<E<F>> {
+    let lookup_reads = lookup_reads
+        .iter()
+        .map(|(table_id, reads)| {
+            let reads = reads
+                .iter()
+                .map(|value| (one_expr(), value.clone()))
+                .collect();
+            (*table_id, reads)
+        })
+        .collect();
+    constraint_lookups_with_numerators(&lookup_reads, lookup_writes)
+}
+
+fn one_expr<F: PrimeField>() -> E<F> {
+    Expr::Atom(ExprInner::Constant(ConstantExpr::from(
+        ConstantTerm::Literal(F::one()),
+    )))
+}
+
+/// Same as [constraint_lookups], where each value read is given with the
+/// numerator of its fraction, e.g. a selector enabling the lookup on some rows
+/// only. The numerators must be of degree at most one.
+pub fn constraint_lookups_with_numerators<F: PrimeField, ID: LookupTableID>(
+    lookup_reads: &BTreeMap<ID, Vec<(E<F>, Vec<E<F>>)>>,
+    lookup_writes: &BTreeMap<ID, Vec<Vec<E<F>>>>,
 ) -> Vec<E<F>> {
     let mut constraints: Vec<E<F>> = vec![];
     let mut lookup_terms_cols: Vec<Column> = vec![];
@@ -428,11 +488,9 @@ pub fn constraint_lookups<F: PrimeField, ID: LookupTableID>(
         let mut lookups: Vec<_> = reads
             .clone()
             .into_iter()
-            .map(|value| Logup {
+            .map(|(numerator, value)| Logup {
                 table_id: *table_id,
-                numerator: Expr::Atom(ExprInner::Constant(ConstantExpr::from(
-                    ConstantTerm::Literal(F::one()),
-                ))),
+                numerator,
                 value,
             })
             .collect();
@@ -481,6 +539,282 @@ pub fn constraint_lookups<F: PrimeField, ID: LookupTableID>(
     constraints
 }
 
+/// Errors that can arise when building the witness of a logUp argument
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LogupError<ID: core::fmt::Debug> {
+    #[error("the table {0:?} is not declared in the argument")]
+    UndeclaredTable(ID),
+    #[error("the table {0:?} has {1} lookups in the argument, got the values of {2}")]
+    WrongNumberOfLookups(ID, usize, usize),
+    #[error("the table {0:?} has {1} multiplicity columns in the argument, got {2} table columns")]
+    WrongNumberOfTableColumns(ID, usize, usize),
+    #[error("the values of the table {0:?} do not have one value per row")]
+    LengthMismatch(ID),
+    #[error("the value looked up in the table {0:?} at row {1} is not in the table")]
+    ValueNotInTable(ID, usize),
+}
+
+/// A logUp argument, declared independently of the circuit using it: the
+/// tables, and the values looked up in each of them as expressions over the
+/// columns of the circuit.
+///
+/// The argument allocates the columns it needs, see
+/// [LogupArgument::multiplicity_columns] and
+/// [LogupArgument::partial_sum_columns], and gives the constraints of the
+/// partial sums and of their aggregation with [LogupArgument::constraints].
+/// The witness of a table is built with [LogupArgument::witness], which counts
+/// the multiplicities.
+///
+/// A fixed table, or a runtime table with
+/// [runtime_create_column](LookupTableID::runtime_create_column), is the column
+/// [Column::LookupFixedTable] with one multiplicity column. The other runtime
+/// tables are made of existing columns, declared with
+/// [LogupArgument::add_table_column], each one with its own multiplicity
+/// column.
+#[derive(Debug, Clone)]
+pub struct LogupArgument<F: PrimeField, ID: LookupTableID> {
+    /// The values looked up in each table, one vector of expressions per
+    /// lookup, with the numerator of its fraction
+    reads: BTreeMap<ID, Vec<(E<F>, Vec<E<F>>)>>,
+    /// The columns of the runtime tables made of existing columns
+    writes: BTreeMap<ID, Vec<Vec<E<F>>>>,
+}
+
+impl<F: PrimeField, ID: LookupTableID> Default for LogupArgument<F, ID> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField, ID: LookupTableID> LogupArgument<F, ID> {
+    pub fn new() -> Self {
+        Self {
+            reads: BTreeMap::new(),
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Declares the table `table_id`, if it is not already declared
+    pub fn add_table(&mut self, table_id: ID) {
+        self.reads.entry(table_id).or_default();
+        if !table_id.is_fixed() && !table_id.runtime_create_column() {
+            self.writes.entry(table_id).or_default();
+        }
+    }
+
+    /// Adds the column `value` to the runtime table `table_id`, declaring the
+    /// table if needed. Panics if the table is fixed or creates its own column.
+    pub fn add_table_column(&mut self, table_id: ID, value: Vec<E<F>>) {
+        assert!(
+            !table_id.is_fixed() && !table_id.runtime_create_column(),
+            "the table {table_id:?} does not have columns made of existing columns"
+        );
+        self.add_table(table_id);
+        self.writes.get_mut(&table_id).unwrap().push(value);
+    }
+
+    /// Looks up `value` in the table `table_id` at each row, declaring the
+    /// table if needed
+    pub fn add_lookup(&mut self, table_id: ID, value: Vec<E<F>>) {
+        self.add_lookup_with_numerator(table_id, one_expr(), value);
+    }
+
+    /// Looks up `value` in the table `table_id`, with the numerator
+    /// `numerator` at each row, declaring the table if needed. The lookup is
+    /// disabled on the rows where the numerator is zero, e.g. if it is a
+    /// selector. The numerator must be of degree at most one.
+    pub fn add_lookup_with_numerator(&mut self, table_id: ID, numerator: E<F>, value: Vec<E<F>>) {
+        self.add_table(table_id);
+        self.reads
+            .get_mut(&table_id)
+            .unwrap()
+            .push((numerator, value));
+    }
+
+    /// The declared tables
+    pub fn tables(&self) -> Vec<ID> {
+        self.reads.keys().copied().collect()
+    }
+
+    /// The number of lookups in the table `table_id`
+    pub fn number_of_lookups(&self, table_id: ID) -> usize {
+        self.reads.get(&table_id).map_or(0, |reads| reads.len())
+    }
+
+    /// The number of multiplicity columns of the table `table_id`
+    fn number_of_multiplicities(&self, table_id: ID) -> usize {
+        if !self.reads.contains_key(&table_id) {
+            0
+        } else if table_id.is_fixed() || table_id.runtime_create_column() {
+            1
+        } else {
+            self.writes.get(&table_id).map_or(0, |writes| writes.len())
+        }
+    }
+
+    /// The multiplicity columns allocated for each table
+    pub fn multiplicity_columns(&self) -> BTreeMap<ID, Vec<Column>> {
+        self.reads
+            .keys()
+            .map(|table_id| {
+                let columns = (0..self.number_of_multiplicities(*table_id))
+                    .map(|i| Column::LookupMultiplicity((table_id.to_u32(), i)))
+                    .collect();
+                (*table_id, columns)
+            })
+            .collect()
+    }
+
+    /// The partial sum columns allocated for each table, each one summing
+    /// at most `MAX_SUPPORTED_DEGREE - 2` terms, see [constraint_lookups]
+    pub fn partial_sum_columns(&self) -> BTreeMap<ID, Vec<Column>> {
+        self.reads
+            .iter()
+            .map(|(table_id, reads)| {
+                let terms = reads.len() + self.number_of_multiplicities(*table_id);
+                let chunk_size = MAX_SUPPORTED_DEGREE - 2;
+                let columns = (0..(terms + chunk_size - 1) / chunk_size)
+                    .map(|i| Column::LookupPartialSum((table_id.to_u32(), i)))
+                    .collect();
+                (*table_id, columns)
+            })
+            .collect()
+    }
+
+    /// The constraints of the partial sums and of their aggregation, or no
+    /// constraint if no table is declared
+    pub fn constraints(&self) -> Vec<E<F>> {
+        if self.reads.is_empty() {
+            return vec![];
+        }
+        constraint_lookups_with_numerators(&self.reads, &self.writes)
+    }
+
+    /// Builds the witness of the table `table_id`, for a domain of size
+    /// `domain_size`. `lookups` are the values of each lookup of the table at
+    /// each row, in the order they were added, and `table` the values of each
+    /// column of the table, see [logup_witness]. The numerators of the lookups
+    /// are taken to be one, see [logup_witness_with_numerators] otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the table is not declared, if the number of lookups
+    /// or of table columns is not the one of the argument, or if the values
+    /// can not be looked up, see [logup_witness].
+    pub fn witness(
+        &self,
+        table_id: ID,
+        lookups: Vec<Vec<Vec<F>>>,
+        table: Vec<Vec<Vec<F>>>,
+        domain_size: usize,
+    ) -> Result<LogupWitness<F, ID>, LogupError<ID>> {
+        if !self.reads.contains_key(&table_id) {
+            return Err(LogupError::UndeclaredTable(table_id));
+        }
+        let expected = self.number_of_lookups(table_id);
+        if lookups.len() != expected {
+            return Err(LogupError::WrongNumberOfLookups(
+                table_id,
+                expected,
+                lookups.len(),
+            ));
+        }
+        let expected = self.number_of_multiplicities(table_id);
+        if table.len() != expected {
+            return Err(LogupError::WrongNumberOfTableColumns(
+                table_id,
+                expected,
+                table.len(),
+            ));
+        }
+        logup_witness(table_id, lookups, table, domain_size)
+    }
+}
+
+/// Builds the witness of the lookups `lookups` in the table `table_id`, for a
+/// domain of size `domain_size`. Each lookup and each column of the table
+/// `table` must have a value per row. The multiplicity of an entry appearing
+/// several times in the table is counted on its first occurrence, the other
+/// ones having a multiplicity of zero, so that the table can be padded with
+/// one of its entries.
+///
+/// # Errors
+///
+/// Will give error if a lookup or a column of the table does not have a value
+/// per row, or if a value looked up is not in the table.
+pub fn logup_witness<F: PrimeField, ID: LookupTableID>(
+    table_id: ID,
+    lookups: Vec<Vec<Vec<F>>>,
+    table: Vec<Vec<Vec<F>>>,
+    domain_size: usize,
+) -> Result<LogupWitness<F, ID>, LogupError<ID>> {
+    let lookups = lookups
+        .into_iter()
+        .map(|lookup| lookup.into_iter().map(|value| (F::one(), value)).collect())
+        .collect();
+    logup_witness_with_numerators(table_id, lookups, table, domain_size)
+}
+
+/// Same as [logup_witness], where each value looked up is given with the
+/// numerator of its fraction, see
+/// [add_lookup_with_numerator](LogupArgument::add_lookup_with_numerator). The
+/// multiplicity of an entry is the sum of the numerators of its lookups, and
+/// the values whose numerator is zero do not have to be in the table.
+///
+/// # Errors
+///
+/// See [logup_witness].
+pub fn logup_witness_with_numerators<F: PrimeField, ID: LookupTableID>(
+    table_id: ID,
+    lookups: Vec<Vec<(F, Vec<F>)>>,
+    table: Vec<Vec<Vec<F>>>,
+    domain_size: usize,
+) -> Result<LogupWitness<F, ID>, LogupError<ID>> {
+    if table.iter().any(|column| column.len() != domain_size) {
+        return Err(LogupError::LengthMismatch(table_id));
+    }
+    let mut index: HashMap<&Vec<F>, (usize, usize)> = HashMap::new();
+    for (i, column) in table.iter().enumerate() {
+        for (row, entry) in column.iter().enumerate() {
+            index.entry(entry).or_insert((i, row));
+        }
+    }
+    let mut m = vec![vec![F::zero(); domain_size]; table.len()];
+    let mut f = Vec::with_capacity(lookups.len() + table.len());
+    for lookup in lookups.iter() {
+        if lookup.len() != domain_size {
+            return Err(LogupError::LengthMismatch(table_id));
+        }
+        for (row, (numerator, value)) in lookup.iter().enumerate() {
+            if numerator.is_zero() {
+                continue;
+            }
+            match index.get(value) {
+                Some(&(i, j)) => m[i][j] += numerator,
+                None => return Err(LogupError::ValueNotInTable(table_id, row)),
+            }
+        }
+        f.push(
+            lookup
+                .iter()
+                .map(|(numerator, value)| Logup::new(table_id, *numerator, value))
+                .collect(),
+        );
+    }
+    // The columns of the table come last, with the opposite of the
+    // multiplicities
+    for (column, m) in table.iter().zip(m.iter()) {
+        f.push(
+            column
+                .iter()
+                .zip(m.iter())
+                .map(|(entry, m)| Logup::new(table_id, -*m, entry))
+                .collect(),
+        );
+    }
+    Ok(LogupWitness { f, m })
+}
+
 pub mod prover {
     use crate::{
         logup::{Logup, LogupWitness, LookupTableID},
@@ -494,6 +828,7 @@ pub mod prover {
         commitment::{absorb_commitment, PolyComm},
         OpenProof, SRS as _,
     };
+    use rand::{CryptoRng, RngCore};
     use rayon::iter::{IntoParallelIterator, ParallelIterator};
     use std::collections::BTreeMap;
 
@@ -517,16 +852,22 @@ pub mod prover {
         pub lookup_counters_poly_d1: BTreeMap<ID, Vec<DensePolynomial<G::ScalarField>>>,
         /// The commitments to the multiplicities, indexed by the table ID.
         pub lookup_counters_comm_d1: BTreeMap<ID, Vec<PolyComm<G>>>,
+        /// The blinders of the commitments to the multiplicities.
+        pub lookup_counters_blinders_d1: BTreeMap<ID, Vec<PolyComm<G::ScalarField>>>,
 
         /// The polynomials of the inner sums.
         pub lookup_terms_poly_d1: BTreeMap<ID, Vec<DensePolynomial<G::ScalarField>>>,
         /// The commitments of the inner sums.
         pub lookup_terms_comms_d1: BTreeMap<ID, Vec<PolyComm<G>>>,
+        /// The blinders of the commitments of the inner sums.
+        pub lookup_terms_blinders_d1: BTreeMap<ID, Vec<PolyComm<G::ScalarField>>>,
 
         /// The aggregation polynomial.
         pub lookup_aggregation_poly_d1: DensePolynomial<G::ScalarField>,
         /// The commitment to the aggregation polynomial.
         pub lookup_aggregation_comm_d1: PolyComm<G>,
+        /// The blinder of the commitment to the aggregation polynomial.
+        pub lookup_aggregation_blinders_d1: PolyComm<G::ScalarField>,
 
         // Evaluating over d8 for the quotient polynomial
         #[allow(clippy::type_complexity)]
@@ -555,18 +896,45 @@ pub mod prover {
         /// committed as part of the columns.
         /// Therefore, the protocol only focus on commiting to the "grand
         /// product sum" and the "row-accumulated" values.
+        /// The commitments to the multiplicities, the inner sums and the
+        /// aggregation are hidden with blinders sampled from `rng`, while the
+        /// fixed tables are committed without blinder, so that the verifier can
+        /// compute their commitments.
         pub fn create<
             OpeningProof: OpenProof<G>,
             Sponge: FqSponge<G::BaseField, G, G::ScalarField>,
+            RNG: RngCore + CryptoRng,
         >(
             lookups: BTreeMap<ID, LogupWitness<G::ScalarField, ID>>,
             domain: EvaluationDomains<G::ScalarField>,
             fq_sponge: &mut Sponge,
             srs: &OpeningProof::SRS,
+            rng: &mut RNG,
         ) -> Self
         where
             OpeningProof::SRS: Sync,
         {
+            // Hides the commitments, sequentially as the blinders are sampled
+            // from `rng`
+            let mut mask = |comms: BTreeMap<ID, Vec<PolyComm<G>>>| {
+                let mut blinders: BTreeMap<ID, Vec<PolyComm<G::ScalarField>>> = BTreeMap::new();
+                let comms: BTreeMap<ID, Vec<PolyComm<G>>> = comms
+                    .into_iter()
+                    .map(|(id, comms)| {
+                        let (comms, id_blinders): (Vec<_>, Vec<_>) = comms
+                            .into_iter()
+                            .map(|comm| {
+                                let comm = srs.mask(comm, &mut *rng);
+                                (comm.commitment, comm.blinders)
+                            })
+                            .unzip();
+                        blinders.insert(id, id_blinders);
+                        (id, comms)
+                    })
+                    .collect();
+                (comms, blinders)
+            };
+
             // Use parallel iterators where possible.
             // Polynomial m(X)
             // FIXME/IMPROVEME: m(X) is only for fixed table
@@ -615,7 +983,7 @@ pub mod prover {
                 })
                 .collect();
 
-            let lookup_counters_comm_d1: BTreeMap<ID, Vec<PolyComm<G>>> =
+            let (lookup_counters_comm_d1, lookup_counters_blinders_d1) = mask(
                 (&lookup_counters_evals_d1)
                     .into_par_iter()
                     .map(|(id, polys)| {
@@ -627,7 +995,8 @@ pub mod prover {
                                 .collect(),
                         )
                     })
-                    .collect();
+                    .collect(),
+            );
 
             lookup_counters_comm_d1.values().for_each(|comms| {
                 comms
@@ -847,18 +1216,20 @@ pub mod prover {
                 .map(|(id, poly)| (*id, poly.evaluate_over_domain_by_ref(domain.d8)))
                 .collect();
 
-            let lookup_terms_comms_d1: BTreeMap<ID, Vec<PolyComm<G>>> = lookup_terms_evals_d1
-                .iter()
-                .map(|(id, lookup_terms)| {
-                    let lookup_terms = lookup_terms
-                        .into_par_iter()
-                        .map(|lookup_term| {
-                            srs.commit_evaluations_non_hiding(domain.d1, lookup_term)
-                        })
-                        .collect();
-                    (*id, lookup_terms)
-                })
-                .collect();
+            let (lookup_terms_comms_d1, lookup_terms_blinders_d1) = mask(
+                lookup_terms_evals_d1
+                    .iter()
+                    .map(|(id, lookup_terms)| {
+                        let lookup_terms = lookup_terms
+                            .into_par_iter()
+                            .map(|lookup_term| {
+                                srs.commit_evaluations_non_hiding(domain.d1, lookup_term)
+                            })
+                            .collect();
+                        (*id, lookup_terms)
+                    })
+                    .collect(),
+            );
 
             let fixed_lookup_tables_comms_d1: BTreeMap<ID, PolyComm<G>> =
                 (&fixed_lookup_tables_evals_d1)
@@ -886,11 +1257,10 @@ pub mod prover {
             // - φ(ω^n) = 0
             let lookup_aggregation_evals_d1 = {
                 {
-                    for table_id in ID::all_variants().into_iter() {
+                    for (table_id, lookup_terms) in lookup_terms_evals_d1.iter() {
                         let mut acc = G::ScalarField::zero();
                         for i in 0..domain.d1.size as usize {
                             // φ(1) = 0
-                            let lookup_terms = lookup_terms_evals_d1.get(&table_id).unwrap();
                             acc = lookup_terms.iter().fold(acc, |acc, lte| acc + lte[i]);
                         }
                         // Sanity check to verify that the accumulator ends up being zero.
@@ -928,19 +1298,24 @@ pub mod prover {
             let lookup_aggregation_evals_d8 =
                 lookup_aggregation_poly_d1.evaluate_over_domain_by_ref(domain.d8);
 
-            let lookup_aggregation_comm_d1 =
-                srs.commit_evaluations_non_hiding(domain.d1, &lookup_aggregation_evals_d1);
+            let lookup_aggregation_comm_d1 = srs.mask(
+                srs.commit_evaluations_non_hiding(domain.d1, &lookup_aggregation_evals_d1),
+                rng,
+            );
 
-            absorb_commitment(fq_sponge, &lookup_aggregation_comm_d1);
+            absorb_commitment(fq_sponge, &lookup_aggregation_comm_d1.commitment);
             Self {
                 lookup_counters_poly_d1,
                 lookup_counters_comm_d1,
+                lookup_counters_blinders_d1,
 
                 lookup_terms_poly_d1,
                 lookup_terms_comms_d1,
+                lookup_terms_blinders_d1,
 
                 lookup_aggregation_poly_d1,
-                lookup_aggregation_comm_d1,
+                lookup_aggregation_comm_d1: lookup_aggregation_comm_d1.commitment,
+                lookup_aggregation_blinders_d1: lookup_aggregation_comm_d1.blinders,
 
                 lookup_counters_evals_d8,
                 lookup_terms_evals_d8,
@@ -161,11 +161,12 @@ where
 
     // -- Start Logup
     let lookup_env = if !inputs.logups.is_empty() {
-        Some(Env::create::<OpeningProof, EFqSponge>(
+        Some(Env::create::<OpeningProof, EFqSponge, RNG>(
             inputs.logups,
             domain,
             &mut fq_sponge,
             srs,
+            rng,
         ))
     } else {
         None
@@ -520,29 +521,25 @@ where
             lookup_env
                 .lookup_counters_poly_d1
                 .values()
-                .flat_map(|polys| {
-                    polys
-                        .iter()
-                        .map(|poly| (coefficients_form(poly), non_hiding(1)))
-                        .collect::<Vec<_>>()
-                })
+                .flatten()
+                .zip(lookup_env.lookup_counters_blinders_d1.values().flatten())
+                .map(|(poly, blinders)| (coefficients_form(poly), blinders.clone()))
                 .collect::<Vec<_>>(),
         );
         // -- after that the partial sums
-        polynomials.extend({
-            let polys = lookup_env.lookup_terms_poly_d1.values().map(|polys| {
-                polys
-                    .iter()
-                    .map(|poly| (coefficients_form(poly), non_hiding(1)))
-                    .collect::<Vec<_>>()
-            });
-            let polys: Vec<_> = polys.flatten().collect();
-            polys
-        });
+        polynomials.extend(
+            lookup_env
+                .lookup_terms_poly_d1
+                .values()
+                .flatten()
+                .zip(lookup_env.lookup_terms_blinders_d1.values().flatten())
+                .map(|(poly, blinders)| (coefficients_form(poly), blinders.clone()))
+                .collect::<Vec<_>>(),
+        );
         // -- after that the running sum
         polynomials.push((
             coefficients_form(&lookup_env.lookup_aggregation_poly_d1),
-            non_hiding(1),
+            lookup_env.lookup_aggregation_blinders_d1.clone(),
         ));
         // -- Adding fixed lookup tables
         polynomials.extend(
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
     use crate::{
+        columns::Column,
+        expr::{curr_cell, E},
+        logup::{LogupArgument, LogupError, LookupTableID},
         lookups::{Lookup, LookupTableIDs},
         proof::ProofInputs,
         prover::prove,
@@ -8,7 +11,7 @@ mod tests {
         witness::Witness,
         BaseSponge, Fp, OpeningProof, ScalarSponge, BN254,
     };
-    use ark_ff::UniformRand;
+    use ark_ff::{One, UniformRand, Zero};
     use kimchi::circuits::domains::EvaluationDomains;
     use poly_commitment::{kzg::PairingSRS, SRS as _};
 
@@ -76,4 +79,81 @@ mod tests {
         // FIXME: At the moment, it does verify. It should not. We are missing constraints.
         assert!(!verifies);
     }
+
+    #[test]
+    fn test_logup_argument_columns_and_witness() {
+        let table_id = LookupTableIDs::RangeCheck16;
+        let mut argument = LogupArgument::<Fp, LookupTableIDs>::new();
+        for i in 0..7 {
+            argument.add_lookup(table_id, vec![curr_cell(Column::Relation(i))]);
+        }
+        assert_eq!(argument.tables(), vec![table_id]);
+        assert_eq!(argument.number_of_lookups(table_id), 7);
+
+        // The 7 lookups and the table are split into two partial sums
+        let id = table_id.to_u32();
+        assert_eq!(
+            argument.multiplicity_columns()[&table_id],
+            vec![Column::LookupMultiplicity((id, 0))]
+        );
+        assert_eq!(
+            argument.partial_sum_columns()[&table_id],
+            vec![
+                Column::LookupPartialSum((id, 0)),
+                Column::LookupPartialSum((id, 1))
+            ]
+        );
+        let constraints: Vec<E<Fp>> = argument.constraints();
+        assert_eq!(constraints.len(), 3);
+
+        // A table of 4 entries in a domain of 8 rows, padded with its first
+        // entry
+        let domain_size = 8;
+        let table: Vec<Vec<Fp>> = [0, 1, 2, 3, 0, 0, 0, 0]
+            .into_iter()
+            .map(|x| vec![Fp::from(x as u64)])
+            .collect();
+        let lookup = |x: u64| vec![vec![Fp::from(x)]; domain_size];
+        let lookups: Vec<_> = (0..7).map(|i| lookup(i % 4)).collect();
+        let witness = argument
+            .witness(table_id, lookups.clone(), vec![table.clone()], domain_size)
+            .unwrap();
+        assert_eq!(witness.f.len(), 8);
+        let m: Vec<Fp> = [16, 16, 16, 8, 0, 0, 0, 0]
+            .into_iter()
+            .map(|x| Fp::from(x as u64))
+            .collect();
+        assert_eq!(witness.m, vec![m]);
+        // The sum of the fractions is zero when the numerators are the
+        // multiplicities of the values
+        let sum: Fp = witness
+            .f
+            .iter()
+            .flatten()
+            .map(|logup| logup.numerator)
+            .sum();
+        assert!(sum.is_zero());
+        assert_eq!(witness.f[0][0].numerator, Fp::one());
+
+        // Wrong inputs
+        assert_eq!(
+            argument.witness(
+                table_id,
+                lookups[..6].to_vec(),
+                vec![table.clone()],
+                domain_size
+            ),
+            Err(LogupError::WrongNumberOfLookups(table_id, 7, 6))
+        );
+        let mut wrong_lookups = lookups;
+        wrong_lookups[2][5] = vec![Fp::from(4u64)];
+        assert_eq!(
+            argument.witness(table_id, wrong_lookups, vec![table], domain_size),
+            Err(LogupError::ValueNotInTable(table_id, 5))
+        );
+        assert_eq!(
+            argument.witness(LookupTableIDs::Custom(0), vec![], vec![], domain_size),
+            Err(LogupError::UndeclaredTable(LookupTableIDs::Custom(0)))
+        );
+    }
 }
@@ -0,0 +1,94 @@
+//! Benchmark of the pickles prover on a full-size MIPS trace, with a single
+//! thread and with the default rayon thread pool, to measure the speedup of
+//! committing to and evaluating the columns in parallel.
+
+use ark_ff::UniformRand;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use kimchi::circuits::domains::EvaluationDomains;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use o1vm::{
+    interpreters::mips::{
+        column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
+        constraints as mips_constraints,
+    },
+    pickles::{
+        lookup::LookupValues,
+        proof::{ProofInputs, WitnessColumns},
+        prover::prove,
+    },
+};
+use poly_commitment::{ipa::SRS, SRS as _};
+use rand::Rng;
+
+const DOMAIN_SIZE: usize = 1 << 15;
+
+/// A random trace, with a single selector set at each row
+fn random_inputs<RNG: Rng>(rng: &mut RNG) -> ProofInputs<Vesta> {
+    let mut random_column = || (0..DOMAIN_SIZE).map(|_| Fp::rand(rng)).collect::<Vec<_>>();
+    ProofInputs {
+        evaluations: WitnessColumns {
+            scratch: std::array::from_fn(|_| random_column()),
+            scratch_inverse: std::array::from_fn(|_| random_column()),
+            instruction_counter: random_column(),
+            error: random_column(),
+            selector: (0..DOMAIN_SIZE)
+                .map(|_| Fp::from(rng.gen_range(0..N_MIPS_SEL_COLS) as u64))
+                .collect(),
+        },
+        public: vec![],
+        lookups: LookupValues::new(),
+    }
+}
+
+fn bench_pickles_prover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Pickles prover");
+    group.sample_size(10);
+
+    let domain = EvaluationDomains::<Fp>::create(DOMAIN_SIZE).unwrap();
+    let srs: SRS<Vesta> = SRS::create(DOMAIN_SIZE);
+    srs.get_lagrange_basis(domain.d1);
+    // The constraints of the selectors, which hold on a random trace. The cost
+    // of the prover is dominated by the columns.
+    let constraints = mips_constraints::Env::<Fp>::default().get_selector_constraints();
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let mut prover_rng = o1_utils::tests::make_test_rng(None);
+
+    let columns = SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 2 + N_MIPS_SEL_COLS;
+    for threads in [1, rayon::current_num_threads()] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new(format!("prove {columns} columns, threads"), threads),
+            &threads,
+            |b, _| {
+                b.iter_batched(
+                    || random_inputs(&mut rng),
+                    |inputs| {
+                        pool.install(|| {
+                            prove::<
+                                Vesta,
+                                DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>,
+                                DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>,
+                                _,
+                            >(
+                                domain, &srs, inputs, &constraints, &mut prover_rng
+                            )
+                            .unwrap()
+                        })
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pickles_prover);
+criterion_main!(benches);
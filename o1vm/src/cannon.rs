@@ -210,11 +210,21 @@ pub struct VmConfiguration {
     pub proof_at: StepFrequency,
     pub stop_at: StepFrequency,
     pub snapshot_state_at: StepFrequency,
+    /// When to write a snapshot of the witness environment, from which the
+    /// execution can be resumed
+    pub checkpoint_at: StepFrequency,
     pub info_at: StepFrequency,
     pub proof_fmt: String,
     pub snapshot_fmt: String,
     pub pprof_cpu: bool,
     pub host: Option<HostProgram>,
+    /// The file whose content is given to the program on its standard input
+    pub stdin: Option<String>,
+    /// The snapshot of the witness environment to resume the execution from,
+    /// instead of the input state
+    pub resume: Option<String>,
+    /// The maximum number of instructions the proven execution can run
+    pub max_cycles: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -9,6 +9,13 @@ pub fn main_cli() -> clap::Command {
         .arg(arg!(--input <FILE> "initial state file").default_value("state.json"))
         .arg(arg!(--output <FILE> "output state file").default_value("out.json"))
         .arg(arg!(--meta <FILE> "metadata file").default_value("meta.json"))
+        .arg(arg!(--stdin <FILE> "file given to the program on its standard input").required(false))
+        .arg(arg!(--resume <FILE> "checkpoint to resume the execution from").required(false))
+        .arg(
+            arg!(--"max-cycles" <N> "maximum number of instructions of the proven execution")
+                .required(false)
+                .value_parser(value_parser!(u64)),
+        )
         // The CLI arguments below this line are ignored at this point
         .arg(
             Arg::new("proof-at")
@@ -62,6 +69,13 @@ pub fn main_cli() -> clap::Command {
                 .default_value("never")
                 .value_parser(step_frequency_parser),
         )
+        .arg(
+            Arg::new("checkpoint-at")
+                .long("checkpoint-at")
+                .value_name("FREQ")
+                .default_value("never")
+                .value_parser(step_frequency_parser),
+        )
 }
 
 pub fn read_configuration(cli: &clap::ArgMatches) -> VmConfiguration {
@@ -73,10 +87,14 @@ pub fn read_configuration(cli: &clap::ArgMatches) -> VmConfiguration {
     let info_at = cli.get_one::<StepFrequency>("info-at").unwrap();
     let stop_at = cli.get_one::<StepFrequency>("stop-at").unwrap();
     let snapshot_state_at = cli.get_one::<StepFrequency>("snapshot-state-at").unwrap();
+    let checkpoint_at = cli.get_one::<StepFrequency>("checkpoint-at").unwrap();
 
     let proof_fmt = cli.get_one::<String>("proof-fmt").unwrap();
     let snapshot_fmt = cli.get_one::<String>("snapshot-fmt").unwrap();
     let pprof_cpu = cli.get_one::<bool>("pprof-cpu").unwrap();
+    let stdin = cli.get_one::<String>("stdin");
+    let resume = cli.get_one::<String>("resume");
+    let max_cycles = cli.get_one::<u64>("max-cycles");
 
     let host_spec = cli
         .get_many::<String>("host")
@@ -103,10 +121,14 @@ pub fn read_configuration(cli: &clap::ArgMatches) -> VmConfiguration {
         proof_at: proof_at.clone(),
         stop_at: stop_at.clone(),
         snapshot_state_at: snapshot_state_at.clone(),
+        checkpoint_at: checkpoint_at.clone(),
         info_at: info_at.clone(),
         proof_fmt: proof_fmt.to_string(),
         snapshot_fmt: snapshot_fmt.to_string(),
         pprof_cpu: *pprof_cpu,
         host,
+        stdin: stdin.cloned(),
+        resume: resume.cloned(),
+        max_cycles: max_cycles.copied(),
     }
 }
@@ -0,0 +1,421 @@
+//! The `o1vm` binary, executing, tracing, proving a program with the pickles
+//! flavor of the o1vm, and verifying its proofs, see [o1vm::cli].
+
+use kimchi::circuits::domains::EvaluationDomains;
+use log::{debug, error, info};
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use o1vm::{
+    cannon::{self, Meta, Start, State, VmConfiguration},
+    cli::{self, CliCommand, ExecutionConfiguration, ProgramSource, TraceFormat},
+    elf_loader::{self, Architecture},
+    interpreters::mips::{boundary::BoundaryState, witness::Env},
+    pickles::{
+        continuation::{self, Aggregator, ChunkProof},
+        get_mips_constraints,
+        keccak::{self, KeccakCoprocessor, KeccakProof},
+        proof::Proof,
+    },
+    preimage_oracle::{PreImageOracle, PreImageOracleT, PreImageStore},
+};
+use poly_commitment::{ipa::SRS, SRS as _};
+use std::{fs::File, io::BufReader, path::Path, process::ExitCode, time::Instant};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// Reads the initial state of the program, with the symbols of its functions
+fn load_program(program: &ProgramSource) -> Result<(State, Meta), String> {
+    match program {
+        ProgramSource::Elf(elf) => {
+            let program = elf_loader::parse_elf(Architecture::Mips, elf)
+                .map_err(|e| format!("Could not load the ELF file {}: {e}", elf.display()))?;
+            Ok((program.state, program.meta))
+        }
+        ProgramSource::State { state, meta } => {
+            let file = File::open(state)
+                .map_err(|e| format!("Could not open the state file {}: {e}", state.display()))?;
+            let state: State = serde_json::from_reader(BufReader::new(file))
+                .map_err(|e| format!("Could not read the state file: {e}"))?;
+            let meta = match meta {
+                Some(meta) => {
+                    let file = File::open(meta).map_err(|e| {
+                        format!("Could not open the metadata file {}: {e}", meta.display())
+                    })?;
+                    serde_json::from_reader(BufReader::new(file))
+                        .map_err(|e| format!("Could not read the metadata file: {e}"))?
+                }
+                None => Meta { symbols: vec![] },
+            };
+            Ok((state, meta))
+        }
+    }
+}
+
+fn srs(domain: EvaluationDomains<Fp>, chunk_size: usize) -> SRS<Vesta> {
+    let srs = SRS::create(chunk_size);
+    srs.get_lagrange_basis(domain.d1);
+    srs
+}
+
+fn domain(chunk_size: usize) -> Result<EvaluationDomains<Fp>, String> {
+    EvaluationDomains::<Fp>::create(chunk_size)
+        .map_err(|e| format!("Invalid chunk size {chunk_size}: {e}"))
+}
+
+/// Executes the program until it halts, without recording its execution trace
+fn run<PreImageOracle: PreImageOracleT>(
+    env: &mut Env<Fp, PreImageOracle>,
+    configuration: &ExecutionConfiguration,
+    vm_configuration: &VmConfiguration,
+    meta: &Meta,
+    start: &Start,
+) -> Result<(), String> {
+    let start_iteration = Instant::now();
+    let mut cycles = 0;
+    while !env.halt {
+        if configuration.max_cycles == Some(cycles) {
+            return Err(format!(
+                "The program did not halt after {cycles} instructions"
+            ));
+        }
+        env.step(vm_configuration, meta, start);
+        cycles += 1;
+    }
+    info!(
+        "The program halted after {cycles} instructions, executed in {elapsed} μs",
+        elapsed = start_iteration.elapsed().as_micros()
+    );
+    Ok(())
+}
+
+/// Executes the program until it halts, writing the trace of each chunk of
+/// instructions to a file of the output directory
+#[cfg(feature = "hooks")]
+fn trace<PreImageOracle: PreImageOracleT>(
+    env: &mut Env<Fp, PreImageOracle>,
+    configuration: &ExecutionConfiguration,
+    vm_configuration: &VmConfiguration,
+    meta: &Meta,
+    start: &Start,
+    format: TraceFormat,
+) -> Result<(), String> {
+    use o1vm::interpreters::mips::trace::{TraceFilter, Tracer};
+
+    let mut tracer = Tracer::new(TraceFilter::default());
+    tracer.attach(env);
+    let mut cycles = 0;
+    let mut index = 0;
+    while !env.halt {
+        if configuration.max_cycles == Some(cycles) {
+            return Err(format!(
+                "The program did not halt after {cycles} instructions"
+            ));
+        }
+        tracer.step(env, vm_configuration, meta, start);
+        cycles += 1;
+        if tracer.entries.len() == configuration.chunk_size || env.halt {
+            let trace = match format {
+                TraceFormat::Text => tracer.to_text(),
+                TraceFormat::Json => tracer
+                    .to_json()
+                    .map_err(|e| format!("Could not encode the trace: {e}"))?,
+            };
+            let path = configuration
+                .output_dir
+                .join(cli::trace_file_name(index, format));
+            std::fs::write(&path, trace)
+                .map_err(|e| format!("Could not write the trace {}: {e}", path.display()))?;
+            tracer.entries.clear();
+            index += 1;
+        }
+    }
+    info!("The trace of {cycles} instructions was written in {index} files");
+    Ok(())
+}
+
+#[cfg(not(feature = "hooks"))]
+fn trace<PreImageOracle: PreImageOracleT>(
+    _env: &mut Env<Fp, PreImageOracle>,
+    _configuration: &ExecutionConfiguration,
+    _vm_configuration: &VmConfiguration,
+    _meta: &Meta,
+    _start: &Start,
+    _format: TraceFormat,
+) -> Result<(), String> {
+    Err("The trace subcommand requires the o1vm to be built with the hooks feature".to_string())
+}
+
+/// Executes the program until it halts, writing the proof of each chunk of
+/// the execution, and of each domain of the Keccak coprocessor, to the output
+/// directory. The traces of all the chunks are kept in memory until the
+/// execution halts, as the challenges of the bus connecting them to the
+/// coprocessor depend on all of them, see [continuation].
+fn prove<PreImageOracle: PreImageOracleT>(
+    env: &mut Env<Fp, PreImageOracle>,
+    configuration: &ExecutionConfiguration,
+    vm_configuration: &VmConfiguration,
+    meta: &Meta,
+    start: &Start,
+) -> Result<(), String> {
+    let mut rng = rand::thread_rng();
+    let chunk_size = configuration.chunk_size;
+    let domain = domain(chunk_size)?;
+    let srs = srs(domain, chunk_size);
+    let constraints = get_mips_constraints::<Fp>();
+
+    let mut memory = continuation::memory_store::<Vesta, _>(env);
+    let mut aggregator = Aggregator::<Vesta>::new(BoundaryState::spill(env), memory.root());
+    if let Some(max_cycles) = configuration.max_cycles {
+        aggregator = aggregator.with_max_cycles(max_cycles);
+    }
+    let mut host_digest = aggregator.initial_host_digest;
+    let mut keccak = KeccakCoprocessor::<Vesta>::new(chunk_size);
+    let mut traces = vec![];
+    let mut cycles = 0;
+    while !env.halt {
+        let trace = continuation::execute_chunk(
+            chunk_size,
+            env,
+            &mut memory,
+            &mut host_digest,
+            &mut keccak,
+            vm_configuration,
+            meta,
+            start,
+        );
+        cycles += trace.length as u64;
+        if matches!(configuration.max_cycles, Some(max_cycles) if cycles > max_cycles) {
+            return Err(format!(
+                "The execution exceeds the maximum number of instructions in the chunk {}",
+                traces.len()
+            ));
+        }
+        traces.push(trace);
+    }
+
+    let challenges =
+        continuation::bus_challenges::<Vesta, BaseSponge>(domain, &srs, &traces, &mut keccak);
+    for trace in traces {
+        let start_iteration = Instant::now();
+        let chunk = continuation::prove_chunk::<Vesta, BaseSponge, ScalarSponge, _>(
+            domain,
+            &srs,
+            &constraints,
+            trace,
+            &challenges,
+            &mut rng,
+        )
+        .map_err(|e| format!("Could not prove the chunk {}: {e}", aggregator.chunks.len()))?;
+        aggregator.check(&chunk).map_err(|e| e.to_string())?;
+        let path = configuration
+            .output_dir
+            .join(cli::chunk_file_name(aggregator.chunks.len()));
+        chunk
+            .write(&path)
+            .map_err(|e| format!("Could not write the proof {}: {e}", path.display()))?;
+        debug!(
+            "Proof of a chunk of {length} instructions written in {elapsed} μs",
+            length = chunk.length,
+            elapsed = start_iteration.elapsed().as_micros()
+        );
+        aggregator.push(chunk).map_err(|e| e.to_string())?;
+    }
+
+    let keccak_constraints = keccak::get_constraints::<Fp>();
+    let proofs = keccak
+        .prove::<BaseSponge, ScalarSponge, _>(
+            domain,
+            &srs,
+            &keccak_constraints,
+            &challenges,
+            &mut rng,
+        )
+        .map_err(|e| format!("Could not prove the Keccak hashes: {e}"))?;
+    for (index, proof) in proofs.iter().enumerate() {
+        let path = configuration.output_dir.join(cli::keccak_file_name(index));
+        proof
+            .write(&path)
+            .map_err(|e| format!("Could not write the proof {}: {e}", path.display()))?;
+    }
+    info!(
+        "The proofs of {chunks} chunks of {cycles} instructions and of {domains} Keccak domains were written",
+        chunks = aggregator.chunks.len(),
+        cycles = aggregator.cycles(),
+        domains = proofs.len()
+    );
+    Ok(())
+}
+
+/// The state of the program before its first instruction, with the root of
+/// its memory
+fn initial_state(program: &ProgramSource) -> Result<(BoundaryState, Fp), String> {
+    let (state, _) = load_program(program)?;
+    // The initial state does not depend on the preimages
+    let mut env = Env::<Fp, PreImageStore>::create(
+        cannon::PAGE_SIZE as usize,
+        state,
+        PreImageStore::default(),
+    );
+    let memory = continuation::memory_store::<Vesta, _>(&mut env);
+    Ok((BoundaryState::spill(&env), memory.root()))
+}
+
+/// Verifies the proofs written by [prove] in `proof_dir`, for an execution of
+/// the program `program` which halts
+fn verify(
+    program: &ProgramSource,
+    proof_dir: &Path,
+    chunk_size: usize,
+    max_cycles: Option<u64>,
+) -> Result<(), String> {
+    let start_iteration = Instant::now();
+    let (initial_state, initial_memory_root) = initial_state(program)?;
+    let read_all = |file_name: fn(usize) -> String| {
+        (0..)
+            .map(|index| proof_dir.join(file_name(index)))
+            .take_while(|path| path.exists())
+            .collect::<Vec<_>>()
+    };
+
+    let chunks = read_all(cli::chunk_file_name)
+        .into_iter()
+        .map(|path| {
+            ChunkProof::<Vesta>::read(&path)
+                .map_err(|e| format!("Could not read the proof {}: {e}", path.display()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if chunks.is_empty() {
+        return Err(format!("No proof found in {}", proof_dir.display()));
+    }
+    let mut aggregator = Aggregator::<Vesta>::new(initial_state, initial_memory_root);
+    if let Some(max_cycles) = max_cycles {
+        aggregator = aggregator.with_max_cycles(max_cycles);
+    }
+    for chunk in chunks {
+        aggregator.push(chunk).map_err(|e| e.to_string())?;
+    }
+    if !aggregator.final_state().halt {
+        return Err("The proven execution does not halt".to_string());
+    }
+
+    let keccak_proofs = read_all(cli::keccak_file_name)
+        .into_iter()
+        .map(|path| {
+            Proof::read(&path)
+                .map_err(|e| format!("Could not read the proof {}: {e}", path.display()))
+        })
+        .collect::<Result<Vec<KeccakProof<Vesta>>, _>>()?;
+    if aggregator.hashes() > 0 && keccak_proofs.is_empty() {
+        return Err(format!(
+            "The execution requested {hashes} Keccak hashes, but their proofs are missing",
+            hashes = aggregator.hashes()
+        ));
+    }
+
+    let domain = domain(chunk_size)?;
+    let srs = srs(domain, chunk_size);
+    let constraints = get_mips_constraints::<Fp>();
+    let keccak_constraints = keccak::get_constraints::<Fp>();
+    aggregator
+        .verify::<BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &keccak_constraints,
+            &keccak_proofs,
+        )
+        .map_err(|e| e.to_string())?;
+    info!(
+        "The proofs of {chunks} chunks of {cycles} instructions are valid, verified in {elapsed} μs",
+        chunks = aggregator.chunks.len(),
+        cycles = aggregator.cycles(),
+        elapsed = start_iteration.elapsed().as_micros()
+    );
+    Ok(())
+}
+
+/// Runs the subcommand `command` on the program of `configuration`, with the
+/// preimage oracle `po`
+fn execute<PreImageOracle: PreImageOracleT>(
+    command: &CliCommand,
+    configuration: &ExecutionConfiguration,
+    state: State,
+    meta: &Meta,
+    po: PreImageOracle,
+) -> Result<(), String> {
+    // Initialize some data used for statistical computations
+    let start = Start::create(state.step as usize);
+    let mut env = Env::<Fp, PreImageOracle>::create(cannon::PAGE_SIZE as usize, state, po);
+    if let Some(stdin) = &configuration.stdin {
+        env.syscall_env.stdin = std::fs::read(stdin)
+            .map_err(|e| format!("Could not read the input file {}: {e}", stdin.display()))?;
+    }
+    std::fs::create_dir_all(&configuration.output_dir).map_err(|e| {
+        format!(
+            "Could not create the output directory {}: {e}",
+            configuration.output_dir.display()
+        )
+    })?;
+    let vm_configuration = configuration.vm_configuration();
+    match command {
+        CliCommand::Run(_) => run(&mut env, configuration, &vm_configuration, meta, &start),
+        CliCommand::Trace(_, format) => trace(
+            &mut env,
+            configuration,
+            &vm_configuration,
+            meta,
+            &start,
+            *format,
+        ),
+        CliCommand::Prove(_) => prove(&mut env, configuration, &vm_configuration, meta, &start),
+        CliCommand::Verify { .. } => unreachable!("the verifier does not execute the program"),
+    }
+}
+
+pub fn main() -> ExitCode {
+    let matches = cli::main_cli().get_matches();
+    let command = cli::read_command(&matches);
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let result = match &command {
+        CliCommand::Verify {
+            program,
+            proof_dir,
+            chunk_size,
+            max_cycles,
+        } => verify(program, proof_dir, *chunk_size, *max_cycles),
+        CliCommand::Run(configuration)
+        | CliCommand::Trace(configuration, _)
+        | CliCommand::Prove(configuration) => {
+            load_program(&configuration.program).and_then(|(state, meta)| {
+                match &configuration.host {
+                    Some(_) => {
+                        let mut po = PreImageOracle::create(&configuration.host);
+                        let _child = po.start();
+                        execute(&command, configuration, state, &meta, po)
+                    }
+                    // Without a host, the program can not request any preimage
+                    None => execute(
+                        &command,
+                        configuration,
+                        state,
+                        &meta,
+                        PreImageStore::default(),
+                    ),
+                }
+            })
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
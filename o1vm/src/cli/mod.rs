@@ -0,0 +1,365 @@
+//! The command-line interface of the `o1vm` binary, running the pickles flavor
+//! of the o1vm with a subcommand for each stage:
+//!
+//! - `run` executes the program, without recording its execution trace;
+//! - `trace` executes the program and writes the instructions it executes,
+//!   see [Tracer](crate::interpreters::mips::trace::Tracer);
+//! - `prove` executes the program and writes the proof of each chunk of the
+//!   execution, see [continuation](crate::pickles::continuation), and of the
+//!   Keccak hashes it requested;
+//! - `verify` checks the proofs written by `prove`, against the initial state
+//!   of the program.
+//!
+//! The options can also be given with environment variables, e.g.
+//! `O1VM_ELF=program.elf o1vm run`, the flags taking precedence.
+//!
+//! ```bash
+//! o1vm prove --elf program.elf --host "op-program --server" --output-dir proofs
+//! o1vm verify --elf program.elf --output-dir proofs
+//! ```
+
+use crate::cannon::{HostProgram, StepFrequency, VmConfiguration};
+use clap::{arg, value_parser, ArgGroup, ArgMatches};
+use std::path::{Path, PathBuf};
+
+/// The default number of instructions of a chunk, i.e. the size of the domain
+/// of the proofs
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 15;
+
+/// The program to execute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramSource {
+    /// A MIPS ELF file
+    Elf(PathBuf),
+    /// A state file of Cannon, with the file of its symbols, if any
+    State {
+        state: PathBuf,
+        meta: Option<PathBuf>,
+    },
+}
+
+/// The options of the subcommands executing a program
+#[derive(Debug, Clone)]
+pub struct ExecutionConfiguration {
+    pub program: ProgramSource,
+    /// The host program serving the preimages and the hints. Without it, the
+    /// program can not request any preimage.
+    pub host: Option<HostProgram>,
+    /// The file whose content is given to the program on its standard input
+    pub stdin: Option<PathBuf>,
+    /// The maximum number of instructions of a chunk
+    pub chunk_size: usize,
+    /// The directory the traces and the proofs are written to
+    pub output_dir: PathBuf,
+    /// The maximum number of instructions of the execution
+    pub max_cycles: Option<u64>,
+}
+
+impl ExecutionConfiguration {
+    /// The configuration of the witness environment, without any of the
+    /// periodic actions of Cannon
+    pub fn vm_configuration(&self) -> VmConfiguration {
+        let path = |path: &Path| path.display().to_string();
+        let (input_state_file, metadata_file) = match &self.program {
+            ProgramSource::Elf(elf) => (path(elf), String::new()),
+            ProgramSource::State { state, meta } => {
+                (path(state), meta.as_deref().map(path).unwrap_or_default())
+            }
+        };
+        VmConfiguration {
+            input_state_file,
+            output_state_file: String::new(),
+            metadata_file,
+            proof_at: StepFrequency::Never,
+            stop_at: StepFrequency::Never,
+            snapshot_state_at: StepFrequency::Never,
+            checkpoint_at: StepFrequency::Never,
+            info_at: StepFrequency::Never,
+            proof_fmt: String::new(),
+            snapshot_fmt: String::new(),
+            pprof_cpu: false,
+            host: self.host.clone(),
+            stdin: self.stdin.as_deref().map(path),
+            resume: None,
+            max_cycles: self.max_cycles,
+        }
+    }
+}
+
+/// The format of the trace written by `trace`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    Text,
+    Json,
+}
+
+/// A subcommand with its options
+#[derive(Debug, Clone)]
+pub enum CliCommand {
+    Run(ExecutionConfiguration),
+    Trace(ExecutionConfiguration, TraceFormat),
+    Prove(ExecutionConfiguration),
+    Verify {
+        /// The program whose execution is proven. The initial state of the
+        /// proven execution must be the one of the program.
+        program: ProgramSource,
+        /// The directory the proofs were written to
+        proof_dir: PathBuf,
+        /// The size of the domain of the proofs
+        chunk_size: usize,
+        /// The maximum number of instructions of the proven execution
+        max_cycles: Option<u64>,
+    },
+}
+
+/// The name of the file of the proof of the chunk `index`
+pub fn chunk_file_name(index: usize) -> String {
+    format!("chunk-{index}.bin")
+}
+
+/// The name of the file of the proof of the domain `index` of the Keccak
+/// coprocessor
+pub fn keccak_file_name(index: usize) -> String {
+    format!("keccak-{index}.bin")
+}
+
+/// The name of the file of the trace of the chunk `index`
+pub fn trace_file_name(index: usize, format: TraceFormat) -> String {
+    match format {
+        TraceFormat::Text => format!("trace-{index}.txt"),
+        TraceFormat::Json => format!("trace-{index}.json"),
+    }
+}
+
+/// Parses the command line of the host program, e.g. `op-program --server`
+pub fn host_program_parser(s: &str) -> Result<HostProgram, String> {
+    let mut words = s.split_whitespace().map(str::to_string);
+    match words.next() {
+        Some(name) => Ok(HostProgram {
+            name,
+            arguments: words.collect(),
+        }),
+        None => Err("the host program is empty".to_string()),
+    }
+}
+
+/// Parses a chunk size, which must be a power of two
+pub fn chunk_size_parser(s: &str) -> Result<usize, String> {
+    let size = s
+        .parse::<usize>()
+        .map_err(|e| format!("invalid chunk size {s}: {e}"))?;
+    if !size.is_power_of_two() {
+        return Err(format!("the chunk size {size} is not a power of two"));
+    }
+    Ok(size)
+}
+
+fn chunk_size_arg() -> clap::Arg {
+    arg!(--"chunk-size" <N> "the maximum number of instructions of a chunk, a power of two")
+        .env("O1VM_CHUNK_SIZE")
+        // DEFAULT_CHUNK_SIZE
+        .default_value("32768")
+        .value_parser(chunk_size_parser)
+}
+
+fn output_dir_arg(help: &'static str) -> clap::Arg {
+    arg!(--"output-dir" <DIR>)
+        .help(help)
+        .env("O1VM_OUTPUT_DIR")
+        .default_value(".")
+        .value_parser(value_parser!(PathBuf))
+}
+
+fn max_cycles_arg() -> clap::Arg {
+    arg!(--"max-cycles" <N> "maximum number of instructions of the execution")
+        .env("O1VM_MAX_CYCLES")
+        .required(false)
+        .value_parser(value_parser!(u64))
+}
+
+/// A subcommand taking the program as an ELF file or as a state file of
+/// Cannon
+fn program_command(name: &'static str, about: &'static str) -> clap::Command {
+    clap::Command::new(name)
+        .about(about)
+        .arg(
+            arg!(--elf <FILE> "the MIPS ELF file of the program")
+                .env("O1VM_ELF")
+                .required(false)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--input <FILE> "the initial state file of Cannon")
+                .env("O1VM_INPUT")
+                .required(false)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .group(
+            ArgGroup::new("program")
+                .args(["elf", "input"])
+                .required(true),
+        )
+        .arg(
+            arg!(--meta <FILE> "the metadata file of the initial state")
+                .env("O1VM_META")
+                .required(false)
+                .requires("input")
+                .value_parser(value_parser!(PathBuf)),
+        )
+}
+
+fn execution_command(name: &'static str, about: &'static str) -> clap::Command {
+    program_command(name, about)
+        .arg(
+            arg!(--host <COMMAND> "the host program serving the preimages, with its arguments")
+                .env("O1VM_HOST")
+                .required(false)
+                .value_parser(host_program_parser),
+        )
+        .arg(
+            arg!(--stdin <FILE> "file given to the program on its standard input")
+                .env("O1VM_STDIN")
+                .required(false)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(max_cycles_arg())
+        .arg(chunk_size_arg())
+        .arg(output_dir_arg(
+            "the directory the traces and the proofs are written to",
+        ))
+}
+
+pub fn main_cli() -> clap::Command {
+    clap::Command::new("o1vm")
+        .version("0.1")
+        .about("o1vm - a generic purpose zero-knowledge virtual machine")
+        .subcommand_required(true)
+        .subcommand(execution_command(
+            "run",
+            "Executes the program, without proving it",
+        ))
+        .subcommand(
+            execution_command(
+                "trace",
+                "Executes the program and writes the trace of its instructions, a file per chunk",
+            )
+            .arg(
+                arg!(--format <FORMAT> "the format of the trace")
+                    .env("O1VM_TRACE_FORMAT")
+                    .default_value("text")
+                    .value_parser(["text", "json"]),
+            ),
+        )
+        .subcommand(execution_command(
+            "prove",
+            "Executes the program and writes the proofs of its chunks",
+        ))
+        .subcommand(
+            program_command(
+                "verify",
+                "Verifies the proofs written by the prove subcommand for the program",
+            )
+            .arg(max_cycles_arg())
+            .arg(chunk_size_arg())
+            .arg(output_dir_arg("the directory the proofs were written to")),
+        )
+}
+
+fn read_program(matches: &ArgMatches) -> ProgramSource {
+    match matches.get_one::<PathBuf>("elf") {
+        Some(elf) => ProgramSource::Elf(elf.clone()),
+        // The group of the program is required
+        None => ProgramSource::State {
+            state: matches.get_one::<PathBuf>("input").unwrap().clone(),
+            meta: matches.get_one::<PathBuf>("meta").cloned(),
+        },
+    }
+}
+
+fn read_execution_configuration(matches: &ArgMatches) -> ExecutionConfiguration {
+    ExecutionConfiguration {
+        program: read_program(matches),
+        host: matches.get_one::<HostProgram>("host").cloned(),
+        stdin: matches.get_one::<PathBuf>("stdin").cloned(),
+        chunk_size: *matches.get_one::<usize>("chunk-size").unwrap(),
+        output_dir: matches.get_one::<PathBuf>("output-dir").unwrap().clone(),
+        max_cycles: matches.get_one::<u64>("max-cycles").copied(),
+    }
+}
+
+/// Reads the subcommand and its options from the matches of [main_cli]
+///
+/// # Panics
+///
+/// Will panic if the matches are not the ones of [main_cli].
+pub fn read_command(matches: &ArgMatches) -> CliCommand {
+    match matches.subcommand() {
+        Some(("run", matches)) => CliCommand::Run(read_execution_configuration(matches)),
+        Some(("trace", matches)) => {
+            let format = match matches.get_one::<String>("format").unwrap().as_str() {
+                "json" => TraceFormat::Json,
+                _ => TraceFormat::Text,
+            };
+            CliCommand::Trace(read_execution_configuration(matches), format)
+        }
+        Some(("prove", matches)) => CliCommand::Prove(read_execution_configuration(matches)),
+        Some(("verify", matches)) => CliCommand::Verify {
+            program: read_program(matches),
+            proof_dir: matches.get_one::<PathBuf>("output-dir").unwrap().clone(),
+            chunk_size: *matches.get_one::<usize>("chunk-size").unwrap(),
+            max_cycles: matches.get_one::<u64>("max-cycles").copied(),
+        },
+        _ => panic!("unknown subcommand"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_size_parser() {
+        assert_eq!(chunk_size_parser("1"), Ok(1));
+        assert_eq!(chunk_size_parser("32768"), Ok(DEFAULT_CHUNK_SIZE));
+        assert!(chunk_size_parser("0").is_err());
+        assert!(chunk_size_parser("1000").is_err());
+        assert!(chunk_size_parser("-8").is_err());
+        assert!(chunk_size_parser("2^15").is_err());
+        assert!(chunk_size_parser("").is_err());
+    }
+
+    #[test]
+    fn test_host_program_parser() {
+        let host = host_program_parser("op-program --server --datadir  /tmp/db").unwrap();
+        assert_eq!(host.name, "op-program");
+        assert_eq!(host.arguments, ["--server", "--datadir", "/tmp/db"]);
+        let host = host_program_parser(" op-program ").unwrap();
+        assert_eq!(host.name, "op-program");
+        assert!(host.arguments.is_empty());
+        assert!(host_program_parser("").is_err());
+        assert!(host_program_parser("  ").is_err());
+    }
+
+    #[test]
+    fn test_verify_requires_the_program() {
+        let matches = main_cli()
+            .try_get_matches_from(["o1vm", "verify", "--elf", "program.elf"])
+            .unwrap();
+        match read_command(&matches) {
+            CliCommand::Verify {
+                program,
+                chunk_size,
+                max_cycles,
+                ..
+            } => {
+                assert_eq!(program, ProgramSource::Elf(PathBuf::from("program.elf")));
+                assert_eq!(chunk_size, DEFAULT_CHUNK_SIZE);
+                assert_eq!(max_cycles, None);
+            }
+            command => panic!("unexpected command {command:?}"),
+        }
+        assert!(main_cli()
+            .try_get_matches_from(["o1vm", "verify", "--output-dir", "proofs"])
+            .is_err());
+    }
+}
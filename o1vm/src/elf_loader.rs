@@ -1,112 +1,161 @@
-use crate::cannon::{Page, State, PAGE_SIZE};
-use elf::{endian::LittleEndian, section::SectionHeader, ElfBytes};
+use crate::cannon::{Meta, Page, State, Symbol, PAGE_ADDRESS_MASK, PAGE_ADDRESS_SIZE, PAGE_SIZE};
+use elf::{
+    abi::{EM_MIPS, EM_RISCV, SHF_ALLOC, SHT_NOBITS, STT_FUNC},
+    endian::AnyEndian,
+    ElfBytes,
+};
 use log::debug;
-use std::{collections::HashMap, path::Path};
+use std::{collections::BTreeMap, path::Path};
 
-/// Parse an ELF file and return the parsed data as a structure that is expected
-/// by the o1vm RISC-V 32 bits edition.
-// FIXME: parametrize by an architecture. We should return a state depending on the
-// architecture. In the meantime, we can have parse_riscv32i and parse_mips.
+/// The architectures of the programs that can be loaded from an ELF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Architecture {
+    Mips,
+    RiscV32,
+}
+
+impl Architecture {
+    /// The machine of the header of the ELF files of this architecture
+    pub fn e_machine(self) -> u16 {
+        match self {
+            Architecture::Mips => EM_MIPS,
+            Architecture::RiscV32 => EM_RISCV,
+        }
+    }
+
+    /// The index of the register holding the stack pointer
+    pub fn stack_pointer_register(self) -> usize {
+        match self {
+            Architecture::Mips => 29,
+            Architecture::RiscV32 => 2,
+        }
+    }
+
+    /// The value of the stack pointer when the program starts
+    // FIXME: the RISC-V value is the one hardcoded in the witness environment.
+    // The MIPS value is the one used by Cannon.
+    pub fn initial_stack_pointer(self) -> u32 {
+        match self {
+            Architecture::Mips => 0x7fff_d000,
+            Architecture::RiscV32 => 0x4080_04f0,
+        }
+    }
+}
+
+/// A program loaded from an ELF file: the initial state of the VM, and the
+/// symbols of the functions of the program, used to trace the execution.
+pub struct Program {
+    pub state: State,
+    pub meta: Meta,
+}
+
+/// Copy `bytes` at the address `address` of the memory, creating the pages
+/// when they are first written to.
+fn write_memory(pages: &mut BTreeMap<u32, Vec<u8>>, address: u32, bytes: &[u8]) {
+    for (i, byte) in bytes.iter().enumerate() {
+        let address = address + i as u32;
+        let page = pages
+            .entry(address >> PAGE_ADDRESS_SIZE)
+            .or_insert_with(|| vec![0; PAGE_SIZE as usize]);
+        page[(address & PAGE_ADDRESS_MASK) as usize] = *byte;
+    }
+}
+
+/// Parse an ELF file and return the initial state of a program of the
+/// architecture `arch`, with the symbols of its functions.
+///
+/// The sections that are allocated at runtime, e.g. `.text`, `.rodata`,
+/// `.data` and `.bss`, are loaded into the memory pages. The program counter
+/// is set to the entry point, the stack pointer to
+/// [Architecture::initial_stack_pointer] and the heap starts at the first page
+/// after the sections.
 // FIXME: for now, we return a State structure, either for RISC-V 32i or MIPS.
 // We should return a structure specifically built for the o1vm, and not tight
 // to Cannon. It will be done in a future PR to avoid breaking the current code
 // and have a huge diff.
-pub fn parse_riscv32(path: &Path) -> Result<State, String> {
-    debug!("Start parsing the ELF file to load a RISC-V 32i compatible state");
-    let file_data = std::fs::read(path).expect("Could not read file.");
-    let slice = file_data.as_slice();
-    let file = ElfBytes::<LittleEndian>::minimal_parse(slice).expect("Open ELF file failed.");
+pub fn parse_elf(arch: Architecture, path: &Path) -> Result<Program, String> {
+    debug!("Start parsing the ELF file to load a {arch:?} compatible state");
+    let file_data = std::fs::read(path).map_err(|e| format!("Could not read file: {e}"))?;
+    let file = ElfBytes::<AnyEndian>::minimal_parse(file_data.as_slice())
+        .map_err(|e| format!("Open ELF file failed: {e}"))?;
 
-    // Checking it is RISC-V
-    assert_eq!(file.ehdr.e_machine, 243);
+    if file.ehdr.e_machine != arch.e_machine() {
+        return Err(format!(
+            "The ELF file is for the machine {}, not for {arch:?}",
+            file.ehdr.e_machine
+        ));
+    }
 
-    let (shdrs_opt, strtab_opt) = file
+    let (shdrs, strtab) = match file
         .section_headers_with_strtab()
-        .expect("shdrs offsets should be valid");
-    let (shdrs, strtab) = (
-        shdrs_opt.expect("Should have shdrs"),
-        strtab_opt.expect("Should have strtab"),
-    );
-
-    // Parse the shdrs and collect them into a map keyed on their zero-copied name
-    let sections_by_name: HashMap<&str, SectionHeader> = shdrs
-        .iter()
-        .map(|shdr| {
-            (
-                strtab
-                    .get(shdr.sh_name as usize)
-                    .expect("Failed to get section name"),
-                shdr,
-            )
-        })
-        .collect();
+        .map_err(|e| format!("Invalid section headers: {e}"))?
+    {
+        (Some(shdrs), Some(strtab)) => (shdrs, strtab),
+        _ => return Err("The ELF file should have section headers".to_string()),
+    };
+
+    // Building the memory pages from the allocated sections
+    let mut pages: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    let mut end_address = 0;
+    for shdr in shdrs.iter() {
+        if (shdr.sh_flags & SHF_ALLOC as u64) == 0 || shdr.sh_size == 0 {
+            continue;
+        }
+        let name = strtab
+            .get(shdr.sh_name as usize)
+            .map_err(|e| format!("Failed to get section name: {e}"))?;
+        let address = u32::try_from(shdr.sh_addr)
+            .map_err(|_| format!("The section {name} is out of the 32 bits address space"))?;
+        let size = shdr.sh_size as usize;
+        debug!("Loading the section {name}, of {size} bytes, at address {address}");
 
-    debug!("Loading the text section, which contains the executable code.");
-    // Getting the executable code.
-    let text_section = sections_by_name
-        .get(".text")
-        .expect("Should have .text section");
-
-    let (text_section_data, _) = file
-        .section_data(text_section)
-        .expect("Failed to read data from .text section");
-
-    let code_section_starting_address = text_section.sh_addr as usize;
-    let code_section_size = text_section.sh_size as usize;
-    let code_section_end_address = code_section_starting_address + code_section_size;
-    debug!(
-        "The executable code starts at address {}, has size {} bytes, and ends at address {}.",
-        code_section_starting_address, code_section_size, code_section_end_address
-    );
-
-    // Building the memory pages
-    let mut memory: Vec<Page> = vec![];
-    let page_size_usize: usize = PAGE_SIZE.try_into().unwrap();
-    // Padding to get the right number of pages. We suppose that the memory
-    // index starts at 0.
-    let start_page_address: usize =
-        (code_section_starting_address / page_size_usize) * page_size_usize;
-    let end_page_address =
-        (code_section_end_address / (page_size_usize - 1)) * page_size_usize + page_size_usize;
-
-    let first_page_index = start_page_address / page_size_usize;
-    let last_page_index = (end_page_address - 1) / page_size_usize;
-    let mut data_offset = 0;
-    (first_page_index..=last_page_index).for_each(|page_index| {
-        let mut data = vec![0; page_size_usize];
-        // Special case of only one page
-        if first_page_index == last_page_index {
-            let data_length = code_section_end_address - code_section_starting_address;
-            let page_offset = code_section_starting_address - start_page_address;
-            data[page_offset..page_offset + data_length]
-                .copy_from_slice(&text_section_data[0..data_length]);
-            data_offset += data_length;
+        if shdr.sh_type == SHT_NOBITS {
+            // e.g. .bss, which is not stored in the file
+            write_memory(&mut pages, address, &vec![0; size]);
         } else {
-            let data_length = if page_index == last_page_index {
-                code_section_end_address - (page_index * page_size_usize)
-            } else {
-                page_size_usize
-            };
-            let page_offset = if page_index == first_page_index {
-                code_section_starting_address - start_page_address
-            } else {
-                0
-            };
-            data[page_offset..]
-                .copy_from_slice(&text_section_data[data_offset..data_offset + data_length]);
-            data_offset += data_length;
+            let (data, _) = file
+                .section_data(&shdr)
+                .map_err(|e| format!("Failed to read data from the section {name}: {e}"))?;
+            write_memory(&mut pages, address, data);
         }
-        let page = Page {
-            index: page_index as u32,
-            data,
-        };
-        memory.push(page);
-    });
+        end_address = end_address.max(address as u64 + size as u64);
+    }
 
-    // FIXME: add data section into memory for static data saved in the binary
+    let memory: Vec<Page> = pages
+        .into_iter()
+        .map(|(index, data)| Page { index, data })
+        .collect();
+
+    // Symbols of the functions, in ascending order and without the 0-size
+    // ones, as expected by Meta
+    let mut symbols = vec![];
+    if let Some((symtab, strtab)) = file
+        .symbol_table()
+        .map_err(|e| format!("Invalid symbol table: {e}"))?
+    {
+        for symbol in symtab.iter() {
+            if symbol.st_symtype() != STT_FUNC || symbol.st_size == 0 {
+                continue;
+            }
+            let name = strtab
+                .get(symbol.st_name as usize)
+                .map_err(|e| format!("Failed to get symbol name: {e}"))?;
+            symbols.push(Symbol {
+                name: name.to_string(),
+                start: symbol.st_value as u32,
+                size: symbol.st_size as usize,
+            });
+        }
+    }
+    symbols.sort_by(|a, b| a.start.cmp(&b.start));
 
     // FIXME: we're lucky that RISCV32i and MIPS have the same number of
-    let registers: [u32; 32] = [0; 32];
+    // registers
+    let mut registers: [u32; 32] = [0; 32];
+    registers[arch.stack_pointer_register()] = arch.initial_stack_pointer();
+
+    // The heap starts at the first page after the program
+    let heap = ((end_address + PAGE_SIZE as u64 - 1) & !(PAGE_ADDRESS_MASK as u64)) as u32;
 
     // FIXME: it is only because we share the same structure for the state.
     let preimage_key: [u8; 32] = [0; 32];
@@ -115,7 +164,9 @@ pub fn parse_riscv32(path: &Path) -> Result<State, String> {
 
     // Entry point of the program
     let pc: u32 = file.ehdr.e_entry as u32;
-    assert!(pc != 0, "Entry point is 0. The documentation of the ELF library says that it means the ELF doesn't have an entry point. This is not supported.");
+    if pc == 0 {
+        return Err("Entry point is 0. The documentation of the ELF library says that it means the ELF doesn't have an entry point. This is not supported.".to_string());
+    }
     let next_pc: u32 = pc + 4u32;
 
     let state = State {
@@ -130,7 +181,7 @@ pub fn parse_riscv32(path: &Path) -> Result<State, String> {
         lo: 0,
         // FIXME: only because Cannon related
         hi: 0,
-        heap: 0,
+        heap,
         exit: 0,
         exited: false,
         step: 0,
@@ -141,5 +192,20 @@ pub fn parse_riscv32(path: &Path) -> Result<State, String> {
         preimage: None,
     };
 
-    Ok(state)
+    Ok(Program {
+        state,
+        meta: Meta { symbols },
+    })
+}
+
+/// Parse an ELF file and return the parsed data as a structure that is expected
+/// by the o1vm RISC-V 32 bits edition.
+pub fn parse_riscv32(path: &Path) -> Result<State, String> {
+    parse_elf(Architecture::RiscV32, path).map(|program| program.state)
+}
+
+/// Parse an ELF file and return the parsed data as a structure that is expected
+/// by the o1vm MIPS edition.
+pub fn parse_mips(path: &Path) -> Result<State, String> {
+    parse_elf(Architecture::Mips, path).map(|program| program.state)
 }
@@ -467,17 +467,23 @@ where
     /// When in Absorb mode, reads Lookups containing the 136 bytes of the block of the preimage
     /// - if is_absorb, adds 136 lookups
     /// - otherwise, adds 0 lookups
+    /// NOTE: the bytes of the padding are not part of the preimage, so they
+    ///       are read 0 times.
     // TODO: optimize this by using a single lookup reusing PadSuffix
     fn lookup_syscall_preimage(&mut self, step: Steps) {
         for i in 0..RATE_IN_BYTES {
-            self.read_syscall(
+            self.add_lookup(
                 self.is_absorb(step),
-                vec![
-                    self.hash_index(),
-                    self.block_index() * Self::constant(RATE_IN_BYTES as u64)
-                        + Self::constant(i as u64),
-                    self.sponge_byte(i),
-                ],
+                Lookup::read_if(
+                    Self::not(self.in_padding(i)),
+                    SyscallLookup,
+                    vec![
+                        self.hash_index(),
+                        self.block_index() * Self::constant(RATE_IN_BYTES as u64)
+                            + Self::constant(i as u64),
+                        self.sponge_byte(i),
+                    ],
+                ),
             );
         }
     }
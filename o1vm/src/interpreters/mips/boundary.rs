@@ -0,0 +1,181 @@
+//! Convention used to carry the state of the virtual machine across the
+//! boundary between two chunks of the execution trace.
+//!
+//! The execution trace is split in chunks, and a proof is made for each chunk.
+//! As one row of the trace corresponds to one instruction, a chunk always ends
+//! after a complete instruction. However, some state still has to be carried
+//! from one chunk to the next one:
+//! - the registers HI and LO, used by the multiplication and division family,
+//! - the pending branch, as MIPS has a delay slot: after a branch or a jump,
+//!   the next instruction pointer is not the current one plus 4,
+//! - the partially executed syscalls reading a preimage, whose progress is
+//!   kept in the registers `preimage_key` and `preimage_offset`, and in the
+//!   counters of the environment.
+//!
+//! All of these values are registers (see [Registers]). The canonical
+//! convention is the following:
+//! - at the end of a chunk, the state is *spilled* into a [BoundaryState],
+//!   which is serialized into the public state of the chunk using
+//!   [BoundaryState::to_public_state],
+//! - at the beginning of the next chunk, the state is *restored* from the same
+//!   [BoundaryState], using [BoundaryState::restore].
+//!
+//! The spilled values are bound to the register argument by reading each
+//! register and asserting that the value read is equal to the public value,
+//! see [constrain_spilled_registers]. As each read uses some scratch space, the
+//! registers are constrained by batches of at most
+//! [MAX_SPILLED_REGISTERS_PER_ROW] registers.
+//!
+//! FIXME: the chunks proven by [crate::pickles::continuation] do not have the
+//! rows reading the registers at their boundaries yet, and the pickles prover
+//! does not enforce the register argument, so the spilled values are only bound
+//! to the proofs as public values.
+
+use crate::{
+    interpreters::mips::{
+        interpreter::InterpreterEnv,
+        registers::{Registers, NUM_REGISTERS},
+        witness::{Env as WEnv, MAX_ACC},
+    },
+    preimage_oracle::PreImageOracleT,
+};
+use ark_ff::Field;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of registers that can be constrained on a single row by
+/// [constrain_spilled_registers]. Each read of a register uses 2 scratch
+/// columns (the value and the last access index).
+pub const MAX_SPILLED_REGISTERS_PER_ROW: usize = 8;
+
+/// Number of field elements in the public state of a boundary, see
+/// [BoundaryState::to_public_state].
+pub const BOUNDARY_PUBLIC_STATE_SIZE: usize = 2 * NUM_REGISTERS + 4;
+
+/// The state of the virtual machine carried from one chunk of the execution
+/// trace to the next one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundaryState {
+    /// The value of all the registers, including HI, LO, the instruction
+    /// pointers and the preimage registers.
+    pub registers: Registers<u32>,
+    /// The last access index of each register, required to continue the
+    /// register argument in the next chunk.
+    pub registers_write_index: Registers<u64>,
+    /// The instruction counter after the last instruction of the chunk.
+    pub instruction_counter: u64,
+    /// The number of bytes of the current preimage already read, if a syscall
+    /// reading a preimage is in progress.
+    pub preimage_bytes_read: u64,
+    /// The number of preimages completely read so far.
+    pub hash_counter: u64,
+    /// Whether the program has halted.
+    pub halt: bool,
+}
+
+impl BoundaryState {
+    /// Spill the state of the witness environment at the end of a chunk.
+    pub fn spill<Fp: Field, PreImageOracle: PreImageOracleT>(
+        env: &WEnv<Fp, PreImageOracle>,
+    ) -> Self {
+        BoundaryState {
+            registers: env.registers.clone(),
+            registers_write_index: env.registers_write_index.clone(),
+            instruction_counter: env.instruction_counter,
+            preimage_bytes_read: env.preimage_bytes_read,
+            hash_counter: env.hash_counter,
+            halt: env.halt,
+        }
+    }
+
+    /// Restore the state in the witness environment at the beginning of a
+    /// chunk. If a syscall reading a preimage was in progress, the preimage is
+    /// requested again to the oracle.
+    pub fn restore<Fp: Field, PreImageOracle: PreImageOracleT>(
+        &self,
+        env: &mut WEnv<Fp, PreImageOracle>,
+    ) {
+        env.registers = self.registers.clone();
+        env.registers_write_index = self.registers_write_index.clone();
+        env.instruction_counter = self.instruction_counter;
+        env.preimage_bytes_read = self.preimage_bytes_read;
+        env.hash_counter = self.hash_counter;
+        env.halt = self.halt;
+        if self.has_pending_syscall() {
+            let preimage_key = self.preimage_key();
+            env.preimage = Some(env.preimage_oracle.get_preimage(preimage_key).get());
+            env.preimage_key = Some(preimage_key);
+        }
+    }
+
+    /// Returns true if the last instruction of the chunk was a branch or a
+    /// jump, i.e. if the instruction in the delay slot has not been executed
+    /// yet.
+    pub fn has_pending_branch(&self) -> bool {
+        self.registers.next_instruction_pointer
+            != self.registers.current_instruction_pointer.wrapping_add(4)
+    }
+
+    /// Returns true if a syscall reading a preimage has been partially
+    /// executed.
+    pub fn has_pending_syscall(&self) -> bool {
+        self.registers.preimage_offset != 0
+    }
+
+    /// The number of instructions executed since the beginning of the
+    /// program, see [WEnv::normalized_instruction_counter].
+    pub fn step(&self) -> u64 {
+        self.instruction_counter / MAX_ACC
+    }
+
+    /// The key of the preimage being read, as bytes.
+    pub fn preimage_key(&self) -> [u8; 32] {
+        let mut preimage_key = [0u8; 32];
+        for (i, word) in self.registers.preimage_key.iter().enumerate() {
+            preimage_key[4 * i..4 * (i + 1)].copy_from_slice(&word.to_be_bytes());
+        }
+        preimage_key
+    }
+
+    /// Serialize the state into field elements, in the canonical order:
+    /// - the value of the registers, ordered by register index,
+    /// - the last access index of the registers, ordered by register index,
+    /// - the instruction counter,
+    /// - the number of bytes of the current preimage already read,
+    /// - the number of preimages read,
+    /// - `1` if the program has halted, `0` otherwise.
+    pub fn to_public_state<Fp: Field>(&self) -> Vec<Fp> {
+        let mut res = Vec::with_capacity(BOUNDARY_PUBLIC_STATE_SIZE);
+        res.extend((0..NUM_REGISTERS).map(|i| Fp::from(self.registers[i])));
+        res.extend((0..NUM_REGISTERS).map(|i| Fp::from(self.registers_write_index[i])));
+        res.push(Fp::from(self.instruction_counter));
+        res.push(Fp::from(self.preimage_bytes_read));
+        res.push(Fp::from(self.hash_counter));
+        res.push(Fp::from(self.halt as u64));
+        res
+    }
+}
+
+/// Constrain the values of the registers `(idx, value)` to be equal to the
+/// values `value` given in the public state of the boundary. The registers are
+/// read using the register argument, therefore the values are bound to the
+/// values written by the previous instructions of the chunk.
+///
+/// # Panics
+///
+/// Will panic if more than [MAX_SPILLED_REGISTERS_PER_ROW] registers are given,
+/// or if a register index is out of bounds.
+pub fn constrain_spilled_registers<Env: InterpreterEnv>(
+    env: &mut Env,
+    registers: &[(usize, Env::Variable)],
+) {
+    assert!(
+        registers.len() <= MAX_SPILLED_REGISTERS_PER_ROW,
+        "At most {MAX_SPILLED_REGISTERS_PER_ROW} registers can be spilled on a single row, got {}",
+        registers.len()
+    );
+    for (idx, value) in registers.iter() {
+        assert!(*idx < NUM_REGISTERS, "Register index {idx} out of bounds");
+        let read_value = env.read_register(&Env::constant(*idx as u32));
+        env.assert_equal(read_value, value.clone());
+    }
+}
@@ -1,10 +1,11 @@
 use crate::interpreters::mips::Instruction::{self, IType, JType, RType};
+use kimchi::circuits::column_registry::{ColumnInfo, ColumnRegistry};
 use kimchi_msm::{
     columns::{Column, ColumnIndexer},
     witness::Witness,
 };
 use std::ops::{Index, IndexMut};
-use strum::EnumCount;
+use strum::{EnumCount, IntoEnumIterator};
 
 use super::{ITypeInstruction, JTypeInstruction, RTypeInstruction};
 
@@ -32,9 +33,19 @@ pub(crate) const MIPS_CHUNK_BYTES_LEN: usize = 4;
 /// The location of the preimage key as a field element of 248bits
 pub(crate) const MIPS_PREIMAGE_KEY: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 5 + 4 + 4 + 4;
 
+/// A flag indicating whether the preimage has a Keccak256 key, in which case
+/// its bytes and its key are sent to the Keccak coprocessor
+pub(crate) const MIPS_IS_KECCAK_PREIMAGE_OFF: usize = MIPS_PREIMAGE_KEY + 1;
+/// The columns of the bus connecting the preimage reads to the Keccak
+/// coprocessor, see [crate::pickles::keccak::mips_bus]
+pub(crate) const MIPS_BUS_OFF: usize = MIPS_IS_KECCAK_PREIMAGE_OFF + 1;
+/// The number of columns of the bus: one per byte of the chunk, one for the
+/// key of the preimage and one for the running sum
+pub(crate) const MIPS_BUS_COLUMNS: usize = MIPS_CHUNK_BYTES_LEN + 2;
+
 // MIPS + hash_counter + byte_counter + eof + num_bytes_read + chunk + bytes
-// + length + has_n_bytes + chunk_bytes + preimage
-pub const SCRATCH_SIZE: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 5 + 4 + 4 + 4 + 1;
+// + length + has_n_bytes + chunk_bytes + preimage + is_keccak + bus
+pub const SCRATCH_SIZE: usize = MIPS_BUS_OFF + MIPS_BUS_COLUMNS;
 
 /// Number of columns used by the MIPS interpreter to keep values to be
 /// inverted.
@@ -124,6 +135,10 @@ impl From<Instruction> for usize {
 /// - the (at most) 4 bytes of the preimage key that are currently being
 ///   processed
 /// - 4 helpers to check if at least n bytes were read in the current row
+/// - the preimage key, excluding its most significant byte
+/// - the flag to indicate if the preimage has a Keccak256 key
+/// - the columns of the bus connecting the preimage reads to the Keccak
+///   coprocessor
 pub type MIPSWitness<T> = Witness<N_MIPS_COLS, T>;
 
 // IMPLEMENTATIONS FOR COLUMN ALIAS
@@ -204,3 +219,73 @@ impl ColumnIndexer for Instruction {
         Column::DynamicSelector(usize::from(self) - N_MIPS_REL_COLS)
     }
 }
+
+// NAMES OF THE COLUMNS
+
+/// Returns the instruction activated by the selector `s`, i.e. the inverse of
+/// the mapping `From<Instruction> for usize`, shifted by [N_MIPS_REL_COLS].
+pub fn instruction_of_selector(s: usize) -> Option<Instruction> {
+    RTypeInstruction::iter()
+        .map(RType)
+        .chain(JTypeInstruction::iter().map(JType))
+        .chain(ITypeInstruction::iter().map(IType))
+        .nth(s)
+}
+
+/// The naming function of the columns of the MIPS circuit, see
+/// [column_registry].
+pub fn column_info(col: &Column) -> ColumnInfo {
+    match *col {
+        Column::Relation(i) if i < SCRATCH_SIZE => {
+            let info = ColumnInfo::new(format!("scratch[{i}]"), "mips");
+            let description = match i {
+                MIPS_HASH_COUNTER_OFF => Some("hash counter".to_string()),
+                MIPS_BYTE_COUNTER_OFF => Some("byte counter".to_string()),
+                MIPS_END_OF_PREIMAGE_OFF => Some("end of preimage flag".to_string()),
+                MIPS_NUM_BYTES_READ_OFF => Some("number of bytes read".to_string()),
+                MIPS_PREIMAGE_CHUNK_OFF => Some("preimage chunk".to_string()),
+                MIPS_PREIMAGE_KEY => Some("preimage key".to_string()),
+                i if (MIPS_PREIMAGE_BYTES_OFF..MIPS_LENGTH_BYTES_OFF).contains(&i) => {
+                    Some(format!("preimage byte {}", i - MIPS_PREIMAGE_BYTES_OFF))
+                }
+                i if (MIPS_LENGTH_BYTES_OFF..MIPS_HAS_N_BYTES_OFF).contains(&i) => {
+                    Some(format!("length byte {}", i - MIPS_LENGTH_BYTES_OFF))
+                }
+                i if (MIPS_HAS_N_BYTES_OFF..MIPS_PREIMAGE_KEY).contains(&i) => {
+                    Some(format!("has {} bytes flag", i - MIPS_HAS_N_BYTES_OFF + 1))
+                }
+                MIPS_IS_KECCAK_PREIMAGE_OFF => Some("Keccak preimage flag".to_string()),
+                i if i >= MIPS_BUS_OFF => Some(format!("bus {}", i - MIPS_BUS_OFF)),
+                _ => None,
+            };
+            match description {
+                Some(description) => info.with_description(description),
+                None => info,
+            }
+        }
+        Column::Relation(i) if i < SCRATCH_SIZE + SCRATCH_SIZE_INVERSE => {
+            ColumnInfo::new(format!("scratch_inverse[{}]", i - SCRATCH_SIZE), "mips")
+        }
+        Column::Relation(i) if i == SCRATCH_SIZE + SCRATCH_SIZE_INVERSE => {
+            ColumnInfo::new("instruction_counter", "mips")
+        }
+        Column::Relation(i) if i == SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 1 => {
+            ColumnInfo::new("error", "mips")
+        }
+        Column::DynamicSelector(s) => {
+            let info = ColumnInfo::new(format!("selector[{s}]"), "mips");
+            match instruction_of_selector(s) {
+                Some(instr) => info.with_description(format!("{instr:?}")),
+                None => info,
+            }
+        }
+        col => ColumnInfo::new(format!("{col:?}"), "mips").with_description("unknown column"),
+    }
+}
+
+/// The registry of the columns of the MIPS circuit. The scratch columns are
+/// shared by all the instructions; an instruction can describe the use it
+/// makes of a scratch column with [ColumnRegistry::annotate].
+pub fn column_registry() -> ColumnRegistry<Column> {
+    ColumnRegistry::new(column_info)
+}
@@ -3,8 +3,8 @@ use crate::{
         column::{
             ColumnAlias as MIPSColumn, MIPS_BYTE_COUNTER_OFF, MIPS_CHUNK_BYTES_LEN,
             MIPS_END_OF_PREIMAGE_OFF, MIPS_HASH_COUNTER_OFF, MIPS_HAS_N_BYTES_OFF,
-            MIPS_LENGTH_BYTES_OFF, MIPS_NUM_BYTES_READ_OFF, MIPS_PREIMAGE_BYTES_OFF,
-            MIPS_PREIMAGE_CHUNK_OFF, MIPS_PREIMAGE_KEY, N_MIPS_REL_COLS,
+            MIPS_IS_KECCAK_PREIMAGE_OFF, MIPS_LENGTH_BYTES_OFF, MIPS_NUM_BYTES_READ_OFF,
+            MIPS_PREIMAGE_BYTES_OFF, MIPS_PREIMAGE_CHUNK_OFF, MIPS_PREIMAGE_KEY, N_MIPS_REL_COLS,
         },
         interpreter::InterpreterEnv,
         Instruction,
@@ -405,10 +405,11 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
     fn report_exit(&mut self, _exit_code: &Self::Variable) {}
 
     /// This function checks that the preimage is read correctly.
-    /// It adds 13 constraints, and 5 lookups for the communication channel.
-    /// In particular, at every step it writes the bytes of the preimage into
-    /// the channel (excluding the length bytes) and it reads the hash digest
-    /// from the channel when the preimage is fully read.
+    /// It adds 14 constraints, and 5 lookups for the communication channel.
+    /// In particular, when the preimage has a Keccak256 key, at every step it
+    /// writes the bytes of the preimage into the channel (excluding the length
+    /// bytes) and it reads the hash digest from the channel when the preimage
+    /// is fully read.
     /// The output is the actual number of bytes that have been read.
     fn request_preimage_write(
         &mut self,
@@ -425,6 +426,10 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         // Whether this is the last step of the preimage or not (boolean)
         let end_of_preimage = self.variable(Self::Position::ScratchState(MIPS_END_OF_PREIMAGE_OFF));
 
+        // Whether the preimage has a Keccak256 key, i.e. is hashed by the
+        // Keccak circuit (boolean)
+        let is_keccak = self.variable(Self::Position::ScratchState(MIPS_IS_KECCAK_PREIMAGE_OFF));
+
         // How many preimage bytes are being processed in this instruction
         // FIXME: need to connect this to REGISTER_PREIMAGE_OFFSET or pos?
         let num_preimage_bytes_read =
@@ -456,14 +461,15 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         // The actual number of bytes read in this instruction, will be 0 <= x <= len <= 4
         let actual_read_bytes = self.variable(pos);
 
-        // EXTRA 13 CONSTRAINTS
+        // EXTRA 14 CONSTRAINTS
 
-        // 5 Booleanity constraints
+        // 6 Booleanity constraints
         {
             for var in has_n_bytes.iter() {
                 self.assert_boolean(var.clone());
             }
             self.assert_boolean(end_of_preimage.clone());
+            self.assert_boolean(is_keccak.clone());
         }
 
         // + 4 constraints
@@ -598,13 +604,15 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         self.lookup_2bits(&(actual_read_bytes.clone() - num_preimage_bytes_read.clone()));
 
         // COMMUNICATION CHANNEL: Write preimage chunk (1, 2, 3, or 4 bytes)
+        // The byte counter includes the bytes of this step, so the index of
+        // the first one is the counter minus the number of bytes read
         for i in 0..MIPS_CHUNK_BYTES_LEN {
             self.add_lookup(Lookup::write_if(
-                has_n_bytes[i].clone(),
+                has_n_bytes[i].clone() * is_keccak.clone(),
                 LookupTableIDs::SyscallLookup,
                 vec![
                     hash_counter.clone(),
-                    byte_counter.clone() + Expr::from(i as u64),
+                    byte_counter.clone() - num_preimage_bytes_read.clone() + Expr::from(i as u64),
                     bytes[i].clone(),
                 ],
             ));
@@ -616,7 +624,7 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         // TODO: keep track of counter to diminish the number of bytes at
         // each step and check it is zero at the end?
         self.add_lookup(Lookup::read_if(
-            end_of_preimage,
+            end_of_preimage * is_keccak,
             LookupTableIDs::SyscallLookup,
             vec![hash_counter.clone(), preimage_key],
         ));
@@ -629,6 +637,40 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         // No-op, witness only
     }
 
+    fn request_input_read(
+        &mut self,
+        _fd: &Self::Variable,
+        _addr: &Self::Variable,
+        _len: &Self::Variable,
+        bytes: [Self::Position; 4],
+        pos: Self::Position,
+    ) -> Self::Variable {
+        let read_length = self.variable(pos);
+        // The bytes which are not read are zero: the i-th byte is zero when
+        // the number of bytes read, at most 4, is at most i
+        for (i, byte) in bytes.into_iter().enumerate() {
+            let at_most_i = ((i + 1)..=4).fold(Expr::from(1u64), |acc, j| {
+                acc * (read_length.clone() - Expr::from(j as u64))
+            });
+            let byte = self.variable(byte);
+            self.add_constraint(at_most_i * byte);
+        }
+        read_length
+    }
+
+    fn request_output_write(
+        &mut self,
+        _fd: &Self::Variable,
+        _addr: &Self::Variable,
+        _len: &Self::Variable,
+    ) {
+        // No-op, witness only
+    }
+
+    fn request_brk(&mut self, _addr: &Self::Variable, pos: Self::Position) -> Self::Variable {
+        self.variable(pos)
+    }
+
     fn reset(&mut self) {
         self.scratch_state_idx = 0;
         self.scratch_state_idx_inverse = 0;
@@ -0,0 +1,96 @@
+//! This module implements the [`Coverage`] report of the MIPS witness
+//! environment (see
+//! [`Env::coverage`](crate::interpreters::mips::witness::Env::coverage)), which
+//! records the instructions executed by a program, to check that a set of test
+//! programs covers the whole instruction set.
+
+use crate::interpreters::mips::{
+    constraints,
+    interpreter::{self, Instruction},
+};
+use ark_ff::Field;
+use std::{collections::BTreeMap, fmt};
+use strum::IntoEnumIterator;
+
+/// The executions of an instruction, and the constraints and lookups of the
+/// instruction in the circuit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InstructionCoverage {
+    /// The number of times the instruction has been executed
+    pub activations: usize,
+    /// The number of constraints of the instruction
+    pub constraints: usize,
+    /// The number of lookups of the instruction
+    pub lookups: usize,
+}
+
+/// A report of the instructions executed by the witness environment
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub instructions: BTreeMap<Instruction, InstructionCoverage>,
+}
+
+impl Coverage {
+    /// Records the execution of `instruction`. The constraints and the lookups
+    /// of the instruction are the same at every execution: they are counted
+    /// once, on a constraints environment over `Fp`, as the witness
+    /// environment does not build them.
+    pub(crate) fn activate<Fp: Field>(&mut self, instruction: Instruction) {
+        let coverage = self.instructions.entry(instruction).or_insert_with(|| {
+            let mut env = constraints::Env::<Fp>::default();
+            interpreter::interpret_instruction(&mut env, instruction);
+            InstructionCoverage {
+                activations: 0,
+                constraints: env.get_constraints().len(),
+                lookups: env.get_lookups().len(),
+            }
+        });
+        coverage.activations += 1;
+    }
+
+    /// The instructions which have never been executed
+    pub fn missing(&self) -> Vec<Instruction> {
+        Instruction::iter()
+            .flat_map(|instr_typ| instr_typ.into_iter())
+            .filter(|instr| !self.instructions.contains_key(instr))
+            .collect()
+    }
+
+    /// Returns true if all the instructions have been executed
+    pub fn is_complete(&self) -> bool {
+        self.missing().is_empty()
+    }
+
+    /// The instructions which have been executed, but which add no constraint
+    pub fn unconstrained(&self) -> Vec<Instruction> {
+        self.instructions
+            .iter()
+            .filter(|(_, coverage)| coverage.constraints == 0)
+            .map(|(instr, _)| *instr)
+            .collect()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing = self.missing();
+        let total = self.instructions.len() + missing.len();
+        writeln!(
+            f,
+            "{}/{} instructions covered",
+            self.instructions.len(),
+            total
+        )?;
+        for (instr, coverage) in self.instructions.iter() {
+            writeln!(
+                f,
+                "{:?}: {} activations, {} constraints, {} lookups",
+                instr, coverage.activations, coverage.constraints, coverage.lookups
+            )?;
+        }
+        for instr in missing {
+            writeln!(f, "{:?}: not covered", instr)?;
+        }
+        Ok(())
+    }
+}
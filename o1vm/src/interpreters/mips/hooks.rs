@@ -0,0 +1,30 @@
+//! This module defines the [`Hooks`] trait, which lets embedders observe the
+//! execution of the MIPS witness environment (see
+//! [`Env::add_hook`](crate::interpreters::mips::witness::Env::add_hook)), e.g.
+//! to implement tracing, coverage or custom metering, without modifying the
+//! interpreter.
+//!
+//! The hooks are only available with the `hooks` feature, and are compiled
+//! out otherwise.
+
+use crate::interpreters::mips::interpreter::{Instruction, RTypeInstruction};
+
+/// An observer of the MIPS witness environment. All the methods do nothing by
+/// default, so that implementations only need to define the events they are
+/// interested in. The `step` given to the methods is the normalized
+/// instruction counter of the instruction being executed.
+pub trait Hooks {
+    /// Called before executing `instruction`, located at address `pc`
+    fn on_instruction(&mut self, _step: u64, _pc: u32, _instruction: Instruction) {}
+
+    /// Called before executing the syscall `syscall`. It is called after
+    /// [`Hooks::on_instruction`] for the same instruction.
+    fn on_syscall(&mut self, _step: u64, _syscall: RTypeInstruction) {}
+
+    /// Called when the byte `value` is read from memory at address `addr`,
+    /// including the bytes of the instruction being executed
+    fn on_memory_read(&mut self, _step: u64, _addr: u32, _value: u8) {}
+
+    /// Called when the byte `value` is written in memory at address `addr`
+    fn on_memory_write(&mut self, _step: u64, _addr: u32, _value: u8) {}
+}
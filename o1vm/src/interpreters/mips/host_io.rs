@@ -0,0 +1,31 @@
+//! This module defines the [`HostIO`] trait, which lets embedders feed
+//! external data to the program executed by the MIPS witness environment (see
+//! [`Env::set_host_io`](crate::interpreters::mips::witness::Env::set_host_io)),
+//! e.g. the content of files, hints or the responses of an oracle, enabling
+//! applications beyond fault proofs.
+//!
+//! The program requests the data with the `read` syscall on the file
+//! descriptor [`FD_HOST_READ`](crate::interpreters::mips::interpreter::FD_HOST_READ).
+//! The bytes given by the host are recorded in
+//! [`SyscallEnv::host_input`](crate::interpreters::mips::witness::SyscallEnv::host_input),
+//! and the proofs of the pickles flavor commit to a digest of the bytes in
+//! their public values, see [crate::pickles::continuation].
+
+use std::collections::VecDeque;
+
+/// A source of external data for the program. The `step` given to the methods
+/// is the normalized instruction counter of the syscall being executed.
+pub trait HostIO {
+    /// Returns at most `len` bytes of data for the program. Returning fewer
+    /// bytes than requested is a short read, and returning no bytes signals
+    /// the end of the data, like for the standard input.
+    fn read(&mut self, step: u64, len: usize) -> Vec<u8>;
+}
+
+/// The bytes of the queue are given to the program in order
+impl HostIO for VecDeque<u8> {
+    fn read(&mut self, _step: u64, len: usize) -> Vec<u8> {
+        let len = std::cmp::min(len, self.len());
+        self.drain(..len).collect()
+    }
+}
@@ -17,15 +17,23 @@ pub const FD_HINT_READ: u32 = 3;
 pub const FD_HINT_WRITE: u32 = 4;
 pub const FD_PREIMAGE_READ: u32 = 5;
 pub const FD_PREIMAGE_WRITE: u32 = 6;
+/// The data of the host, see [crate::interpreters::mips::host_io]
+pub const FD_HOST_READ: u32 = 7;
 
 pub const SYSCALL_MMAP: u32 = 4090;
 pub const SYSCALL_BRK: u32 = 4045;
 pub const SYSCALL_CLONE: u32 = 4120;
+pub const SYSCALL_EXIT: u32 = 4001;
 pub const SYSCALL_EXIT_GROUP: u32 = 4246;
 pub const SYSCALL_READ: u32 = 4003;
 pub const SYSCALL_WRITE: u32 = 4004;
 pub const SYSCALL_FCNTL: u32 = 4055;
 
+/// The initial program break, returned by `brk` until the program moves it
+pub const INITIAL_BRK: u32 = 0x4000_0000;
+/// The bound of the program break, which keeps the heap below the stack
+pub const MAX_BRK: u32 = INITIAL_BRK + (1 << 29);
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, EnumCount, EnumIter, Hash, Ord, PartialOrd)]
 pub enum Instruction {
     RType(RTypeInstruction),
@@ -80,6 +88,9 @@ pub enum RTypeInstruction {
     MultiplyToRegister,           // mul
     CountLeadingOnes,             // clo
     CountLeadingZeros,            // clz
+    SignExtendByte,               // seb
+    SignExtendHalfword,           // seh
+    TrapIfEqual,                  // teq
 }
 
 #[derive(
@@ -102,6 +113,8 @@ pub enum ITypeInstruction {
     BranchGtZero,                 // bgtz
     BranchLtZero,                 // bltz
     BranchGeqZero,                // bgez
+    BranchLtZeroAndLink,          // bltzal
+    BranchGeqZeroAndLink,         // bgezal
     AddImmediate,                 // addi
     AddImmediateUnsigned,         // addiu
     SetLessThanImmediate,         // slti
@@ -953,6 +966,36 @@ pub trait InterpreterEnv {
 
     fn request_hint_write(&mut self, addr: &Self::Variable, len: &Self::Variable);
 
+    /// Request at most `len` bytes of the input given by the host to the
+    /// program on the file descriptor `fd`, i.e. the standard input or the
+    /// data of the host, and store the bytes starting from `addr`.
+    /// It returns the number of bytes actually read, which will be set into
+    /// `pos`. Like for the preimages, at most 4 bytes are read, without
+    /// crossing a word boundary, and the i-th byte read is set into `bytes[i]`,
+    /// so that the input is part of the witness.
+    fn request_input_read(
+        &mut self,
+        fd: &Self::Variable,
+        addr: &Self::Variable,
+        len: &Self::Variable,
+        bytes: [Self::Position; 4],
+        pos: Self::Position,
+    ) -> Self::Variable;
+
+    /// Write the `len` bytes starting from `addr` to the output of the file
+    /// descriptor `fd`, i.e. the standard output or the standard error.
+    fn request_output_write(
+        &mut self,
+        fd: &Self::Variable,
+        addr: &Self::Variable,
+        len: &Self::Variable,
+    );
+
+    /// Move the program break to `addr`, unless `addr` is below
+    /// [INITIAL_BRK] or not below [MAX_BRK], and return the resulting program
+    /// break, which will be set into `pos`.
+    fn request_brk(&mut self, addr: &Self::Variable, pos: Self::Position) -> Self::Variable;
+
     /// Reset the environment to handle the next instruction
     fn reset(&mut self);
 }
@@ -1172,21 +1215,44 @@ pub fn interpret_rtype<Env: InterpreterEnv>(env: &mut Env, instr: RTypeInstructi
         }
         RTypeInstruction::SyscallReadOther => {
             let fd_id = env.read_register(&Env::constant(4));
+            let addr = env.read_register(&Env::constant(5));
+            let length = env.read_register(&Env::constant(6));
             let mut check_equal = |expected_fd_id: u32| {
                 // FIXME: Requires constraints
                 let pos = env.alloc_scratch();
                 unsafe { env.test_zero(&(fd_id.clone() - Env::constant(expected_fd_id)), pos) }
             };
             let is_stdin = check_equal(FD_STDIN);
+            let is_host_read = check_equal(FD_HOST_READ);
             let is_preimage_read = check_equal(FD_PREIMAGE_READ);
             let is_hint_read = check_equal(FD_HINT_READ);
 
+            let is_input = is_stdin + is_host_read;
             // FIXME: Should assert that `is_preimage_read` and `is_hint_read` cannot be true here.
-            let other_fd = Env::constant(1) - is_stdin - is_preimage_read - is_hint_read;
+            let other_fd = Env::constant(1) - is_input.clone() - is_preimage_read - is_hint_read;
 
-            // We're either reading stdin, in which case we get `(0, 0)` as desired, or we've hit a
-            // bad FD that we reject with EBADF.
-            let v0 = other_fd.clone() * Env::constant(0xFFFFFFFF);
+            // Nothing is read if the FD is neither stdin nor the data of the host
+            let requested_length = is_input * length;
+            let bytes = std::array::from_fn(|_| env.alloc_scratch());
+            let read_length = {
+                let pos = env.alloc_scratch();
+                env.request_input_read(&fd_id, &addr, &requested_length, bytes, pos)
+            };
+            // At most 4 bytes are read, and none if none is requested
+            // FIXME: Requires constraints linking the bytes to the memory, and
+            // bounding the length read by the length requested and the
+            // alignment of `addr`
+            env.range_check2(&read_length);
+            let nothing_requested = env.is_zero(&requested_length);
+            env.add_constraint(nothing_requested * read_length.clone());
+            for byte in bytes {
+                let byte = env.variable(byte);
+                env.lookup_8bits(&byte);
+            }
+
+            // We're either reading an input, in which case we get `(read_length, 0)` as desired, or
+            // we've hit a bad FD that we reject with EBADF.
+            let v0 = read_length + other_fd.clone() * Env::constant(0xFFFFFFFF);
             let v1 = other_fd * Env::constant(0x9); // EBADF
 
             env.write_register(&Env::constant(2), v0);
@@ -1357,7 +1423,9 @@ pub fn interpret_rtype<Env: InterpreterEnv>(env: &mut Env, instr: RTypeInstructi
         }
         RTypeInstruction::SyscallWriteOther => {
             let fd_id = env.read_register(&Env::constant(4));
+            let addr = env.read_register(&Env::constant(5));
             let write_length = env.read_register(&Env::constant(6));
+            env.request_output_write(&fd_id, &addr, &write_length);
             let mut check_equal = |expected_fd_id: u32| {
                 // FIXME: Requires constraints
                 let pos = env.alloc_scratch();
@@ -1418,7 +1486,31 @@ pub fn interpret_rtype<Env: InterpreterEnv>(env: &mut Env, instr: RTypeInstructi
             let syscall_num = env.read_register(&Env::constant(2));
             let is_sysbrk = env.equal(&syscall_num, &Env::constant(SYSCALL_BRK));
             let is_sysclone = env.equal(&syscall_num, &Env::constant(SYSCALL_CLONE));
-            let v0 = { is_sysbrk * Env::constant(0x40000000) + is_sysclone };
+            // The program break is left unchanged if the syscall is not brk
+            // FIXME: Requires constraints linking the program break to the
+            // previous one and to the address requested
+            let brk = {
+                let addr = env.read_register(&Env::constant(4));
+                let pos = env.alloc_scratch();
+                env.request_brk(&(is_sysbrk.clone() * addr), pos)
+            };
+            // The program break is between the initial one and the bound below
+            // the stack: 0 <= brk - INITIAL_BRK < 2^29
+            {
+                let offset = brk.clone() - Env::constant(INITIAL_BRK);
+                let low = {
+                    let pos = env.alloc_scratch();
+                    unsafe { env.bitmask(&offset, 16, 0, pos) }
+                };
+                let high = {
+                    let pos = env.alloc_scratch();
+                    unsafe { env.bitmask(&offset, 32, 16, pos) }
+                };
+                env.lookup_16bits(&low);
+                env.range_check16(&high, 13);
+                env.add_constraint(offset - (high * Env::constant(1 << 16) + low));
+            }
+            let v0 = { is_sysbrk * brk + is_sysclone };
             let v1 = Env::constant(0);
             env.write_register(&Env::constant(2), v0);
             env.write_register(&Env::constant(7), v1);
@@ -1691,6 +1783,60 @@ pub fn interpret_rtype<Env: InterpreterEnv>(env: &mut Env, instr: RTypeInstructi
             env.set_instruction_pointer(next_instruction_pointer.clone());
             env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
         }
+        RTypeInstruction::SignExtendByte | RTypeInstruction::SignExtendHalfword => {
+            let bitlength = if instr == RTypeInstruction::SignExtendByte {
+                8
+            } else {
+                16
+            };
+            let rt = env.read_register(&rt);
+            // Decompose rt as rest + 2^(bitlength - 1) * sign_bit
+            // + 2^bitlength * high_bits, each part being range checked.
+            let sign_bit = {
+                let pos = env.alloc_scratch();
+                unsafe { env.bitmask(&rt, bitlength, bitlength - 1, pos) }
+            };
+            let rest = {
+                let pos = env.alloc_scratch();
+                unsafe { env.bitmask(&rt, bitlength - 1, 0, pos) }
+            };
+            let high_16 = {
+                let pos = env.alloc_scratch();
+                unsafe { env.bitmask(&rt, 32, 16, pos) }
+            };
+            env.lookup_16bits(&high_16);
+            let high_bits = if bitlength == 8 {
+                let mid_8 = {
+                    let pos = env.alloc_scratch();
+                    unsafe { env.bitmask(&rt, 16, 8, pos) }
+                };
+                env.lookup_8bits(&mid_8);
+                env.range_check8(&rest, 7);
+                mid_8 + high_16 * Env::constant(1 << 8)
+            } else {
+                env.range_check16(&rest, 15);
+                high_16
+            };
+            let low_bits = sign_bit.clone() * Env::constant(1 << (bitlength - 1)) + rest;
+            env.add_constraint(rt - (low_bits.clone() + high_bits * Env::constant(1 << bitlength)));
+            env.add_constraint(sign_bit.clone() * (Env::constant(1) - sign_bit.clone()));
+            let res =
+                sign_bit * Env::constant(((1 << (32 - bitlength)) - 1) << bitlength) + low_bits;
+            env.write_register(&rd, res);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        RTypeInstruction::TrapIfEqual => {
+            let rs = env.read_register(&rs);
+            let rt = env.read_register(&rt);
+            let equals = env.equal(&rs, &rt);
+            // A trap stops the program with an error, so an execution trapping
+            // cannot be proven.
+            Env::check_is_zero(&equals);
+            env.add_constraint(equals);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
     };
 }
 
@@ -1889,9 +2035,17 @@ pub fn interpret_itype<Env: InterpreterEnv>(env: &mut Env, instr: ITypeInstructi
             env.set_instruction_pointer(next_instruction_pointer);
             env.set_next_instruction_pointer(addr);
         }
-        ITypeInstruction::BranchLtZero => {
+        ITypeInstruction::BranchLtZero | ITypeInstruction::BranchLtZeroAndLink => {
             let offset = env.sign_extend(&(immediate * Env::constant(1 << 2)), 18);
             let rs = env.read_register(&rs);
+            if instr == ITypeInstruction::BranchLtZeroAndLink {
+                // The return address is written whether the branch is taken or
+                // not
+                env.write_register(
+                    &Env::constant(31),
+                    instruction_pointer.clone() + Env::constant(8),
+                );
+            }
             let less_than = {
                 // FIXME: Requires constraints
                 let pos = env.alloc_scratch();
@@ -1916,9 +2070,17 @@ pub fn interpret_itype<Env: InterpreterEnv>(env: &mut Env, instr: ITypeInstructi
             env.set_instruction_pointer(next_instruction_pointer);
             env.set_next_instruction_pointer(addr);
         }
-        ITypeInstruction::BranchGeqZero => {
+        ITypeInstruction::BranchGeqZero | ITypeInstruction::BranchGeqZeroAndLink => {
             let offset = env.sign_extend(&(immediate * Env::constant(1 << 2)), 18);
             let rs = env.read_register(&rs);
+            if instr == ITypeInstruction::BranchGeqZeroAndLink {
+                // The return address is written whether the branch is taken or
+                // not
+                env.write_register(
+                    &Env::constant(31),
+                    instruction_pointer.clone() + Env::constant(8),
+                );
+            }
             let less_than = {
                 // FIXME: Requires constraints
                 let pos = env.alloc_scratch();
@@ -16,14 +16,22 @@
 //! [crate::interpreters::mips::interpreter], and the evaluations will be kept
 //! in the structure ProofInputs.
 
+pub mod boundary;
 pub mod column;
 pub mod constraints;
+pub mod coverage;
+#[cfg(feature = "hooks")]
+pub mod hooks;
+pub mod host_io;
 pub mod interpreter;
 pub mod registers;
+pub mod snapshot;
 #[cfg(test)]
 pub mod tests;
 #[cfg(test)]
 pub mod tests_helpers;
+#[cfg(feature = "hooks")]
+pub mod trace;
 pub mod witness;
 
 pub use interpreter::{ITypeInstruction, Instruction, JTypeInstruction, RTypeInstruction};
@@ -0,0 +1,171 @@
+//! Checkpointing the witness environment to disk.
+//!
+//! A [Snapshot] holds the complete state of the interpreter between two
+//! instructions: the registers and the counters carried by a [BoundaryState],
+//! the memory pages and their last access indices, the state of the syscalls,
+//! and the cursor in the preimage being read. It can be written to a file (see
+//! [Snapshot::write]) while generating a long witness, and the execution can be
+//! resumed later from it (see [Snapshot::restore]), e.g. to re-execute a
+//! failing step in isolation.
+//!
+//! The snapshots are encoded with MessagePack, as the memory makes them too
+//! large for JSON.
+//!
+//! The Keccak environment created when a preimage has been read entirely is
+//! not part of the snapshot: a snapshot can only be taken once it has been
+//! consumed.
+
+use crate::{
+    interpreters::mips::{
+        boundary::BoundaryState,
+        column::N_MIPS_SEL_COLS,
+        coverage::Coverage,
+        witness::{Env as WEnv, SyscallEnv},
+    },
+    preimage_oracle::PreImageOracleT,
+};
+use ark_ff::Field;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+use thiserror::Error;
+
+/// Errors that can arise when writing or reading a snapshot
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("the snapshot file cannot be accessed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the snapshot cannot be encoded: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("the snapshot cannot be decoded: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+    #[error("the Keccak hash of a preimage is in progress")]
+    KeccakInProgress,
+}
+
+/// The state of the witness environment between two instructions
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The registers, the instruction counter and the preimage counters
+    pub boundary: BoundaryState,
+    pub memory: Vec<(u32, Vec<u8>)>,
+    pub memory_write_index: Vec<(u32, Vec<u64>)>,
+    /// The caches of the indices of the last accessed pages
+    pub last_memory_accesses: [usize; 3],
+    pub last_memory_write_index_accesses: [usize; 3],
+    pub syscall_env: SyscallEnv,
+    /// The pages written since the Merkle tree of the memory was last updated
+    pub written_pages: BTreeSet<u32>,
+    /// The preimage being read, with its key
+    pub preimage: Option<Vec<u8>>,
+    pub preimage_key: Option<[u8; 32]>,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the witness environment
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the Keccak environment of the last preimage read has
+    /// not been consumed, as it cannot be restored.
+    pub fn capture<Fp: Field, PreImageOracle: PreImageOracleT>(
+        env: &WEnv<Fp, PreImageOracle>,
+    ) -> Result<Self, SnapshotError> {
+        if env.keccak_env.is_some() {
+            return Err(SnapshotError::KeccakInProgress);
+        }
+        Ok(Snapshot {
+            boundary: BoundaryState::spill(env),
+            memory: env.memory.clone(),
+            memory_write_index: env.memory_write_index.clone(),
+            last_memory_accesses: env.last_memory_accesses,
+            last_memory_write_index_accesses: env.last_memory_write_index_accesses,
+            syscall_env: env.syscall_env.clone(),
+            written_pages: env.written_pages.clone(),
+            preimage: env.preimage.clone(),
+            preimage_key: env.preimage_key,
+        })
+    }
+
+    /// Creates a witness environment resuming the execution from the
+    /// snapshot. The preimages still to be read are requested to
+    /// `preimage_oracle`.
+    pub fn restore<Fp: Field, PreImageOracle: PreImageOracleT>(
+        self,
+        preimage_oracle: PreImageOracle,
+    ) -> WEnv<Fp, PreImageOracle> {
+        let boundary = self.boundary;
+        WEnv {
+            instruction_counter: boundary.instruction_counter,
+            memory: self.memory,
+            last_memory_accesses: self.last_memory_accesses,
+            memory_write_index: self.memory_write_index,
+            last_memory_write_index_accesses: self.last_memory_write_index_accesses,
+            registers: boundary.registers,
+            registers_write_index: boundary.registers_write_index,
+            scratch_state_idx: 0,
+            scratch_state_idx_inverse: 0,
+            scratch_state: std::array::from_fn(|_| Fp::zero()),
+            scratch_state_inverse: std::array::from_fn(|_| Fp::zero()),
+            lookups: vec![],
+            halt: boundary.halt,
+            syscall_env: self.syscall_env,
+            written_pages: self.written_pages,
+            selector: N_MIPS_SEL_COLS,
+            preimage_oracle,
+            preimage: self.preimage,
+            preimage_bytes_read: boundary.preimage_bytes_read,
+            preimage_key: self.preimage_key,
+            keccak_env: None,
+            hash_counter: boundary.hash_counter,
+            host_io: None,
+            #[cfg(feature = "hooks")]
+            hooks: vec![],
+            coverage: Coverage::default(),
+        }
+    }
+
+    /// Encodes the snapshot
+    ///
+    /// # Errors
+    ///
+    /// See [rmp_serde::to_vec].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        Ok(rmp_serde::to_vec(self)?)
+    }
+
+    /// Decodes a snapshot encoded with [Snapshot::to_bytes]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not a valid snapshot.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// Writes the snapshot to the file `path`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), SnapshotError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        rmp_serde::encode::write(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a snapshot written with [Snapshot::write]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be read or is not a valid snapshot.
+    pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(rmp_serde::from_read(reader)?)
+    }
+}
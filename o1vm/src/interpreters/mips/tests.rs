@@ -3,7 +3,7 @@ use crate::{
     interpreters::mips::{
         column::{N_MIPS_REL_COLS, N_MIPS_SEL_COLS},
         constraints,
-        interpreter::debugging::InstructionParts,
+        interpreter::{debugging::InstructionParts, interpret_instruction},
         tests_helpers::*,
         ITypeInstruction, JTypeInstruction, RTypeInstruction,
     },
@@ -67,10 +67,59 @@ fn test_all_instructions_have_a_usize_representation_smaller_than_the_number_of_
         );
     });
 }
+
+#[test]
+fn test_coverage_counts_the_constraints_of_the_executed_instructions() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let mut dummy_env = dummy_env(&mut rng);
+    for instr in [
+        Instruction::RType(RTypeInstruction::Add),
+        Instruction::RType(RTypeInstruction::AddUnsigned),
+        Instruction::RType(RTypeInstruction::And),
+        Instruction::RType(RTypeInstruction::MoveZero),
+    ] {
+        let mut mips_con_env = constraints::Env::<Fp>::default();
+        interpret_instruction(&mut mips_con_env, instr);
+        interpret_instruction(&mut dummy_env, instr);
+        dummy_env.reset_scratch_state();
+        dummy_env.reset_scratch_state_inverse();
+        let coverage = dummy_env.coverage.instructions[&instr];
+        assert_eq!(coverage.activations, 1);
+        assert_eq!(coverage.constraints, mips_con_env.get_constraints().len());
+        assert_eq!(coverage.lookups, mips_con_env.get_lookups().len());
+    }
+}
+
+#[test]
+fn test_coverage_reports_missing_instructions() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let mut dummy_env = dummy_env(&mut rng);
+    let add = Instruction::RType(RTypeInstruction::Add);
+    for _ in 0..2 {
+        interpret_instruction(&mut dummy_env, add);
+        dummy_env.reset_scratch_state();
+    }
+    let coverage = &dummy_env.coverage;
+    assert_eq!(coverage.instructions.len(), 1);
+    assert_eq!(coverage.instructions[&add].activations, 2);
+    let missing = coverage.missing();
+    assert_eq!(missing.len(), N_MIPS_SEL_COLS - 1);
+    assert!(!missing.contains(&add));
+    assert!(!coverage.is_complete());
+    assert!(coverage
+        .to_string()
+        .starts_with(&format!("1/{N_MIPS_SEL_COLS} instructions covered")));
+}
+
 mod rtype {
 
     use super::*;
-    use crate::interpreters::mips::{interpreter::interpret_rtype, RTypeInstruction};
+    use crate::interpreters::mips::{
+        interpreter::{
+            interpret_rtype, FD_HOST_READ, FD_STDIN, FD_STDOUT, INITIAL_BRK, MAX_BRK, SYSCALL_BRK,
+        },
+        RTypeInstruction,
+    };
 
     #[test]
     fn test_unit_syscall_read_preimage() {
@@ -152,6 +201,135 @@ mod rtype {
         }
     }
 
+    #[test]
+    fn test_unit_syscall_read_stdin() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let input = b"hello, world".to_vec();
+        dummy_env.syscall_env.stdin = input.clone();
+
+        // Set a random address for register 5 that might not be aligned
+        let addr = rng.gen_range(100..200);
+        let mut offset = 0;
+        loop {
+            dummy_env.reset_scratch_state();
+            dummy_env.reset_scratch_state_inverse();
+
+            dummy_env.registers[4] = FD_STDIN;
+            dummy_env.registers[5] = addr + offset;
+            dummy_env.registers[6] = rng.gen_range(1..=8);
+
+            interpret_rtype(&mut dummy_env, RTypeInstruction::SyscallReadOther);
+
+            // No error, and at most 4 bytes read per call
+            assert_eq!(dummy_env.registers[7], 0);
+            let read_length = dummy_env.registers[2];
+            assert!(read_length <= 4);
+            // The end of the input has been reached
+            if read_length == 0 {
+                break;
+            }
+            offset += read_length;
+        }
+
+        assert_eq!(offset as usize, input.len());
+        for (i, b) in input.iter().enumerate() {
+            assert_eq!(
+                dummy_env.memory[0].1[i + addr as usize],
+                *b,
+                "{}-th input byte does not match",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_unit_syscall_read_host_io() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let input = b"an oracle response".to_vec();
+        dummy_env.set_host_io(Box::new(std::collections::VecDeque::from(input.clone())));
+
+        let addr = rng.gen_range(100..200);
+        let mut offset = 0;
+        loop {
+            dummy_env.reset_scratch_state();
+            dummy_env.reset_scratch_state_inverse();
+
+            dummy_env.registers[4] = FD_HOST_READ;
+            dummy_env.registers[5] = addr + offset;
+            dummy_env.registers[6] = rng.gen_range(1..=8);
+
+            interpret_rtype(&mut dummy_env, RTypeInstruction::SyscallReadOther);
+
+            assert_eq!(dummy_env.registers[7], 0);
+            let read_length = dummy_env.registers[2];
+            assert!(read_length <= 4);
+            if read_length == 0 {
+                break;
+            }
+            offset += read_length;
+        }
+
+        // The data is written in memory and recorded, and stdin is not read
+        assert_eq!(offset as usize, input.len());
+        assert_eq!(
+            &dummy_env.memory[0].1[addr as usize..addr as usize + input.len()],
+            input.as_slice()
+        );
+        assert_eq!(dummy_env.syscall_env.host_input, input);
+        assert_eq!(dummy_env.syscall_env.stdin_offset, 0);
+    }
+
+    #[test]
+    fn test_unit_syscall_write_stdout() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let output = b"hello, world";
+        let addr = rng.gen_range(100..200);
+        dummy_env.memory[0].1[addr..addr + output.len()].copy_from_slice(output);
+
+        dummy_env.registers[4] = FD_STDOUT;
+        dummy_env.registers[5] = addr as u32;
+        dummy_env.registers[6] = output.len() as u32;
+
+        interpret_rtype(&mut dummy_env, RTypeInstruction::SyscallWriteOther);
+
+        assert_eq!(dummy_env.registers[2], output.len() as u32);
+        assert_eq!(dummy_env.registers[7], 0);
+        assert_eq!(dummy_env.syscall_env.stdout, output);
+        assert!(dummy_env.syscall_env.stderr.is_empty());
+    }
+
+    #[test]
+    fn test_unit_syscall_brk() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+
+        // brk(0) returns the current break
+        dummy_env.registers[2] = SYSCALL_BRK;
+        dummy_env.registers[4] = 0;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::SyscallOther);
+        assert_eq!(dummy_env.registers[2], INITIAL_BRK);
+
+        // Moving the break returns the new break
+        dummy_env.reset_scratch_state();
+        dummy_env.reset_scratch_state_inverse();
+        dummy_env.registers[2] = SYSCALL_BRK;
+        dummy_env.registers[4] = INITIAL_BRK + 0x1000;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::SyscallOther);
+        assert_eq!(dummy_env.registers[2], INITIAL_BRK + 0x1000);
+        assert_eq!(dummy_env.syscall_env.brk, INITIAL_BRK + 0x1000);
+
+        // The break is not moved up to the stack
+        dummy_env.reset_scratch_state();
+        dummy_env.reset_scratch_state_inverse();
+        dummy_env.registers[2] = SYSCALL_BRK;
+        dummy_env.registers[4] = MAX_BRK;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::SyscallOther);
+        assert_eq!(dummy_env.registers[2], INITIAL_BRK + 0x1000);
+    }
+
     #[test]
     fn test_unit_sub_instruction() {
         let mut rng = o1_utils::tests::make_test_rng(None);
@@ -180,12 +358,152 @@ mod rtype {
         interpret_rtype(&mut dummy_env, RTypeInstruction::Sub);
         assert_eq!(dummy_env.registers.general_purpose[reg_dst], exp_res);
     }
+
+    #[test]
+    fn test_unit_div_edge_cases() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        // Instruction: 0b00000000001000100000000000011010 div $at, $v0
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b000000,
+                rs: 0b00001,
+                rt: 0b00010,
+                rd: 0b00000,
+                shamt: 0b00000,
+                funct: 0b011010,
+            },
+        );
+        let pc = dummy_env.registers.current_instruction_pointer;
+        // Division by zero
+        dummy_env.registers[1] = 42;
+        dummy_env.registers[2] = 0;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::Div);
+        assert_eq!(dummy_env.registers.lo, u32::MAX);
+        assert_eq!(dummy_env.registers.hi, 42);
+
+        // Overflow
+        dummy_env.reset_scratch_state();
+        dummy_env.registers.current_instruction_pointer = pc;
+        dummy_env.registers[1] = i32::MIN as u32;
+        dummy_env.registers[2] = -1i32 as u32;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::Div);
+        assert_eq!(dummy_env.registers.lo, i32::MIN as u32);
+        assert_eq!(dummy_env.registers.hi, 0);
+
+        // Unsigned division by zero
+        dummy_env.reset_scratch_state();
+        dummy_env.registers.current_instruction_pointer = pc;
+        dummy_env.registers[2] = 0;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::DivUnsigned);
+        assert_eq!(dummy_env.registers.lo, u32::MAX);
+        assert_eq!(dummy_env.registers.hi, i32::MIN as u32);
+    }
+
+    #[test]
+    fn test_unit_seb_seh_instructions() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        // Instruction: 0b01111100000000010001010000100000 seb $v0, $at
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b011111,
+                rs: 0b00000,
+                rt: 0b00001,
+                rd: 0b00010,
+                shamt: 0b10000,
+                funct: 0b100000,
+            },
+        );
+        dummy_env.registers[1] = 0x1234_5680;
+        let pc = dummy_env.registers.current_instruction_pointer;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::SignExtendByte);
+        assert_eq!(dummy_env.registers[2], 0xFFFF_FF80);
+
+        dummy_env.reset_scratch_state();
+        dummy_env.registers.current_instruction_pointer = pc;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::SignExtendHalfword);
+        assert_eq!(dummy_env.registers[2], 0x5680);
+    }
+
+    #[test]
+    fn test_unit_teq_instruction() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        // Instruction: 0b00000000001000100000000111110100 teq $at, $v0, 7
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b000000,
+                rs: 0b00001,
+                rt: 0b00010,
+                rd: 0b00000,
+                shamt: 0b00111,
+                funct: 0b110100,
+            },
+        );
+        dummy_env.registers[1] = 1;
+        dummy_env.registers[2] = 2;
+        let next_instruction_pointer = dummy_env.registers.next_instruction_pointer;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::TrapIfEqual);
+        assert_eq!(
+            dummy_env.registers.current_instruction_pointer,
+            next_instruction_pointer
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unit_teq_instruction_traps() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        // Instruction: 0b00000000001000100000000111110100 teq $at, $v0, 7
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b000000,
+                rs: 0b00001,
+                rt: 0b00010,
+                rd: 0b00000,
+                shamt: 0b00111,
+                funct: 0b110100,
+            },
+        );
+        dummy_env.registers[1] = 0;
+        dummy_env.registers[2] = 0;
+        interpret_rtype(&mut dummy_env, RTypeInstruction::TrapIfEqual);
+    }
 }
 
 mod itype {
     use super::*;
     use crate::interpreters::mips::{interpreter::interpret_itype, ITypeInstruction};
 
+    #[test]
+    fn test_unit_bgezal_instruction() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        // Instruction: 0b00000100000100010000000000000100 bal 16
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b000001,
+                rs: 0b00000,
+                rt: 0b10001,
+                rd: 0b00000,
+                shamt: 0b00000,
+                funct: 0b000100,
+            },
+        );
+        dummy_env.registers[0] = 0;
+        let pc = dummy_env.registers.current_instruction_pointer;
+        interpret_itype(&mut dummy_env, ITypeInstruction::BranchGeqZeroAndLink);
+        assert_eq!(dummy_env.registers[31], pc + 8);
+        assert_eq!(dummy_env.registers.next_instruction_pointer, pc + 4 + 16);
+    }
+
     #[test]
     fn test_unit_addi_instruction() {
         let mut rng = o1_utils::tests::make_test_rng(None);
@@ -349,3 +667,443 @@ fn test_regression_selectors_for_instructions() {
         .iter()
         .for_each(|c| assert!(c.degree(1, 0) == 2 || c.degree(1, 0) == 1));
 }
+
+mod boundary {
+    use super::*;
+    use crate::interpreters::mips::{
+        boundary::{
+            constrain_spilled_registers, BoundaryState, BOUNDARY_PUBLIC_STATE_SIZE,
+            MAX_SPILLED_REGISTERS_PER_ROW,
+        },
+        interpreter::{interpret_itype, InterpreterEnv},
+        registers::{REGISTER_HI, REGISTER_LO},
+        ITypeInstruction,
+    };
+
+    #[test]
+    fn test_spill_and_restore_boundary_state() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut env = dummy_env(&mut rng);
+        env.registers.hi = rng.gen();
+        env.registers.lo = rng.gen();
+        // beq $0, $0, 8: the branch is taken, and the delay slot is pending
+        write_instruction(
+            &mut env,
+            InstructionParts {
+                op_code: 0b000100,
+                rs: 0b00000,
+                rt: 0b00000,
+                rd: 0b00000,
+                shamt: 0b00000,
+                funct: 0b000010,
+            },
+        );
+        interpret_itype(&mut env, ITypeInstruction::BranchEq);
+        let state = BoundaryState::spill(&env);
+        assert!(state.has_pending_branch());
+        assert!(!state.has_pending_syscall());
+        assert_eq!(
+            state.to_public_state::<Fp>().len(),
+            BOUNDARY_PUBLIC_STATE_SIZE
+        );
+
+        let mut next_env = dummy_env(&mut rng);
+        state.restore(&mut next_env);
+        assert_eq!(BoundaryState::spill(&next_env), state);
+        assert_eq!(next_env.registers.hi, env.registers.hi);
+        assert_eq!(next_env.registers.lo, env.registers.lo);
+    }
+
+    #[test]
+    fn test_constrain_spilled_registers() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut env = dummy_env(&mut rng);
+        let registers: Vec<(usize, u64)> = [0, 1, 2, 3, 4, 5, REGISTER_HI, REGISTER_LO]
+            .iter()
+            .map(|&i| (i, env.registers[i] as u64))
+            .collect();
+        // Does not panic, the values are the ones of the registers
+        constrain_spilled_registers(&mut env, &registers);
+
+        let mut con_env = constraints::Env::<Fp>::default();
+        let registers: Vec<_> = (0..MAX_SPILLED_REGISTERS_PER_ROW)
+            .map(|i| {
+                let pos = con_env.alloc_scratch();
+                (i, con_env.variable(pos))
+            })
+            .collect();
+        constrain_spilled_registers(&mut con_env, &registers);
+        let constraints = con_env.get_constraints();
+        assert_eq!(constraints.len(), MAX_SPILLED_REGISTERS_PER_ROW);
+        constraints
+            .iter()
+            .for_each(|c| assert_eq!(c.degree(1, 0), 1));
+    }
+}
+
+mod column_names {
+    use crate::interpreters::mips::{
+        column::{
+            column_registry, instruction_of_selector, MIPS_HASH_COUNTER_OFF, N_MIPS_REL_COLS,
+        },
+        ITypeInstruction, Instruction, JTypeInstruction, RTypeInstruction,
+    };
+    use kimchi_msm::columns::Column;
+
+    #[test]
+    fn test_selector_names_match_selector_indices() {
+        for instr in [
+            Instruction::RType(RTypeInstruction::Add),
+            Instruction::JType(JTypeInstruction::JumpAndLink),
+            Instruction::IType(ITypeInstruction::BranchEq),
+        ] {
+            let s = usize::from(instr) - N_MIPS_REL_COLS;
+            assert_eq!(instruction_of_selector(s), Some(instr));
+        }
+    }
+
+    #[test]
+    fn test_column_descriptions() {
+        let mut registry = column_registry();
+        assert_eq!(
+            registry.describe(&Column::Relation(MIPS_HASH_COUNTER_OFF)),
+            format!("scratch[{MIPS_HASH_COUNTER_OFF}] (mips::hash counter)")
+        );
+        registry.annotate(Column::Relation(12), "mips", "add carry bit");
+        assert_eq!(
+            registry.describe(&Column::Relation(12)),
+            "scratch[12] (mips::add carry bit)"
+        );
+        assert_eq!(
+            registry.describe(&Column::DynamicSelector(0)),
+            format!(
+                "selector[0] (mips::{:?})",
+                instruction_of_selector(0).unwrap()
+            )
+        );
+    }
+}
+
+mod snapshot {
+    use super::*;
+    use crate::interpreters::mips::{
+        interpreter::interpret_rtype,
+        snapshot::{Snapshot, SnapshotError},
+        witness::Env as WEnv,
+    };
+    use ark_ff::Field;
+
+    /// Reads the preimage by chunks of 4 bytes at the address 100
+    fn read<Fp: Field>(env: &mut WEnv<Fp, OnDiskPreImageOracle>) {
+        env.reset_scratch_state();
+        env.reset_scratch_state_inverse();
+        env.registers[5] = 100 + env.registers.preimage_offset;
+        env.registers[6] = 4;
+        interpret_rtype(env, RTypeInstruction::SyscallReadPreimage);
+    }
+
+    #[test]
+    fn test_resume_in_the_middle_of_a_preimage() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let preimage_key = [
+            0x02, 0x21, 0x07, 0x30, 0x78, 0x79, 0x25, 0x85, 0x77, 0x23, 0x0c, 0x5a, 0xa2, 0xf9,
+            0x05, 0x67, 0xbd, 0xa4, 0x08, 0x77, 0xa7, 0xe8, 0x5d, 0xce, 0xb6, 0xff, 0x1f, 0x37,
+            0x48, 0x0f, 0xef, 0x3d,
+        ];
+        dummy_env.registers.preimage_key = std::array::from_fn(|i| {
+            u32::from_be_bytes(preimage_key[4 * i..4 * (i + 1)].try_into().unwrap())
+        });
+        let total_length = 8 + dummy_env
+            .preimage_oracle
+            .get_preimage(preimage_key)
+            .get()
+            .len();
+
+        for _ in 0..3 {
+            read(&mut dummy_env);
+        }
+        let snapshot = Snapshot::capture(&dummy_env).unwrap();
+        let bytes = snapshot.to_bytes().unwrap();
+        assert_eq!(Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+
+        let mut resumed_env = Snapshot::from_bytes(&bytes)
+            .unwrap()
+            .restore(OnDiskPreImageOracle);
+        for env in [&mut dummy_env, &mut resumed_env] {
+            while (env.registers.preimage_offset as usize) < total_length {
+                read(env);
+            }
+            // the preimage is hashed before another snapshot can be taken
+            assert!(matches!(
+                Snapshot::capture(env),
+                Err(SnapshotError::KeccakInProgress)
+            ));
+            env.keccak_env = None;
+        }
+        assert_eq!(
+            Snapshot::capture(&resumed_env).unwrap(),
+            Snapshot::capture(&dummy_env).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_and_read_snapshot() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let dummy_env = dummy_env(&mut rng);
+        let snapshot = Snapshot::capture(&dummy_env).unwrap();
+        let path = std::env::temp_dir().join(format!("o1vm-snapshot-{}.bin", std::process::id()));
+        snapshot.write(&path).unwrap();
+        let read = Snapshot::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap(), snapshot);
+    }
+}
+
+#[cfg(feature = "hooks")]
+mod hooks {
+    use super::*;
+    use crate::{
+        cannon::{Meta, Start, StepFrequency, VmConfiguration},
+        interpreters::mips::{hooks::Hooks, interpreter::InterpreterEnv},
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Instruction(u32, Instruction),
+        Syscall(RTypeInstruction),
+        MemoryWrite(u32, u8),
+    }
+
+    /// Records the events, so that they can be inspected once the hook is
+    /// owned by the environment
+    struct Recorder(Rc<RefCell<Vec<Event>>>);
+
+    impl Hooks for Recorder {
+        fn on_instruction(&mut self, _step: u64, pc: u32, instruction: Instruction) {
+            self.0
+                .borrow_mut()
+                .push(Event::Instruction(pc, instruction));
+        }
+
+        fn on_syscall(&mut self, _step: u64, syscall: RTypeInstruction) {
+            self.0.borrow_mut().push(Event::Syscall(syscall));
+        }
+
+        fn on_memory_write(&mut self, _step: u64, addr: u32, value: u8) {
+            self.0.borrow_mut().push(Event::MemoryWrite(addr, value));
+        }
+    }
+
+    fn config() -> VmConfiguration {
+        VmConfiguration {
+            input_state_file: String::new(),
+            output_state_file: String::new(),
+            metadata_file: String::new(),
+            proof_at: StepFrequency::Never,
+            stop_at: StepFrequency::Never,
+            snapshot_state_at: StepFrequency::Never,
+            checkpoint_at: StepFrequency::Never,
+            info_at: StepFrequency::Never,
+            proof_fmt: String::new(),
+            snapshot_fmt: String::new(),
+            pprof_cpu: false,
+            host: None,
+            stdin: None,
+            resume: None,
+            max_cycles: None,
+        }
+    }
+
+    #[test]
+    fn test_hooks_observe_instructions_and_syscalls() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let events = Rc::new(RefCell::new(vec![]));
+        dummy_env.add_hook(Box::new(Recorder(events.clone())));
+
+        // Instruction: syscall, with sysBrk in $v0
+        dummy_env.registers.general_purpose[2] = 4045;
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b000000,
+                rs: 0b00000,
+                rt: 0b00000,
+                rd: 0b00000,
+                shamt: 0b00000,
+                funct: 0b001100,
+            },
+        );
+        let pc = dummy_env.registers.current_instruction_pointer;
+        let instruction = dummy_env.step(&config(), &Meta { symbols: vec![] }, &Start::create(0));
+
+        assert_eq!(
+            instruction,
+            Instruction::RType(RTypeInstruction::SyscallOther)
+        );
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                Event::Instruction(pc, instruction),
+                Event::Syscall(RTypeInstruction::SyscallOther),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hooks_observe_memory_writes() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let events = Rc::new(RefCell::new(vec![]));
+        dummy_env.add_hook(Box::new(Recorder(events.clone())));
+
+        unsafe { dummy_env.push_memory(&42, 7) };
+        assert_eq!(*events.borrow(), vec![Event::MemoryWrite(42, 7)]);
+    }
+}
+
+#[cfg(feature = "hooks")]
+mod trace {
+    use super::*;
+    use crate::{
+        cannon::{Meta, Start, StepFrequency, VmConfiguration},
+        interpreters::mips::trace::{disassemble, RegisterDiff, TraceFilter, Tracer},
+    };
+
+    fn config() -> VmConfiguration {
+        VmConfiguration {
+            input_state_file: String::new(),
+            output_state_file: String::new(),
+            metadata_file: String::new(),
+            proof_at: StepFrequency::Never,
+            stop_at: StepFrequency::Never,
+            snapshot_state_at: StepFrequency::Never,
+            checkpoint_at: StepFrequency::Never,
+            info_at: StepFrequency::Never,
+            proof_fmt: String::new(),
+            snapshot_fmt: String::new(),
+            pprof_cpu: false,
+            host: None,
+            stdin: None,
+            resume: None,
+            max_cycles: None,
+        }
+    }
+
+    #[test]
+    fn test_disassemble() {
+        // beq $1, $2, -2
+        let word = InstructionParts {
+            op_code: 0b000100,
+            rs: 0b00001,
+            rt: 0b00010,
+            rd: 0b11111,
+            shamt: 0b11111,
+            funct: 0b111110,
+        }
+        .encode();
+        assert_eq!(
+            disassemble(Instruction::IType(ITypeInstruction::BranchEq), word, 0x1000),
+            "beq $1, $2, 0x00000ffc"
+        );
+        // lw $8, 12($29)
+        let word = InstructionParts {
+            op_code: 0b100011,
+            rs: 0b11101,
+            rt: 0b01000,
+            rd: 0b00000,
+            shamt: 0b00000,
+            funct: 0b001100,
+        }
+        .encode();
+        assert_eq!(
+            disassemble(Instruction::IType(ITypeInstruction::Load32), word, 0x1000),
+            "lw $8, 12($29)"
+        );
+    }
+
+    #[test]
+    fn test_tracer_records_register_diffs() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let mut tracer = Tracer::new(TraceFilter::default());
+        tracer.attach(&mut dummy_env);
+
+        // Instruction: 0b00100100000000010000000000101010
+        // addiu $1, $0, 42
+        dummy_env.registers.general_purpose[1] = 0;
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b001001,
+                rs: 0b00000,
+                rt: 0b00001,
+                rd: 0b00000,
+                shamt: 0b00000,
+                funct: 0b101010,
+            },
+        );
+        let pc = dummy_env.registers.current_instruction_pointer;
+        tracer.step(
+            &mut dummy_env,
+            &config(),
+            &Meta { symbols: vec![] },
+            &Start::create(0),
+        );
+
+        assert_eq!(tracer.entries.len(), 1);
+        let entry = &tracer.entries[0];
+        assert_eq!(entry.pc, pc);
+        assert_eq!(entry.word, 0b00100100000000010000000000101010);
+        assert_eq!(entry.disassembly, "addiu $1, $0, 42");
+        assert_eq!(
+            entry.registers,
+            vec![RegisterDiff {
+                index: 1,
+                before: 0,
+                after: 42
+            }]
+        );
+        assert!(entry.memory.is_empty());
+        let text = tracer.to_text();
+        assert!(text.contains("addiu $1, $0, 42"));
+        assert!(text.contains("r1: 0x0 -> 0x2a"));
+        assert!(tracer
+            .to_json()
+            .unwrap()
+            .contains("\"disassembly\": \"addiu $1, $0, 42\""));
+    }
+
+    #[test]
+    fn test_tracer_filters_steps() {
+        let mut rng = o1_utils::tests::make_test_rng(None);
+        let mut dummy_env = dummy_env(&mut rng);
+        let mut tracer = Tracer::new(TraceFilter {
+            instructions: Some(vec![Instruction::IType(ITypeInstruction::Store32)]),
+            ..Default::default()
+        });
+        tracer.attach(&mut dummy_env);
+
+        // addiu $1, $0, 42
+        write_instruction(
+            &mut dummy_env,
+            InstructionParts {
+                op_code: 0b001001,
+                rs: 0b00000,
+                rt: 0b00001,
+                rd: 0b00000,
+                shamt: 0b00000,
+                funct: 0b101010,
+            },
+        );
+        tracer.step(
+            &mut dummy_env,
+            &config(),
+            &Meta { symbols: vec![] },
+            &Start::create(0),
+        );
+        assert!(tracer.entries.is_empty());
+    }
+}
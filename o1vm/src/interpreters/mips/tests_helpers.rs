@@ -1,6 +1,7 @@
 use crate::{
     cannon::{Hint, Preimage, PAGE_ADDRESS_MASK, PAGE_ADDRESS_SIZE, PAGE_SIZE},
     interpreters::mips::{
+        coverage::Coverage,
         interpreter::{debugging::InstructionParts, InterpreterEnv},
         registers::Registers,
         witness::{Env as WEnv, SyscallEnv},
@@ -93,15 +94,21 @@ where
         scratch_state: [Fp::from(0); SCRATCH_SIZE],
         scratch_state_inverse: [Fp::from(0); SCRATCH_SIZE_INVERSE],
         selector: crate::interpreters::mips::column::N_MIPS_SEL_COLS,
+        lookups: vec![],
         halt: false,
         // Keccak related
         syscall_env: SyscallEnv::default(),
+        written_pages: Default::default(),
         preimage: None,
         preimage_oracle: dummy_preimage_oracle,
         preimage_bytes_read: 0,
         preimage_key: None,
         keccak_env: None,
         hash_counter: 0,
+        host_io: None,
+        #[cfg(feature = "hooks")]
+        hooks: vec![],
+        coverage: Coverage::default(),
     };
     // Initialize general purpose registers with random values
     for reg in env.registers.general_purpose.iter_mut() {
@@ -0,0 +1,432 @@
+//! This module implements an instruction-level tracer of the MIPS witness
+//! environment, to debug divergences between the native execution of a
+//! program and the interpreter.
+//!
+//! The [Tracer] executes the steps of the environment, see [Tracer::step], and
+//! records for each of them the instruction pointer, the disassembled
+//! instruction, the registers modified by the instruction and its memory
+//! accesses, which are observed with a [Hooks] implementation installed by
+//! [Tracer::attach]. The steps can be filtered with a [TraceFilter], and the
+//! trace can be dumped as text or as JSON.
+
+use crate::{
+    cannon::{Meta, Start, VmConfiguration},
+    interpreters::mips::{
+        hooks::Hooks,
+        interpreter::{ITypeInstruction, Instruction, JTypeInstruction, RTypeInstruction},
+        registers::{NUM_REGISTERS, REGISTER_CURRENT_IP, REGISTER_NEXT_IP},
+        witness::Env as WEnv,
+    },
+    preimage_oracle::PreImageOracleT,
+};
+use ark_ff::Field;
+use serde::Serialize;
+use std::{cell::RefCell, fmt::Write, ops::Range, rc::Rc};
+
+/// Returns the mnemonic of the instruction
+pub fn mnemonic(instruction: Instruction) -> &'static str {
+    match instruction {
+        Instruction::RType(instruction) => match instruction {
+            RTypeInstruction::ShiftLeftLogical => "sll",
+            RTypeInstruction::ShiftRightLogical => "srl",
+            RTypeInstruction::ShiftRightArithmetic => "sra",
+            RTypeInstruction::ShiftLeftLogicalVariable => "sllv",
+            RTypeInstruction::ShiftRightLogicalVariable => "srlv",
+            RTypeInstruction::ShiftRightArithmeticVariable => "srav",
+            RTypeInstruction::JumpRegister => "jr",
+            RTypeInstruction::JumpAndLinkRegister => "jalr",
+            RTypeInstruction::SyscallMmap
+            | RTypeInstruction::SyscallExitGroup
+            | RTypeInstruction::SyscallReadHint
+            | RTypeInstruction::SyscallReadPreimage
+            | RTypeInstruction::SyscallReadOther
+            | RTypeInstruction::SyscallWriteHint
+            | RTypeInstruction::SyscallWritePreimage
+            | RTypeInstruction::SyscallWriteOther
+            | RTypeInstruction::SyscallFcntl
+            | RTypeInstruction::SyscallOther => "syscall",
+            RTypeInstruction::MoveZero => "movz",
+            RTypeInstruction::MoveNonZero => "movn",
+            RTypeInstruction::Sync => "sync",
+            RTypeInstruction::MoveFromHi => "mfhi",
+            RTypeInstruction::MoveToHi => "mthi",
+            RTypeInstruction::MoveFromLo => "mflo",
+            RTypeInstruction::MoveToLo => "mtlo",
+            RTypeInstruction::Multiply => "mult",
+            RTypeInstruction::MultiplyUnsigned => "multu",
+            RTypeInstruction::Div => "div",
+            RTypeInstruction::DivUnsigned => "divu",
+            RTypeInstruction::Add => "add",
+            RTypeInstruction::AddUnsigned => "addu",
+            RTypeInstruction::Sub => "sub",
+            RTypeInstruction::SubUnsigned => "subu",
+            RTypeInstruction::And => "and",
+            RTypeInstruction::Or => "or",
+            RTypeInstruction::Xor => "xor",
+            RTypeInstruction::Nor => "nor",
+            RTypeInstruction::SetLessThan => "slt",
+            RTypeInstruction::SetLessThanUnsigned => "sltu",
+            RTypeInstruction::MultiplyToRegister => "mul",
+            RTypeInstruction::CountLeadingOnes => "clo",
+            RTypeInstruction::CountLeadingZeros => "clz",
+            RTypeInstruction::SignExtendByte => "seb",
+            RTypeInstruction::SignExtendHalfword => "seh",
+            RTypeInstruction::TrapIfEqual => "teq",
+        },
+        Instruction::JType(instruction) => match instruction {
+            JTypeInstruction::Jump => "j",
+            JTypeInstruction::JumpAndLink => "jal",
+        },
+        Instruction::IType(instruction) => match instruction {
+            ITypeInstruction::BranchEq => "beq",
+            ITypeInstruction::BranchNeq => "bne",
+            ITypeInstruction::BranchLeqZero => "blez",
+            ITypeInstruction::BranchGtZero => "bgtz",
+            ITypeInstruction::BranchLtZero => "bltz",
+            ITypeInstruction::BranchGeqZero => "bgez",
+            ITypeInstruction::BranchLtZeroAndLink => "bltzal",
+            ITypeInstruction::BranchGeqZeroAndLink => "bgezal",
+            ITypeInstruction::AddImmediate => "addi",
+            ITypeInstruction::AddImmediateUnsigned => "addiu",
+            ITypeInstruction::SetLessThanImmediate => "slti",
+            ITypeInstruction::SetLessThanImmediateUnsigned => "sltiu",
+            ITypeInstruction::AndImmediate => "andi",
+            ITypeInstruction::OrImmediate => "ori",
+            ITypeInstruction::XorImmediate => "xori",
+            ITypeInstruction::LoadUpperImmediate => "lui",
+            ITypeInstruction::Load8 => "lb",
+            ITypeInstruction::Load16 => "lh",
+            ITypeInstruction::Load32 => "lw",
+            ITypeInstruction::Load8Unsigned => "lbu",
+            ITypeInstruction::Load16Unsigned => "lhu",
+            ITypeInstruction::LoadWordLeft => "lwl",
+            ITypeInstruction::LoadWordRight => "lwr",
+            ITypeInstruction::Store8 => "sb",
+            ITypeInstruction::Store16 => "sh",
+            ITypeInstruction::Store32 => "sw",
+            ITypeInstruction::Store32Conditional => "sc",
+            ITypeInstruction::StoreWordLeft => "swl",
+            ITypeInstruction::StoreWordRight => "swr",
+        },
+    }
+}
+
+/// Returns the assembly of the instruction `word`, decoded as `instruction`
+/// and located at address `pc`. The targets of the branches and of the jumps
+/// are absolute addresses.
+pub fn disassemble(instruction: Instruction, word: u32, pc: u32) -> String {
+    let rs = (word >> 21) & 0x1F;
+    let rt = (word >> 16) & 0x1F;
+    let rd = (word >> 11) & 0x1F;
+    let shamt = (word >> 6) & 0x1F;
+    let imm = word & 0xFFFF;
+    let simm = imm as u16 as i16 as i32;
+    let branch_target = pc.wrapping_add(4).wrapping_add((simm << 2) as u32);
+    let name = mnemonic(instruction);
+    match instruction {
+        Instruction::RType(instruction) => match instruction {
+            RTypeInstruction::ShiftLeftLogical
+            | RTypeInstruction::ShiftRightLogical
+            | RTypeInstruction::ShiftRightArithmetic => {
+                format!("{name} ${rd}, ${rt}, {shamt}")
+            }
+            RTypeInstruction::ShiftLeftLogicalVariable
+            | RTypeInstruction::ShiftRightLogicalVariable
+            | RTypeInstruction::ShiftRightArithmeticVariable => {
+                format!("{name} ${rd}, ${rt}, ${rs}")
+            }
+            RTypeInstruction::JumpRegister
+            | RTypeInstruction::MoveToHi
+            | RTypeInstruction::MoveToLo => format!("{name} ${rs}"),
+            RTypeInstruction::JumpAndLinkRegister
+            | RTypeInstruction::CountLeadingOnes
+            | RTypeInstruction::CountLeadingZeros => format!("{name} ${rd}, ${rs}"),
+            RTypeInstruction::SignExtendByte | RTypeInstruction::SignExtendHalfword => {
+                format!("{name} ${rd}, ${rt}")
+            }
+            RTypeInstruction::MoveFromHi | RTypeInstruction::MoveFromLo => {
+                format!("{name} ${rd}")
+            }
+            RTypeInstruction::Multiply
+            | RTypeInstruction::MultiplyUnsigned
+            | RTypeInstruction::Div
+            | RTypeInstruction::DivUnsigned
+            | RTypeInstruction::TrapIfEqual => format!("{name} ${rs}, ${rt}"),
+            RTypeInstruction::Sync
+            | RTypeInstruction::SyscallMmap
+            | RTypeInstruction::SyscallExitGroup
+            | RTypeInstruction::SyscallReadHint
+            | RTypeInstruction::SyscallReadPreimage
+            | RTypeInstruction::SyscallReadOther
+            | RTypeInstruction::SyscallWriteHint
+            | RTypeInstruction::SyscallWritePreimage
+            | RTypeInstruction::SyscallWriteOther
+            | RTypeInstruction::SyscallFcntl
+            | RTypeInstruction::SyscallOther => name.to_string(),
+            RTypeInstruction::MoveZero
+            | RTypeInstruction::MoveNonZero
+            | RTypeInstruction::Add
+            | RTypeInstruction::AddUnsigned
+            | RTypeInstruction::Sub
+            | RTypeInstruction::SubUnsigned
+            | RTypeInstruction::And
+            | RTypeInstruction::Or
+            | RTypeInstruction::Xor
+            | RTypeInstruction::Nor
+            | RTypeInstruction::SetLessThan
+            | RTypeInstruction::SetLessThanUnsigned
+            | RTypeInstruction::MultiplyToRegister => format!("{name} ${rd}, ${rs}, ${rt}"),
+        },
+        Instruction::JType(_) => {
+            let target = (pc.wrapping_add(4) & 0xF000_0000) | ((word & 0x03FF_FFFF) << 2);
+            format!("{name} 0x{target:08x}")
+        }
+        Instruction::IType(instruction) => match instruction {
+            ITypeInstruction::BranchEq | ITypeInstruction::BranchNeq => {
+                format!("{name} ${rs}, ${rt}, 0x{branch_target:08x}")
+            }
+            ITypeInstruction::BranchLeqZero
+            | ITypeInstruction::BranchGtZero
+            | ITypeInstruction::BranchLtZero
+            | ITypeInstruction::BranchGeqZero
+            | ITypeInstruction::BranchLtZeroAndLink
+            | ITypeInstruction::BranchGeqZeroAndLink => {
+                format!("{name} ${rs}, 0x{branch_target:08x}")
+            }
+            ITypeInstruction::AddImmediate
+            | ITypeInstruction::AddImmediateUnsigned
+            | ITypeInstruction::SetLessThanImmediate
+            | ITypeInstruction::SetLessThanImmediateUnsigned => {
+                format!("{name} ${rt}, ${rs}, {simm}")
+            }
+            ITypeInstruction::AndImmediate
+            | ITypeInstruction::OrImmediate
+            | ITypeInstruction::XorImmediate => format!("{name} ${rt}, ${rs}, 0x{imm:x}"),
+            ITypeInstruction::LoadUpperImmediate => format!("{name} ${rt}, 0x{imm:x}"),
+            ITypeInstruction::Load8
+            | ITypeInstruction::Load16
+            | ITypeInstruction::Load32
+            | ITypeInstruction::Load8Unsigned
+            | ITypeInstruction::Load16Unsigned
+            | ITypeInstruction::LoadWordLeft
+            | ITypeInstruction::LoadWordRight
+            | ITypeInstruction::Store8
+            | ITypeInstruction::Store16
+            | ITypeInstruction::Store32
+            | ITypeInstruction::Store32Conditional
+            | ITypeInstruction::StoreWordLeft
+            | ITypeInstruction::StoreWordRight => format!("{name} ${rt}, {simm}(${rs})"),
+        },
+    }
+}
+
+/// Whether a memory access is a read or a write
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum MemoryAccessKind {
+    Read,
+    Write,
+}
+
+/// A memory access of an instruction, of a single byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct MemoryAccess {
+    pub kind: MemoryAccessKind,
+    pub addr: u32,
+    pub value: u8,
+}
+
+/// A register modified by an instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct RegisterDiff {
+    /// The index of the register, see [crate::interpreters::mips::registers]
+    pub index: usize,
+    pub before: u32,
+    pub after: u32,
+}
+
+/// The record of the execution of a single instruction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TraceEntry {
+    /// The normalized instruction counter of the instruction
+    pub step: u64,
+    pub pc: u32,
+    /// The encoding of the instruction
+    pub word: u32,
+    #[serde(skip)]
+    pub instruction: Instruction,
+    pub disassembly: String,
+    /// The registers modified by the instruction, except the instruction
+    /// pointers
+    pub registers: Vec<RegisterDiff>,
+    /// The memory accesses of the instruction, except the fetch of the
+    /// instruction itself
+    pub memory: Vec<MemoryAccess>,
+}
+
+impl std::fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>10} {:08x} {:08x} {:<32}",
+            self.step, self.pc, self.word, self.disassembly
+        )?;
+        for diff in &self.registers {
+            write!(
+                f,
+                " r{}: {:#x} -> {:#x}",
+                diff.index, diff.before, diff.after
+            )?;
+        }
+        for access in &self.memory {
+            match access.kind {
+                MemoryAccessKind::Read => write!(f, " [{:#x}] = {:#x}", access.addr, access.value)?,
+                MemoryAccessKind::Write => {
+                    write!(f, " [{:#x}] <- {:#x}", access.addr, access.value)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The steps to record. A step is recorded if it matches all the criteria
+/// which are set.
+#[derive(Clone, Debug, Default)]
+pub struct TraceFilter {
+    /// Only record the instructions located in this range of addresses
+    pub pc_range: Option<Range<u32>>,
+    /// Only record the instructions executed in this range of steps
+    pub steps: Option<Range<u64>>,
+    /// Only record these instructions
+    pub instructions: Option<Vec<Instruction>>,
+    /// Only record the instructions writing to the memory
+    pub memory_writes: bool,
+}
+
+impl TraceFilter {
+    pub fn matches(&self, entry: &TraceEntry) -> bool {
+        self.pc_range
+            .as_ref()
+            .map_or(true, |range| range.contains(&entry.pc))
+            && self
+                .steps
+                .as_ref()
+                .map_or(true, |range| range.contains(&entry.step))
+            && self.instructions.as_ref().map_or(true, |instructions| {
+                instructions.contains(&entry.instruction)
+            })
+            && (!self.memory_writes
+                || entry
+                    .memory
+                    .iter()
+                    .any(|access| access.kind == MemoryAccessKind::Write))
+    }
+}
+
+/// The hook installed in the witness environment to observe the memory
+/// accesses
+struct MemoryRecorder(Rc<RefCell<Vec<MemoryAccess>>>);
+
+impl Hooks for MemoryRecorder {
+    fn on_memory_read(&mut self, _step: u64, addr: u32, value: u8) {
+        self.0.borrow_mut().push(MemoryAccess {
+            kind: MemoryAccessKind::Read,
+            addr,
+            value,
+        });
+    }
+
+    fn on_memory_write(&mut self, _step: u64, addr: u32, value: u8) {
+        self.0.borrow_mut().push(MemoryAccess {
+            kind: MemoryAccessKind::Write,
+            addr,
+            value,
+        });
+    }
+}
+
+/// Records the steps of a witness environment
+#[derive(Default)]
+pub struct Tracer {
+    pub filter: TraceFilter,
+    /// The recorded steps, in order
+    pub entries: Vec<TraceEntry>,
+    memory: Rc<RefCell<Vec<MemoryAccess>>>,
+}
+
+impl Tracer {
+    pub fn new(filter: TraceFilter) -> Self {
+        Tracer {
+            filter,
+            ..Default::default()
+        }
+    }
+
+    /// Installs the hook observing the memory accesses of the environment.
+    /// It must be called before the first step.
+    pub fn attach<Fp: Field, PreImageOracle: PreImageOracleT>(
+        &self,
+        env: &mut WEnv<Fp, PreImageOracle>,
+    ) {
+        env.add_hook(Box::new(MemoryRecorder(self.memory.clone())));
+    }
+
+    /// Executes the next instruction of the environment, records it if it
+    /// matches the filter, and returns it
+    pub fn step<Fp: Field, PreImageOracle: PreImageOracleT>(
+        &mut self,
+        env: &mut WEnv<Fp, PreImageOracle>,
+        config: &VmConfiguration,
+        metadata: &Meta,
+        start: &Start,
+    ) -> Instruction {
+        let step = env.normalized_instruction_counter();
+        let pc = env.registers.current_instruction_pointer;
+        let word = (0..4).fold(0, |word, i| {
+            (word << 8) | env.get_memory_direct(pc.wrapping_add(i)) as u32
+        });
+        let registers = env.registers.clone();
+        self.memory.borrow_mut().clear();
+
+        let instruction = env.step(config, metadata, start);
+
+        let registers = (0..NUM_REGISTERS)
+            .filter(|index| *index != REGISTER_CURRENT_IP && *index != REGISTER_NEXT_IP)
+            .filter(|index| registers[*index] != env.registers[*index])
+            .map(|index| RegisterDiff {
+                index,
+                before: registers[index],
+                after: env.registers[index],
+            })
+            .collect();
+        // The interpreter starts by reading the 4 bytes of the instruction
+        let memory = self.memory.borrow_mut().drain(..).skip(4).collect();
+        let entry = TraceEntry {
+            step,
+            pc,
+            word,
+            instruction,
+            disassembly: disassemble(instruction, word, pc),
+            registers,
+            memory,
+        };
+        if self.filter.matches(&entry) {
+            self.entries.push(entry);
+        }
+        instruction
+    }
+
+    /// Returns the trace as text, one line per step
+    pub fn to_text(&self) -> String {
+        self.entries.iter().fold(String::new(), |mut text, entry| {
+            // writing to a string cannot fail
+            writeln!(text, "{entry}").unwrap();
+            text
+        })
+    }
+
+    /// Returns the trace as a JSON array
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.entries)
+    }
+}
@@ -10,28 +10,36 @@ use crate::{
             column::{
                 ColumnAlias as Column, MIPS_BYTE_COUNTER_OFF, MIPS_CHUNK_BYTES_LEN,
                 MIPS_END_OF_PREIMAGE_OFF, MIPS_HASH_COUNTER_OFF, MIPS_HAS_N_BYTES_OFF,
-                MIPS_LENGTH_BYTES_OFF, MIPS_NUM_BYTES_READ_OFF, MIPS_PREIMAGE_BYTES_OFF,
-                MIPS_PREIMAGE_CHUNK_OFF, MIPS_PREIMAGE_KEY,
+                MIPS_IS_KECCAK_PREIMAGE_OFF, MIPS_LENGTH_BYTES_OFF, MIPS_NUM_BYTES_READ_OFF,
+                MIPS_PREIMAGE_BYTES_OFF, MIPS_PREIMAGE_CHUNK_OFF, MIPS_PREIMAGE_KEY,
             },
+            coverage::Coverage,
+            host_io::HostIO,
             interpreter::{
                 self, ITypeInstruction, Instruction, InterpreterEnv, JTypeInstruction,
-                RTypeInstruction,
+                RTypeInstruction, INITIAL_BRK, MAX_BRK,
             },
             registers::Registers,
+            snapshot::Snapshot,
         },
     },
     lookups::Lookup,
-    preimage_oracle::PreImageOracleT,
+    preimage_oracle::{keccak256_key, PreImageOracleT, PreimageKeyType},
     utils::memory_size,
 };
+#[cfg(feature = "hooks")]
+use crate::{interpreters::mips::hooks::Hooks, pickles::sampling::is_syscall};
 use ark_ff::Field;
 use core::panic;
 use kimchi::o1_utils::Two;
-use log::{debug, info};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
 use std::{
     array,
+    collections::BTreeSet,
     fs::File,
     io::{BufWriter, Write},
+    path::Path,
 };
 
 // TODO: do we want to be more restrictive and refer to the number of accesses
@@ -51,15 +59,44 @@ pub const NUM_LOOKUP_TERMS: usize =
     NUM_GLOBAL_LOOKUP_TERMS + NUM_DECODING_LOOKUP_TERMS + NUM_INSTRUCTION_LOOKUP_TERMS;
 // TODO: Delete and use a vector instead
 
-#[derive(Clone, Default)]
+/// The state of the Linux-style syscalls of the program
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SyscallEnv {
     pub last_hint: Option<Vec<u8>>,
+    /// The input given by the host to the program on its standard input
+    pub stdin: Vec<u8>,
+    /// The number of bytes of `stdin` already read by the program
+    pub stdin_offset: usize,
+    /// The bytes given by the host to the program on
+    /// [FD_HOST_READ](interpreter::FD_HOST_READ), see [Env::set_host_io]
+    pub host_input: Vec<u8>,
+    /// The bytes written by the program to its standard output
+    pub stdout: Vec<u8>,
+    /// The bytes written by the program to its standard error
+    pub stderr: Vec<u8>,
+    /// The current program break, between [INITIAL_BRK] and [MAX_BRK]
+    pub brk: u32,
+}
+
+impl Default for SyscallEnv {
+    fn default() -> Self {
+        SyscallEnv {
+            last_hint: None,
+            stdin: vec![],
+            stdin_offset: 0,
+            host_input: vec![],
+            stdout: vec![],
+            stderr: vec![],
+            brk: INITIAL_BRK,
+        }
+    }
 }
 
 impl SyscallEnv {
     pub fn create(state: &State) -> Self {
         SyscallEnv {
             last_hint: state.last_hint.clone(),
+            ..Default::default()
         }
     }
 }
@@ -81,8 +118,13 @@ pub struct Env<Fp, PreImageOracle: PreImageOracleT> {
     pub scratch_state_idx_inverse: usize,
     pub scratch_state: [Fp; SCRATCH_SIZE],
     pub scratch_state_inverse: [Fp; SCRATCH_SIZE_INVERSE],
+    /// The lookups of the last instruction executed, in the order of the
+    /// interpreter
+    pub lookups: Vec<Lookup<u64>>,
     pub halt: bool,
     pub syscall_env: SyscallEnv,
+    /// The pages written since the last call to [Env::take_written_pages]
+    pub written_pages: BTreeSet<u32>,
     pub selector: usize,
     pub preimage_oracle: PreImageOracle,
     pub preimage: Option<Vec<u8>>,
@@ -90,6 +132,14 @@ pub struct Env<Fp, PreImageOracle: PreImageOracleT> {
     pub preimage_key: Option<[u8; 32]>,
     pub keccak_env: Option<KeccakEnv<Fp>>,
     pub hash_counter: u64,
+    /// The source of the data of the host, see [`Env::set_host_io`]
+    pub host_io: Option<Box<dyn HostIO>>,
+    /// The observers of the execution, see [`Env::add_hook`]
+    #[cfg(feature = "hooks")]
+    pub hooks: Vec<Box<dyn Hooks>>,
+    /// The instructions executed since the environment was created, with the
+    /// number of constraints and lookups of each of them
+    pub coverage: Coverage,
 }
 
 fn fresh_scratch_state<Fp: Field, const N: usize>() -> [Fp; N] {
@@ -126,6 +176,7 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
 
     fn activate_selector(&mut self, instruction: Instruction) {
         self.selector = instruction.into();
+        self.coverage.activate::<Fp>(instruction);
     }
 
     fn check_is_zero(assert_equals_zero: &Self::Variable) {
@@ -142,9 +193,8 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
         }
     }
 
-    fn add_lookup(&mut self, _lookup: Lookup<Self::Variable>) {
-        // No-op, constraints only
-        // TODO: keep track of multiplicities of fixed tables here as in Keccak?
+    fn add_lookup(&mut self, lookup: Lookup<Self::Variable>) {
+        self.lookups.push(lookup);
     }
 
     fn instruction_counter(&self) -> Self::Variable {
@@ -217,6 +267,11 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
         let memory_page_idx = self.get_memory_page_index(page);
         let value = self.memory[memory_page_idx].1[page_address];
         self.write_column(output, value.into());
+        #[cfg(feature = "hooks")]
+        {
+            let step = self.normalized_instruction_counter();
+            self.run_hooks(|hook| hook.on_memory_read(step, addr, value));
+        }
         value.into()
     }
 
@@ -225,8 +280,15 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
         let page = addr >> PAGE_ADDRESS_SIZE;
         let page_address = (addr & PAGE_ADDRESS_MASK) as usize;
         let memory_page_idx = self.get_memory_page_index(page);
-        self.memory[memory_page_idx].1[page_address] =
-            value.try_into().expect("push_memory values fit in a u8");
+        let value: u8 = value.try_into().expect("push_memory values fit in a u8");
+        self.memory[memory_page_idx].1[page_address] = value;
+        self.written_pages.insert(page);
+
+        #[cfg(feature = "hooks")]
+        {
+            let step = self.normalized_instruction_counter();
+            self.run_hooks(|hook| hook.on_memory_write(step, addr, value));
+        }
     }
 
     unsafe fn fetch_memory_access(
@@ -532,8 +594,18 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
     ) -> (Self::Variable, Self::Variable) {
         let x: u32 = (*x).try_into().unwrap();
         let y: u32 = (*y).try_into().unwrap();
-        let q = ((x as i32) / (y as i32)) as u32;
-        let r = ((x as i32) % (y as i32)) as u32;
+        // The result is unpredictable on MIPS when dividing by zero. As the
+        // compilers check the divisor after the division, it must not fail:
+        // the quotient is -1 and the remainder is the dividend. The overflow
+        // of i32::MIN / -1 wraps around.
+        let (q, r) = if y == 0 {
+            (u32::MAX, x)
+        } else {
+            (
+                (x as i32).wrapping_div(y as i32) as u32,
+                (x as i32).wrapping_rem(y as i32) as u32,
+            )
+        };
         let q = q as u64;
         let r = r as u64;
         self.write_column(position_quotient, q);
@@ -550,8 +622,12 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
     ) -> (Self::Variable, Self::Variable) {
         let x: u32 = (*x).try_into().unwrap();
         let y: u32 = (*y).try_into().unwrap();
-        let q = x / y;
-        let r = x % y;
+        // See divmod_signed for the division by zero
+        let (q, r) = if y == 0 {
+            (u32::MAX, x)
+        } else {
+            (x / y, x % y)
+        };
         let q = q as u64;
         let r = r as u64;
         self.write_column(position_quotient, q);
@@ -622,6 +698,15 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
                 }
             }
             let preimage = self.preimage_oracle.get_preimage(preimage_key).get();
+            // The Keccak256 pre-images are also checked by the Keccak circuit,
+            // but a wrong pre-image given by the host is better caught here
+            if PreimageKeyType::from_key(&preimage_key) == Some(PreimageKeyType::Keccak256) {
+                assert_eq!(
+                    keccak256_key(&preimage),
+                    preimage_key,
+                    "The pre-image oracle returned a pre-image that does not match its Keccak256 key"
+                );
+            }
             self.preimage = Some(preimage.clone());
             self.preimage_key = Some(preimage_key);
         }
@@ -634,6 +719,10 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
             .expect("to have a preimage if we're requesting it at a non-zero offset");
         let preimage_len = preimage.len();
         let preimage_offset = self.registers.preimage_offset as u64;
+        let is_keccak = self
+            .preimage_key
+            .and_then(|key| PreimageKeyType::from_key(&key))
+            == Some(PreimageKeyType::Keccak256);
 
         let max_read_len =
             std::cmp::min(preimage_offset + len, (preimage_len + LENGTH_SIZE) as u64)
@@ -673,9 +762,9 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
                     self.push_memory_access(&(*addr + i), self.next_instruction_counter());
                 }
             } else {
-                // Compute the byte index in the chunk of at most 4 bytes read
-                // from the preimage
-                let byte_i = (idx - LENGTH_SIZE) % MIPS_CHUNK_BYTES_LEN;
+                // The bytes of the preimage read in this step are stored
+                // from the first column on, as the constraints expect
+                let byte_i = preimage_read_len as usize;
 
                 // This should really be handled by the keccak oracle.
                 let preimage_byte = self.preimage.as_ref().unwrap()[idx - LENGTH_SIZE];
@@ -733,6 +822,16 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
             self.preimage_bytes_read,
         );
 
+        // The bytes of the preimage are sent to the Keccak circuit with the
+        // index of its hash
+        self.write_column(
+            Column::ScratchState(MIPS_HASH_COUNTER_OFF),
+            self.hash_counter,
+        );
+        if is_keccak {
+            self.write_column(Column::ScratchState(MIPS_IS_KECCAK_PREIMAGE_OFF), 1);
+        }
+
         // If we've read the entire preimage, trigger Keccak workflow
         if self.preimage_bytes_read == preimage_len as u64 {
             self.write_column(Column::ScratchState(MIPS_END_OF_PREIMAGE_OFF), 1);
@@ -744,19 +843,18 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
             });
             self.write_field_column(Self::Position::ScratchState(MIPS_PREIMAGE_KEY), bytes31);
 
-            debug!("Preimage has been read entirely, triggering Keccak process");
-            self.keccak_env = Some(KeccakEnv::<Fp>::new(
-                self.hash_counter,
-                self.preimage.as_ref().unwrap(),
-            ));
+            // Only the preimages of Keccak256 keys are hashed by the Keccak
+            // circuit
+            if is_keccak {
+                debug!("Preimage has been read entirely, triggering Keccak process");
+                self.keccak_env = Some(KeccakEnv::<Fp>::new(
+                    self.hash_counter,
+                    self.preimage.as_ref().unwrap(),
+                ));
+            }
 
             // COMMUNICATION CHANNEL: only on constraint side
 
-            // Update hash counter column
-            self.write_column(
-                Column::ScratchState(MIPS_HASH_COUNTER_OFF),
-                self.hash_counter,
-            );
             // Number of preimage bytes left to be read should be zero at this
             // point
 
@@ -812,9 +910,83 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreI
         self.syscall_env.last_hint = Some(remaining);
     }
 
+    fn request_input_read(
+        &mut self,
+        fd: &Self::Variable,
+        addr: &Self::Variable,
+        len: &Self::Variable,
+        bytes: [Self::Position; 4],
+        pos: Self::Position,
+    ) -> Self::Variable {
+        // We read at most 4 bytes, ensuring that we respect word alignment,
+        // like for the preimages.
+        let max_len = std::cmp::min(*len, 4 - (addr & 3));
+        let input: Vec<u8> = if *fd as u32 == interpreter::FD_HOST_READ {
+            let step = self.normalized_instruction_counter();
+            let input = match self.host_io.as_mut() {
+                Some(host_io) => host_io.read(step, max_len as usize),
+                None => vec![],
+            };
+            assert!(
+                input.len() as u64 <= max_len,
+                "the host gave {} bytes, but at most {} bytes were requested",
+                input.len(),
+                max_len
+            );
+            self.syscall_env.host_input.extend(&input);
+            input
+        } else {
+            let offset = self.syscall_env.stdin_offset;
+            let remaining = (self.syscall_env.stdin.len() - offset) as u64;
+            let read_len = std::cmp::min(max_len, remaining) as usize;
+            self.syscall_env.stdin_offset += read_len;
+            self.syscall_env.stdin[offset..offset + read_len].to_vec()
+        };
+        for (i, byte) in input.iter().enumerate() {
+            let byte_addr = *addr + i as u64;
+            self.write_column(bytes[i], *byte as u64);
+            unsafe {
+                self.push_memory(&byte_addr, *byte as u64);
+                self.push_memory_access(&byte_addr, self.next_instruction_counter());
+            }
+        }
+        let read_len = input.len() as u64;
+        self.write_column(pos, read_len);
+        read_len
+    }
+
+    fn request_output_write(
+        &mut self,
+        fd: &Self::Variable,
+        addr: &Self::Variable,
+        len: &Self::Variable,
+    ) {
+        let output: Vec<u8> = (0..*len)
+            .map(|i| self.get_memory_direct((*addr + i) as u32))
+            .collect();
+        match *fd as u32 {
+            interpreter::FD_STDOUT => self.syscall_env.stdout.extend(output),
+            interpreter::FD_STDERR => self.syscall_env.stderr.extend(output),
+            // Writes to the other file descriptors are discarded
+            _ => (),
+        }
+    }
+
+    fn request_brk(&mut self, addr: &Self::Variable, pos: Self::Position) -> Self::Variable {
+        // brk(0), or any address below the initial break, only queries the
+        // current break, and the break cannot be moved up to the stack
+        if (INITIAL_BRK as u64..MAX_BRK as u64).contains(addr) {
+            self.syscall_env.brk = *addr as u32;
+        }
+        let brk = self.syscall_env.brk as u64;
+        self.write_column(pos, brk);
+        brk
+    }
+
     fn reset(&mut self) {
         self.scratch_state_idx = 0;
         self.scratch_state = fresh_scratch_state();
+        self.lookups.clear();
         self.selector = N_MIPS_SEL_COLS;
     }
 }
@@ -882,8 +1054,10 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
             scratch_state_idx_inverse: 0,
             scratch_state: fresh_scratch_state(),
             scratch_state_inverse: fresh_scratch_state(),
+            lookups: vec![],
             halt: state.exited,
             syscall_env,
+            written_pages: BTreeSet::new(),
             selector,
             preimage_oracle,
             preimage: state.preimage,
@@ -891,12 +1065,39 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
             preimage_key: None,
             keccak_env: None,
             hash_counter: 0,
+            host_io: None,
+            #[cfg(feature = "hooks")]
+            hooks: vec![],
+            coverage: Coverage::default(),
+        }
+    }
+
+    /// Sets the source of the data read by the program on
+    /// [FD_HOST_READ](interpreter::FD_HOST_READ). Without a source, the program
+    /// reads no data from the host. The source is not part of the snapshots,
+    /// and must be set again after restoring one.
+    pub fn set_host_io(&mut self, host_io: Box<dyn HostIO>) {
+        self.host_io = Some(host_io);
+    }
+
+    /// Registers `hook` to observe the execution. The hooks are called in the
+    /// order they were added.
+    #[cfg(feature = "hooks")]
+    pub fn add_hook(&mut self, hook: Box<dyn Hooks>) {
+        self.hooks.push(hook);
+    }
+
+    #[cfg(feature = "hooks")]
+    fn run_hooks(&mut self, mut f: impl FnMut(&mut dyn Hooks)) {
+        for hook in self.hooks.iter_mut() {
+            f(hook.as_mut());
         }
     }
 
     pub fn reset_scratch_state(&mut self) {
         self.scratch_state_idx = 0;
         self.scratch_state = fresh_scratch_state();
+        self.lookups.clear();
         self.selector = N_MIPS_SEL_COLS;
     }
 
@@ -971,6 +1172,12 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
         i
     }
 
+    /// Returns the indices of the pages written since the last call, e.g. to
+    /// update the Merkle tree of the memory, see [crate::merkle::MerkleStore].
+    pub fn take_written_pages(&mut self) -> BTreeSet<u32> {
+        std::mem::take(&mut self.written_pages)
+    }
+
     pub fn get_memory_direct(&mut self, addr: u32) -> u8 {
         let page = addr >> PAGE_ADDRESS_SIZE;
         let page_address = (addr & PAGE_ADDRESS_MASK) as usize;
@@ -1009,7 +1216,9 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
                             // sysClone
                             Instruction::RType(RTypeInstruction::SyscallOther)
                         }
-                        4246 => Instruction::RType(RTypeInstruction::SyscallExitGroup),
+                        // sysExit is handled like sysExitGroup, as the
+                        // programs are single-threaded
+                        4001 | 4246 => Instruction::RType(RTypeInstruction::SyscallExitGroup),
                         4003 => match self.registers.general_purpose[4] {
                             interpreter::FD_HINT_READ => {
                                 Instruction::RType(RTypeInstruction::SyscallReadHint)
@@ -1053,6 +1262,7 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
                     0x27 => Instruction::RType(RTypeInstruction::Nor),
                     0x2a => Instruction::RType(RTypeInstruction::SetLessThan),
                     0x2b => Instruction::RType(RTypeInstruction::SetLessThanUnsigned),
+                    0x34 => Instruction::RType(RTypeInstruction::TrapIfEqual),
                     _ => {
                         panic!("Unhandled instruction {:#X}", instruction)
                     }
@@ -1062,6 +1272,8 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
                     match (instruction >> 16) & 0x1F {
                         0x0 => Instruction::IType(ITypeInstruction::BranchLtZero),
                         0x1 => Instruction::IType(ITypeInstruction::BranchGeqZero),
+                        0x10 => Instruction::IType(ITypeInstruction::BranchLtZeroAndLink),
+                        0x11 => Instruction::IType(ITypeInstruction::BranchGeqZeroAndLink),
                         _ => panic!("Unhandled instruction {:#X}", instruction),
                     }
                 }
@@ -1085,6 +1297,11 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
                     0x21 => Instruction::RType(RTypeInstruction::CountLeadingOnes),
                     _ => panic!("Unhandled instruction {:#X}", instruction),
                 },
+                0x1F => match (instruction & 0x3F, (instruction >> 6) & 0x1F) {
+                    (0x20, 0x10) => Instruction::RType(RTypeInstruction::SignExtendByte),
+                    (0x20, 0x18) => Instruction::RType(RTypeInstruction::SignExtendHalfword),
+                    _ => panic!("Unhandled instruction {:#X}", instruction),
+                },
                 0x20 => Instruction::IType(ITypeInstruction::Load8),
                 0x21 => Instruction::IType(ITypeInstruction::Load16),
                 0x22 => Instruction::IType(ITypeInstruction::LoadWordLeft),
@@ -1152,6 +1369,7 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
 
         self.pp_info(&config.info_at, metadata, start);
         self.snapshot_state_at(&config.snapshot_state_at);
+        self.checkpoint_at(&config.checkpoint_at);
 
         // Force stops at given iteration
         if self.should_trigger_at(&config.stop_at) {
@@ -1164,6 +1382,18 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
             return opcode;
         }
 
+        #[cfg(feature = "hooks")]
+        {
+            let step = self.normalized_instruction_counter();
+            let pc = self.registers.current_instruction_pointer;
+            self.run_hooks(|hook| hook.on_instruction(step, pc, opcode));
+            if let Instruction::RType(syscall) = opcode {
+                if is_syscall(&opcode) {
+                    self.run_hooks(|hook| hook.on_syscall(step, syscall));
+                }
+            }
+        }
+
         interpreter::interpret_instruction(self, opcode);
 
         self.instruction_counter = self.next_instruction_counter();
@@ -1267,6 +1497,31 @@ impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
         }
     }
 
+    /// Writes a [Snapshot] of the environment, from which the execution can be
+    /// resumed
+    fn checkpoint_at(&mut self, at: &StepFrequency) {
+        if self.should_trigger_at(at) {
+            let filename = format!("checkpoint-{}.bin", self.normalized_instruction_counter());
+            match Snapshot::capture(self) {
+                Ok(snapshot) => {
+                    snapshot.write(Path::new(&filename)).unwrap_or_else(|e| {
+                        panic!("Could not write the checkpoint {filename}: {e}")
+                    });
+                    info!(
+                        "Checkpoint in {}, step {}",
+                        filename,
+                        self.normalized_instruction_counter()
+                    );
+                }
+                Err(e) => warn!(
+                    "No checkpoint at step {}: {}",
+                    self.normalized_instruction_counter(),
+                    e
+                ),
+            }
+        }
+    }
+
     fn pp_info(&mut self, at: &StepFrequency, meta: &Meta, start: &Start) {
         if self.should_trigger_at(at) {
             let elapsed = start.time.elapsed();
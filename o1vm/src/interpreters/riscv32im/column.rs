@@ -10,9 +10,10 @@ use kimchi::circuits::{
     berkeley_columns::BerkeleyChallengeTerm,
     expr::{ConstantExpr, Expr},
 };
+use kimchi_msm::columns::{Column as MSMColumn, ColumnIndexer};
 use strum::EnumCount;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Column {
     ScratchState(usize),
     InstructionCounter,
@@ -100,5 +101,38 @@ impl From<Instruction> for usize {
     }
 }
 
+/// The columns are laid out like the ones of the MIPS interpreter in the
+/// pickles prover, i.e. the scratch state, the (empty) inverse scratch state,
+/// the instruction counter and the error are relation columns, and the
+/// selectors are dynamic selectors. The error column is not used by the
+/// RISC-V interpreter.
+impl ColumnIndexer for Column {
+    const N_COL: usize = SCRATCH_SIZE + 1 + INSTRUCTION_SET_SIZE;
+
+    fn to_column(self) -> MSMColumn {
+        match self {
+            Column::ScratchState(ss) => {
+                assert!(
+                    ss < SCRATCH_SIZE,
+                    "The maximum index is {}, got {}",
+                    SCRATCH_SIZE,
+                    ss
+                );
+                MSMColumn::Relation(ss)
+            }
+            Column::InstructionCounter => MSMColumn::Relation(SCRATCH_SIZE),
+            Column::Selector(s) => {
+                assert!(
+                    s < INSTRUCTION_SET_SIZE,
+                    "The maximum index is {}, got {}",
+                    INSTRUCTION_SET_SIZE,
+                    s
+                );
+                MSMColumn::DynamicSelector(s)
+            }
+        }
+    }
+}
+
 // FIXME: use other challenges, not Berkeley.
-pub type E<F> = Expr<ConstantExpr<F, BerkeleyChallengeTerm>, Column>;
+pub type E<F> = Expr<ConstantExpr<F, BerkeleyChallengeTerm>, MSMColumn>;
@@ -12,6 +12,7 @@ use kimchi::circuits::{
     expr::{ConstantTerm, Expr, ExprInner, Operations, Variable},
     gate::CurrOrNext,
 };
+use kimchi_msm::columns::ColumnIndexer;
 
 pub struct Env<F: Field> {
     pub scratch_state_idx: usize,
@@ -53,7 +54,7 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
 
     fn variable(&self, column: Self::Position) -> Self::Variable {
         Expr::Atom(ExprInner::Cell(Variable {
-            col: column,
+            col: column.to_column(),
             row: CurrOrNext::Curr,
         }))
     }
@@ -7,13 +7,8 @@
 //! traces.
 //! The goal of this flavor was to support the curve `bn254`. For the time
 //! being, the project has been stopped in favor of the pickles version defined
-//! in [crate::pickles] and we do not aim to provide any support for now.
-//!
-//! You can still run the legacy flavor by using:
-//!
-//! ```bash
-//! O1VM_FLAVOR=legacy bash run-code.sh
-//! ```
+//! in [crate::pickles] and we do not aim to provide any support for now. The
+//! flavor has no binary anymore, it is only exercised by its tests.
 
 use ark_ec::bn::Bn;
 use mina_poseidon::{
@@ -170,14 +170,19 @@ pub mod mips {
                     | SetLessThanUnsigned
                     | MultiplyToRegister
                     | CountLeadingOnes
-                    | CountLeadingZeros => assert_num_constraints(&instr, 4),
-                    MoveZero | MoveNonZero => assert_num_constraints(&instr, 6),
-                    SyscallReadOther | SyscallWriteHint | SyscallWriteOther | Multiply
-                    | MultiplyUnsigned | Div | DivUnsigned => assert_num_constraints(&instr, 7),
-                    SyscallOther => assert_num_constraints(&instr, 11),
+                    | CountLeadingZeros
+                    | TrapIfEqual => assert_num_constraints(&instr, 4),
+                    MoveZero | MoveNonZero | SignExtendByte | SignExtendHalfword => {
+                        assert_num_constraints(&instr, 6)
+                    }
+                    SyscallWriteHint | SyscallWriteOther | Multiply | MultiplyUnsigned | Div
+                    | DivUnsigned => assert_num_constraints(&instr, 7),
+                    SyscallOther => assert_num_constraints(&instr, 12),
+                    SyscallReadOther => assert_num_constraints(&instr, 14),
                     SyscallMmap => assert_num_constraints(&instr, 12),
-                    SyscallFcntl | SyscallReadPreimage => assert_num_constraints(&instr, 23),
-                    // TODO: update SyscallReadPreimage to 31 when using self.equal()
+                    SyscallFcntl => assert_num_constraints(&instr, 23),
+                    // TODO: update SyscallReadPreimage to 32 when using self.equal()
+                    SyscallReadPreimage => assert_num_constraints(&instr, 24),
                     SyscallWritePreimage => assert_num_constraints(&instr, 31),
                 },
                 JType(jtype) => match jtype {
@@ -201,7 +206,9 @@ pub mod mips {
                     | Load32
                     | Load8Unsigned
                     | Load16Unsigned
-                    | Store32Conditional => assert_num_constraints(&instr, 4),
+                    | Store32Conditional
+                    | BranchLtZeroAndLink
+                    | BranchGeqZeroAndLink => assert_num_constraints(&instr, 4),
                     LoadWordLeft | LoadWordRight | StoreWordLeft | StoreWordRight => {
                         assert_num_constraints(&instr, 13)
                     }
@@ -4,11 +4,17 @@ pub mod cannon;
 /// A CLI mimicking the Cannon CLI.
 pub mod cannon_cli;
 
+/// The command-line interface of the `o1vm` binary.
+pub mod cli;
+
 /// A module to load ELF files.
 pub mod elf_loader;
 
 pub mod interpreters;
 
+/// The Merkle tree committing to the memory.
+pub mod merkle;
+
 /// Legacy implementation of the recursive proof composition.
 /// It does use the folding and ivc libraries defined in this monorepo, and aims
 /// to be compatible with Ethereum natively, using the curve bn254.
@@ -26,6 +32,10 @@ pub mod preimage_oracle;
 /// The RAM lookup argument.
 pub mod ramlookup;
 
+/// Experimental support for 64-bit fields.
+#[cfg(feature = "small-field")]
+pub mod small_field;
+
 pub mod utils;
 
 use kimchi::circuits::{
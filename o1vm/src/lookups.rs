@@ -11,6 +11,7 @@ use kimchi::{
     o1_utils::{FieldHelpers, Two},
 };
 use kimchi_msm::{LogupTable, LogupWitness, LookupTableID};
+use serde::{Deserialize, Serialize};
 
 /// The lookups struct based on RAMLookups for the VM table IDs
 pub(crate) type Lookup<F> = RAMLookup<F, LookupTableIDs>;
@@ -23,7 +24,7 @@ pub(crate) type LookupWitness<F> = LogupWitness<F, LookupTableIDs>;
 pub(crate) type LookupTable<F> = LogupTable<F, LookupTableIDs>;
 
 /// All of the possible lookup table IDs used in the zkVM
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum LookupTableIDs {
     // PadLookup ID is 0 because this is the only fixed table whose first entry is not 0.
     // This way, it is guaranteed that the 0 value is not always in the tables after the
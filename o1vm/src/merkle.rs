@@ -0,0 +1,231 @@
+//! A Merkle tree committing to the memory of the virtual machine.
+//!
+//! The leaves of the tree are the hashes of the pages of the memory, indexed
+//! by the page index, so the tree has one leaf per page of the 32 bits address
+//! space. The pages which have never been written to are zero, and the tree is
+//! stored sparsely: only the nodes which are not the root of a tree of zero
+//! pages are kept.
+//!
+//! The hash function is Poseidon, with the parameters of the scalar field of
+//! the proof, so the root can be given to the prover once the updates are
+//! constrained, see [crate::pickles::continuation].
+//!
+//! The tree is only maintained by the host: no constraint checks the paths
+//! read or updated by the memory accesses of a chunk, so the roots at the
+//! boundaries of a chunk are claims of the host, which are not part of the
+//! public input of the proof and are checked against each other by the
+//! [Aggregator](crate::pickles::continuation::Aggregator) only. Constraining
+//! the updates requires the memory accesses of the execution trace to be read
+//! with a lookup argument, which the pickles prover does not have yet, and the
+//! rounds of Poseidon to be spread over several rows, as its S-box has a degree
+//! higher than
+//! [MAXIMUM_DEGREE_CONSTRAINTS](crate::pickles::MAXIMUM_DEGREE_CONSTRAINTS).
+
+use crate::cannon::{PAGE_ADDRESS_SIZE, PAGE_SIZE};
+use ark_ff::PrimeField;
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, ArithmeticSpongeParams, Sponge},
+};
+use std::collections::HashMap;
+
+/// The depth of the tree, i.e. the number of bits of a page index
+pub const MEMORY_TREE_DEPTH: usize = 32 - PAGE_ADDRESS_SIZE as usize;
+
+/// The number of bytes of a page packed into a single field element
+const BYTES_PER_ELEMENT: usize = 16;
+
+/// Returns the hash of a page. The pages shorter than [PAGE_SIZE] are padded
+/// with zeroes.
+///
+/// # Panics
+///
+/// Will panic if the page is longer than [PAGE_SIZE].
+pub fn hash_page<F: PrimeField>(params: &'static ArithmeticSpongeParams<F>, data: &[u8]) -> F {
+    assert!(
+        data.len() <= PAGE_SIZE as usize,
+        "The page has {} bytes, more than {PAGE_SIZE}",
+        data.len()
+    );
+    let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(params);
+    let elements: Vec<F> = (0..PAGE_SIZE as usize)
+        .step_by(BYTES_PER_ELEMENT)
+        .map(|start| {
+            let mut bytes = [0u8; BYTES_PER_ELEMENT];
+            if start < data.len() {
+                let end = std::cmp::min(start + BYTES_PER_ELEMENT, data.len());
+                bytes[..end - start].copy_from_slice(&data[start..end]);
+            }
+            F::from(u128::from_le_bytes(bytes))
+        })
+        .collect();
+    sponge.absorb(&elements);
+    sponge.squeeze()
+}
+
+/// Returns the hash of two sibling nodes
+pub fn hash_nodes<F: PrimeField>(
+    params: &'static ArithmeticSpongeParams<F>,
+    left: F,
+    right: F,
+) -> F {
+    let mut sponge = ArithmeticSponge::<F, PlonkSpongeConstantsKimchi>::new(params);
+    sponge.absorb(&[left, right]);
+    sponge.squeeze()
+}
+
+/// The path from a leaf to the root, used to prove the content of a page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath<F> {
+    /// The siblings of the nodes of the path, from the leaf to the root
+    pub siblings: Vec<F>,
+}
+
+impl<F: PrimeField> MerklePath<F> {
+    /// Returns the root of the tree whose leaf `index` is `leaf`
+    pub fn compute_root(
+        &self,
+        params: &'static ArithmeticSpongeParams<F>,
+        index: u32,
+        leaf: F,
+    ) -> F {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(leaf, |node, (level, sibling)| {
+                if (index >> level) & 1 == 0 {
+                    hash_nodes(params, node, *sibling)
+                } else {
+                    hash_nodes(params, *sibling, node)
+                }
+            })
+    }
+}
+
+/// The host-side store of the Merkle tree of the memory
+pub struct MerkleStore<F: PrimeField> {
+    params: &'static ArithmeticSpongeParams<F>,
+    /// The nodes which are not the root of a tree of zero pages, by level,
+    /// starting from the leaves, and by index in the level
+    nodes: Vec<HashMap<u32, F>>,
+    /// The root of a tree of zero pages, by level
+    empty: Vec<F>,
+}
+
+impl<F: PrimeField> MerkleStore<F> {
+    /// Creates the tree of a memory whose pages are all zero
+    pub fn new(params: &'static ArithmeticSpongeParams<F>) -> Self {
+        let mut empty = Vec::with_capacity(MEMORY_TREE_DEPTH + 1);
+        empty.push(hash_page(params, &[]));
+        for level in 0..MEMORY_TREE_DEPTH {
+            empty.push(hash_nodes(params, empty[level], empty[level]));
+        }
+        MerkleStore {
+            params,
+            nodes: vec![HashMap::new(); MEMORY_TREE_DEPTH + 1],
+            empty,
+        }
+    }
+
+    /// Creates the tree of the given pages, given by index
+    pub fn from_memory(
+        params: &'static ArithmeticSpongeParams<F>,
+        memory: &[(u32, Vec<u8>)],
+    ) -> Self {
+        let mut store = Self::new(params);
+        for (index, data) in memory {
+            store.update_page(*index, data);
+        }
+        store
+    }
+
+    fn node(&self, level: usize, index: u32) -> F {
+        self.nodes[level]
+            .get(&index)
+            .copied()
+            .unwrap_or(self.empty[level])
+    }
+
+    /// The root of the tree
+    pub fn root(&self) -> F {
+        self.node(MEMORY_TREE_DEPTH, 0)
+    }
+
+    /// Sets the content of the page `index`, and updates the nodes up to the
+    /// root
+    pub fn update_page(&mut self, index: u32, data: &[u8]) {
+        let mut node = hash_page(self.params, data);
+        let mut index = index;
+        for level in 0..MEMORY_TREE_DEPTH {
+            self.nodes[level].insert(index, node);
+            let sibling = self.node(level, index ^ 1);
+            node = if index & 1 == 0 {
+                hash_nodes(self.params, node, sibling)
+            } else {
+                hash_nodes(self.params, sibling, node)
+            };
+            index >>= 1;
+        }
+        self.nodes[MEMORY_TREE_DEPTH].insert(0, node);
+    }
+
+    /// The leaf of the page `index`
+    pub fn leaf(&self, index: u32) -> F {
+        self.node(0, index)
+    }
+
+    /// The path from the leaf of the page `index` to the root
+    pub fn path(&self, index: u32) -> MerklePath<F> {
+        let siblings = (0..MEMORY_TREE_DEPTH)
+            .map(|level| self.node(level, (index >> level) ^ 1))
+            .collect();
+        MerklePath { siblings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mina_curves::pasta::Fp;
+    use mina_poseidon::pasta::fp_kimchi;
+
+    #[test]
+    fn test_zero_pages_do_not_change_the_root() {
+        let params = fp_kimchi::static_params();
+        let empty = MerkleStore::<Fp>::new(params);
+        let memory = vec![(3, vec![0; PAGE_SIZE as usize]), (42, vec![])];
+        let store = MerkleStore::from_memory(params, &memory);
+        assert_eq!(store.root(), empty.root());
+    }
+
+    #[test]
+    fn test_paths_prove_the_pages() {
+        let params = fp_kimchi::static_params();
+        let mut store = MerkleStore::<Fp>::new(params);
+        let empty_root = store.root();
+
+        store.update_page(5, b"hello");
+        store.update_page(6, b"world");
+        let root = store.root();
+        assert_ne!(root, empty_root);
+
+        for index in [5, 6, 7, 1 << 19] {
+            let path = store.path(index);
+            assert_eq!(path.siblings.len(), MEMORY_TREE_DEPTH);
+            assert_eq!(path.compute_root(params, index, store.leaf(index)), root);
+        }
+        assert_eq!(store.leaf(5), hash_page(params, b"hello"));
+        // A page does not match the path of another page
+        assert_ne!(
+            store
+                .path(5)
+                .compute_root(params, 5, hash_page(params, b"world")),
+            root
+        );
+
+        // Writing back zeroes gives back the empty tree
+        store.update_page(5, &[]);
+        store.update_page(6, &[0; 16]);
+        assert_eq!(store.root(), empty_root);
+    }
+}
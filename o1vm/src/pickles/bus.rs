@@ -0,0 +1,417 @@
+//! A bus connecting the sub-circuits of the o1vm proven independently with
+//! the pickles prover, like the MIPS trace and its coprocessors.
+//!
+//! Each sub-circuit declares the messages it sends and receives on the
+//! channels of the bus with a [Bus]. A message is a list of expressions over
+//! the columns of the trace, sent or received with a multiplicity. The bus is
+//! balanced when each message is received as many times as it is sent, over
+//! all the sub-circuits, which is checked with a logUp argument:
+//!
+//! 1. the prover commits to the columns of the traces, and the challenges
+//!    [BusChallenges] are sampled from the commitments to the columns referred
+//!    to by the messages of all the sub-circuits;
+//! 2. in each sub-circuit, the prover writes the terms `m / (β + v)` of the
+//!    sum of logUp in a column per message, `m` being the multiplicity and `v`
+//!    the combination of the channel and the message, and accumulates them in
+//!    a running sum column, see [Bus::fill]. The sum `S` of the sub-circuit is
+//!    given to the verifier;
+//! 3. each sub-circuit is proven with the constraints [Bus::constraints] of
+//!    the columns, which embed the challenges and its sum;
+//! 4. the verifier samples the same challenges from the proofs, verifies them
+//!    and checks that the sums add up to zero, see [check_balance].
+//!
+//! The messages and the multiplicities can only refer to the columns of the
+//! trace and to literal constants, as in [super::degree], and the columns of
+//! the bus must be scratch columns not used by the trace.
+//!
+//! The bus connects the chunks of the MIPS trace to the Keccak coprocessor,
+//! see [super::keccak]: the chunks are proven with [prove_chunk] and
+//! the bus is checked by [Aggregator::verify].
+//!
+//! [prove_chunk]: super::continuation::prove_chunk
+//! [Aggregator::verify]: super::continuation::Aggregator::verify
+
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::{Evaluations, Radix2EvaluationDomain as D};
+use kimchi::{
+    circuits::{
+        domains::EvaluationDomains,
+        expr::{ConstantTerm::Literal, ExprInner, Operations, Variable},
+        gate::CurrOrNext,
+    },
+    curve::KimchiCurve,
+};
+use kimchi_msm::{columns::Column, logup::LookupTableID};
+use mina_poseidon::FqSponge;
+use poly_commitment::{
+    commitment::{absorb_commitment, PolyComm},
+    ipa::SRS,
+    SRS as _,
+};
+use thiserror::Error;
+
+use super::{
+    degree::{evaluate_rows, inverted_scratch, is_computable},
+    proof::{Proof, ProofInputs},
+};
+use crate::E;
+
+/// Errors that can arise when building the columns or the constraints of a
+/// bus
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BusError {
+    #[error("the bus needs {0} columns, got {1}")]
+    WrongNumberOfColumns(usize, usize),
+    #[error("the column {0:?} of the bus is not a scratch column of the trace")]
+    NotAScratchColumn(Column),
+    #[error("the column {0:?} of the bus is referred to by a message")]
+    ColumnInMessage(Column),
+    #[error("the message {0} does not only refer to the columns of the trace and to constants")]
+    UnsupportedMessage(usize),
+    #[error("the message {0} at row {1} is a root of the denominator of the argument")]
+    NullDenominator(usize, usize),
+    #[error("the bus is not balanced, the sums add up to {0}")]
+    Unbalanced(String),
+}
+
+/// A message sent on a channel of the bus, the multiplicity being negative
+/// when it is received
+pub struct Interaction<F: Field, ID: LookupTableID> {
+    pub channel: ID,
+    pub multiplicity: E<F>,
+    pub message: Vec<E<F>>,
+}
+
+/// The messages sent and received by a sub-circuit
+pub struct Bus<F: Field, ID: LookupTableID> {
+    pub interactions: Vec<Interaction<F, ID>>,
+}
+
+impl<F: Field, ID: LookupTableID> Default for Bus<F, ID> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The challenges of the argument of the bus, shared by all the sub-circuits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusChallenges<F> {
+    /// The point the denominators are evaluated at
+    pub beta: F,
+    /// The challenge combining the channel and the values of a message
+    pub joint_combiner: F,
+}
+
+impl<F: Field, ID: LookupTableID> Bus<F, ID> {
+    pub fn new() -> Self {
+        Bus {
+            interactions: vec![],
+        }
+    }
+
+    /// Sends `message` on `channel`, `multiplicity` times
+    pub fn send(&mut self, channel: ID, multiplicity: E<F>, message: Vec<E<F>>) {
+        self.interactions.push(Interaction {
+            channel,
+            multiplicity,
+            message,
+        });
+    }
+
+    /// Receives `message` from `channel`, `multiplicity` times
+    pub fn receive(&mut self, channel: ID, multiplicity: E<F>, message: Vec<E<F>>) {
+        self.interactions.push(Interaction {
+            channel,
+            multiplicity: E::zero() - multiplicity,
+            message,
+        });
+    }
+
+    /// The number of scratch columns used by the argument: one per message,
+    /// and the running sum
+    pub fn number_of_columns(&self) -> usize {
+        self.interactions.len() + 1
+    }
+
+    /// The columns of the trace referred to by the messages and the
+    /// multiplicities, whose commitments are absorbed to sample the
+    /// challenges
+    pub fn columns(&self) -> Vec<Column> {
+        let mut columns = vec![];
+        for interaction in self.interactions.iter() {
+            for expr in std::iter::once(&interaction.multiplicity).chain(interaction.message.iter())
+            {
+                collect_columns(expr, &mut columns);
+            }
+        }
+        columns
+    }
+
+    /// Returns the commitments to the columns [Bus::columns] of the trace
+    /// `inputs`, as computed by the prover
+    pub fn commitments<
+        G: KimchiCurve<ScalarField = F>,
+        const N_SCRATCH: usize,
+        const N_SCRATCH_INVERSE: usize,
+    >(
+        &self,
+        domain: EvaluationDomains<F>,
+        srs: &SRS<G>,
+        inputs: &ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+    ) -> Vec<PolyComm<G>>
+    where
+        F: PrimeField,
+    {
+        let domain_size = domain.d1.size as usize;
+        let scratch_inverse = inverted_scratch(inputs);
+        let one = PolyComm::new(vec![F::one()]);
+        self.columns()
+            .into_iter()
+            .map(|column| {
+                let evals = evaluate_rows(
+                    &E::cell(column, CurrOrNext::Curr),
+                    inputs,
+                    &scratch_inverse,
+                    domain_size,
+                );
+                let poly =
+                    Evaluations::<F, D<F>>::from_vec_and_domain(evals, domain.d1).interpolate();
+                srs.commit_custom(&poly, 1, &one).unwrap().commitment
+            })
+            .collect()
+    }
+
+    /// Returns the commitments to the columns [Bus::columns] in `proof`
+    pub fn commitments_in_proof<
+        G: KimchiCurve<ScalarField = F>,
+        const N_SCRATCH: usize,
+        const N_SCRATCH_INVERSE: usize,
+        const N_SEL: usize,
+    >(
+        &self,
+        proof: &Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>,
+    ) -> Vec<PolyComm<G>> {
+        let commitments = proof.commitments.as_ref().into_vec();
+        self.columns()
+            .into_iter()
+            .map(|column| match column {
+                Column::Relation(i) => commitments[i].clone(),
+                Column::DynamicSelector(i) => {
+                    commitments[N_SCRATCH + N_SCRATCH_INVERSE + 2 + i].clone()
+                }
+                _ => unreachable!("the messages are computable"),
+            })
+            .collect()
+    }
+
+    fn check_columns<const N_SCRATCH: usize>(&self, columns: &[Column]) -> Result<(), BusError> {
+        if columns.len() != self.number_of_columns() {
+            return Err(BusError::WrongNumberOfColumns(
+                self.number_of_columns(),
+                columns.len(),
+            ));
+        }
+        let referred = self.columns();
+        for column in columns.iter() {
+            match column {
+                Column::Relation(i) if *i < N_SCRATCH => (),
+                _ => return Err(BusError::NotAScratchColumn(*column)),
+            }
+            if referred.contains(column) {
+                return Err(BusError::ColumnInMessage(*column));
+            }
+        }
+        for (i, interaction) in self.interactions.iter().enumerate() {
+            if !std::iter::once(&interaction.multiplicity)
+                .chain(interaction.message.iter())
+                .all(is_computable)
+            {
+                return Err(BusError::UnsupportedMessage(i));
+            }
+        }
+        Ok(())
+    }
+
+    /// The combination of the channel and the values of the message of
+    /// `interaction`
+    fn combined_message(
+        &self,
+        challenges: &BusChallenges<F>,
+        interaction: &Interaction<F, ID>,
+    ) -> E<F> {
+        let mut combiner = F::one();
+        interaction
+            .message
+            .iter()
+            .fold(literal(interaction.channel.to_field()), |acc, value| {
+                combiner *= challenges.joint_combiner;
+                acc + literal(combiner) * value.clone()
+            })
+    }
+
+    /// Writes the columns of the argument in the trace `inputs`, for a domain
+    /// of size `domain_size`, and returns the sum of the terms of the
+    /// sub-circuit. `columns` are the columns of the messages, followed by the
+    /// one of the running sum. The columns of the trace shorter than the
+    /// domain are padded with zeroes, as in the prover.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the columns are not free scratch columns of the
+    /// trace, if a message can not be computed from the trace, or if a
+    /// denominator is zero.
+    pub fn fill<G, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize>(
+        &self,
+        challenges: &BusChallenges<F>,
+        inputs: &mut ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+        columns: &[Column],
+        domain_size: usize,
+    ) -> Result<F, BusError>
+    where
+        G: KimchiCurve<ScalarField = F>,
+    {
+        self.check_columns::<N_SCRATCH>(columns)?;
+        let scratch_inverse = inverted_scratch(inputs);
+        let mut terms = Vec::with_capacity(self.interactions.len());
+        for (i, interaction) in self.interactions.iter().enumerate() {
+            let message = self.combined_message(challenges, interaction);
+            let mut denominators = evaluate_rows(&message, inputs, &scratch_inverse, domain_size);
+            denominators.iter_mut().for_each(|v| *v += challenges.beta);
+            if let Some(row) = denominators.iter().position(|d| d.is_zero()) {
+                return Err(BusError::NullDenominator(i, row));
+            }
+            ark_ff::batch_inversion(&mut denominators);
+            let multiplicities = evaluate_rows(
+                &interaction.multiplicity,
+                inputs,
+                &scratch_inverse,
+                domain_size,
+            );
+            terms.push(
+                denominators
+                    .into_iter()
+                    .zip(multiplicities)
+                    .map(|(d, m)| d * m)
+                    .collect::<Vec<F>>(),
+            );
+        }
+        let row_sums: Vec<F> = (0..domain_size)
+            .map(|row| terms.iter().map(|term| term[row]).sum())
+            .collect();
+        let sum: F = row_sums.iter().copied().sum();
+        let average = sum / F::from(domain_size as u64);
+        let mut running_sum = Vec::with_capacity(domain_size);
+        let mut acc = F::zero();
+        for row_sum in row_sums {
+            running_sum.push(acc);
+            acc += row_sum - average;
+        }
+        for (column, values) in columns.iter().zip(terms.into_iter().chain([running_sum])) {
+            match column {
+                Column::Relation(i) => inputs.evaluations.scratch[*i] = values,
+                _ => unreachable!("the column is a scratch column"),
+            }
+        }
+        Ok(sum)
+    }
+
+    /// Returns the constraints of the columns of the argument, see
+    /// [Bus::fill], for the sum `sum` of the sub-circuit and a domain of size
+    /// `domain_size`. The running sum wraps around the domain, so that its
+    /// increments add up to zero.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the columns are not free scratch columns of the
+    /// trace, or if a message can not be computed from the trace.
+    pub fn constraints<const N_SCRATCH: usize>(
+        &self,
+        challenges: &BusChallenges<F>,
+        sum: F,
+        columns: &[Column],
+        domain_size: usize,
+    ) -> Result<Vec<E<F>>, BusError> {
+        self.check_columns::<N_SCRATCH>(columns)?;
+        let (running_sum, term_columns) = columns.split_last().unwrap();
+        let mut constraints: Vec<E<F>> = self
+            .interactions
+            .iter()
+            .zip(term_columns.iter())
+            .map(|(interaction, column)| {
+                let denominator =
+                    literal(challenges.beta) + self.combined_message(challenges, interaction);
+                E::cell(*column, CurrOrNext::Curr) * denominator - interaction.multiplicity.clone()
+            })
+            .collect();
+        let terms = term_columns.iter().fold(E::zero(), |acc, column| {
+            acc + E::cell(*column, CurrOrNext::Curr)
+        });
+        constraints.push(
+            E::cell(*running_sum, CurrOrNext::Next)
+                - E::cell(*running_sum, CurrOrNext::Curr)
+                - terms
+                + literal(sum / F::from(domain_size as u64)),
+        );
+        Ok(constraints)
+    }
+}
+
+impl<F: PrimeField> BusChallenges<F> {
+    /// Samples the challenges from the commitments to the columns referred to
+    /// by the messages of each sub-circuit, see [Bus::commitments] and
+    /// [Bus::commitments_in_proof]. The sub-circuits must be given in the same
+    /// order to the prover and to the verifier.
+    pub fn sample<G, EFqSponge>(commitments: &[Vec<PolyComm<G>>]) -> Self
+    where
+        G: KimchiCurve<ScalarField = F>,
+        G::BaseField: PrimeField,
+        EFqSponge: FqSponge<G::BaseField, G, F>,
+    {
+        let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
+        for comm in commitments.iter().flatten() {
+            absorb_commitment(&mut fq_sponge, comm);
+        }
+        let beta = fq_sponge.challenge();
+        let joint_combiner = fq_sponge.challenge();
+        BusChallenges {
+            beta,
+            joint_combiner,
+        }
+    }
+}
+
+/// Checks that the sums of the sub-circuits, returned by [Bus::fill], add up
+/// to zero, i.e. that each message is received as many times as it is sent.
+pub fn check_balance<F: Field>(sums: &[F]) -> Result<(), BusError> {
+    let total: F = sums.iter().copied().sum();
+    if total.is_zero() {
+        Ok(())
+    } else {
+        Err(BusError::Unbalanced(total.to_string()))
+    }
+}
+
+fn literal<F: Field>(x: F) -> E<F> {
+    E::constant(Operations::from(Literal(x)))
+}
+
+/// Adds to `columns` the columns of the trace `expr` refers to, if they are
+/// not already in it
+fn collect_columns<F: Field>(expr: &E<F>, columns: &mut Vec<Column>) {
+    match expr {
+        Operations::Atom(ExprInner::Cell(Variable { col, .. })) => {
+            if !columns.contains(col) {
+                columns.push(*col)
+            }
+        }
+        Operations::Atom(_) | Operations::IfFeature(_, _, _) => (),
+        Operations::Add(x, y) | Operations::Mul(x, y) | Operations::Sub(x, y) => {
+            collect_columns(x, columns);
+            collect_columns(y, columns);
+        }
+        Operations::Double(x)
+        | Operations::Square(x)
+        | Operations::Pow(x, _)
+        | Operations::Cache(_, x) => collect_columns(x, columns),
+    }
+}
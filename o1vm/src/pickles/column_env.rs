@@ -1,10 +1,11 @@
 use ark_ff::FftField;
 use ark_poly::{Evaluations, Radix2EvaluationDomain};
-use kimchi_msm::columns::Column;
+use kimchi_msm::{columns::Column, logup::prover::QuotientPolynomialEnvironment, LookupTableID};
 
 use crate::{
     interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
-    pickles::proof::WitnessColumns,
+    lookups::LookupTableIDs,
+    pickles::{proof::WitnessColumns, PUBLIC_COLUMN},
 };
 use kimchi::circuits::{
     berkeley_columns::{BerkeleyChallengeTerm, BerkeleyChallenges},
@@ -18,10 +19,24 @@ type Evals<F> = Evaluations<F, Radix2EvaluationDomain<F>>;
 /// required to evaluate an expression as a polynomial.
 ///
 /// All are evaluations.
-pub struct ColumnEnvironment<'a, F: FftField> {
+pub struct ColumnEnvironment<
+    'a,
+    F: FftField,
+    const N_SCRATCH: usize = SCRATCH_SIZE,
+    const N_SCRATCH_INVERSE: usize = SCRATCH_SIZE_INVERSE,
+    const N_SEL: usize = N_MIPS_SEL_COLS,
+> {
     /// The witness column polynomials. Includes relation columns and dynamic
     /// selector columns.
-    pub witness: &'a WitnessColumns<Evals<F>, [Evals<F>; N_MIPS_SEL_COLS]>,
+    pub witness: &'a WitnessColumns<Evals<F>, [Evals<F>; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>,
+    /// The public column, see [PUBLIC_COLUMN]
+    pub public: &'a Evals<F>,
+    /// The polynomials of the lookup argument, if there are lookups
+    pub lookup: Option<QuotientPolynomialEnvironment<'a, F, LookupTableIDs>>,
+    /// The evaluations of the polynomial vanishing on the last row, used by
+    /// the constraints referring to the next row, see
+    /// [transition](super::transition).
+    pub vanishes_on_last_row: &'a Evals<F>,
     /// The value `prod_{j != 1} (1 - ω^j)`, used for efficiently
     /// computing the evaluations of the unnormalized Lagrange basis
     /// polynomials.
@@ -36,42 +51,50 @@ pub struct ColumnEnvironment<'a, F: FftField> {
 }
 
 pub fn get_all_columns() -> Vec<Column> {
-    let mut cols =
-        Vec::<Column>::with_capacity(SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 2 + N_MIPS_SEL_COLS);
-    for i in 0..SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 2 {
+    get_columns::<SCRATCH_SIZE, SCRATCH_SIZE_INVERSE, N_MIPS_SEL_COLS>()
+}
+
+/// Same as [get_all_columns], for an interpreter with `N_SCRATCH` scratch
+/// columns, `N_SCRATCH_INVERSE` inverse scratch columns and `N_SEL` selectors.
+pub fn get_columns<const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize, const N_SEL: usize>(
+) -> Vec<Column> {
+    let mut cols = Vec::<Column>::with_capacity(N_SCRATCH + N_SCRATCH_INVERSE + 2 + N_SEL);
+    for i in 0..N_SCRATCH + N_SCRATCH_INVERSE + 2 {
         cols.push(Column::Relation(i));
     }
-    for i in 0..N_MIPS_SEL_COLS {
+    for i in 0..N_SEL {
         cols.push(Column::DynamicSelector(i));
     }
     cols
 }
 
-impl<G> WitnessColumns<G, [G; N_MIPS_SEL_COLS]> {
+impl<G, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize, const N_SEL: usize>
+    WitnessColumns<G, [G; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>
+{
     pub fn get_column(&self, col: &Column) -> Option<&G> {
         match *col {
             Column::Relation(i) => {
-                if i < SCRATCH_SIZE {
+                if i < N_SCRATCH {
                     let res = &self.scratch[i];
                     Some(res)
-                } else if i < SCRATCH_SIZE + SCRATCH_SIZE_INVERSE {
-                    let res = &self.scratch_inverse[i - SCRATCH_SIZE];
+                } else if i < N_SCRATCH + N_SCRATCH_INVERSE {
+                    let res = &self.scratch_inverse[i - N_SCRATCH];
                     Some(res)
-                } else if i == SCRATCH_SIZE + SCRATCH_SIZE_INVERSE {
+                } else if i == N_SCRATCH + N_SCRATCH_INVERSE {
                     let res = &self.instruction_counter;
                     Some(res)
-                } else if i == SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 1 {
+                } else if i == N_SCRATCH + N_SCRATCH_INVERSE + 1 {
                     let res = &self.error;
                     Some(res)
                 } else {
-                    panic!("We should not have that many relation columns. We have {} columns and index {} was given", SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 2, i);
+                    panic!("We should not have that many relation columns. We have {} columns and index {} was given", N_SCRATCH + N_SCRATCH_INVERSE + 2, i);
                 }
             }
             Column::DynamicSelector(i) => {
                 assert!(
-                    i < N_MIPS_SEL_COLS,
+                    i < N_SEL,
                     "We do not have that many dynamic selector columns. We have {} columns and index {} was given",
-                    N_MIPS_SEL_COLS,
+                    N_SEL,
                     i
                 );
                 let res = &self.selector[i];
@@ -87,15 +110,38 @@ impl<G> WitnessColumns<G, [G; N_MIPS_SEL_COLS]> {
     }
 }
 
-impl<'a, F: FftField> TColumnEnvironment<'a, F, BerkeleyChallengeTerm, BerkeleyChallenges<F>>
-    for ColumnEnvironment<'a, F>
+impl<
+        'a,
+        F: FftField,
+        const N_SCRATCH: usize,
+        const N_SCRATCH_INVERSE: usize,
+        const N_SEL: usize,
+    > TColumnEnvironment<'a, F, BerkeleyChallengeTerm, BerkeleyChallenges<F>>
+    for ColumnEnvironment<'a, F, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>
 {
     // FIXME: do we change to the MIPS column type?
     // We do not want to keep kimchi_msm/generic prover
     type Column = Column;
 
     fn get_column(&self, col: &Self::Column) -> Option<&'a Evals<F>> {
-        self.witness.get_column(col)
+        let lookup = self.lookup.as_ref();
+        match *col {
+            Column::LookupPartialSum((table_id, i)) => {
+                let evals = lookup?.lookup_terms_evals_d8;
+                evals.get(&LookupTableIDs::from_u32(table_id))?.get(i)
+            }
+            Column::LookupAggregation => Some(lookup?.lookup_aggregation_evals_d8),
+            Column::LookupMultiplicity((table_id, i)) => {
+                let evals = lookup?.lookup_counters_evals_d8;
+                evals.get(&LookupTableIDs::from_u32(table_id))?.get(i)
+            }
+            Column::LookupFixedTable(table_id) => {
+                let evals = lookup?.fixed_tables_evals_d8;
+                evals.get(&LookupTableIDs::from_u32(table_id))
+            }
+            PUBLIC_COLUMN => Some(self.public),
+            _ => self.witness.get_column(col),
+        }
     }
 
     fn get_domain(&self, d: Domain) -> Radix2EvaluationDomain<F> {
@@ -119,10 +165,12 @@ impl<'a, F: FftField> TColumnEnvironment<'a, F, BerkeleyChallengeTerm, BerkeleyC
         &self.challenges
     }
 
+    /// We do not support zero-knowledge for now, so it only vanishes on the
+    /// last row.
     fn vanishes_on_zero_knowledge_and_previous_rows(
         &self,
     ) -> &'a Evaluations<F, Radix2EvaluationDomain<F>> {
-        panic!("Not supposed to be used in MIPS. We do not support zero-knowledge for now")
+        self.vanishes_on_last_row
     }
 
     fn l0_1(&self) -> F {
@@ -0,0 +1,818 @@
+//! Proving executions longer than a single domain.
+//!
+//! The execution trace is split into chunks of at most the size of the domain,
+//! and a proof is made for each chunk with the pickles prover, see
+//! [prove_chunk]. A chunk which ends because the program halted is padded with
+//! copies of its last row. The copies satisfy the constraints of the
+//! instructions, which are on a single row, as the last row does, and the
+//! constraints binding the boundaries, see [boundary_constraints], as the
+//! padding does not change the instruction counter. The copies of the exit do
+//! not read any preimage, so they send nothing on the bus connecting the chunk
+//! to the Keccak coprocessor. A constraint relating a row to the next one, see
+//! [transition](super::transition), would have to hold between the last row
+//! and its copies too.
+//!
+//! Each chunk carries the state of the virtual machine at its boundaries,
+//! following the convention of [crate::interpreters::mips::boundary]. It forms
+//! the public input and output of the chunk, see [ChunkProof::public_input].
+//! The [Aggregator] checks that the state at the end of a chunk is the state at
+//! the beginning of the next one, and verifies the proofs of all the chunks.
+//!
+//! The chunk also carries the root of the Merkle tree of the memory at its
+//! boundaries, see [crate::merkle]. The tree is kept by the host, and only the
+//! pages written during a chunk are hashed again. No constraint reads or
+//! writes the tree, so the roots are not part of the public input of the
+//! chunk: they are claims of the host, which the [Aggregator] only checks to
+//! be consecutive.
+//!
+//! The number of instructions executed by a chunk is the difference of the
+//! instruction counters of its boundaries, which are public. The [Aggregator]
+//! can be given a maximum number of instructions, see
+//! [Aggregator::with_max_cycles], to enforce a limit on the length of the
+//! proven executions.
+//!
+//! The data given by the host to the program, see
+//! [crate::interpreters::mips::host_io], is part of the public input of the
+//! chunk: each boundary carries a Poseidon digest of the data given so far,
+//! updated with the data given during the chunk, see
+//! [ChunkProof::host_digest_end].
+//!
+//! The public input and output are committed to in the proof of the chunk, see
+//! [ChunkProof::public_values]. The public states of the boundaries are hashed
+//! with Poseidon, so that the public values fit in any domain. A chunk can be
+//! written to a file with its boundaries, see [ChunkProof::write], to be
+//! checked by another host.
+//!
+//! The lookups of the instructions in the fixed tables fitting in the domain
+//! are enforced, see [MipsLookups]. The copies of the last row padding a chunk
+//! copy its lookups.
+//!
+//! The instruction counters of the boundaries are bound to the execution trace
+//! by [boundary_constraints], which [prove_chunk] and [Aggregator::verify] add
+//! to the constraints of the instructions. The other public values are bound to
+//! the proofs only: the hashes of the states and the digests of the data of the
+//! host are computed with Poseidon, whose S-box has a degree
+//! higher than [MAXIMUM_DEGREE_CONSTRAINTS](super::MAXIMUM_DEGREE_CONSTRAINTS),
+//! and the registers and the memory are not read with a lookup argument yet, so
+//! the trace cannot be checked against them.
+//!
+//! The preimages of Keccak256 keys read by the chunks are hashed by the Keccak
+//! coprocessor, and the chunks are connected to its proofs with the
+//! [bus](super::bus), see [super::keccak]. The challenges of the bus are
+//! sampled from the traces of all the chunks and of the coprocessor, so the
+//! whole execution is run before any chunk is proven: the chunks are executed
+//! with [execute_chunk], and their traces are kept in memory until they are
+//! proven with [prove_chunk], with the challenges of [bus_challenges].
+
+use ark_ff::{Field, PrimeField, Zero};
+use kimchi::{
+    circuits::{domains::EvaluationDomains, gate::CurrOrNext},
+    curve::KimchiCurve,
+    plonk_sponge::FrSponge,
+};
+use kimchi_msm::columns::ColumnIndexer;
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    poseidon::{ArithmeticSponge, Sponge},
+    FqSponge,
+};
+use poly_commitment::{ipa::SRS, PolyComm};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+use thiserror::Error;
+
+use super::{
+    bus::{check_balance, BusChallenges, BusError},
+    keccak::{self, KeccakCoprocessor, KeccakProof},
+    lookup::MipsLookups,
+    on_first_row, on_last_row,
+    proof::{Proof, ProofError, ProofInputs},
+    prover::{self, ProverError},
+    verifier, PUBLIC_COLUMN,
+};
+use crate::{
+    cannon::{Meta, Start, VmConfiguration},
+    interpreters::mips::{
+        boundary::BoundaryState,
+        column::{ColumnAlias as MIPSColumn, N_MIPS_REL_COLS, SCRATCH_SIZE},
+        witness::{Env as WEnv, MAX_ACC},
+    },
+    merkle::MerkleStore,
+    preimage_oracle::PreImageOracleT,
+    E,
+};
+
+/// The proof of a chunk of the execution trace, with the state of the virtual
+/// machine before and after the chunk
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "G: KimchiCurve")]
+pub struct ChunkProof<G: KimchiCurve> {
+    /// The state before the first instruction of the chunk
+    pub start: BoundaryState,
+    /// The state after the last instruction of the chunk
+    pub end: BoundaryState,
+    /// The root of the memory before the first instruction of the chunk, given
+    /// by the host and not committed to by the proof, see [crate::merkle]
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub memory_root_start: G::ScalarField,
+    /// The root of the memory after the last instruction of the chunk, given
+    /// by the host and not committed to by the proof, see [crate::merkle]
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub memory_root_end: G::ScalarField,
+    /// The digest of the data given by the host before the chunk
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub host_digest_start: G::ScalarField,
+    /// The data given by the host to the program during the chunk
+    pub host_input: Vec<u8>,
+    /// The number of instructions executed in the chunk. The rest of the
+    /// domain is padding.
+    pub length: usize,
+    /// The number of Keccak hashes requested by the program during the chunk,
+    /// which are proven by the coprocessor, see [KeccakCoprocessor]
+    pub hashes: usize,
+    /// The sum of the chunk on the bus connecting it to the coprocessor, see
+    /// [keccak::mips_bus]
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub bus_sum: G::ScalarField,
+    pub proof: Proof<G>,
+}
+
+impl<G: KimchiCurve> ChunkProof<G> {
+    /// The public input and output of the chunk: the public state of the
+    /// boundary before the chunk and the digest of the data of the host,
+    /// followed by the same values after the chunk.
+    pub fn public_input(&self) -> Vec<G::ScalarField> {
+        let mut res = self.start.to_public_state();
+        res.push(self.host_digest_start);
+        res.extend(self.end.to_public_state());
+        res.push(self.host_digest_end());
+        res
+    }
+
+    /// The public values of the proof of the chunk, see [ProofInputs::public]:
+    /// the instruction counter of the boundary before the chunk, the hash of
+    /// its public state and the digest of the data of the host, followed by the
+    /// same values after the chunk.
+    pub fn public_values(&self) -> Vec<G::ScalarField> {
+        public_values::<G>(
+            &self.start,
+            self.host_digest_start,
+            &self.end,
+            self.host_digest_end(),
+        )
+    }
+
+    /// The digest of the data given by the host up to the end of the chunk:
+    /// the Poseidon hash of the digest before the chunk and of the data given
+    /// during the chunk
+    pub fn host_digest_end(&self) -> G::ScalarField {
+        host_digest::<G>(self.host_digest_start, &self.host_input)
+    }
+
+    /// The number of instructions executed by the chunk, according to its
+    /// boundaries
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the chunk ends at an earlier instruction than it
+    /// starts at, e.g. if it has been tampered with.
+    pub fn cycles(&self) -> Result<u64, ContinuationError> {
+        self.end
+            .step()
+            .checked_sub(self.start.step())
+            .ok_or(ContinuationError::EndsBeforeStart)
+    }
+
+    /// Encodes the chunk with MessagePack
+    ///
+    /// # Errors
+    ///
+    /// See [rmp_serde::to_vec].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofError> {
+        Ok(rmp_serde::to_vec(self)?)
+    }
+
+    /// Decodes a chunk encoded with [ChunkProof::to_bytes]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not a valid chunk.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// Writes the chunk to the file `path`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), ProofError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        rmp_serde::encode::write(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a chunk written with [ChunkProof::write]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be read or is not a valid chunk.
+    pub fn read(path: &Path) -> Result<Self, ProofError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(rmp_serde::from_read(reader)?)
+    }
+}
+
+/// Errors that can arise when aggregating the proofs of the chunks
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ContinuationError {
+    #[error("the chunk {0} does not start with the state the previous chunk ends with")]
+    BoundaryMismatch(usize),
+    #[error("the chunk {0} does not start with the memory the previous chunk ends with")]
+    MemoryRootMismatch(usize),
+    #[error("the chunk {0} does not start with the data of the host the previous chunk ends with")]
+    HostDigestMismatch(usize),
+    #[error("the chunk {0} is added after the program halted")]
+    ChunkAfterHalt(usize),
+    #[error("the proof of the chunk {0} is not valid")]
+    InvalidProof(usize),
+    #[error("the chunk {0} does not execute as many instructions as its length")]
+    LengthMismatch(usize),
+    #[error("the chunk ends at an earlier instruction than it starts at")]
+    EndsBeforeStart,
+    #[error("the chunk {0} exceeds the maximum number of instructions of the execution")]
+    CycleLimitExceeded(usize),
+    #[error("the proof of the domain {0} of the Keccak coprocessor is not valid")]
+    InvalidKeccakProof(usize),
+    #[error("the preimages read by the chunks are not the ones hashed by the Keccak coprocessor")]
+    UnbalancedBus,
+}
+
+fn hash<G: KimchiCurve>(values: &[G::ScalarField]) -> G::ScalarField {
+    let mut sponge =
+        ArithmeticSponge::<G::ScalarField, PlonkSpongeConstantsKimchi>::new(G::sponge_params());
+    sponge.absorb(values);
+    sponge.squeeze()
+}
+
+fn public_values<G: KimchiCurve>(
+    start: &BoundaryState,
+    host_digest_start: G::ScalarField,
+    end: &BoundaryState,
+    host_digest_end: G::ScalarField,
+) -> Vec<G::ScalarField> {
+    vec![
+        G::ScalarField::from(start.instruction_counter),
+        hash::<G>(&start.to_public_state()),
+        host_digest_start,
+        G::ScalarField::from(end.instruction_counter),
+        hash::<G>(&end.to_public_state()),
+        host_digest_end,
+    ]
+}
+
+/// Returns the constraints binding the execution trace of a chunk to the
+/// instruction counters of its boundaries, the first public value of each
+/// half of [ChunkProof::public_values], see
+/// [public_rows](super::proof::public_rows):
+/// - the instruction counter of the first row is the one after the first
+///   instruction of the chunk, i.e. the one of the state before the chunk
+///   increased by [MAX_ACC];
+/// - the instruction counter of the last row, a copy of the row of the last
+///   instruction if the chunk is padded, is the one of the state after the
+///   chunk.
+pub fn boundary_constraints<F: Field>() -> Vec<E<F>> {
+    let instruction_counter = E::cell(MIPSColumn::InstructionCounter.to_column(), CurrOrNext::Curr);
+    let public = E::cell(PUBLIC_COLUMN, CurrOrNext::Curr);
+    vec![
+        on_first_row(instruction_counter.clone() - public.clone() - E::from(MAX_ACC)),
+        on_last_row(instruction_counter - public),
+    ]
+}
+
+/// Returns the constraints of the instructions `constraints`, e.g. the ones
+/// of [get_mips_constraints](super::get_mips_constraints), followed by the
+/// [boundary_constraints], the constraints of [keccak::mips_bus] for the
+/// challenges `challenges` and the sum `bus_sum` of a chunk, and the ones of
+/// the lookups of [MipsLookups], in a domain of size `domain_size`
+///
+/// # Errors
+///
+/// See [Bus::constraints](super::bus::Bus::constraints).
+pub fn chunk_constraints<F: PrimeField>(
+    constraints: &[E<F>],
+    challenges: &BusChallenges<F>,
+    bus_sum: F,
+    domain_size: usize,
+) -> Result<Vec<E<F>>, BusError> {
+    let bus = keccak::mips_bus().constraints::<SCRATCH_SIZE>(
+        challenges,
+        bus_sum,
+        &keccak::mips_bus_columns(),
+        domain_size,
+    )?;
+    Ok(constraints
+        .iter()
+        .cloned()
+        .chain(boundary_constraints())
+        .chain(bus)
+        .chain(MipsLookups::new(domain_size).constraints())
+        .collect())
+}
+
+/// Returns the digest of the data `host_input` given by the host after the
+/// data of digest `previous`. The length of the data is hashed, so that the
+/// data can not be extended with zeroes.
+fn host_digest<G: KimchiCurve>(previous: G::ScalarField, host_input: &[u8]) -> G::ScalarField {
+    let values: Vec<G::ScalarField> = [previous, G::ScalarField::from(host_input.len() as u64)]
+        .into_iter()
+        .chain(host_input.iter().map(|byte| G::ScalarField::from(*byte)))
+        .collect();
+    hash::<G>(&values)
+}
+
+/// Returns the Merkle tree of the memory of the witness environment, and
+/// forgets the pages written so far, as they are in the tree.
+pub fn memory_store<G: KimchiCurve, PreImageOracle: PreImageOracleT>(
+    env: &mut WEnv<G::ScalarField, PreImageOracle>,
+) -> MerkleStore<G::ScalarField> {
+    env.take_written_pages();
+    MerkleStore::from_memory(G::sponge_params(), &env.memory)
+}
+
+/// Updates the Merkle tree of the memory with the pages written since the
+/// last update.
+fn update_memory_store<Fp: PrimeField, PreImageOracle: PreImageOracleT>(
+    memory: &mut MerkleStore<Fp>,
+    env: &mut WEnv<Fp, PreImageOracle>,
+) {
+    for index in env.take_written_pages() {
+        let (_, data) = env
+            .memory
+            .iter()
+            .find(|(page, _)| *page == index)
+            .expect("a written page is in the memory");
+        memory.update_page(index, data);
+    }
+}
+
+/// Adds the row of the last instruction executed by the witness environment
+/// to the execution trace, with its lookups in the tables of `lookups`.
+pub fn push_row<G: KimchiCurve, PreImageOracle: PreImageOracleT>(
+    inputs: &mut ProofInputs<G>,
+    lookups: &MipsLookups<G::ScalarField>,
+    env: &WEnv<G::ScalarField, PreImageOracle>,
+) {
+    for (scratch, scratch_chunk) in env
+        .scratch_state
+        .iter()
+        .zip(inputs.evaluations.scratch.iter_mut())
+    {
+        scratch_chunk.push(*scratch);
+    }
+    for (scratch, scratch_chunk) in env
+        .scratch_state_inverse
+        .iter()
+        .zip(inputs.evaluations.scratch_inverse.iter_mut())
+    {
+        scratch_chunk.push(*scratch);
+    }
+    inputs
+        .evaluations
+        .instruction_counter
+        .push(G::ScalarField::from(env.instruction_counter));
+    // The error column is not constrained
+    inputs.evaluations.error.push(G::ScalarField::zero());
+    inputs.evaluations.selector.push(G::ScalarField::from(
+        (env.selector - N_MIPS_REL_COLS) as u64,
+    ));
+    lookups.push_row(&mut inputs.lookups, env.selector, &env.lookups);
+}
+
+/// Pads the execution trace up to `domain_size` rows with copies of its last
+/// row, see the [module documentation](self).
+///
+/// # Panics
+///
+/// Will panic if the execution trace is empty.
+fn pad<G: KimchiCurve>(inputs: &mut ProofInputs<G>, domain_size: usize) {
+    let evaluations = &mut inputs.evaluations;
+    let pad_column = |column: &mut Vec<G::ScalarField>| {
+        let last = *column.last().expect("the execution trace is empty");
+        column.resize(domain_size, last);
+    };
+    evaluations.scratch.iter_mut().for_each(pad_column);
+    evaluations.scratch_inverse.iter_mut().for_each(pad_column);
+    pad_column(&mut evaluations.instruction_counter);
+    pad_column(&mut evaluations.error);
+    pad_column(&mut evaluations.selector);
+    for column in inputs.lookups.values_mut().flatten() {
+        let last = column.last().expect("the execution trace is empty").clone();
+        column.resize(domain_size, last);
+    }
+}
+
+/// The execution trace of a chunk, with the state of the virtual machine
+/// before and after the chunk, to be proven with [prove_chunk]
+pub struct ChunkTrace<G: KimchiCurve> {
+    /// The state before the first instruction of the chunk
+    pub start: BoundaryState,
+    /// The state after the last instruction of the chunk
+    pub end: BoundaryState,
+    /// The root of the memory before the first instruction of the chunk
+    pub memory_root_start: G::ScalarField,
+    /// The root of the memory after the last instruction of the chunk
+    pub memory_root_end: G::ScalarField,
+    /// The digest of the data given by the host before the chunk
+    pub host_digest_start: G::ScalarField,
+    /// The data given by the host to the program during the chunk
+    pub host_input: Vec<u8>,
+    /// The number of instructions executed in the chunk
+    pub length: usize,
+    /// The number of Keccak hashes requested by the program during the chunk
+    pub hashes: usize,
+    /// The execution trace, padded up to the size of the domain
+    pub inputs: ProofInputs<G>,
+}
+
+impl<G: KimchiCurve> ChunkTrace<G> {
+    /// Returns the commitments to the columns referred to by the messages of
+    /// [keccak::mips_bus], to sample the challenges of the bus, see
+    /// [bus_challenges]
+    pub fn bus_commitments(
+        &self,
+        domain: EvaluationDomains<G::ScalarField>,
+        srs: &SRS<G>,
+    ) -> Vec<PolyComm<G>> {
+        keccak::mips_bus().commitments(domain, srs, &self.inputs)
+    }
+}
+
+/// Executes the program of the witness environment until the domain is full
+/// or the program halts, and returns the chunk of the execution trace. The
+/// Merkle tree of the memory is updated with the pages written during the
+/// chunk, and `host_digest` with the data given by the host during the chunk.
+/// The digest is zero at the beginning of an execution. The Keccak hashes
+/// requested by the program during the chunk are executed by the coprocessor
+/// `keccak`.
+///
+/// # Panics
+///
+/// Will panic if the program has already halted.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_chunk<G: KimchiCurve, PreImageOracle: PreImageOracleT>(
+    domain_size: usize,
+    env: &mut WEnv<G::ScalarField, PreImageOracle>,
+    memory: &mut MerkleStore<G::ScalarField>,
+    host_digest: &mut G::ScalarField,
+    keccak: &mut KeccakCoprocessor<G>,
+    configuration: &VmConfiguration,
+    meta: &Meta,
+    start: &Start,
+) -> ChunkTrace<G> {
+    assert!(!env.halt, "the program has already halted");
+    let start_state = BoundaryState::spill(env);
+    update_memory_store(memory, env);
+    let memory_root_start = memory.root();
+    let host_digest_start = *host_digest;
+    let host_input_start = env.syscall_env.host_input.len();
+    let hashes_start = keccak.hashes;
+
+    let lookups = MipsLookups::new(domain_size);
+    let mut inputs = ProofInputs::new(domain_size);
+    let mut length = 0;
+    while length < domain_size && !env.halt {
+        env.step(configuration, meta, start);
+        push_row(&mut inputs, &lookups, env);
+        keccak.take_request(env);
+        length += 1;
+    }
+    pad(&mut inputs, domain_size);
+    update_memory_store(memory, env);
+
+    let end_state = BoundaryState::spill(env);
+    let memory_root_end = memory.root();
+    let host_input = env.syscall_env.host_input[host_input_start..].to_vec();
+    *host_digest = host_digest::<G>(host_digest_start, &host_input);
+    inputs.public = public_values::<G>(&start_state, host_digest_start, &end_state, *host_digest);
+    ChunkTrace {
+        start: start_state,
+        end: end_state,
+        memory_root_start,
+        memory_root_end,
+        host_digest_start,
+        host_input,
+        length,
+        hashes: keccak.hashes - hashes_start,
+        inputs,
+    }
+}
+
+/// Samples the challenges of the bus from the commitments to the traces of
+/// the chunks `chunks`, followed by the ones of the domains of the
+/// coprocessor `keccak`, see [BusChallenges::sample].
+/// [Aggregator::verify] samples them from the proofs in the same order.
+pub fn bus_challenges<G: KimchiCurve, EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    chunks: &[ChunkTrace<G>],
+    keccak: &mut KeccakCoprocessor<G>,
+) -> BusChallenges<G::ScalarField>
+where
+    G::BaseField: PrimeField,
+{
+    let mut commitments: Vec<Vec<PolyComm<G>>> = chunks
+        .iter()
+        .map(|chunk| chunk.bus_commitments(domain, srs))
+        .collect();
+    commitments.extend(keccak.bus_commitments(domain, srs));
+    BusChallenges::sample::<G, EFqSponge>(&commitments)
+}
+
+/// Makes a proof of the chunk of the execution trace `trace`, for the
+/// constraints of the instructions `constraints`, e.g. the ones of
+/// [get_mips_constraints](super::get_mips_constraints), and the ones of
+/// [chunk_constraints]. The columns of the bus are filled for the challenges
+/// `challenges`, see [bus_challenges].
+///
+/// # Soundness
+///
+/// Only the instruction counters of the boundaries are bound to the execution
+/// trace. The proof does not show that the trace starts from the registers
+/// and the memory of the state before the chunk, nor that it ends in the state
+/// carried by the returned chunk: they are trusted as they are given by the
+/// host, see the [module documentation](self). The memory roots are not
+/// committed to by the proof at all.
+///
+/// # Errors
+///
+/// Will give error if the columns of the bus can not be filled, or if the
+/// proof can not be made.
+pub fn prove_chunk<
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
+    EFrSponge: FrSponge<G::ScalarField>,
+    RNG,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    constraints: &[E<G::ScalarField>],
+    trace: ChunkTrace<G>,
+    challenges: &BusChallenges<G::ScalarField>,
+    rng: &mut RNG,
+) -> Result<ChunkProof<G>, ProverError>
+where
+    G::BaseField: PrimeField,
+    RNG: RngCore + CryptoRng,
+{
+    let domain_size = domain.d1.size as usize;
+    let ChunkTrace {
+        start,
+        end,
+        memory_root_start,
+        memory_root_end,
+        host_digest_start,
+        host_input,
+        length,
+        hashes,
+        mut inputs,
+    } = trace;
+    let bus_sum = keccak::mips_bus().fill(
+        challenges,
+        &mut inputs,
+        &keccak::mips_bus_columns(),
+        domain_size,
+    )?;
+    let constraints = chunk_constraints(constraints, challenges, bus_sum, domain_size)?;
+    let proof =
+        prover::prove::<G, EFqSponge, EFrSponge, RNG>(domain, srs, inputs, &constraints, rng)?;
+    Ok(ChunkProof {
+        start,
+        end,
+        memory_root_start,
+        memory_root_end,
+        host_digest_start,
+        host_input,
+        length,
+        hashes,
+        bus_sum,
+        proof,
+    })
+}
+
+/// Collects the proofs of the consecutive chunks of an execution, starting
+/// from a given state.
+///
+/// # Soundness
+///
+/// The boundaries of consecutive chunks are checked to be equal, but the
+/// proofs bind only the instruction counters of the boundaries to the
+/// execution traces, see [prove_chunk], and the memory roots are claims of the
+/// host, not committed to by the proofs. A successful [Aggregator::verify]
+/// does not prove that the final state is reached from the initial one. The
+/// preimages read by the chunks are checked against the hashes of the Keccak
+/// coprocessor with the limits given in [super::keccak].
+pub struct Aggregator<G: KimchiCurve> {
+    /// The state before the first chunk
+    pub initial_state: BoundaryState,
+    /// The root of the memory before the first chunk
+    pub initial_memory_root: G::ScalarField,
+    /// The digest of the data given by the host before the first chunk, zero
+    /// at the beginning of an execution
+    pub initial_host_digest: G::ScalarField,
+    pub chunks: Vec<ChunkProof<G>>,
+    /// The maximum number of instructions the chunks can execute, if any
+    pub max_cycles: Option<u64>,
+}
+
+impl<G: KimchiCurve> Aggregator<G> {
+    pub fn new(initial_state: BoundaryState, initial_memory_root: G::ScalarField) -> Self {
+        Aggregator {
+            initial_state,
+            initial_memory_root,
+            initial_host_digest: G::ScalarField::zero(),
+            chunks: vec![],
+            max_cycles: None,
+        }
+    }
+
+    /// Limits the number of instructions the chunks can execute
+    pub fn with_max_cycles(mut self, max_cycles: u64) -> Self {
+        self.max_cycles = Some(max_cycles);
+        self
+    }
+
+    /// The state after the last chunk
+    pub fn final_state(&self) -> &BoundaryState {
+        self.chunks
+            .last()
+            .map_or(&self.initial_state, |chunk| &chunk.end)
+    }
+
+    /// The root of the memory after the last chunk
+    pub fn final_memory_root(&self) -> G::ScalarField {
+        self.chunks
+            .last()
+            .map_or(self.initial_memory_root, |chunk| chunk.memory_root_end)
+    }
+
+    /// The digest of the data given by the host up to the end of the last
+    /// chunk
+    pub fn final_host_digest(&self) -> G::ScalarField {
+        self.chunks
+            .last()
+            .map_or(self.initial_host_digest, |chunk| chunk.host_digest_end())
+    }
+
+    /// The total number of instructions executed by the chunks
+    pub fn length(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.length).sum()
+    }
+
+    /// The total number of Keccak hashes requested by the chunks
+    pub fn hashes(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.hashes).sum()
+    }
+
+    /// The total number of instructions executed by the chunks, according to
+    /// the boundaries
+    pub fn cycles(&self) -> u64 {
+        // the chunks are checked to go forward when they are pushed
+        self.final_state()
+            .step()
+            .saturating_sub(self.initial_state.step())
+    }
+
+    /// Adds the proof of the next chunk, checking that it continues the
+    /// execution where the previous chunk ended.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the boundary state, the memory root or the digest of
+    /// the data of the host at the beginning of the chunk is not the one at the
+    /// end of the previous chunk,
+    /// if the program has already halted, if the chunk ends before it starts or
+    /// does not execute as many instructions as its length, or if the chunk
+    /// goes beyond the maximum number of instructions.
+    pub fn push(&mut self, chunk: ChunkProof<G>) -> Result<(), ContinuationError> {
+        self.check(&chunk)?;
+        self.chunks.push(chunk);
+        Ok(())
+    }
+
+    /// Checks that the chunk continues the execution where the previous chunk
+    /// ended, without adding it, see [Aggregator::push].
+    ///
+    /// # Errors
+    ///
+    /// See [Aggregator::push].
+    pub fn check(&self, chunk: &ChunkProof<G>) -> Result<(), ContinuationError> {
+        let index = self.chunks.len();
+        if self.final_state().halt {
+            return Err(ContinuationError::ChunkAfterHalt(index));
+        }
+        if chunk.start != *self.final_state() {
+            return Err(ContinuationError::BoundaryMismatch(index));
+        }
+        if chunk.memory_root_start != self.final_memory_root() {
+            return Err(ContinuationError::MemoryRootMismatch(index));
+        }
+        if chunk.host_digest_start != self.final_host_digest() {
+            return Err(ContinuationError::HostDigestMismatch(index));
+        }
+        let cycles = chunk.cycles()?;
+        if cycles != chunk.length as u64 {
+            return Err(ContinuationError::LengthMismatch(index));
+        }
+        if let Some(max_cycles) = self.max_cycles {
+            let total = self.cycles().checked_add(cycles);
+            if total.map_or(true, |total| total > max_cycles) {
+                return Err(ContinuationError::CycleLimitExceeded(index));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies the proofs of all the chunks, for the constraints of the
+    /// instructions `constraints` and the ones of [chunk_constraints], and
+    /// that they commit to the public input and output of the chunks. The
+    /// proofs of the domains of the Keccak coprocessor `keccak_proofs` are
+    /// verified for the constraints `keccak_constraints`, e.g. the ones of
+    /// [keccak::get_constraints], and the bus connecting them to the chunks is
+    /// checked to be balanced.
+    ///
+    /// # Errors
+    ///
+    /// Will give error with the index of the first chunk or Keccak domain
+    /// whose proof is not valid, or if the bus is not balanced.
+    pub fn verify<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+    >(
+        &self,
+        domain: EvaluationDomains<G::ScalarField>,
+        srs: &SRS<G>,
+        constraints: &[E<G::ScalarField>],
+        keccak_constraints: &[E<G::ScalarField>],
+        keccak_proofs: &[KeccakProof<G>],
+    ) -> Result<(), ContinuationError>
+    where
+        G::BaseField: PrimeField,
+    {
+        let domain_size = domain.d1.size as usize;
+        let mips_bus = keccak::mips_bus::<G::ScalarField>();
+        let coprocessor_bus = keccak::coprocessor_bus::<G::ScalarField>();
+        let commitments: Vec<Vec<PolyComm<G>>> = self
+            .chunks
+            .iter()
+            .map(|chunk| mips_bus.commitments_in_proof(&chunk.proof))
+            .chain(
+                keccak_proofs
+                    .iter()
+                    .map(|proof| coprocessor_bus.commitments_in_proof(proof)),
+            )
+            .collect();
+        let challenges = BusChallenges::sample::<G, EFqSponge>(&commitments);
+
+        let mut sums = Vec::with_capacity(self.chunks.len() + keccak_proofs.len());
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let constraints =
+                match chunk_constraints(constraints, &challenges, chunk.bus_sum, domain_size) {
+                    Ok(constraints) => constraints,
+                    Err(_) => return Err(ContinuationError::InvalidProof(index)),
+                };
+            if chunk.proof.public != chunk.public_values()
+                || verifier::verify::<G, EFqSponge, EFrSponge>(
+                    domain,
+                    srs,
+                    &constraints,
+                    &chunk.proof,
+                )
+                .is_err()
+            {
+                return Err(ContinuationError::InvalidProof(index));
+            }
+            sums.push(chunk.bus_sum);
+        }
+        for (index, proof) in keccak_proofs.iter().enumerate() {
+            keccak::verify::<G, EFqSponge, EFrSponge>(
+                domain,
+                srs,
+                keccak_constraints,
+                &challenges,
+                proof,
+            )
+            .map_err(|_| ContinuationError::InvalidKeccakProof(index))?;
+            sums.push(proof.public[0]);
+        }
+        check_balance(&sums).map_err(|_| ContinuationError::UnbalancedBus)
+    }
+}
@@ -0,0 +1,321 @@
+//! Lowering the degree of the constraints given to the pickles prover.
+//!
+//! The prover evaluates the constraints over a domain of size 8 times the one
+//! of the trace, so their degree is bounded, see
+//! [MAXIMUM_DEGREE_CONSTRAINTS](super::MAXIMUM_DEGREE_CONSTRAINTS). Instead of
+//! rewriting by hand the constraints of an instruction above this degree,
+//! [split_constraints] lowers the degree of the constraints to a target
+//! degree: the factors of a product whose degree is too high are replaced by
+//! intermediate columns, constrained to be equal to the factors. The columns
+//! must be scratch columns not used by the trace, and their values are written
+//! in the trace with [SplitConstraints::fill].
+//!
+//! The expressions replaced by intermediate columns can only refer to the
+//! columns of the trace and to literal constants, as the columns are computed
+//! before the challenges are sampled.
+
+use ark_ff::Field;
+use kimchi::{
+    circuits::{
+        expr::{ConstantExprInner, ConstantTerm, ExprInner, Operations, Variable},
+        gate::CurrOrNext,
+    },
+    curve::KimchiCurve,
+};
+use kimchi_msm::columns::Column;
+use thiserror::Error;
+
+use super::proof::ProofInputs;
+use crate::E;
+
+/// Errors that can arise when lowering the degree of the constraints
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SplitError {
+    #[error("the constraints can not be lowered to degree {0}, the minimum is 2")]
+    MaximumDegreeTooLow(u64),
+    #[error("not enough intermediate columns to lower the constraints to degree {0}")]
+    NotEnoughColumns(u64),
+    #[error("an expression of degree {0} can not be replaced by an intermediate column, as it does not only refer to the columns of the trace and to constants")]
+    UnsupportedExpression(u64),
+    #[error("the intermediate column {0:?} is not a scratch column of the trace")]
+    NotAScratchColumn(Column),
+}
+
+/// Constraints of degree at most a target degree, see [split_constraints]
+pub struct SplitConstraints<F: Field> {
+    /// The lowered constraints, followed by the constraints of the
+    /// intermediate columns
+    pub constraints: Vec<E<F>>,
+    /// The intermediate columns, with the expressions they are equal to. An
+    /// expression only refers to the intermediate columns before its own.
+    pub intermediate_columns: Vec<(Column, E<F>)>,
+}
+
+/// Returns the constraints `constraints` simplified, see
+/// [simplify](kimchi::circuits::expr::Operations::simplify), and lowered to
+/// degree `max_degree`, using the intermediate columns `columns`.
+///
+/// # Errors
+///
+/// Will give error if `max_degree` is lower than 2, if there are not enough
+/// intermediate columns, or if a factor that must be replaced by an
+/// intermediate column can not be computed from the trace.
+pub fn split_constraints<F: Field>(
+    constraints: &[E<F>],
+    max_degree: u64,
+    columns: impl IntoIterator<Item = Column>,
+) -> Result<SplitConstraints<F>, SplitError> {
+    if max_degree < 2 {
+        return Err(SplitError::MaximumDegreeTooLow(max_degree));
+    }
+    let mut splitter = Splitter {
+        max_degree,
+        columns: columns.into_iter(),
+        intermediate_columns: vec![],
+    };
+    let mut lowered = constraints
+        .iter()
+        .map(|constraint| splitter.lower(&constraint.simplify()))
+        .collect::<Result<Vec<_>, _>>()?;
+    lowered.extend(
+        splitter
+            .intermediate_columns
+            .iter()
+            .map(|(column, expr)| E::cell(*column, CurrOrNext::Curr) - expr.clone()),
+    );
+    Ok(SplitConstraints {
+        constraints: lowered,
+        intermediate_columns: splitter.intermediate_columns,
+    })
+}
+
+struct Splitter<F: Field, I: Iterator<Item = Column>> {
+    max_degree: u64,
+    columns: I,
+    intermediate_columns: Vec<(Column, E<F>)>,
+}
+
+impl<F: Field, I: Iterator<Item = Column>> Splitter<F, I> {
+    /// Returns `expr` with a degree of at most `max_degree`
+    fn lower(&mut self, expr: &E<F>) -> Result<E<F>, SplitError> {
+        match expr {
+            Operations::Atom(_) => Ok(expr.clone()),
+            Operations::Add(x, y) => Ok(self.lower(x)? + self.lower(y)?),
+            Operations::Sub(x, y) => Ok(self.lower(x)? - self.lower(y)?),
+            Operations::Double(x) => Ok(Operations::Double(Box::new(self.lower(x)?))),
+            Operations::Cache(_, x) => self.lower(x),
+            Operations::IfFeature(feature, x, y) => Ok(Operations::IfFeature(
+                *feature,
+                Box::new(self.lower(x)?),
+                Box::new(self.lower(y)?),
+            )),
+            Operations::Mul(x, y) => {
+                let (x, y) = (self.lower(x)?, self.lower(y)?);
+                self.lower_product(x, y)
+            }
+            Operations::Square(x) => {
+                let x = self.lower(x)?;
+                self.lower_product(x.clone(), x)
+            }
+            Operations::Pow(_, 0) => Ok(E::one()),
+            Operations::Pow(x, n) => {
+                let x = self.lower(x)?;
+                (1..*n).try_fold(x.clone(), |acc, _| self.lower_product(acc, x.clone()))
+            }
+        }
+    }
+
+    /// Returns the product of `x` and `y`, both of degree at most
+    /// `max_degree`, with a degree of at most `max_degree`
+    fn lower_product(&mut self, x: E<F>, y: E<F>) -> Result<E<F>, SplitError> {
+        let (mut x, mut y) = (x, y);
+        // The factor of highest degree is replaced first
+        if degree(&x) < degree(&y) {
+            std::mem::swap(&mut x, &mut y);
+        }
+        if degree(&x) + degree(&y) > self.max_degree {
+            x = self.intermediate_column(x)?;
+        }
+        if degree(&x) + degree(&y) > self.max_degree {
+            y = self.intermediate_column(y)?;
+        }
+        Ok(x * y)
+    }
+
+    /// Returns an intermediate column equal to `expr`
+    fn intermediate_column(&mut self, expr: E<F>) -> Result<E<F>, SplitError> {
+        if !is_computable(&expr) {
+            return Err(SplitError::UnsupportedExpression(degree(&expr)));
+        }
+        let column = match self
+            .intermediate_columns
+            .iter()
+            .find(|(_, intermediate)| *intermediate == expr)
+        {
+            Some((column, _)) => *column,
+            None => match self.columns.next() {
+                Some(column) => {
+                    self.intermediate_columns.push((column, expr));
+                    column
+                }
+                None => return Err(SplitError::NotEnoughColumns(self.max_degree)),
+            },
+        };
+        Ok(E::cell(column, CurrOrNext::Curr))
+    }
+}
+
+fn degree<F: Field>(expr: &E<F>) -> u64 {
+    expr.degree(1, 0)
+}
+
+/// Checks that the atoms of `expr` satisfy `is_valid`
+fn all_atoms<T>(expr: &Operations<T>, is_valid: &dyn Fn(&T) -> bool) -> bool {
+    match expr {
+        Operations::Atom(x) => is_valid(x),
+        Operations::Add(x, y) | Operations::Mul(x, y) | Operations::Sub(x, y) => {
+            all_atoms(x, is_valid) && all_atoms(y, is_valid)
+        }
+        Operations::Double(x)
+        | Operations::Square(x)
+        | Operations::Pow(x, _)
+        | Operations::Cache(_, x) => all_atoms(x, is_valid),
+        Operations::IfFeature(_, _, _) => false,
+    }
+}
+
+/// Evaluates `expr`, the atoms being evaluated by `atom`
+fn evaluate<T, F: Field>(expr: &Operations<T>, atom: &dyn Fn(&T) -> F) -> F {
+    match expr {
+        Operations::Atom(x) => atom(x),
+        Operations::Add(x, y) => evaluate(x, atom) + evaluate(y, atom),
+        Operations::Mul(x, y) => evaluate(x, atom) * evaluate(y, atom),
+        Operations::Sub(x, y) => evaluate(x, atom) - evaluate(y, atom),
+        Operations::Double(x) => evaluate(x, atom).double(),
+        Operations::Square(x) => evaluate(x, atom).square(),
+        Operations::Pow(x, n) => evaluate(x, atom).pow([*n]),
+        Operations::Cache(_, x) => evaluate(x, atom),
+        Operations::IfFeature(_, _, _) => unreachable!("the expression is computable"),
+    }
+}
+
+/// Checks that `expr` only refers to the columns of the trace and to literal
+/// constants
+pub(crate) fn is_computable<F: Field>(expr: &E<F>) -> bool {
+    all_atoms(expr, &|atom| match atom {
+        ExprInner::Constant(c) => all_atoms(c, &|c| {
+            matches!(c, ConstantExprInner::Constant(ConstantTerm::Literal(_)))
+        }),
+        ExprInner::Cell(Variable { col, .. }) => {
+            matches!(col, Column::Relation(_) | Column::DynamicSelector(_))
+        }
+        ExprInner::VanishesOnZeroKnowledgeAndPreviousRows
+        | ExprInner::UnnormalizedLagrangeBasis(_) => false,
+    })
+}
+
+impl<F: Field> SplitConstraints<F> {
+    /// Writes the values of the intermediate columns in the trace `inputs`, for
+    /// a domain of size `domain_size`. The columns of the trace shorter than
+    /// the domain are padded with zeroes, as in the prover.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if an intermediate column is not a scratch column.
+    pub fn fill<G, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize>(
+        &self,
+        inputs: &mut ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+        domain_size: usize,
+    ) -> Result<(), SplitError>
+    where
+        G: KimchiCurve<ScalarField = F>,
+    {
+        for (column, _) in self.intermediate_columns.iter() {
+            match column {
+                Column::Relation(i) if *i < N_SCRATCH => (),
+                _ => return Err(SplitError::NotAScratchColumn(*column)),
+            }
+        }
+        let scratch_inverse = inverted_scratch(inputs);
+        for (column, expr) in self.intermediate_columns.iter() {
+            let values = evaluate_rows(expr, inputs, &scratch_inverse, domain_size);
+            match column {
+                Column::Relation(i) => inputs.evaluations.scratch[*i] = values,
+                _ => unreachable!("the column is a scratch column"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the values of the inverse scratch columns of the trace `inputs`,
+/// which the prover inverts before committing to them
+pub(crate) fn inverted_scratch<
+    G: KimchiCurve,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+>(
+    inputs: &ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+) -> Vec<Vec<G::ScalarField>> {
+    let mut scratch_inverse = inputs.evaluations.scratch_inverse.to_vec();
+    scratch_inverse
+        .iter_mut()
+        .for_each(|evals| ark_ff::batch_inversion(evals));
+    scratch_inverse
+}
+
+/// Returns the values of the computable expression `expr` on each row of the
+/// trace `inputs`, for a domain of size `domain_size`. `scratch_inverse` are
+/// the values of the inverse scratch columns, see [inverted_scratch]. The
+/// columns of the trace shorter than the domain are padded with zeroes, as in
+/// the prover, and the selector columns are expanded from the selector
+/// indices.
+pub(crate) fn evaluate_rows<G, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize>(
+    expr: &E<G::ScalarField>,
+    inputs: &ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+    scratch_inverse: &[Vec<G::ScalarField>],
+    domain_size: usize,
+) -> Vec<G::ScalarField>
+where
+    G: KimchiCurve,
+{
+    let evaluations = &inputs.evaluations;
+    (0..domain_size)
+        .map(|row| {
+            let cell = |variable: &Variable<Column>| {
+                let row = match variable.row {
+                    CurrOrNext::Curr => row,
+                    CurrOrNext::Next => (row + 1) % domain_size,
+                };
+                match variable.col {
+                    Column::Relation(i) if i < N_SCRATCH => {
+                        evaluations.scratch[i].get(row).copied()
+                    }
+                    Column::Relation(i) if i < N_SCRATCH + N_SCRATCH_INVERSE => {
+                        scratch_inverse[i - N_SCRATCH].get(row).copied()
+                    }
+                    Column::Relation(i) if i == N_SCRATCH + N_SCRATCH_INVERSE => {
+                        evaluations.instruction_counter.get(row).copied()
+                    }
+                    Column::Relation(i) if i == N_SCRATCH + N_SCRATCH_INVERSE + 1 => {
+                        evaluations.error.get(row).copied()
+                    }
+                    Column::DynamicSelector(i) => evaluations
+                        .selector
+                        .get(row)
+                        .map(|s| G::ScalarField::from(*s == G::ScalarField::from(i as u64))),
+                    _ => unreachable!("the expression is computable"),
+                }
+                .unwrap_or(G::ScalarField::zero())
+            };
+            evaluate(expr, &|atom| match atom {
+                ExprInner::Constant(c) => evaluate(c, &|c| match c {
+                    ConstantExprInner::Constant(ConstantTerm::Literal(x)) => *x,
+                    _ => unreachable!("the expression is computable"),
+                }),
+                ExprInner::Cell(variable) => cell(variable),
+                _ => unreachable!("the expression is computable"),
+            })
+        })
+        .collect()
+}
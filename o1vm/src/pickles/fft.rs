@@ -0,0 +1,48 @@
+//! The FFTs used by the pickles prover.
+//!
+//! Interpolating the columns of the execution trace, and evaluating them on
+//! the larger domain `d8` for the quotient polynomial, dominate the time of the
+//! prover for large traces. The prover does these operations with a
+//! [FftBackend], see [prove_with_backend](super::prover::prove_with_backend).
+//! The only backend provided, [CpuBackend], uses the FFTs of arkworks.
+//!
+//! FIXME: there is no GPU backend behind a feature yet, as the workspace does
+//! not depend on a GPU library such as ICICLE. Until then, a GPU backend can be
+//! implemented outside of this crate with the trait.
+//!
+//! A backend must compute the same polynomials and evaluations as the
+//! arkworks FFTs, as the proofs do not depend on the backend.
+
+use ark_ff::FftField;
+use ark_poly::{univariate::DensePolynomial, Evaluations, Radix2EvaluationDomain as D};
+
+/// The implementation of the FFTs of the prover
+pub trait FftBackend<F: FftField>: Sync {
+    /// Interpolates the evaluations `evals` over their domain, i.e. computes
+    /// the polynomial of degree less than the size of the domain with these
+    /// evaluations (inverse FFT).
+    fn interpolate(&self, evals: Evaluations<F, D<F>>) -> DensePolynomial<F>;
+
+    /// Evaluates the polynomial `poly` over the domain `domain` (FFT). The
+    /// polynomial must have a degree less than the size of the domain.
+    fn evaluate_over_domain(&self, poly: &DensePolynomial<F>, domain: D<F>)
+        -> Evaluations<F, D<F>>;
+}
+
+/// The FFTs of arkworks, run on the CPU
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuBackend;
+
+impl<F: FftField> FftBackend<F> for CpuBackend {
+    fn interpolate(&self, evals: Evaluations<F, D<F>>) -> DensePolynomial<F> {
+        evals.interpolate()
+    }
+
+    fn evaluate_over_domain(
+        &self,
+        poly: &DensePolynomial<F>,
+        domain: D<F>,
+    ) -> Evaluations<F, D<F>> {
+        poly.evaluate_over_domain_by_ref(domain)
+    }
+}
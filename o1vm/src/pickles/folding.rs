@@ -0,0 +1,566 @@
+//! Folding of the execution traces of consecutive chunks, as an alternative to
+//! proving each chunk separately, see [continuation](super::continuation).
+//!
+//! The columns of the trace of each chunk are committed to, giving a folding
+//! instance, see [Folder::instance_witness]. The instances and their witnesses
+//! are folded one after the other into an accumulator, a relaxed instance à la
+//! [Nova](https://eprint.iacr.org/2021/370.pdf), with the [folding] library,
+//! which reduces the constraints to degree 2 by adding columns to the witness:
+//! - the prover folds the witnesses with [Folder::fold_witnesses], sending a
+//!   [FoldingStep] to the verifier for each chunk;
+//! - the verifier folds the instances with [Folder::fold_instances];
+//! - the folded instance is decided once, at the end, against the accumulated
+//!   witness with [Folder::decide].
+//!
+//! FIXME: the pickles prover does not prove relaxed instances yet, so the
+//! decider is given the accumulated witness, and is linear in the size of the
+//! trace, instead of verifying a single pickles proof of the accumulator. The
+//! public values and the lookups of the chunks are not folded, and the
+//! constraints can not use [transition](super::transition).
+
+use std::{marker::PhantomData, ops::Index};
+
+use ark_ec::AffineRepr;
+use ark_ff::{FftField, Field, One, Zero};
+use ark_poly::{Evaluations, Radix2EvaluationDomain as D};
+use folding::{
+    checker::{Checker, ExtendedProvider},
+    expressions::FoldingCompatibleExpr,
+    instance_witness::{Foldable, RelaxableInstance, RelaxablePair},
+    Alphas, FoldingConfig, FoldingEnv, FoldingScheme, Instance, RelaxedInstance, RelaxedWitness,
+    Side, Witness,
+};
+use kimchi::{
+    circuits::{berkeley_columns::BerkeleyChallengeTerm, gate::CurrOrNext},
+    curve::KimchiCurve,
+};
+use kimchi_msm::columns::Column;
+use mina_poseidon::FqSponge;
+use poly_commitment::{commitment::absorb_commitment, ipa::SRS, PolyComm, SRS as _};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use strum::EnumCount;
+use strum_macros::EnumCount as EnumCountMacro;
+use thiserror::Error;
+
+use super::{
+    column_env::get_all_columns,
+    proof::{ProofInputs, WitnessColumns},
+};
+use crate::{
+    interpreters::mips::column::{N_MIPS_REL_COLS, N_MIPS_SEL_COLS},
+    E,
+};
+
+type Evals<F> = Evaluations<F, D<F>>;
+
+/// Errors that can arise when folding an instance or deciding an accumulator
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FoldingError {
+    #[error("the accumulator folded by the prover is not the one of the verifier")]
+    AccumulatorMismatch,
+    #[error("the instance folded by the prover is not the one of the verifier")]
+    InstanceMismatch,
+    #[error("the commitment to the column {0:?} does not match the accumulated witness")]
+    ColumnCommitmentMismatch(Column),
+    #[error("the accumulator does not have the {0} columns added by the quadraticization")]
+    MissingExtendedColumns(usize),
+    #[error("the commitment to the column {0} added by the quadraticization does not match the accumulated witness")]
+    ExtendedColumnCommitmentMismatch(usize),
+    #[error("the commitment to the error term does not match the accumulated witness")]
+    ErrorCommitmentMismatch,
+    #[error("the relaxed constraints are not satisfied at row {0}")]
+    ConstraintsNotSatisfied(usize),
+}
+
+/// The challenges of the constraints. α is not one of them, as the constraints
+/// are combined by the folding scheme.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, EnumCountMacro)]
+pub enum Challenge {
+    Beta,
+    Gamma,
+    JointCombiner,
+}
+
+impl From<BerkeleyChallengeTerm> for Challenge {
+    fn from(chal: BerkeleyChallengeTerm) -> Self {
+        match chal {
+            BerkeleyChallengeTerm::Beta => Challenge::Beta,
+            BerkeleyChallengeTerm::Gamma => Challenge::Gamma,
+            BerkeleyChallengeTerm::JointCombiner => Challenge::JointCombiner,
+            BerkeleyChallengeTerm::Alpha => panic!("Alpha not allowed in folding expressions"),
+        }
+    }
+}
+
+/// The commitments to the columns of the trace of a chunk, in the order of
+/// [get_all_columns], with the challenges of the constraints
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoldingInstance<G: KimchiCurve> {
+    pub commitments: Vec<G>,
+    pub challenges: [G::ScalarField; Challenge::COUNT],
+    pub alphas: Alphas<G::ScalarField>,
+    /// The blinder of the commitments to the columns
+    pub blinder: G::ScalarField,
+}
+
+impl<G: KimchiCurve> Foldable<G::ScalarField> for FoldingInstance<G> {
+    fn combine(a: Self, b: Self, challenge: G::ScalarField) -> Self {
+        FoldingInstance {
+            commitments: a
+                .commitments
+                .into_iter()
+                .zip(b.commitments)
+                .map(|(a, b)| (a + b.mul(challenge)).into())
+                .collect(),
+            challenges: std::array::from_fn(|i| a.challenges[i] + challenge * b.challenges[i]),
+            alphas: Alphas::combine(a.alphas, b.alphas, challenge),
+            blinder: a.blinder + challenge * b.blinder,
+        }
+    }
+}
+
+impl<G: KimchiCurve> Instance<G> for FoldingInstance<G> {
+    fn to_absorb(&self) -> (Vec<G::ScalarField>, Vec<G>) {
+        let mut scalars = self.challenges.to_vec();
+        scalars.extend(self.alphas.clone().powers());
+        (scalars, self.commitments.clone())
+    }
+
+    fn get_alphas(&self) -> &Alphas<G::ScalarField> {
+        &self.alphas
+    }
+
+    fn get_blinder(&self) -> G::ScalarField {
+        self.blinder
+    }
+}
+
+impl<G: KimchiCurve> Index<Challenge> for FoldingInstance<G> {
+    type Output = G::ScalarField;
+
+    fn index(&self, index: Challenge) -> &Self::Output {
+        &self.challenges[index as usize]
+    }
+}
+
+/// The columns of the trace of a chunk, in the order of [get_all_columns]
+#[derive(Clone, Debug)]
+pub struct FoldingWitness<F: FftField> {
+    pub columns: Vec<Evals<F>>,
+}
+
+impl<F: FftField> Foldable<F> for FoldingWitness<F> {
+    fn combine(a: Self, b: Self, challenge: F) -> Self {
+        let columns = a
+            .columns
+            .into_iter()
+            .zip(b.columns)
+            .map(|(mut a, b)| {
+                a.evals
+                    .iter_mut()
+                    .zip(b.evals)
+                    .for_each(|(a, b)| *a += challenge * b);
+                a
+            })
+            .collect();
+        FoldingWitness { columns }
+    }
+}
+
+impl<G: KimchiCurve> Witness<G> for FoldingWitness<G::ScalarField> {}
+
+impl<F: FftField> Index<Column> for FoldingWitness<F> {
+    type Output = Evals<F>;
+
+    fn index(&self, index: Column) -> &Self::Output {
+        match index {
+            Column::Relation(i) => &self.columns[i],
+            Column::DynamicSelector(i) => &self.columns[N_MIPS_REL_COLS + i],
+            _ => panic!("Invalid column type"),
+        }
+    }
+}
+
+// The selectors are columns of the constraints, the folding library does not
+// handle them
+impl<F: FftField> Index<()> for FoldingWitness<F> {
+    type Output = Evals<F>;
+
+    fn index(&self, _index: ()) -> &Self::Output {
+        unreachable!("The selectors are not handled by the folding library")
+    }
+}
+
+pub struct FoldingEnvironment<G: KimchiCurve> {
+    /// Commitments to the witness columns, for both sides
+    pub instances: [FoldingInstance<G>; 2],
+    /// Corresponds to the evaluations at ω, for both sides
+    pub curr_witnesses: [FoldingWitness<G::ScalarField>; 2],
+    /// Corresponds to the evaluations at ζω, for both sides
+    /// This is curr_witness but left shifted by 1
+    pub next_witnesses: [FoldingWitness<G::ScalarField>; 2],
+}
+
+impl<G: KimchiCurve>
+    FoldingEnv<
+        G::ScalarField,
+        FoldingInstance<G>,
+        FoldingWitness<G::ScalarField>,
+        Column,
+        Challenge,
+        (),
+    > for FoldingEnvironment<G>
+{
+    type Structure = ();
+
+    fn new(
+        _structure: &Self::Structure,
+        instances: [&FoldingInstance<G>; 2],
+        witnesses: [&FoldingWitness<G::ScalarField>; 2],
+    ) -> Self {
+        let curr_witnesses = [witnesses[0].clone(), witnesses[1].clone()];
+        let mut next_witnesses = curr_witnesses.clone();
+        for side in next_witnesses.iter_mut() {
+            for col in side.columns.iter_mut() {
+                col.evals.rotate_left(1);
+            }
+        }
+        FoldingEnvironment {
+            instances: [instances[0].clone(), instances[1].clone()],
+            curr_witnesses,
+            next_witnesses,
+        }
+    }
+
+    fn col(&self, col: Column, curr_or_next: CurrOrNext, side: Side) -> &[G::ScalarField] {
+        let wit = match curr_or_next {
+            CurrOrNext::Curr => &self.curr_witnesses[side as usize],
+            CurrOrNext::Next => &self.next_witnesses[side as usize],
+        };
+        &wit[col].evals
+    }
+
+    fn challenge(&self, challenge: Challenge, side: Side) -> G::ScalarField {
+        self.instances[side as usize][challenge]
+    }
+
+    fn selector(&self, _s: &(), _side: Side) -> &[G::ScalarField] {
+        unreachable!("The selectors are not handled by the folding library")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PicklesFoldingConfig<G>(PhantomData<G>);
+
+impl<G: KimchiCurve> FoldingConfig for PicklesFoldingConfig<G> {
+    type Column = Column;
+    type Selector = ();
+    type Challenge = Challenge;
+    type Curve = G;
+    type Srs = SRS<G>;
+    type Instance = FoldingInstance<G>;
+    type Witness = FoldingWitness<G::ScalarField>;
+    type Structure = ();
+    type Env = FoldingEnvironment<G>;
+}
+
+impl<G: KimchiCurve> Checker<PicklesFoldingConfig<G>>
+    for ExtendedProvider<PicklesFoldingConfig<G>>
+{
+}
+
+pub type RelaxedFoldingInstance<G> = RelaxedInstance<G, FoldingInstance<G>>;
+pub type RelaxedFoldingWitness<G> =
+    RelaxedWitness<G, FoldingWitness<<G as AffineRepr>::ScalarField>>;
+
+/// The instance and the witness accumulated by the prover
+pub struct Accumulator<G: KimchiCurve> {
+    pub instance: RelaxedFoldingInstance<G>,
+    pub witness: RelaxedFoldingWitness<G>,
+    /// The blinder of the commitment to the error term, which is not tracked
+    /// by the relaxed instance
+    pub error_blinder: G::ScalarField,
+}
+
+/// The messages of the prover when folding an instance into the accumulator:
+/// both instances, relaxed and extended with the commitments to the columns
+/// added by the quadraticization, and the commitments to the cross terms
+pub struct FoldingStep<G: KimchiCurve> {
+    pub left: RelaxedFoldingInstance<G>,
+    pub right: RelaxedFoldingInstance<G>,
+    pub cross_terms: [PolyComm<G>; 2],
+}
+
+/// Folds the traces of the chunks of an execution for the constraints given to
+/// [Folder::new]
+pub struct Folder<'a, G: KimchiCurve> {
+    scheme: FoldingScheme<'a, PicklesFoldingConfig<G>>,
+    /// The relaxed constraints, combined with the α's
+    relaxed_constraint: FoldingCompatibleExpr<PicklesFoldingConfig<G>>,
+    /// The number of constraints, including the ones added by the
+    /// quadraticization, each combined with an α
+    number_of_alphas: usize,
+}
+
+impl<'a, G: KimchiCurve> Folder<'a, G> {
+    pub fn new(
+        constraints: &[E<G::ScalarField>],
+        srs: &'a SRS<G>,
+        domain: D<G::ScalarField>,
+    ) -> Self {
+        let constraints: Vec<FoldingCompatibleExpr<PicklesFoldingConfig<G>>> = constraints
+            .iter()
+            .map(|c| FoldingCompatibleExpr::from(c.clone()))
+            .collect();
+        let number_of_constraints = constraints.len();
+        let (scheme, relaxed_constraint) = FoldingScheme::new(constraints, srs, domain, &());
+        let number_of_alphas = number_of_constraints + scheme.get_number_of_additional_columns();
+        Folder {
+            scheme,
+            relaxed_constraint,
+            number_of_alphas,
+        }
+    }
+
+    /// Returns the instance of the commitments `commitments` to the columns of
+    /// the trace of a chunk, absorbing them in `fq_sponge` to sample the
+    /// challenges. The verifier uses it to check the instances given by the
+    /// prover.
+    pub fn instance<EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        commitments: &[PolyComm<G>],
+        fq_sponge: &mut EFqSponge,
+    ) -> FoldingInstance<G> {
+        commitments
+            .iter()
+            .for_each(|comm| absorb_commitment(fq_sponge, comm));
+        let challenges = std::array::from_fn(|_| fq_sponge.challenge());
+        let alpha = fq_sponge.challenge();
+        FoldingInstance {
+            commitments: commitments
+                .iter()
+                .map(|comm| comm.get_first_chunk())
+                .collect(),
+            challenges,
+            alphas: Alphas::new_sized(alpha, self.number_of_alphas),
+            blinder: G::ScalarField::one(),
+        }
+    }
+
+    /// Commits to the columns of the trace of a chunk, given as the inputs of
+    /// the pickles prover, see [Folder::instance]. The public values and the
+    /// lookups are ignored.
+    pub fn instance_witness<EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        inputs: ProofInputs<G>,
+        fq_sponge: &mut EFqSponge,
+    ) -> (FoldingInstance<G>, FoldingWitness<G::ScalarField>) {
+        let domain = self.scheme.domain;
+        let WitnessColumns {
+            scratch,
+            scratch_inverse,
+            instruction_counter,
+            error,
+            selector,
+        } = inputs.evaluations;
+        // The columns are in the order of the pickles prover, with the inverses
+        // computed and the selectors expanded
+        let mut columns: Vec<Vec<G::ScalarField>> = scratch.into_iter().collect();
+        columns.extend(scratch_inverse.into_iter().map(|mut evals| {
+            ark_ff::batch_inversion(&mut evals);
+            evals
+        }));
+        columns.push(instruction_counter);
+        columns.push(error);
+        columns.extend((0..N_MIPS_SEL_COLS).map(|i| {
+            selector
+                .iter()
+                .map(|s| {
+                    if G::ScalarField::from(i as u64) == *s {
+                        G::ScalarField::one()
+                    } else {
+                        G::ScalarField::zero()
+                    }
+                })
+                .collect()
+        }));
+        assert_eq!(columns.len(), get_all_columns().len());
+        let columns: Vec<Evals<G::ScalarField>> = columns
+            .into_par_iter()
+            .map(|evals| Evaluations::from_vec_and_domain(evals, domain))
+            .collect();
+
+        let blinder = PolyComm::new(vec![G::ScalarField::one()]);
+        let commitments: Vec<PolyComm<G>> = columns
+            .par_iter()
+            .map(|evals| {
+                self.scheme
+                    .srs
+                    .commit_evaluations_custom(domain, evals, &blinder)
+                    .unwrap()
+                    .commitment
+            })
+            .collect();
+        let instance = self.instance(&commitments, fq_sponge);
+        (instance, FoldingWitness { columns })
+    }
+
+    /// Starts the accumulator with the instance and the witness of the first
+    /// chunk
+    pub fn accumulate(
+        &self,
+        instance: FoldingInstance<G>,
+        witness: FoldingWitness<G::ScalarField>,
+    ) -> Accumulator<G> {
+        let (instance, witness) = (instance, witness).relax(&self.scheme.zero_vec);
+        Accumulator {
+            instance,
+            witness,
+            // The error term is committed to as the point at infinity
+            error_blinder: G::ScalarField::zero(),
+        }
+    }
+
+    /// Folds the instance and the witness of the next chunk into the
+    /// accumulator `accumulator`. Returns the new accumulator, and the messages
+    /// to send to the verifier.
+    pub fn fold_witnesses<EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        accumulator: Accumulator<G>,
+        instance: FoldingInstance<G>,
+        witness: FoldingWitness<G::ScalarField>,
+        fq_sponge: &mut EFqSponge,
+    ) -> (Accumulator<G>, FoldingStep<G>) {
+        let Accumulator {
+            instance: accumulated_instance,
+            witness: accumulated_witness,
+            error_blinder,
+        } = accumulator;
+        let output = self.scheme.fold_instance_witness_pair(
+            (accumulated_instance, accumulated_witness),
+            (instance, witness),
+            fq_sponge,
+        );
+        // The challenge is recovered from u, which is one for the new instance
+        let left = output.relaxed_extended_left_instance;
+        let right = output.relaxed_extended_right_instance;
+        let challenge = (output.folded_instance.u - left.u) / right.u;
+        // The cross terms are committed to with a blinder of one, and the error
+        // term of the new instance is zero:
+        // E <- E1 - c T1 - c^2 T2
+        let error_blinder = error_blinder - challenge - challenge.square();
+        let accumulator = Accumulator {
+            instance: output.folded_instance,
+            witness: output.folded_witness,
+            error_blinder,
+        };
+        let step = FoldingStep {
+            left,
+            right,
+            cross_terms: [output.t_0, output.t_1],
+        };
+        (accumulator, step)
+    }
+
+    /// Folds the instance `instance` of the next chunk into the accumulator
+    /// `accumulator` of the verifier, given the messages `step` of the prover.
+    /// The accumulator of the first chunk is its instance, relaxed with
+    /// [RelaxableInstance::relax].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the instances folded by the prover are not the ones
+    /// of the verifier, up to the commitments to the columns added by the
+    /// quadraticization.
+    pub fn fold_instances<EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
+        &self,
+        accumulator: &RelaxedFoldingInstance<G>,
+        instance: &FoldingInstance<G>,
+        step: FoldingStep<G>,
+        fq_sponge: &mut EFqSponge,
+    ) -> Result<RelaxedFoldingInstance<G>, FoldingError> {
+        let same_instance = |expected: &RelaxedFoldingInstance<G>,
+                             given: &RelaxedFoldingInstance<G>| {
+            given.extended_instance.instance == expected.extended_instance.instance
+                && given.u == expected.u
+                && given.error_commitment == expected.error_commitment
+                && given
+                    .extended_instance
+                    .extended
+                    .starts_with(&expected.extended_instance.extended)
+        };
+        if !same_instance(accumulator, &step.left) {
+            return Err(FoldingError::AccumulatorMismatch);
+        }
+        if !same_instance(&instance.clone().relax(), &step.right) {
+            return Err(FoldingError::InstanceMismatch);
+        }
+        let [t_0, t_1] = step.cross_terms;
+        Ok(self
+            .scheme
+            .verify_fold(step.left, step.right, t_0, t_1, fq_sponge))
+    }
+
+    /// Decides the instance `instance` folded by the verifier: checks that it
+    /// commits to the witness of the accumulator `accumulator` of the prover,
+    /// and that the witness satisfies the relaxed constraints for the relaxed
+    /// instance, i.e. with its scalar `u` and its error term.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a commitment of the instance does not match the
+    /// witness, or if the relaxed constraints are not satisfied.
+    pub fn decide(
+        &self,
+        instance: &RelaxedFoldingInstance<G>,
+        accumulator: &Accumulator<G>,
+    ) -> Result<(), FoldingError> {
+        let domain = self.scheme.domain;
+        let srs = self.scheme.srs;
+        let witness = &accumulator.witness;
+        let commit = |evals: &Evals<G::ScalarField>, blinder: G::ScalarField| {
+            srs.commit_evaluations_custom(domain, evals, &PolyComm::new(vec![blinder]))
+                .unwrap()
+                .commitment
+        };
+
+        let blinder = instance.extended_instance.instance.blinder;
+        for (i, column) in get_all_columns().into_iter().enumerate() {
+            let commitment = commit(&witness.extended_witness.witness[column], blinder);
+            if instance.extended_instance.instance.commitments.get(i)
+                != Some(&commitment.get_first_chunk())
+            {
+                return Err(FoldingError::ColumnCommitmentMismatch(column));
+            }
+        }
+
+        let additional_columns = self.scheme.get_number_of_additional_columns();
+        if instance.extended_instance.extended.len() != additional_columns
+            || witness.extended_witness.extended.len() != additional_columns
+        {
+            return Err(FoldingError::MissingExtendedColumns(additional_columns));
+        }
+        for (i, (evals, commitment)) in witness
+            .extended_witness
+            .extended
+            .values()
+            .zip(instance.extended_instance.extended.iter())
+            .enumerate()
+        {
+            if commit(evals, blinder) != *commitment {
+                return Err(FoldingError::ExtendedColumnCommitmentMismatch(i));
+            }
+        }
+
+        if commit(&witness.error_vec, accumulator.error_blinder) != instance.error_commitment {
+            return Err(FoldingError::ErrorCommitmentMismatch);
+        }
+
+        let provider = ExtendedProvider::new(instance.clone(), witness.clone());
+        let evaluations = provider.check_rec(self.relaxed_constraint.clone(), domain);
+        match evaluations.iter().position(|x| !x.is_zero()) {
+            Some(row) => Err(FoldingError::ConstraintsNotSatisfied(row)),
+            None => Ok(()),
+        }
+    }
+}
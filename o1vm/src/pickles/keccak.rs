@@ -0,0 +1,436 @@
+//! Proving the Keccak hashes requested by the program with a dedicated
+//! circuit, instead of executing Keccak step by step in the MIPS trace.
+//!
+//! When the program finishes to read the preimage of a Keccak256 key, the MIPS
+//! witness environment starts a Keccak environment for the hash, see
+//! [Env::keccak_env](crate::interpreters::mips::witness::Env::keccak_env). The
+//! [KeccakCoprocessor] takes it and executes all the steps of the hash, each
+//! step being a row of its own trace, with the layout of the Keccak
+//! interpreter: [N_ZKVM_KECCAK_REL_COLS] relation columns followed by the
+//! [KECCAK_BUS_COLUMNS] columns of the bus, no inverse scratch columns and one
+//! selector per kind of step. The trace of the coprocessor is split into
+//! domains, and proven independently of the MIPS trace with the pickles
+//! prover.
+//!
+//! The hashes of the coprocessor are connected to the preimages read by the
+//! MIPS trace with the [bus](super::bus), on the channels of the lookups of
+//! the interpreters:
+//! - on [SyscallLookup], the MIPS trace sends each byte of the preimage with
+//!   the index of its hash and its position, and receives the key at the end
+//!   of the preimage, see [mips_bus]. The coprocessor receives the bytes it
+//!   absorbs, but the padding, and sends the hash when it squeezes, see
+//!   [coprocessor_bus];
+//! - on [KeccakStepLookup], each step of the coprocessor sends its output to
+//!   the next one.
+//!
+//! The rows padding the last domain of the coprocessor send nothing: the
+//! instruction counter column, unused by the Keccak interpreter, flags the
+//! rows of the hashes, see [KeccakCoprocessor::prove].
+//!
+//! The MIPS trace only sends the preimages whose key is a Keccak256 key, as
+//! flagged in the trace, and the flag, the bytes and the key are not yet bound
+//! to the registers and to the memory of the program.
+
+use ark_ff::{Field, One, PrimeField, Zero};
+use kimchi::{
+    circuits::{
+        domains::EvaluationDomains, gate::CurrOrNext, polynomials::keccak::constants::RATE_IN_BYTES,
+    },
+    curve::KimchiCurve,
+    plonk_sponge::FrSponge,
+};
+use kimchi_msm::columns::{Column, ColumnIndexer};
+use mina_poseidon::FqSponge;
+use poly_commitment::{ipa::SRS, PolyComm};
+use rand::{CryptoRng, RngCore};
+use strum::IntoEnumIterator;
+
+use super::{
+    bus::{Bus, BusChallenges, BusError, Interaction},
+    proof::{Proof, ProofInputs},
+    prover::{prove_with_sizes, ProverError},
+    verifier::{verify_with_sizes, VerifyError},
+};
+use crate::{
+    interpreters::{
+        keccak::{
+            column::{N_ZKVM_KECCAK_REL_COLS, N_ZKVM_KECCAK_SEL_COLS},
+            environment::KeccakEnv,
+            Steps,
+        },
+        mips::{
+            column::{MIPS_BUS_COLUMNS, MIPS_BUS_OFF},
+            constraints as mips_constraints, interpreter,
+            witness::Env as WEnv,
+            Instruction, RTypeInstruction,
+        },
+    },
+    lookups::{
+        Lookup,
+        LookupTableIDs::{self, KeccakStepLookup, SyscallLookup},
+    },
+    preimage_oracle::PreImageOracleT,
+    ramlookup::LookupMode,
+    E,
+};
+
+/// The number of columns of the bus in the trace of the coprocessor, see
+/// [coprocessor_bus]: one per byte of a block of the preimage, one for the
+/// hash, one for the input and one for the output of a step, and one for the
+/// running sum
+pub const KECCAK_BUS_COLUMNS: usize = RATE_IN_BYTES + 4;
+
+/// The number of scratch columns of the trace of the coprocessor
+pub const N_KECCAK_SCRATCH: usize = N_ZKVM_KECCAK_REL_COLS + KECCAK_BUS_COLUMNS;
+
+/// The column flagging the rows of the hashes, as opposed to the rows padding
+/// the last domain: the instruction counter column, unused by the Keccak
+/// interpreter
+const IS_HASH_ROW: Column = Column::Relation(N_KECCAK_SCRATCH);
+
+/// The trace of the Keccak coprocessor
+pub type KeccakProofInputs<G> = ProofInputs<G, N_KECCAK_SCRATCH, 0>;
+
+/// The proof of a domain of the trace of the Keccak coprocessor. Its only
+/// public value is the sum of the domain on the bus, see [Bus::fill].
+pub type KeccakProof<G> = Proof<G, N_KECCAK_SCRATCH, 0, N_ZKVM_KECCAK_SEL_COLS>;
+
+/// Returns the constraints of the Keccak steps, multiplied by their selector,
+/// followed by the constraints of the selectors and of the flag of the rows of
+/// the hashes.
+pub fn get_constraints<F: Field>() -> Vec<E<F>> {
+    let steps: Vec<Steps> = Steps::iter().flat_map(|step| step.into_iter()).collect();
+    let mut constraints = steps.iter().fold(vec![], |mut acc, step| {
+        let selector = E::cell(step.to_column(), CurrOrNext::Curr);
+        acc.extend(
+            KeccakEnv::<F>::constraints_of(*step)
+                .into_iter()
+                .map(|c| selector.clone() * c),
+        );
+        acc
+    });
+    let selectors: Vec<E<F>> = steps
+        .iter()
+        .map(|step| E::cell(step.to_column(), CurrOrNext::Curr))
+        .collect();
+    constraints.extend(
+        selectors
+            .iter()
+            .map(|selector| (selector.clone() - E::one()) * selector.clone()),
+    );
+    constraints.push(
+        selectors
+            .into_iter()
+            .fold(E::one(), |acc, selector| acc - selector),
+    );
+    let is_hash_row = E::cell(IS_HASH_ROW, CurrOrNext::Curr);
+    constraints.push((is_hash_row.clone() - E::one()) * is_hash_row);
+    constraints
+}
+
+/// Returns the messages of the MIPS trace on the bus: the bytes of the
+/// preimages of Keccak256 keys, sent on [SyscallLookup] with the index of
+/// their hash and their position, and the keys, received at the end of the
+/// preimages. They are the lookups of
+/// [SyscallReadPreimage](RTypeInstruction::SyscallReadPreimage) on the
+/// channel, enabled by its selector.
+pub fn mips_bus<F: Field>() -> Bus<F, LookupTableIDs> {
+    let mut env = mips_constraints::Env::<F>::default();
+    interpreter::interpret_instruction(
+        &mut env,
+        Instruction::RType(RTypeInstruction::SyscallReadPreimage),
+    );
+    let selector = env.get_selector();
+    bus_of_lookups(
+        env.get_lookups()
+            .into_iter()
+            .map(|lookup| (selector.clone(), lookup)),
+    )
+}
+
+/// The columns of [mips_bus] in the MIPS trace
+pub fn mips_bus_columns() -> Vec<Column> {
+    (MIPS_BUS_OFF..MIPS_BUS_OFF + MIPS_BUS_COLUMNS)
+        .map(Column::Relation)
+        .collect()
+}
+
+/// Returns the messages of the trace of the coprocessor on the bus: the
+/// lookups of the Keccak steps on [SyscallLookup] and [KeccakStepLookup],
+/// enabled by the selector of the step on the rows of the hashes. The steps
+/// sharing a selector, i.e. the rounds, are taken once, and the messages of
+/// different steps with the same values are merged.
+pub fn coprocessor_bus<F: Field>() -> Bus<F, LookupTableIDs> {
+    let is_hash_row = E::cell(IS_HASH_ROW, CurrOrNext::Curr);
+    let mut selectors = vec![];
+    let steps: Vec<Steps> = Steps::iter()
+        .flat_map(|step| step.into_iter())
+        .filter(|step| {
+            let selector = step.to_column();
+            let is_new = !selectors.contains(&selector);
+            selectors.push(selector);
+            is_new
+        })
+        .collect();
+    bus_of_lookups(steps.into_iter().flat_map(|step| {
+        let selector = E::cell(step.to_column(), CurrOrNext::Curr) * is_hash_row.clone();
+        KeccakEnv::<F>::lookups_of(step)
+            .into_iter()
+            .map(move |lookup| (selector.clone(), lookup))
+    }))
+}
+
+/// The columns of [coprocessor_bus] in the trace of the coprocessor, after the
+/// columns of the Keccak interpreter
+pub fn coprocessor_bus_columns() -> Vec<Column> {
+    (N_ZKVM_KECCAK_REL_COLS..N_KECCAK_SCRATCH)
+        .map(Column::Relation)
+        .collect()
+}
+
+/// Builds the bus of the lookups on [SyscallLookup] and [KeccakStepLookup],
+/// each one being enabled by its selector: the written values are sent and
+/// the read ones are received. The messages with the same channel and values
+/// are merged, adding their multiplicities.
+fn bus_of_lookups<F: Field>(
+    lookups: impl IntoIterator<Item = (E<F>, Lookup<E<F>>)>,
+) -> Bus<F, LookupTableIDs> {
+    let mut bus = Bus::new();
+    for (selector, lookup) in lookups {
+        if !matches!(lookup.table_id, SyscallLookup | KeccakStepLookup) {
+            continue;
+        }
+        let multiplicity = match lookup.mode {
+            LookupMode::Write => selector * lookup.magnitude,
+            LookupMode::Read => E::zero() - selector * lookup.magnitude,
+        };
+        match bus.interactions.iter_mut().find(|interaction| {
+            interaction.channel == lookup.table_id && interaction.message == lookup.value
+        }) {
+            Some(interaction) => {
+                interaction.multiplicity = interaction.multiplicity.clone() + multiplicity
+            }
+            None => bus.interactions.push(Interaction {
+                channel: lookup.table_id,
+                multiplicity,
+                message: lookup.value,
+            }),
+        }
+    }
+    bus
+}
+
+/// The constraints of the coprocessor `constraints`, e.g. the ones of
+/// [get_constraints], followed by the constraints of [coprocessor_bus] for
+/// the sum `sum` of a domain of size `domain_size`
+fn with_bus_constraints<F: Field>(
+    constraints: &[E<F>],
+    challenges: &BusChallenges<F>,
+    sum: F,
+    domain_size: usize,
+) -> Result<Vec<E<F>>, BusError> {
+    let bus = coprocessor_bus().constraints::<N_KECCAK_SCRATCH>(
+        challenges,
+        sum,
+        &coprocessor_bus_columns(),
+        domain_size,
+    )?;
+    Ok(constraints.iter().cloned().chain(bus).collect())
+}
+
+/// The Keccak hashes requested by the program, executed step by step
+pub struct KeccakCoprocessor<G: KimchiCurve> {
+    domain_size: usize,
+    /// The trace of the hashes, split into domains. Only the last one can be
+    /// incomplete.
+    pub chunks: Vec<KeccakProofInputs<G>>,
+    /// The number of hashes executed
+    pub hashes: usize,
+}
+
+impl<G: KimchiCurve> KeccakCoprocessor<G> {
+    pub fn new(domain_size: usize) -> Self {
+        KeccakCoprocessor {
+            domain_size,
+            chunks: vec![],
+            hashes: 0,
+        }
+    }
+
+    /// Executes the hash requested by the last instruction executed by the
+    /// MIPS witness environment, if any
+    pub fn take_request<PreImageOracle: PreImageOracleT>(
+        &mut self,
+        env: &mut WEnv<G::ScalarField, PreImageOracle>,
+    ) {
+        if let Some(mut keccak_env) = env.keccak_env.take() {
+            self.push_hash(&mut keccak_env);
+        }
+    }
+
+    /// Executes all the steps of the hash of the Keccak environment, adding a
+    /// row to the trace for each step
+    pub fn push_hash(&mut self, keccak_env: &mut KeccakEnv<G::ScalarField>) {
+        while keccak_env.step.is_some() {
+            let step = keccak_env.selector();
+            keccak_env.step();
+            self.push_row(step, &keccak_env.witness_env.witness.cols);
+        }
+        self.hashes += 1;
+    }
+
+    fn push_row(&mut self, step: Steps, row: &[G::ScalarField; N_ZKVM_KECCAK_REL_COLS]) {
+        let is_full = match self.chunks.last() {
+            Some(chunk) => chunk.evaluations.selector.len() == self.domain_size,
+            None => true,
+        };
+        if is_full {
+            self.chunks.push(ProofInputs::new(self.domain_size));
+        }
+        let evaluations = &mut self.chunks.last_mut().unwrap().evaluations;
+        // The columns of the bus are written when proving, see
+        // [KeccakCoprocessor::prove]
+        let zero = G::ScalarField::zero();
+        for (column, value) in evaluations
+            .scratch
+            .iter_mut()
+            .zip(row.iter().chain(std::iter::repeat(&zero)))
+        {
+            column.push(*value);
+        }
+        // The instruction counter column flags the rows of the hashes, and the
+        // error column is not used by the Keccak interpreter
+        evaluations.instruction_counter.push(G::ScalarField::one());
+        evaluations.error.push(zero);
+        // The selectors are located after the relation columns
+        evaluations.selector.push(G::ScalarField::from(
+            (usize::from(step) - N_ZKVM_KECCAK_REL_COLS) as u64,
+        ));
+    }
+
+    /// Pads the last domain with copies of its last row, which is sound as
+    /// all the constraints of the steps are on a single row. The copies are
+    /// not flagged as rows of the hashes, so that they send nothing on the
+    /// bus.
+    fn pad(&mut self) {
+        let domain_size = self.domain_size;
+        if let Some(inputs) = self.chunks.last_mut() {
+            let evaluations = &mut inputs.evaluations;
+            let pad_column = |column: &mut Vec<G::ScalarField>| {
+                let last = *column.last().unwrap();
+                column.resize(domain_size, last);
+            };
+            evaluations.scratch.iter_mut().for_each(pad_column);
+            evaluations
+                .instruction_counter
+                .resize(domain_size, G::ScalarField::zero());
+            pad_column(&mut evaluations.error);
+            pad_column(&mut evaluations.selector);
+        }
+    }
+
+    /// Returns the commitments to the columns referred to by the messages of
+    /// [coprocessor_bus] in each domain of the trace, to sample the challenges
+    /// of the bus, see [Bus::commitments]
+    pub fn bus_commitments(
+        &mut self,
+        domain: EvaluationDomains<G::ScalarField>,
+        srs: &SRS<G>,
+    ) -> Vec<Vec<PolyComm<G>>> {
+        self.pad();
+        let bus = coprocessor_bus();
+        self.chunks
+            .iter()
+            .map(|inputs| bus.commitments(domain, srs, inputs))
+            .collect()
+    }
+
+    /// Makes a proof of each domain of the trace, see
+    /// [prove](super::prover::prove), for the constraints `constraints` and the
+    /// ones of [coprocessor_bus]. The columns of the bus are filled for the
+    /// challenges `challenges`, and the sum of each domain is its public
+    /// value. The last domain is padded, see [KeccakCoprocessor::pad].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the columns of the bus can not be filled, or if a
+    /// proof can not be made.
+    pub fn prove<
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG,
+    >(
+        mut self,
+        domain: EvaluationDomains<G::ScalarField>,
+        srs: &SRS<G>,
+        constraints: &[E<G::ScalarField>],
+        challenges: &BusChallenges<G::ScalarField>,
+        rng: &mut RNG,
+    ) -> Result<Vec<KeccakProof<G>>, ProverError>
+    where
+        G::BaseField: PrimeField,
+        RNG: RngCore + CryptoRng,
+    {
+        self.pad();
+        let domain_size = self.domain_size;
+        let bus = coprocessor_bus();
+        let columns = coprocessor_bus_columns();
+        self.chunks
+            .into_iter()
+            .map(|mut inputs| {
+                let sum = bus.fill(challenges, &mut inputs, &columns, domain_size)?;
+                inputs.public = vec![sum];
+                let constraints = with_bus_constraints(constraints, challenges, sum, domain_size)?;
+                prove_with_sizes::<
+                    G,
+                    EFqSponge,
+                    EFrSponge,
+                    RNG,
+                    N_KECCAK_SCRATCH,
+                    0,
+                    N_ZKVM_KECCAK_SEL_COLS,
+                >(domain, srs, inputs, &constraints, &[], rng)
+            })
+            .collect()
+    }
+}
+
+/// Verify the proof of a domain of the trace of the Keccak coprocessor, see
+/// [verify](super::verifier::verify), for the constraints `constraints` and
+/// the ones of [coprocessor_bus] with the challenges `challenges` and the sum
+/// given by the proof.
+pub fn verify<
+    G: KimchiCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    constraints: &[E<G::ScalarField>],
+    challenges: &BusChallenges<G::ScalarField>,
+    proof: &KeccakProof<G>,
+) -> Result<(), VerifyError>
+where
+    G::BaseField: PrimeField,
+{
+    let sum = bus_sum(proof)?;
+    let constraints = with_bus_constraints(constraints, challenges, sum, domain.d1.size as usize)?;
+    verify_with_sizes::<G, EFqSponge, EFrSponge, N_KECCAK_SCRATCH, 0, N_ZKVM_KECCAK_SEL_COLS>(
+        domain,
+        srs,
+        &constraints,
+        proof,
+    )
+}
+
+/// The sum on the bus of the domain of the proof, its only public value
+///
+/// # Errors
+///
+/// Will give error if the proof does not have a single public value.
+pub fn bus_sum<G: KimchiCurve>(proof: &KeccakProof<G>) -> Result<G::ScalarField, VerifyError> {
+    match proof.public.as_slice() {
+        [sum] => Ok(*sum),
+        _ => Err(VerifyError::MissingBusSum),
+    }
+}
@@ -0,0 +1,276 @@
+//! Lookups in the fixed tables of the o1vm, e.g. to constrain the byte
+//! decompositions and the 16 bits range checks with the tables
+//! [ByteLookup](LookupTableIDs::ByteLookup) and
+//! [RangeCheck16Lookup](LookupTableIDs::RangeCheck16Lookup) instead of bit
+//! constraints.
+//!
+//! The argument is the logarithmic derivative lookup of [kimchi_msm::logup].
+//! Its polynomials are committed to after the columns of the execution trace,
+//! with blinders sampled by the prover, and opened with them. The values
+//! looked up in each table are given to the prover in
+//! [ProofInputs::lookups](super::proof::ProofInputs::lookups), and the lookups
+//! are enforced by the expressions returned by [constraints], which must be
+//! added to the constraints given to the prover and the verifier.
+//!
+//! Each value is looked up with a numerator, which is the multiplicity of the
+//! value at the row, e.g. the selector of the instruction looking it up. The
+//! values and the numerators must be expressions of degree at most one.
+//!
+//! Only the fixed tables are supported, and they must fit in the domain. The
+//! rows of the domain after the table are filled with its first entry, with a
+//! multiplicity of zero. The verifier computes the commitments to the tables
+//! with the Lagrange bases, so that the prover can not use other tables.
+//!
+//! The lookups of the MIPS instructions in the fixed tables are given by
+//! [MipsLookups]. The lookups in a table which does not fit in the domain, e.g.
+//! [RangeCheck16Lookup](LookupTableIDs::RangeCheck16Lookup) in a domain of
+//! less than 2^16 rows, are not enforced.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ark_ff::{PrimeField, Zero};
+use ark_poly::{Evaluations, Radix2EvaluationDomain as D};
+use kimchi::curve::KimchiCurve;
+use kimchi_msm::{
+    logup::{logup_witness_with_numerators, LogupArgument, LogupError, LogupWitness},
+    LookupTableID,
+};
+use poly_commitment::{ipa::SRS, PolyComm, SRS as _};
+use strum::IntoEnumIterator;
+
+use super::prover::ProverError;
+use crate::{
+    interpreters::mips::{constraints as mips_constraints, interpreter, Instruction},
+    lookups::{FixedLookupTables, Lookup, LookupTable, LookupTableIDs},
+    ramlookup::LookupMode,
+    E,
+};
+
+/// The values looked up in each table. For each lookup of the constraints, the
+/// numerator and the value looked up at each row of the domain.
+pub type LookupValues<F> = BTreeMap<LookupTableIDs, Vec<Vec<(F, Vec<F>)>>>;
+
+/// The lookups of each table, as the expressions of the numerator and of the
+/// value of each lookup
+pub type LookupReads<F> = BTreeMap<LookupTableIDs, Vec<(E<F>, Vec<E<F>>)>>;
+
+/// Returns the constraints of the lookups `reads`. The expressions must be of
+/// degree at most one, and given in the same order as the values in
+/// [LookupValues].
+pub fn constraints<F: PrimeField>(reads: &LookupReads<F>) -> Vec<E<F>> {
+    let mut argument = LogupArgument::new();
+    for (table_id, reads) in reads.iter() {
+        argument.add_table(*table_id);
+        for (numerator, value) in reads.iter() {
+            argument.add_lookup_with_numerator(*table_id, numerator.clone(), value.clone());
+        }
+    }
+    argument.constraints()
+}
+
+/// The lookups of the MIPS instructions in the fixed tables fitting in a
+/// domain. The lookups of all the instructions in a table are merged when
+/// they look up the same expression, their numerators being added, so that
+/// the instructions decoding the same fields share their lookups.
+pub struct MipsLookups<F: PrimeField> {
+    /// The lookups of each table, the numerator of a lookup being the sum of
+    /// the selectors of the instructions making it
+    pub reads: LookupReads<F>,
+    /// For each instruction, indexed by its selector, the table and the index
+    /// in [MipsLookups::reads] of each of its lookups, in the order of the
+    /// interpreter, or `None` if the lookup is not in the argument
+    slots: HashMap<usize, Vec<Option<(LookupTableIDs, usize)>>>,
+}
+
+impl<F: PrimeField> MipsLookups<F> {
+    /// Collects the lookups of the MIPS instructions in the fixed tables
+    /// fitting in a domain of size `domain_size`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an instruction writes to a fixed table, or reads from it
+    /// a value of degree higher than one or with a magnitude which is not a
+    /// constant.
+    pub fn new(domain_size: usize) -> Self {
+        let mut env = mips_constraints::Env::<F>::default();
+        let mut reads = LookupReads::new();
+        let mut slots = HashMap::new();
+        for instruction in Instruction::iter().flat_map(|instr| instr.into_iter()) {
+            interpreter::interpret_instruction(&mut env, instruction);
+            let selector = env.get_selector();
+            let instruction_slots = env
+                .get_lookups()
+                .into_iter()
+                .map(|lookup| {
+                    let table_id = lookup.table_id;
+                    if !table_id.is_fixed() || table_id.length() > domain_size {
+                        return None;
+                    }
+                    assert!(
+                        matches!(lookup.mode, LookupMode::Read)
+                            && lookup.magnitude.degree(1, 0) == 0
+                            && lookup.value.iter().all(|x| x.degree(1, 0) <= 1),
+                        "the lookup of {instruction:?} in the table {table_id:?} is not supported"
+                    );
+                    let numerator = selector.clone() * lookup.magnitude;
+                    let reads = reads.entry(table_id).or_default();
+                    let index = match reads.iter().position(|(_, value)| *value == lookup.value) {
+                        Some(index) => {
+                            reads[index].0 = reads[index].0.clone() + numerator;
+                            index
+                        }
+                        None => {
+                            reads.push((numerator, lookup.value));
+                            reads.len() - 1
+                        }
+                    };
+                    Some((table_id, index))
+                })
+                .collect();
+            slots.insert(usize::from(instruction), instruction_slots);
+            env.reset();
+        }
+        MipsLookups { reads, slots }
+    }
+
+    /// Returns the constraints of the lookups, see [constraints]
+    pub fn constraints(&self) -> Vec<E<F>> {
+        constraints(&self.reads)
+    }
+
+    /// Adds to `values` the numerators and the values of the lookups at the
+    /// row of the instruction of selector `selector`, whose lookups in the
+    /// order of the interpreter are `lookups`. The lookups of the other
+    /// instructions have a numerator of zero.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `lookups` are not the lookups of the instruction.
+    pub(crate) fn push_row(
+        &self,
+        values: &mut LookupValues<F>,
+        selector: usize,
+        lookups: &[Lookup<u64>],
+    ) {
+        let mut row: BTreeMap<LookupTableIDs, Vec<(F, Vec<F>)>> = self
+            .reads
+            .iter()
+            .map(|(table_id, reads)| {
+                let row = reads
+                    .iter()
+                    .map(|(_, value)| (F::zero(), vec![F::zero(); value.len()]))
+                    .collect();
+                (*table_id, row)
+            })
+            .collect();
+        // The row of an instruction which is not interpreted, e.g. when the
+        // execution is stopped, has no lookup
+        let slots = self.slots.get(&selector).map_or(&[][..], |slots| slots);
+        assert!(
+            lookups.is_empty() || lookups.len() == slots.len(),
+            "the lookups of the row are not the ones of the instruction {selector}"
+        );
+        for (lookup, slot) in lookups.iter().zip(slots.iter()) {
+            if let Some((table_id, index)) = slot {
+                let (numerator, value) = &mut row.get_mut(table_id).unwrap()[*index];
+                *numerator += F::from(lookup.magnitude);
+                *value = lookup.value.iter().map(|x| F::from(*x)).collect();
+            }
+        }
+        for (table_id, row) in row {
+            let columns = values
+                .entry(table_id)
+                .or_insert_with(|| vec![vec![]; row.len()]);
+            for (column, value) in columns.iter_mut().zip(row) {
+                column.push(value);
+            }
+        }
+    }
+}
+
+/// Returns the fixed table `table_id`, or `None` if the table is not fixed
+pub(crate) fn fixed_table<F: PrimeField>(table_id: LookupTableIDs) -> Option<LookupTable<F>> {
+    match table_id {
+        LookupTableIDs::PadLookup => Some(LookupTable::table_pad()),
+        LookupTableIDs::RoundConstantsLookup => Some(LookupTable::table_round_constants()),
+        LookupTableIDs::AtMost4Lookup => Some(LookupTable::table_at_most_4()),
+        LookupTableIDs::ByteLookup => Some(LookupTable::table_byte()),
+        LookupTableIDs::RangeCheck16Lookup => Some(LookupTable::table_range_check_16()),
+        LookupTableIDs::SparseLookup => Some(LookupTable::table_sparse()),
+        LookupTableIDs::ResetLookup => Some(LookupTable::table_reset()),
+        LookupTableIDs::MemoryLookup
+        | LookupTableIDs::RegisterLookup
+        | LookupTableIDs::SyscallLookup
+        | LookupTableIDs::KeccakStepLookup => None,
+    }
+}
+
+/// Returns the entries of the table at each row of a domain of size
+/// `domain_size`, or `None` if the table does not fit in the domain
+fn table_rows<F: PrimeField>(table: &LookupTable<F>, domain_size: usize) -> Option<Vec<Vec<F>>> {
+    if table.entries.len() > domain_size {
+        return None;
+    }
+    let mut rows = table.entries.clone();
+    rows.resize(domain_size, table.entries[0].clone());
+    Some(rows)
+}
+
+/// Builds the witness of the lookups of the values `values`, with their
+/// numerators, in the fixed table `table_id`, over a domain of size
+/// `domain_size`.
+///
+/// # Errors
+///
+/// Will give error if the table is not a fixed table fitting in the domain, if
+/// a lookup does not have a value for each row, or if a value with a non-zero
+/// numerator is not in the table.
+pub(crate) fn witness<F: PrimeField>(
+    table_id: LookupTableIDs,
+    values: Vec<Vec<(F, Vec<F>)>>,
+    domain_size: usize,
+) -> Result<LogupWitness<F, LookupTableIDs>, ProverError> {
+    let table = match fixed_table::<F>(table_id) {
+        Some(table) => table,
+        None => return Err(ProverError::UnsupportedLookupTable(table_id)),
+    };
+    let rows = match table_rows(&table, domain_size) {
+        Some(rows) => rows,
+        None => return Err(ProverError::UnsupportedLookupTable(table_id)),
+    };
+    // The table is the last column, with the opposite of the multiplicities
+    logup_witness_with_numerators(table_id, values, vec![rows], domain_size).map_err(
+        |err| match err {
+            LogupError::ValueNotInTable(_, row) => {
+                ProverError::LookupValueNotInTable(table_id, row)
+            }
+            _ => ProverError::LookupLengthMismatch(table_id),
+        },
+    )
+}
+
+/// Returns the commitment to the column of the fixed table `table_id`, the
+/// entries being combined with `joint_combiner`, or `None` if the table is not
+/// a fixed table fitting in the domain. It is the commitment computed by the
+/// prover in [kimchi_msm::logup::prover::Env::create].
+pub(crate) fn table_commitment<G: KimchiCurve>(
+    srs: &SRS<G>,
+    domain: D<G::ScalarField>,
+    table_id: LookupTableIDs,
+    joint_combiner: G::ScalarField,
+) -> Option<PolyComm<G>> {
+    let table = fixed_table::<G::ScalarField>(table_id)?;
+    let rows = table_rows(&table, domain.size())?;
+    let evals = rows
+        .iter()
+        .map(|entry| {
+            entry
+                .iter()
+                .rev()
+                .fold(G::ScalarField::zero(), |acc, x| acc * joint_combiner + x)
+        })
+        .collect();
+    Some(
+        srs.commit_evaluations_non_hiding(domain, &Evaluations::from_vec_and_domain(evals, domain)),
+    )
+}
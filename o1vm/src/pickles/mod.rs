@@ -3,18 +3,42 @@
 //! each instruction using the Pasta curves and the IPA PCS.
 //!
 //! A proof is generated for each set of N continuous instructions, where N is
-//! the size of the supported SRS. The proofs will then be aggregated using
-//! a modified version of pickles.
+//! the size of the supported SRS, see [continuation]. The proofs will then be
+//! aggregated using a modified version of pickles.
 //!
-//! You can run this flavor by using:
+//! You can run this flavor with the `o1vm` binary, see [crate::cli], e.g.
+//! with:
 //!
 //! ```bash
-//! O1VM_FLAVOR=pickles bash run-code.sh
+//! O1VM_COMMAND=prove bash run-code.sh
 //! ```
 
+use ark_ff::Field;
+use kimchi::circuits::expr::{ExprInner, RowOffset};
+use kimchi_msm::columns::Column;
+use strum::IntoEnumIterator;
+
+use crate::{
+    interpreters::mips::{
+        constraints as mips_constraints,
+        interpreter::{self, InterpreterEnv},
+        Instruction,
+    },
+    E,
+};
+
+pub mod bus;
 pub mod column_env;
+pub mod continuation;
+pub mod degree;
+pub mod fft;
+pub mod folding;
+pub mod keccak;
+pub mod lookup;
 pub mod proof;
 pub mod prover;
+pub mod riscv32im;
+pub mod sampling;
 pub mod verifier;
 
 /// Maximum degree of the constraints.
@@ -31,7 +55,58 @@ pub const DEGREE_QUOTIENT_POLYNOMIAL: u64 = 7;
 
 /// Total number of constraints for all instructions, including the constraints
 /// added for the selectors.
-pub const TOTAL_NUMBER_OF_CONSTRAINTS: usize = 464;
+pub const TOTAL_NUMBER_OF_CONSTRAINTS: usize = 477;
+
+/// The column the constraints refer to for the public values. The pickles
+/// flavor has no fixed selector, and the public column is the only column
+/// computed by the verifier, see [proof::public_rows].
+pub const PUBLIC_COLUMN: Column = Column::FixedSelector(0);
+
+/// Returns the constraint `constraint` enforced on the first row only
+pub fn on_first_row<F: Field>(constraint: E<F>) -> E<F> {
+    E::Atom(ExprInner::UnnormalizedLagrangeBasis(RowOffset {
+        zk_rows: false,
+        offset: 0,
+    })) * constraint
+}
+
+/// Returns the constraint `constraint` enforced on the last row only
+pub fn on_last_row<F: Field>(constraint: E<F>) -> E<F> {
+    E::Atom(ExprInner::UnnormalizedLagrangeBasis(RowOffset {
+        zk_rows: false,
+        offset: -1,
+    })) * constraint
+}
+
+/// Returns the constraint `constraint`, referring to the current and the next
+/// rows, enforced on all the rows but the last one. The next row of the last
+/// row is the first one, as the columns are interpolated over the domain, so
+/// the transitions between two rows must not be enforced on it.
+pub fn transition<F: Field>(constraint: E<F>) -> E<F> {
+    E::Atom(ExprInner::VanishesOnZeroKnowledgeAndPreviousRows) * constraint
+}
+
+/// Returns the constraints of all the MIPS instructions, multiplied by their
+/// selector, followed by the constraints of the selectors.
+pub fn get_mips_constraints<F: Field>() -> Vec<E<F>> {
+    let mut mips_con_env = mips_constraints::Env::<F>::default();
+    let mut constraints = Instruction::iter()
+        .flat_map(|instr_typ| instr_typ.into_iter())
+        .fold(vec![], |mut acc, instr| {
+            interpreter::interpret_instruction(&mut mips_con_env, instr);
+            let selector = mips_con_env.get_selector();
+            let constraints_with_selector: Vec<E<F>> = mips_con_env
+                .get_constraints()
+                .into_iter()
+                .map(|c| selector.clone() * c)
+                .collect();
+            acc.extend(constraints_with_selector);
+            mips_con_env.reset();
+            acc
+        });
+    constraints.extend(mips_con_env.get_selector_constraints());
+    constraints
+}
 
 #[cfg(test)]
 mod tests;
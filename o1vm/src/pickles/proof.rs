@@ -1,21 +1,114 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
 use kimchi::{curve::KimchiCurve, proof::PointEvaluations};
+use kimchi_msm::logup::LookupProof;
 use poly_commitment::{ipa::OpeningProof, PolyComm};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::serde_as;
+use thiserror::Error;
 
-use crate::interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE};
+use super::lookup::LookupValues;
+use crate::{
+    interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
+    lookups::LookupTableIDs,
+};
 
-pub struct WitnessColumns<G, S> {
-    pub scratch: [G; SCRATCH_SIZE],
-    pub scratch_inverse: [G; SCRATCH_SIZE_INVERSE],
+/// The columns of the execution trace. The number of scratch columns default
+/// to the ones of the MIPS interpreter.
+pub struct WitnessColumns<
+    G,
+    S,
+    const N_SCRATCH: usize = SCRATCH_SIZE,
+    const N_SCRATCH_INVERSE: usize = SCRATCH_SIZE_INVERSE,
+> {
+    pub scratch: [G; N_SCRATCH],
+    pub scratch_inverse: [G; N_SCRATCH_INVERSE],
     pub instruction_counter: G,
     pub error: G,
     pub selector: S,
 }
 
-pub struct ProofInputs<G: KimchiCurve> {
-    pub evaluations: WitnessColumns<Vec<G::ScalarField>, Vec<G::ScalarField>>,
+impl<G, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize, const N_SEL: usize>
+    WitnessColumns<G, [G; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>
+{
+    /// Returns the columns in the order of
+    /// [get_columns](super::column_env::get_columns)
+    pub(crate) fn into_vec(self) -> Vec<G> {
+        let mut columns = Vec::with_capacity(N_SCRATCH + N_SCRATCH_INVERSE + 2 + N_SEL);
+        columns.extend(self.scratch);
+        columns.extend(self.scratch_inverse);
+        columns.push(self.instruction_counter);
+        columns.push(self.error);
+        columns.extend(self.selector);
+        columns
+    }
+
+    /// Inverse of [WitnessColumns::into_vec], or `None` if the number of
+    /// columns is not the expected one
+    pub(crate) fn from_vec(columns: Vec<G>) -> Option<Self> {
+        if columns.len() != N_SCRATCH + N_SCRATCH_INVERSE + 2 + N_SEL {
+            return None;
+        }
+        let mut columns = columns.into_iter();
+        let mut next = || columns.next().unwrap();
+        Some(WitnessColumns {
+            scratch: std::array::from_fn(|_| next()),
+            scratch_inverse: std::array::from_fn(|_| next()),
+            instruction_counter: next(),
+            error: next(),
+            selector: std::array::from_fn(|_| next()),
+        })
+    }
+
+    /// Returns the references to the columns
+    pub(crate) fn as_ref(&self) -> WitnessColumns<&G, [&G; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE> {
+        WitnessColumns {
+            scratch: std::array::from_fn(|i| &self.scratch[i]),
+            scratch_inverse: std::array::from_fn(|i| &self.scratch_inverse[i]),
+            instruction_counter: &self.instruction_counter,
+            error: &self.error,
+            selector: std::array::from_fn(|i| &self.selector[i]),
+        }
+    }
+
+    /// Applies `f` to all the columns, in parallel
+    pub(crate) fn par_map<H: Send, F: Fn(G) -> H + Sync + Send>(
+        self,
+        f: F,
+    ) -> WitnessColumns<H, [H; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>
+    where
+        G: Send,
+    {
+        let columns = self.into_vec().into_par_iter().map(f).collect();
+        WitnessColumns::from_vec(columns).unwrap()
+    }
 }
 
-impl<G: KimchiCurve> ProofInputs<G> {
+pub struct ProofInputs<
+    G: KimchiCurve,
+    const N_SCRATCH: usize = SCRATCH_SIZE,
+    const N_SCRATCH_INVERSE: usize = SCRATCH_SIZE_INVERSE,
+> {
+    pub evaluations:
+        WitnessColumns<Vec<G::ScalarField>, Vec<G::ScalarField>, N_SCRATCH, N_SCRATCH_INVERSE>,
+    /// The public values of the execution, e.g. the initial and final state
+    /// roots, given in clear with the proof. They are the values at the
+    /// beginning of the trace followed by the values at its end, on the first
+    /// and the last rows of the public column, see [public_rows]. The other
+    /// rows of the public column are zero.
+    pub public: Vec<G::ScalarField>,
+    /// The values looked up in the fixed tables, see [super::lookup]
+    pub lookups: LookupValues<G::ScalarField>,
+}
+
+impl<G: KimchiCurve, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize>
+    ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>
+{
     pub fn new(domain_size: usize) -> Self {
         ProofInputs {
             evaluations: WitnessColumns {
@@ -25,17 +118,242 @@ impl<G: KimchiCurve> ProofInputs<G> {
                 error: Vec::with_capacity(domain_size),
                 selector: Vec::with_capacity(domain_size),
             },
+            public: vec![],
+            lookups: LookupValues::new(),
         }
     }
 }
 
+/// The rows of the public column holding `len` public values, in a domain of
+/// `domain_size` rows. The first half of the values, rounded up, is on the
+/// first rows, and the second half on the last rows in reverse order, so that
+/// the first value of each half is on the first or the last row of the trace,
+/// where the constraints can refer to it, see
+/// [on_first_row](super::on_first_row) and [on_last_row](super::on_last_row).
+///
+/// The rows are distinct when `len` is at most `domain_size`.
+pub fn public_rows(len: usize, domain_size: usize) -> impl Iterator<Item = usize> {
+    let half = (len + 1) / 2;
+    (0..len).map(move |i| {
+        if i < half {
+            i
+        } else {
+            domain_size - 1 - (i - half)
+        }
+    })
+}
+
 // FIXME: should we blind the commitment?
-pub struct Proof<G: KimchiCurve> {
-    pub commitments: WitnessColumns<PolyComm<G>, [PolyComm<G>; N_MIPS_SEL_COLS]>,
-    pub zeta_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]>,
-    pub zeta_omega_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]>,
+pub struct Proof<
+    G: KimchiCurve,
+    const N_SCRATCH: usize = SCRATCH_SIZE,
+    const N_SCRATCH_INVERSE: usize = SCRATCH_SIZE_INVERSE,
+    const N_SEL: usize = N_MIPS_SEL_COLS,
+> {
+    pub commitments:
+        WitnessColumns<PolyComm<G>, [PolyComm<G>; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>,
+    pub zeta_evaluations:
+        WitnessColumns<G::ScalarField, [G::ScalarField; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>,
+    pub zeta_omega_evaluations:
+        WitnessColumns<G::ScalarField, [G::ScalarField; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>,
+    /// The public values, see [ProofInputs::public]. The commitment to the
+    /// public column is not part of the proof, as the verifier computes it
+    /// from the Lagrange bases.
+    pub public: Vec<G::ScalarField>,
+    /// The evaluations of the public column at ζ and ζω
+    pub public_evaluations: PointEvaluations<G::ScalarField>,
+    /// The commitments to the polynomials of the lookup argument, if there
+    /// are lookups
+    pub lookup_commitments: Option<LookupProof<PolyComm<G>, LookupTableIDs>>,
+    /// The evaluations at ζ and ζω of the polynomials of the lookup argument
+    pub lookup_evaluations: Option<LookupProof<PointEvaluations<G::ScalarField>, LookupTableIDs>>,
     pub quotient_commitment: PolyComm<G>,
     pub quotient_evaluations: PointEvaluations<Vec<G::ScalarField>>,
     /// IPA opening proof
     pub opening_proof: OpeningProof<G>,
 }
+
+/// Errors that can arise when writing or reading a proof
+#[derive(Error, Debug)]
+pub enum ProofError {
+    #[error("the proof file cannot be accessed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the proof cannot be encoded: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("the proof cannot be decoded: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+}
+
+impl<
+        G: KimchiCurve,
+        const N_SCRATCH: usize,
+        const N_SCRATCH_INVERSE: usize,
+        const N_SEL: usize,
+    > Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>
+{
+    /// Encodes the proof with MessagePack
+    ///
+    /// # Errors
+    ///
+    /// See [rmp_serde::to_vec].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofError> {
+        Ok(rmp_serde::to_vec(self)?)
+    }
+
+    /// Decodes a proof encoded with [Proof::to_bytes]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not a valid proof.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// Writes the proof to the file `path`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), ProofError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        rmp_serde::encode::write(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a proof written with [Proof::write]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be read or is not a valid proof.
+    pub fn read(path: &Path) -> Result<Self, ProofError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(rmp_serde::from_read(reader)?)
+    }
+}
+
+/// The serialized form of a [Proof]. The columns are listed in the order of
+/// [get_columns](super::column_env::get_columns), so that their number is not
+/// part of the type, and the evaluations are wrapped in vectors, as the field
+/// elements are only serializable in vectors.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "G: KimchiCurve")]
+struct SerializedProof<G: KimchiCurve> {
+    commitments: Vec<PolyComm<G>>,
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    zeta_evaluations: Vec<G::ScalarField>,
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    zeta_omega_evaluations: Vec<G::ScalarField>,
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    public: Vec<G::ScalarField>,
+    public_evaluations: PointEvaluations<Vec<G::ScalarField>>,
+    lookup_commitments: Option<LookupProof<PolyComm<G>, LookupTableIDs>>,
+    lookup_evaluations: Option<LookupProof<PointEvaluations<Vec<G::ScalarField>>, LookupTableIDs>>,
+    quotient_commitment: PolyComm<G>,
+    quotient_evaluations: PointEvaluations<Vec<G::ScalarField>>,
+    opening_proof: OpeningProof<G>,
+}
+
+impl<
+        G: KimchiCurve,
+        const N_SCRATCH: usize,
+        const N_SCRATCH_INVERSE: usize,
+        const N_SEL: usize,
+    > Serialize for Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let wrap = |x: G::ScalarField| vec![x];
+        SerializedProof {
+            commitments: self
+                .commitments
+                .as_ref()
+                .into_vec()
+                .into_iter()
+                .cloned()
+                .collect(),
+            zeta_evaluations: self
+                .zeta_evaluations
+                .as_ref()
+                .into_vec()
+                .into_iter()
+                .cloned()
+                .collect(),
+            zeta_omega_evaluations: self
+                .zeta_omega_evaluations
+                .as_ref()
+                .into_vec()
+                .into_iter()
+                .cloned()
+                .collect(),
+            public: self.public.clone(),
+            public_evaluations: self.public_evaluations.map(&wrap),
+            lookup_commitments: self.lookup_commitments.clone(),
+            lookup_evaluations: self
+                .lookup_evaluations
+                .clone()
+                .map(|evals| evals.map(&|evals| evals.map(&wrap))),
+            quotient_commitment: self.quotient_commitment.clone(),
+            quotient_evaluations: self.quotient_evaluations.clone(),
+            opening_proof: self.opening_proof.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<
+        'de,
+        G: KimchiCurve,
+        const N_SCRATCH: usize,
+        const N_SCRATCH_INVERSE: usize,
+        const N_SEL: usize,
+    > Deserialize<'de> for Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let proof = SerializedProof::<G>::deserialize(deserializer)?;
+        let columns = |n: usize| {
+            D::Error::custom(format!(
+                "expected {} columns, found {n}",
+                N_SCRATCH + N_SCRATCH_INVERSE + 2 + N_SEL
+            ))
+        };
+        let is_single = |evals: &PointEvaluations<Vec<G::ScalarField>>| {
+            evals.zeta.len() == 1 && evals.zeta_omega.len() == 1
+        };
+        let unwrap_single = |evals: PointEvaluations<Vec<G::ScalarField>>| PointEvaluations {
+            zeta: evals.zeta[0],
+            zeta_omega: evals.zeta_omega[0],
+        };
+
+        let n = proof.commitments.len();
+        let commitments = WitnessColumns::from_vec(proof.commitments).ok_or_else(|| columns(n))?;
+        let n = proof.zeta_evaluations.len();
+        let zeta_evaluations =
+            WitnessColumns::from_vec(proof.zeta_evaluations).ok_or_else(|| columns(n))?;
+        let n = proof.zeta_omega_evaluations.len();
+        let zeta_omega_evaluations =
+            WitnessColumns::from_vec(proof.zeta_omega_evaluations).ok_or_else(|| columns(n))?;
+        if !is_single(&proof.public_evaluations)
+            || !proof
+                .lookup_evaluations
+                .iter()
+                .all(|evals| evals.into_iter().all(&is_single))
+        {
+            return Err(D::Error::custom("expected a single evaluation per point"));
+        }
+        Ok(Proof {
+            commitments,
+            zeta_evaluations,
+            zeta_omega_evaluations,
+            public: proof.public,
+            public_evaluations: unwrap_single(proof.public_evaluations),
+            lookup_commitments: proof.lookup_commitments,
+            lookup_evaluations: proof
+                .lookup_evaluations
+                .map(|evals| evals.map(&unwrap_single)),
+            quotient_commitment: proof.quotient_commitment,
+            quotient_evaluations: proof.quotient_evaluations,
+            opening_proof: proof.opening_proof,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use std::array;
+use std::collections::BTreeMap;
 
 use ark_ff::{One, PrimeField, Zero};
 use ark_poly::{univariate::DensePolynomial, Evaluations, Polynomial, Radix2EvaluationDomain as D};
@@ -7,29 +7,41 @@ use kimchi::{
         berkeley_columns::BerkeleyChallenges,
         domains::EvaluationDomains,
         expr::{l0_1, Constants},
+        polynomials::permutation::vanishes_on_last_n_rows,
     },
     curve::KimchiCurve,
     groupmap::GroupMap,
     plonk_sponge::FrSponge,
     proof::PointEvaluations,
 };
+use kimchi_msm::logup::{self, prover::QuotientPolynomialEnvironment, LookupProof};
 use log::debug;
 use mina_poseidon::{sponge::ScalarChallenge, FqSponge};
 use o1_utils::ExtendedDensePolynomial;
 use poly_commitment::{
     commitment::{absorb_commitment, PolyComm},
     ipa::{DensePolynomialOrEvaluations, OpeningProof, SRS},
+    shared::SharedBlob,
     OpenProof as _, SRS as _,
 };
 use rand::{CryptoRng, RngCore};
-use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
 
 use super::{
+    bus::BusError,
     column_env::ColumnEnvironment,
-    proof::{Proof, ProofInputs, WitnessColumns},
+    fft::{CpuBackend, FftBackend},
+    lookup,
+    proof::{public_rows, Proof, ProofInputs, WitnessColumns},
     DEGREE_QUOTIENT_POLYNOMIAL,
 };
-use crate::{interpreters::mips::column::N_MIPS_SEL_COLS, E};
+use crate::{
+    interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
+    lookups::LookupTableIDs,
+    E,
+};
 use thiserror::Error;
 
 /// Errors that can arise when creating a proof
@@ -37,6 +49,22 @@ use thiserror::Error;
 pub enum ProverError {
     #[error("the provided constraint has degree {0} > allowed {1}; expr: {2}")]
     ConstraintDegreeTooHigh(u64, u64, String),
+    #[error("the scratch column {0} does not match its shared blob")]
+    SharedColumnMismatch(usize),
+    #[error("there are {0} public values, but the domain only has {1} rows")]
+    TooManyPublicValues(usize, usize),
+    #[error("the lookup table {0:?} is not a fixed table fitting in the domain")]
+    UnsupportedLookupTable(LookupTableIDs),
+    #[error("the values looked up in the table {0:?} do not have one value per row")]
+    LookupLengthMismatch(LookupTableIDs),
+    #[error("the value looked up in the table {0:?} at row {1} is not in the table")]
+    LookupValueNotInTable(LookupTableIDs, usize),
+    #[error("the constraints polynomial cannot be divided by the vanishing polynomial")]
+    DivisionByVanishingPolynomial,
+    #[error("the constraints are not satisfied, the remainder of the division by the vanishing polynomial is not zero")]
+    ConstraintsNotSatisfied,
+    #[error("the columns of the bus cannot be built: {0}")]
+    Bus(#[from] BusError),
 }
 
 /// Make a PlonKish proof for the given circuit. As inputs, we get the execution
@@ -44,13 +72,22 @@ pub enum ProverError {
 /// `domain`.
 ///
 /// The proof is made of the following steps:
-/// 1. For each column, we create a commitment and absorb it in the sponge.
+/// 1. We commit to the public column, see [public_rows], and absorb the
+///    commitment in the sponge.
+///    For each column, we create a commitment and absorb it in the sponge. If
+///    there are lookups, we do the same for the polynomials of the lookup
+///    argument, see [super::lookup].
 /// 2. We compute the quotient polynomial.
 /// 3. We evaluate each polynomial (columns + quotient) to two challenges ζ and ζω.
 /// 4. We make a batch opening proof using the IPA PCS.
 ///
-/// The final proof consists of the opening proof, the commitments and the
-/// evaluations at ζ and ζω.
+/// The final proof consists of the opening proof, the public values, the
+/// commitments and the evaluations at ζ and ζω.
+///
+/// # Errors
+///
+/// Will give error if the inputs do not satisfy the constraints, see
+/// [ProverError].
 pub fn prove<
     G: KimchiCurve,
     EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
@@ -63,6 +100,100 @@ pub fn prove<
     constraints: &[E<G::ScalarField>],
     rng: &mut RNG,
 ) -> Result<Proof<G>, ProverError>
+where
+    G::BaseField: PrimeField,
+    RNG: RngCore + CryptoRng,
+{
+    prove_with_shared::<G, EFqSponge, EFrSponge, RNG>(domain, srs, inputs, constraints, &[], rng)
+}
+
+/// Same as [prove], where some scratch columns are data blobs already
+/// committed to, e.g. because they are shared with a kimchi proof. Each element
+/// of `shared` is the index of a scratch column and the blob it contains. The
+/// commitment of the blob is used as the commitment of the column instead of
+/// being recomputed, so the blob must have been committed without random
+/// rows. The column is opened with the blinders of the blob.
+pub fn prove_with_shared<
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
+    EFrSponge: FrSponge<G::ScalarField>,
+    RNG,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    inputs: ProofInputs<G>,
+    constraints: &[E<G::ScalarField>],
+    shared: &[(usize, &SharedBlob<G>)],
+    rng: &mut RNG,
+) -> Result<Proof<G>, ProverError>
+where
+    G::BaseField: PrimeField,
+    RNG: RngCore + CryptoRng,
+{
+    prove_with_sizes::<
+        G,
+        EFqSponge,
+        EFrSponge,
+        RNG,
+        SCRATCH_SIZE,
+        SCRATCH_SIZE_INVERSE,
+        N_MIPS_SEL_COLS,
+    >(domain, srs, inputs, constraints, shared, rng)
+}
+
+/// Same as [prove_with_shared], for the execution trace of an interpreter
+/// with `N_SCRATCH` scratch columns, `N_SCRATCH_INVERSE` inverse scratch
+/// columns and `N_SEL` selectors, e.g. the RISC-V 32IM interpreter.
+pub fn prove_with_sizes<
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
+    EFrSponge: FrSponge<G::ScalarField>,
+    RNG,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    inputs: ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+    constraints: &[E<G::ScalarField>],
+    shared: &[(usize, &SharedBlob<G>)],
+    rng: &mut RNG,
+) -> Result<Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>, ProverError>
+where
+    G::BaseField: PrimeField,
+    RNG: RngCore + CryptoRng,
+{
+    prove_with_backend::<G, EFqSponge, EFrSponge, RNG, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>(
+        domain,
+        srs,
+        inputs,
+        constraints,
+        shared,
+        &CpuBackend,
+        rng,
+    )
+}
+
+/// Same as [prove_with_sizes], with the interpolations and the evaluations of
+/// the polynomials over the domains done by `backend`, see [super::fft].
+pub fn prove_with_backend<
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
+    EFrSponge: FrSponge<G::ScalarField>,
+    RNG,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    inputs: ProofInputs<G, N_SCRATCH, N_SCRATCH_INVERSE>,
+    constraints: &[E<G::ScalarField>],
+    shared: &[(usize, &SharedBlob<G>)],
+    backend: &dyn FftBackend<G::ScalarField>,
+    rng: &mut RNG,
+) -> Result<Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>, ProverError>
 where
     G::BaseField: PrimeField,
     RNG: RngCore + CryptoRng,
@@ -77,87 +208,127 @@ where
     ////////////////////////////////////////////////////////////////////////////
 
     debug!("Prover: interpolating all columns, including the selectors");
-    let ProofInputs { evaluations } = inputs;
+    let ProofInputs {
+        evaluations,
+        public,
+        lookups,
+    } = inputs;
+    let domain_size = domain.d1.size as usize;
+    if public.len() > domain_size {
+        return Err(ProverError::TooManyPublicValues(public.len(), domain_size));
+    }
+    let logups = lookups
+        .into_iter()
+        .map(|(table_id, values)| Ok((table_id, lookup::witness(table_id, values, domain_size)?)))
+        .collect::<Result<BTreeMap<_, _>, ProverError>>()?;
+    let one = PolyComm::new(vec![G::ScalarField::one()]);
+    for (i, blob) in shared {
+        if *i >= N_SCRATCH
+            || blob.evals.domain() != domain.d1
+            || !blob.matches(&evaluations.scratch[*i])
+            || blob.evals.evals[evaluations.scratch[*i].len()..]
+                .iter()
+                .any(|x| !x.is_zero())
+            || blob.commitment.blinders.len() != num_chunks
+        {
+            return Err(ProverError::SharedColumnMismatch(*i));
+        }
+    }
     let polys: WitnessColumns<
         DensePolynomial<G::ScalarField>,
-        [DensePolynomial<G::ScalarField>; N_MIPS_SEL_COLS],
+        [DensePolynomial<G::ScalarField>; N_SEL],
+        N_SCRATCH,
+        N_SCRATCH_INVERSE,
     > = {
         let WitnessColumns {
             scratch,
-            scratch_inverse,
+            mut scratch_inverse,
             instruction_counter,
             error,
             selector,
         } = evaluations;
 
-        let domain_size = domain.d1.size as usize;
-
         // Build the selectors
-        let selector: [Vec<G::ScalarField>; N_MIPS_SEL_COLS] = array::from_fn(|i| {
-            let mut s_i = Vec::with_capacity(domain_size);
-            for s in &selector {
-                s_i.push(if G::ScalarField::from(i as u64) == *s {
-                    G::ScalarField::one()
-                } else {
-                    G::ScalarField::zero()
-                })
-            }
-            s_i
-        });
-
-        let eval_col = |evals: Vec<G::ScalarField>| {
-            Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(evals, domain.d1)
-                .interpolate()
-        };
-        // Doing in parallel
-        let scratch = scratch.into_par_iter().map(eval_col).collect::<Vec<_>>();
-        let scratch_inverse = scratch_inverse
+        let selector: Vec<Vec<G::ScalarField>> = (0..N_SEL)
             .into_par_iter()
-            .map(|mut evals| {
-                ark_ff::batch_inversion(&mut evals);
-                eval_col(evals)
+            .map(|i| {
+                let i = G::ScalarField::from(i as u64);
+                selector
+                    .iter()
+                    .map(|s| {
+                        if i == *s {
+                            G::ScalarField::one()
+                        } else {
+                            G::ScalarField::zero()
+                        }
+                    })
+                    .collect()
             })
-            .collect::<Vec<_>>();
-        let selector = selector.into_par_iter().map(eval_col).collect::<Vec<_>>();
-        WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
-            instruction_counter: eval_col(instruction_counter),
-            error: eval_col(error.clone()),
-            selector: selector.try_into().unwrap(),
-        }
-    };
+            .collect();
+        scratch_inverse
+            .par_iter_mut()
+            .for_each(|evals| ark_ff::batch_inversion(evals));
 
-    debug!("Prover: committing to all columns, including the selectors");
-    let commitments: WitnessColumns<PolyComm<G>, [PolyComm<G>; N_MIPS_SEL_COLS]> = {
-        let WitnessColumns {
+        // Doing in parallel, over all the columns at once
+        WitnessColumns {
             scratch,
             scratch_inverse,
             instruction_counter,
             error,
-            selector,
-        } = &polys;
-
-        let comm = |poly: &DensePolynomial<G::ScalarField>| {
-            srs.commit_custom(
-                poly,
-                num_chunks,
-                &PolyComm::new(vec![G::ScalarField::one()]),
-            )
-            .unwrap()
-            .commitment
-        };
-        // Doing in parallel
-        let scratch = scratch.par_iter().map(comm).collect::<Vec<_>>();
-        let scratch_inverse = scratch_inverse.par_iter().map(comm).collect::<Vec<_>>();
-        let selector = selector.par_iter().map(comm).collect::<Vec<_>>();
-        WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
-            instruction_counter: comm(instruction_counter),
-            error: comm(error),
             selector: selector.try_into().unwrap(),
         }
+        .par_map(|evals| {
+            backend.interpolate(
+                Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(
+                    evals, domain.d1,
+                ),
+            )
+        })
+    };
+
+    // The public values are on the first and the last rows, the other rows
+    // being zero, see [public_rows]. The commitment is the one the verifier
+    // computes with the Lagrange bases.
+    let public_poly = {
+        let mut evals = vec![G::ScalarField::zero(); domain_size];
+        for (row, value) in public_rows(public.len(), domain_size).zip(public.iter()) {
+            evals[row] = *value;
+        }
+        backend.interpolate(
+            Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(evals, domain.d1),
+        )
+    };
+    let public_commitment = srs
+        .commit_custom(&public_poly, num_chunks, &one)
+        .unwrap()
+        .commitment;
+
+    debug!("Prover: committing to all columns, including the selectors");
+    let commitments: WitnessColumns<
+        PolyComm<G>,
+        [PolyComm<G>; N_SEL],
+        N_SCRATCH,
+        N_SCRATCH_INVERSE,
+    > = {
+        // Doing in parallel, over all the columns at once. The scratch columns
+        // come first, so the index of a column is its index in the shared
+        // blobs.
+        let commitments = polys
+            .as_ref()
+            .into_vec()
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, poly)| match shared.iter().find(|(j, _)| *j == i) {
+                // shared column: reuse the commitment of the blob
+                Some((_, blob)) => blob.commitment.commitment.clone(),
+                None => {
+                    srs.commit_custom(poly, num_chunks, &one)
+                        .unwrap()
+                        .commitment
+                }
+            })
+            .collect();
+        WitnessColumns::from_vec(commitments).unwrap()
     };
 
     debug!("Prover: evaluating all columns, including the selectors, on d8");
@@ -165,34 +336,14 @@ where
     // Based on the regression test
     // `test_regression_constraints_with_selectors`, the highest degree is 6.
     // Therefore, we do evaluate on d8.
-    let evaluations_d8 = {
-        let WitnessColumns {
-            scratch,
-            scratch_inverse,
-            instruction_counter,
-            error,
-            selector,
-        } = &polys;
-        let eval_d8 =
-            |poly: &DensePolynomial<G::ScalarField>| poly.evaluate_over_domain_by_ref(domain.d8);
-        // Doing in parallel
-        let scratch = scratch.into_par_iter().map(eval_d8).collect::<Vec<_>>();
-        let scratch_inverse = scratch_inverse
-            .into_par_iter()
-            .map(eval_d8)
-            .collect::<Vec<_>>();
-        let selector = selector.into_par_iter().map(eval_d8).collect::<Vec<_>>();
-        WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
-            instruction_counter: eval_d8(instruction_counter),
-            error: eval_d8(error),
-            selector: selector.try_into().unwrap(),
-        }
-    };
+    let evaluations_d8 = polys
+        .as_ref()
+        .par_map(|poly| backend.evaluate_over_domain(poly, domain.d8));
+    let public_evaluations_d8 = backend.evaluate_over_domain(&public_poly, domain.d8);
 
     // Absorbing the commitments - Fiat Shamir
     // We do not parallelize as we need something deterministic.
+    absorb_commitment(&mut fq_sponge, &public_commitment);
     for comm in commitments.scratch.iter() {
         absorb_commitment(&mut fq_sponge, comm)
     }
@@ -205,6 +356,25 @@ where
         absorb_commitment(&mut fq_sponge, comm)
     }
 
+    // The polynomials of the lookup argument are committed to and absorbed by
+    // the environment, as they depend on challenges coined in between.
+    let lookup_env = if logups.is_empty() {
+        None
+    } else {
+        debug!("Prover: committing to the polynomials of the lookup argument");
+        Some(logup::prover::Env::<G, LookupTableIDs>::create::<
+            OpeningProof<G>,
+            EFqSponge,
+            RNG,
+        >(logups, domain, &mut fq_sponge, srs, rng))
+    };
+    let lookup_commitments = lookup_env.as_ref().map(|lookup_env| LookupProof {
+        m: lookup_env.lookup_counters_comm_d1.clone(),
+        h: lookup_env.lookup_terms_comms_d1.clone(),
+        sum: lookup_env.lookup_aggregation_comm_d1.clone(),
+        fixed_tables: lookup_env.fixed_lookup_tables_comms_d1.clone(),
+    });
+
     ////////////////////////////////////////////////////////////////////////////
     // Round 2: Creating and committing to the quotient polynomial
     ////////////////////////////////////////////////////////////////////////////
@@ -215,15 +385,25 @@ where
     let alpha: G::ScalarField = fq_sponge.challenge();
 
     let zk_rows = 0;
-    let column_env: ColumnEnvironment<'_, G::ScalarField> = {
+    // The constraints referring to the next row are not enforced on the last
+    // one, whose next row is the first one.
+    let vanishes_on_last_row =
+        backend.evaluate_over_domain(&vanishes_on_last_n_rows(domain.d1, zk_rows + 1), domain.d8);
+    let column_env: ColumnEnvironment<'_, G::ScalarField, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL> = {
         // FIXME: use a proper Challenge structure
         let challenges = BerkeleyChallenges {
             alpha,
-            // No permutation argument for the moment
-            beta: G::ScalarField::zero(),
+            // No permutation argument for the moment, β is only used by the
+            // lookup argument
+            beta: lookup_env
+                .as_ref()
+                .map_or(G::ScalarField::zero(), |lookup_env| lookup_env.beta),
             gamma: G::ScalarField::zero(),
-            // No lookup for the moment
-            joint_combiner: G::ScalarField::zero(),
+            joint_combiner: lookup_env
+                .as_ref()
+                .map_or(G::ScalarField::zero(), |lookup_env| {
+                    lookup_env.joint_combiner
+                }),
         };
         ColumnEnvironment {
             constants: Constants {
@@ -233,6 +413,16 @@ where
             },
             challenges,
             witness: &evaluations_d8,
+            public: &public_evaluations_d8,
+            vanishes_on_last_row: &vanishes_on_last_row,
+            lookup: lookup_env
+                .as_ref()
+                .map(|lookup_env| QuotientPolynomialEnvironment {
+                    lookup_terms_evals_d8: &lookup_env.lookup_terms_evals_d8,
+                    lookup_aggregation_evals_d8: &lookup_env.lookup_aggregation_evals_d8,
+                    lookup_counters_evals_d8: &lookup_env.lookup_counters_evals_d8,
+                    fixed_tables_evals_d8: &lookup_env.fixed_lookup_tables_evals_d8,
+                }),
             l0_1: l0_1(domain.d1),
             domain,
         }
@@ -247,9 +437,12 @@ where
     // git revert 96d42c127ef025869c91e5fed680e0e383108706
     // ```
     let quotient_poly: DensePolynomial<G::ScalarField> = {
-        // Compute ∑ α^i constraint_i as an expression
-        let combined_expr =
-            E::combine_constraints(0..(constraints.len() as u32), (constraints).to_vec());
+        // Compute ∑ α^i constraint_i as an expression, the constraints being
+        // simplified first to reduce the evaluation work
+        let combined_expr = E::combine_constraints(
+            0..(constraints.len() as u32),
+            constraints.iter().map(|c| c.simplify()).collect(),
+        );
 
         // We want to compute the quotient polynomial, i.e.
         // t(X) = (∑ α^i constraint_i(X)) / Z_H(X).
@@ -267,21 +460,20 @@ where
             combined_expr.evaluations(&column_env);
 
         // And we interpolate using the evaluations
-        let expr_evaluation_interpolated = expr_evaluation.interpolate();
+        let expr_evaluation_interpolated = backend.interpolate(expr_evaluation);
 
-        let fail_final_q_division = || panic!("Fail division by vanishing poly");
-        let fail_remainder_not_zero =
-            || panic!("The constraints are not satisifed since the remainder is not zero");
         // We compute the polynomial t(X) by dividing the constraints polynomial
         // by the vanishing polynomial, i.e. Z_H(X).
-        let (quotient, rem) = expr_evaluation_interpolated
-            .divide_by_vanishing_poly(domain.d1)
-            .unwrap_or_else(fail_final_q_division);
+        let (quotient, rem) = match expr_evaluation_interpolated.divide_by_vanishing_poly(domain.d1)
+        {
+            Some(division) => division,
+            None => return Err(ProverError::DivisionByVanishingPolynomial),
+        };
         // As the constraints must be verified on H, the rest of the division
         // must be equal to 0 as the constraints polynomial and Z_H(X) are both
         // equal on H.
         if !rem.is_zero() {
-            fail_remainder_not_zero();
+            return Err(ProverError::ConstraintsNotSatisfied);
         }
 
         quotient
@@ -309,33 +501,52 @@ where
     let zeta = zeta_chal.to_field(endo_r);
     let zeta_omega = zeta * omega;
 
-    let evals = |point| {
-        let WitnessColumns {
-            scratch,
-            scratch_inverse,
-            instruction_counter,
-            error,
-            selector,
-        } = &polys;
-        let eval = |poly: &DensePolynomial<G::ScalarField>| poly.evaluate(point);
-        let scratch = scratch.par_iter().map(eval).collect::<Vec<_>>();
-        let scratch_inverse = scratch_inverse.par_iter().map(eval).collect::<Vec<_>>();
-        let selector = selector.par_iter().map(eval).collect::<Vec<_>>();
-        WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
-            instruction_counter: eval(instruction_counter),
-            error: eval(error),
-            selector: selector.try_into().unwrap(),
-        }
-    };
+    let evals = |point| polys.as_ref().par_map(|poly| poly.evaluate(point));
     // All evaluations at ζ
-    let zeta_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]> =
-        evals(&zeta);
+    let zeta_evaluations: WitnessColumns<
+        G::ScalarField,
+        [G::ScalarField; N_SEL],
+        N_SCRATCH,
+        N_SCRATCH_INVERSE,
+    > = evals(&zeta);
 
     // All evaluations at ζω
-    let zeta_omega_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]> =
-        evals(&zeta_omega);
+    let zeta_omega_evaluations: WitnessColumns<
+        G::ScalarField,
+        [G::ScalarField; N_SEL],
+        N_SCRATCH,
+        N_SCRATCH_INVERSE,
+    > = evals(&zeta_omega);
+
+    let public_evaluations = PointEvaluations {
+        zeta: public_poly.evaluate(&zeta),
+        zeta_omega: public_poly.evaluate(&zeta_omega),
+    };
+
+    let lookup_evaluations = lookup_env.as_ref().map(|lookup_env| {
+        let evals = |poly: &DensePolynomial<G::ScalarField>| PointEvaluations {
+            zeta: poly.evaluate(&zeta),
+            zeta_omega: poly.evaluate(&zeta_omega),
+        };
+        LookupProof {
+            m: lookup_env
+                .lookup_counters_poly_d1
+                .iter()
+                .map(|(id, polys)| (*id, polys.iter().map(evals).collect()))
+                .collect(),
+            h: lookup_env
+                .lookup_terms_poly_d1
+                .iter()
+                .map(|(id, polys)| (*id, polys.iter().map(evals).collect()))
+                .collect(),
+            sum: evals(&lookup_env.lookup_aggregation_poly_d1),
+            fixed_tables: lookup_env
+                .fixed_lookup_tables_poly_d1
+                .iter()
+                .map(|(id, poly)| (*id, evals(poly)))
+                .collect(),
+        }
+    });
 
     let chunked_quotient = quotient_poly
         .to_chunked_polynomial(DEGREE_QUOTIENT_POLYNOMIAL as usize, domain.d1.size as usize);
@@ -358,6 +569,8 @@ where
     let mut fr_sponge = EFrSponge::new(G::sponge_params());
     fr_sponge.absorb(&fq_sponge.digest());
 
+    fr_sponge.absorb(&public_evaluations.zeta);
+    fr_sponge.absorb(&public_evaluations.zeta_omega);
     for (zeta_eval, zeta_omega_eval) in zeta_evaluations
         .scratch
         .iter()
@@ -394,11 +607,18 @@ where
         fr_sponge.absorb(quotient_zeta_eval);
         fr_sponge.absorb(quotient_zeta_omega_eval);
     }
+    if let Some(lookup_evaluations) = &lookup_evaluations {
+        for PointEvaluations { zeta, zeta_omega } in lookup_evaluations {
+            fr_sponge.absorb(zeta);
+            fr_sponge.absorb(zeta_omega);
+        }
+    }
     ////////////////////////////////////////////////////////////////////////////
     // Round 4: Opening proof w/o linearization polynomial
     ////////////////////////////////////////////////////////////////////////////
 
-    let mut polynomials: Vec<_> = polys.scratch.into_iter().collect();
+    let mut polynomials: Vec<_> = vec![public_poly];
+    polynomials.extend(polys.scratch);
     polynomials.extend(polys.scratch_inverse);
     polynomials.push(polys.instruction_counter);
     polynomials.push(polys.error);
@@ -415,6 +635,43 @@ where
             )
         })
         .collect();
+    // The shared columns are opened with the blinders of their blobs. The
+    // scratch columns come after the public polynomial.
+    for (i, blob) in shared {
+        polynomials[1 + *i].1 = blob.commitment.blinders.clone();
+    }
+    // The polynomials of the lookup argument, in the order of the iterator
+    // over [LookupProof]. The fixed tables are committed to without blinder,
+    // as the verifier computes their commitments.
+    if let Some(lookup_env) = &lookup_env {
+        let lookup_polys = lookup_env
+            .lookup_counters_poly_d1
+            .values()
+            .flatten()
+            .zip(lookup_env.lookup_counters_blinders_d1.values().flatten())
+            .chain(
+                lookup_env
+                    .lookup_terms_poly_d1
+                    .values()
+                    .flatten()
+                    .zip(lookup_env.lookup_terms_blinders_d1.values().flatten()),
+            )
+            .chain(std::iter::once((
+                &lookup_env.lookup_aggregation_poly_d1,
+                &lookup_env.lookup_aggregation_blinders_d1,
+            )))
+            .map(|(poly, blinders)| (poly, blinders.clone()));
+        let fixed_tables = lookup_env
+            .fixed_lookup_tables_poly_d1
+            .values()
+            .map(|poly| (poly, PolyComm::new(vec![G::ScalarField::zero()])));
+        polynomials.extend(lookup_polys.chain(fixed_tables).map(|(poly, blinders)| {
+            (
+                DensePolynomialOrEvaluations::DensePolynomial(poly),
+                blinders,
+            )
+        }));
+    }
     // we handle the quotient separately because the number of blinders =
     // number of chunks, which is different for just the quotient polynomial.
     polynomials.push((
@@ -445,7 +702,11 @@ where
     );
 
     Ok(Proof {
+        public,
+        public_evaluations,
         commitments,
+        lookup_commitments,
+        lookup_evaluations,
         zeta_evaluations,
         zeta_omega_evaluations,
         quotient_commitment: quotient_commitment.commitment,
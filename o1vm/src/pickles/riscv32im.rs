@@ -0,0 +1,138 @@
+//! Proving the execution of RISC-V 32IM programs with the pickles prover.
+//!
+//! The execution trace of the RISC-V interpreter has the same layout as the
+//! one of MIPS, with [SCRATCH_SIZE] scratch columns, no inverse scratch
+//! columns and one selector per instruction, see the mapping of
+//! [Column](crate::interpreters::riscv32im::column::Column) to the columns of
+//! the prover.
+//!
+//! As not every instruction is implemented by the interpreter yet, the
+//! constraints are built for a list of instructions given by the caller.
+
+use ark_ff::{Field, PrimeField, Zero};
+use kimchi::{circuits::domains::EvaluationDomains, curve::KimchiCurve, plonk_sponge::FrSponge};
+use mina_poseidon::FqSponge;
+use poly_commitment::ipa::SRS;
+use rand::{CryptoRng, RngCore};
+
+use super::{
+    proof::{Proof, ProofInputs},
+    prover::{prove_with_sizes, ProverError},
+    verifier::{verify_with_sizes, VerifyError},
+};
+use crate::{
+    interpreters::riscv32im::{
+        constraints,
+        interpreter::{self, Instruction, InterpreterEnv},
+        witness, INSTRUCTION_SET_SIZE, SCRATCH_SIZE,
+    },
+    E,
+};
+
+/// The execution trace of a RISC-V 32IM program
+pub type RiscvProofInputs<G> = ProofInputs<G, SCRATCH_SIZE, 0>;
+
+/// The proof of the execution of a RISC-V 32IM program
+pub type RiscvProof<G> = Proof<G, SCRATCH_SIZE, 0, INSTRUCTION_SET_SIZE>;
+
+/// Returns the constraints of the given instructions, multiplied by their
+/// selector, followed by the constraints of the selectors.
+///
+/// # Panics
+///
+/// Will panic if one of the instructions is not implemented by the
+/// interpreter.
+pub fn get_constraints<F: Field>(instructions: &[Instruction]) -> Vec<E<F>> {
+    let mut riscv_con_env = constraints::Env::<F>::default();
+    let mut constraints = instructions.iter().fold(vec![], |mut acc, instr| {
+        interpreter::interpret_instruction(&mut riscv_con_env, *instr);
+        let selector = riscv_con_env.get_selector();
+        let constraints_with_selector: Vec<E<F>> = riscv_con_env
+            .get_constraints()
+            .into_iter()
+            .map(|c| selector.clone() * c)
+            .collect();
+        acc.extend(constraints_with_selector);
+        riscv_con_env.reset();
+        acc
+    });
+    constraints.extend(riscv_con_env.get_selector_constraints());
+    constraints
+}
+
+/// Adds the row of the last instruction executed by the witness environment
+/// to the execution trace.
+pub fn push_row<G: KimchiCurve>(
+    inputs: &mut RiscvProofInputs<G>,
+    riscv_wit_env: &witness::Env<G::ScalarField>,
+) {
+    for (scratch, scratch_chunk) in riscv_wit_env
+        .scratch_state
+        .iter()
+        .zip(inputs.evaluations.scratch.iter_mut())
+    {
+        scratch_chunk.push(*scratch);
+    }
+    inputs
+        .evaluations
+        .instruction_counter
+        .push(G::ScalarField::from(riscv_wit_env.instruction_counter));
+    // The error column is not used by the RISC-V interpreter
+    inputs.evaluations.error.push(G::ScalarField::zero());
+    // The witness environment keeps the index of the selector among all the
+    // columns
+    inputs.evaluations.selector.push(G::ScalarField::from(
+        (riscv_wit_env.selector - SCRATCH_SIZE - 1) as u64,
+    ));
+}
+
+/// Make a proof of the execution trace of a RISC-V 32IM program, see
+/// [prove](super::prover::prove).
+pub fn prove<
+    G: KimchiCurve,
+    EFqSponge: FqSponge<G::BaseField, G, G::ScalarField> + Clone,
+    EFrSponge: FrSponge<G::ScalarField>,
+    RNG,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    inputs: RiscvProofInputs<G>,
+    constraints: &[E<G::ScalarField>],
+    rng: &mut RNG,
+) -> Result<RiscvProof<G>, ProverError>
+where
+    G::BaseField: PrimeField,
+    RNG: RngCore + CryptoRng,
+{
+    prove_with_sizes::<G, EFqSponge, EFrSponge, RNG, SCRATCH_SIZE, 0, INSTRUCTION_SET_SIZE>(
+        domain,
+        srs,
+        inputs,
+        constraints,
+        &[],
+        rng,
+    )
+}
+
+/// Verify the proof of the execution trace of a RISC-V 32IM program, see
+/// [verify](super::verifier::verify).
+pub fn verify<
+    G: KimchiCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &SRS<G>,
+    constraints: &[E<G::ScalarField>],
+    proof: &RiscvProof<G>,
+) -> Result<(), VerifyError>
+where
+    G::BaseField: PrimeField,
+{
+    verify_with_sizes::<G, EFqSponge, EFrSponge, SCRATCH_SIZE, 0, INSTRUCTION_SET_SIZE>(
+        domain,
+        srs,
+        constraints,
+        proof,
+    )
+}
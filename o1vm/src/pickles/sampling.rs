@@ -0,0 +1,302 @@
+//! Sampling mode for the constraint self-check.
+//!
+//! Proving a full chunk of the execution trace is expensive. To quickly catch
+//! a divergence between the witness interpreter and the constraints (e.g. in
+//! CI), the witness environment can record the full trace in a
+//! [SampledTrace], and the constraints are then only checked on a subset of
+//! the rows with [check_sampled_rows]. The subset is made of:
+//! - a number of rows chosen uniformly at random,
+//! - all the rows around a syscall or a control-flow instruction (branches and
+//!   jumps), as these are the instructions the most likely to diverge.
+//!
+//! A successful check does not replace a proof: it only gives some confidence
+//! that the interpreter and the constraints agree on the sampled rows.
+
+use std::collections::BTreeSet;
+
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use kimchi::{
+    circuits::{
+        berkeley_columns::BerkeleyChallenges,
+        domains::EvaluationDomains,
+        expr::{ColumnEvaluations, Constants, ExprError},
+        gate::CurrOrNext,
+    },
+    curve::KimchiCurve,
+    proof::PointEvaluations,
+};
+use kimchi_msm::columns::Column;
+use rand::{Rng, RngCore};
+use thiserror::Error;
+
+use super::proof::WitnessColumns;
+use crate::{
+    interpreters::mips::{
+        column::{column_registry, N_MIPS_REL_COLS, N_MIPS_SEL_COLS},
+        witness::Env as WitnessEnv,
+        ITypeInstruction, Instruction, JTypeInstruction, RTypeInstruction,
+    },
+    preimage_oracle::PreImageOracleT,
+    E,
+};
+
+/// A row of the execution trace, as seen by the constraints. The selectors are
+/// already expanded into one column per instruction, and the scratch values
+/// that must be inverted are inverted.
+pub type TraceRow<F> = WitnessColumns<F, [F; N_MIPS_SEL_COLS]>;
+
+/// Errors that can arise when checking the constraints on a sample of rows
+#[derive(Error, Debug, Clone)]
+pub enum SamplingError {
+    #[error("the trace is empty, there is no row to check")]
+    EmptyTrace,
+    #[error("the trace has {0} rows, but the domain has only {1} rows")]
+    TraceTooLarge(usize, usize),
+    #[error("constraint {constraint} is not satisfied on row {row} (instruction {instruction:?})")]
+    ConstraintNotSatisfied {
+        row: usize,
+        constraint: usize,
+        instruction: Instruction,
+    },
+    #[error("the constraint {0} cannot be evaluated on row {1}: {2}")]
+    EvaluationFailed(usize, usize, String),
+}
+
+/// Parameters of the sampling mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingConfig {
+    /// Number of rows chosen uniformly at random in the trace.
+    pub nb_random_rows: usize,
+    /// Number of rows checked before and after each syscall, branch or jump.
+    pub window: usize,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        SamplingConfig {
+            nb_random_rows: 64,
+            window: 1,
+        }
+    }
+}
+
+/// Returns true if the instruction is a syscall
+pub fn is_syscall(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::RType(
+            RTypeInstruction::SyscallMmap
+                | RTypeInstruction::SyscallExitGroup
+                | RTypeInstruction::SyscallReadHint
+                | RTypeInstruction::SyscallReadPreimage
+                | RTypeInstruction::SyscallReadOther
+                | RTypeInstruction::SyscallWriteHint
+                | RTypeInstruction::SyscallWritePreimage
+                | RTypeInstruction::SyscallWriteOther
+                | RTypeInstruction::SyscallFcntl
+                | RTypeInstruction::SyscallOther
+        )
+    )
+}
+
+/// Returns true if the instruction modifies the control flow, i.e. if it is a
+/// branch or a jump
+pub fn is_control_flow(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::RType(RTypeInstruction::JumpRegister | RTypeInstruction::JumpAndLinkRegister)
+            | Instruction::JType(JTypeInstruction::Jump | JTypeInstruction::JumpAndLink)
+            | Instruction::IType(
+                ITypeInstruction::BranchEq
+                    | ITypeInstruction::BranchNeq
+                    | ITypeInstruction::BranchLeqZero
+                    | ITypeInstruction::BranchGtZero
+                    | ITypeInstruction::BranchLtZero
+                    | ITypeInstruction::BranchGeqZero
+                    | ITypeInstruction::BranchLtZeroAndLink
+                    | ITypeInstruction::BranchGeqZeroAndLink
+            )
+    )
+}
+
+/// The full execution trace, recorded row by row by the witness environment,
+/// together with the instruction executed on each row.
+pub struct SampledTrace<F> {
+    pub rows: Vec<TraceRow<F>>,
+    pub instructions: Vec<Instruction>,
+}
+
+impl<F: Field> Default for SampledTrace<F> {
+    fn default() -> Self {
+        SampledTrace {
+            rows: vec![],
+            instructions: vec![],
+        }
+    }
+}
+
+impl<F: Field> SampledTrace<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Add a row to the trace.
+    pub fn push(&mut self, row: TraceRow<F>, instr: Instruction) {
+        self.rows.push(row);
+        self.instructions.push(instr);
+    }
+
+    /// Record the current state of the witness environment, after the
+    /// instruction `instr` has been executed with [WitnessEnv::step].
+    pub fn record<PreImageOracle: PreImageOracleT>(
+        &mut self,
+        env: &WitnessEnv<F, PreImageOracle>,
+        instr: Instruction,
+    ) {
+        let selector_idx = env.selector - N_MIPS_REL_COLS;
+        let row = WitnessColumns {
+            scratch: env.scratch_state,
+            // The prover inverts these values using a batch inversion, which
+            // maps zero to zero.
+            scratch_inverse: env
+                .scratch_state_inverse
+                .map(|x| x.inverse().unwrap_or(F::zero())),
+            instruction_counter: F::from(env.instruction_counter),
+            error: F::zero(),
+            selector: std::array::from_fn(|i| {
+                if i == selector_idx {
+                    F::one()
+                } else {
+                    F::zero()
+                }
+            }),
+        };
+        self.push(row, instr)
+    }
+
+    /// Returns the indices of the rows that must be checked, sorted in
+    /// increasing order: all the rows at distance at most `config.window` of a
+    /// syscall or a control-flow instruction, and `config.nb_random_rows` rows
+    /// chosen uniformly at random.
+    pub fn select_rows<RNG: RngCore>(&self, config: &SamplingConfig, rng: &mut RNG) -> Vec<usize> {
+        let n = self.len();
+        let mut rows = BTreeSet::new();
+        if n == 0 {
+            return vec![];
+        }
+        for (i, instr) in self.instructions.iter().enumerate() {
+            if is_syscall(instr) || is_control_flow(instr) {
+                let start = i.saturating_sub(config.window);
+                let end = std::cmp::min(i + config.window, n - 1);
+                rows.extend(start..=end);
+            }
+        }
+        for _ in 0..config.nb_random_rows {
+            rows.insert(rng.gen_range(0..n));
+        }
+        rows.into_iter().collect()
+    }
+}
+
+/// Evaluations of the columns on a given row and the next one.
+struct RowEval<'a, F> {
+    curr: &'a TraceRow<F>,
+    next: &'a TraceRow<F>,
+}
+
+impl<F: Copy> ColumnEvaluations<F> for RowEval<'_, F> {
+    type Column = Column;
+    fn evaluate(&self, col: Self::Column) -> Result<PointEvaluations<F>, ExprError<Self::Column>> {
+        let zeta = *self
+            .curr
+            .get_column(&col)
+            .ok_or(ExprError::MissingEvaluation(col, CurrOrNext::Curr))?;
+        let zeta_omega = *self
+            .next
+            .get_column(&col)
+            .ok_or(ExprError::MissingEvaluation(col, CurrOrNext::Next))?;
+        Ok(PointEvaluations { zeta, zeta_omega })
+    }
+}
+
+/// Check the constraints on a sample of the rows of the trace, selected with
+/// [SampledTrace::select_rows]. The trace is seen as it is proven: padded up to
+/// the size of `domain` with copies of its last row, see
+/// [continuation](super::continuation), so the next row of the last row of the
+/// trace is a copy of it, or the first row if the trace fills the domain. The
+/// challenges are sampled at random from `rng`.
+/// Returns the number of rows that have been checked.
+pub fn check_sampled_rows<G: KimchiCurve, RNG: RngCore>(
+    domain: EvaluationDomains<G::ScalarField>,
+    trace: &SampledTrace<G::ScalarField>,
+    constraints: &[E<G::ScalarField>],
+    config: &SamplingConfig,
+    rng: &mut RNG,
+) -> Result<usize, SamplingError>
+where
+    G::BaseField: PrimeField,
+{
+    let n = trace.len();
+    if n == 0 {
+        return Err(SamplingError::EmptyTrace);
+    }
+    if n > domain.d1.size as usize {
+        return Err(SamplingError::TraceTooLarge(n, domain.d1.size as usize));
+    }
+
+    // The constraints must hold for any challenges, e.g. the ones of the
+    // lookups.
+    let challenges = BerkeleyChallenges {
+        alpha: G::ScalarField::rand(rng),
+        beta: G::ScalarField::rand(rng),
+        gamma: G::ScalarField::rand(rng),
+        joint_combiner: G::ScalarField::rand(rng),
+    };
+    let (_, endo_r) = G::endos();
+    let constants = Constants {
+        endo_coefficient: *endo_r,
+        mds: &G::sponge_params().mds,
+        zk_rows: 0,
+    };
+
+    let registry = column_registry();
+    let rows = trace.select_rows(config, rng);
+    for &row in rows.iter() {
+        let next = if row + 1 < n {
+            row + 1
+        } else if n < domain.d1.size as usize {
+            // the padding copies the last row
+            row
+        } else {
+            0
+        };
+        let evals = RowEval {
+            curr: &trace.rows[row],
+            next: &trace.rows[next],
+        };
+        let pt = domain.d1.group_gen.pow([row as u64]);
+        for (i, constraint) in constraints.iter().enumerate() {
+            let res = constraint
+                .evaluate_(domain.d1, pt, &evals, &constants, &challenges)
+                .map_err(|e| {
+                    SamplingError::EvaluationFailed(i, row, registry.describe_error(&e))
+                })?;
+            if !res.is_zero() {
+                return Err(SamplingError::ConstraintNotSatisfied {
+                    row,
+                    constraint: i,
+                    instruction: trace.instructions[row],
+                });
+            }
+        }
+    }
+    Ok(rows.len())
+}
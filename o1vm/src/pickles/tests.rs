@@ -1,21 +1,64 @@
-use std::time::Instant;
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
 
 use super::{
     super::interpreters::mips::column::SCRATCH_SIZE,
-    proof::{ProofInputs, WitnessColumns},
-    prover::prove,
+    lookup::{self, LookupValues},
+    proof::{public_rows, Proof, ProofError, ProofInputs, WitnessColumns},
+    prover::{prove, prove_with_backend, prove_with_shared, ProverError},
 };
 use crate::{
-    interpreters::mips::{
-        column::SCRATCH_SIZE_INVERSE,
-        constraints as mips_constraints,
-        interpreter::{self, InterpreterEnv},
-        Instruction,
+    cannon::{
+        Meta, Page, Start, State, StepFrequency, VmConfiguration, PAGE_SIZE as MIPS_PAGE_SIZE,
+    },
+    interpreters::{
+        keccak::environment::KeccakEnv,
+        mips::{
+            boundary::BoundaryState,
+            column::{
+                MIPS_BUS_COLUMNS, MIPS_IS_KECCAK_PREIMAGE_OFF, N_MIPS_SEL_COLS,
+                SCRATCH_SIZE_INVERSE,
+            },
+            constraints as mips_constraints,
+            interpreter::{self, InterpreterEnv},
+            witness::Env as MipsEnv,
+            Instruction, RTypeInstruction,
+        },
+        riscv32im::{
+            interpreter::{IInstruction, Instruction as RiscvInstruction},
+            witness::Env as RiscvEnv,
+            PAGE_SIZE,
+        },
     },
-    pickles::{verifier::verify, MAXIMUM_DEGREE_CONSTRAINTS, TOTAL_NUMBER_OF_CONSTRAINTS},
+    lookups::LookupTableIDs,
+    merkle::MerkleStore,
+    pickles::{
+        bus::{check_balance, Bus, BusChallenges, BusError},
+        continuation::{self, Aggregator, ContinuationError},
+        degree::{split_constraints, SplitError},
+        fft::{CpuBackend, FftBackend},
+        folding::{Accumulator, Folder, FoldingError, RelaxedFoldingInstance},
+        get_mips_constraints,
+        keccak::{self as keccak_pickles, KeccakCoprocessor, KECCAK_BUS_COLUMNS},
+        on_first_row, on_last_row,
+        riscv32im::{self as riscv_pickles, RiscvProofInputs},
+        sampling::{check_sampled_rows, SampledTrace, SamplingConfig, SamplingError},
+        transition,
+        verifier::{batch_verify, verify, VerifyError},
+        MAXIMUM_DEGREE_CONSTRAINTS, PUBLIC_COLUMN, TOTAL_NUMBER_OF_CONSTRAINTS,
+    },
+    preimage_oracle::PreImageStore,
 };
 use ark_ff::{Field, One, UniformRand, Zero};
-use kimchi::circuits::{domains::EvaluationDomains, expr::Expr, gate::CurrOrNext};
+use ark_poly::{univariate::DensePolynomial, Evaluations, Radix2EvaluationDomain};
+use folding::instance_witness::RelaxableInstance;
+use kimchi::{
+    circuits::{domains::EvaluationDomains, expr::Expr, gate::CurrOrNext},
+    curve::KimchiCurve,
+};
 use kimchi_msm::{columns::Column, expr::E};
 use log::debug;
 use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters};
@@ -24,7 +67,7 @@ use mina_poseidon::{
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
 use o1_utils::tests::make_test_rng;
-use poly_commitment::SRS;
+use poly_commitment::{shared::SharedBlob, PolyComm, SRS};
 use strum::IntoEnumIterator;
 
 #[test]
@@ -59,11 +102,8 @@ fn zero_to_n_minus_one(n: usize) -> Vec<Fq> {
     (0..n).map(|i| Fq::from((i) as u64)).collect()
 }
 
-#[test]
-fn test_small_circuit() {
-    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
-    let srs = SRS::create(8);
-    let proof_input = ProofInputs::<Pallas> {
+fn small_circuit_inputs() -> ProofInputs<Pallas> {
+    ProofInputs::<Pallas> {
         evaluations: WitnessColumns {
             scratch: std::array::from_fn(|_| zero_to_n_minus_one(8)),
             scratch_inverse: std::array::from_fn(|_| (0..8).map(|_| Fq::zero()).collect()),
@@ -76,11 +116,41 @@ fn test_small_circuit() {
                 .collect(),
             selector: zero_to_n_minus_one(8),
         },
-    };
+        public: vec![],
+        lookups: LookupValues::new(),
+    }
+}
+
+/// The inputs of the small circuit, with the scratch columns multiplied by
+/// `k`, as the trace of the `k`-th chunk of an execution
+fn small_circuit_chunk_inputs(k: usize) -> ProofInputs<Pallas> {
+    let mut inputs = small_circuit_inputs();
+    let k = Fq::from(k as u64);
+    inputs
+        .evaluations
+        .scratch
+        .iter_mut()
+        .for_each(|col| col.iter_mut().for_each(|x| *x *= k));
+    inputs.evaluations.error = (0..8)
+        .map(|i| -(k * Fq::from((i * SCRATCH_SIZE) as u64) + Fq::from((i + 1) as u64)))
+        .collect();
+    inputs
+}
+
+fn small_circuit_constraint() -> E<Fq> {
     let mut expr = Expr::zero();
     for i in 0..SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 2 {
         expr += Expr::cell(Column::Relation(i), CurrOrNext::Curr);
     }
+    expr
+}
+
+#[test]
+fn test_small_circuit() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let proof_input = small_circuit_inputs();
+    let expr = small_circuit_constraint();
     let mut rng = make_test_rng(None);
 
     type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
@@ -102,7 +172,672 @@ fn test_small_circuit() {
         "Verification took: {} ms",
         (instant_after_verification - instant_before_verification).as_millis()
     );
-    assert!(verif, "Verification fails");
+    verif.unwrap();
+}
+
+/// A backend counting the FFTs done with the CPU backend
+#[derive(Default)]
+struct CountingBackend {
+    interpolations: AtomicUsize,
+    evaluations: AtomicUsize,
+}
+
+impl FftBackend<Fq> for CountingBackend {
+    fn interpolate(
+        &self,
+        evals: Evaluations<Fq, Radix2EvaluationDomain<Fq>>,
+    ) -> DensePolynomial<Fq> {
+        self.interpolations.fetch_add(1, Ordering::Relaxed);
+        CpuBackend.interpolate(evals)
+    }
+
+    fn evaluate_over_domain(
+        &self,
+        poly: &DensePolynomial<Fq>,
+        domain: Radix2EvaluationDomain<Fq>,
+    ) -> Evaluations<Fq, Radix2EvaluationDomain<Fq>> {
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+        CpuBackend.evaluate_over_domain(poly, domain)
+    }
+}
+
+#[test]
+fn test_small_circuit_with_fft_backend() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let constraints = vec![small_circuit_constraint()];
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let backend = CountingBackend::default();
+    let proof = prove_with_backend::<
+        Pallas,
+        BaseSponge,
+        ScalarSponge,
+        _,
+        SCRATCH_SIZE,
+        SCRATCH_SIZE_INVERSE,
+        N_MIPS_SEL_COLS,
+    >(
+        domain,
+        &srs,
+        small_circuit_inputs(),
+        &constraints,
+        &[],
+        &backend,
+        &mut make_test_rng(Some([0; 32])),
+    )
+    .unwrap();
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof).unwrap();
+
+    // The columns, the public column and the quotient are interpolated, and
+    // the columns and the polynomial vanishing on the last row are evaluated
+    // on d8
+    let columns = SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 2 + N_MIPS_SEL_COLS;
+    assert_eq!(backend.interpolations.into_inner(), columns + 2);
+    assert_eq!(backend.evaluations.into_inner(), columns + 1);
+
+    // The proof does not depend on the backend
+    let cpu_proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        small_circuit_inputs(),
+        &constraints,
+        &mut make_test_rng(Some([0; 32])),
+    )
+    .unwrap();
+    assert_eq!(cpu_proof.to_bytes().unwrap(), proof.to_bytes().unwrap());
+}
+
+#[test]
+fn test_small_circuit_proof_serialization() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let constraints = vec![small_circuit_constraint()];
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        small_circuit_inputs(),
+        &constraints,
+        &mut rng,
+    )
+    .unwrap();
+
+    let bytes = proof.to_bytes().unwrap();
+    let decoded_proof = Proof::<Pallas>::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded_proof.to_bytes().unwrap(), bytes);
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &decoded_proof).unwrap();
+
+    // a truncated proof can not be decoded
+    assert!(matches!(
+        Proof::<Pallas>::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ProofError::Decode(_))
+    ));
+}
+
+#[test]
+fn test_small_circuit_with_shared_column() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let expr = small_circuit_constraint();
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    // the blob is committed once, with its own blinder
+    let blob = SharedBlob::commit(&srs, domain.d1, &zero_to_n_minus_one(8), 0, &mut rng);
+
+    let proof = prove_with_shared::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        small_circuit_inputs(),
+        &[expr.clone()],
+        &[(3, &blob)],
+        &mut rng,
+    )
+    .unwrap();
+    assert!(blob.is_committed_in(&proof.commitments.scratch[3]));
+    // the blinder of the blob is fresh, not the one of the other columns
+    assert_ne!(proof.commitments.scratch[3], proof.commitments.scratch[2]);
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &[expr.clone()], &proof).unwrap();
+
+    // a blob that does not match the column is rejected
+    let other = SharedBlob::commit(&srs, domain.d1, &[Fq::one()], 0, &mut rng);
+    assert!(matches!(
+        prove_with_shared::<Pallas, BaseSponge, ScalarSponge, _>(
+            domain,
+            &srs,
+            small_circuit_inputs(),
+            &[expr],
+            &[(3, &other)],
+            &mut rng,
+        ),
+        Err(ProverError::SharedColumnMismatch(3))
+    ));
+}
+
+#[test]
+fn test_small_circuit_with_public_values() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let expr = small_circuit_constraint();
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let mut inputs = small_circuit_inputs();
+    inputs.public = vec![Fq::from(42u64), Fq::zero(), Fq::from(7u64)];
+    let mut proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        inputs,
+        &[expr.clone()],
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(
+        proof.public,
+        vec![Fq::from(42u64), Fq::zero(), Fq::from(7u64)]
+    );
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &[expr.clone()], &proof).unwrap();
+
+    // the proof does not verify with other public values
+    proof.public[1] = Fq::one();
+    assert!(
+        verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &[expr.clone()], &proof).is_err()
+    );
+    proof.public[1] = Fq::zero();
+    proof.public.pop();
+    assert!(
+        verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &[expr.clone()], &proof).is_err()
+    );
+
+    // the public values must fit in the domain
+    let mut inputs = small_circuit_inputs();
+    inputs.public = zero_to_n_minus_one(9);
+    assert!(matches!(
+        prove::<Pallas, BaseSponge, ScalarSponge, _>(domain, &srs, inputs, &[expr], &mut rng),
+        Err(ProverError::TooManyPublicValues(9, 8))
+    ));
+}
+
+#[test]
+fn test_small_circuit_with_public_boundaries() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    // The instruction counters of the first and the last rows are the first
+    // and the last public values
+    let instruction_counter = Expr::cell(
+        Column::Relation(SCRATCH_SIZE + SCRATCH_SIZE_INVERSE),
+        CurrOrNext::Curr,
+    );
+    let public = Expr::cell(PUBLIC_COLUMN, CurrOrNext::Curr);
+    let constraints = vec![
+        small_circuit_constraint(),
+        on_first_row(instruction_counter.clone() - public.clone()),
+        on_last_row(instruction_counter - public),
+    ];
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let mut inputs = small_circuit_inputs();
+    inputs.public = vec![Fq::from(1u64), Fq::from(8u64)];
+    let mut proof =
+        prove::<Pallas, BaseSponge, ScalarSponge, _>(domain, &srs, inputs, &constraints, &mut rng)
+            .unwrap();
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof).unwrap();
+
+    // the proof does not verify with another boundary
+    proof.public[1] = Fq::from(9u64);
+    assert!(
+        verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof).is_err()
+    );
+
+    // the trace must match the boundaries
+    let mut inputs = small_circuit_inputs();
+    inputs.public = vec![Fq::from(1u64), Fq::from(9u64)];
+    assert!(matches!(
+        prove::<Pallas, BaseSponge, ScalarSponge, _>(domain, &srs, inputs, &constraints, &mut rng),
+        Err(ProverError::ConstraintsNotSatisfied)
+    ));
+}
+
+#[test]
+fn test_public_rows() {
+    assert_eq!(public_rows(0, 8).collect::<Vec<_>>(), Vec::<usize>::new());
+    assert_eq!(public_rows(1, 8).collect::<Vec<_>>(), vec![0]);
+    assert_eq!(public_rows(3, 8).collect::<Vec<_>>(), vec![0, 1, 7]);
+    assert_eq!(
+        public_rows(8, 8).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 7, 6, 5, 4]
+    );
+}
+
+/// Folds the chunks `chunks` of the small circuit with `folder`, as the prover
+/// and as the verifier. Returns the instance folded by the verifier, and the
+/// accumulator of the prover.
+fn fold_small_circuit_chunks(
+    folder: &Folder<Pallas>,
+    chunks: Vec<ProofInputs<Pallas>>,
+) -> (RelaxedFoldingInstance<Pallas>, Accumulator<Pallas>) {
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+
+    // The prover folds the chunks, and sends the commitments to their columns
+    // with the folding steps
+    let mut prover_sponge = BaseSponge::new(Pallas::other_curve_sponge_params());
+    let mut chunks = chunks.into_iter();
+    let (instance, witness) = folder.instance_witness(chunks.next().unwrap(), &mut prover_sponge);
+    let mut messages = vec![(instance.commitments.clone(), None)];
+    let mut accumulator = folder.accumulate(instance, witness);
+    for chunk in chunks {
+        let (instance, witness) = folder.instance_witness(chunk, &mut prover_sponge);
+        let commitments = instance.commitments.clone();
+        let (folded, step) =
+            folder.fold_witnesses(accumulator, instance, witness, &mut prover_sponge);
+        accumulator = folded;
+        messages.push((commitments, Some(step)));
+    }
+
+    // The verifier folds the instances
+    let mut verifier_sponge = BaseSponge::new(Pallas::other_curve_sponge_params());
+    let mut folded: Option<RelaxedFoldingInstance<Pallas>> = None;
+    for (commitments, step) in messages {
+        let commitments: Vec<_> = commitments
+            .into_iter()
+            .map(|comm| PolyComm::new(vec![comm]))
+            .collect();
+        let instance = folder.instance(&commitments, &mut verifier_sponge);
+        folded = match (folded, step) {
+            (None, None) => Some(instance.relax()),
+            (Some(folded), Some(step)) => Some(
+                folder
+                    .fold_instances(&folded, &instance, step, &mut verifier_sponge)
+                    .unwrap(),
+            ),
+            _ => unreachable!(),
+        };
+    }
+    (folded.unwrap(), accumulator)
+}
+
+#[test]
+fn test_folding_small_circuit_chunks() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    // A constraint of degree 3, reduced to degree 2 by the folding scheme
+    let cubic_constraint = {
+        let cell = |i| Expr::cell(Column::Relation(i), CurrOrNext::Curr);
+        cell(0) * cell(1) * (cell(2) - cell(3))
+    };
+    let constraints = vec![small_circuit_constraint(), cubic_constraint];
+    let folder = Folder::<Pallas>::new(&constraints, &srs, domain.d1);
+
+    let chunks = (1..=3).map(small_circuit_chunk_inputs).collect();
+    let (folded, mut accumulator) = fold_small_circuit_chunks(&folder, chunks);
+    assert!(folded == accumulator.instance);
+    folder.decide(&folded, &accumulator).unwrap();
+
+    // the decider rejects a witness that the instance does not commit to
+    accumulator.witness.extended_witness.witness.columns[0].evals[1] += Fq::one();
+    assert_eq!(
+        folder.decide(&folded, &accumulator),
+        Err(FoldingError::ColumnCommitmentMismatch(Column::Relation(0)))
+    );
+
+    // and the accumulator of a chunk which does not satisfy the constraints,
+    // although the folding steps are the ones of the verifier
+    let mut chunks: Vec<_> = (1..=3).map(small_circuit_chunk_inputs).collect();
+    chunks[2].evaluations.error[1] += Fq::one();
+    let (folded, accumulator) = fold_small_circuit_chunks(&folder, chunks);
+    assert!(folded == accumulator.instance);
+    assert_eq!(
+        folder.decide(&folded, &accumulator),
+        Err(FoldingError::ConstraintsNotSatisfied(1))
+    );
+}
+
+/// The instruction counter of the small circuit is incremented at each row
+fn instruction_counter_transition() -> E<Fq> {
+    let instruction_counter = Column::Relation(SCRATCH_SIZE + SCRATCH_SIZE_INVERSE);
+    Expr::cell(instruction_counter, CurrOrNext::Next)
+        - Expr::cell(instruction_counter, CurrOrNext::Curr)
+        - Expr::one()
+}
+
+#[test]
+fn test_small_circuit_with_next_row() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let constraints = vec![
+        small_circuit_constraint(),
+        transition(instruction_counter_transition()),
+    ];
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        small_circuit_inputs(),
+        &constraints,
+        &mut rng,
+    )
+    .unwrap();
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof).unwrap();
+
+    // the proof does not verify if the transition is not enforced
+    assert!(matches!(
+        verify::<Pallas, BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &[small_circuit_constraint()],
+            &proof
+        ),
+        Err(VerifyError::QuotientCheckFailure)
+    ));
+}
+
+#[test]
+fn test_small_circuit_with_next_row_on_last_row() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    // The next row of the last row is the first one, where the instruction
+    // counter is not incremented
+    let constraints = vec![small_circuit_constraint(), instruction_counter_transition()];
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    assert!(matches!(
+        prove::<Pallas, BaseSponge, ScalarSponge, _>(
+            domain,
+            &srs,
+            small_circuit_inputs(),
+            &constraints,
+            &mut rng
+        ),
+        Err(ProverError::ConstraintsNotSatisfied)
+    ));
+}
+
+/// The first scratch column is looked up in the byte table, and the second one
+/// in the table of the values up to 4
+#[test]
+fn test_split_constraints_of_high_degree() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    // All the scratch columns are equal, so x0^5 = x1 x2 x3 x4 x5
+    let cell = |i| Expr::cell(Column::Relation(i), CurrOrNext::Curr);
+    let constraint: E<Fq> = (0..5).fold(Expr::one(), |acc, _| acc * cell(0))
+        - (1..6).fold(Expr::one(), |acc, i| acc * cell(i));
+    assert_eq!(constraint.degree(1, 0), 5);
+
+    let split = split_constraints(
+        &[constraint.clone()],
+        2,
+        (20..SCRATCH_SIZE).map(Column::Relation),
+    )
+    .unwrap();
+    assert!(split.constraints.iter().all(|c| c.degree(1, 0) <= 2));
+
+    let mut proof_input = small_circuit_inputs();
+    split.fill(&mut proof_input, 8).unwrap();
+    let proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        proof_input,
+        &split.constraints,
+        &mut rng,
+    )
+    .unwrap();
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &split.constraints, &proof).unwrap();
+
+    // a single intermediate column is not enough
+    assert!(matches!(
+        split_constraints(&[constraint], 2, [Column::Relation(20)]),
+        Err(SplitError::NotEnoughColumns(2))
+    ));
+}
+
+fn bus_circuit_inputs(values: Vec<Fq>) -> ProofInputs<Pallas> {
+    let domain_size = values.len();
+    let mut inputs = ProofInputs::<Pallas>::new(domain_size);
+    let zeroes = vec![Fq::zero(); domain_size];
+    inputs.evaluations.scratch = std::array::from_fn(|_| zeroes.clone());
+    inputs.evaluations.scratch[0] = values;
+    inputs.evaluations.scratch_inverse = std::array::from_fn(|_| zeroes.clone());
+    inputs.evaluations.instruction_counter = zeroes.clone();
+    inputs.evaluations.error = zeroes.clone();
+    inputs.evaluations.selector = zeroes;
+    inputs
+}
+
+#[test]
+fn test_bus_between_two_circuits() {
+    let domain_size = 8;
+    let domain = EvaluationDomains::<Fq>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let value = || vec![Expr::cell(Column::Relation(0), CurrOrNext::Curr)];
+    let channel = LookupTableIDs::SyscallLookup;
+    let mut sender = Bus::new();
+    sender.send(channel, Expr::one(), value());
+    let mut receiver = Bus::new();
+    receiver.receive(channel, Expr::one(), value());
+    let columns = [Column::Relation(10), Column::Relation(11)];
+
+    // The receiver gets the values in another order
+    let sent: Vec<Fq> = (0..domain_size).map(|i| Fq::from(i as u64)).collect();
+    let received: Vec<Fq> = sent.iter().rev().copied().collect();
+    let mut inputs = [bus_circuit_inputs(sent), bus_circuit_inputs(received)];
+    let buses = [&sender, &receiver];
+
+    // Prover side
+    let commitments: Vec<_> = buses
+        .iter()
+        .zip(inputs.iter())
+        .map(|(bus, inputs)| bus.commitments(domain, &srs, inputs))
+        .collect();
+    let challenges = BusChallenges::sample::<Pallas, BaseSponge>(&commitments);
+    let mut sums = vec![];
+    let mut proofs = vec![];
+    for (bus, mut inputs) in buses.iter().zip(inputs) {
+        let sum = bus
+            .fill(&challenges, &mut inputs, &columns, domain_size)
+            .unwrap();
+        let constraints = bus
+            .constraints::<SCRATCH_SIZE>(&challenges, sum, &columns, domain_size)
+            .unwrap();
+        let proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+            domain,
+            &srs,
+            inputs,
+            &constraints,
+            &mut rng,
+        )
+        .unwrap();
+        sums.push(sum);
+        proofs.push(proof);
+    }
+
+    // Verifier side
+    let commitments: Vec<_> = buses
+        .iter()
+        .zip(proofs.iter())
+        .map(|(bus, proof)| bus.commitments_in_proof(proof))
+        .collect();
+    assert_eq!(
+        BusChallenges::sample::<Pallas, BaseSponge>(&commitments),
+        challenges
+    );
+    for ((bus, proof), sum) in buses.iter().zip(proofs.iter()).zip(sums.iter()) {
+        let constraints = bus
+            .constraints::<SCRATCH_SIZE>(&challenges, *sum, &columns, domain_size)
+            .unwrap();
+        verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, proof).unwrap();
+    }
+    check_balance(&sums).unwrap();
+
+    // A value received but never sent unbalances the bus
+    inputs = [
+        bus_circuit_inputs((0..domain_size).map(|i| Fq::from(i as u64)).collect()),
+        bus_circuit_inputs((1..=domain_size).map(|i| Fq::from(i as u64)).collect()),
+    ];
+    let sums: Vec<Fq> = buses
+        .iter()
+        .zip(inputs.iter_mut())
+        .map(|(bus, inputs)| {
+            bus.fill(&challenges, inputs, &columns, domain_size)
+                .unwrap()
+        })
+        .collect();
+    assert!(matches!(check_balance(&sums), Err(BusError::Unbalanced(_))));
+
+    // The columns of the bus can not be referred to by the messages
+    assert!(matches!(
+        sender.constraints::<SCRATCH_SIZE>(
+            &challenges,
+            Fq::zero(),
+            &[Column::Relation(0), Column::Relation(11)],
+            domain_size
+        ),
+        Err(BusError::ColumnInMessage(Column::Relation(0)))
+    ));
+}
+
+fn lookup_circuit_inputs(domain_size: usize) -> ProofInputs<Pallas> {
+    let mut inputs = ProofInputs::<Pallas>::new(domain_size);
+    let zeroes = vec![Fq::zero(); domain_size];
+    inputs.evaluations.scratch = std::array::from_fn(|_| zeroes.clone());
+    inputs.evaluations.scratch[0] = (0..domain_size).map(|i| Fq::from(i as u64)).collect();
+    inputs.evaluations.scratch[1] = (0..domain_size).map(|i| Fq::from((i % 5) as u64)).collect();
+    inputs.evaluations.scratch_inverse = std::array::from_fn(|_| zeroes.clone());
+    inputs.evaluations.instruction_counter = zeroes.clone();
+    inputs.evaluations.error = zeroes.clone();
+    inputs.evaluations.selector = zeroes;
+    for (table_id, column) in [
+        (LookupTableIDs::ByteLookup, 0),
+        (LookupTableIDs::AtMost4Lookup, 1),
+    ] {
+        let values = inputs.evaluations.scratch[column]
+            .iter()
+            .map(|x| (Fq::one(), vec![*x]))
+            .collect();
+        inputs.lookups.insert(table_id, vec![values]);
+    }
+    inputs
+}
+
+#[test]
+fn test_lookups_in_fixed_tables() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fq>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let constraints = {
+        let mut reads = BTreeMap::new();
+        reads.insert(
+            LookupTableIDs::ByteLookup,
+            vec![(
+                E::one(),
+                vec![Expr::cell(Column::Relation(0), CurrOrNext::Curr)],
+            )],
+        );
+        reads.insert(
+            LookupTableIDs::AtMost4Lookup,
+            vec![(
+                E::one(),
+                vec![Expr::cell(Column::Relation(1), CurrOrNext::Curr)],
+            )],
+        );
+        lookup::constraints(&reads)
+    };
+
+    let mut proof = prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        lookup_circuit_inputs(domain_size),
+        &constraints,
+        &mut rng,
+    )
+    .unwrap();
+    verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof).unwrap();
+
+    // The prover can not use another table
+    let lookup_commitments = proof.lookup_commitments.as_mut().unwrap();
+    let sum = lookup_commitments.sum.clone();
+    lookup_commitments
+        .fixed_tables
+        .insert(LookupTableIDs::AtMost4Lookup, sum);
+    assert!(matches!(
+        verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof),
+        Err(VerifyError::TranscriptMismatch(
+            LookupTableIDs::AtMost4Lookup
+        ))
+    ));
+
+    // The values must be in the tables
+    let mut inputs = lookup_circuit_inputs(domain_size);
+    inputs
+        .lookups
+        .get_mut(&LookupTableIDs::AtMost4Lookup)
+        .unwrap()[0][3] = (Fq::one(), vec![Fq::from(7u64)]);
+    assert!(matches!(
+        prove::<Pallas, BaseSponge, ScalarSponge, _>(domain, &srs, inputs, &constraints, &mut rng),
+        Err(ProverError::LookupValueNotInTable(
+            LookupTableIDs::AtMost4Lookup,
+            3
+        ))
+    ));
+
+    // Only the fixed tables fitting in the domain are supported
+    for table_id in [
+        LookupTableIDs::MemoryLookup,
+        LookupTableIDs::RangeCheck16Lookup,
+    ] {
+        let mut inputs = lookup_circuit_inputs(domain_size);
+        inputs.lookups = LookupValues::new();
+        inputs.lookups.insert(
+            table_id,
+            vec![vec![(Fq::one(), vec![Fq::zero()]); domain_size]],
+        );
+        assert!(matches!(
+            prove::<Pallas, BaseSponge, ScalarSponge, _>(
+                domain,
+                &srs,
+                inputs,
+                &constraints,
+                &mut rng
+            ),
+            Err(ProverError::UnsupportedLookupTable(id)) if id == table_id
+        ));
+    }
 }
 
 #[test]
@@ -132,3 +867,687 @@ fn test_arkworks_batch_inversion_with_zeroes_and_random() {
     ark_ff::batch_inversion::<Fq>(&mut output);
     assert_eq!(output, exp_output);
 }
+
+fn sampled_trace_for_tests(n: usize) -> SampledTrace<Fq> {
+    let mut trace = SampledTrace::new();
+    for i in 0..n {
+        let row = WitnessColumns {
+            scratch: std::array::from_fn(|_| Fq::from(i as u64)),
+            scratch_inverse: std::array::from_fn(|_| Fq::zero()),
+            instruction_counter: Fq::from(i as u64),
+            error: Fq::zero(),
+            selector: std::array::from_fn(|_| Fq::zero()),
+        };
+        let instr = if i % 5 == 0 {
+            Instruction::RType(RTypeInstruction::SyscallMmap)
+        } else {
+            Instruction::RType(RTypeInstruction::Add)
+        };
+        trace.push(row, instr);
+    }
+    trace
+}
+
+#[test]
+fn test_sampling_selects_rows_around_syscalls() {
+    let mut rng = make_test_rng(None);
+    let trace = sampled_trace_for_tests(16);
+    let config = SamplingConfig {
+        nb_random_rows: 0,
+        window: 1,
+    };
+    let rows = trace.select_rows(&config, &mut rng);
+    assert_eq!(rows, vec![0, 1, 4, 5, 6, 9, 10, 11, 14, 15]);
+}
+
+#[test]
+fn test_sampling_detects_unsatisfied_constraint() {
+    let mut rng = make_test_rng(None);
+    let domain = EvaluationDomains::<Fq>::create(16).unwrap();
+    let mut trace = sampled_trace_for_tests(16);
+    // The instruction counter is equal to the first scratch value, and it is
+    // incremented on each row, except on the last one.
+    let instruction_counter = Column::Relation(SCRATCH_SIZE + SCRATCH_SIZE_INVERSE);
+    let constraint: E<Fq> = Expr::cell(instruction_counter, CurrOrNext::Curr)
+        - Expr::cell(Column::Relation(0), CurrOrNext::Curr);
+    let config = SamplingConfig {
+        nb_random_rows: 4,
+        window: 1,
+    };
+    let nb_rows =
+        check_sampled_rows::<Pallas, _>(domain, &trace, &[constraint.clone()], &config, &mut rng)
+            .unwrap();
+    assert!(nb_rows >= 10);
+
+    // Corrupting a row next to a syscall is always detected
+    trace.rows[4].instruction_counter += Fq::one();
+    let res = check_sampled_rows::<Pallas, _>(domain, &trace, &[constraint], &config, &mut rng);
+    assert!(matches!(
+        res,
+        Err(SamplingError::ConstraintNotSatisfied {
+            row: 4,
+            constraint: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_sampling_checks_the_mips_constraints() {
+    let mut rng = make_test_rng(None);
+    let domain = EvaluationDomains::<Fq>::create(16).unwrap();
+    let constraints = get_mips_constraints::<Fq>();
+    let configuration = vm_configuration();
+    let meta = Meta { symbols: vec![] };
+    let start = Start::create(0);
+
+    let mut env = MipsEnv::<Fq, PreImageStore>::create(
+        MIPS_PAGE_SIZE as usize,
+        mips_counter_state(),
+        PreImageStore::default(),
+    );
+    let mut trace = SampledTrace::new();
+    while !env.halt {
+        let instr = env.step(&configuration, &meta, &start);
+        trace.record(&env, instr);
+    }
+    assert_eq!(trace.len(), 13);
+
+    // The rows of the execution satisfy the constraints, the last one with
+    // its padding as the next row
+    let config = SamplingConfig {
+        nb_random_rows: 64,
+        window: 1,
+    };
+    let nb_rows =
+        check_sampled_rows::<Pallas, _>(domain, &trace, &constraints, &config, &mut rng).unwrap();
+    assert!(nb_rows >= 2);
+
+    // The exit is a syscall, whose row is always checked
+    let last = trace.len() - 1;
+    trace.rows[last].selector = std::array::from_fn(|_| Fq::zero());
+    let res = check_sampled_rows::<Pallas, _>(domain, &trace, &constraints, &config, &mut rng);
+    assert!(matches!(
+        res,
+        Err(SamplingError::ConstraintNotSatisfied { row, .. }) if row == last
+    ));
+}
+
+#[test]
+fn test_keccak_coprocessor() {
+    let domain = EvaluationDomains::<Fq>::create(32).unwrap();
+    let srs = SRS::create(32);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    // A preimage of two blocks: 2 absorbs, 2 * 24 rounds and a squeeze
+    let preimage: Vec<u8> = (0..200).map(|i| i as u8).collect();
+    let mut keccak = KeccakCoprocessor::<Pallas>::new(32);
+    keccak.push_hash(&mut KeccakEnv::new(0, &preimage));
+    assert_eq!(keccak.hashes, 1);
+    assert_eq!(keccak.chunks.len(), 2);
+    assert_eq!(keccak.chunks[1].evaluations.selector.len(), 51 - 32);
+
+    let constraints = keccak_pickles::get_constraints::<Fq>();
+    let challenges =
+        BusChallenges::sample::<Pallas, BaseSponge>(&keccak.bus_commitments(domain, &srs));
+    let proofs = keccak
+        .prove::<BaseSponge, ScalarSponge, _>(domain, &srs, &constraints, &challenges, &mut rng)
+        .unwrap();
+    for proof in proofs.iter() {
+        keccak_pickles::verify::<Pallas, BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &challenges,
+            proof,
+        )
+        .unwrap();
+    }
+    // The steps are chained across the domains, but no MIPS trace sends the
+    // bytes of the preimage
+    let sums: Vec<Fq> = proofs
+        .iter()
+        .map(|proof| keccak_pickles::bus_sum(proof).unwrap())
+        .collect();
+    assert!(check_balance(&sums).is_err());
+}
+
+#[test]
+fn test_keccak_bus_columns() {
+    assert_eq!(
+        keccak_pickles::coprocessor_bus::<Fq>().number_of_columns(),
+        KECCAK_BUS_COLUMNS
+    );
+    assert_eq!(
+        keccak_pickles::mips_bus::<Fq>().number_of_columns(),
+        MIPS_BUS_COLUMNS
+    );
+}
+
+#[test]
+fn test_riscv32im_no_action() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let path = std::env::current_dir()
+        .unwrap()
+        .join("resources/programs/riscv32im/no-action");
+    let state = crate::elf_loader::parse_riscv32(&path).unwrap();
+    let mut riscv_wit_env = RiscvEnv::<Fq>::create(PAGE_SIZE as usize, state);
+
+    // The program starts with 8 `li` instructions, i.e. `addi`
+    let addi = RiscvInstruction::IType(IInstruction::AddImmediate);
+    let mut inputs = RiscvProofInputs::<Pallas>::new(8);
+    for _ in 0..8 {
+        assert_eq!(riscv_wit_env.step(), addi);
+        riscv_pickles::push_row(&mut inputs, &riscv_wit_env);
+    }
+
+    let constraints = riscv_pickles::get_constraints::<Fq>(&[addi]);
+    let proof = riscv_pickles::prove::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        inputs,
+        &constraints,
+        &mut rng,
+    )
+    .unwrap();
+    riscv_pickles::verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proof)
+        .unwrap();
+}
+
+/// A MIPS program incrementing the register 1 ten times, storing it at the
+/// address 0x100, and exiting
+fn mips_counter_state() -> State {
+    // addiu $1, $1, 1
+    let mut program: Vec<u32> = vec![0x24210001; 10];
+    // sw $1, 0x100($0)
+    program.push(0xac010100);
+    // addiu $2, $0, 4246 (sysExitGroup)
+    program.push(0x24021096);
+    // syscall
+    program.push(0x0000000c);
+    mips_state(&program)
+}
+
+/// The state of the MIPS program `program`, loaded at the address 0
+fn mips_state(program: &[u32]) -> State {
+    State {
+        memory: vec![Page {
+            index: 0,
+            data: program.iter().flat_map(|i| i.to_be_bytes()).collect(),
+        }],
+        preimage_key: [0; 32],
+        preimage_offset: 0,
+        pc: 0,
+        next_pc: 4,
+        lo: 0,
+        hi: 0,
+        heap: MIPS_PAGE_SIZE,
+        exit: 0,
+        exited: false,
+        step: 0,
+        registers: [0; 32],
+        last_hint: None,
+        preimage: None,
+    }
+}
+
+/// A configuration of the virtual machine doing nothing but executing the
+/// program
+fn vm_configuration() -> VmConfiguration {
+    VmConfiguration {
+        input_state_file: String::new(),
+        output_state_file: String::new(),
+        metadata_file: String::new(),
+        proof_at: StepFrequency::Never,
+        stop_at: StepFrequency::Never,
+        snapshot_state_at: StepFrequency::Never,
+        checkpoint_at: StepFrequency::Never,
+        info_at: StepFrequency::Never,
+        proof_fmt: String::new(),
+        snapshot_fmt: String::new(),
+        pprof_cpu: false,
+        host: None,
+        stdin: None,
+        resume: None,
+        max_cycles: None,
+    }
+}
+
+#[test]
+fn test_mips_continuation() {
+    let domain = EvaluationDomains::<Fq>::create(8).unwrap();
+    let srs = SRS::create(8);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let constraints = {
+        let mut mips_con_env = mips_constraints::Env::<Fq>::default();
+        let mut constraints = Instruction::iter()
+            .flat_map(|instr_typ| instr_typ.into_iter())
+            .fold(vec![], |mut acc, instr| {
+                interpreter::interpret_instruction(&mut mips_con_env, instr);
+                let selector = mips_con_env.get_selector();
+                acc.extend(
+                    mips_con_env
+                        .get_constraints()
+                        .into_iter()
+                        .map(|c| selector.clone() * c),
+                );
+                mips_con_env.reset();
+                acc
+            });
+        constraints.extend(mips_con_env.get_selector_constraints());
+        constraints
+    };
+    let configuration = vm_configuration();
+    let meta = Meta { symbols: vec![] };
+    let start = Start::create(0);
+
+    let mut env = MipsEnv::<Fq, PreImageStore>::create(
+        MIPS_PAGE_SIZE as usize,
+        mips_counter_state(),
+        PreImageStore::default(),
+    );
+    let mut memory = continuation::memory_store::<Pallas, _>(&mut env);
+    let aggregator = Aggregator::<Pallas>::new(BoundaryState::spill(&env), memory.root());
+    let mut host_digest = Fq::zero();
+    let mut keccak = KeccakCoprocessor::<Pallas>::new(8);
+    let mut traces = vec![];
+    while !env.halt {
+        traces.push(continuation::execute_chunk(
+            8,
+            &mut env,
+            &mut memory,
+            &mut host_digest,
+            &mut keccak,
+            &configuration,
+            &meta,
+            &start,
+        ));
+    }
+    let challenges =
+        continuation::bus_challenges::<Pallas, BaseSponge>(domain, &srs, &traces, &mut keccak);
+    let chunks: Vec<_> = traces
+        .into_iter()
+        .map(|trace| {
+            continuation::prove_chunk::<Pallas, BaseSponge, ScalarSponge, _>(
+                domain,
+                &srs,
+                &constraints,
+                trace,
+                &challenges,
+                &mut rng,
+            )
+            .unwrap()
+        })
+        .collect();
+    // A full chunk, and a chunk padded after the exit
+    assert_eq!(
+        chunks.iter().map(|chunk| chunk.length).collect::<Vec<_>>(),
+        vec![8, 5]
+    );
+    assert_eq!(chunks[1].start.registers[1], 8);
+    // Only the second chunk writes to the memory
+    assert_eq!(chunks[0].memory_root_start, chunks[0].memory_root_end);
+    assert_ne!(chunks[1].memory_root_start, chunks[1].memory_root_end);
+    assert_eq!(
+        chunks[1].memory_root_end,
+        MerkleStore::from_memory(Pallas::sponge_params(), &env.memory).root()
+    );
+
+    // The proofs commit to the boundaries of the chunks
+    assert_eq!(chunks[0].proof.public, chunks[0].public_values());
+    assert_eq!(
+        chunks[0].public_values()[0],
+        Fq::from(chunks[0].start.instruction_counter)
+    );
+    // The memory roots are not committed to
+    assert_eq!(chunks[0].public_values().len(), 6);
+    assert_eq!(chunks[0].public_values()[2], chunks[0].host_digest_start);
+    assert_eq!(chunks[1].host_digest_start, chunks[0].host_digest_end());
+    assert_eq!(host_digest, chunks[1].host_digest_end());
+
+    // The chunks survive a round trip through their encoding
+    let bytes = chunks[1].to_bytes().unwrap();
+    let decoded = continuation::ChunkProof::<Pallas>::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.start, chunks[1].start);
+    assert_eq!(decoded.end, chunks[1].end);
+    assert_eq!(decoded.length, chunks[1].length);
+    assert_eq!(decoded.host_input, chunks[1].host_input);
+    assert_eq!(decoded.bus_sum, chunks[1].bus_sum);
+    assert_eq!(decoded.public_values(), chunks[1].public_values());
+    assert_eq!(decoded.proof.public, chunks[1].proof.public);
+    // A chunk ending before it starts is rejected
+    let mut backwards = continuation::ChunkProof::<Pallas>::from_bytes(&bytes).unwrap();
+    backwards.end = chunks[0].start.clone();
+    assert_eq!(backwards.cycles(), Err(ContinuationError::EndsBeforeStart));
+
+    // The chunks can not be given out of order
+    assert_eq!(
+        aggregator.check(&chunks[1]),
+        Err(ContinuationError::BoundaryMismatch(0))
+    );
+    // The chunks can not go beyond the maximum number of instructions
+    let limited = Aggregator::<Pallas>::new(chunks[0].start.clone(), chunks[0].memory_root_start)
+        .with_max_cycles(7);
+    assert_eq!(
+        limited.check(&chunks[0]),
+        Err(ContinuationError::CycleLimitExceeded(0))
+    );
+    let mut aggregator = aggregator.with_max_cycles(13);
+    for chunk in chunks {
+        aggregator.push(chunk).unwrap();
+    }
+    assert_eq!(aggregator.length(), 13);
+    assert_eq!(aggregator.cycles(), 13);
+    assert!(aggregator.final_state().halt);
+    assert_eq!(aggregator.final_state().registers[1], 10);
+    let keccak_constraints = keccak_pickles::get_constraints::<Fq>();
+    assert_eq!(
+        aggregator.verify::<BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &keccak_constraints,
+            &[]
+        ),
+        Ok(())
+    );
+    // The proofs are made for the constraints binding the boundaries
+    let proofs: Vec<_> = aggregator.chunks.iter().map(|chunk| &chunk.proof).collect();
+    assert!(
+        verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, proofs[0]).is_err()
+    );
+    // The proofs of the chunks can be verified at once, in order. No preimage
+    // is read, so the chunks have the same constraints of the bus.
+    assert!(aggregator
+        .chunks
+        .iter()
+        .all(|chunk| chunk.bus_sum.is_zero()));
+    let constraints =
+        continuation::chunk_constraints(&constraints, &challenges, Fq::zero(), 8).unwrap();
+    batch_verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &constraints, &proofs).unwrap();
+    assert!(matches!(
+        batch_verify::<Pallas, BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &[proofs[1], proofs[0]]
+        ),
+        Err(VerifyError::PublicStateMismatch(1))
+    ));
+}
+
+#[test]
+fn test_mips_lookups() {
+    // Only the fixed tables fitting in the domain are looked up
+    let tables = |domain_size| {
+        lookup::MipsLookups::<Fq>::new(domain_size)
+            .reads
+            .keys()
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(tables(8), vec![LookupTableIDs::AtMost4Lookup]);
+    assert_eq!(
+        tables(1 << 8),
+        vec![LookupTableIDs::AtMost4Lookup, LookupTableIDs::ByteLookup]
+    );
+    assert!(tables(1 << 16).contains(&LookupTableIDs::RangeCheck16Lookup));
+
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fq>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let mut rng = make_test_rng(None);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let constraints = get_mips_constraints::<Fq>();
+    let configuration = vm_configuration();
+    let meta = Meta { symbols: vec![] };
+    let start = Start::create(0);
+    let execute = || {
+        let mut env = MipsEnv::<Fq, PreImageStore>::create(
+            MIPS_PAGE_SIZE as usize,
+            mips_counter_state(),
+            PreImageStore::default(),
+        );
+        let mut memory = continuation::memory_store::<Pallas, _>(&mut env);
+        let aggregator = Aggregator::<Pallas>::new(BoundaryState::spill(&env), memory.root());
+        let mut keccak = KeccakCoprocessor::<Pallas>::new(domain_size);
+        let trace = continuation::execute_chunk(
+            domain_size,
+            &mut env,
+            &mut memory,
+            &mut Fq::zero(),
+            &mut keccak,
+            &configuration,
+            &meta,
+            &start,
+        );
+        let challenges = continuation::bus_challenges::<Pallas, BaseSponge>(
+            domain,
+            &srs,
+            std::slice::from_ref(&trace),
+            &mut keccak,
+        );
+        (aggregator, trace, challenges)
+    };
+
+    // The lookups of the instructions are proven with the chunk
+    let (mut aggregator, trace, challenges) = execute();
+    assert!(trace.end.halt);
+    let chunk = continuation::prove_chunk::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        &srs,
+        &constraints,
+        trace,
+        &challenges,
+        &mut rng,
+    )
+    .unwrap();
+    let lookup_commitments = chunk.proof.lookup_commitments.as_ref().unwrap();
+    assert!(lookup_commitments
+        .fixed_tables
+        .contains_key(&LookupTableIDs::ByteLookup));
+    aggregator.push(chunk).unwrap();
+    let keccak_constraints = keccak_pickles::get_constraints::<Fq>();
+    assert_eq!(
+        aggregator.verify::<BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &keccak_constraints,
+            &[]
+        ),
+        Ok(())
+    );
+
+    // The values looked up are the ones of the columns of the trace: another
+    // byte can not be looked up instead of the first one of the first row
+    let (_, mut trace, challenges) = execute();
+    let lookup = trace
+        .inputs
+        .lookups
+        .get_mut(&LookupTableIDs::ByteLookup)
+        .unwrap()
+        .iter_mut()
+        .find(|lookup| !lookup[0].0.is_zero())
+        .unwrap();
+    let byte = lookup[0].1[0];
+    lookup[0].1[0] = if byte.is_zero() {
+        Fq::one()
+    } else {
+        Fq::zero()
+    };
+    assert!(
+        continuation::prove_chunk::<Pallas, BaseSponge, ScalarSponge, _>(
+            domain,
+            &srs,
+            &constraints,
+            trace,
+            &challenges,
+            &mut rng,
+        )
+        .is_err()
+    );
+}
+
+/// A MIPS program reading the preimage of the Keccak256 key `key`, of 5
+/// bytes, 4 bytes at a time at the address 0x200, and exiting
+fn mips_preimage_state(key: [u8; 32]) -> State {
+    // addiu $4, $0, 5 (FD_PREIMAGE_READ)
+    // addiu $6, $0, 4
+    let mut program: Vec<u32> = vec![0x24040005, 0x24060004];
+    // The length of the preimage, on 8 bytes, and the 5 bytes of the preimage
+    for i in 0..4 {
+        // addiu $2, $0, 4003 (sysRead)
+        program.push(0x24020fa3);
+        // addiu $5, $0, 0x200 + 4 * i
+        program.push(0x24050200 + 4 * i);
+        // syscall
+        program.push(0x0000000c);
+    }
+    // addiu $2, $0, 4246 (sysExitGroup)
+    program.push(0x24021096);
+    // syscall
+    program.push(0x0000000c);
+    State {
+        preimage_key: key,
+        ..mips_state(&program)
+    }
+}
+
+/// Proves the execution of [mips_preimage_state], in a single chunk of 16
+/// instructions, and the hash of its preimage by the Keccak coprocessor. If
+/// `omit_preimage`, the MIPS trace does not flag the preimage as the one of a
+/// Keccak256 key, and sends nothing to the coprocessor.
+fn prove_preimage_read(
+    domain: EvaluationDomains<Fq>,
+    srs: &poly_commitment::ipa::SRS<Pallas>,
+    omit_preimage: bool,
+) -> (Aggregator<Pallas>, Vec<keccak_pickles::KeccakProof<Pallas>>) {
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+    let mut rng = make_test_rng(None);
+
+    let mut preimages = PreImageStore::default();
+    let key = preimages.insert_keccak256(b"hello".to_vec());
+    let mut env = MipsEnv::<Fq, PreImageStore>::create(
+        MIPS_PAGE_SIZE as usize,
+        mips_preimage_state(key),
+        preimages,
+    );
+    let mut memory = continuation::memory_store::<Pallas, _>(&mut env);
+    let mut aggregator = Aggregator::<Pallas>::new(BoundaryState::spill(&env), memory.root());
+    let mut host_digest = Fq::zero();
+    let mut keccak = KeccakCoprocessor::<Pallas>::new(16);
+    let mut trace = continuation::execute_chunk(
+        16,
+        &mut env,
+        &mut memory,
+        &mut host_digest,
+        &mut keccak,
+        &vm_configuration(),
+        &Meta { symbols: vec![] },
+        &Start::create(0),
+    );
+    assert!(env.halt);
+    assert_eq!(trace.hashes, 1);
+    // An absorb, 24 rounds and a squeeze, over two domains
+    assert_eq!(keccak.chunks.len(), 2);
+    if omit_preimage {
+        trace.inputs.evaluations.scratch[MIPS_IS_KECCAK_PREIMAGE_OFF]
+            .iter_mut()
+            .for_each(|is_keccak| *is_keccak = Fq::zero());
+    }
+
+    let traces = [trace];
+    let challenges =
+        continuation::bus_challenges::<Pallas, BaseSponge>(domain, srs, &traces, &mut keccak);
+    let [trace] = traces;
+    let chunk = continuation::prove_chunk::<Pallas, BaseSponge, ScalarSponge, _>(
+        domain,
+        srs,
+        &get_mips_constraints::<Fq>(),
+        trace,
+        &challenges,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(chunk.bus_sum.is_zero(), omit_preimage);
+    aggregator.push(chunk).unwrap();
+    let keccak_proofs = keccak
+        .prove::<BaseSponge, ScalarSponge, _>(
+            domain,
+            srs,
+            &keccak_pickles::get_constraints::<Fq>(),
+            &challenges,
+            &mut rng,
+        )
+        .unwrap();
+    (aggregator, keccak_proofs)
+}
+
+#[test]
+fn test_keccak_bus_with_mips() {
+    let domain = EvaluationDomains::<Fq>::create(16).unwrap();
+    let srs = SRS::create(16);
+
+    type BaseSponge = DefaultFqSponge<PallasParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fq, PlonkSpongeConstantsKimchi>;
+
+    let constraints = get_mips_constraints::<Fq>();
+    let keccak_constraints = keccak_pickles::get_constraints::<Fq>();
+
+    // The bytes and the key read by the MIPS trace are the ones hashed by
+    // the coprocessor
+    let (aggregator, keccak_proofs) = prove_preimage_read(domain, &srs, false);
+    assert_eq!(
+        aggregator.verify::<BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &keccak_constraints,
+            &keccak_proofs
+        ),
+        Ok(())
+    );
+    // The challenges are sampled from all the proofs, so the proofs can not
+    // be verified without the hashes
+    assert_eq!(
+        aggregator.verify::<BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &keccak_constraints,
+            &keccak_proofs[..1]
+        ),
+        Err(ContinuationError::InvalidProof(0))
+    );
+
+    // A MIPS trace which does not send its preimage to the coprocessor
+    let (aggregator, keccak_proofs) = prove_preimage_read(domain, &srs, true);
+    assert_eq!(
+        aggregator.verify::<BaseSponge, ScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &keccak_constraints,
+            &keccak_proofs
+        ),
+        Err(ContinuationError::UnbalancedBus)
+    );
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use ark_ec::AffineRepr;
 use ark_ff::{Field, One, PrimeField, Zero};
 use rand::thread_rng;
@@ -20,26 +22,79 @@ use poly_commitment::{
         absorb_commitment, combined_inner_product, BatchEvaluationProof, Evaluation, PolyComm,
     },
     ipa::OpeningProof,
-    OpenProof,
+    OpenProof, SRS as _,
 };
 
 use super::{
-    column_env::get_all_columns,
-    proof::{Proof, WitnessColumns},
+    bus::BusError,
+    column_env::get_columns,
+    lookup,
+    proof::{public_rows, Proof, WitnessColumns},
+    PUBLIC_COLUMN,
+};
+use crate::{
+    interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
+    lookups::LookupTableIDs,
+    E,
 };
-use crate::{interpreters::mips::column::N_MIPS_SEL_COLS, E};
-use kimchi_msm::columns::Column;
+use kimchi_msm::{columns::Column, logup::LookupProof, LookupTableID};
+use thiserror::Error;
+
+/// Errors that can arise when verifying a proof
+#[derive(Error, Debug)]
+pub enum VerifyError {
+    #[error("there are {0} public values, but the domain only has {1} rows")]
+    TooManyPublicValues(usize, usize),
+    #[error("the commitment to the lookup table {0:?} is not the one of the fixed table")]
+    TranscriptMismatch(LookupTableIDs),
+    #[error("the evaluations of the {0} do not match the commitments")]
+    EvaluationLengthMismatch(&'static str),
+    #[error("the constraints cannot be evaluated: {0}")]
+    ConstraintEvaluation(#[from] ExprError<Column>),
+    #[error("the quotient polynomial does not match the constraints at ζ")]
+    QuotientCheckFailure,
+    #[error("the opening proof is not valid")]
+    OpeningProofFailure,
+    #[error("the proof {0} does not start with the public state the previous proof ends with")]
+    PublicStateMismatch(usize),
+    #[error("the proof {0} is not valid: {1}")]
+    InvalidProof(usize, Box<VerifyError>),
+    #[error("the constraints of the bus cannot be built: {0}")]
+    Bus(#[from] BusError),
+    #[error("the proof does not give its sum on the bus")]
+    MissingBusSum,
+}
 
-type CommitmentColumns<G> = WitnessColumns<PolyComm<G>, [PolyComm<G>; N_MIPS_SEL_COLS]>;
-type EvaluationColumns<F> = WitnessColumns<F, [F; N_MIPS_SEL_COLS]>;
+type CommitmentColumns<
+    G,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+> = WitnessColumns<PolyComm<G>, [PolyComm<G>; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>;
+type EvaluationColumns<
+    F,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+> = WitnessColumns<F, [F; N_SEL], N_SCRATCH, N_SCRATCH_INVERSE>;
 
-struct ColumnEval<'a, G: AffineRepr> {
-    commitment: &'a CommitmentColumns<G>,
-    zeta_eval: &'a EvaluationColumns<G::ScalarField>,
-    zeta_omega_eval: &'a EvaluationColumns<G::ScalarField>,
+struct ColumnEval<
+    'a,
+    G: AffineRepr,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+> {
+    commitment: &'a CommitmentColumns<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>,
+    zeta_eval: &'a EvaluationColumns<G::ScalarField, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>,
+    zeta_omega_eval: &'a EvaluationColumns<G::ScalarField, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>,
+    public: &'a PointEvaluations<G::ScalarField>,
+    lookup: Option<&'a LookupProof<PointEvaluations<G::ScalarField>, LookupTableIDs>>,
 }
 
-impl<G: AffineRepr> ColumnEvaluations<G::ScalarField> for ColumnEval<'_, G> {
+impl<G: AffineRepr, const N_SCRATCH: usize, const N_SCRATCH_INVERSE: usize, const N_SEL: usize>
+    ColumnEvaluations<G::ScalarField> for ColumnEval<'_, G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>
+{
     type Column = Column;
     fn evaluate(
         &self,
@@ -49,19 +104,58 @@ impl<G: AffineRepr> ColumnEvaluations<G::ScalarField> for ColumnEval<'_, G> {
             commitment: _,
             zeta_eval,
             zeta_omega_eval,
+            public,
+            lookup,
         } = self;
-        if let Some(&zeta) = zeta_eval.get_column(&col) {
-            if let Some(&zeta_omega) = zeta_omega_eval.get_column(&col) {
-                Ok(PointEvaluations { zeta, zeta_omega })
-            } else {
-                Err(ExprError::MissingEvaluation(col, CurrOrNext::Next))
+        match col {
+            PUBLIC_COLUMN => Ok(**public),
+            Column::LookupPartialSum(_)
+            | Column::LookupMultiplicity(_)
+            | Column::LookupAggregation
+            | Column::LookupFixedTable(_) => {
+                let eval = lookup.and_then(|lookup| match col {
+                    Column::LookupPartialSum((table_id, i)) => {
+                        lookup.h.get(&LookupTableIDs::from_u32(table_id))?.get(i)
+                    }
+                    Column::LookupMultiplicity((table_id, i)) => {
+                        lookup.m.get(&LookupTableIDs::from_u32(table_id))?.get(i)
+                    }
+                    Column::LookupFixedTable(table_id) => {
+                        lookup.fixed_tables.get(&LookupTableIDs::from_u32(table_id))
+                    }
+                    _ => Some(&lookup.sum),
+                });
+                eval.cloned()
+                    .ok_or(ExprError::MissingEvaluation(col, CurrOrNext::Curr))
+            }
+            _ => {
+                if let Some(&zeta) = zeta_eval.get_column(&col) {
+                    if let Some(&zeta_omega) = zeta_omega_eval.get_column(&col) {
+                        Ok(PointEvaluations { zeta, zeta_omega })
+                    } else {
+                        Err(ExprError::MissingEvaluation(col, CurrOrNext::Next))
+                    }
+                } else {
+                    Err(ExprError::MissingEvaluation(col, CurrOrNext::Curr))
+                }
             }
-        } else {
-            Err(ExprError::MissingEvaluation(col, CurrOrNext::Curr))
         }
     }
 }
 
+/// The number of polynomials of the lookup argument for each table
+fn shape<T>(polys: &BTreeMap<LookupTableIDs, Vec<T>>) -> Vec<(LookupTableIDs, usize)> {
+    polys.iter().map(|(id, v)| (*id, v.len())).collect()
+}
+
+/// Verifies a proof made with [prove](super::prover::prove). The commitment to
+/// the public column is computed from the public values given with the proof,
+/// which the caller must compare to the values it expects, e.g. the initial
+/// state root of the execution.
+///
+/// # Errors
+///
+/// Will give error if the proof is not valid, see [VerifyError].
 pub fn verify<
     G: KimchiCurve,
     EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
@@ -71,12 +165,140 @@ pub fn verify<
     srs: &<OpeningProof<G> as OpenProof<G>>::SRS,
     constraints: &[E<G::ScalarField>],
     proof: &Proof<G>,
-) -> bool
+) -> Result<(), VerifyError>
+where
+    <G as AffineRepr>::BaseField: PrimeField,
+{
+    verify_with_sizes::<G, EFqSponge, EFrSponge, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE, N_MIPS_SEL_COLS>(
+        domain,
+        srs,
+        constraints,
+        proof,
+    )
+}
+
+/// Same as [verify], for the proof of the execution trace of an interpreter
+/// with `N_SCRATCH` scratch columns, `N_SCRATCH_INVERSE` inverse scratch
+/// columns and `N_SEL` selectors, see
+/// [prove_with_sizes](super::prover::prove_with_sizes).
+pub fn verify_with_sizes<
+    G: KimchiCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &<OpeningProof<G> as OpenProof<G>>::SRS,
+    constraints: &[E<G::ScalarField>],
+    proof: &Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>,
+) -> Result<(), VerifyError>
+where
+    <G as AffineRepr>::BaseField: PrimeField,
+{
+    let batch =
+        batch_evaluation_proof::<G, EFqSponge, EFrSponge, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>(
+            domain,
+            srs,
+            constraints,
+            proof,
+        )?;
+    let group_map = G::Map::setup();
+    if !OpeningProof::verify(srs, &group_map, &mut [batch], &mut thread_rng()) {
+        return Err(VerifyError::OpeningProofFailure);
+    }
+    Ok(())
+}
+
+/// Verifies the proofs `proofs` made with [prove](super::prover::prove), e.g.
+/// the proofs of consecutive chunks of an execution, checking all their opening
+/// proofs at once with a single MSM.
+///
+/// The public values of each proof must be the public state at the beginning of
+/// the chunk followed by the one at its end, both of the same length, as in
+/// [ChunkProof::public_values](super::continuation::ChunkProof::public_values).
+/// The state at the beginning of each proof must be the one at the end of the
+/// previous proof.
+///
+/// # Errors
+///
+/// Will give error if the public states of two consecutive proofs do not match,
+/// or if a proof is not valid. As the opening proofs are checked together, an
+/// invalid opening proof is reported without the index of its proof.
+pub fn batch_verify<
+    G: KimchiCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &<OpeningProof<G> as OpenProof<G>>::SRS,
+    constraints: &[E<G::ScalarField>],
+    proofs: &[&Proof<G>],
+) -> Result<(), VerifyError>
+where
+    <G as AffineRepr>::BaseField: PrimeField,
+{
+    for (index, proof) in proofs.iter().enumerate() {
+        if proof.public.len() % 2 != 0 {
+            return Err(VerifyError::PublicStateMismatch(index));
+        }
+        if index > 0 {
+            let previous = &proofs[index - 1].public;
+            let (_, previous_end) = previous.split_at(previous.len() / 2);
+            let (start, _) = proof.public.split_at(proof.public.len() / 2);
+            if previous_end != start {
+                return Err(VerifyError::PublicStateMismatch(index));
+            }
+        }
+    }
+    let mut batch = proofs
+        .iter()
+        .enumerate()
+        .map(|(index, proof)| {
+            batch_evaluation_proof::<
+                G,
+                EFqSponge,
+                EFrSponge,
+                SCRATCH_SIZE,
+                SCRATCH_SIZE_INVERSE,
+                N_MIPS_SEL_COLS,
+            >(domain, srs, constraints, proof)
+            .map_err(|e| VerifyError::InvalidProof(index, Box::new(e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let group_map = G::Map::setup();
+    if !OpeningProof::verify(srs, &group_map, &mut batch, &mut thread_rng()) {
+        return Err(VerifyError::OpeningProofFailure);
+    }
+    Ok(())
+}
+
+/// Checks the proof `proof`, except its opening proof, which is returned to be
+/// checked by the IPA, possibly with the opening proofs of other proofs.
+fn batch_evaluation_proof<
+    'a,
+    G: KimchiCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+    const N_SCRATCH: usize,
+    const N_SCRATCH_INVERSE: usize,
+    const N_SEL: usize,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &<OpeningProof<G> as OpenProof<G>>::SRS,
+    constraints: &[E<G::ScalarField>],
+    proof: &'a Proof<G, N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>,
+) -> Result<BatchEvaluationProof<'a, G, EFqSponge, OpeningProof<G>>, VerifyError>
 where
     <G as AffineRepr>::BaseField: PrimeField,
 {
     let Proof {
+        public,
+        public_evaluations,
         commitments,
+        lookup_commitments,
+        lookup_evaluations,
         zeta_evaluations,
         zeta_omega_evaluations,
         quotient_commitment,
@@ -85,14 +307,37 @@ where
     } = proof;
 
     ////////////////////////////////////////////////////////////////////////////
-    // TODO :  public inputs
+    // Public values
     ////////////////////////////////////////////////////////////////////////////
 
+    // The public column is committed with the Lagrange bases of its rows, see
+    // [public_rows], with the same blinder as the other columns.
+    if public.len() > domain.d1.size as usize {
+        return Err(VerifyError::TooManyPublicValues(
+            public.len(),
+            domain.d1.size as usize,
+        ));
+    }
+    let public_commitment = {
+        let lagrange_basis = srs.get_lagrange_basis(domain.d1);
+        let com: Vec<_> = public_rows(public.len(), domain.d1.size as usize)
+            .map(|row| &lagrange_basis[row])
+            .collect();
+        let public_commitment = PolyComm::<G>::multi_scalar_mul(&com, public);
+        srs.mask_custom(
+            public_commitment.clone(),
+            &public_commitment.map(|_| G::ScalarField::one()),
+        )
+        .unwrap()
+        .commitment
+    };
+
     ////////////////////////////////////////////////////////////////////////////
     // Absorbing all the commitments to the columns
     ////////////////////////////////////////////////////////////////////////////
 
     let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
+    absorb_commitment(&mut fq_sponge, &public_commitment);
     for comm in commitments.scratch.iter() {
         absorb_commitment(&mut fq_sponge, comm)
     }
@@ -105,6 +350,52 @@ where
         absorb_commitment(&mut fq_sponge, comm)
     }
 
+    ////////////////////////////////////////////////////////////////////////////
+    // Lookup argument
+    ////////////////////////////////////////////////////////////////////////////
+
+    let (joint_combiner, beta) = match (lookup_commitments, lookup_evaluations) {
+        (Some(lookup_commitments), Some(lookup_evaluations)) => {
+            // The evaluations must be given for each commitment
+            if shape(&lookup_commitments.m) != shape(&lookup_evaluations.m)
+                || shape(&lookup_commitments.h) != shape(&lookup_evaluations.h)
+                || !lookup_commitments
+                    .fixed_tables
+                    .keys()
+                    .eq(lookup_evaluations.fixed_tables.keys())
+            {
+                return Err(VerifyError::EvaluationLengthMismatch("lookup polynomials"));
+            }
+            lookup_commitments.m.values().for_each(|comms| {
+                comms
+                    .iter()
+                    .for_each(|comm| absorb_commitment(&mut fq_sponge, comm))
+            });
+            let joint_combiner = fq_sponge.challenge();
+            let beta = fq_sponge.challenge();
+            // The tables must be the fixed tables of the o1vm
+            for (table_id, comm) in lookup_commitments.fixed_tables.iter() {
+                match lookup::table_commitment(srs, domain.d1, *table_id, joint_combiner) {
+                    Some(table_comm) if table_comm == *comm => (),
+                    _ => return Err(VerifyError::TranscriptMismatch(*table_id)),
+                }
+            }
+            lookup_commitments.h.values().for_each(|comms| {
+                comms
+                    .iter()
+                    .for_each(|comm| absorb_commitment(&mut fq_sponge, comm))
+            });
+            lookup_commitments
+                .fixed_tables
+                .values()
+                .for_each(|comm| absorb_commitment(&mut fq_sponge, comm));
+            absorb_commitment(&mut fq_sponge, &lookup_commitments.sum);
+            (joint_combiner, beta)
+        }
+        (None, None) => (G::ScalarField::zero(), G::ScalarField::zero()),
+        _ => return Err(VerifyError::EvaluationLengthMismatch("lookup polynomials")),
+    };
+
     // Sample α with the Fq-Sponge.
     let alpha = fq_sponge.challenge();
 
@@ -112,6 +403,12 @@ where
     // Quotient polynomial
     ////////////////////////////////////////////////////////////////////////////
 
+    // The quotient polynomial is evaluated by chunks
+    if quotient_evaluations.zeta.len() != quotient_commitment.len()
+        || quotient_evaluations.zeta_omega.len() != quotient_commitment.len()
+    {
+        return Err(VerifyError::EvaluationLengthMismatch("quotient polynomial"));
+    }
     absorb_commitment(&mut fq_sponge, quotient_commitment);
 
     // -- Preparing for opening proof verification
@@ -125,6 +422,8 @@ where
         commitment: commitments,
         zeta_eval: zeta_evaluations,
         zeta_omega_eval: zeta_omega_evaluations,
+        public: public_evaluations,
+        lookup: lookup_evaluations.as_ref(),
     };
 
     // -- Absorb all commitments_and_evaluations
@@ -132,6 +431,8 @@ where
     let mut fr_sponge = EFrSponge::new(G::sponge_params());
     fr_sponge.absorb(&fq_sponge.digest());
 
+    fr_sponge.absorb(&public_evaluations.zeta);
+    fr_sponge.absorb(&public_evaluations.zeta_omega);
     for (zeta_eval, zeta_omega_eval) in zeta_evaluations
         .scratch
         .iter()
@@ -168,15 +469,21 @@ where
         fr_sponge.absorb(quotient_zeta_eval);
         fr_sponge.absorb(quotient_zeta_omega_eval);
     }
+    if let Some(lookup_evaluations) = lookup_evaluations {
+        for PointEvaluations { zeta, zeta_omega } in lookup_evaluations {
+            fr_sponge.absorb(zeta);
+            fr_sponge.absorb(zeta_omega);
+        }
+    }
 
     // FIXME: use a proper Challenge structure
     let challenges = BerkeleyChallenges {
         alpha,
-        // No permutation argument for the moment
-        beta: G::ScalarField::zero(),
+        // No permutation argument for the moment, β is only used by the lookup
+        // argument
+        beta,
         gamma: G::ScalarField::zero(),
-        // No lookup for the moment
-        joint_combiner: G::ScalarField::zero(),
+        joint_combiner,
     };
     let (_, endo_r) = G::endos();
 
@@ -186,8 +493,11 @@ where
         zk_rows: 0,
     };
 
-    let combined_expr =
-        Expr::combine_constraints(0..(constraints.len() as u32), constraints.to_vec());
+    // The constraints are simplified as in the prover
+    let combined_expr = Expr::combine_constraints(
+        0..(constraints.len() as u32),
+        constraints.iter().map(|c| c.simplify()).collect(),
+    );
 
     let numerator_zeta = PolishToken::evaluate(
         combined_expr.to_polish().as_slice(),
@@ -196,33 +506,51 @@ where
         &column_eval,
         &constants,
         &challenges,
-    )
-    .unwrap_or_else(|_| panic!("Could not evaluate quotient polynomial at zeta"));
+    )?;
 
     let v_chal = fr_sponge.challenge();
     let v = v_chal.to_field(endo_r);
     let u_chal = fr_sponge.challenge();
     let u = u_chal.to_field(endo_r);
 
-    let mut evaluations: Vec<_> = get_all_columns()
-        .into_iter()
-        .map(|column| {
-            let commitment = column_eval
-                .commitment
-                .get_column(&column)
-                .unwrap_or_else(|| panic!("Could not get `commitment` for `Evaluation`"))
-                .clone();
-
-            let evaluations = column_eval
-                .evaluate(column)
-                .unwrap_or_else(|_| panic!("Could not get `evaluations` for `Evaluation`"));
-
-            Evaluation {
-                commitment,
-                evaluations: vec![vec![evaluations.zeta], vec![evaluations.zeta_omega]],
-            }
-        })
-        .collect();
+    let mut evaluations = vec![Evaluation {
+        commitment: public_commitment,
+        evaluations: vec![
+            vec![public_evaluations.zeta],
+            vec![public_evaluations.zeta_omega],
+        ],
+    }];
+    evaluations.extend(
+        get_columns::<N_SCRATCH, N_SCRATCH_INVERSE, N_SEL>()
+            .into_iter()
+            .map(|column| {
+                let commitment = column_eval
+                    .commitment
+                    .get_column(&column)
+                    .unwrap_or_else(|| panic!("Could not get `commitment` for `Evaluation`"))
+                    .clone();
+
+                let evaluations = column_eval
+                    .evaluate(column)
+                    .unwrap_or_else(|_| panic!("Could not get `evaluations` for `Evaluation`"));
+
+                Evaluation {
+                    commitment,
+                    evaluations: vec![vec![evaluations.zeta], vec![evaluations.zeta_omega]],
+                }
+            }),
+    );
+
+    if let (Some(lookup_commitments), Some(lookup_evaluations)) =
+        (lookup_commitments, lookup_evaluations)
+    {
+        evaluations.extend(lookup_commitments.into_iter().zip(lookup_evaluations).map(
+            |(commitment, eval)| Evaluation {
+                commitment: commitment.clone(),
+                evaluations: vec![vec![eval.zeta], vec![eval.zeta_omega]],
+            },
+        ));
+    }
 
     evaluations.push(Evaluation {
         commitment: proof.quotient_commitment.clone(),
@@ -251,8 +579,6 @@ where
         combined_inner_product,
     };
 
-    let group_map = G::Map::setup();
-
     // Check the actual quotient works.
     let (quotient_zeta, _) = quotient_evaluations.zeta.iter().fold(
         (G::ScalarField::zero(), G::ScalarField::one()),
@@ -262,6 +588,8 @@ where
             (res, zeta_i_n)
         },
     );
-    (quotient_zeta == numerator_zeta / (zeta.pow([domain.d1.size]) - G::ScalarField::one()))
-        && OpeningProof::verify(srs, &group_map, &mut [batch], &mut thread_rng())
+    if quotient_zeta != numerator_zeta / (zeta.pow([domain.d1.size]) - G::ScalarField::one()) {
+        return Err(VerifyError::QuotientCheckFailure);
+    }
+    Ok(batch)
 }
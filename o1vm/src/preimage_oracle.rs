@@ -5,8 +5,10 @@ use crate::cannon::{
 use command_fds::{CommandFdExt, FdMapping};
 use log::debug;
 use os_pipe::{PipeReader, PipeWriter};
+use sha3::{Digest, Keccak256};
 use std::{
-    io::{Read, Write},
+    collections::HashMap,
+    io::{ErrorKind, Read, Write},
     os::fd::{AsRawFd, FromRawFd, OwnedFd},
     process::{Child, Command},
 };
@@ -25,6 +27,87 @@ pub trait PreImageOracleT {
     fn hint(&mut self, hint: Hint);
 }
 
+/// The type of a pre-image key, given by its first byte, as defined by the
+/// pre-image oracle of Cannon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreimageKeyType {
+    /// A value local to the program, e.g. one of its inputs
+    Local = 1,
+    /// The pre-image of the Keccak256 hash given by the rest of the key
+    Keccak256 = 2,
+    GlobalGeneric = 3,
+    Sha256 = 4,
+    Blob = 5,
+    Precompile = 6,
+}
+
+impl PreimageKeyType {
+    /// Returns the type of the key, or `None` if its first byte is not a
+    /// known type
+    pub fn from_key(key: &[u8; 32]) -> Option<Self> {
+        match key[0] {
+            1 => Some(PreimageKeyType::Local),
+            2 => Some(PreimageKeyType::Keccak256),
+            3 => Some(PreimageKeyType::GlobalGeneric),
+            4 => Some(PreimageKeyType::Sha256),
+            5 => Some(PreimageKeyType::Blob),
+            6 => Some(PreimageKeyType::Precompile),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the key under which `preimage` is requested to the oracle as a
+/// Keccak256 pre-image, i.e. its Keccak256 hash whose first byte is replaced by
+/// the type of the key.
+pub fn keccak256_key(preimage: &[u8]) -> [u8; 32] {
+    let mut key: [u8; 32] = Keccak256::digest(preimage).into();
+    key[0] = PreimageKeyType::Keccak256 as u8;
+    key
+}
+
+/// A pre-image oracle keeping the pre-images in memory, which can be used as
+/// the host-side oracle, see [RW::serve_preimage].
+#[derive(Default)]
+pub struct PreImageStore {
+    pub preimages: HashMap<[u8; 32], Vec<u8>>,
+    /// The hints received from the program, in order
+    pub hints: Vec<Vec<u8>>,
+}
+
+impl PreImageStore {
+    /// Add a pre-image, and return the key under which it can be requested
+    pub fn insert_keccak256(&mut self, preimage: Vec<u8>) -> [u8; 32] {
+        let key = keccak256_key(&preimage);
+        self.preimages.insert(key, preimage);
+        key
+    }
+
+    /// Add a pre-image under a local key
+    pub fn insert_local(&mut self, key: [u8; 32], preimage: Vec<u8>) {
+        assert_eq!(
+            PreimageKeyType::from_key(&key),
+            Some(PreimageKeyType::Local),
+            "The key is not a local key"
+        );
+        self.preimages.insert(key, preimage);
+    }
+}
+
+impl PreImageOracleT for PreImageStore {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        let preimage = self
+            .preimages
+            .get(&key)
+            .unwrap_or_else(|| panic!("No pre-image for the key {}", hex::encode(key)));
+        Preimage::create(preimage.clone())
+    }
+
+    fn hint(&mut self, hint: Hint) {
+        self.hints.push(hint.get());
+    }
+}
+
 pub struct ReadWrite<R, W> {
     pub reader: R,
     pub writer: W,
@@ -105,59 +188,43 @@ pub fn create_bidirectional_channel() -> Option<(RW, RW)> {
     ))
 }
 
-impl PreImageOracle {
-    pub fn create(hp_opt: &Option<HostProgram>) -> PreImageOracle {
-        let host_program = hp_opt.as_ref().expect("No host program given");
-
-        let mut cmd = Command::new(&host_program.name);
-        cmd.args(&host_program.arguments);
-
-        let (oracle_client, oracle_server) =
-            create_bidirectional_channel().expect("Could not create bidirectional oracle channel");
-        let (hint_client, hint_server) =
-            create_bidirectional_channel().expect("Could not create bidirectional hint channel");
-
-        // file descriptors 0, 1, 2 respectively correspond to the inherited stdin,
-        // stdout, stderr.
-        // We need to map 3, 4, 5, 6 in the child process
-        cmd.fd_mappings(vec![
-            FdMapping {
-                parent_fd: unsafe { OwnedFd::from_raw_fd(hint_server.0.writer.as_raw_fd()) },
-                child_fd: HINT_CLIENT_WRITE_FD,
-            },
-            FdMapping {
-                parent_fd: unsafe { OwnedFd::from_raw_fd(hint_server.0.reader.as_raw_fd()) },
-                child_fd: HINT_CLIENT_READ_FD,
-            },
-            FdMapping {
-                parent_fd: unsafe { OwnedFd::from_raw_fd(oracle_server.0.writer.as_raw_fd()) },
-                child_fd: PREIMAGE_CLIENT_WRITE_FD,
-            },
-            FdMapping {
-                parent_fd: unsafe { OwnedFd::from_raw_fd(oracle_server.0.reader.as_raw_fd()) },
-                child_fd: PREIMAGE_CLIENT_READ_FD,
-            },
-        ])
-        .unwrap_or_else(|_| panic!("Could not map file descriptors to preimage server process"));
-
-        PreImageOracle {
-            cmd,
-            oracle_client,
-            oracle_server,
-            hint_client,
-            hint_server,
-        }
+/// Read exactly `buf.len()` bytes, returning `false` if the channel has been
+/// closed before the first byte
+fn read_request(reader: &mut PipeReader, buf: &mut [u8]) -> std::io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
     }
+}
 
-    pub fn start(&mut self) -> Child {
-        // Spawning inherits the current process's stdin/stdout/stderr descriptors
-        self.cmd
-            .spawn()
-            .expect("Could not spawn pre-image oracle process")
+/// Read a message made of its length on 8 bytes followed by its content,
+/// returning `None` if the channel has been closed.
+// The pipes are in packet mode (see `create_pipe`), where a read smaller than
+// the next packet discards the rest of the packet. As the clients write the
+// length and the content in a single packet, the first read must be large
+// enough to hold it.
+fn read_message(reader: &mut PipeReader) -> std::io::Result<Option<Vec<u8>>> {
+    let mut buf = vec![0_u8; libc::PIPE_BUF];
+    let mut n = reader.read(&mut buf)?;
+    if n == 0 {
+        return Ok(None);
+    }
+    if n < 8 {
+        reader.read_exact(&mut buf[n..8])?;
+        n = 8;
+    }
+    let length = u64::from_be_bytes(buf[0..8].try_into().unwrap()) as usize;
+    let mut msg = buf[8..n].to_vec();
+    if msg.len() < length {
+        let mut rest = vec![0_u8; length - msg.len()];
+        reader.read_exact(&mut rest)?;
+        msg.extend(rest);
     }
+    Ok(Some(msg))
 }
 
-impl PreImageOracleT for PreImageOracle {
+impl RW {
     // The preimage protocol goes as follows
     // 1. Ask for data through a key
     // 2. Get the answers in the following format
@@ -166,8 +233,8 @@ impl PreImageOracleT for PreImageOracle {
     //      +---------------------------------+
     //   a. a 64-bit integer indicating the length of the actual data
     //   b. the preimage data, with a size of <length> bits
-    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
-        let RW(ReadWrite { reader, writer }) = &mut self.oracle_client;
+    pub fn request_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        let RW(ReadWrite { reader, writer }) = self;
 
         let r = writer.write_all(&key);
         assert!(r.is_ok());
@@ -204,8 +271,8 @@ impl PreImageOracleT for PreImageOracle {
     //       +----------------------------+
     //
     // 2. Get back a single ack byte informing the hint has been processed.
-    fn hint(&mut self, hint: Hint) {
-        let RW(ReadWrite { reader, writer }) = &mut self.hint_client;
+    pub fn request_hint(&mut self, hint: Hint) {
+        let RW(ReadWrite { reader, writer }) = self;
 
         // Write hint request
         let mut hint_bytes = hint.get();
@@ -222,6 +289,106 @@ impl PreImageOracleT for PreImageOracle {
         // And do nothing with it anyway
         let _ = reader.read_exact(&mut buf);
     }
+
+    /// Answer the next pre-image request of the client with the pre-image
+    /// given by `oracle`, following the protocol of [RW::request_preimage].
+    /// Returns `false` once the client has closed the channel.
+    pub fn serve_preimage<O: PreImageOracleT>(&mut self, oracle: &mut O) -> std::io::Result<bool> {
+        let RW(ReadWrite { reader, writer }) = self;
+
+        let mut key = [0_u8; 32];
+        if !read_request(reader, &mut key)? {
+            return Ok(false);
+        }
+        debug!("Serving the pre-image of the key {}", hex::encode(key));
+        let preimage = oracle.get_preimage(key).get();
+
+        // The length and the pre-image are written separately, as they are
+        // read separately by the client
+        writer.write_all(&u64::to_be_bytes(preimage.len() as u64))?;
+        writer.write_all(&preimage)?;
+        writer.flush()?;
+        Ok(true)
+    }
+
+    /// Give the next hint of the client to `oracle` and acknowledge it,
+    /// following the protocol of [RW::request_hint]. Returns `false` once the
+    /// client has closed the channel.
+    pub fn serve_hint<O: PreImageOracleT>(&mut self, oracle: &mut O) -> std::io::Result<bool> {
+        let RW(ReadWrite { reader, writer }) = self;
+
+        let hint = match read_message(reader)? {
+            Some(hint) => hint,
+            None => return Ok(false),
+        };
+        oracle.hint(Hint::create(hint));
+
+        writer.write_all(&[1_u8])?;
+        writer.flush()?;
+        Ok(true)
+    }
+}
+
+impl PreImageOracle {
+    pub fn create(hp_opt: &Option<HostProgram>) -> PreImageOracle {
+        let host_program = hp_opt.as_ref().expect("No host program given");
+
+        let mut cmd = Command::new(&host_program.name);
+        cmd.args(&host_program.arguments);
+
+        let (oracle_client, oracle_server) =
+            create_bidirectional_channel().expect("Could not create bidirectional oracle channel");
+        let (hint_client, hint_server) =
+            create_bidirectional_channel().expect("Could not create bidirectional hint channel");
+
+        // file descriptors 0, 1, 2 respectively correspond to the inherited stdin,
+        // stdout, stderr.
+        // We need to map 3, 4, 5, 6 in the child process
+        cmd.fd_mappings(vec![
+            FdMapping {
+                parent_fd: unsafe { OwnedFd::from_raw_fd(hint_server.0.writer.as_raw_fd()) },
+                child_fd: HINT_CLIENT_WRITE_FD,
+            },
+            FdMapping {
+                parent_fd: unsafe { OwnedFd::from_raw_fd(hint_server.0.reader.as_raw_fd()) },
+                child_fd: HINT_CLIENT_READ_FD,
+            },
+            FdMapping {
+                parent_fd: unsafe { OwnedFd::from_raw_fd(oracle_server.0.writer.as_raw_fd()) },
+                child_fd: PREIMAGE_CLIENT_WRITE_FD,
+            },
+            FdMapping {
+                parent_fd: unsafe { OwnedFd::from_raw_fd(oracle_server.0.reader.as_raw_fd()) },
+                child_fd: PREIMAGE_CLIENT_READ_FD,
+            },
+        ])
+        .unwrap_or_else(|_| panic!("Could not map file descriptors to preimage server process"));
+
+        PreImageOracle {
+            cmd,
+            oracle_client,
+            oracle_server,
+            hint_client,
+            hint_server,
+        }
+    }
+
+    pub fn start(&mut self) -> Child {
+        // Spawning inherits the current process's stdin/stdout/stderr descriptors
+        self.cmd
+            .spawn()
+            .expect("Could not spawn pre-image oracle process")
+    }
+}
+
+impl PreImageOracleT for PreImageOracle {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        self.oracle_client.request_preimage(key)
+    }
+
+    fn hint(&mut self, hint: Hint) {
+        self.hint_client.request_hint(hint)
+    }
 }
 
 #[cfg(test)]
@@ -299,4 +466,51 @@ mod tests {
         assert_eq!(n, len);
         assert_eq!(data, msg2);
     }
+
+    #[test]
+    fn test_keccak256_key() {
+        // The key of the pre-image stored in the resources of the tests
+        let preimage = hex::decode(
+            std::fs::read_to_string(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/resources/tests/0x022107307879258577230c5aa2f90567bda40877a7e85dceb6ff1f37480fef3d.txt"
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        let key = keccak256_key(&preimage);
+        assert_eq!(
+            hex::encode(key),
+            "022107307879258577230c5aa2f90567bda40877a7e85dceb6ff1f37480fef3d"
+        );
+        assert_eq!(
+            PreimageKeyType::from_key(&key),
+            Some(PreimageKeyType::Keccak256)
+        );
+    }
+
+    // Test that a client gets the pre-images and sends the hints of a server
+    // answering from a pre-image store
+    #[test]
+    fn test_serve_preimages_and_hints() {
+        let (mut oracle_client, mut oracle_server) = create_bidirectional_channel().unwrap();
+        let (mut hint_client, mut hint_server) = create_bidirectional_channel().unwrap();
+
+        let preimage = b"hello, world".to_vec();
+        let mut store = PreImageStore::default();
+        let key = store.insert_keccak256(preimage.clone());
+
+        let server_joiner = std::thread::spawn(move || {
+            assert!(hint_server.serve_hint(&mut store).unwrap());
+            assert!(oracle_server.serve_preimage(&mut store).unwrap());
+            store
+        });
+
+        hint_client.request_hint(Hint::create(b"some hint".to_vec()));
+        let response = oracle_client.request_preimage(key).get();
+        let store = server_joiner.join().unwrap();
+
+        assert_eq!(response, preimage);
+        assert_eq!(store.hints, vec![b"some hint".to_vec()]);
+    }
 }
@@ -0,0 +1,81 @@
+//! The interface of the commitment schemes over small fields, and a
+//! placeholder for a FRI-based implementation.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_poly::{EvaluationDomain, Evaluations, Polynomial, Radix2EvaluationDomain as D};
+use sha3::{Digest, Keccak256};
+use std::fmt::Debug;
+
+/// A commitment scheme for polynomials given by their evaluations over a
+/// domain, which does not require a curve over the field
+pub trait FieldCommitmentScheme<F: PrimeField> {
+    /// The commitment to a polynomial
+    type Commitment: Clone + Debug + PartialEq;
+
+    /// The proof of the evaluation of a polynomial at a point
+    type OpeningProof: Clone + Debug;
+
+    /// Commits to the polynomial of evaluations `evals`
+    fn commit(&self, evals: &Evaluations<F, D<F>>) -> Self::Commitment;
+
+    /// Returns the evaluation at `point` of the polynomial of evaluations
+    /// `evals`, and the proof of this evaluation
+    fn open(&self, evals: &Evaluations<F, D<F>>, point: F) -> (F, Self::OpeningProof);
+
+    /// Checks that `eval` is the evaluation at `point` of the polynomial
+    /// committed in `commitment`
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        point: F,
+        eval: F,
+        proof: &Self::OpeningProof,
+    ) -> bool;
+}
+
+/// A placeholder for a FRI-based commitment scheme, with the same interface.
+/// The commitment is the Keccak256 hash of the evaluations, and an opening
+/// reveals all of them, so that it is neither succinct nor hiding. It is only
+/// meant to run the pipeline over small fields until FRI is implemented.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FriStub;
+
+fn hash_evaluations<F: PrimeField>(evals: &[F]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for eval in evals {
+        hasher.update(eval.into_bigint().to_bytes_le());
+    }
+    hasher.finalize().into()
+}
+
+impl<F: PrimeField> FieldCommitmentScheme<F> for FriStub {
+    type Commitment = [u8; 32];
+
+    type OpeningProof = Vec<F>;
+
+    fn commit(&self, evals: &Evaluations<F, D<F>>) -> Self::Commitment {
+        hash_evaluations(&evals.evals)
+    }
+
+    fn open(&self, evals: &Evaluations<F, D<F>>, point: F) -> (F, Self::OpeningProof) {
+        let eval = evals.interpolate_by_ref().evaluate(&point);
+        (eval, evals.evals.clone())
+    }
+
+    fn verify(
+        &self,
+        commitment: &Self::Commitment,
+        point: F,
+        eval: F,
+        proof: &Self::OpeningProof,
+    ) -> bool {
+        match D::<F>::new(proof.len()) {
+            Some(domain) if domain.size() == proof.len() => {
+                let evals = Evaluations::from_vec_and_domain(proof.clone(), domain);
+                hash_evaluations(proof) == *commitment
+                    && evals.interpolate().evaluate(&point) == eval
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! The Goldilocks field, of modulus `2^64 - 2^32 + 1`. Its multiplicative
+//! group has a subgroup of order `2^32`, so that the FFTs of the o1vm
+//! traces can be done over it.
+
+use ark_ff::fields::{Fp64, MontBackend, MontConfig};
+
+#[derive(MontConfig)]
+#[modulus = "18446744069414584321"]
+#[generator = "7"]
+pub struct GoldilocksConfig;
+
+/// An element of the Goldilocks field
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
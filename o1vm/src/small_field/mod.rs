@@ -0,0 +1,69 @@
+//! Experimental support for running the o1vm arithmetization over 64-bit
+//! fields, like the Goldilocks field, instead of the scalar fields of the
+//! Pasta or BN254 curves. It is only available with the `small-field`
+//! feature.
+//!
+//! Small fields make the field operations of the prover much cheaper, but
+//! they have no curve of the same size for an IPA or KZG commitment, so they
+//! are committed with a hash-based scheme implementing
+//! [`commitment::FieldCommitmentScheme`], for which only a placeholder is
+//! provided for now (see [`commitment::FriStub`]).
+//!
+//! The constraints must also check that they do not wrap around the modulus,
+//! which is more likely over a small field. [`FieldSize`] is used to know
+//! which ones can be used over a given field, and [`mips_constraints`] only
+//! returns the MIPS constraints over the fields where they are sound.
+
+pub mod commitment;
+pub mod goldilocks;
+
+use crate::{pickles::get_mips_constraints, E};
+use ark_ff::PrimeField;
+use thiserror::Error;
+
+/// The size of the field of the arithmetization
+pub trait FieldSize: PrimeField {
+    /// The number of bits of the modulus
+    const BITS: u32 = Self::MODULUS_BIT_SIZE;
+
+    /// Whether the field is small, i.e. whether its elements fit in 64 bits
+    const IS_SMALL: bool = Self::MODULUS_BIT_SIZE <= 64;
+
+    /// Returns `true` if the product of two values of `bits` bits fits in
+    /// the field without wrapping around the modulus, e.g. for the
+    /// constraints of the multiplications of 32-bit words.
+    fn fits_product(bits: u32) -> bool {
+        2 * bits < Self::MODULUS_BIT_SIZE
+    }
+}
+
+impl<F: PrimeField> FieldSize for F {}
+
+/// Errors that can arise when using a field for the arithmetization
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FieldSizeError {
+    #[error("the product of two {0}-bit words wraps around the {1}-bit modulus of the field")]
+    ProductWrapsAround(u32, u32),
+}
+
+/// The size of the words of the MIPS architecture
+const MIPS_WORD_BITS: u32 = 32;
+
+/// Returns the MIPS constraints over `F` (see [get_mips_constraints]).
+///
+/// # Errors
+///
+/// Will give error if the product of two 32-bit words wraps around the
+/// modulus of `F`, as the constraints relating the registers to their products
+/// (e.g. `hi * 2^32 + lo = rs * rt` for the multiplications) would then be
+/// unsound. This is the case of the Goldilocks field, for which the words must
+/// first be split into smaller limbs.
+pub fn mips_constraints<F: FieldSize>() -> Result<Vec<E<F>>, FieldSizeError> {
+    if !F::fits_product(MIPS_WORD_BITS) {
+        return Err(FieldSizeError::ProductWrapsAround(MIPS_WORD_BITS, F::BITS));
+    }
+    Ok(get_mips_constraints())
+}
+
+#[cfg(test)]
+mod tests;
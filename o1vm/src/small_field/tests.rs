@@ -0,0 +1,68 @@
+use super::{
+    commitment::{FieldCommitmentScheme, FriStub},
+    goldilocks::Goldilocks,
+    mips_constraints, FieldSize, FieldSizeError,
+};
+use ark_ff::{FftField, Field, One, UniformRand};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use mina_curves::pasta::Fp;
+
+#[test]
+fn test_goldilocks_field() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    assert_eq!(Goldilocks::BITS, 64);
+    assert!(Goldilocks::IS_SMALL);
+    assert_eq!(Goldilocks::TWO_ADICITY, 32);
+    // 2^64 = 2^32 - 1 mod p
+    assert_eq!(
+        Goldilocks::from(2u64).pow([64]),
+        Goldilocks::from((1u64 << 32) - 1)
+    );
+
+    let x = Goldilocks::rand(&mut rng);
+    assert_eq!(x * x.inverse().unwrap(), Goldilocks::one());
+}
+
+#[test]
+fn test_field_size() {
+    assert!(!Fp::IS_SMALL);
+    assert!(Fp::fits_product(32));
+    // the product of two 32-bit words can wrap around the Goldilocks modulus
+    assert!(!Goldilocks::fits_product(32));
+    assert!(Goldilocks::fits_product(16));
+}
+
+#[test]
+fn test_mips_constraints_field_size() {
+    assert!(!mips_constraints::<Fp>().unwrap().is_empty());
+    assert_eq!(
+        mips_constraints::<Goldilocks>().unwrap_err(),
+        FieldSizeError::ProductWrapsAround(32, 64)
+    );
+}
+
+#[test]
+fn test_fri_stub() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let domain = D::<Goldilocks>::new(1 << 6).unwrap();
+    let evals = Evaluations::from_vec_and_domain(
+        (0..domain.size())
+            .map(|_| Goldilocks::rand(&mut rng))
+            .collect(),
+        domain,
+    );
+
+    let commitment = FriStub.commit(&evals);
+    let point = Goldilocks::rand(&mut rng);
+    let (eval, proof) = FriStub.open(&evals, point);
+    assert!(FriStub.verify(&commitment, point, eval, &proof));
+
+    // a wrong evaluation is rejected
+    assert!(!FriStub.verify(&commitment, point, eval + Goldilocks::one(), &proof));
+
+    // so is a proof for other evaluations
+    let mut other_proof = proof.clone();
+    other_proof[0] += Goldilocks::one();
+    assert!(!FriStub.verify(&commitment, point, eval, &other_proof));
+}
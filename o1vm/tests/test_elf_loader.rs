@@ -17,3 +17,35 @@ fn test_correctly_parsing_elf() {
     // Which is the 17th
     assert_eq!(state.memory[0].index, 17);
 }
+
+#[test]
+fn test_parsing_elf_symbols_and_registers() {
+    use o1vm::elf_loader::{parse_elf, Architecture};
+
+    let curr_dir = std::env::current_dir().unwrap();
+    let path = curr_dir.join(std::path::PathBuf::from(
+        "resources/programs/riscv32im/fibonacci",
+    ));
+    let program = parse_elf(Architecture::RiscV32, &path).unwrap();
+
+    // This is the output we get by running readelf -s fibonacci
+    assert_eq!(
+        program.meta.find_address_symbol(0x1112c),
+        Some("_start".to_string())
+    );
+    assert_eq!(
+        program.meta.find_address_symbol(0x110d4 + 4),
+        Some("fibonacci".to_string())
+    );
+    assert_eq!(program.meta.find_address_symbol(0x10000), None);
+
+    // The stack pointer is set, and the heap starts after the code
+    assert_eq!(
+        program.state.registers[Architecture::RiscV32.stack_pointer_register()],
+        Architecture::RiscV32.initial_stack_pointer()
+    );
+    assert_eq!(program.state.heap, 18 * 4096);
+
+    // The program is not a MIPS one
+    assert!(parse_elf(Architecture::Mips, &path).is_err());
+}
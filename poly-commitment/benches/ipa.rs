@@ -7,6 +7,7 @@ use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSpon
 use poly_commitment::{
     commitment::CommitmentCurve,
     ipa::{DensePolynomialOrEvaluations, SRS},
+    msm::{ArkworksMsm, Pippenger},
     PolyComm, SRS as _,
 };
 
@@ -54,5 +55,35 @@ fn benchmark_ipa_open(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, benchmark_ipa_open);
+fn benchmark_ipa_commit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("IPA commit");
+    let mut rng = o1_utils::tests::make_test_rng(None);
+
+    for log_n in [10, 14].into_iter() {
+        let n = 1 << log_n;
+        let srs = SRS::<Vesta>::create(n);
+        let poly_coefficients: Vec<Fp> = (0..n).map(|_| Fp::rand(&mut rng)).collect();
+        let poly = DensePolynomial::<Fp>::from_coefficients_vec(poly_coefficients);
+
+        group.bench_with_input(BenchmarkId::new("arkworks", n), &n, |b, _| {
+            b.iter(|| black_box(srs.commit_non_hiding_with(&poly, 1, &ArkworksMsm)))
+        });
+        for window in [8, 12] {
+            let backend = Pippenger::with_window(window);
+            group.bench_with_input(
+                BenchmarkId::new(format!("pippenger window {window}"), n),
+                &n,
+                |b, _| b.iter(|| black_box(srs.commit_non_hiding_with(&poly, 1, &backend))),
+            );
+        }
+
+        let srs_with_table = SRS::<Vesta>::create(n);
+        srs_with_table.precompute_msm_table(8);
+        group.bench_with_input(BenchmarkId::new("precomputed window 8", n), &n, |b, _| {
+            b.iter(|| black_box(srs_with_table.commit_non_hiding(&poly, 1)))
+        });
+    }
+}
+
+criterion_group!(benches, benchmark_ipa_open, benchmark_ipa_commit);
 criterion_main!(benches);
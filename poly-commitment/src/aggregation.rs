@@ -0,0 +1,282 @@
+//! This module implements [`OpeningAccumulator`], which aggregates the
+//! openings of any number of polynomials at any number of points into a
+//! single IPA opening proof.
+//!
+//! The openings are registered one by one with [`OpeningAccumulator::add`],
+//! and proven at once with [`OpeningAccumulator::finalize`]. As an IPA proof
+//! opens all its polynomials at all its points, each polynomial is opened at
+//! all the registered points, and the resulting [`AggregatedOpening`]
+//! contains all these evaluations.
+//!
+//! The challenges `polyscale` and `evalscale` combining the polynomials and
+//! the points must be sampled by the caller after absorbing the commitments
+//! and the evaluations, which can be computed beforehand with
+//! [`OpeningAccumulator::evaluations`].
+
+use crate::{
+    commitment::{combined_inner_product, BatchEvaluationProof, EndoCurve, Evaluation},
+    ipa::{DensePolynomialOrEvaluations, OpeningProof, SRS},
+    PolyComm,
+};
+use ark_ff::{PrimeField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, Polynomial, Radix2EvaluationDomain as D,
+};
+use mina_poseidon::FqSponge;
+use rand_core::{CryptoRng, RngCore};
+use std::cmp::{max, min};
+
+/// The openings of polynomials at points, to be proven with a single IPA
+/// opening proof
+pub struct OpeningAccumulator<'a, G: EndoCurve> {
+    /// The polynomials, with their blinders if they have been committed with
+    /// a hiding commitment
+    polys: Vec<(
+        &'a DensePolynomial<G::ScalarField>,
+        Option<PolyComm<G::ScalarField>>,
+    )>,
+    /// The evaluation points, without duplicates
+    points: Vec<G::ScalarField>,
+}
+
+impl<'a, G: EndoCurve> Default for OpeningAccumulator<'a, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, G: EndoCurve> OpeningAccumulator<'a, G> {
+    /// Creates an empty accumulator
+    pub fn new() -> Self {
+        OpeningAccumulator {
+            polys: vec![],
+            points: vec![],
+        }
+    }
+
+    /// Registers the opening of `poly` at `point`, `poly` being committed
+    /// without blinders. Returns the index of `poly` in the accumulator,
+    /// which is the same for all the openings of the same polynomial.
+    pub fn add(
+        &mut self,
+        poly: &'a DensePolynomial<G::ScalarField>,
+        point: G::ScalarField,
+    ) -> usize {
+        self.add_opening(poly, None, point)
+    }
+
+    /// Same as [`OpeningAccumulator::add`], for a polynomial committed with
+    /// the blinders `blinders`, one per chunk of the commitment.
+    pub fn add_blinded(
+        &mut self,
+        poly: &'a DensePolynomial<G::ScalarField>,
+        blinders: PolyComm<G::ScalarField>,
+        point: G::ScalarField,
+    ) -> usize {
+        self.add_opening(poly, Some(blinders), point)
+    }
+
+    fn add_opening(
+        &mut self,
+        poly: &'a DensePolynomial<G::ScalarField>,
+        blinders: Option<PolyComm<G::ScalarField>>,
+        point: G::ScalarField,
+    ) -> usize {
+        if !self.points.contains(&point) {
+            self.points.push(point);
+        }
+        match self.polys.iter().position(|(p, _)| std::ptr::eq(*p, poly)) {
+            Some(index) => index,
+            None => {
+                self.polys.push((poly, blinders));
+                self.polys.len() - 1
+            }
+        }
+    }
+
+    /// Returns the number of polynomials of the accumulator
+    pub fn num_polys(&self) -> usize {
+        self.polys.len()
+    }
+
+    /// Returns the evaluation points, in the order of the evaluations
+    pub fn points(&self) -> &[G::ScalarField] {
+        &self.points
+    }
+
+    /// Returns the number of chunks of the commitment to the polynomial
+    /// `index`, for an SRS of size `srs_length`
+    fn num_chunks(&self, index: usize, srs_length: usize) -> usize {
+        let (poly, blinders) = &self.polys[index];
+        match blinders {
+            Some(blinders) => blinders.len(),
+            None => max(1, (poly.coeffs.len() + srs_length - 1) / srs_length),
+        }
+    }
+
+    /// Returns the evaluations of the polynomials at the points, chunk by
+    /// chunk for an SRS of size `srs_length`: `evaluations[i][j][k]` is the
+    /// evaluation of the chunk `k` of the polynomial `i` at the point `j`.
+    pub fn evaluations(&self, srs_length: usize) -> Vec<Vec<Vec<G::ScalarField>>> {
+        self.polys
+            .iter()
+            .enumerate()
+            .map(|(index, (poly, _))| {
+                let num_chunks = self.num_chunks(index, srs_length);
+                let chunks: Vec<_> = (0..num_chunks)
+                    .map(|k| {
+                        let start = min(k * srs_length, poly.coeffs.len());
+                        let end = min((k + 1) * srs_length, poly.coeffs.len());
+                        DensePolynomial::from_coefficients_slice(&poly.coeffs[start..end])
+                    })
+                    .collect();
+                self.points
+                    .iter()
+                    .map(|point| chunks.iter().map(|chunk| chunk.evaluate(point)).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Creates the opening proof of all the polynomials at all the points,
+    /// combined with `polyscale` and `evalscale`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no opening has been registered.
+    #[allow(clippy::too_many_arguments)]
+    pub fn finalize<EFqSponge, RNG>(
+        self,
+        srs: &SRS<G>,
+        group_map: &G::Map,
+        polyscale: G::ScalarField,
+        evalscale: G::ScalarField,
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> AggregatedOpening<G>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        assert!(!self.points.is_empty(), "no opening to prove");
+
+        let srs_length = srs.g.len();
+        let evaluations = self.evaluations(srs_length);
+        let plnms: Vec<(DensePolynomialOrEvaluations<_, D<G::ScalarField>>, _)> = self
+            .polys
+            .iter()
+            .enumerate()
+            .map(|(index, (poly, blinders))| {
+                let blinders = blinders.clone().unwrap_or_else(|| {
+                    PolyComm::new(vec![
+                        G::ScalarField::zero();
+                        self.num_chunks(index, srs_length)
+                    ])
+                });
+                (
+                    DensePolynomialOrEvaluations::DensePolynomial(*poly),
+                    blinders,
+                )
+            })
+            .collect();
+
+        let proof = srs.open(
+            group_map,
+            &plnms,
+            &self.points,
+            polyscale,
+            evalscale,
+            sponge,
+            rng,
+        );
+
+        AggregatedOpening {
+            points: self.points,
+            evaluations,
+            proof,
+        }
+    }
+}
+
+/// The openings of polynomials at points, proven with a single IPA opening
+/// proof (see [`OpeningAccumulator`])
+#[derive(Clone, Debug)]
+pub struct AggregatedOpening<G: EndoCurve> {
+    /// The evaluation points
+    pub points: Vec<G::ScalarField>,
+    /// The chunked evaluations of the polynomials at the points, as given by
+    /// [`OpeningAccumulator::evaluations`]
+    pub evaluations: Vec<Vec<Vec<G::ScalarField>>>,
+    /// The opening proof
+    pub proof: OpeningProof<G>,
+}
+
+impl<G: EndoCurve> AggregatedOpening<G> {
+    /// Returns the chunked evaluation of the polynomial `index` at `point`,
+    /// if it has been opened at this point
+    pub fn evaluation(&self, index: usize, point: G::ScalarField) -> Option<&[G::ScalarField]> {
+        let j = self.points.iter().position(|p| *p == point)?;
+        self.evaluations
+            .get(index)
+            .map(|evaluations| evaluations[j].as_slice())
+    }
+
+    /// Verifies the opening proof against `commitments`, the commitments to
+    /// the polynomials in the order of their indices in the accumulator.
+    /// `polyscale`, `evalscale` and `sponge` must be the ones given to
+    /// [`OpeningAccumulator::finalize`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify<EFqSponge, RNG>(
+        &self,
+        srs: &SRS<G>,
+        group_map: &G::Map,
+        commitments: &[PolyComm<G>],
+        polyscale: G::ScalarField,
+        evalscale: G::ScalarField,
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        if commitments.len() != self.evaluations.len()
+            || commitments
+                .iter()
+                .zip(&self.evaluations)
+                .any(|(commitment, evaluations)| {
+                    evaluations.len() != self.points.len()
+                        || evaluations.iter().any(|e| e.len() != commitment.len())
+                })
+        {
+            return false;
+        }
+
+        let combined_inner_product =
+            combined_inner_product(&polyscale, &evalscale, &self.evaluations);
+        let evaluations = commitments
+            .iter()
+            .zip(&self.evaluations)
+            .map(|(commitment, evaluations)| Evaluation {
+                commitment: commitment.clone(),
+                evaluations: evaluations.clone(),
+            })
+            .collect();
+
+        srs.verify(
+            group_map,
+            &mut [BatchEvaluationProof {
+                sponge,
+                evaluations,
+                evaluation_points: self.points.clone(),
+                polyscale,
+                evalscale,
+                opening: &self.proof,
+                combined_inner_product,
+            }],
+            rng,
+        )
+    }
+}
@@ -20,8 +20,10 @@ use ark_ec::{
     CurveGroup, Group,
 };
 use ark_ff::{BitIteratorBE, Field, One, PrimeField, Zero};
+use ark_std::{cfg_into_iter, cfg_iter, cfg_iter_mut};
 use itertools::Itertools;
 use mina_poseidon::sponge::ScalarChallenge;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use std::ops::AddAssign;
 
@@ -92,8 +94,7 @@ fn batch_add_assign_no_branch<P: SWCurveConfig>(
     v0: &mut [SWJAffine<P>],
     v1: &[SWJAffine<P>],
 ) {
-    denominators
-        .par_iter_mut()
+    cfg_iter_mut!(denominators)
         .enumerate()
         .for_each(|(i, denom)| {
             let p0 = v0[i];
@@ -104,10 +105,9 @@ fn batch_add_assign_no_branch<P: SWCurveConfig>(
 
     ark_ff::batch_inversion::<P::BaseField>(denominators);
 
-    denominators
-        .par_iter()
-        .zip(v0.par_iter_mut())
-        .zip(v1.par_iter())
+    cfg_iter!(denominators)
+        .zip(cfg_iter_mut!(v0))
+        .zip(cfg_iter!(v1))
         .for_each(|((d, p0), p1)| {
             let s = (p0.y - p1.y) * d;
             let x = s.square() - p0.x - p1.x;
@@ -123,10 +123,9 @@ pub fn batch_add_assign<P: SWCurveConfig>(
     v0: &mut [SWJAffine<P>],
     v1: &[SWJAffine<P>],
 ) {
-    denominators
-        .par_iter_mut()
-        .zip(v0.par_iter())
-        .zip(v1.par_iter())
+    cfg_iter_mut!(denominators)
+        .zip(cfg_iter!(v0))
+        .zip(cfg_iter!(v1))
         .for_each(|((denom, p0), p1)| {
             let d = if p0.x == p1.x {
                 if p1.y.is_zero() {
@@ -142,10 +141,9 @@ pub fn batch_add_assign<P: SWCurveConfig>(
 
     ark_ff::batch_inversion::<P::BaseField>(denominators);
 
-    denominators
-        .par_iter()
-        .zip(v0.par_iter_mut())
-        .zip(v1.par_iter())
+    cfg_iter!(denominators)
+        .zip(cfg_iter_mut!(v0))
+        .zip(cfg_iter!(v1))
         .for_each(|((d, p0), p1)| {
             if p1.is_zero() {
             } else if p0.is_zero() {
@@ -277,11 +275,11 @@ fn affine_window_combine_base<P: SWCurveConfig>(
 }
 
 fn batch_endo_in_place<P: SWCurveConfig>(endo_coeff: P::BaseField, ps: &mut [SWJAffine<P>]) {
-    ps.par_iter_mut().for_each(|p| p.x *= endo_coeff);
+    cfg_iter_mut!(ps).for_each(|p| p.x *= endo_coeff);
 }
 
 fn batch_negate_in_place<P: SWCurveConfig>(ps: &mut [SWJAffine<P>]) {
-    ps.par_iter_mut().for_each(|p| {
+    cfg_iter_mut!(ps).for_each(|p| {
         p.y = -p.y;
     });
 }
@@ -345,18 +343,16 @@ fn batch_double_in_place<P: SWCurveConfig>(
     denominators: &mut Vec<P::BaseField>,
     points: &mut [SWJAffine<P>],
 ) {
-    denominators
-        .par_iter_mut()
-        .zip(points.par_iter())
+    cfg_iter_mut!(denominators)
+        .zip(cfg_iter!(points))
         .for_each(|(d, p)| {
             *d = p.y.double();
         });
     ark_ff::batch_inversion::<P::BaseField>(denominators);
 
     // TODO: Use less memory
-    denominators
-        .par_iter()
-        .zip(points.par_iter_mut())
+    cfg_iter!(denominators)
+        .zip(cfg_iter_mut!(points))
         .for_each(|(d, p)| {
             let sq = p.x.square();
             let s = (sq.double() + sq + P::COEFF_A) * d;
@@ -421,8 +417,7 @@ pub fn affine_window_combine<P: SWCurveConfig>(
 ) -> Vec<SWJAffine<P>> {
     const CHUNK_SIZE: usize = 10_000;
     let b: Vec<_> = g1.chunks(CHUNK_SIZE).zip(g2.chunks(CHUNK_SIZE)).collect();
-    let v: Vec<_> = b
-        .into_par_iter()
+    let v: Vec<_> = cfg_into_iter!(b)
         .map(|(v1, v2)| affine_window_combine_base(v1, v2, x1, x2))
         .collect();
     v.concat()
@@ -440,8 +435,7 @@ pub fn affine_window_combine_one_endo<P: SWCurveConfig>(
 ) -> Vec<SWJAffine<P>> {
     const CHUNK_SIZE: usize = 4096;
     let b: Vec<_> = g1.chunks(CHUNK_SIZE).zip(g2.chunks(CHUNK_SIZE)).collect();
-    let v: Vec<_> = b
-        .into_par_iter()
+    let v: Vec<_> = cfg_into_iter!(b)
         .map(|(v1, v2)| affine_window_combine_one_endo_base(endo_coeff, v1, v2, chal.clone()))
         .collect();
     v.concat()
@@ -453,8 +447,7 @@ pub fn affine_window_combine_one<P: SWCurveConfig>(
 ) -> Vec<SWJAffine<P>> {
     const CHUNK_SIZE: usize = 10_000;
     let b: Vec<_> = g1.chunks(CHUNK_SIZE).zip(g2.chunks(CHUNK_SIZE)).collect();
-    let v: Vec<_> = b
-        .into_par_iter()
+    let v: Vec<_> = cfg_into_iter!(b)
         .map(|(v1, v2)| affine_window_combine_one_base(v1, v2, x2))
         .collect();
     v.concat()
@@ -468,8 +461,7 @@ pub fn window_combine<G: AffineRepr>(
 ) -> Vec<G> {
     let mut g_proj: Vec<G::Group> = {
         let pairs: Vec<_> = g_lo.iter().zip(g_hi).collect();
-        pairs
-            .into_par_iter()
+        cfg_into_iter!(pairs)
             .map(|(lo, hi)| window_shamir::<G>(x_lo, *lo, x_hi, *hi))
             .collect()
     };
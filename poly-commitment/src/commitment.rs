@@ -6,6 +6,7 @@
 //! scaling factor scalar producing the batched opening proof
 //! 3. Verify batch of batched opening proofs
 
+use crate::error::CommitmentError;
 use ark_ec::{
     models::short_weierstrass::Affine as SWJAffine, short_weierstrass::SWCurveConfig, AffineRepr,
     CurveGroup, VariableBaseMSM,
@@ -23,7 +24,7 @@ use serde_with::{
 use std::{
     iter::Iterator,
     marker::PhantomData,
-    ops::{Add, AddAssign, Sub},
+    ops::{Add, AddAssign, Mul, Sub},
 };
 
 /// Represent a polynomial commitment when the type is instantiated with a
@@ -103,6 +104,58 @@ where
     pub blinders: PolyComm<G::ScalarField>,
 }
 
+impl<G: CommitmentCurve> BlindedCommitment<G> {
+    /// Adds the commitments and their blinders, i.e. commits to the sum of the
+    /// polynomials with the sum of the blinders.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the commitments, or their blinders, have different
+    /// numbers of chunks.
+    pub fn checked_add(&self, other: &Self) -> Result<Self, CommitmentError> {
+        Self::combine(&[self, other], &[G::ScalarField::one(); 2])
+    }
+
+    /// Multiplies the commitment and its blinders by `c`.
+    pub fn scale(&self, c: G::ScalarField) -> Self {
+        BlindedCommitment {
+            commitment: self.commitment.scale(c),
+            blinders: PolyComm::new(self.blinders.chunks.iter().map(|b| *b * c).collect()),
+        }
+    }
+
+    /// Combines the commitments `comms` and their blinders with the scalars
+    /// `scalars`, i.e. commits to the linear combination of the polynomials,
+    /// so that the result can be opened with the combination of the blinders.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the numbers of commitments and scalars differ, or if
+    /// the commitments or the blinders have different numbers of chunks.
+    pub fn combine(comms: &[&Self], scalars: &[G::ScalarField]) -> Result<Self, CommitmentError> {
+        for comm in comms {
+            if comm.blinders.chunks.len() != comm.commitment.chunks.len() {
+                return Err(CommitmentError::BlindersDontMatch(
+                    comm.blinders.chunks.len(),
+                    comm.commitment.chunks.len(),
+                ));
+            }
+        }
+        let commitments: Vec<_> = comms.iter().map(|comm| &comm.commitment).collect();
+        let commitment = PolyComm::checked_multi_scalar_mul(&commitments, scalars)?;
+        let mut blinders = vec![G::ScalarField::zero(); commitment.chunks.len()];
+        for (comm, scalar) in comms.iter().zip(scalars) {
+            for (acc, blinder) in blinders.iter_mut().zip(&comm.blinders.chunks) {
+                *acc += *blinder * scalar;
+            }
+        }
+        Ok(BlindedCommitment {
+            commitment,
+            blinders: PolyComm::new(blinders),
+        })
+    }
+}
+
 impl<T> PolyComm<T> {
     pub fn new(chunks: Vec<T>) -> Self {
         Self { chunks }
@@ -309,13 +362,85 @@ impl<'a, 'b, C: AffineRepr + Sub<Output = C::Group>> Sub<&'a PolyComm<C>> for &'
     }
 }
 
+impl<'a, C: AffineRepr> Mul<C::ScalarField> for &'a PolyComm<C> {
+    type Output = PolyComm<C>;
+
+    fn mul(self, c: C::ScalarField) -> PolyComm<C> {
+        self.scale(c)
+    }
+}
+
 impl<C: AffineRepr> PolyComm<C> {
+    /// Multiplies each chunk of the commitment by `c`, i.e. commits to the
+    /// polynomial multiplied by `c`.
     pub fn scale(&self, c: C::ScalarField) -> PolyComm<C> {
         PolyComm {
             chunks: self.chunks.iter().map(|g| g.mul(c).into_affine()).collect(),
         }
     }
 
+    /// Adds the commitments chunk by chunk, i.e. commits to the sum of the
+    /// polynomials. Contrary to the `+` operator, which keeps the extra chunks
+    /// of the longest commitment, the commitments must have the same number of
+    /// chunks.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the commitments have different numbers of chunks.
+    pub fn checked_add(&self, other: &PolyComm<C>) -> Result<PolyComm<C>, CommitmentError> {
+        if self.chunks.len() != other.chunks.len() {
+            return Err(CommitmentError::ChunksDontMatch(
+                self.chunks.len(),
+                other.chunks.len(),
+            ));
+        }
+        Ok(self + other)
+    }
+
+    /// Subtracts the commitments chunk by chunk, see [PolyComm::checked_add].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the commitments have different numbers of chunks.
+    pub fn checked_sub(&self, other: &PolyComm<C>) -> Result<PolyComm<C>, CommitmentError>
+    where
+        C: Sub<Output = C::Group>,
+    {
+        if self.chunks.len() != other.chunks.len() {
+            return Err(CommitmentError::ChunksDontMatch(
+                self.chunks.len(),
+                other.chunks.len(),
+            ));
+        }
+        Ok(self - other)
+    }
+
+    /// Combines the commitments `com` with the scalars `elm` like
+    /// [PolyComm::multi_scalar_mul], the commitments having the same number of
+    /// chunks.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the numbers of commitments and scalars differ, or if
+    /// the commitments have different numbers of chunks.
+    pub fn checked_multi_scalar_mul(
+        com: &[&PolyComm<C>],
+        elm: &[C::ScalarField],
+    ) -> Result<Self, CommitmentError> {
+        if com.len() != elm.len() {
+            return Err(CommitmentError::ScalarsDontMatch(elm.len(), com.len()));
+        }
+        if let Some(first) = com.first() {
+            if let Some(other) = com.iter().find(|c| c.chunks.len() != first.chunks.len()) {
+                return Err(CommitmentError::ChunksDontMatch(
+                    first.chunks.len(),
+                    other.chunks.len(),
+                ));
+            }
+        }
+        Ok(Self::multi_scalar_mul(com, elm))
+    }
+
     /// Performs a multi-scalar multiplication between scalars `elm` and commitments `com`.
     /// If both are empty, returns a commitment of length 1 containing the point at infinity.
     ///
@@ -6,4 +6,34 @@ pub enum CommitmentError {
         "the length of the given blinders ({0}) don't match the length of the commitment ({1})"
     )]
     BlindersDontMatch(usize, usize),
+    #[error("the commitments have different numbers of chunks ({0} and {1})")]
+    ChunksDontMatch(usize, usize),
+    #[error("the number of scalars ({0}) doesn't match the number of commitments ({1})")]
+    ScalarsDontMatch(usize, usize),
+}
+
+#[derive(Error, Debug)]
+pub enum SRSError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the points of the SRS could not be deserialized: {0}")]
+    Serialization(#[from] ark_serialize::SerializationError),
+    #[error("invalid SRS file: {0}")]
+    InvalidFormat(&'static str),
+    #[error("the SRS file is for another field")]
+    FieldMismatch,
+    #[error("the SRS has {0} points, but at least {1} are required")]
+    TooShort(usize, usize),
+    #[error("the point {0} of the SRS is not a point of the prime order subgroup")]
+    InvalidPoint(usize),
+    #[error("the digest of the SRS does not match the expected one")]
+    DigestMismatch,
+    #[error("the SRS does not start with the generator of the curve")]
+    InvalidGenerator,
+    #[error("the points of the SRS are not the powers of a single secret")]
+    InconsistentPowers,
+    #[error("the point {0} of the SRS is not the one generated from the seed")]
+    NotGenerated(usize),
+    #[error("the precomputation was made for another SRS")]
+    PrecomputationMismatch,
 }
@@ -10,6 +10,7 @@ use crate::{
     },
     error::CommitmentError,
     hash_map_cache::HashMapCache,
+    msm::{ArkworksMsm, MsmBackend, PrecomputedTable},
     BlindedCommitment, PolyComm, PolynomialsToCombine, SRS as SRSTrait,
 };
 use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
@@ -19,6 +20,7 @@ use ark_poly::{
     Radix2EvaluationDomain as D,
 };
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_into_iter, cfg_iter, cfg_iter_mut};
 use blake2::{Blake2b512, Digest};
 use groupmap::GroupMap;
 use mina_poseidon::{sponge::ScalarChallenge, FqSponge};
@@ -26,11 +28,13 @@ use o1_utils::{
     field_helpers::{inner_prod, pows},
     math, ExtendedDensePolynomial,
 };
+use once_cell::sync::OnceCell;
 use rand::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use std::{cmp::min, iter::Iterator, ops::AddAssign};
+use std::{cmp::min, iter::Iterator, ops::AddAssign, sync::Arc};
 
 /// A formal sum of the form
 /// `s_0 * p_0 + ... s_n * p_n`
@@ -68,15 +72,13 @@ impl<'a, F: Field> ScaledChunkedPolynomial<F, &'a [F]> {
         // Note: using a reference to avoid reallocation of the result.
         let mut res = DensePolynomial::<F>::zero();
 
-        let scaled: Vec<_> = self
-            .0
-            .par_iter()
+        let scaled: Vec<_> = cfg_iter!(self.0)
             .map(|(scale, segment)| {
                 let scale = *scale;
                 // We simply scale each coefficients.
                 // It is simply because DensePolynomial doesn't have a method
                 // `scale`.
-                let v = segment.par_iter().map(|x| scale * *x).collect();
+                let v = cfg_iter!(segment).map(|x| scale * *x).collect();
                 DensePolynomial::from_coefficients_vec(v)
             })
             .collect();
@@ -157,8 +159,7 @@ pub fn combine_polys<G: CommitmentCurve, D: EvaluationDomain<G::ScalarField>>(
             DensePolynomialOrEvaluations::Evaluations(evals_i, sub_domain) => {
                 let stride = evals_i.evals.len() / sub_domain.size();
                 let evals = &evals_i.evals;
-                plnm_evals_part
-                    .par_iter_mut()
+                cfg_iter_mut!(plnm_evals_part)
                     .enumerate()
                     .for_each(|(i, x)| {
                         *x += scale * evals[i * stride];
@@ -229,6 +230,11 @@ pub struct SRS<G> {
     /// Commitments to Lagrange bases, per domain size
     #[serde(skip)]
     pub lagrange_bases: HashMapCache<usize, Vec<PolyComm<G>>>,
+
+    /// Multiples of the points `g`, used to commit to polynomials in
+    /// coefficient form once computed (see [SRS::precompute_msm_table])
+    #[serde(skip)]
+    pub msm_table: Arc<OnceCell<PrecomputedTable<G>>>,
 }
 
 impl<G> PartialEq for SRS<G>
@@ -291,8 +297,75 @@ where
     G::of_coordinates(x, y).mul_by_cofactor()
 }
 
+/// Returns the `i`-th point of the SRS generated by [`SRS::create`], i.e. the
+/// hash to the curve of the index of the point
+pub(crate) fn generated_point<G: CommitmentCurve>(map: &G::Map, i: usize) -> G {
+    let mut h = Blake2b512::new();
+    h.update((i as u32).to_be_bytes());
+    point_of_random_bytes(map, &h.finalize())
+}
+
+/// Returns the group element used for blinding commitments by the SRS created
+/// in this module
+pub(crate) fn blinding_point<G: CommitmentCurve>(map: &G::Map) -> G {
+    let mut h = Blake2b512::new();
+    h.update("srs_misc".as_bytes());
+    // FIXME: This is for retrocompatibility with a previous version
+    // that was using a list initialisation. It is not necessary.
+    h.update(0_u32.to_be_bytes());
+    point_of_random_bytes(map, &h.finalize())
+}
+
 /// Additional methods for the SRS structure
 impl<G: CommitmentCurve> SRS<G> {
+    /// Precomputes the multiples of the points `g` for windows of `window`
+    /// bits (see [PrecomputedTable]), so that the next commitments to
+    /// polynomials in coefficient form use them instead of a variable-base
+    /// MSM. This speeds up the commitments when the same SRS is used for many
+    /// proofs, at the cost of storing `ceil(255 / window)` points per point of
+    /// the SRS. The table is shared by the clones of the SRS.
+    ///
+    /// Does nothing if the table has already been computed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `window` is not between 1 and
+    /// [`MAX_WINDOW`](crate::msm::MAX_WINDOW).
+    pub fn precompute_msm_table(&self, window: usize) {
+        self.msm_table
+            .get_or_init(|| PrecomputedTable::new(&self.g, window));
+    }
+
+    /// Same as [SRSTrait::commit_non_hiding], but computing the MSMs with
+    /// `backend` instead of the precomputed table or the default backend.
+    pub fn commit_non_hiding_with<M: MsmBackend<G>>(
+        &self,
+        plnm: &DensePolynomial<G::ScalarField>,
+        num_chunks: usize,
+        backend: &M,
+    ) -> PolyComm<G> {
+        let is_zero = plnm.is_zero();
+
+        let coeffs: Vec<_> = plnm.iter().map(|c| c.into_bigint()).collect();
+
+        // chunk while commiting
+        let mut chunks = vec![];
+        if is_zero {
+            chunks.push(G::zero());
+        } else {
+            coeffs.chunks(self.g.len()).for_each(|coeffs_chunk| {
+                let chunk = backend.msm(&self.g, coeffs_chunk);
+                chunks.push(chunk.into_affine());
+            });
+        }
+
+        for _ in chunks.len()..num_chunks {
+            chunks.push(G::zero());
+        }
+
+        PolyComm::<G>::new(chunks)
+    }
+
     /// This function verifies a batch of polynomial commitment opening proofs.
     /// Return `true` if the verification is successful, `false` otherwise.
     pub fn verify<EFqSponge, RNG>(
@@ -301,6 +374,80 @@ impl<G: CommitmentCurve> SRS<G> {
         batch: &mut [BatchEvaluationProof<G, EFqSponge, OpeningProof<G>>],
         rng: &mut RNG,
     ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        self.verify_batch(group_map, batch, rng, true).is_some()
+    }
+
+    /// Verify a batch of polynomial commitment opening proofs like
+    /// [SRS::verify], except that the `sg` component of each proof is not
+    /// checked against the challenges of the proof. This check, which requires
+    /// an MSM of the size of the SRS, is deferred: the challenges and the `sg`
+    /// component of each proof are returned, and can be checked later, possibly
+    /// in batch with other proofs, using [SRS::check_accumulators].
+    /// Return `None` if the verification fails.
+    pub fn verify_deferred<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [BatchEvaluationProof<G, EFqSponge, OpeningProof<G>>],
+        rng: &mut RNG,
+    ) -> Option<Vec<(Vec<G::ScalarField>, G)>>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+    {
+        self.verify_batch(group_map, batch, rng, false)
+    }
+
+    /// Check that the `sg` component of each accumulator `(chals, sg)` is the
+    /// commitment to the polynomial `b_poly(chals, X)`. The checks are
+    /// combined with random scalars into a single MSM.
+    /// Return `true` if all the accumulators are correct.
+    pub fn check_accumulators<RNG>(
+        &self,
+        accumulators: &[(Vec<G::ScalarField>, G)],
+        rng: &mut RNG,
+    ) -> bool
+    where
+        RNG: RngCore + CryptoRng,
+    {
+        let padded_length = accumulators
+            .iter()
+            .map(|(chals, _)| 1 << chals.len())
+            .fold(self.g.len(), std::cmp::max);
+        let mut points = self.g.clone();
+        points.extend(vec![G::zero(); padded_length - self.g.len()]);
+        let mut scalars = vec![G::ScalarField::zero(); padded_length];
+
+        let rand_base = G::ScalarField::rand(rng);
+        let mut rand_base_i = G::ScalarField::one();
+        for (chals, sg) in accumulators {
+            for (i, s) in b_poly_coefficients(chals).iter().enumerate() {
+                scalars[i] += rand_base_i * s;
+            }
+            points.push(*sg);
+            scalars.push(-rand_base_i);
+            rand_base_i *= &rand_base;
+        }
+
+        let scalars: Vec<_> = scalars.iter().map(|x| x.into_bigint()).collect();
+        G::Group::msm_bigint(&points, &scalars) == G::Group::zero()
+    }
+
+    /// Verify the batch, checking the `sg` components only if `check_sg` is
+    /// set. Return the accumulators `(chals, sg)` of the proofs if the
+    /// verification succeeds.
+    fn verify_batch<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [BatchEvaluationProof<G, EFqSponge, OpeningProof<G>>],
+        rng: &mut RNG,
+        check_sg: bool,
+    ) -> Option<Vec<(Vec<G::ScalarField>, G)>>
     where
         EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
         RNG: RngCore + CryptoRng,
@@ -350,6 +497,8 @@ impl<G: CommitmentCurve> SRS<G> {
         let mut rand_base_i = G::ScalarField::one();
         let mut sg_rand_base_i = G::ScalarField::one();
 
+        let mut accumulators = Vec::with_capacity(batch.len());
+
         for BatchEvaluationProof {
             sponge,
             evaluation_points,
@@ -360,6 +509,12 @@ impl<G: CommitmentCurve> SRS<G> {
             combined_inner_product,
         } in batch.iter_mut()
         {
+            // The proofs may have fewer rounds than the SRS allows, see
+            // [SRS::open_with_rounds], but not more
+            if opening.lr.len() > max_rounds {
+                return None;
+            }
+
             sponge.absorb_fr(&[shift_scalar::<G>(*combined_inner_product)]);
 
             let t = sponge.challenge_fq();
@@ -387,29 +542,34 @@ impl<G: CommitmentCurve> SRS<G> {
                 res
             };
 
-            let s = b_poly_coefficients(&chal);
-
             let neg_rand_base_i = -rand_base_i;
 
-            // TERM
-            // - rand_base_i z1 G
-            //
-            // we also add -sg_rand_base_i * G to check correctness of sg.
-            points.push(opening.sg);
-            scalars.push(neg_rand_base_i * opening.z1 - sg_rand_base_i);
-
-            // Here we add
-            // sg_rand_base_i * ( < s, self.g > )
-            // =
-            // < sg_rand_base_i s, self.g >
-            //
-            // to check correctness of the sg component.
-            {
-                let terms: Vec<_> = s.par_iter().map(|s| sg_rand_base_i * s).collect();
+            if check_sg {
+                let s = b_poly_coefficients(&chal);
+
+                // TERM
+                // - rand_base_i z1 G
+                //
+                // we also add -sg_rand_base_i * G to check correctness of sg.
+                points.push(opening.sg);
+                scalars.push(neg_rand_base_i * opening.z1 - sg_rand_base_i);
+
+                // Here we add
+                // sg_rand_base_i * ( < s, self.g > )
+                // =
+                // < sg_rand_base_i s, self.g >
+                //
+                // to check correctness of the sg component.
+                let terms: Vec<_> = cfg_iter!(s).map(|s| sg_rand_base_i * s).collect();
 
                 for (i, term) in terms.iter().enumerate() {
                     scalars[i + 1] += term;
                 }
+            } else {
+                // TERM
+                // - rand_base_i z1 G
+                points.push(opening.sg);
+                scalars.push(neg_rand_base_i * opening.z1);
             }
 
             // TERM
@@ -453,13 +613,19 @@ impl<G: CommitmentCurve> SRS<G> {
             scalars.push(rand_base_i);
             points.push(opening.delta);
 
+            accumulators.push((chal, opening.sg));
+
             rand_base_i *= &rand_base;
             sg_rand_base_i *= &sg_rand_base;
         }
 
         // verify the equation
         let scalars: Vec<_> = scalars.iter().map(|x| x.into_bigint()).collect();
-        G::Group::msm_bigint(&points, &scalars) == G::Group::zero()
+        if G::Group::msm_bigint(&points, &scalars) == G::Group::zero() {
+            Some(accumulators)
+        } else {
+            None
+        }
     }
 
     /// This function creates a trusted-setup SRS instance for circuits with
@@ -482,19 +648,13 @@ impl<G: CommitmentCurve> SRS<G> {
             .collect();
 
         // Compute a blinder
-        let h = {
-            let mut h = Blake2b512::new();
-            h.update("srs_misc".as_bytes());
-            // FIXME: This is for retrocompatibility with a previous version
-            // that was using a list initialisation. It is not necessary.
-            h.update(0_u32.to_be_bytes());
-            point_of_random_bytes(&m, &h.finalize())
-        };
+        let h = blinding_point::<G>(&m);
 
         Self {
             g,
             h,
             lagrange_bases: HashMapCache::new(),
+            msm_table: Arc::default(),
         }
     }
 }
@@ -509,29 +669,18 @@ where
     pub fn create_parallel(depth: usize) -> Self {
         let m = G::Map::setup();
 
-        let g: Vec<_> = (0..depth)
-            .into_par_iter()
-            .map(|i| {
-                let mut h = Blake2b512::new();
-                h.update((i as u32).to_be_bytes());
-                point_of_random_bytes(&m, &h.finalize())
-            })
+        let g: Vec<_> = cfg_into_iter!(0..depth)
+            .map(|i| generated_point::<G>(&m, i))
             .collect();
 
         // Compute a blinder
-        let h = {
-            let mut h = Blake2b512::new();
-            h.update("srs_misc".as_bytes());
-            // FIXME: This is for retrocompatibility with a previous version
-            // that was using a list initialisation. It is not necessary.
-            h.update(0_u32.to_be_bytes());
-            point_of_random_bytes(&m, &h.finalize())
-        };
+        let h = blinding_point::<G>(&m);
 
         Self {
             g,
             h,
             lagrange_bases: HashMapCache::new(),
+            msm_table: Arc::default(),
         }
     }
 }
@@ -585,26 +734,10 @@ where
         plnm: &DensePolynomial<G::ScalarField>,
         num_chunks: usize,
     ) -> PolyComm<G> {
-        let is_zero = plnm.is_zero();
-
-        let coeffs: Vec<_> = plnm.iter().map(|c| c.into_bigint()).collect();
-
-        // chunk while commiting
-        let mut chunks = vec![];
-        if is_zero {
-            chunks.push(G::zero());
-        } else {
-            coeffs.chunks(self.g.len()).for_each(|coeffs_chunk| {
-                let chunk = G::Group::msm_bigint(&self.g, coeffs_chunk);
-                chunks.push(chunk.into_affine());
-            });
-        }
-
-        for _ in chunks.len()..num_chunks {
-            chunks.push(G::zero());
+        match self.msm_table.get() {
+            Some(table) => self.commit_non_hiding_with(plnm, num_chunks, table),
+            None => self.commit_non_hiding_with(plnm, num_chunks, &ArkworksMsm),
         }
-
-        PolyComm::<G>::new(chunks)
     }
 
     fn commit(
@@ -668,28 +801,16 @@ where
     fn create(depth: usize) -> Self {
         let m = G::Map::setup();
 
-        let g: Vec<_> = (0..depth)
-            .map(|i| {
-                let mut h = Blake2b512::new();
-                h.update((i as u32).to_be_bytes());
-                point_of_random_bytes(&m, &h.finalize())
-            })
-            .collect();
+        let g: Vec<_> = (0..depth).map(|i| generated_point::<G>(&m, i)).collect();
 
         // Compute a blinder
-        let h = {
-            let mut h = Blake2b512::new();
-            h.update("srs_misc".as_bytes());
-            // FIXME: This is for retrocompatibility with a previous version
-            // that was using a list initialisation. It is not necessary.
-            h.update(0_u32.to_be_bytes());
-            point_of_random_bytes(&m, &h.finalize())
-        };
+        let h = blinding_point::<G>(&m);
 
         Self {
             g,
             h,
             lagrange_bases: HashMapCache::new(),
+            msm_table: Arc::default(),
         }
     }
 
@@ -716,6 +837,56 @@ impl<G: CommitmentCurve> SRS<G> {
     // the first prover message to improve the efficiency in a recursive
     // setting.
     pub fn open<EFqSponge, RNG, D: EvaluationDomain<G::ScalarField>>(
+        &self,
+        group_map: &G::Map,
+        plnms: PolynomialsToCombine<G, D>,
+        elm: &[G::ScalarField],
+        polyscale: G::ScalarField,
+        evalscale: G::ScalarField,
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> OpeningProof<G>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G::BaseField: PrimeField,
+        G: EndoCurve,
+    {
+        self.open_with_rounds(
+            group_map,
+            plnms,
+            elm,
+            polyscale,
+            evalscale,
+            sponge,
+            self.max_rounds(),
+            rng,
+        )
+    }
+
+    /// The number of rounds of an opening proof over the whole SRS, i.e. the
+    /// log of its size
+    pub fn max_rounds(&self) -> usize {
+        math::ceil_log2(self.g.len())
+    }
+
+    /// Creates an opening proof like [SRS::open], with `rounds` rounds instead
+    /// of [SRS::max_rounds], i.e. using only the first `2^rounds` points of
+    /// the SRS. Small polynomials, e.g. auxiliary ones, are opened with fewer
+    /// rounds, and the proof has fewer `L` and `R` commitments.
+    ///
+    /// The commitments of the polynomials must have chunks of `2^rounds`
+    /// coefficients, i.e. the polynomials must have fewer coefficients than
+    /// that if they were committed with the whole SRS. The proof is checked
+    /// by [SRS::verify] as any other proof, and can be batched with proofs
+    /// having another number of rounds.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `rounds` is larger than [SRS::max_rounds].
+    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::many_single_char_names)]
+    pub fn open_with_rounds<EFqSponge, RNG, D: EvaluationDomain<G::ScalarField>>(
         &self,
         group_map: &G::Map,
         plnms: PolynomialsToCombine<G, D>,
@@ -723,6 +894,7 @@ impl<G: CommitmentCurve> SRS<G> {
         polyscale: G::ScalarField,
         evalscale: G::ScalarField,
         mut sponge: EFqSponge,
+        rounds: usize,
         rng: &mut RNG,
     ) -> OpeningProof<G>
     where
@@ -731,20 +903,25 @@ impl<G: CommitmentCurve> SRS<G> {
         G::BaseField: PrimeField,
         G: EndoCurve,
     {
+        assert!(
+            rounds <= self.max_rounds(),
+            "an opening proof has at most {} rounds",
+            self.max_rounds()
+        );
         let (endo_q, endo_r) = endos::<G>();
 
-        let rounds = math::ceil_log2(self.g.len());
         let padded_length = 1 << rounds;
+        let srs_length = std::cmp::min(padded_length, self.g.len());
 
         // TODO: Trim this to the degree of the largest polynomial
         // TODO: We do always suppose we have a power of 2 for the SRS in
         // practice. Therefore, padding equals zero, and this code can be
         // removed. Only a current test case uses a SRS with a non-power of 2.
-        let padding = padded_length - self.g.len();
-        let mut g = self.g.clone();
+        let padding = padded_length - srs_length;
+        let mut g = self.g[..srs_length].to_vec();
         g.extend(vec![G::zero(); padding]);
 
-        let (p, blinding_factor) = combine_polys::<G, D>(plnms, polyscale, self.g.len());
+        let (p, blinding_factor) = combine_polys::<G, D>(plnms, polyscale, srs_length);
 
         // The initial evaluation vector for polynomial commitment b_init is not
         // just the powers of a single point as in the original IPA, but rather
@@ -852,8 +1029,7 @@ impl<G: CommitmentCurve> SRS<G> {
             chal_invs.push(u_inv);
 
             // IPA-folding polynomial coefficients
-            a = a_hi
-                .par_iter()
+            a = cfg_iter!(a_hi)
                 .zip(a_lo)
                 .map(|(&hi, &lo)| {
                     // lo + u_inv * hi
@@ -865,8 +1041,7 @@ impl<G: CommitmentCurve> SRS<G> {
                 .collect();
 
             // IPA-folding evaluation points
-            b = b_lo
-                .par_iter()
+            b = cfg_iter!(b_lo)
                 .zip(b_hi)
                 .map(|(&lo, &hi)| {
                     // lo + u * hi
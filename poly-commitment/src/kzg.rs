@@ -8,6 +8,12 @@
 //!
 //! The pairing friendly curve requirement is hidden in the Pairing trait
 //! parameter.
+//!
+//! [`KZGProof`] implements [`OpenProof`](crate::OpenProof), so that it can be
+//! used by kimchi instead of the inner-product argument, e.g. over BN254. The
+//! opening proof is then a single group element and a scalar, and the size of
+//! the proofs does not depend on the size of the circuit, which makes them
+//! cheaper to verify on Ethereum.
 
 use crate::{
     commitment::*,
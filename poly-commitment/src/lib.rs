@@ -1,9 +1,14 @@
+pub mod aggregation;
 mod combine;
 pub mod commitment;
 pub mod error;
 pub mod hash_map_cache;
 pub mod ipa;
 pub mod kzg;
+pub mod msm;
+pub mod precomputation;
+pub mod setup;
+pub mod shared;
 
 // Exposing property based tests for the SRS trait
 pub mod pbt_srs;
@@ -118,6 +123,31 @@ pub trait SRS<G: CommitmentCurve>: Clone + Sized {
         plnm: &Evaluations<G::ScalarField, D<G::ScalarField>>,
     ) -> PolyComm<G>;
 
+    /// Commit to the evaluations over `domain` of a sparse polynomial, without
+    /// blinding factors. The polynomial is zero on all the points of the
+    /// domain, except the ones of `evals`, given as `(index, value)` pairs.
+    /// Only the commitments to the Lagrange bases of these points are used,
+    /// so that the cost is an MSM of the size of `evals` instead of the size
+    /// of the domain, e.g. for the selector of a gate used in a few rows.
+    /// The result is the same as with [SRS::commit_evaluations_non_hiding].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an index is not in the domain.
+    fn commit_sparse_evaluations_non_hiding(
+        &self,
+        domain: D<G::ScalarField>,
+        evals: &[(usize, G::ScalarField)],
+    ) -> PolyComm<G> {
+        let basis = self.get_lagrange_basis(domain);
+        if evals.is_empty() {
+            return PolyComm::new(vec![G::zero(); basis[0].len()]);
+        }
+        let (bases, scalars): (Vec<_>, Vec<_>) =
+            evals.iter().map(|(i, eval)| (&basis[*i], *eval)).unzip();
+        PolyComm::multi_scalar_mul(&bases, &scalars)
+    }
+
     /// Commit to evaluations with blinding factors, generated using the random
     /// number generator `rng`.
     /// It is analogous to [SRS::commit] but for evaluations.
@@ -177,7 +207,7 @@ type PolynomialsToCombine<'a, G: CommitmentCurve, D: EvaluationDomain<G::ScalarF
 )];
 
 pub trait OpenProof<G: CommitmentCurve>: Sized + Clone {
-    type SRS: SRS<G> + std::fmt::Debug;
+    type SRS: SRS<G> + std::fmt::Debug + Send + Sync;
 
     /// Create an opening proof for a batch of polynomials. The parameters are
     /// the following:
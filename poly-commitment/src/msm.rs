@@ -0,0 +1,303 @@
+//! This module implements the multi-scalar multiplications (MSM) used to
+//! commit to polynomials, behind the [`MsmBackend`] trait so that the
+//! algorithm can be chosen by the caller (see
+//! [`SRS::commit_non_hiding_with`](crate::ipa::SRS::commit_non_hiding_with)).
+//!
+//! Three backends are provided:
+//! - [`ArkworksMsm`], the variable-base MSM of arkworks, used by default;
+//! - [`Pippenger`], a bucket method with a configurable window size, to tune
+//!   the window to the size of the MSM and to the machine;
+//! - [`PrecomputedTable`], a table of multiples of fixed bases, e.g. the
+//!   points of an SRS, which removes the doublings of the bucket method. It is
+//!   worth computing when the same SRS is used for many commitments, e.g. by a
+//!   prover creating many proofs (see
+//!   [`SRS::precompute_msm_table`](crate::ipa::SRS::precompute_msm_table)).
+//!
+//! Other implementations can be plugged in by implementing [`MsmBackend`].
+//!
+//! FIXME: there is no GPU backend behind a feature, as the workspace does not
+//! depend on a GPU library. Such a backend can be implemented outside of this
+//! crate with [`MsmBackend`].
+
+use ark_ec::{AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{PrimeField, Zero};
+use ark_std::{cfg_chunks, cfg_into_iter, cfg_iter};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::cmp::min;
+
+/// The largest window size supported by [`Pippenger`] and [`PrecomputedTable`],
+/// i.e. the [`default_window`] of an MSM of `2^20` bases. The buckets of a
+/// window take `2^window` points.
+pub const MAX_WINDOW: usize = 16;
+
+/// The big integer representation of the scalars of `G`
+pub type ScalarBigInt<G> = <<G as AffineRepr>::ScalarField as PrimeField>::BigInt;
+
+/// An implementation of the multi-scalar multiplication
+pub trait MsmBackend<G: AffineRepr> {
+    /// Returns `sum_i scalars[i] * bases[i]`. The extra bases or scalars, if
+    /// any, are ignored.
+    fn msm(&self, bases: &[G], scalars: &[ScalarBigInt<G>]) -> G::Group;
+}
+
+/// The variable-base MSM of arkworks
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArkworksMsm;
+
+impl<G: AffineRepr> MsmBackend<G> for ArkworksMsm {
+    fn msm(&self, bases: &[G], scalars: &[ScalarBigInt<G>]) -> G::Group {
+        G::Group::msm_bigint(bases, scalars)
+    }
+}
+
+/// The bucket method of Pippenger, processing the scalars by windows of
+/// `window` bits
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Pippenger {
+    /// The size of the windows in bits, or `None` to pick it from the number
+    /// of bases (see [`default_window`]). It is capped at the
+    /// [`default_window`] of the number of bases, as larger windows only add
+    /// buckets.
+    pub window: Option<usize>,
+}
+
+impl Pippenger {
+    /// Creates a backend with windows of `window` bits
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `window` is not between 1 and [`MAX_WINDOW`].
+    pub fn with_window(window: usize) -> Self {
+        assert!((1..=MAX_WINDOW).contains(&window), "invalid window size");
+        Pippenger {
+            window: Some(window),
+        }
+    }
+}
+
+/// Returns the window size used by [`Pippenger`] for an MSM of `n` bases,
+/// i.e. about `ln(n) + 2` bits, and at most [`MAX_WINDOW`]
+pub fn default_window(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        let log2 = (usize::BITS - n.leading_zeros()) as usize;
+        min(log2 * 69 / 100 + 2, MAX_WINDOW)
+    }
+}
+
+/// Returns the `window` bits of `scalar` starting at bit `start`
+fn window_digit<B: AsRef<[u64]>>(scalar: &B, start: usize, window: usize) -> usize {
+    let limbs = scalar.as_ref();
+    let (limb, offset) = (start / 64, start % 64);
+    if limb >= limbs.len() {
+        return 0;
+    }
+    let mut digit = limbs[limb] >> offset;
+    if offset + window > 64 && limb + 1 < limbs.len() {
+        digit |= limbs[limb + 1] << (64 - offset);
+    }
+    (digit & ((1 << window) - 1)) as usize
+}
+
+/// Returns `sum_d d * buckets[d - 1]`
+fn sum_buckets<C: CurveGroup>(buckets: Vec<C>) -> C {
+    let mut running_sum = C::zero();
+    let mut res = C::zero();
+    for bucket in buckets.into_iter().rev() {
+        running_sum += bucket;
+        res += running_sum;
+    }
+    res
+}
+
+/// Returns the number of windows of `window` bits in a scalar of `G`
+fn num_windows<G: AffineRepr>(window: usize) -> usize {
+    let num_bits = G::ScalarField::MODULUS_BIT_SIZE as usize;
+    (num_bits + window - 1) / window
+}
+
+impl<G: AffineRepr> MsmBackend<G> for Pippenger {
+    fn msm(&self, bases: &[G], scalars: &[ScalarBigInt<G>]) -> G::Group {
+        let n = min(bases.len(), scalars.len());
+        let window = self.window.map_or_else(
+            || default_window(n),
+            |window| min(window, default_window(n)),
+        );
+
+        // the sums of the windows, from the lowest to the highest bits
+        let window_sums: Vec<G::Group> = cfg_into_iter!(0..num_windows::<G>(window))
+            .map(|j| {
+                let mut buckets = vec![G::Group::zero(); (1 << window) - 1];
+                for (base, scalar) in bases[..n].iter().zip(&scalars[..n]) {
+                    let digit = window_digit(scalar, j * window, window);
+                    if digit != 0 {
+                        buckets[digit - 1] += *base;
+                    }
+                }
+                sum_buckets(buckets)
+            })
+            .collect();
+
+        window_sums
+            .into_iter()
+            .rev()
+            .fold(G::Group::zero(), |mut acc, window_sum| {
+                for _ in 0..window {
+                    acc.double_in_place();
+                }
+                acc + window_sum
+            })
+    }
+}
+
+/// The multiples `2^(window * j) * base` of fixed bases, for each window `j`
+/// of the scalars. An MSM with these bases is then a single bucket method
+/// over all the windows, without doublings.
+///
+/// The table takes [`PrecomputedTable::num_windows`] times the memory of the
+/// bases. Like for [`Pippenger`], the window is capped at the
+/// [`default_window`] of the number of bases.
+#[derive(Clone, Debug)]
+pub struct PrecomputedTable<G> {
+    window: usize,
+    num_windows: usize,
+    /// The multiples of the bases, the ones of base `i` being at
+    /// `i * num_windows..(i + 1) * num_windows`
+    table: Vec<G>,
+}
+
+impl<G: AffineRepr> PrecomputedTable<G> {
+    /// Precomputes the multiples of `bases` for windows of `window` bits
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `window` is not between 1 and [`MAX_WINDOW`].
+    pub fn new(bases: &[G], window: usize) -> Self {
+        assert!((1..=MAX_WINDOW).contains(&window), "invalid window size");
+        let window = min(window, default_window(bases.len()));
+        let num_windows = num_windows::<G>(window);
+        let table: Vec<G::Group> = cfg_iter!(bases)
+            .flat_map(|base| {
+                let mut multiple = base.into_group();
+                (0..num_windows)
+                    .map(|_| {
+                        let res = multiple;
+                        for _ in 0..window {
+                            multiple.double_in_place();
+                        }
+                        res
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        PrecomputedTable {
+            window,
+            num_windows,
+            table: G::Group::normalize_batch(&table),
+        }
+    }
+
+    /// Creates a table from the multiples computed by [`PrecomputedTable::new`]
+    /// for windows of `window` bits, e.g. read from a file. Returns `None` if
+    /// the number of multiples does not match the window size.
+    pub(crate) fn from_points(window: usize, table: Vec<G>) -> Option<Self> {
+        if !(1..=MAX_WINDOW).contains(&window) {
+            return None;
+        }
+        let num_windows = num_windows::<G>(window);
+        if table.is_empty() || table.len() % num_windows != 0 {
+            return None;
+        }
+        Some(PrecomputedTable {
+            window,
+            num_windows,
+            table,
+        })
+    }
+
+    /// Returns the multiples of the bases, the ones of each base being
+    /// consecutive
+    pub(crate) fn points(&self) -> &[G] {
+        &self.table
+    }
+
+    /// Returns `true` if the table was computed from `bases`, i.e. if the
+    /// first multiple of each base of the table is this base
+    pub(crate) fn has_bases(&self, bases: &[G]) -> bool {
+        bases.len() == self.num_bases()
+            && self
+                .table
+                .iter()
+                .step_by(self.num_windows)
+                .zip(bases)
+                .all(|(multiple, base)| multiple == base)
+    }
+
+    /// Returns the size of the windows, in bits
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the number of windows of the scalars, i.e. the number of
+    /// multiples stored per base
+    pub fn num_windows(&self) -> usize {
+        self.num_windows
+    }
+
+    /// Returns the number of bases of the table
+    pub fn num_bases(&self) -> usize {
+        self.table.len() / self.num_windows
+    }
+
+    /// Returns `sum_i scalars[i] * bases[i]`, for the bases of the table. The
+    /// scalars can be fewer than the bases.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are more scalars than bases.
+    pub fn msm_fixed(&self, scalars: &[ScalarBigInt<G>]) -> G::Group {
+        assert!(
+            scalars.len() <= self.num_bases(),
+            "more scalars than precomputed bases"
+        );
+
+        // each chunk of scalars is summed with its own buckets
+        #[cfg(feature = "parallel")]
+        let chunk_size = (scalars.len() / rayon::current_num_threads()).max(1);
+        #[cfg(not(feature = "parallel"))]
+        let chunk_size = scalars.len().max(1);
+
+        cfg_chunks!(scalars, chunk_size)
+            .enumerate()
+            .map(|(c, chunk)| {
+                let mut buckets = vec![G::Group::zero(); (1 << self.window) - 1];
+                for (k, scalar) in chunk.iter().enumerate() {
+                    let i = c * chunk_size + k;
+                    let multiples = &self.table[i * self.num_windows..(i + 1) * self.num_windows];
+                    for (j, multiple) in multiples.iter().enumerate() {
+                        let digit = window_digit(scalar, j * self.window, self.window);
+                        if digit != 0 {
+                            buckets[digit - 1] += *multiple;
+                        }
+                    }
+                }
+                sum_buckets(buckets)
+            })
+            .sum()
+    }
+}
+
+/// Uses the table for the bases it was computed from. Only the number of
+/// `bases` is used, so they must be the first bases of the table.
+impl<G: AffineRepr> MsmBackend<G> for PrecomputedTable<G> {
+    fn msm(&self, bases: &[G], scalars: &[ScalarBigInt<G>]) -> G::Group {
+        debug_assert!(
+            bases.len() <= self.num_bases() && bases.first() == self.table.first(),
+            "the bases are not the ones of the table"
+        );
+        let n = min(bases.len(), scalars.len());
+        self.msm_fixed(&scalars[..n])
+    }
+}
@@ -0,0 +1,200 @@
+//! This module implements [`SrsPrecomputation`], the table of multiples of
+//! the points of an SRS used to commit to polynomials (see
+//! [`PrecomputedTable`]), in a form that can be written to a file once and
+//! loaded by the next runs of a prover instead of being recomputed.
+//!
+//! The points of the file are checked to be on the curve when it is read. The
+//! file is bound to the SRS it was computed from by the digest of the SRS (see
+//! [`SRS::digest`]), and is only installed on this SRS, whose points must be
+//! the first multiples of the table. Once
+//! installed, the commitments of the SRS use the table without any change to
+//! the callers (see [`SRS::precompute_msm_table`]):
+//!
+//! ```ignore
+//! srs.load_or_precompute_msm_table(Path::new("srs.msm"), 16)?;
+//! let comm = srs.commit_non_hiding(&poly, 1);
+//! ```
+
+use crate::{
+    commitment::CommitmentCurve, error::SRSError, ipa::SRS, msm::PrecomputedTable, setup::SRSDigest,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use blake2::{Blake2b512, Digest};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, ErrorKind, Write},
+    path::Path,
+};
+
+/// The magic bytes at the start of the file of an [`SrsPrecomputation`]
+pub const PRECOMPUTATION_FILE_MAGIC: [u8; 8] = *b"o1msm\0\0\0";
+
+/// The version of the format of the file of an [`SrsPrecomputation`]
+pub const PRECOMPUTATION_FILE_VERSION: u32 = 1;
+
+/// The size of the header of the file: magic bytes, version, digest of the
+/// SRS and window size
+const HEADER_SIZE: usize = 8 + 4 + 64 + 4;
+
+/// The size of the checksum at the end of the file
+const CHECKSUM_SIZE: usize = 64;
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut checksum = [0u8; CHECKSUM_SIZE];
+    checksum.copy_from_slice(&Blake2b512::digest(bytes));
+    checksum
+}
+
+/// The MSM precomputation of an SRS, with the digest of the SRS it was
+/// computed from
+#[derive(Clone, Debug)]
+pub struct SrsPrecomputation<G> {
+    srs_digest: SRSDigest,
+    table: PrecomputedTable<G>,
+}
+
+impl<G: CommitmentCurve> SrsPrecomputation<G> {
+    /// Precomputes the multiples of the points of `srs` for windows of
+    /// `window` bits
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `window` is not between 1 and
+    /// [`MAX_WINDOW`](crate::msm::MAX_WINDOW).
+    pub fn create(srs: &SRS<G>, window: usize) -> Self {
+        SrsPrecomputation {
+            srs_digest: srs.digest(),
+            table: PrecomputedTable::new(&srs.g, window),
+        }
+    }
+
+    /// Returns the digest of the SRS the table was computed from
+    pub fn srs_digest(&self) -> &SRSDigest {
+        &self.srs_digest
+    }
+
+    /// Returns the precomputed table
+    pub fn table(&self) -> &PrecomputedTable<G> {
+        &self.table
+    }
+
+    /// Writes the precomputation to `writer`, the points being uncompressed
+    /// so that they can be read without any computation.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `writer` fails.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), SRSError> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend(PRECOMPUTATION_FILE_MAGIC);
+        bytes.extend(PRECOMPUTATION_FILE_VERSION.to_le_bytes());
+        bytes.extend(self.srs_digest);
+        bytes.extend((self.table.window() as u32).to_le_bytes());
+        self.table.points().serialize_uncompressed(&mut bytes)?;
+        writer.write_all(&bytes)?;
+        writer.write_all(&checksum(&bytes))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a precomputation written by [`SrsPrecomputation::write`] from
+    /// `bytes`. The points are checked to be in the prime-order subgroup of
+    /// the curve.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not in the format of the precomputation,
+    /// if its checksum is incorrect, or if a point is not valid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SRSError> {
+        if bytes.len() < HEADER_SIZE + CHECKSUM_SIZE {
+            return Err(SRSError::InvalidFormat("truncated precomputation"));
+        }
+        if bytes[..8] != PRECOMPUTATION_FILE_MAGIC {
+            return Err(SRSError::InvalidFormat("wrong magic bytes"));
+        }
+        if bytes[8..12] != PRECOMPUTATION_FILE_VERSION.to_le_bytes() {
+            return Err(SRSError::InvalidFormat("unsupported version"));
+        }
+        let (content, expected_checksum) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+        if checksum(content) != expected_checksum {
+            return Err(SRSError::DigestMismatch);
+        }
+
+        let srs_digest = content[12..76].try_into().unwrap();
+        let window = u32::from_le_bytes(content[76..80].try_into().unwrap()) as usize;
+        let mut points = &content[HEADER_SIZE..];
+        let table = Vec::<G>::deserialize_with_mode(&mut points, Compress::No, Validate::Yes)?;
+        if !points.is_empty() {
+            return Err(SRSError::InvalidFormat("trailing bytes"));
+        }
+        let table = PrecomputedTable::from_points(window, table)
+            .ok_or(SRSError::InvalidFormat("invalid table size"))?;
+
+        Ok(SrsPrecomputation { srs_digest, table })
+    }
+
+    /// Reads the precomputation from the file at `path`, written with
+    /// [`SrsPrecomputation::write`] (see [`SrsPrecomputation::from_bytes`]).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file cannot be read or decoded.
+    pub fn load(path: &Path) -> Result<Self, SRSError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Installs the table on `srs`, which uses it for its next commitments.
+    /// Does nothing if `srs` already has a table.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the precomputation was made for another SRS, i.e.
+    /// if its digest or its first multiples are not the ones of `srs`.
+    pub fn install(self, srs: &SRS<G>) -> Result<(), SRSError> {
+        if self.table.num_bases() != srs.g.len()
+            || self.srs_digest != srs.digest()
+            || !self.table.has_bases(&srs.g)
+        {
+            return Err(SRSError::PrecomputationMismatch);
+        }
+        // the table is already there if the SRS was precomputed concurrently
+        let _ = srs.msm_table.set(self.table);
+        Ok(())
+    }
+}
+
+impl<G: CommitmentCurve> SRS<G> {
+    /// Installs the precomputation of the file at `path` (see
+    /// [`SrsPrecomputation`]) if it exists and was made for this SRS, whatever
+    /// its window size. Does nothing if the SRS already has a table.
+    /// Otherwise, precomputes the table for windows of `window` bits as
+    /// [`SRS::precompute_msm_table`] does, and writes it to `path` for the
+    /// next runs.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the file exists but cannot be read, or if the new
+    /// precomputation cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `window` is not between 1 and
+    /// [`MAX_WINDOW`](crate::msm::MAX_WINDOW).
+    pub fn load_or_precompute_msm_table(&self, path: &Path, window: usize) -> Result<(), SRSError> {
+        if self.msm_table.get().is_some() {
+            return Ok(());
+        }
+        match SrsPrecomputation::load(path) {
+            Ok(precomputation) => match precomputation.install(self) {
+                Err(SRSError::PrecomputationMismatch) => (),
+                res => return res,
+            },
+            Err(SRSError::Io(e)) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+
+        let precomputation = SrsPrecomputation::create(self, window);
+        precomputation.write(BufWriter::new(File::create(path)?))?;
+        precomputation.install(self)
+    }
+}
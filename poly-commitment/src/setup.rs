@@ -0,0 +1,396 @@
+//! This module implements the import of SRS from trusted setup ceremonies,
+//! the verification of their consistency, and the export of SRS to a
+//! canonical binary file identified by its digest.
+//!
+//! The canonical file of an [`SRS`] contains [`SRS_FILE_MAGIC`], the version
+//! of the format, the points of the SRS in compressed form and their digest
+//! (see [`SRS::digest`]). It can be published along with its digest, so that
+//! its users check that they load the expected SRS instead of regenerating it
+//! from the seed (see [`SRS::read_canonical`]).
+//!
+//! The SRS of the KZG protocol can be imported from the `.ptau` files of the
+//! Powers of Tau ceremonies (see [`PairingSRS::from_ptau`]), and their
+//! consistency checked with pairings (see [`PairingSRS::verify`]).
+
+use crate::{
+    commitment::CommitmentCurve,
+    error::SRSError,
+    hash_map_cache::HashMapCache,
+    ipa::{blinding_point, generated_point, SRS},
+    kzg::PairingSRS,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Valid, Validate};
+use blake2::{Blake2b512, Digest};
+use groupmap::GroupMap;
+use rand_core::RngCore;
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+    iter,
+    sync::Arc,
+};
+
+/// The magic bytes at the start of the canonical file of an SRS
+pub const SRS_FILE_MAGIC: [u8; 8] = *b"o1srs\0\0\0";
+
+/// The version of the format of the canonical file of an SRS
+pub const SRS_FILE_VERSION: u32 = 1;
+
+/// The Blake2b-512 digest of the points of an SRS (see [`SRS::digest`])
+pub type SRSDigest = [u8; 64];
+
+fn hash(bytes: &[u8]) -> SRSDigest {
+    let mut digest = [0u8; 64];
+    digest.copy_from_slice(&Blake2b512::digest(bytes));
+    digest
+}
+
+impl<G: CommitmentCurve> SRS<G> {
+    /// Returns the points `g` and `h` of the SRS, in compressed form
+    fn points_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        // serializing to a vector cannot fail
+        self.g.serialize_compressed(&mut bytes).unwrap();
+        self.h.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Returns the digest of the SRS, i.e. the Blake2b-512 hash of its points
+    /// in compressed form. The commitments to the Lagrange bases are not part
+    /// of the digest.
+    pub fn digest(&self) -> SRSDigest {
+        hash(&self.points_bytes())
+    }
+
+    /// Writes the SRS to `writer` in the canonical format, and returns its
+    /// digest. The commitments to the Lagrange bases are not written.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `writer` fails.
+    pub fn write_canonical<W: Write>(&self, mut writer: W) -> Result<SRSDigest, SRSError> {
+        let points = self.points_bytes();
+        let digest = hash(&points);
+        writer.write_all(&SRS_FILE_MAGIC)?;
+        writer.write_all(&SRS_FILE_VERSION.to_le_bytes())?;
+        writer.write_all(&points)?;
+        writer.write_all(&digest)?;
+        writer.flush()?;
+        Ok(digest)
+    }
+
+    /// Reads an SRS written by [`SRS::write_canonical`] from `reader`. The
+    /// points are checked to be in the prime order subgroup of the curve, and
+    /// their digest to be the one stored in the file, and `expected_digest`
+    /// if given.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `reader` fails, if the file is not in the canonical
+    /// format, or if the checks fail.
+    pub fn read_canonical<R: Read>(
+        mut reader: R,
+        expected_digest: Option<&SRSDigest>,
+    ) -> Result<Self, SRSError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != SRS_FILE_MAGIC {
+            return Err(SRSError::InvalidFormat("wrong magic bytes"));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        if u32::from_le_bytes(version) != SRS_FILE_VERSION {
+            return Err(SRSError::InvalidFormat("unsupported version"));
+        }
+
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        if bytes.len() < 64 {
+            return Err(SRSError::InvalidFormat("missing digest"));
+        }
+        let (mut points, digest) = bytes.split_at(bytes.len() - 64);
+        let actual_digest = hash(points);
+        if digest != &actual_digest[..] || expected_digest.map_or(false, |d| *d != actual_digest) {
+            return Err(SRSError::DigestMismatch);
+        }
+
+        let g = Vec::<G>::deserialize_with_mode(&mut points, Compress::Yes, Validate::Yes)?;
+        let h = G::deserialize_with_mode(&mut points, Compress::Yes, Validate::Yes)?;
+        if !points.is_empty() {
+            return Err(SRSError::InvalidFormat("trailing bytes"));
+        }
+
+        Ok(SRS {
+            g,
+            h,
+            lagrange_bases: HashMapCache::new(),
+            msm_table: Arc::default(),
+        })
+    }
+
+    /// Checks that the points of the SRS are points of the prime order
+    /// subgroup of the curve.
+    ///
+    /// # Errors
+    ///
+    /// Will give error with the index of the first invalid point, `h` being
+    /// the last one.
+    pub fn check_points(&self) -> Result<(), SRSError> {
+        for (i, point) in self.g.iter().chain(iter::once(&self.h)).enumerate() {
+            point.check().map_err(|_| SRSError::InvalidPoint(i))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the SRS is the one generated from the seed by
+    /// [`SRS::create`](crate::SRS::create), i.e. that nobody knows the
+    /// discrete logarithms between its points.
+    ///
+    /// # Errors
+    ///
+    /// Will give error with the index of the first point which is not the
+    /// generated one, `h` being the last one.
+    pub fn verify_generated(&self) -> Result<(), SRSError> {
+        let m = G::Map::setup();
+        for (i, point) in self.g.iter().enumerate() {
+            if *point != generated_point::<G>(&m, i) {
+                return Err(SRSError::NotGenerated(i));
+            }
+        }
+        if self.h != blinding_point::<G>(&m) {
+            return Err(SRSError::NotGenerated(self.g.len()));
+        }
+        Ok(())
+    }
+}
+
+/// The magic bytes at the start of a `.ptau` file
+const PTAU_MAGIC: [u8; 4] = *b"ptau";
+
+/// The sections of a `.ptau` file read by [`PairingSRS::from_ptau`]
+const PTAU_HEADER_SECTION: u32 = 1;
+const PTAU_TAU_G1_SECTION: u32 = 2;
+const PTAU_TAU_G2_SECTION: u32 = 3;
+
+/// Number of points of the SRS of the verifier, as in
+/// [`PairingSRS::create_trusted_setup`]
+const VERIFIER_SRS_SIZE: usize = 3;
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, SRSError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, SRSError> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Returns the inverse of the Montgomery factor `R = 2^(8 * n8)` used to
+/// encode the elements of `F` in `.ptau` files
+fn montgomery_factor_inv<F: PrimeField>(n8: usize) -> F {
+    F::from(2u64)
+        .pow([8 * n8 as u64])
+        .inverse()
+        .expect("2 is invertible")
+}
+
+/// Reads a coordinate from a `.ptau` file, made of its coefficients over the
+/// prime field in Montgomery form and in little-endian order
+fn read_ptau_coordinate<F: Field, R: Read>(
+    reader: &mut R,
+    n8: usize,
+    r_inv: F::BasePrimeField,
+) -> Result<F, SRSError> {
+    let mut coefficients = vec![];
+    for _ in 0..F::extension_degree() {
+        let mut bytes = vec![0u8; n8];
+        reader.read_exact(&mut bytes)?;
+        coefficients.push(F::BasePrimeField::from_le_bytes_mod_order(&bytes) * r_inv);
+    }
+    F::from_base_prime_field_elems(&coefficients)
+        .ok_or(SRSError::InvalidFormat("invalid coordinate"))
+}
+
+/// Reads a point of `C` from a `.ptau` file, made of its coordinates `x` and
+/// `y`. The point at infinity is encoded with zeros.
+fn read_ptau_point<C: CommitmentCurve, R: Read>(
+    reader: &mut R,
+    n8: usize,
+    r_inv: <C::BaseField as Field>::BasePrimeField,
+) -> Result<C, SRSError> {
+    let x: C::BaseField = read_ptau_coordinate(reader, n8, r_inv)?;
+    let y: C::BaseField = read_ptau_coordinate(reader, n8, r_inv)?;
+    if x.is_zero() && y.is_zero() {
+        Ok(C::zero())
+    } else {
+        Ok(C::of_coordinates(x, y))
+    }
+}
+
+/// Reads `count` points of `C` from the section of a `.ptau` file starting
+/// at `start`
+fn read_ptau_points<C: CommitmentCurve, R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    count: usize,
+    n8: usize,
+) -> Result<Vec<C>, SRSError> {
+    reader.seek(SeekFrom::Start(start))?;
+    let r_inv = montgomery_factor_inv(n8);
+    (0..count)
+        .map(|_| read_ptau_point(reader, n8, r_inv))
+        .collect()
+}
+
+impl<
+        F: PrimeField,
+        G: CommitmentCurve<ScalarField = F>,
+        G2: CommitmentCurve<ScalarField = F>,
+        Pair: Pairing<G1Affine = G, G2Affine = G2>,
+    > PairingSRS<Pair>
+{
+    /// Imports the first `depth` powers of tau from a `.ptau` file, as
+    /// produced by the Powers of Tau ceremonies (e.g. with `snarkjs`). The
+    /// blinders are the ones of [`PairingSRS::create_trusted_setup`].
+    ///
+    /// The points are not checked, see [`PairingSRS::verify`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `reader` fails, if the file is not a `.ptau` file
+    /// for the base field of `G`, or if it has less than `depth` powers.
+    pub fn from_ptau<R: Read + Seek>(mut reader: R, depth: usize) -> Result<Self, SRSError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != PTAU_MAGIC {
+            return Err(SRSError::InvalidFormat("wrong magic bytes"));
+        }
+        let _version = read_u32(&mut reader)?;
+
+        // locate the sections
+        let num_sections = read_u32(&mut reader)?;
+        let mut sections = HashMap::new();
+        for _ in 0..num_sections {
+            let section_type = read_u32(&mut reader)?;
+            let size = read_u64(&mut reader)?;
+            let start = reader.stream_position()?;
+            sections.insert(section_type, start);
+            reader.seek(SeekFrom::Start(start + size))?;
+        }
+        let section = |section_type| {
+            sections
+                .get(&section_type)
+                .copied()
+                .ok_or(SRSError::InvalidFormat("missing section"))
+        };
+
+        // read the header
+        reader.seek(SeekFrom::Start(section(PTAU_HEADER_SECTION)?))?;
+        let n8 = read_u32(&mut reader)? as usize;
+        let mut modulus = vec![0u8; n8];
+        reader.read_exact(&mut modulus)?;
+        let expected_modulus =
+            <<G::BaseField as Field>::BasePrimeField as PrimeField>::MODULUS.to_bytes_le();
+        if modulus != expected_modulus {
+            return Err(SRSError::FieldMismatch);
+        }
+        let power = read_u32(&mut reader)?;
+
+        // the file contains 2^(power + 1) - 1 powers in G1, and 2^power in G2
+        let num_powers = (1usize << power) * 2 - 1;
+        if num_powers < depth {
+            return Err(SRSError::TooShort(num_powers, depth));
+        }
+        let g = read_ptau_points::<G, _>(&mut reader, section(PTAU_TAU_G1_SECTION)?, depth, n8)?;
+        let g2 = read_ptau_points::<G2, _>(
+            &mut reader,
+            section(PTAU_TAU_G2_SECTION)?,
+            VERIFIER_SRS_SIZE,
+            n8,
+        )?;
+
+        Ok(Self {
+            full_srs: SRS {
+                g,
+                h: blinding_point::<G>(&G::Map::setup()),
+                lagrange_bases: HashMapCache::new(),
+                msm_table: Arc::default(),
+            },
+            verifier_srs: SRS {
+                g: g2,
+                h: blinding_point::<G2>(&G2::Map::setup()),
+                lagrange_bases: HashMapCache::new(),
+                msm_table: Arc::default(),
+            },
+        })
+    }
+
+    /// Checks the consistency of the SRS, i.e. that its points are in the
+    /// prime order subgroups of the curves, that they start with the
+    /// generators of the curves, and that they are the powers of the same
+    /// secret in both groups. The powers in `G` are checked at once with a
+    /// random linear combination sampled from `rng`.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if one of the checks fails.
+    pub fn verify<RNG: RngCore>(&self, rng: &mut RNG) -> Result<(), SRSError> {
+        self.full_srs.check_points()?;
+        self.verifier_srs.check_points()?;
+
+        let g1 = &self.full_srs.g;
+        let g2 = &self.verifier_srs.g;
+        if g1.len() < 2 {
+            return Err(SRSError::TooShort(g1.len(), 2));
+        }
+        if g2.len() < 2 {
+            return Err(SRSError::TooShort(g2.len(), 2));
+        }
+        if g1[0] != G::generator() || g2[0] != G2::generator() {
+            return Err(SRSError::InvalidGenerator);
+        }
+
+        // e(g1[i + 1], g2[0]) = e(g1[i], g2[1]) for all i, checked on a
+        // random linear combination
+        let r: Vec<F> = (0..g1.len() - 1).map(|_| F::rand(rng)).collect();
+        let lhs = G::Group::msm_unchecked(&g1[1..], &r);
+        let rhs = G::Group::msm_unchecked(&g1[..g1.len() - 1], &r);
+        let powers_g1 = Pair::multi_pairing(
+            [
+                ark_ec::pairing::prepare_g1::<Pair>(lhs),
+                ark_ec::pairing::prepare_g1::<Pair>(-rhs),
+            ],
+            [
+                ark_ec::pairing::prepare_g2::<Pair>(g2[0]),
+                ark_ec::pairing::prepare_g2::<Pair>(g2[1]),
+            ],
+        );
+        if !powers_g1.is_zero() {
+            return Err(SRSError::InconsistentPowers);
+        }
+
+        // e(g1[j], g2[0]) = e(g1[0], g2[j]) for the powers in G2
+        for (j, g2_j) in g2.iter().enumerate().skip(1).take(g1.len() - 1) {
+            let powers_g2 = Pair::multi_pairing(
+                [
+                    ark_ec::pairing::prepare_g1::<Pair>(g1[j].into_group()),
+                    ark_ec::pairing::prepare_g1::<Pair>(-g1[0].into_group()),
+                ],
+                [
+                    ark_ec::pairing::prepare_g2::<Pair>(g2[0]),
+                    ark_ec::pairing::prepare_g2::<Pair>(*g2_j),
+                ],
+            );
+            if !powers_g2.is_zero() {
+                return Err(SRSError::InconsistentPowers);
+            }
+        }
+
+        Ok(())
+    }
+}
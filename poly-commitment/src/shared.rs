@@ -0,0 +1,90 @@
+//! This module implements [`SharedBlob`], a data blob that is committed once
+//! and whose commitment can be referenced by several proofs, e.g. a kimchi
+//! proof and an o1vm proof, instead of being recomputed by each of them.
+//!
+//! Each proof uses the blob as one of its columns, and reuses the commitment
+//! of the blob as the commitment of this column. As the proofs open this
+//! column against the same commitment, a verifier checking that each proof
+//! contains the commitment of the blob (see [`SharedBlob::is_committed_in`])
+//! is ensured that they are about the same data.
+
+use crate::{
+    commitment::{BlindedCommitment, CommitmentCurve},
+    PolyComm, SRS,
+};
+use ark_ff::{UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use rand_core::{CryptoRng, RngCore};
+
+/// A data blob, padded to the size of an evaluation domain, together with its
+/// commitment.
+#[derive(Clone, Debug)]
+pub struct SharedBlob<G: CommitmentCurve> {
+    /// The length of the data, without padding
+    pub len: usize,
+    /// The data, padded with zeros and `random_rows` random values to the size
+    /// of the domain
+    pub evals: Evaluations<G::ScalarField, D<G::ScalarField>>,
+    /// The commitment to `evals`, and its blinders
+    pub commitment: BlindedCommitment<G>,
+}
+
+impl<G: CommitmentCurve> SharedBlob<G> {
+    /// Commits to `data`, padded with zeros to the size of `domain`. The last
+    /// `random_rows` values are then replaced by random values, e.g. to leave
+    /// room for the zero-knowledge rows of a kimchi witness column.
+    ///
+    /// The commitment is masked with fresh random blinders, sampled from
+    /// `rng`, so that two blobs never share their blinders. The proofs
+    /// referencing the blob open the column with the blinders of the blob.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `data` and the random values do not fit in `domain`.
+    pub fn commit<S: SRS<G>>(
+        srs: &S,
+        domain: D<G::ScalarField>,
+        data: &[G::ScalarField],
+        random_rows: usize,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let size = domain.size();
+        assert!(
+            data.len() + random_rows <= size,
+            "the blob does not fit in the domain"
+        );
+
+        let mut evals = data.to_vec();
+        evals.resize(size, G::ScalarField::zero());
+        for e in evals.iter_mut().rev().take(random_rows) {
+            *e = G::ScalarField::rand(rng);
+        }
+        let evals = Evaluations::from_vec_and_domain(evals, domain);
+
+        let commitment = srs.commit_evaluations_non_hiding(domain, &evals);
+        let commitment = srs.mask(commitment, rng);
+
+        SharedBlob {
+            len: data.len(),
+            evals,
+            commitment,
+        }
+    }
+
+    /// Returns the data of the blob, without padding
+    pub fn data(&self) -> &[G::ScalarField] {
+        &self.evals.evals[..self.len]
+    }
+
+    /// Returns `true` if the padded data of the blob starts with `column`,
+    /// i.e. if the blob can be used as the padded version of this column.
+    pub fn matches(&self, column: &[G::ScalarField]) -> bool {
+        column.len() <= self.evals.evals.len() && self.evals.evals[..column.len()] == *column
+    }
+
+    /// Returns `true` if `commitment`, taken from a proof, is the commitment
+    /// of the blob.
+    pub fn is_committed_in(&self, commitment: &PolyComm<G>) -> bool {
+        self.commitment.commitment == *commitment
+    }
+}
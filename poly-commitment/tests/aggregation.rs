@@ -0,0 +1,85 @@
+use ark_ff::{Field, One, UniformRand};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi as SC, sponge::DefaultFqSponge, FqSponge,
+};
+use poly_commitment::{
+    aggregation::OpeningAccumulator, commitment::CommitmentCurve, ipa::SRS, SRS as _,
+};
+
+type VestaFqSponge = DefaultFqSponge<VestaParameters, SC>;
+
+#[test]
+fn test_opening_accumulator() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let sponge = VestaFqSponge::new(mina_poseidon::pasta::fq_kimchi::static_params());
+    let srs = SRS::<Vesta>::create(32);
+
+    // a polynomial of two chunks, committed with blinders, and a small one
+    let p1 = DensePolynomial::<Fp>::rand(50, &mut rng);
+    let p2 = DensePolynomial::<Fp>::rand(10, &mut rng);
+    let p1_comm = srs.commit(&p1, 2, &mut rng);
+    let p2_comm = srs.commit_non_hiding(&p2, 1);
+
+    let (x, y, z) = (Fp::rand(&mut rng), Fp::rand(&mut rng), Fp::rand(&mut rng));
+    let mut accumulator = OpeningAccumulator::<Vesta>::new();
+    let i1 = accumulator.add_blinded(&p1, p1_comm.blinders.clone(), x);
+    let i2 = accumulator.add(&p2, y);
+    // the same polynomial at another point
+    assert_eq!(
+        accumulator.add_blinded(&p1, p1_comm.blinders.clone(), z),
+        i1
+    );
+    // the same point for another polynomial
+    assert_eq!(accumulator.add(&p2, x), i2);
+    assert_eq!(accumulator.num_polys(), 2);
+    assert_eq!(accumulator.points(), &[x, y, z]);
+
+    let polyscale = Fp::rand(&mut rng);
+    let evalscale = Fp::rand(&mut rng);
+    let opening = accumulator.finalize(
+        &srs,
+        &group_map,
+        polyscale,
+        evalscale,
+        sponge.clone(),
+        &mut rng,
+    );
+
+    // the chunks of p1 sum up to its evaluation
+    let chunks = opening.evaluation(i1, z).unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0] + z.pow([32]) * chunks[1], p1.evaluate(&z));
+    assert_eq!(opening.evaluation(i2, y).unwrap(), &[p2.evaluate(&y)]);
+
+    let commitments = [p1_comm.commitment, p2_comm];
+    assert!(opening.verify(
+        &srs,
+        &group_map,
+        &commitments,
+        polyscale,
+        evalscale,
+        sponge.clone(),
+        &mut rng,
+    ));
+
+    // a wrong evaluation is rejected
+    let mut wrong_opening = opening.clone();
+    wrong_opening.evaluations[i2][1][0] += Fp::one();
+    assert!(!wrong_opening.verify(
+        &srs,
+        &group_map,
+        &commitments,
+        polyscale,
+        evalscale,
+        sponge.clone(),
+        &mut rng,
+    ));
+
+    // so are commitments in the wrong order
+    let swapped = [commitments[1].clone(), commitments[0].clone()];
+    assert!(!opening.verify(&srs, &group_map, &swapped, polyscale, evalscale, sponge, &mut rng,));
+}
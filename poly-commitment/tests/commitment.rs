@@ -14,6 +14,7 @@ use poly_commitment::{
         combined_inner_product, BatchEvaluationProof, BlindedCommitment, CommitmentCurve,
         Evaluation, PolyComm,
     },
+    error::CommitmentError,
     ipa::{DensePolynomialOrEvaluations, OpeningProof, SRS},
     SRS as _,
 };
@@ -436,3 +437,96 @@ pub fn ser_regression_canonical_opening_proof() {
 
     test_generic_serialization_regression_serde(data_expected, buf_expected);
 }
+
+#[test]
+fn test_commit_sparse_evaluations() {
+    use ark_poly::{EvaluationDomain, Evaluations};
+
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    // the domain is larger than the SRS, so that the commitments are chunked
+    let srs = SRS::<Vesta>::create(16);
+    let domain = Radix2EvaluationDomain::<Fp>::new(64).unwrap();
+
+    let sparse: Vec<_> = [0, 5, 17, 63]
+        .into_iter()
+        .map(|row| (row, Fp::rand(&mut rng)))
+        .collect();
+    let mut evals = vec![Fp::zero(); domain.size()];
+    for (row, eval) in &sparse {
+        evals[*row] = *eval;
+    }
+    let evals = Evaluations::from_vec_and_domain(evals, domain);
+
+    assert_eq!(
+        srs.commit_sparse_evaluations_non_hiding(domain, &sparse),
+        srs.commit_evaluations_non_hiding(domain, &evals)
+    );
+
+    // the zero polynomial
+    let zero = Evaluations::from_vec_and_domain(vec![Fp::zero(); domain.size()], domain);
+    assert_eq!(
+        srs.commit_sparse_evaluations_non_hiding(domain, &[]),
+        srs.commit_evaluations_non_hiding(domain, &zero)
+    );
+}
+
+#[test]
+fn test_combine_blinded_commitments() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let srs = SRS::<Vesta>::create(16);
+
+    // Polynomials larger than the SRS, committed in two chunks
+    let p1 = DensePolynomial::<Fp>::rand(25, rng);
+    let p2 = DensePolynomial::<Fp>::rand(30, rng);
+    let c1 = srs.commit(&p1, 2, rng);
+    let c2 = srs.commit(&p2, 2, rng);
+    let (a, b) = (Fp::rand(rng), Fp::rand(rng));
+
+    // The combination commits to the combined polynomial with the combined
+    // blinders
+    let combined = BlindedCommitment::combine(&[&c1, &c2], &[a, b]).unwrap();
+    let coeffs: Vec<_> = p2
+        .coeffs
+        .iter()
+        .enumerate()
+        .map(|(i, c2)| a * p1.coeffs.get(i).copied().unwrap_or_default() + b * c2)
+        .collect();
+    let expected = srs
+        .commit_custom(
+            &DensePolynomial::from_coefficients_vec(coeffs),
+            2,
+            &combined.blinders,
+        )
+        .unwrap();
+    assert_eq!(combined.commitment, expected.commitment);
+
+    let sum = c1.checked_add(&c2).unwrap();
+    assert_eq!(
+        sum.commitment,
+        c1.commitment.checked_add(&c2.commitment).unwrap()
+    );
+    assert_eq!(c1.scale(a).commitment, &c1.commitment * a,);
+    assert_eq!(
+        PolyComm::checked_multi_scalar_mul(&[&c1.commitment, &c2.commitment], &[a, b]).unwrap(),
+        combined.commitment
+    );
+    assert_eq!(
+        sum.commitment.checked_sub(&c2.commitment).unwrap(),
+        c1.commitment
+    );
+
+    // The commitments must have the same number of chunks
+    let c3 = srs.commit(&p1, 3, rng);
+    assert!(matches!(
+        c1.commitment.checked_add(&c3.commitment),
+        Err(CommitmentError::ChunksDontMatch(2, 3))
+    ));
+    assert!(matches!(
+        BlindedCommitment::combine(&[&c1, &c3], &[a, b]),
+        Err(CommitmentError::ChunksDontMatch(2, 3))
+    ));
+    assert!(matches!(
+        PolyComm::checked_multi_scalar_mul(&[&c1.commitment], &[a, b]),
+        Err(CommitmentError::ScalarsDontMatch(2, 1))
+    ));
+}
@@ -11,7 +11,7 @@ use mina_poseidon::{
 use o1_utils::ExtendedDensePolynomial;
 use poly_commitment::{
     commitment::{combined_inner_product, BatchEvaluationProof, CommitmentCurve, Evaluation},
-    ipa::{DensePolynomialOrEvaluations, SRS},
+    ipa::{DensePolynomialOrEvaluations, OpeningProof, SRS},
     pbt_srs, PolyComm, SRS as _,
 };
 use rand::Rng;
@@ -223,3 +223,61 @@ fn test_regression_commit_non_hiding_expected_number_of_chunks() {
     pbt_srs::test_regression_commit_non_hiding_expected_number_of_chunks::<VestaG, SRS<VestaG>>();
     pbt_srs::test_regression_commit_non_hiding_expected_number_of_chunks::<Pallas, SRS<Pallas>>()
 }
+
+#[test]
+fn test_opening_proof_with_fewer_rounds() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let group_map = <VestaG as CommitmentCurve>::Map::setup();
+    let sponge = DefaultFqSponge::<_, SC>::new(mina_poseidon::pasta::fq_kimchi::static_params());
+
+    // A polynomial much smaller than the SRS
+    let srs = SRS::<VestaG>::create(64);
+    assert_eq!(srs.max_rounds(), 6);
+    let poly = DensePolynomial::<Fp>::rand(9, rng);
+    let commitment = srs.commit(&poly, 1, rng);
+
+    let (u, v) = (Fp::rand(rng), Fp::rand(rng));
+    let elm = vec![Fp::rand(rng), Fp::rand(rng)];
+    let polys: Vec<(
+        DensePolynomialOrEvaluations<_, Radix2EvaluationDomain<_>>,
+        PolyComm<_>,
+    )> = vec![(
+        DensePolynomialOrEvaluations::DensePolynomial(&poly),
+        commitment.blinders,
+    )];
+
+    let evals: Vec<Vec<Fp>> = elm.iter().map(|x| vec![poly.evaluate(x)]).collect();
+    let combined_inner_product = combined_inner_product(&v, &u, &[evals.clone()]);
+
+    // The polynomial has less than 2^4 coefficients, so that 4 rounds suffice
+    let short_proof = srs.open_with_rounds(&group_map, &polys, &elm, v, u, sponge.clone(), 4, rng);
+    assert_eq!(short_proof.lr.len(), 4);
+    let full_proof = srs.open(&group_map, &polys, &elm, v, u, sponge.clone(), rng);
+    assert_eq!(full_proof.lr.len(), 6);
+
+    let batch = |openings: &[&OpeningProof<VestaG>]| -> Vec<_> {
+        openings
+            .iter()
+            .map(|opening| BatchEvaluationProof {
+                sponge: sponge.clone(),
+                evaluation_points: elm.clone(),
+                polyscale: v,
+                evalscale: u,
+                evaluations: vec![Evaluation {
+                    commitment: commitment.commitment.clone(),
+                    evaluations: evals.clone(),
+                }],
+                opening: *opening,
+                combined_inner_product,
+            })
+            .collect()
+    };
+
+    // The proofs with different numbers of rounds are verified in batch
+    assert!(srs.verify(&group_map, &mut batch(&[&short_proof, &full_proof]), rng));
+
+    // A proof with more rounds than the SRS allows is rejected
+    let small_srs = SRS::<VestaG>::create(16);
+    assert!(small_srs.verify(&group_map, &mut batch(&[&short_proof]), rng));
+    assert!(!small_srs.verify(&group_map, &mut batch(&[&full_proof]), rng));
+}
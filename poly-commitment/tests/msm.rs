@@ -0,0 +1,126 @@
+use ark_ec::{AffineRepr, VariableBaseMSM};
+use ark_ff::{PrimeField, UniformRand};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use blake2::{Blake2b512, Digest};
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::{
+    error::SRSError,
+    ipa::SRS,
+    msm::{default_window, ArkworksMsm, MsmBackend, Pippenger, PrecomputedTable, MAX_WINDOW},
+    precomputation::SrsPrecomputation,
+    SRS as _,
+};
+
+#[test]
+fn test_msm_backends() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let srs = SRS::<Vesta>::create(100);
+    let scalars: Vec<_> = (0..100)
+        .map(|i| match i {
+            // edge cases of the windows
+            0 => Fp::from(0u64),
+            1 => Fp::from(1u64),
+            2 => -Fp::from(1u64),
+            _ => Fp::rand(&mut rng),
+        })
+        .map(|x| x.into_bigint())
+        .collect();
+
+    let expected = <Vesta as AffineRepr>::Group::msm_bigint(&srs.g, &scalars);
+    assert_eq!(ArkworksMsm.msm(&srs.g, &scalars), expected);
+    assert_eq!(Pippenger::default().msm(&srs.g, &scalars), expected);
+    for window in [1, 5, 8, 13] {
+        assert_eq!(
+            Pippenger::with_window(window).msm(&srs.g, &scalars),
+            expected
+        );
+
+        let table = PrecomputedTable::new(&srs.g, window);
+        assert_eq!(table.num_bases(), srs.g.len());
+        assert_eq!(table.msm_fixed(&scalars), expected);
+        // fewer scalars than bases
+        assert_eq!(
+            table.msm_fixed(&scalars[..10]),
+            <Vesta as AffineRepr>::Group::msm_bigint(&srs.g[..10], &scalars[..10])
+        );
+    }
+}
+
+#[test]
+fn test_default_window() {
+    assert_eq!(default_window(1), 3);
+    assert!(default_window(1 << 16) > default_window(1 << 10));
+    assert_eq!(default_window(1 << 20), MAX_WINDOW);
+    assert_eq!(default_window(1 << 30), MAX_WINDOW);
+    // the window is capped near ln(n)
+    assert!(
+        PrecomputedTable::new(&SRS::<Vesta>::create(100).g, 13).window() <= default_window(100)
+    );
+}
+
+#[test]
+fn test_commit_with_precomputed_table() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let srs = SRS::<Vesta>::create(64);
+    // two chunks
+    let poly = DensePolynomial::<Fp>::rand(100, &mut rng);
+    let expected = srs.commit_non_hiding(&poly, 3);
+
+    srs.precompute_msm_table(6);
+    assert!(srs.msm_table.get().is_some());
+    assert_eq!(srs.commit_non_hiding(&poly, 3), expected);
+    assert_eq!(
+        srs.commit_non_hiding_with(&poly, 3, &Pippenger::default()),
+        expected
+    );
+
+    // the table is shared by the clones of the SRS
+    let cloned = srs.clone();
+    assert_eq!(cloned.msm_table.get().unwrap().window(), 6);
+    assert_eq!(cloned.commit_non_hiding(&poly, 3), expected);
+}
+
+#[test]
+fn test_srs_precomputation_file() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let srs = SRS::<Vesta>::create(32);
+    let poly = DensePolynomial::<Fp>::rand(50, &mut rng);
+    let expected = srs.commit_non_hiding(&poly, 2);
+
+    let path = std::env::temp_dir().join(format!("srs-{}.msm", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // the first run computes the table and writes it
+    srs.load_or_precompute_msm_table(&path, 5).unwrap();
+    assert!(path.exists());
+    assert_eq!(srs.commit_non_hiding(&poly, 2), expected);
+
+    // the next ones load it
+    let loaded = SRS::<Vesta>::create(32);
+    loaded.load_or_precompute_msm_table(&path, 8).unwrap();
+    assert_eq!(loaded.msm_table.get().unwrap().window(), 5);
+    assert_eq!(loaded.commit_non_hiding(&poly, 2), expected);
+
+    // the file cannot be installed on another SRS
+    let precomputation = SrsPrecomputation::<Vesta>::load(&path).unwrap();
+    assert!(matches!(
+        precomputation.install(&SRS::<Vesta>::create(16)),
+        Err(SRSError::PrecomputationMismatch)
+    ));
+
+    // and a corrupted file is rejected
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[100] ^= 1;
+    assert!(matches!(
+        SrsPrecomputation::<Vesta>::from_bytes(&bytes),
+        Err(SRSError::DigestMismatch)
+    ));
+
+    // even when its checksum is recomputed, as its points are checked
+    let content_len = bytes.len() - 64;
+    let checksum = Blake2b512::digest(&bytes[..content_len]);
+    bytes[content_len..].copy_from_slice(&checksum);
+    assert!(SrsPrecomputation::<Vesta>::from_bytes(&bytes).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}
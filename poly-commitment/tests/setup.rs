@@ -0,0 +1,138 @@
+use ark_bn254::{Config, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::{bn::Bn, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use mina_curves::pasta::Vesta;
+use poly_commitment::{
+    error::SRSError,
+    ipa::SRS,
+    kzg::PairingSRS,
+    setup::{SRS_FILE_MAGIC, SRS_FILE_VERSION},
+};
+use std::io::Cursor;
+
+#[test]
+fn test_srs_canonical_roundtrip() {
+    let srs = SRS::<Vesta>::create(64);
+
+    let mut bytes = vec![];
+    let digest = srs.write_canonical(&mut bytes).unwrap();
+    assert_eq!(digest, srs.digest());
+    assert_eq!(bytes[..8], SRS_FILE_MAGIC);
+    assert_eq!(bytes[8..12], SRS_FILE_VERSION.to_le_bytes());
+
+    let read = SRS::<Vesta>::read_canonical(bytes.as_slice(), Some(&digest)).unwrap();
+    assert_eq!(read.g, srs.g);
+    assert_eq!(read.h, srs.h);
+    read.check_points().unwrap();
+    read.verify_generated().unwrap();
+
+    // another expected digest is rejected
+    let mut other_digest = digest;
+    other_digest[0] ^= 1;
+    assert!(matches!(
+        SRS::<Vesta>::read_canonical(bytes.as_slice(), Some(&other_digest)),
+        Err(SRSError::DigestMismatch)
+    ));
+
+    // so is a corrupted file
+    let mut corrupted = bytes.clone();
+    corrupted[20] ^= 1;
+    assert!(SRS::<Vesta>::read_canonical(corrupted.as_slice(), None).is_err());
+}
+
+#[test]
+fn test_srs_verify_generated() {
+    let mut srs = SRS::<Vesta>::create(16);
+    srs.verify_generated().unwrap();
+
+    srs.g.swap(3, 4);
+    assert!(matches!(
+        srs.verify_generated(),
+        Err(SRSError::NotGenerated(3))
+    ));
+}
+
+/// Writes the Montgomery form of `x` to a `.ptau` file
+fn write_fq(bytes: &mut Vec<u8>, x: Fq) {
+    bytes.extend(x.0.to_bytes_le());
+}
+
+/// Writes a `.ptau` file with `2^power` powers of `tau`
+fn write_ptau(tau: Fr, power: u32) -> Vec<u8> {
+    let n8 = 32;
+    let powers = |count: usize| {
+        let mut x = Fr::from(1u64);
+        (0..count)
+            .map(|_| {
+                let res = x;
+                x *= tau;
+                res
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut header = vec![];
+    header.extend((n8 as u32).to_le_bytes());
+    header.extend(Fq::MODULUS.to_bytes_le());
+    header.extend(power.to_le_bytes());
+
+    let mut tau_g1 = vec![];
+    for x in powers((1 << (power + 1)) - 1) {
+        let p = (G1Affine::generator() * x).into_affine();
+        write_fq(&mut tau_g1, p.x);
+        write_fq(&mut tau_g1, p.y);
+    }
+
+    let mut tau_g2 = vec![];
+    for x in powers(1 << power) {
+        let p = (G2Affine::generator() * x).into_affine();
+        write_fq(&mut tau_g2, p.x.c0);
+        write_fq(&mut tau_g2, p.x.c1);
+        write_fq(&mut tau_g2, p.y.c0);
+        write_fq(&mut tau_g2, p.y.c1);
+    }
+
+    let mut bytes = b"ptau".to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(3u32.to_le_bytes());
+    for (section_type, data) in [(1u32, header), (2, tau_g1), (3, tau_g2)] {
+        bytes.extend(section_type.to_le_bytes());
+        bytes.extend((data.len() as u64).to_le_bytes());
+        bytes.extend(data);
+    }
+    bytes
+}
+
+#[test]
+fn test_pairing_srs_from_ptau() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let tau = Fr::rand(&mut rng);
+    let ptau = write_ptau(tau, 3);
+
+    let srs = PairingSRS::<Bn<Config>>::from_ptau(Cursor::new(&ptau), 10).unwrap();
+    let expected = PairingSRS::<Bn<Config>>::create_trusted_setup(tau, 10);
+    assert_eq!(srs.full_srs.g, expected.full_srs.g);
+    assert_eq!(srs.full_srs.h, expected.full_srs.h);
+    assert_eq!(srs.verifier_srs.g, expected.verifier_srs.g);
+    assert_eq!(srs.verifier_srs.h, expected.verifier_srs.h);
+    srs.verify(&mut rng).unwrap();
+
+    // the file has 2^4 - 1 powers in G1
+    assert!(matches!(
+        PairingSRS::<Bn<Config>>::from_ptau(Cursor::new(&ptau), 16),
+        Err(SRSError::TooShort(15, 16))
+    ));
+}
+
+#[test]
+fn test_pairing_srs_verify_inconsistent() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let mut srs = PairingSRS::<Bn<Config>>::create_trusted_setup(Fr::rand(&mut rng), 8);
+    srs.verify(&mut rng).unwrap();
+
+    srs.full_srs.g[5] = (srs.full_srs.g[5] + G1Affine::generator()).into_affine();
+    assert!(matches!(
+        srs.verify(&mut rng),
+        Err(SRSError::InconsistentPowers)
+    ));
+}
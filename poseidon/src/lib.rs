@@ -1,8 +1,10 @@
 pub mod constants;
 pub mod dummy_values;
+pub mod params;
 pub mod pasta;
 pub mod permutation;
 pub mod poseidon;
+pub mod rescue;
 pub mod sponge;
 
 pub use sponge::FqSponge; // Commonly used so reexported for convenience
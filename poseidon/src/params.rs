@@ -0,0 +1,359 @@
+//! This module generates the parameters of the Poseidon permutation, i.e. the
+//! round constants and the MDS matrix, for any prime field.
+//!
+//! Two generators are provided:
+//! - [`grain_params`], the generator of the Poseidon paper, which draws the
+//!   parameters from the Grain LFSR seeded with the configuration of the
+//!   permutation (see [`PoseidonConfig`]);
+//! - [`sha256_params`], the generator of `pasta/params.sage`, which draws them
+//!   from SHA-256 hashes of a name. It is the one used to generate the
+//!   parameters of the Pasta fields (e.g. [`fp_kimchi::params`]), which it
+//!   reproduces.
+//!
+//! [`fp_kimchi::params`]: crate::pasta::fp_kimchi::params
+
+use crate::poseidon::ArithmeticSpongeParams;
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+/// The configuration of a Poseidon permutation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoseidonConfig {
+    /// The number of field elements absorbed per permutation
+    pub rate: usize,
+    /// The number of field elements of the state not absorbed into
+    pub capacity: usize,
+    /// The number of full rounds
+    pub full_rounds: usize,
+    /// The number of partial rounds
+    pub partial_rounds: usize,
+    /// The exponent of the S-box
+    pub alpha: u64,
+}
+
+impl PoseidonConfig {
+    /// The configuration of the Poseidon permutation of kimchi (see
+    /// [`PlonkSpongeConstantsKimchi`](crate::constants::PlonkSpongeConstantsKimchi))
+    pub fn kimchi() -> Self {
+        PoseidonConfig {
+            rate: 2,
+            capacity: 1,
+            full_rounds: 55,
+            partial_rounds: 0,
+            alpha: 7,
+        }
+    }
+
+    /// Returns the number of field elements of the state
+    pub fn width(&self) -> usize {
+        self.rate + self.capacity
+    }
+
+    /// Returns the total number of rounds
+    pub fn rounds(&self) -> usize {
+        self.full_rounds + self.partial_rounds
+    }
+}
+
+/// The Grain LFSR of the Poseidon paper, in self-shrinking mode
+pub struct GrainLfsr {
+    state: [bool; 80],
+}
+
+impl GrainLfsr {
+    /// Creates the LFSR for a permutation of `config` over a prime field of
+    /// `field_size` bits, and discards its first 160 bits
+    pub fn new(config: &PoseidonConfig, field_size: u64) -> Self {
+        let mut state = [true; 80];
+        let mut bits = vec![];
+        let mut push = |value: u64, size: usize| {
+            bits.extend((0..size).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        // prime field, and S-box x^alpha
+        push(1, 2);
+        push(0, 4);
+        push(field_size, 12);
+        push(config.width() as u64, 12);
+        push(config.full_rounds as u64, 10);
+        push(config.partial_rounds as u64, 10);
+        // the last 30 bits are ones
+        state[..bits.len()].copy_from_slice(&bits);
+
+        let mut lfsr = GrainLfsr { state };
+        for _ in 0..160 {
+            lfsr.next_raw_bit();
+        }
+        lfsr
+    }
+
+    fn next_raw_bit(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.copy_within(1.., 0);
+        self.state[79] = bit;
+        bit
+    }
+
+    /// Returns the next bit of the self-shrinking generator: the bits are
+    /// taken by pairs, and the second one is output if the first one is set.
+    pub fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.next_raw_bit();
+            let bit = self.next_raw_bit();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    /// Returns the integer of the next `num_bits` bits, the first one being
+    /// the most significant
+    pub fn next_int(&mut self, num_bits: usize) -> BigUint {
+        let mut res = BigUint::default();
+        for _ in 0..num_bits {
+            res <<= 1;
+            if self.next_bit() {
+                res |= BigUint::from(1u8);
+            }
+        }
+        res
+    }
+
+    /// Returns the next field element, resampled until the integer of its
+    /// bits is smaller than the modulus
+    pub fn next_field_element<F: PrimeField>(&mut self) -> F {
+        let modulus: BigUint = F::MODULUS.into();
+        loop {
+            let x = self.next_int(F::MODULUS_BIT_SIZE as usize);
+            if x < modulus {
+                return F::from(x);
+            }
+        }
+    }
+
+    /// Returns the next field element, the integer of its bits being reduced
+    /// modulo the modulus
+    pub fn next_field_element_reduced<F: PrimeField>(&mut self) -> F {
+        F::from(self.next_int(F::MODULUS_BIT_SIZE as usize))
+    }
+}
+
+/// Generates the parameters of a Poseidon permutation of `config` over `F`
+/// with the Grain LFSR, as the reference script of the Poseidon paper does:
+/// the round constants first, then a Cauchy matrix `1 / (x_i + y_j)` for
+/// distinct `x_i` and `y_j`, as the MDS matrix.
+///
+/// The reference script also discards the matrices with invariant subspace
+/// trails, which is not implemented here. The matrices are MDS, but they
+/// should be checked with the reference script before being used with
+/// partial rounds.
+///
+/// # Panics
+///
+/// Will panic if the width of `config` is 0.
+pub fn grain_params<F: PrimeField>(config: &PoseidonConfig) -> ArithmeticSpongeParams<F> {
+    let width = config.width();
+    assert!(width > 0, "the state must not be empty");
+    let mut lfsr = GrainLfsr::new(config, F::MODULUS_BIT_SIZE as u64);
+
+    let round_constants = (0..config.rounds())
+        .map(|_| (0..width).map(|_| lfsr.next_field_element()).collect())
+        .collect();
+
+    let mds = loop {
+        let values: Vec<F> = (0..2 * width)
+            .map(|_| lfsr.next_field_element_reduced())
+            .collect();
+        if !all_distinct(&values) {
+            continue;
+        }
+        let (xs, ys) = values.split_at(width);
+        let mds: Option<Vec<Vec<F>>> = xs
+            .iter()
+            .map(|x| ys.iter().map(|y| (*x + y).inverse()).collect())
+            .collect();
+        if let Some(mds) = mds {
+            break mds;
+        }
+    };
+
+    ArithmeticSpongeParams {
+        round_constants,
+        mds,
+    }
+}
+
+/// Returns the element `i` of the sequence `prefix`, i.e. the first hash
+/// `SHA-256(prefix || i || "_" || j)` smaller than the modulus
+fn sha256_value<F: PrimeField>(prefix: &str, i: usize) -> F {
+    let modulus: BigUint = F::MODULUS.into();
+    (0..)
+        .map(|j| BigUint::from_bytes_be(&Sha256::digest(format!("{prefix}{i}_{j}"))))
+        .find(|x| *x < modulus)
+        .map(F::from)
+        .unwrap()
+}
+
+/// Generates `rounds` rounds of constants and the MDS matrix of a Poseidon
+/// permutation of width `width` over `F`, as `pasta/params.sage` does in
+/// named mode. `name` is the name of the parameter set, e.g. `Pasta_p_kimchi`
+/// for the parameters of [`fp_kimchi`](crate::pasta::fp_kimchi).
+///
+/// The MDS matrix is the first Cauchy matrix `1 / (x_i - y_j)` without
+/// eigenvalues in `F`.
+///
+/// # Panics
+///
+/// Will panic if `width` is 0, or if no matrix is found in 100 attempts, as
+/// the script does.
+pub fn sha256_params<F: PrimeField>(
+    name: &str,
+    width: usize,
+    rounds: usize,
+) -> ArithmeticSpongeParams<F> {
+    assert!(width > 0, "the state must not be empty");
+    let prefix = format!("CodaRescue{name}");
+
+    let round_constants = (0..rounds)
+        .map(|r| {
+            (0..width)
+                .map(|i| sha256_value(&format!("{prefix}RoundConstants"), r * width + i))
+                .collect()
+        })
+        .collect();
+
+    let mds = (0..100)
+        .find_map(|attempt| {
+            let values = |axis: &str| -> Vec<F> {
+                (0..width)
+                    .map(|i| sha256_value(&format!("{prefix}MDS{axis}"), attempt * width + i))
+                    .collect()
+            };
+            let (xs, ys) = (values("x"), values("y"));
+            assert!(
+                all_distinct(&[xs.as_slice(), ys.as_slice()].concat()),
+                "the values of the Cauchy matrix are not distinct"
+            );
+            let mds: Vec<Vec<F>> = xs
+                .iter()
+                .map(|x| ys.iter().map(|y| (*x - y).inverse().unwrap()).collect())
+                .collect();
+            (!has_eigenvalue(&mds)).then_some(mds)
+        })
+        .expect("no MDS matrix without eigenvalues found");
+
+    ArithmeticSpongeParams {
+        round_constants,
+        mds,
+    }
+}
+
+fn all_distinct<F: PrimeField>(values: &[F]) -> bool {
+    values
+        .iter()
+        .enumerate()
+        .all(|(i, x)| !values[..i].contains(x))
+}
+
+/// Returns the coefficients of the characteristic polynomial of the square
+/// matrix `m`, from the constant one, with the Faddeev-LeVerrier algorithm
+fn characteristic_polynomial<F: PrimeField>(m: &[Vec<F>]) -> Vec<F> {
+    let n = m.len();
+    let mut coeffs = vec![F::zero(); n + 1];
+    coeffs[n] = F::one();
+    // m_k = m * m_{k - 1} + c_{n - k + 1} * I, with m_0 = 0
+    let mut m_k = vec![vec![F::zero(); n]; n];
+    for k in 1..=n {
+        let mut next: Vec<Vec<F>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (0..n).map(|l| m[i][l] * m_k[l][j]).sum())
+                    .collect()
+            })
+            .collect();
+        for (i, row) in next.iter_mut().enumerate() {
+            row[i] += coeffs[n - k + 1];
+        }
+        m_k = next;
+        let trace: F = (0..n)
+            .map(|i| (0..n).map(|l| m[i][l] * m_k[l][i]).sum::<F>())
+            .sum();
+        coeffs[n - k] = -trace / F::from(k as u64);
+    }
+    coeffs
+}
+
+/// Removes the leading zero coefficients of `a`
+fn trim<F: PrimeField>(mut a: Vec<F>) -> Vec<F> {
+    while a.last().map_or(false, |c| c.is_zero()) {
+        a.pop();
+    }
+    a
+}
+
+/// Returns the remainder of the division of `a` by the non-zero `b`
+fn rem<F: PrimeField>(a: &[F], b: &[F]) -> Vec<F> {
+    let mut r = trim(a.to_vec());
+    let lead_inv = b[b.len() - 1].inverse().unwrap();
+    while r.len() >= b.len() {
+        let q = r[r.len() - 1] * lead_inv;
+        let shift = r.len() - b.len();
+        for (i, c) in b.iter().enumerate() {
+            r[shift + i] -= q * c;
+        }
+        r = trim(r);
+    }
+    r
+}
+
+/// Returns `a * b mod f`
+fn mul_mod<F: PrimeField>(a: &[F], b: &[F], f: &[F]) -> Vec<F> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut res = vec![F::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            res[i + j] += *x * y;
+        }
+    }
+    rem(&res, f)
+}
+
+/// Returns whether the square matrix `m` has an eigenvalue in `F`, i.e.
+/// whether its characteristic polynomial `f` has a root in `F`, i.e. whether
+/// `gcd(f, x^p - x)` is not constant
+fn has_eigenvalue<F: PrimeField>(m: &[Vec<F>]) -> bool {
+    let f = characteristic_polynomial(m);
+
+    // x^p mod f, by square and multiply
+    let x = rem(&[F::zero(), F::one()], &f);
+    let mut x_p = vec![F::one()];
+    for bit in F::MODULUS.to_bits_be() {
+        x_p = mul_mod(&x_p, &x_p, &f);
+        if bit {
+            x_p = mul_mod(&x_p, &x, &f);
+        }
+    }
+
+    // x^p - x mod f
+    let mut g = x_p;
+    g.resize(std::cmp::max(g.len(), x.len()), F::zero());
+    for (c, x_c) in g.iter_mut().zip(&x) {
+        *c -= x_c;
+    }
+    let mut g = trim(g);
+    if g.is_empty() {
+        // all the elements of F are roots
+        return true;
+    }
+
+    let mut f = f;
+    while !g.is_empty() {
+        let r = rem(&f, &g);
+        f = g;
+        g = r;
+    }
+    f.len() > 1
+}
@@ -0,0 +1,424 @@
+//! This module implements the Rescue-Prime permutation, and sponges over it
+//! which can be used in place of the Poseidon sponges as Fiat-Shamir oracles:
+//! [`RescueFqSponge`] implements [`FqSponge`], and the kimchi `FrSponge` is
+//! implemented for [`RescueFrSponge`].
+//!
+//! The parameters follow the reference implementation of Rescue-Prime
+//! (<https://eprint.iacr.org/2020/1143>): the S-box exponent is the smallest
+//! prime `alpha` coprime with `p - 1`, the number of rounds is derived from
+//! the security level, the round constants are drawn from SHAKE256, and the
+//! MDS matrix is derived from a Vandermonde matrix. The only difference is
+//! the primitive element of the Vandermonde matrix, which is
+//! [`FftField::GENERATOR`](ark_ff::FftField::GENERATOR) instead of the
+//! smallest primitive element. For the Pasta fields, both are 5, so the
+//! parameters are the ones of the reference implementation.
+//!
+//! The sponges absorb and squeeze as the Poseidon ones do, the parameters
+//! given to their constructors are ignored, and the ones of
+//! [`RescueField::rescue_params`] are used instead.
+
+use crate::{
+    poseidon::{ArithmeticSpongeParams, SpongeState},
+    sponge::{pack, scalar_to_base, FqSponge, CHALLENGE_LENGTH_IN_LIMBS, HIGH_ENTROPY_LIMBS},
+};
+use ark_ec::models::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ff::{PrimeField, Zero};
+use mina_curves::pasta::{Fp, Fq};
+use num_bigint::BigUint;
+use once_cell::sync::Lazy;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+/// The number of field elements of the state of the Rescue sponges
+pub const RESCUE_WIDTH: usize = 3;
+
+/// The number of field elements of the state of the Rescue sponges not
+/// absorbed into
+pub const RESCUE_CAPACITY: usize = 1;
+
+/// The security level of the Rescue sponges, in bits
+pub const RESCUE_SECURITY_LEVEL: usize = 128;
+
+/// The parameters of a Rescue-Prime permutation
+#[derive(Clone, Debug)]
+pub struct RescueParams<F> {
+    /// The number of field elements of the state
+    pub width: usize,
+    /// The number of field elements of the state not absorbed into
+    pub capacity: usize,
+    /// The number of rounds, each made of an S-box step and an inverse S-box
+    /// step
+    pub rounds: usize,
+    /// The exponent of the S-box
+    pub alpha: u64,
+    /// The exponent of the inverse S-box, i.e. the inverse of `alpha` modulo
+    /// `p - 1`, as little-endian limbs
+    pub alpha_inv: Vec<u64>,
+    /// The MDS matrix
+    pub mds: Vec<Vec<F>>,
+    /// The round constants, two rows per round
+    pub round_constants: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> RescueParams<F> {
+    /// Generates the parameters of a Rescue-Prime permutation of `width`
+    /// elements with a capacity of `capacity` elements, for a security level
+    /// of `security_level` bits
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `capacity` is not smaller than `width`.
+    pub fn new(width: usize, capacity: usize, security_level: usize) -> Self {
+        assert!(capacity < width, "the rate must not be zero");
+        let (alpha, alpha_inv) = sbox_exponents::<F>();
+        let rounds = num_rounds(width, capacity, security_level, alpha);
+        RescueParams {
+            width,
+            capacity,
+            rounds,
+            alpha,
+            alpha_inv: alpha_inv.to_u64_digits(),
+            mds: mds_matrix(width),
+            round_constants: round_constants(width, capacity, security_level, rounds),
+        }
+    }
+
+    fn apply_mds(&self, state: &mut [F]) {
+        let res: Vec<F> = self
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum())
+            .collect();
+        state.copy_from_slice(&res);
+    }
+
+    /// Applies the permutation to `state`
+    pub fn permute(&self, state: &mut [F]) {
+        for r in 0..self.rounds {
+            for x in state.iter_mut() {
+                *x = x.pow([self.alpha]);
+            }
+            self.apply_mds(state);
+            for (x, c) in state.iter_mut().zip(&self.round_constants[2 * r]) {
+                *x += c;
+            }
+
+            for x in state.iter_mut() {
+                *x = x.pow(&self.alpha_inv);
+            }
+            self.apply_mds(state);
+            for (x, c) in state.iter_mut().zip(&self.round_constants[2 * r + 1]) {
+                *x += c;
+            }
+        }
+    }
+}
+
+/// Returns the smallest prime `alpha` coprime with `p - 1`, and its inverse
+/// modulo `p - 1`
+fn sbox_exponents<F: PrimeField>() -> (u64, BigUint) {
+    let modulus: BigUint = F::MODULUS.into();
+    let p_minus_one = modulus - 1u8;
+    let is_prime = |n: u64| (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0);
+    (3u64..)
+        .filter(|alpha| is_prime(*alpha))
+        .find_map(|alpha| {
+            BigUint::from(alpha)
+                .modinv(&p_minus_one)
+                .map(|alpha_inv| (alpha, alpha_inv))
+        })
+        .unwrap()
+}
+
+/// Returns the binomial coefficient `n` choose `k`
+fn binomial(n: usize, k: usize) -> BigUint {
+    (1..=k).fold(BigUint::from(1u8), |acc, i| acc * (n - k + i) / i)
+}
+
+/// Returns the number of rounds resisting the Gröbner basis attacks at the
+/// security level, plus 50%, with a minimum of 8
+fn num_rounds(width: usize, capacity: usize, security_level: usize, alpha: u64) -> usize {
+    let rate = width - capacity;
+    let target = BigUint::from(1u8) << security_level;
+    let l1 = (1..25)
+        .find(|&l| {
+            let degree = (alpha as usize - 1) * width * (l - 1) / 2 + 2;
+            let variables = width * (l - 1) + rate;
+            binomial(variables + degree, variables).pow(2) > target
+        })
+        .unwrap_or(25);
+    (3 * std::cmp::max(5, l1) + 1) / 2
+}
+
+/// Returns the round constants, drawn from SHAKE256 seeded with the
+/// parameters of the permutation
+fn round_constants<F: PrimeField>(
+    width: usize,
+    capacity: usize,
+    security_level: usize,
+    rounds: usize,
+) -> Vec<Vec<F>> {
+    let bytes_per_int = (F::MODULUS_BIT_SIZE as usize + 7) / 8 + 1;
+    let modulus: BigUint = F::MODULUS.into();
+    let seed = format!("Rescue-XLIX({modulus},{width},{capacity},{security_level})");
+    let mut shake = Shake256::default();
+    shake.update(seed.as_bytes());
+    let mut reader = shake.finalize_xof();
+
+    (0..2 * rounds)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    let mut chunk = vec![0u8; bytes_per_int];
+                    reader.read(&mut chunk);
+                    F::from_le_bytes_mod_order(&chunk)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the MDS matrix: the transpose of the right half of the reduced
+/// echelon form of the Vandermonde matrix `g^(i * j)` of `width` rows and
+/// `2 * width` columns
+fn mds_matrix<F: PrimeField>(width: usize) -> Vec<Vec<F>> {
+    let g = F::GENERATOR;
+    let mut v: Vec<Vec<F>> = (0..width)
+        .map(|i| (0..2 * width).map(|j| g.pow([(i * j) as u64])).collect())
+        .collect();
+
+    // the left half is an invertible Vandermonde matrix, so the reduced
+    // echelon form is the identity followed by the right half
+    for col in 0..width {
+        let pivot = (col..width).find(|&row| !v[row][col].is_zero()).unwrap();
+        v.swap(col, pivot);
+        let inv = v[col][col].inverse().unwrap();
+        for x in v[col].iter_mut() {
+            *x *= inv;
+        }
+        for row in 0..width {
+            if row != col && !v[row][col].is_zero() {
+                let factor = v[row][col];
+                let pivot_row = v[col].clone();
+                for (x, p) in v[row].iter_mut().zip(pivot_row) {
+                    *x -= factor * p;
+                }
+            }
+        }
+    }
+
+    (0..width)
+        .map(|i| (0..width).map(|j| v[j][width + i]).collect())
+        .collect()
+}
+
+/// A field with Rescue-Prime parameters available for the sponges
+pub trait RescueField: PrimeField {
+    /// Returns the parameters of the permutation of the sponges, of
+    /// [`RESCUE_WIDTH`] elements with a capacity of [`RESCUE_CAPACITY`]
+    /// elements, at the [`RESCUE_SECURITY_LEVEL`]
+    fn rescue_params() -> &'static RescueParams<Self>;
+}
+
+impl RescueField for Fp {
+    fn rescue_params() -> &'static RescueParams<Fp> {
+        static PARAMS: Lazy<RescueParams<Fp>> =
+            Lazy::new(|| RescueParams::new(RESCUE_WIDTH, RESCUE_CAPACITY, RESCUE_SECURITY_LEVEL));
+        &PARAMS
+    }
+}
+
+impl RescueField for Fq {
+    fn rescue_params() -> &'static RescueParams<Fq> {
+        static PARAMS: Lazy<RescueParams<Fq>> =
+            Lazy::new(|| RescueParams::new(RESCUE_WIDTH, RESCUE_CAPACITY, RESCUE_SECURITY_LEVEL));
+        &PARAMS
+    }
+}
+
+/// A duplex sponge over the Rescue-Prime permutation, absorbing into the
+/// first elements of the state as [`ArithmeticSponge`] does
+///
+/// [`ArithmeticSponge`]: crate::poseidon::ArithmeticSponge
+#[derive(Clone)]
+pub struct RescueSponge<F: 'static> {
+    pub sponge_state: SpongeState,
+    pub state: Vec<F>,
+    params: &'static RescueParams<F>,
+}
+
+impl<F: PrimeField> RescueSponge<F> {
+    /// Creates a sponge over the permutation of `params`
+    pub fn new(params: &'static RescueParams<F>) -> Self {
+        RescueSponge {
+            sponge_state: SpongeState::Absorbed(0),
+            state: vec![F::zero(); params.width],
+            params,
+        }
+    }
+
+    fn rate(&self) -> usize {
+        self.params.width - self.params.capacity
+    }
+
+    /// Absorbs the field elements `x`
+    pub fn absorb(&mut self, x: &[F]) {
+        for x in x.iter() {
+            match self.sponge_state {
+                SpongeState::Absorbed(n) if n == self.rate() => {
+                    self.params.permute(&mut self.state);
+                    self.sponge_state = SpongeState::Absorbed(1);
+                    self.state[0] += x;
+                }
+                SpongeState::Absorbed(n) => {
+                    self.sponge_state = SpongeState::Absorbed(n + 1);
+                    self.state[n] += x;
+                }
+                SpongeState::Squeezed(_) => {
+                    self.state[0] += x;
+                    self.sponge_state = SpongeState::Absorbed(1);
+                }
+            }
+        }
+    }
+
+    /// Squeezes a field element
+    pub fn squeeze(&mut self) -> F {
+        match self.sponge_state {
+            SpongeState::Squeezed(n) if n < self.rate() => {
+                self.sponge_state = SpongeState::Squeezed(n + 1);
+                self.state[n]
+            }
+            _ => {
+                self.params.permute(&mut self.state);
+                self.sponge_state = SpongeState::Squeezed(1);
+                self.state[0]
+            }
+        }
+    }
+
+    /// Resets the sponge to its initial state
+    pub fn reset(&mut self) {
+        self.state = vec![F::zero(); self.state.len()];
+        self.sponge_state = SpongeState::Absorbed(0);
+    }
+}
+
+/// The Rescue counterpart of [`DefaultFqSponge`](crate::sponge::DefaultFqSponge)
+#[derive(Clone)]
+pub struct RescueFqSponge<P: SWCurveConfig>
+where
+    P::BaseField: 'static,
+{
+    pub sponge: RescueSponge<P::BaseField>,
+    pub last_squeezed: Vec<u64>,
+}
+
+impl<P: SWCurveConfig> RescueFqSponge<P>
+where
+    P::BaseField: RescueField,
+    <P::BaseField as PrimeField>::BigInt: Into<<P::ScalarField as PrimeField>::BigInt>,
+{
+    pub fn squeeze_limbs(&mut self, num_limbs: usize) -> Vec<u64> {
+        while self.last_squeezed.len() < num_limbs {
+            let x = self.sponge.squeeze().into_bigint();
+            self.last_squeezed
+                .extend(&x.as_ref()[0..HIGH_ENTROPY_LIMBS]);
+        }
+        self.last_squeezed.drain(..num_limbs).collect()
+    }
+
+    pub fn squeeze_field(&mut self) -> P::BaseField {
+        self.last_squeezed = vec![];
+        self.sponge.squeeze()
+    }
+
+    pub fn squeeze(&mut self, num_limbs: usize) -> P::ScalarField {
+        P::ScalarField::from_bigint(pack(&self.squeeze_limbs(num_limbs)))
+            .expect("internal representation was not a valid field element")
+    }
+}
+
+impl<P: SWCurveConfig> FqSponge<P::BaseField, Affine<P>, P::ScalarField> for RescueFqSponge<P>
+where
+    P::BaseField: RescueField,
+    <P::BaseField as PrimeField>::BigInt: Into<<P::ScalarField as PrimeField>::BigInt>,
+{
+    fn new(_params: &'static ArithmeticSpongeParams<P::BaseField>) -> Self {
+        RescueFqSponge {
+            sponge: RescueSponge::new(P::BaseField::rescue_params()),
+            last_squeezed: vec![],
+        }
+    }
+
+    fn absorb_g(&mut self, g: &[Affine<P>]) {
+        self.last_squeezed = vec![];
+        for g in g.iter() {
+            if g.infinity {
+                // absorb a fake point (0, 0)
+                self.sponge
+                    .absorb(&[P::BaseField::zero(), P::BaseField::zero()]);
+            } else {
+                self.sponge.absorb(&[g.x, g.y]);
+            }
+        }
+    }
+
+    fn absorb_fq(&mut self, x: &[P::BaseField]) {
+        self.last_squeezed = vec![];
+        self.sponge.absorb(x);
+    }
+
+    fn absorb_fr(&mut self, x: &[P::ScalarField]) {
+        self.last_squeezed = vec![];
+        for x in x.iter() {
+            self.sponge.absorb(&scalar_to_base::<P>(x));
+        }
+    }
+
+    fn digest(mut self) -> P::ScalarField {
+        let x: <P::BaseField as PrimeField>::BigInt = self.squeeze_field().into_bigint();
+        // Returns zero for values that are too large, as the default sponge
+        P::ScalarField::from_bigint(x.into()).unwrap_or_else(P::ScalarField::zero)
+    }
+
+    fn digest_fq(mut self) -> P::BaseField {
+        self.squeeze_field()
+    }
+
+    fn challenge(&mut self) -> P::ScalarField {
+        self.squeeze(CHALLENGE_LENGTH_IN_LIMBS)
+    }
+
+    fn challenge_fq(&mut self) -> P::BaseField {
+        self.squeeze_field()
+    }
+}
+
+/// The Rescue counterpart of [`DefaultFrSponge`](crate::sponge::DefaultFrSponge)
+pub struct RescueFrSponge<Fr: 'static> {
+    pub sponge: RescueSponge<Fr>,
+    pub last_squeezed: Vec<u64>,
+}
+
+impl<Fr: RescueField> RescueFrSponge<Fr> {
+    /// Creates a sponge with the parameters of [`RescueField::rescue_params`]
+    pub fn create() -> Self {
+        RescueFrSponge {
+            sponge: RescueSponge::new(Fr::rescue_params()),
+            last_squeezed: vec![],
+        }
+    }
+
+    pub fn squeeze(&mut self, num_limbs: usize) -> Fr {
+        while self.last_squeezed.len() < num_limbs {
+            let x = self.sponge.squeeze().into_bigint();
+            self.last_squeezed
+                .extend(&x.as_ref()[0..HIGH_ENTROPY_LIMBS]);
+        }
+        let limbs: Vec<u64> = self.last_squeezed.drain(..num_limbs).collect();
+        Fr::from(pack::<Fr::BigInt>(&limbs))
+    }
+}
@@ -41,7 +41,7 @@ pub trait FqSponge<Fq: Field, G, Fr> {
 
 pub const CHALLENGE_LENGTH_IN_LIMBS: usize = 2;
 
-const HIGH_ENTROPY_LIMBS: usize = 2;
+pub(crate) const HIGH_ENTROPY_LIMBS: usize = 2;
 
 // TODO: move to a different file / module
 /// A challenge which is used as a scalar on a group element in the verifier
@@ -61,7 +61,23 @@ fn get_bit(limbs_lsb: &[u64], i: u64) -> u64 {
 }
 
 impl<F: PrimeField> ScalarChallenge<F> {
-    pub fn to_field_with_length(&self, length_in_bits: usize, endo_coeff: &F) -> F {
+    /// Creates a challenge from its limbs, least significant first, as squeezed by
+    /// [DefaultFqSponge::squeeze_limbs]
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the limbs do not encode an element of the field.
+    pub fn from_limbs(limbs_lsb: &[u64]) -> Self {
+        ScalarChallenge(
+            F::from_bigint(pack(limbs_lsb))
+                .expect("internal representation was not a valid field element"),
+        )
+    }
+
+    /// Returns the coefficients `(a, b)` of the endo-scalar `a * endo_coeff + b`
+    /// represented by the `length_in_bits` lower bits of the challenge, see
+    /// [Self::to_field_with_length]
+    pub fn to_endo_coefficients_with_length(&self, length_in_bits: usize) -> (F, F) {
         let rep = self.0.into_bigint();
         let r = rep.as_ref();
 
@@ -85,6 +101,19 @@ impl<F: PrimeField> ScalarChallenge<F> {
             }
         }
 
+        (a, b)
+    }
+
+    /// Returns the coefficients `(a, b)` of the endo-scalar represented by the
+    /// challenge, see [Self::to_field]
+    pub fn to_endo_coefficients(&self) -> (F, F) {
+        self.to_endo_coefficients_with_length(64 * CHALLENGE_LENGTH_IN_LIMBS)
+    }
+
+    /// Returns the endo-scalar `a * endo_coeff + b` represented by the
+    /// `length_in_bits` lower bits of the challenge, read two bits at a time
+    pub fn to_field_with_length(&self, length_in_bits: usize, endo_coeff: &F) -> F {
+        let (a, b) = self.to_endo_coefficients_with_length(length_in_bits);
         a * endo_coeff + b
     }
 
@@ -105,7 +134,7 @@ pub struct DefaultFrSponge<Fr: Field, SC: SpongeConstants> {
     pub last_squeezed: Vec<u64>,
 }
 
-fn pack<B: BigInteger>(limbs_lsb: &[u64]) -> B {
+pub(crate) fn pack<B: BigInteger>(limbs_lsb: &[u64]) -> B {
     let mut res: B = 0u64.into();
     for &x in limbs_lsb.iter().rev() {
         res.muln(64);
@@ -130,6 +159,36 @@ impl<Fr: PrimeField, SC: SpongeConstants> DefaultFrSponge<Fr, SC> {
     }
 }
 
+/// Returns the base field elements encoding the scalar `x`: the scalar itself
+/// if the scalar field is smaller than the base field, and its high bits
+/// followed by its low bit otherwise.
+pub(crate) fn scalar_to_base<P: SWCurveConfig>(x: &P::ScalarField) -> Vec<P::BaseField>
+where
+    P::BaseField: PrimeField,
+{
+    let bits = x.into_bigint().to_bits_le();
+
+    if <P::ScalarField as PrimeField>::MODULUS < <P::BaseField as PrimeField>::MODULUS.into() {
+        let fe =
+            P::BaseField::from_bigint(<P::BaseField as PrimeField>::BigInt::from_bits_le(&bits))
+                .expect("padding code has a bug");
+        vec![fe]
+    } else {
+        let low_bit = if bits[0] {
+            P::BaseField::one()
+        } else {
+            P::BaseField::zero()
+        };
+
+        let high_bits = P::BaseField::from_bigint(
+            <P::BaseField as PrimeField>::BigInt::from_bits_le(&bits[1..bits.len()]),
+        )
+        .expect("padding code has a bug");
+
+        vec![high_bits, low_bit]
+    }
+}
+
 impl<P: SWCurveConfig, SC: SpongeConstants> DefaultFqSponge<P, SC>
 where
     P::BaseField: PrimeField,
@@ -201,32 +260,7 @@ where
         self.last_squeezed = vec![];
 
         x.iter().for_each(|x| {
-            let bits = x.into_bigint().to_bits_le();
-
-            // absorb
-            if <P::ScalarField as PrimeField>::MODULUS
-                < <P::BaseField as PrimeField>::MODULUS.into()
-            {
-                let fe = P::BaseField::from_bigint(
-                    <P::BaseField as PrimeField>::BigInt::from_bits_le(&bits),
-                )
-                .expect("padding code has a bug");
-                self.sponge.absorb(&[fe]);
-            } else {
-                let low_bit = if bits[0] {
-                    P::BaseField::one()
-                } else {
-                    P::BaseField::zero()
-                };
-
-                let high_bits = P::BaseField::from_bigint(
-                    <P::BaseField as PrimeField>::BigInt::from_bits_le(&bits[1..bits.len()]),
-                )
-                .expect("padding code has a bug");
-
-                self.sponge.absorb(&[high_bits]);
-                self.sponge.absorb(&[low_bit]);
-            }
+            self.sponge.absorb(&scalar_to_base::<P>(x));
         });
     }
 
@@ -0,0 +1,130 @@
+use ark_ff::{Field, PrimeField, Zero};
+use mina_curves::pasta::{Fp, Fq};
+use mina_poseidon::{
+    params::{grain_params, sha256_params, PoseidonConfig},
+    pasta::{fp_kimchi, fq_kimchi},
+};
+use num_bigint::BigUint;
+
+#[test]
+fn test_sha256_params_reproduce_pasta_kimchi() {
+    let params = sha256_params::<Fp>("Pasta_p_kimchi", 3, 55);
+    let expected = fp_kimchi::params();
+    assert_eq!(params.mds, expected.mds);
+    assert_eq!(params.round_constants, expected.round_constants);
+
+    let params = sha256_params::<Fq>("Pasta_q_kimchi", 3, 55);
+    let expected = fq_kimchi::params();
+    assert_eq!(params.mds, expected.mds);
+    assert_eq!(params.round_constants, expected.round_constants);
+}
+
+/// Returns the determinant of the square matrix `m`, by Laplace expansion
+fn determinant<F: Field>(m: &[Vec<F>]) -> F {
+    if m.len() == 1 {
+        return m[0][0];
+    }
+    (0..m.len())
+        .map(|j| {
+            let minor: Vec<Vec<F>> = m[1..]
+                .iter()
+                .map(|row| [&row[..j], &row[j + 1..]].concat())
+                .collect();
+            let term = m[0][j] * determinant(&minor);
+            if j % 2 == 0 {
+                term
+            } else {
+                -term
+            }
+        })
+        .sum()
+}
+
+#[test]
+fn test_grain_params() {
+    let config = PoseidonConfig {
+        rate: 4,
+        capacity: 1,
+        full_rounds: 8,
+        partial_rounds: 56,
+        alpha: 5,
+    };
+    let params = grain_params::<Fp>(&config);
+    assert_eq!(params.round_constants.len(), 64);
+    assert!(params.round_constants.iter().all(|r| r.len() == 5));
+    assert_eq!(params.mds.len(), 5);
+
+    // the leading principal minors of the matrix are not zero, as for any
+    // MDS matrix
+    for k in 1..=5 {
+        let sub: Vec<Vec<Fp>> = params.mds[..k].iter().map(|r| r[..k].to_vec()).collect();
+        assert!(!determinant(&sub).is_zero());
+    }
+
+    // the parameters are deterministic, and depend on the configuration and
+    // the field
+    assert_eq!(grain_params::<Fp>(&config).mds, params.mds);
+    let kimchi = grain_params::<Fp>(&PoseidonConfig::kimchi());
+    assert_eq!(kimchi.round_constants.len(), 55);
+    assert_ne!(kimchi.mds[0][0], params.mds[0][0]);
+    let other_field = grain_params::<Fq>(&config);
+    assert_eq!(other_field.round_constants.len(), 64);
+}
+
+fn from_hex<F: PrimeField>(hex: &str) -> F {
+    F::from(BigUint::parse_bytes(hex.as_bytes(), 16).unwrap())
+}
+
+#[test]
+fn test_grain_params_reference_bn254() {
+    // the parameters of the reference script of the Poseidon paper for the
+    // scalar field of BN254, with a state of 3 elements, 8 full rounds and 57
+    // partial rounds
+    let config = PoseidonConfig {
+        rate: 2,
+        capacity: 1,
+        full_rounds: 8,
+        partial_rounds: 57,
+        alpha: 5,
+    };
+    let params = grain_params::<ark_bn254::Fr>(&config);
+    let fr = from_hex::<ark_bn254::Fr>;
+
+    assert_eq!(params.round_constants.len(), 65);
+    assert_eq!(
+        params.round_constants[0],
+        vec![
+            fr("0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e"),
+            fr("00f1445235f2148c5986587169fc1bcd887b08d4d00868df5696fff40956e864"),
+            fr("08dff3487e8ac99e1f29a058d0fa80b930c728730b7ab36ce879f3890ecf73f5"),
+        ]
+    );
+    assert_eq!(
+        params.round_constants[64],
+        vec![
+            fr("0fe0af7858e49859e2a54d6f1ad945b1316aa24bfbdd23ae40a6d0cb70c3eab1"),
+            fr("216f6717bbc7dedb08536a2220843f4e2da5f1daa9ebdefde8a5ea7344798d22"),
+            fr("1da55cc900f0d21f4a3e694391918a1b3c23b2ac773c6b3ef88e2e4228325161"),
+        ]
+    );
+    assert_eq!(
+        params.mds,
+        vec![
+            vec![
+                fr("109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b"),
+                fr("16ed41e13bb9c0c66ae119424fddbcbc9314dc9fdbdeea55d6c64543dc4903e0"),
+                fr("2b90bba00fca0589f617e7dcbfe82e0df706ab640ceb247b791a93b74e36736d"),
+            ],
+            vec![
+                fr("2969f27eed31a480b9c36c764379dbca2cc8fdd1415c3dded62940bcde0bd771"),
+                fr("2e2419f9ec02ec394c9871c832963dc1b89d743c8c7b964029b2311687b1fe23"),
+                fr("101071f0032379b697315876690f053d148d4e109f5fb065c8aacc55a0f89bfa"),
+            ],
+            vec![
+                fr("143021ec686a3f330d5f9e654638065ce6cd79e28c5b3753326244ee65a1b1a7"),
+                fr("176cc029695ad02582a70eff08a6fd99d057e12e58e7d7b6b16cdfabc8ee2911"),
+                fr("19a3fc0a56702bf417ba7fee3802593fa644470307043f7773279cd71d25d5e0"),
+            ],
+        ]
+    );
+}
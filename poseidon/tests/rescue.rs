@@ -0,0 +1,144 @@
+use ark_ff::{Field, UniformRand};
+use mina_curves::pasta::{Fp, Fq, VestaParameters};
+use mina_poseidon::{
+    pasta::fq_kimchi,
+    rescue::{RescueField, RescueFqSponge, RescueSponge},
+    FqSponge,
+};
+use std::str::FromStr;
+
+#[test]
+fn test_rescue_params_pasta() {
+    let params = Fp::rescue_params();
+    assert_eq!(params.alpha, 5);
+    assert_eq!(params.rounds, 14);
+    assert_eq!(params.round_constants.len(), 28);
+    assert_eq!(params.mds.len(), 3);
+    assert_eq!(Fq::rescue_params().alpha, 5);
+
+    // the inverse S-box inverts the S-box
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let x = Fp::rand(&mut rng);
+    assert_eq!(x.pow([params.alpha]).pow(&params.alpha_inv), x);
+}
+
+/// The MDS matrix of the reference implementation of Rescue-Prime for a
+/// state of 3 elements and the primitive element 5, the same for both Pasta
+/// fields
+fn reference_mds<F: Field>() -> Vec<Vec<F>> {
+    [
+        [125, -155, 31],
+        [3875, -4680, 806],
+        [100750, -121055, 20306],
+    ]
+    .iter()
+    .map(|row| {
+        row.iter()
+            .map(|x: &i64| {
+                let abs = F::from(x.unsigned_abs());
+                if *x < 0 {
+                    -abs
+                } else {
+                    abs
+                }
+            })
+            .collect()
+    })
+    .collect()
+}
+
+// The known answers are computed with a Python transcription of the
+// reference implementation of Rescue-Prime
+// (https://eprint.iacr.org/2020/1143), for a state of 3 elements
+// with a capacity of 1 element, at a security level of 128 bits.
+
+#[test]
+fn test_rescue_params_known_answers_fp() {
+    let params = Fp::rescue_params();
+    assert_eq!(params.mds, reference_mds::<Fp>());
+
+    let fp = |s: &str| Fp::from_str(s).unwrap();
+    assert_eq!(
+        params.round_constants[0],
+        vec![
+            fp("26243106459554767469607247592956915736094566177910007828558025981146347436423"),
+            fp("13707970744370316831896521428631184117873736864722778772486588648560340007445"),
+            fp("13377490042436753713758769485286076521497494122336902508218950602973674007840"),
+        ]
+    );
+    assert_eq!(
+        params.round_constants[27],
+        vec![
+            fp("28469608236594034855318921541283507660671653345522118538236634579684778453550"),
+            fp("4411341754389807481224438075540335360172200749438394905230752300189565065415"),
+            fp("27800443969534286033314298971412729594606264382179416891258347152767066172033"),
+        ]
+    );
+
+    let mut state = vec![Fp::from(0u8), Fp::from(1u8), Fp::from(2u8)];
+    params.permute(&mut state);
+    assert_eq!(
+        state,
+        vec![
+            fp("25100298822787230448004655789519478640620591645519643805714043420150739192216"),
+            fp("19481514861432624275377035113708616312088126625180286046531598547738769710401"),
+            fp("9762742222957771867095963197748757671293986512297882699978432690855086569426"),
+        ]
+    );
+}
+
+#[test]
+fn test_rescue_params_known_answers_fq() {
+    let params = Fq::rescue_params();
+    assert_eq!(params.mds, reference_mds::<Fq>());
+
+    let fq = |s: &str| Fq::from_str(s).unwrap();
+    assert_eq!(
+        params.round_constants[0],
+        vec![
+            fq("14009893170288615318002686037534581418714713575914210661006844978745800921438"),
+            fq("10170092346744069303713190271671511506792816703628420240739531713754958642771"),
+            fq("12536006778931335927769718762197690551174306729333591452292214292518616395621"),
+        ]
+    );
+    assert_eq!(
+        params.round_constants[27],
+        vec![
+            fq("10004729166342976034494173135372694241679820261098920180565873332670092546662"),
+            fq("10847010303064842594106987862881849450205976667327775058588462676071592647259"),
+            fq("1888658175185596813305500355526134231501543337032053157892149908163996793051"),
+        ]
+    );
+
+    let mut state = vec![Fq::from(0u8), Fq::from(1u8), Fq::from(2u8)];
+    params.permute(&mut state);
+    assert_eq!(
+        state,
+        vec![
+            fq("4120075052011233652271253769890605889938106678117757876873551257015891370210"),
+            fq("25843394042409093238088709889703743699172913220224297038087124336365454208763"),
+            fq("18772053913077772565505856559236895719208954022615259852506447199556091371227"),
+        ]
+    );
+}
+
+#[test]
+fn test_rescue_sponge() {
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let inputs: Vec<Fp> = (0..5).map(|_| Fp::rand(&mut rng)).collect();
+
+    let hash = |inputs: &[Fp]| {
+        let mut sponge = RescueSponge::new(Fp::rescue_params());
+        sponge.absorb(inputs);
+        sponge.squeeze()
+    };
+    assert_eq!(hash(&inputs), hash(&inputs));
+    assert_ne!(hash(&inputs), hash(&inputs[1..]));
+
+    // the Fq-sponge ignores the Poseidon parameters
+    let mut sponge = RescueFqSponge::<VestaParameters>::new(fq_kimchi::static_params());
+    sponge.absorb_fq(&[Fq::from(1u8)]);
+    let mut other = RescueFqSponge::<VestaParameters>::new(fq_kimchi::static_params());
+    other.absorb_fq(&[Fq::from(1u8)]);
+    assert_eq!(sponge.challenge(), other.challenge());
+}
@@ -0,0 +1,18 @@
+//! A single entry point to the proof systems of this repository.
+//!
+//! The crates of the workspace are re-exported as they are, and the types and
+//! functions needed to build a circuit, and create and verify a proof, are
+//! gathered in [`prelude`]:
+//!
+//! ```ignore
+//! use proof_systems::prelude::*;
+//! ```
+
+pub use groupmap;
+pub use kimchi;
+pub use mina_curves;
+pub use mina_poseidon;
+pub use o1vm;
+pub use poly_commitment;
+
+pub mod prelude;
@@ -0,0 +1,48 @@
+//! The types and functions commonly needed to use the proof systems of the
+//! workspace, to be glob-imported.
+//!
+//! The names are the ones of the crates they come from, except where two
+//! crates use the same name:
+//! - [`verify`] verifies a kimchi proof, and [`verify_vm`] a proof of the
+//!   o1vm;
+//! - [`SRS`] is the SRS of the IPA commitment scheme, and [`SRSTrait`] the
+//!   interface of all the SRS. The trait is in scope with the glob import.
+
+// Curves and sponges
+pub use groupmap::GroupMap;
+pub use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters, Vesta, VestaParameters};
+pub use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+    FqSponge,
+};
+
+// Polynomial commitments
+pub use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    OpenProof, PolyComm, SRS as SRSTrait,
+};
+
+// Kimchi
+pub use kimchi::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::{CircuitGate, GateType},
+        polynomials::generic::GenericGateSpec,
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    error::{ProverError, SetupError, VerifyError},
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::{batch_verify, verify, Context},
+    verifier_index::VerifierIndex,
+};
+
+// o1vm
+pub use o1vm::pickles::{
+    proof::{Proof as VmProof, ProofInputs},
+    prover::prove,
+    verifier::verify as verify_vm,
+};
@@ -0,0 +1,44 @@
+use proof_systems::prelude::*;
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_prove_and_verify_with_prelude() {
+    let num_gates = 20;
+    let gates: Vec<_> = (0..num_gates)
+        .map(|row| {
+            CircuitGate::create_generic_gadget(
+                Wire::for_row(row),
+                GenericGateSpec::Const(1u32.into()),
+                None,
+            )
+        })
+        .collect();
+
+    let cs = ConstraintSystem::<Fp>::create(gates).build().unwrap();
+    let srs = SRS::<Vesta>::create(cs.domain.d1.size());
+    let &endo_q = Vesta::other_curve_endo();
+    let index = ProverIndex::<Vesta, OpeningProof<Vesta>>::create(cs, endo_q, Arc::new(srs));
+    let verifier_index = index.verifier_index();
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![1u32.into(); num_gates]);
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rand::rngs::OsRng,
+    )
+    .unwrap();
+
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &[],
+    )
+    .unwrap();
+}
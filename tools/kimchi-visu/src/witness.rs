@@ -1,19 +1,20 @@
 use ark_ff::Field;
 use kimchi::circuits::polynomial::COLUMNS;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
 
 /// The type that represents the execution trace.
 /// It represents a table of [COLUMNS] columns, with `n` rows.
 /// `n` being the maximum size of the circuit, and the size of the domain.
+/// Non-canonical encodings of field elements are rejected when deserializing.
 #[serde_as]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Witness<F>
 where
     F: Field,
 {
-    #[serde_as(as = "[Vec<o1_utils::serialization::SerdeAs>; COLUMNS]")]
+    #[serde_as(as = "[Vec<o1_utils::serialization::SerdeAsStrict>; COLUMNS]")]
     inner: [Vec<F>; COLUMNS],
 }
 
@@ -2,6 +2,8 @@
 
 use ark_ff::Field;
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use ark_std::cfg_iter_mut;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use crate::chunked_polynomial::ChunkedPolynomial;
@@ -29,10 +31,7 @@ pub trait ExtendedDensePolynomial<F: Field> {
 impl<F: Field> ExtendedDensePolynomial<F> for DensePolynomial<F> {
     fn scale(&self, elm: F) -> Self {
         let mut result = self.clone();
-        result
-            .coeffs
-            .par_iter_mut()
-            .for_each(|coeff: &mut F| *coeff *= &elm);
+        cfg_iter_mut!(result.coeffs).for_each(|coeff: &mut F| *coeff *= &elm);
         result
     }
 
@@ -1,8 +1,12 @@
 //! Useful helper methods to extend [ark_ff::Field].
 
 use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::cfg_chunks_mut;
 use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
+use num_traits::ToPrimitive;
 use rand::rngs::StdRng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::ops::Neg;
 use thiserror::Error;
 
@@ -18,6 +22,8 @@ pub enum FieldHelpersError {
     DecodeHex,
     #[error("failed to convert BigUint into field element")]
     FromBigToField,
+    #[error("the field element does not fit in {0} bits")]
+    Overflow(usize),
 }
 
 /// Result alias using [FieldHelpersError]
@@ -132,6 +138,81 @@ pub trait FieldHelpers<F> {
     {
         BigUint::from_bytes_le(&F::MODULUS.to_bytes_le())
     }
+
+    /// Create a field element from an [i64], a negative integer being the
+    /// negation of its absolute value
+    fn from_i64(x: i64) -> F
+    where
+        F: Field,
+    {
+        if x >= 0 {
+            F::from(x as u64)
+        } else {
+            -F::from(x.unsigned_abs())
+        }
+    }
+
+    /// Create a field element from an [i128], a negative integer being the
+    /// negation of its absolute value
+    fn from_i128(x: i128) -> F
+    where
+        F: Field,
+    {
+        if x >= 0 {
+            F::from(x as u128)
+        } else {
+            -F::from(x.unsigned_abs())
+        }
+    }
+
+    /// Convert the field element into a [u128]
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the element is not smaller than `2^128`.
+    fn to_u128(&self) -> Result<u128>
+    where
+        F: PrimeField,
+    {
+        self.to_biguint()
+            .to_u128()
+            .ok_or(FieldHelpersError::Overflow(128))
+    }
+
+    /// Decompose the field element into three limbs of 88 bits, in
+    /// little-endian order, as the limbs of the range check and of the foreign
+    /// field gates. The last limb holds the bits above `2 * 88`.
+    fn to_limbs_88(&self) -> [F; 3]
+    where
+        F: PrimeField,
+    {
+        let big = self.to_biguint();
+        let mask = (BigUint::from(1u32) << 88) - 1u32;
+        std::array::from_fn(|i| {
+            let limb = if i < 2 {
+                (&big >> (88 * i)) & &mask
+            } else {
+                &big >> (88 * i)
+            };
+            F::from(limb)
+        })
+    }
+
+    /// Decompose the field element into crumbs, i.e. 2-bit values, in
+    /// little-endian order, covering the bits of the modulus
+    fn to_crumbs_2(&self) -> Vec<F>
+    where
+        F: PrimeField,
+    {
+        let bits = self.to_bits();
+        bits[..F::MODULUS_BIT_SIZE as usize]
+            .chunks(2)
+            .map(|crumb| {
+                let high = crumb.get(1).copied().unwrap_or(false);
+                F::from(crumb[0] as u64 + 2 * high as u64)
+            })
+            .collect()
+    }
 }
 
 impl<F: Field> FieldHelpers<F> for F {
@@ -238,3 +319,53 @@ pub fn inner_prod<F: Field>(xs: &[F], ys: &[F]) -> F {
     }
     res
 }
+
+/// The minimal number of elements inverted with a single inversion by
+/// [batch_inverse_in_place_parallel]
+pub const MIN_BATCH_INVERSION_CHUNK: usize = 1 << 10;
+
+/// Inverts the elements of `v` in place with Montgomery's trick, i.e. with a
+/// single inversion and three multiplications per element. The zero elements
+/// are left unchanged.
+pub fn batch_inverse_in_place<F: Field>(v: &mut [F]) {
+    // The products of the first non-zero elements
+    let mut products = Vec::with_capacity(v.len());
+    let mut product = F::one();
+    for x in v.iter().filter(|x| !x.is_zero()) {
+        product *= x;
+        products.push(product);
+    }
+    // The product of non-zero elements is not zero
+    let mut inverse = product.inverse().unwrap();
+    products.pop();
+    for x in v.iter_mut().rev().filter(|x| !x.is_zero()) {
+        // The inverse of the product of the elements up to x, times the
+        // product of the elements before x
+        let x_inverse = inverse * products.pop().unwrap_or_else(F::one);
+        inverse *= *x;
+        *x = x_inverse;
+    }
+}
+
+/// Same as [batch_inverse_in_place], where `v` is split in chunks of
+/// `chunk_size` elements, each chunk being inverted with its own inversion. The
+/// chunks are inverted in parallel if the feature `parallel` is enabled.
+///
+/// # Panics
+///
+/// Will panic if `chunk_size` is zero.
+pub fn batch_inverse_in_place_chunked<F: Field>(v: &mut [F], chunk_size: usize) {
+    cfg_chunks_mut!(v, chunk_size).for_each(|chunk| batch_inverse_in_place(chunk));
+}
+
+/// Same as [batch_inverse_in_place], with `v` split in a chunk per thread, of at
+/// least [MIN_BATCH_INVERSION_CHUNK] elements, see
+/// [batch_inverse_in_place_chunked].
+pub fn batch_inverse_in_place_parallel<F: Field>(v: &mut [F]) {
+    #[cfg(feature = "parallel")]
+    let threads = rayon::current_num_threads();
+    #[cfg(not(feature = "parallel"))]
+    let threads = 1;
+    let chunk_size = std::cmp::max((v.len() + threads - 1) / threads, MIN_BATCH_INVERSION_CHUNK);
+    batch_inverse_in_place_chunked(v, chunk_size)
+}
@@ -1,9 +1,11 @@
 //! This adds a few utility functions for serializing and deserializing
 //! [arkworks](http://arkworks.rs/) types that implement [CanonicalSerialize] and [CanonicalDeserialize].
 
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Write};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError, Write};
+use serde::de::DeserializeOwned;
 use serde_with::Bytes;
-use std::io::BufReader;
+use std::{cell::Cell, io::BufReader};
+use thiserror::Error;
 
 //
 // Serialization with serde
@@ -50,6 +52,8 @@ pub mod ser {
 /// or containers of types that implement these traits (Vec, arrays, etc.)
 /// Simply add annotations like `#[serde_as(as = "o1_utils::serialization::SerdeAs")]`
 /// See <https://docs.rs/serde_with/1.10.0/serde_with/guide/serde_as/index.html#switching-from-serdes-with-to-serde_as>
+/// The values decoded by [from_msgpack] in [DeserializationMode::Strict] must
+/// be canonical, as with [SerdeAsStrict].
 pub struct SerdeAs;
 
 impl<T> serde_with::SerializeAs<T> for SerdeAs
@@ -85,6 +89,9 @@ where
         } else {
             Bytes::deserialize_as(deserializer)?
         };
+        if STRICT.with(|strict| strict.get().is_some()) {
+            return deserialize_strict(&bytes).map_err(serde::de::Error::custom);
+        }
         T::deserialize_compressed(&mut &bytes[..]).map_err(serde::de::Error::custom)
     }
 }
@@ -129,6 +136,160 @@ where
     }
 }
 
+//
+// Strict deserialization
+//
+
+/// Errors that can arise when deserializing in [DeserializationMode::Strict]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DeserializationError {
+    #[error("failed to decode: {0}")]
+    Decode(String),
+
+    #[error("the encoding is not canonical")]
+    NonCanonical,
+}
+
+/// How strictly encodings are checked when deserializing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeserializationMode {
+    /// Accept any encoding arkworks and serde can decode. Trailing bytes after
+    /// an arkworks value are ignored.
+    #[default]
+    Lenient,
+    /// Only accept the canonical encoding of a value, i.e. the one it is
+    /// serialized to. Field elements that are not reduced modulo the field
+    /// characteristic, unexpected flags and trailing bytes are rejected.
+    Strict,
+}
+
+thread_local! {
+    /// Set while [from_msgpack] decodes a value in [DeserializationMode::Strict]
+    /// on this thread, so that [SerdeAs] checks the arkworks values it decodes.
+    /// Records whether one of them is not canonical.
+    static STRICT: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// Restores the mode of [STRICT] when a strict decoding ends, even if it
+/// panics.
+struct StrictGuard(Option<bool>);
+
+impl Drop for StrictGuard {
+    fn drop(&mut self) {
+        STRICT.with(|strict| strict.set(self.0));
+    }
+}
+
+/// Deserializes a compressed arkworks value from `bytes`, rejecting the
+/// encodings arkworks reports as invalid, e.g. field elements that are not
+/// reduced modulo the field characteristic or unexpected flags, and trailing
+/// bytes.
+fn deserialize_strict<T: CanonicalDeserialize>(bytes: &[u8]) -> Result<T, DeserializationError> {
+    let mut reader = bytes;
+    let res = match T::deserialize_compressed(&mut reader) {
+        Ok(_) if !reader.is_empty() => Err(DeserializationError::NonCanonical),
+        Ok(val) => Ok(val),
+        Err(SerializationError::InvalidData | SerializationError::UnexpectedFlags) => {
+            Err(DeserializationError::NonCanonical)
+        }
+        Err(e) => Err(DeserializationError::Decode(e.to_string())),
+    };
+    if matches!(res, Err(DeserializationError::NonCanonical)) {
+        STRICT.with(|strict| {
+            if strict.get().is_some() {
+                strict.set(Some(true));
+            }
+        });
+    }
+    res
+}
+
+/// Deserializes a compressed arkworks value from `bytes`, and checks that
+/// `bytes` is exactly its canonical encoding.
+///
+/// # Errors
+///
+/// Will give error if `bytes` cannot be decoded, or
+/// [DeserializationError::NonCanonical] if it is not the canonical encoding of
+/// the decoded value.
+pub fn deserialize_canonical<T>(bytes: &[u8]) -> Result<T, DeserializationError>
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+{
+    let val: T = deserialize_strict(bytes)?;
+
+    let mut canonical = Vec::with_capacity(bytes.len());
+    val.serialize_compressed(&mut canonical)
+        .map_err(|e| DeserializationError::Decode(e.to_string()))?;
+    if canonical != bytes {
+        return Err(DeserializationError::NonCanonical);
+    }
+
+    Ok(val)
+}
+
+/// Decodes a MessagePack-encoded value from `bytes`.
+/// With [DeserializationMode::Strict], each arkworks value nested in it with
+/// [SerdeAs] is checked to be canonical, as with [SerdeAsStrict]. The
+/// MessagePack encoding itself is not checked.
+///
+/// # Errors
+///
+/// Will give error if `bytes` cannot be decoded, or
+/// [DeserializationError::NonCanonical] if the mode is strict and one of the
+/// arkworks values is not canonical.
+pub fn from_msgpack<T>(bytes: &[u8], mode: DeserializationMode) -> Result<T, DeserializationError>
+where
+    T: DeserializeOwned,
+{
+    if mode == DeserializationMode::Lenient {
+        return rmp_serde::from_slice(bytes)
+            .map_err(|e| DeserializationError::Decode(e.to_string()));
+    }
+
+    let _guard = StrictGuard(STRICT.with(|strict| strict.replace(Some(false))));
+    rmp_serde::from_slice(bytes).map_err(|e| {
+        if STRICT.with(|strict| strict.get()) == Some(true) {
+            DeserializationError::NonCanonical
+        } else {
+            DeserializationError::Decode(e.to_string())
+        }
+    })
+}
+
+/// Same as `SerdeAs`, but rejects non-canonical encodings when deserializing
+/// (see [deserialize_canonical]).
+pub struct SerdeAsStrict;
+
+impl<T> serde_with::SerializeAs<T> for SerdeAsStrict
+where
+    T: CanonicalSerialize,
+{
+    fn serialize_as<S>(val: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        <SerdeAs as serde_with::SerializeAs<T>>::serialize_as(val, serializer)
+    }
+}
+
+impl<'de, T> serde_with::DeserializeAs<'de, T> for SerdeAsStrict
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let bytes: Vec<u8> = if deserializer.is_human_readable() {
+            hex::serde::deserialize(deserializer)?
+        } else {
+            Bytes::deserialize_as(deserializer)?
+        };
+        deserialize_canonical(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
 /// A generic regression serialization test for serialization via
 /// `CanonicalSerialize` and `CanonicalDeserialize`.
 pub fn test_generic_serialization_regression_canonical<
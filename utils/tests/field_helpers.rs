@@ -154,3 +154,116 @@ fn field_big() {
     assert_eq!(fe.to_biguint(), bi);
     assert_eq!(bi.to_field::<BaseField>().unwrap(), fe);
 }
+
+#[test]
+fn test_batch_inverse_in_place() {
+    use ark_ff::{Field, UniformRand, Zero};
+    use o1_utils::field_helpers::{
+        batch_inverse_in_place, batch_inverse_in_place_chunked, batch_inverse_in_place_parallel,
+        MIN_BATCH_INVERSION_CHUNK,
+    };
+
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let mut v: Vec<BaseField> = (0..3 * MIN_BATCH_INVERSION_CHUNK + 5)
+        .map(|_| BaseField::rand(rng))
+        .collect();
+    // The zero elements are left unchanged
+    v[0] = BaseField::zero();
+    v[17] = BaseField::zero();
+    v[v.len() - 1] = BaseField::zero();
+    let expected: Vec<_> = v
+        .iter()
+        .map(|x| x.inverse().unwrap_or(BaseField::zero()))
+        .collect();
+
+    let mut inverses = v.clone();
+    batch_inverse_in_place(&mut inverses);
+    assert_eq!(inverses, expected);
+
+    for chunk_size in [1, 7, MIN_BATCH_INVERSION_CHUNK, v.len() + 1] {
+        let mut inverses = v.clone();
+        batch_inverse_in_place_chunked(&mut inverses, chunk_size);
+        assert_eq!(inverses, expected);
+    }
+
+    let mut inverses = v.clone();
+    batch_inverse_in_place_parallel(&mut inverses);
+    assert_eq!(inverses, expected);
+
+    // Nothing to invert
+    batch_inverse_in_place::<BaseField>(&mut []);
+    batch_inverse_in_place_parallel::<BaseField>(&mut []);
+    let mut zeroes = vec![BaseField::zero(); 3];
+    batch_inverse_in_place(&mut zeroes);
+    assert_eq!(zeroes, vec![BaseField::zero(); 3]);
+}
+
+#[test]
+fn field_signed_and_u128_conversions() {
+    use o1_utils::Two;
+
+    assert_eq!(BaseField::from_i64(5), BaseField::from(5u64));
+    assert_eq!(BaseField::from_i64(-5), -BaseField::from(5u64));
+    assert_eq!(BaseField::from_i64(i64::MIN), -BaseField::from(1u64 << 63));
+    assert_eq!(BaseField::from_i128(-1), -BaseField::one());
+    assert_eq!(
+        BaseField::from_i128(i128::MIN),
+        -BaseField::from(1u128 << 127)
+    );
+    assert_eq!(
+        BaseField::from_i128(i128::MAX),
+        BaseField::from(i128::MAX as u128)
+    );
+
+    assert_eq!(BaseField::from(u128::MAX).to_u128(), Ok(u128::MAX));
+    assert_eq!(
+        BaseField::from_i64(-7).to_u128(),
+        Err(FieldHelpersError::Overflow(128))
+    );
+    assert_eq!(
+        BaseField::two_pow(128).to_u128(),
+        Err(FieldHelpersError::Overflow(128))
+    );
+}
+
+#[test]
+fn field_limbs_and_crumbs() {
+    use ark_ff::{UniformRand, Zero};
+    use o1_utils::Two;
+
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let x = BaseField::rand(rng);
+
+    let limbs = x.to_limbs_88();
+    assert_eq!(
+        limbs[0] + limbs[1] * BaseField::two_pow(88) + limbs[2] * BaseField::two_pow(176),
+        x
+    );
+    for limb in limbs {
+        assert!(limb.to_biguint() < BigUint::from(1u32) << 88);
+    }
+    assert_eq!(
+        BaseField::from(0xefff111222333444555611_u128).to_limbs_88(),
+        [
+            BaseField::from(0xefff111222333444555611_u128),
+            BaseField::zero(),
+            BaseField::zero()
+        ]
+    );
+
+    let crumbs = x.to_crumbs_2();
+    assert_eq!(crumbs.len(), (BaseField::MODULUS_BIT_SIZE as usize + 1) / 2);
+    let recomposed = crumbs.iter().rev().fold(BaseField::zero(), |acc, crumb| {
+        assert!(*crumb < BaseField::from(4u64));
+        acc * BaseField::from(4u64) + crumb
+    });
+    assert_eq!(recomposed, x);
+    assert_eq!(
+        BaseField::from(0b1110_u64).to_crumbs_2()[..3],
+        [
+            BaseField::from(2u64),
+            BaseField::from(3u64),
+            BaseField::zero()
+        ]
+    );
+}
@@ -74,3 +74,82 @@ pub fn ser_regression_canonical_pasta() {
 
     test_generic_serialization_regression_serde(data_expected, buf_expected);
 }
+
+#[test]
+pub fn deserialize_canonical_rejects_non_canonical_encodings() {
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_serialize::CanonicalSerialize;
+    use mina_curves::pasta::Fp;
+    use o1_utils::serialization::{deserialize_canonical, DeserializationError};
+
+    let mut bytes = vec![];
+    Fp::from(5u64).serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(deserialize_canonical::<Fp>(&bytes), Ok(Fp::from(5u64)));
+
+    // trailing bytes
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        deserialize_canonical::<Fp>(&trailing),
+        Err(DeserializationError::NonCanonical)
+    );
+
+    // the modulus is not a reduced field element
+    let modulus = Fp::MODULUS.to_bytes_le();
+    assert_eq!(
+        deserialize_canonical::<Fp>(&modulus),
+        Err(DeserializationError::NonCanonical)
+    );
+
+    // too short to be a field element
+    assert!(matches!(
+        deserialize_canonical::<Fp>(&bytes[1..]),
+        Err(DeserializationError::Decode(_))
+    ));
+}
+
+#[test]
+pub fn from_msgpack_strict_mode() {
+    use ark_ff::{BigInteger, PrimeField};
+    use mina_curves::pasta::Fp;
+    use o1_utils::serialization::{from_msgpack, DeserializationError, DeserializationMode};
+
+    #[serde_as]
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    struct TestStruct {
+        #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+        x: Fp,
+    }
+
+    let data = TestStruct { x: Fp::from(42u64) };
+    let bytes = rmp_serde::to_vec(&data).unwrap();
+    assert_eq!(
+        from_msgpack::<TestStruct>(&bytes, DeserializationMode::Strict),
+        Ok(data.clone())
+    );
+
+    // replace the field element by the modulus, which is not reduced
+    let mut unreduced = bytes.clone();
+    unreduced[3..].copy_from_slice(&Fp::MODULUS.to_bytes_le());
+    assert!(matches!(
+        from_msgpack::<TestStruct>(&unreduced, DeserializationMode::Lenient),
+        Err(DeserializationError::Decode(_))
+    ));
+    assert_eq!(
+        from_msgpack::<TestStruct>(&unreduced, DeserializationMode::Strict),
+        Err(DeserializationError::NonCanonical)
+    );
+
+    // append a byte to the encoding of the field element: [array(1), bin8, len, ..]
+    let mut padded = bytes;
+    padded[2] += 1;
+    padded.push(0);
+    assert_eq!(
+        from_msgpack::<TestStruct>(&padded, DeserializationMode::Lenient),
+        Ok(data)
+    );
+    assert_eq!(
+        from_msgpack::<TestStruct>(&padded, DeserializationMode::Strict),
+        Err(DeserializationError::NonCanonical)
+    );
+}